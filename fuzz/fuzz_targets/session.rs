@@ -0,0 +1,21 @@
+//! Whole sessions: every input gets a fresh service over an in-memory pack
+//! store and a wiped per-process root for its journals, so no state carries
+//! from one input to the next; every reply must be a well-formed JSON-RPC
+//! envelope.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mcp_context_pack::adapters::mcp_stdio::fuzzing::serve_bytes;
+use mcp_context_pack::service::{ContextPackConfig, ContextPackService, StorageBackend};
+
+fuzz_target!(|data: &[u8]| {
+    let root = std::env::temp_dir().join(format!("context-pack-fuzz-{}", std::process::id()));
+    // Missing on the first input; anything else left over is a real failure.
+    if let Err(e) = std::fs::remove_dir_all(&root) {
+        assert_eq!(e.kind(), std::io::ErrorKind::NotFound, "wipe {root:?}: {e}");
+    }
+    let mut config = ContextPackConfig::new(root.join("store"), root);
+    config.storage = StorageBackend::Memory;
+    let service = ContextPackService::new(config).expect("fuzz service under the temp dir");
+    serve_bytes(&service, data);
+});
@@ -0,0 +1,10 @@
+//! Framing and RPC parsing only: malformed headers, mixed Content-Length and
+//! JSON-line messages, frames over a small cap and invalid UTF-8.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mcp_context_pack::adapters::mcp_stdio::fuzzing::{read_messages, FUZZ_MAX_FRAME_BYTES};
+
+fuzz_target!(|data: &[u8]| {
+    read_messages(data, FUZZ_MAX_FRAME_BYTES);
+});
@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+use fs2::FileExt;
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tokio::task;
+
+use crate::{
+    adapters::pack_cipher::{open_line, seal_line, PackCipher},
+    app::ports::{AuditEntry, AuditLogPort},
+    domain::{
+        errors::{DomainError, Result},
+        types::PackId,
+    },
+};
+
+/// Audit log in `{root}/audit.jsonl`: one JSON entry per line, only ever
+/// appended to. Appends hold an exclusive lock on the file so concurrent
+/// servers on the same storage root never interleave lines; reads scan the
+/// whole file. With a cipher each line is encrypted on its own
+/// ([`seal_line`]), so plaintext lines from before the key was set still read.
+pub struct AuditLogFsAdapter {
+    path: PathBuf,
+    cipher: Option<PackCipher>,
+}
+
+impl AuditLogFsAdapter {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, cipher: None }
+    }
+
+    pub fn with_cipher(mut self, cipher: Option<PackCipher>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    fn append_sync(path: &Path, line: &str) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| DomainError::Io(format!("failed to create storage root: {}", e)))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                DomainError::Io(format!(
+                    "failed to open audit log '{}': {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        file.lock_exclusive()
+            .map_err(|e| DomainError::Io(format!("failed to lock audit log: {}", e)))?;
+        let result = file
+            .write_all(format!("{line}\n").as_bytes())
+            .map_err(|e| DomainError::Io(format!("failed to append to audit log: {}", e)));
+        if let Err(e) = FileExt::unlock(&file) {
+            tracing::warn!("failed to unlock audit log: {e}");
+        }
+        result
+    }
+
+    fn tail_sync(
+        path: &Path,
+        cipher: Option<&PackCipher>,
+        pack_id: &str,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>> {
+        let raw = match std::fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(DomainError::Io(format!(
+                    "failed to read audit log '{}': {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        let mut tail = VecDeque::with_capacity(limit);
+        for (idx, line) in raw
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+        {
+            // A line the key can't open fails the read; it is not "unreadable".
+            let line = open_line(cipher, line, &path.display().to_string())?;
+            let entry: AuditEntry = match serde_json::from_str(&line) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    tracing::warn!("skipping unreadable audit log line {}: {}", idx + 1, e);
+                    continue;
+                }
+            };
+            if entry.pack_id != pack_id {
+                continue;
+            }
+            if tail.len() == limit {
+                tail.pop_front();
+            }
+            tail.push_back(entry);
+        }
+        Ok(tail.into())
+    }
+}
+
+#[async_trait]
+impl AuditLogPort for AuditLogFsAdapter {
+    async fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let path = self.path.clone();
+        let line = seal_line(self.cipher.as_ref(), &serde_json::to_string(entry)?)?;
+        task::spawn_blocking(move || Self::append_sync(&path, &line))
+            .await
+            .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+
+    async fn tail(&self, pack_id: &PackId, limit: usize) -> Result<Vec<AuditEntry>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let path = self.path.clone();
+        let pack_id = pack_id.as_str().to_string();
+        let cipher = self.cipher.clone();
+        task::spawn_blocking(move || Self::tail_sync(&path, cipher.as_ref(), &pack_id, limit))
+            .await
+            .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::ports::AuditEvent;
+    use chrono::Utc;
+    use tempfile::tempdir;
+
+    fn entry(pack_id: &str, revision: u64) -> AuditEntry {
+        AuditEntry {
+            at: Utc::now(),
+            op: "write".into(),
+            event: AuditEvent::Write,
+            pack_id: pack_id.into(),
+            revision_before: Some(revision - 1),
+            revision_after: Some(revision),
+            actor: Some("agent-a".into()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tail_returns_last_entries_for_one_pack_and_skips_bad_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = AuditLogFsAdapter::new(path.clone());
+        let id = PackId::new();
+        let other = PackId::new();
+        for revision in 2..=5 {
+            log.append(&entry(id.as_str(), revision)).await.unwrap();
+            log.append(&entry(other.as_str(), revision)).await.unwrap();
+        }
+        let mut raw = std::fs::read_to_string(&path).unwrap();
+        raw.push_str("{not json\n");
+        std::fs::write(&path, raw).unwrap();
+
+        let tail = log.tail(&id, 2).await.unwrap();
+        let revisions: Vec<_> = tail.iter().map(|e| e.revision_after).collect();
+        assert_eq!(revisions, vec![Some(4), Some(5)]);
+        assert!(tail.iter().all(|e| e.pack_id == id.as_str()));
+        assert!(log.tail(&PackId::new(), 10).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,573 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tokio::task;
+
+use crate::{
+    adapters::{
+        pack_cipher::PackCipher,
+        pack_codec::{pack_file_stem, PackCodec},
+        pack_layout::pack_dirs,
+        storage_json::{parse_max_pack_bytes_from_env, JsonStorageAdapter},
+        storage_migration::{is_pack_file, storage_root_files},
+    },
+    app::ports::{BackupPort, BackupSummary},
+    domain::{
+        errors::{DomainError, Result},
+        models::Pack,
+    },
+};
+
+const BACKUP_FORMAT: &str = "context_pack_backup";
+/// Version 2 added `files/`; version 1 archives (packs only) still restore.
+const BACKUP_FORMAT_VERSION: u32 = 2;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const PACKS_ENTRY_DIR: &str = "packs";
+const FILES_ENTRY_DIR: &str = "files";
+/// Derived from the pack files and rebuilt on the next listing.
+const PACK_INDEX_PATH: &str = "packs/.pack_index";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupManifest {
+    format: String,
+    version: u32,
+    created_at: DateTime<Utc>,
+    pack_ids: Vec<String>,
+    /// Storage-root paths archived under `files/`.
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+/// Outcome of [`restore_backup`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreSummary {
+    pub pack_count: usize,
+    pub file_count: usize,
+    /// Where the pack files and other files present before the restore were
+    /// moved, if any.
+    pub displaced_dir: Option<PathBuf>,
+}
+
+/// Writes tar archives of a storage root into `{storage_root}/backups`.
+///
+/// Archives hold a `manifest.json`, the packs as `packs/<id>.json`, flat
+/// whatever the store's layout, and every other file of the root (history,
+/// `archive/`, audit log, journals, saved filters, sync state) as
+/// `files/<path>`. Earlier backups and the derived `.pack_index` are left
+/// out. Restore is the `mcp-context-pack restore <archive>` subcommand
+/// ([`restore_backup`]). Files are archived as they are, encrypted ones
+/// included, so both commands need the store's `CONTEXT_PACK_ENCRYPTION_KEY`.
+pub struct TarBackupAdapter {
+    storage_root: PathBuf,
+    cipher: Option<PackCipher>,
+}
+
+impl TarBackupAdapter {
+    pub fn new(storage_root: PathBuf) -> Self {
+        Self {
+            storage_root,
+            cipher: None,
+        }
+    }
+
+    /// Key the store's pack files are encrypted with; a backup checks every
+    /// pack opens with it.
+    pub fn with_cipher(mut self, cipher: Option<PackCipher>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    fn packs_dir(storage_root: &Path) -> PathBuf {
+        storage_root.join(PACKS_ENTRY_DIR)
+    }
+
+    fn backups_dir(storage_root: &Path) -> PathBuf {
+        storage_root.join("backups")
+    }
+}
+
+#[async_trait]
+impl BackupPort for TarBackupAdapter {
+    async fn backup(&self) -> Result<BackupSummary> {
+        let storage_root = self.storage_root.clone();
+        let codec = PackCodec {
+            cipher: self.cipher.clone(),
+            ..PackCodec::plain(parse_max_pack_bytes_from_env())
+        };
+        task::spawn_blocking(move || backup_sync(&storage_root, &codec, Utc::now()))
+            .await
+            .map_err(|e| DomainError::Io(format!("backup task failed: {}", e)))?
+    }
+}
+
+/// [`BackupPort`] for stores [`TarBackupAdapter`] can't read: the tar
+/// archives the JSON store's files, so backing up a memory or S3 store
+/// fails instead of writing an archive with no packs in it.
+pub struct UnsupportedBackupAdapter {
+    backend: &'static str,
+}
+
+impl UnsupportedBackupAdapter {
+    pub fn new(backend: &'static str) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl BackupPort for UnsupportedBackupAdapter {
+    async fn backup(&self) -> Result<BackupSummary> {
+        Err(DomainError::InvalidState(format!(
+            "backup archives the json storage backend; this server uses '{}'",
+            self.backend
+        )))
+    }
+}
+
+fn io_err(context: &str, path: &Path, e: impl std::fmt::Display) -> DomainError {
+    DomainError::Io(format!("{} '{}': {}", context, path.display(), e))
+}
+
+fn pack_files(packs_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    let dirs =
+        pack_dirs(packs_dir).map_err(|e| io_err("failed to read storage dir", packs_dir, e))?;
+    for dir in dirs {
+        for entry in
+            std::fs::read_dir(&dir).map_err(|e| io_err("failed to read storage dir", &dir, e))?
+        {
+            let path = entry
+                .map_err(|e| DomainError::Io(format!("dir entry error: {}", e)))?
+                .path();
+            if path.is_file() && pack_file_stem(&path).is_some() {
+                out.push(path);
+            }
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+fn append_entry(
+    builder: &mut tar::Builder<File>,
+    name: &str,
+    data: &[u8],
+    mtime: DateTime<Utc>,
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime.timestamp().max(0) as u64);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, name, data)
+        .map_err(|e| DomainError::Io(format!("failed to append '{}' to backup: {}", name, e)))
+}
+
+/// Storage-root files archived under `files/`, relative to the root.
+fn other_files(storage_root: &Path) -> Result<Vec<PathBuf>> {
+    if !storage_root.is_dir() {
+        return Ok(Vec::new());
+    }
+    Ok(storage_root_files(storage_root)?
+        .into_iter()
+        .filter(|rel| {
+            let earlier_restore = rel
+                .components()
+                .next()
+                .is_some_and(|c| c.as_os_str().to_string_lossy().starts_with("pre-restore-"));
+            !rel.starts_with("backups")
+                && !earlier_restore
+                && !is_pack_file(rel)
+                && rel != Path::new(PACK_INDEX_PATH)
+        })
+        .collect())
+}
+
+/// `/`-separated, so archives read the same on every platform.
+fn entry_path(rel: &Path) -> String {
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn backup_sync(
+    storage_root: &Path,
+    codec: &PackCodec,
+    now: DateTime<Utc>,
+) -> Result<BackupSummary> {
+    let packs_dir = TarBackupAdapter::packs_dir(storage_root);
+    let backups_dir = TarBackupAdapter::backups_dir(storage_root);
+    std::fs::create_dir_all(&backups_dir)
+        .map_err(|e| io_err("failed to create backup dir", &backups_dir, e))?;
+
+    let archive_path = backups_dir.join(format!(
+        "context_pack-{}.tar",
+        now.format("%Y%m%dT%H%M%S%.3fZ")
+    ));
+    let tmp_path = archive_path.with_extension("tar.tmp");
+
+    let counts = JsonStorageAdapter::with_repo_lock(&packs_dir, || {
+        let mut packs = Vec::new();
+        for path in pack_files(&packs_dir)? {
+            let raw =
+                std::fs::read(&path).map_err(|e| io_err("failed to read pack file", &path, e))?;
+            // A pack the key can't open fails the backup rather than going missing.
+            let plain = codec.open(raw.clone(), &path)?;
+            match serde_json::from_str::<Pack>(&plain) {
+                // Entries keep the file's name, so its format survives the round trip.
+                Ok(pack) => {
+                    let suffix = path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .and_then(|name| name.strip_prefix(pack_file_stem(&path)?))
+                        .unwrap_or(".json");
+                    packs.push((pack.id.as_str().to_string(), suffix.to_string(), raw));
+                }
+                Err(e) => tracing::warn!(
+                    "skipping unreadable pack file '{}' in backup: {e}",
+                    path.display()
+                ),
+            }
+        }
+
+        let files = other_files(storage_root)?;
+        let manifest = BackupManifest {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_FORMAT_VERSION,
+            created_at: now,
+            pack_ids: packs.iter().map(|(id, _, _)| id.clone()).collect(),
+            files: files.iter().map(|rel| entry_path(rel)).collect(),
+        };
+        let file = File::create(&tmp_path)
+            .map_err(|e| io_err("failed to create backup archive", &tmp_path, e))?;
+        let mut builder = tar::Builder::new(file);
+        append_entry(
+            &mut builder,
+            MANIFEST_ENTRY,
+            &serde_json::to_vec_pretty(&manifest)?,
+            now,
+        )?;
+        for (id, suffix, raw) in &packs {
+            append_entry(
+                &mut builder,
+                &format!("{PACKS_ENTRY_DIR}/{id}{suffix}"),
+                raw,
+                now,
+            )?;
+        }
+        for rel in &files {
+            let path = storage_root.join(rel);
+            let raw = std::fs::read(&path).map_err(|e| io_err("failed to read", &path, e))?;
+            append_entry(
+                &mut builder,
+                &format!("{FILES_ENTRY_DIR}/{}", entry_path(rel)),
+                &raw,
+                now,
+            )?;
+        }
+        let file = builder
+            .into_inner()
+            .map_err(|e| io_err("failed to finish backup archive", &tmp_path, e))?;
+        file.sync_all()
+            .map_err(|e| io_err("failed to flush backup archive", &tmp_path, e))?;
+        Ok((packs.len(), files.len()))
+    });
+    let (pack_count, file_count) = match counts {
+        Ok(counts) => counts,
+        Err(err) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(err);
+        }
+    };
+
+    std::fs::rename(&tmp_path, &archive_path)
+        .map_err(|e| io_err("failed to finalize backup archive", &archive_path, e))?;
+    let bytes = std::fs::metadata(&archive_path)
+        .map_err(|e| io_err("failed to stat backup archive", &archive_path, e))?
+        .len();
+    Ok(BackupSummary {
+        archive_path: archive_path.display().to_string(),
+        pack_count,
+        file_count,
+        bytes,
+        created_at: now,
+    })
+}
+
+/// Only `manifest.json`, flat `packs/<file>.json[.gz|.zst]` and `files/<path>`
+/// entries are accepted.
+enum ArchiveEntry {
+    Manifest,
+    Pack,
+    /// The path under the storage root.
+    File(PathBuf),
+}
+
+fn classify_entry(path: &Path) -> Option<ArchiveEntry> {
+    let parts: Vec<&str> = path
+        .components()
+        .map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    match parts.as_slice() {
+        [MANIFEST_ENTRY] => Some(ArchiveEntry::Manifest),
+        [PACKS_ENTRY_DIR, file] if pack_file_stem(Path::new(file)).is_some() => {
+            Some(ArchiveEntry::Pack)
+        }
+        [FILES_ENTRY_DIR, rest @ ..] if !rest.is_empty() => {
+            Some(ArchiveEntry::File(rest.iter().collect()))
+        }
+        _ => None,
+    }
+}
+
+/// A validated archive: the manifest, its packs and its other files.
+struct ArchiveContents {
+    packs: Vec<Pack>,
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+fn read_archive(archive_path: &Path, codec: &PackCodec) -> Result<ArchiveContents> {
+    let file =
+        File::open(archive_path).map_err(|e| io_err("failed to open backup", archive_path, e))?;
+    let mut archive = tar::Archive::new(file);
+    let mut manifest: Option<BackupManifest> = None;
+    let mut packs = Vec::new();
+    let mut files = Vec::new();
+    let entries = archive
+        .entries()
+        .map_err(|e| io_err("failed to read backup", archive_path, e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| io_err("corrupt backup entry in", archive_path, e))?;
+        let entry_path = entry
+            .path()
+            .map_err(|e| io_err("invalid entry path in", archive_path, e))?
+            .into_owned();
+        let kind = classify_entry(&entry_path).ok_or_else(|| {
+            DomainError::InvalidData(format!(
+                "unexpected entry '{}' in backup archive",
+                entry_path.display()
+            ))
+        })?;
+        let mut raw = Vec::new();
+        entry
+            .read_to_end(&mut raw)
+            .map_err(|e| io_err("failed to read backup entry", &entry_path, e))?;
+        match kind {
+            ArchiveEntry::Manifest => manifest = Some(serde_json::from_slice(&raw)?),
+            ArchiveEntry::Pack => {
+                let raw = codec.open(raw, &entry_path)?;
+                let pack: Pack = serde_json::from_str(&raw).map_err(|e| {
+                    DomainError::InvalidData(format!(
+                        "backup entry '{}' is not a valid pack: {}",
+                        entry_path.display(),
+                        e
+                    ))
+                })?;
+                packs.push(pack.migrate_schema()?);
+            }
+            ArchiveEntry::File(rel) => files.push((rel, raw)),
+        }
+    }
+
+    let manifest = manifest.ok_or_else(|| {
+        DomainError::InvalidData("backup archive has no manifest.json".to_string())
+    })?;
+    if manifest.format != BACKUP_FORMAT || !(1..=BACKUP_FORMAT_VERSION).contains(&manifest.version)
+    {
+        return Err(DomainError::InvalidData(format!(
+            "unsupported backup format '{}' v{} (expected '{}' v{})",
+            manifest.format, manifest.version, BACKUP_FORMAT, BACKUP_FORMAT_VERSION
+        )));
+    }
+    let listed: BTreeSet<&str> = manifest.pack_ids.iter().map(String::as_str).collect();
+    let found: BTreeSet<&str> = packs.iter().map(|p| p.id.as_str()).collect();
+    if listed != found {
+        return Err(DomainError::InvalidData(format!(
+            "backup archive is incomplete: manifest lists {} packs, archive holds {}",
+            listed.len(),
+            found.len()
+        )));
+    }
+    let listed: BTreeSet<&str> = manifest.files.iter().map(String::as_str).collect();
+    let found: BTreeSet<String> = files.iter().map(|(rel, _)| entry_path(rel)).collect();
+    if listed != found.iter().map(String::as_str).collect() {
+        return Err(DomainError::InvalidData(format!(
+            "backup archive is incomplete: manifest lists {} files, archive holds {}",
+            listed.len(),
+            found.len()
+        )));
+    }
+    Ok(ArchiveContents { packs, files })
+}
+
+/// Replaces the packs under `storage_root` with the contents of `archive_path`
+/// and puts back the other files it holds.
+///
+/// The archive is fully read and validated before anything is touched. Pack
+/// files already present, and files the archive would overwrite, are moved
+/// to `{storage_root}/pre-restore-<ts>/` rather than deleted. Runs under the
+/// repository lock, so a live server never observes a half-restored store.
+pub fn restore_backup(storage_root: &Path, archive_path: &Path) -> Result<RestoreSummary> {
+    let codec = PackCodec::from_env()?;
+    let ArchiveContents { packs, files } = read_archive(archive_path, &codec)?;
+    let packs_dir = TarBackupAdapter::packs_dir(storage_root);
+    let now = Utc::now();
+
+    JsonStorageAdapter::with_repo_lock(&packs_dir, || {
+        let existing = pack_files(&packs_dir)?;
+        let dir = storage_root.join(format!("pre-restore-{}", now.format("%Y%m%dT%H%M%S%.3fZ")));
+        let mut displaced_dir = None;
+        let mut move_aside = |path: &Path, rel: &Path| -> Result<()> {
+            let target = dir.join(rel);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| io_err("failed to create displacement dir", parent, e))?;
+            }
+            std::fs::rename(path, &target).map_err(|e| io_err("failed to move aside", path, e))?;
+            displaced_dir = Some(dir.clone());
+            Ok(())
+        };
+        for path in existing {
+            move_aside(&path, Path::new(path.file_name().unwrap_or_default()))?;
+        }
+        for (rel, raw) in &files {
+            let path = storage_root.join(rel);
+            if path.is_file() {
+                move_aside(&path, rel)?;
+            }
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| io_err("failed to create", parent, e))?;
+            }
+            std::fs::write(&path, raw).map_err(|e| io_err("failed to write", &path, e))?;
+        }
+
+        for pack in &packs {
+            let path = codec.pack_file_path(&packs_dir, pack.id.as_str());
+            let home = path.parent().unwrap_or(&packs_dir);
+            std::fs::create_dir_all(home)
+                .map_err(|e| io_err("failed to create pack dir", home, e))?;
+            let tmp = home.join(format!("{}.tmp", pack.id.as_str()));
+            std::fs::write(&tmp, codec.seal(serde_json::to_string(pack)?)?)
+                .map_err(|e| io_err("failed to write pack file", &tmp, e))?;
+            std::fs::rename(&tmp, &path)
+                .map_err(|e| io_err("failed to rename pack file", &path, e))?;
+        }
+
+        Ok(RestoreSummary {
+            pack_count: packs.len(),
+            file_count: files.len(),
+            displaced_dir,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::ports::PackRepositoryPort;
+    use crate::domain::types::PackId;
+    use chrono::Duration;
+
+    async fn seeded_store(root: &Path, count: usize) -> Vec<PackId> {
+        let repo = JsonStorageAdapter::new(root.join("packs"));
+        let mut ids = Vec::new();
+        for _ in 0..count {
+            let mut pack = Pack::new(PackId::new(), None);
+            pack.expires_at = Utc::now() + Duration::minutes(30);
+            repo.create_new(&pack).await.unwrap();
+            ids.push(pack.id);
+        }
+        ids
+    }
+
+    const SIDE_FILES: [(&str, &str); 4] = [
+        ("packs/archive/pk_gone1234.json", "{}"),
+        ("audit.jsonl", "{\"action\":\"write\"}\n"),
+        ("saved_filters.json", "{\"filters\":[]}"),
+        ("sync_state.json", "{\"packs\":{}}"),
+    ];
+
+    #[tokio::test]
+    async fn test_backup_then_restore_round_trips_and_moves_existing_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        let ids = seeded_store(&source, 2).await;
+        let repo = JsonStorageAdapter::new(source.join("packs"));
+        let mut next = repo.get_by_id(&ids[0]).await.unwrap().unwrap();
+        next.revision = 2;
+        repo.save_with_expected_revision(&next, 1).await.unwrap();
+        for (rel, body) in SIDE_FILES {
+            let path = source.join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, body).unwrap();
+        }
+
+        let summary = TarBackupAdapter::new(source.clone())
+            .backup()
+            .await
+            .unwrap();
+        assert_eq!(summary.pack_count, 2);
+        // The side files plus one history revision.
+        assert_eq!(summary.file_count, SIDE_FILES.len() + 1);
+        assert!(summary.bytes > 0);
+        assert!(Path::new(&summary.archive_path).starts_with(source.join("backups")));
+
+        let target = dir.path().join("target");
+        let stale = seeded_store(&target, 1).await;
+        std::fs::write(target.join("audit.jsonl"), "old\n").unwrap();
+        let restored = restore_backup(&target, Path::new(&summary.archive_path)).unwrap();
+        assert_eq!(restored.pack_count, 2);
+        assert_eq!(restored.file_count, SIDE_FILES.len() + 1);
+        let displaced = restored.displaced_dir.expect("existing pack moved aside");
+        assert!(displaced
+            .join(format!("{}.json", stale[0].as_str()))
+            .is_file());
+        assert_eq!(
+            std::fs::read_to_string(displaced.join("audit.jsonl")).unwrap(),
+            "old\n"
+        );
+
+        let repo = JsonStorageAdapter::new(target.join("packs"));
+        for id in &ids {
+            assert!(repo.get_by_id(id).await.unwrap().is_some());
+        }
+        assert!(repo.get_by_id(&stale[0]).await.unwrap().is_none());
+        assert_eq!(repo.list_revisions(&ids[0]).await.unwrap(), vec![1]);
+        for (rel, body) in SIDE_FILES {
+            assert_eq!(
+                std::fs::read_to_string(target.join(rel)).unwrap(),
+                body,
+                "{rel}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_restore_rejects_path_traversal_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join("evil.tar");
+        let mut builder = tar::Builder::new(File::create(&archive_path).unwrap());
+        // `append_data` refuses `..`, so forge the name directly in the header.
+        let name = b"../escape.json";
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name);
+        header.set_size(2);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, &b"{}"[..]).unwrap();
+        builder.finish().unwrap();
+
+        let err = restore_backup(&dir.path().join("root"), &archive_path).unwrap_err();
+        assert!(matches!(err, DomainError::InvalidData(_)), "{err:?}");
+        assert!(!dir.path().join("escape.json").exists());
+    }
+}
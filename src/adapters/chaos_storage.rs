@@ -0,0 +1,269 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    app::ports::{ListFilter, PackRepositoryPort},
+    domain::{
+        errors::{DomainError, Result},
+        models::Pack,
+        types::{PackId, PackName},
+    },
+};
+
+pub const CHAOS_ENV: &str = "CONTEXT_PACK_CHAOS";
+
+/// Failure rates (0.0..=1.0) for [`ChaosStorageAdapter`].
+///
+/// Parsed from `CONTEXT_PACK_CHAOS`, e.g.
+/// `write_io=0.2,lock_timeout=0.05,decode=0.1,seed=7`. Omitted rates are 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChaosConfig {
+    /// I/O error on `create_new`, `save_with_expected_revision`, `delete_pack_file`.
+    pub write_io: f64,
+    /// Lock timeout on any call that takes the repository lock (writes, list, purge).
+    pub lock_timeout: f64,
+    /// Decode failure on reads (`get_by_id`, `get_by_name`, `list_packs`).
+    pub decode: f64,
+    /// Fixed RNG seed for reproducible fault sequences.
+    pub seed: Option<u64>,
+}
+
+impl ChaosConfig {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut config = Self::default();
+        for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(|| {
+                DomainError::InvalidData(format!("{CHAOS_ENV}: expected key=value, got '{part}'"))
+            })?;
+            let (key, value) = (key.trim(), value.trim());
+            if key == "seed" {
+                config.seed = Some(value.parse().map_err(|_| {
+                    DomainError::InvalidData(format!("{CHAOS_ENV}: seed must be an integer"))
+                })?);
+                continue;
+            }
+            let rate: f64 = value
+                .parse()
+                .ok()
+                .filter(|rate| (0.0..=1.0).contains(rate))
+                .ok_or_else(|| {
+                    DomainError::InvalidData(format!(
+                        "{CHAOS_ENV}: '{key}' must be a rate between 0 and 1 (got '{value}')"
+                    ))
+                })?;
+            match key {
+                "write_io" => config.write_io = rate,
+                "lock_timeout" => config.lock_timeout = rate,
+                "decode" => config.decode = rate,
+                other => {
+                    return Err(DomainError::InvalidData(format!(
+                        "{CHAOS_ENV}: unknown fault '{other}' (expected write_io, lock_timeout, decode, seed)"
+                    )))
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// `None` when `CONTEXT_PACK_CHAOS` is unset or blank.
+    pub fn from_env() -> Result<Option<Self>> {
+        match std::env::var(CHAOS_ENV) {
+            Ok(raw) if !raw.trim().is_empty() => Self::parse(&raw).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Test-only repository decorator that fails a configurable share of calls
+/// with the same error variants the JSON adapter produces, so agent retry
+/// logic can be exercised against realistic server faults.
+pub struct ChaosStorageAdapter {
+    inner: Arc<dyn PackRepositoryPort>,
+    config: ChaosConfig,
+    rng: Mutex<StdRng>,
+}
+
+impl ChaosStorageAdapter {
+    pub fn new(inner: Arc<dyn PackRepositoryPort>, config: ChaosConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            inner,
+            config,
+            rng: Mutex::new(rng),
+        }
+    }
+
+    fn roll(&self, rate: f64) -> bool {
+        rate > 0.0
+            && self
+                .rng
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .gen_bool(rate)
+    }
+
+    fn before_locked(&self, op: &str) -> Result<()> {
+        if self.roll(self.config.lock_timeout) {
+            tracing::debug!("chaos: lock timeout on {op}");
+            return Err(DomainError::Io(format!(
+                "chaos: timed out waiting for repository lock ({op})"
+            )));
+        }
+        Ok(())
+    }
+
+    fn before_write(&self, op: &str) -> Result<()> {
+        self.before_locked(op)?;
+        if self.roll(self.config.write_io) {
+            tracing::debug!("chaos: write I/O error on {op}");
+            return Err(DomainError::Io(format!(
+                "chaos: injected I/O error while writing ({op})"
+            )));
+        }
+        Ok(())
+    }
+
+    fn after_read(&self, op: &str) -> Result<()> {
+        if self.roll(self.config.decode) {
+            tracing::debug!("chaos: decode failure on {op}");
+            return Err(DomainError::Deserialize(format!(
+                "chaos: injected decode failure ({op})"
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PackRepositoryPort for ChaosStorageAdapter {
+    async fn create_new(&self, pack: &Pack) -> Result<()> {
+        self.before_write("create_new")?;
+        self.inner.create_new(pack).await
+    }
+
+    async fn save_with_expected_revision(&self, pack: &Pack, expected_revision: u64) -> Result<()> {
+        self.before_write("save")?;
+        self.inner
+            .save_with_expected_revision(pack, expected_revision)
+            .await
+    }
+
+    async fn create_with_update(
+        &self,
+        created: &Pack,
+        updated: &Pack,
+        expected_revision: u64,
+    ) -> Result<()> {
+        self.before_write("create_with_update")?;
+        self.inner
+            .create_with_update(created, updated, expected_revision)
+            .await
+    }
+
+    async fn delete_pack_file(&self, id: &PackId) -> Result<bool> {
+        self.before_write("delete")?;
+        self.inner.delete_pack_file(id).await
+    }
+
+    async fn get_by_id(&self, id: &PackId) -> Result<Option<Pack>> {
+        let pack = self.inner.get_by_id(id).await?;
+        self.after_read("get_by_id")?;
+        Ok(pack)
+    }
+
+    async fn get_by_name(&self, name: &PackName) -> Result<Option<Pack>> {
+        let pack = self.inner.get_by_name(name).await?;
+        self.after_read("get_by_name")?;
+        Ok(pack)
+    }
+
+    async fn list_packs(&self, filter: ListFilter) -> Result<Vec<Pack>> {
+        self.before_locked("list")?;
+        let packs = self.inner.list_packs(filter).await?;
+        self.after_read("list")?;
+        Ok(packs)
+    }
+
+    async fn purge_expired(&self) -> Result<()> {
+        self.before_locked("purge")?;
+        self.inner.purge_expired().await
+    }
+
+    async fn list_revisions(&self, id: &PackId) -> Result<Vec<u64>> {
+        let revisions = self.inner.list_revisions(id).await?;
+        self.after_read("list_revisions")?;
+        Ok(revisions)
+    }
+
+    async fn get_revision(&self, id: &PackId, revision: u64) -> Result<Option<Pack>> {
+        let pack = self.inner.get_revision(id, revision).await?;
+        self.after_read("get_revision")?;
+        Ok(pack)
+    }
+
+    async fn extend_expiry(&self, id: &PackId, expires_at: DateTime<Utc>) -> Result<()> {
+        self.before_write("extend_expiry")?;
+        self.inner.extend_expiry(id, expires_at).await
+    }
+
+    fn max_pack_bytes(&self) -> Option<usize> {
+        self.inner.max_pack_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::storage_json::JsonStorageAdapter;
+
+    #[test]
+    fn test_parse_rejects_unknown_faults_and_out_of_range_rates() {
+        let config = ChaosConfig::parse("write_io=0.5, decode=1,seed=3").unwrap();
+        assert_eq!(config.write_io, 0.5);
+        assert_eq!(config.decode, 1.0);
+        assert_eq!(config.lock_timeout, 0.0);
+        assert_eq!(config.seed, Some(3));
+        assert!(ChaosConfig::parse("write_io=1.5").is_err());
+        assert!(ChaosConfig::parse("disk_full=0.1").is_err());
+        assert!(ChaosConfig::parse("write_io").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_full_rates_fail_writes_and_reads_without_touching_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner: Arc<dyn PackRepositoryPort> =
+            Arc::new(JsonStorageAdapter::new(dir.path().into()));
+        let mut pack = Pack::new(PackId::new(), None);
+        pack.expires_at = chrono::Utc::now() + chrono::Duration::minutes(30);
+
+        let failing_writes = ChaosStorageAdapter::new(
+            inner.clone(),
+            ChaosConfig {
+                write_io: 1.0,
+                ..ChaosConfig::default()
+            },
+        );
+        let err = failing_writes.create_new(&pack).await.unwrap_err();
+        assert!(matches!(err, DomainError::Io(_)), "{err:?}");
+        assert!(inner.get_by_id(&pack.id).await.unwrap().is_none());
+
+        inner.create_new(&pack).await.unwrap();
+        let failing_reads = ChaosStorageAdapter::new(
+            inner.clone(),
+            ChaosConfig {
+                decode: 1.0,
+                ..ChaosConfig::default()
+            },
+        );
+        let err = failing_reads.get_by_id(&pack.id).await.unwrap_err();
+        assert!(matches!(err, DomainError::Deserialize(_)), "{err:?}");
+
+        let quiet = ChaosStorageAdapter::new(inner, ChaosConfig::default());
+        assert!(quiet.get_by_id(&pack.id).await.unwrap().is_some());
+    }
+}
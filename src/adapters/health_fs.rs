@@ -0,0 +1,171 @@
+use async_trait::async_trait;
+use fs2::FileExt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::task;
+
+use crate::{
+    adapters::storage_json::JsonStorageAdapter,
+    app::ports::{HealthProbePort, SubsystemHealth},
+    domain::types::SourceRootName,
+};
+
+const PROBE_FILE: &str = ".health-probe";
+/// How long the lock check waits for a writer to release the repository lock.
+const LOCK_WAIT: Duration = Duration::from_secs(2);
+const LOCK_POLL: Duration = Duration::from_millis(50);
+
+/// Probes the filesystem store: `storage` writes and removes a probe file in
+/// the pack directory, `repo_lock` takes and releases the repository lock
+/// (waiting up to 2 s for a writer), and `source_root` (plus
+/// `source_root:<name>` per extra root) lists the directory.
+#[derive(Clone)]
+pub struct StoreHealthFsAdapter {
+    storage_dir: PathBuf,
+    source_root: PathBuf,
+    source_roots: Vec<(SourceRootName, PathBuf)>,
+}
+
+impl StoreHealthFsAdapter {
+    pub fn new(storage_dir: PathBuf, source_root: PathBuf) -> Self {
+        Self {
+            storage_dir,
+            source_root,
+            source_roots: Vec::new(),
+        }
+    }
+
+    pub fn with_source_roots(mut self, roots: Vec<(SourceRootName, PathBuf)>) -> Self {
+        self.source_roots = roots;
+        self
+    }
+
+    fn probe_sync(&self) -> Vec<SubsystemHealth> {
+        let mut checks = vec![
+            timed("storage", || check_storage_writable(&self.storage_dir)),
+            timed("repo_lock", || check_repo_lock(&self.storage_dir)),
+            timed("source_root", || check_source_dir(&self.source_root)),
+        ];
+        for (name, path) in &self.source_roots {
+            checks.push(timed(&format!("source_root:{name}"), || {
+                check_source_dir(path)
+            }));
+        }
+        checks
+    }
+}
+
+#[async_trait]
+impl HealthProbePort for StoreHealthFsAdapter {
+    async fn probe(&self) -> Vec<SubsystemHealth> {
+        let probe = self.clone();
+        match task::spawn_blocking(move || probe.probe_sync()).await {
+            Ok(checks) => checks,
+            Err(e) => vec![SubsystemHealth {
+                name: "probe".into(),
+                ok: false,
+                detail: format!("task execution failed: {e}"),
+                elapsed_ms: 0,
+            }],
+        }
+    }
+}
+
+fn timed(name: &str, check: impl FnOnce() -> Result<String, String>) -> SubsystemHealth {
+    let started = Instant::now();
+    let outcome = check();
+    let elapsed_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    let (ok, detail) = match outcome {
+        Ok(detail) => (true, detail),
+        Err(detail) => (false, detail),
+    };
+    SubsystemHealth {
+        name: name.to_string(),
+        ok,
+        detail,
+        elapsed_ms,
+    }
+}
+
+fn check_storage_writable(storage_dir: &Path) -> Result<String, String> {
+    let probe = storage_dir.join(PROBE_FILE);
+    let written = std::fs::create_dir_all(storage_dir).and_then(|()| {
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&probe)
+            .and_then(|mut file| {
+                file.write_all(b"ok")?;
+                file.sync_all()
+            })
+    });
+    let removed = std::fs::remove_file(&probe);
+    written
+        .and(removed)
+        .map(|()| format!("'{}' is writable", storage_dir.display()))
+        .map_err(|e| format!("'{}' is not writable: {e}", storage_dir.display()))
+}
+
+fn check_repo_lock(storage_dir: &Path) -> Result<String, String> {
+    let lock_path = JsonStorageAdapter::repo_lock_path(storage_dir);
+    let lock = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&lock_path)
+        .map_err(|e| format!("cannot open '{}': {e}", lock_path.display()))?;
+    let deadline = Instant::now() + LOCK_WAIT;
+    loop {
+        match lock.try_lock_exclusive() {
+            Ok(()) => break,
+            Err(_) if Instant::now() < deadline => std::thread::sleep(LOCK_POLL),
+            Err(e) => return Err(format!("not obtainable within {LOCK_WAIT:?}: {e}")),
+        }
+    }
+    if let Err(e) = FileExt::unlock(&lock) {
+        tracing::warn!("failed to unlock repo lock: {e}");
+    }
+    Ok("obtained and released".into())
+}
+
+fn check_source_dir(path: &Path) -> Result<String, String> {
+    match std::fs::read_dir(path) {
+        Ok(_) => Ok(format!("'{}' is a readable directory", path.display())),
+        Err(e) => Err(format!("'{}' is not readable: {e}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_probe_reports_each_subsystem_and_missing_roots() {
+        let dir = tempdir().unwrap();
+        let storage_dir = dir.path().join("packs");
+        std::fs::create_dir_all(&storage_dir).unwrap();
+        let probe = StoreHealthFsAdapter::new(storage_dir.clone(), dir.path().to_path_buf())
+            .with_source_roots(vec![(
+                SourceRootName::new("gone").unwrap(),
+                dir.path().join("gone"),
+            )]);
+
+        let checks = probe.probe().await;
+        let outcome: Vec<_> = checks.iter().map(|c| (c.name.as_str(), c.ok)).collect();
+        assert_eq!(
+            outcome,
+            vec![
+                ("storage", true),
+                ("repo_lock", true),
+                ("source_root", true),
+                ("source_root:gone", false),
+            ]
+        );
+        assert!(!storage_dir.join(PROBE_FILE).exists());
+    }
+}
@@ -0,0 +1,185 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::path::{Component, Path, PathBuf};
+use tokio::task;
+
+use crate::{
+    app::ports::{ExportedFile, MarkdownExportPort},
+    domain::errors::{DomainError, Result},
+};
+
+pub const EXPORT_ROOT_ENV: &str = "CONTEXT_PACK_EXPORT_ROOT";
+
+/// Writes rendered packs under one export root. Paths are relative to the
+/// root and end in `.md`; `..`, absolute paths and symlinked directories
+/// leading out of the root are rejected.
+pub struct MarkdownExportFsAdapter {
+    root: PathBuf,
+}
+
+impl MarkdownExportFsAdapter {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+}
+
+#[async_trait]
+impl MarkdownExportPort for MarkdownExportFsAdapter {
+    async fn write_export(&self, relative_path: &str, markdown: &str) -> Result<ExportedFile> {
+        let root = self.root.clone();
+        let relative_path = relative_path.to_string();
+        let markdown = markdown.to_string();
+        task::spawn_blocking(move || write_export_sync(&root, &relative_path, &markdown))
+            .await
+            .map_err(|e| DomainError::Io(format!("export task failed: {}", e)))?
+    }
+}
+
+fn invalid_export_path(requested: &str, reason: &str) -> DomainError {
+    DomainError::DetailedInvalidData {
+        message: format!("invalid export_path '{}': {}", requested, reason),
+        details: json!({
+            "field": "export_path",
+            "requested": requested,
+            "reason": reason,
+        }),
+    }
+}
+
+fn io_err(context: &str, path: &Path, e: impl std::fmt::Display) -> DomainError {
+    DomainError::Io(format!("{} '{}': {}", context, path.display(), e))
+}
+
+/// Lexical checks only; `create_export_dirs` refuses symlinks on the way down.
+fn validate_relative(requested: &str) -> Result<PathBuf> {
+    let path = Path::new(requested.trim());
+    if path.as_os_str().is_empty() {
+        return Err(invalid_export_path(requested, "path is empty"));
+    }
+    if !path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(invalid_export_path(
+            requested,
+            "must be relative to the export root without '.' or '..' segments",
+        ));
+    }
+    if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+        return Err(invalid_export_path(requested, "must end in .md"));
+    }
+    Ok(path.to_path_buf())
+}
+
+/// Walks the directories of `relative` below `root` one at a time and
+/// creates the missing ones. Every existing component must be a real
+/// directory, so a symlink can't carry the walk (or the created
+/// directories) outside the root.
+fn create_export_dirs(root: &Path, relative: &Path, requested: &str) -> Result<PathBuf> {
+    let mut dir = root.to_path_buf();
+    let Some(parent) = relative.parent() else {
+        return Ok(dir);
+    };
+    for component in parent.components() {
+        dir.push(component);
+        match std::fs::symlink_metadata(&dir) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                return Err(invalid_export_path(
+                    requested,
+                    "leaves the export root through a symlink",
+                ));
+            }
+            Ok(meta) if meta.is_dir() => {}
+            Ok(_) => {
+                return Err(invalid_export_path(
+                    requested,
+                    "a directory in the path is not a directory",
+                ));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                std::fs::create_dir(&dir)
+                    .map_err(|e| io_err("failed to create export directory", &dir, e))?;
+            }
+            Err(e) => return Err(io_err("failed to inspect export directory", &dir, e)),
+        }
+    }
+    Ok(dir)
+}
+
+fn write_export_sync(root: &Path, requested: &str, markdown: &str) -> Result<ExportedFile> {
+    let relative = validate_relative(requested)?;
+    std::fs::create_dir_all(root).map_err(|e| io_err("failed to create export root", root, e))?;
+    let root = root
+        .canonicalize()
+        .map_err(|e| io_err("failed to resolve export root", root, e))?;
+    let target = root.join(&relative);
+    let parent = create_export_dirs(&root, &relative, requested)?;
+    let Some(file_name) = target.file_name() else {
+        return Err(invalid_export_path(requested, "path is empty"));
+    };
+    let target = parent.join(file_name);
+    if std::fs::symlink_metadata(&target).is_ok_and(|meta| !meta.is_file()) {
+        return Err(invalid_export_path(
+            requested,
+            "target exists and is not a regular file",
+        ));
+    }
+
+    let tmp = target.with_extension("md.tmp");
+    std::fs::write(&tmp, markdown).map_err(|e| io_err("failed to write", &tmp, e))?;
+    std::fs::rename(&tmp, &target).map_err(|e| io_err("failed to rename", &target, e))?;
+    Ok(ExportedFile {
+        path: target.display().to_string(),
+        bytes: markdown.len() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_writes_under_root_and_rejects_escapes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("exports");
+
+        let written = write_export_sync(&root, "team/pack.md", "# Pack\n").unwrap();
+        assert_eq!(written.bytes, 7);
+        assert_eq!(
+            std::fs::read_to_string(root.join("team").join("pack.md")).unwrap(),
+            "# Pack\n"
+        );
+
+        for bad in ["../pack.md", "/tmp/pack.md", "./pack.md", "pack.txt", ""] {
+            let err = write_export_sync(&root, bad, "x").unwrap_err();
+            assert!(
+                matches!(err, DomainError::DetailedInvalidData { .. }),
+                "{bad}: {err:?}"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_export_rejects_symlinked_directory_outside_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("exports");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+
+        for bad in ["link/pack.md", "link/nested/deeper/pack.md"] {
+            let err = write_export_sync(&root, bad, "x").unwrap_err();
+            assert!(
+                matches!(err, DomainError::DetailedInvalidData { .. }),
+                "{bad}: {err:?}"
+            );
+        }
+        assert!(!outside.join("pack.md").exists());
+        assert!(
+            !outside.join("nested").exists(),
+            "no directory is created through the symlink"
+        );
+    }
+}
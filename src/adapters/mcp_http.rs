@@ -0,0 +1,563 @@
+//! MCP over HTTP with server-sent events, sharing the stdio server's handlers.
+//!
+//! A client opens `GET /sse`; the first event (`endpoint`) names the URL to
+//! post JSON-RPC messages to (`/messages?session_id=<id>`). Each POST is
+//! answered `202 Accepted` and its reply, if any, arrives on the stream as a
+//! `message` event. Every stream is its own MCP session, so many agent
+//! processes can share one server and pack store.
+//!
+//! Requests whose `Origin` is not a loopback origin are refused, and so are
+//! requests naming a non-loopback `Host` while the server is bound to a
+//! loopback address, which stops DNS-rebinding pages. A bearer token
+//! (`CONTEXT_PACK_HTTP_TOKEN`) is required on every request when set, and a
+//! non-loopback bind without one refuses to start.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use rand::Rng;
+use serde::Serialize;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+
+use crate::adapters::mcp_stdio::{
+    handle_frame, next_stale, ResourceSubscriptions, ServerContext, ServerSession, MAX_FRAME_BYTES,
+};
+
+pub const HTTP_ADDR_ENV: &str = "CONTEXT_PACK_HTTP_ADDR";
+pub const HTTP_TOKEN_ENV: &str = "CONTEXT_PACK_HTTP_TOKEN";
+
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+/// Time a client gets to send the request line and headers, and then the
+/// body, so a slow client can't hold a connection open forever.
+const HEADER_READ_TIMEOUT: Duration = Duration::from_secs(10);
+const BODY_READ_TIMEOUT: Duration = Duration::from_secs(30);
+const EVENT_BUFFER: usize = 64;
+/// SSE comment sent on idle streams so dropped clients are noticed.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// `CONTEXT_PACK_HTTP_ADDR` as a socket address; `None` when unset or blank.
+pub fn http_addr_from_env() -> Result<Option<SocketAddr>, String> {
+    match std::env::var(HTTP_ADDR_ENV) {
+        Ok(raw) if !raw.trim().is_empty() => raw
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| format!("expected host:port, got '{}': {}", raw.trim(), e)),
+        _ => Ok(None),
+    }
+}
+
+/// `CONTEXT_PACK_HTTP_TOKEN`, trimmed; `None` when unset or blank.
+pub fn http_token_from_env() -> Option<String> {
+    std::env::var(HTTP_TOKEN_ENV)
+        .ok()
+        .map(|raw| raw.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Which requests the transport serves; see the module docs.
+struct HttpAccess {
+    token: Option<String>,
+    /// Bound to a loopback address, so only loopback `Host`s are genuine.
+    loopback: bool,
+}
+
+impl HttpAccess {
+    fn admit(&self, request: &HttpRequest) -> Result<(), HttpError> {
+        let forbidden = |message: &str| HttpError::new("403 Forbidden", message);
+        if let Some(origin) = request.header("origin") {
+            if !is_loopback_origin(origin) {
+                return Err(forbidden("origin not allowed"));
+            }
+        }
+        if self.loopback && !request.header("host").is_some_and(is_loopback_authority) {
+            return Err(forbidden("host not allowed"));
+        }
+        if let Some(token) = &self.token {
+            let presented = request
+                .header("authorization")
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .map(str::trim);
+            if !presented.is_some_and(|presented| tokens_match(presented, token)) {
+                return Err(HttpError::new(
+                    "401 Unauthorized",
+                    "missing or wrong bearer token",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `host[:port]` naming `localhost` or a loopback IP.
+fn is_loopback_authority(authority: &str) -> bool {
+    let host = match authority.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => authority
+            .rsplit_once(':')
+            .map_or(authority, |(host, _)| host),
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// `http(s)://<loopback authority>`; `null` and other origins are not.
+fn is_loopback_origin(origin: &str) -> bool {
+    origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+        .is_some_and(|rest| is_loopback_authority(rest.split('/').next().unwrap_or_default()))
+}
+
+/// Compares without stopping at the first differing byte.
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+struct HttpSession {
+    state: tokio::sync::Mutex<ServerSession>,
+    events: mpsc::Sender<String>,
+}
+
+#[derive(Default)]
+struct SessionRegistry {
+    sessions: Mutex<HashMap<String, Arc<HttpSession>>>,
+}
+
+impl SessionRegistry {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<HttpSession>>> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// A new session's id, event stream and subscriptions; the stream reads
+    /// subscriptions directly, since the state is locked while a message runs.
+    fn open(&self) -> (String, mpsc::Receiver<String>, ResourceSubscriptions) {
+        let id = format!("{:032x}", rand::thread_rng().gen::<u128>());
+        let (events, rx) = mpsc::channel(EVENT_BUFFER);
+        let state = ServerSession::default().with_replay_scope(format!("http:{id}"));
+        let subscriptions = state.subscriptions().clone();
+        let session = HttpSession {
+            state: tokio::sync::Mutex::new(state),
+            events,
+        };
+        self.lock().insert(id.clone(), Arc::new(session));
+        (id, rx, subscriptions)
+    }
+
+    fn get(&self, id: &str) -> Option<Arc<HttpSession>> {
+        self.lock().get(id).cloned()
+    }
+
+    fn close(&self, id: &str) {
+        self.lock().remove(id);
+    }
+}
+
+#[derive(Debug)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    query: String,
+    /// Header names lowercased, in request order.
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct HttpError {
+    status: &'static str,
+    message: String,
+}
+
+impl HttpError {
+    fn new(status: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+pub(crate) async fn start_mcp_http_server(
+    addr: SocketAddr,
+    token: Option<String>,
+    ctx: ServerContext,
+) -> anyhow::Result<()> {
+    let loopback = addr.ip().is_loopback();
+    if !loopback && token.is_none() {
+        anyhow::bail!(
+            "refusing to serve MCP over HTTP on non-loopback {addr} without {HTTP_TOKEN_ENV}"
+        );
+    }
+    let access = Arc::new(HttpAccess { token, loopback });
+    let listener = TcpListener::bind(addr).await?;
+    tracing::info!(
+        "MCP HTTP/SSE transport listening on {}",
+        listener.local_addr()?
+    );
+    let ctx = Arc::new(ctx);
+    let registry = Arc::new(SessionRegistry::default());
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                tracing::warn!("http accept error: {}", e);
+                continue;
+            }
+        };
+        let ctx = ctx.clone();
+        let registry = registry.clone();
+        let access = access.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &ctx, &registry, &access).await {
+                tracing::debug!("http connection from {} ended: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    ctx: &ServerContext,
+    registry: &SessionRegistry,
+    access: &HttpAccess,
+) -> anyhow::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let request = match read_request(&mut stream).await {
+        Ok(request) => request,
+        Err(e) => return write_plain(stream.get_mut(), e.status, &e.message).await,
+    };
+    if let Err(e) = access.admit(&request) {
+        return write_plain(stream.get_mut(), e.status, &e.message).await;
+    }
+    let mut stream = stream.into_inner();
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/sse") => serve_events(stream, ctx, registry).await,
+        ("POST", "/messages") => {
+            let (status, message) = match post_message(&request, ctx, registry).await {
+                Ok(()) => ("202 Accepted", "accepted".to_string()),
+                Err(e) => (e.status, e.message),
+            };
+            write_plain(&mut stream, status, &message).await
+        }
+        (_, "/sse" | "/messages") => {
+            write_plain(&mut stream, "405 Method Not Allowed", "method not allowed").await
+        }
+        _ => write_plain(&mut stream, "404 Not Found", "not found").await,
+    }
+}
+
+/// Holds the event stream open until the client disconnects or sends `exit`.
+/// Resource updates for subscribed packs go out on it as they happen.
+async fn serve_events(
+    mut stream: TcpStream,
+    ctx: &ServerContext,
+    registry: &SessionRegistry,
+) -> anyhow::Result<()> {
+    let (session_id, mut events, subscriptions) = registry.open();
+    let mut stale_feed = ctx.stale_feed();
+    tracing::info!("http session opened: {}", session_id);
+    let result = async {
+        stream
+            .write_all(
+                b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+            )
+            .await?;
+        write_event(
+            &mut stream,
+            "endpoint",
+            &format!("/messages?session_id={}", session_id),
+        )
+        .await?;
+        loop {
+            let next = tokio::select! {
+                next = tokio::time::timeout(KEEPALIVE_INTERVAL, events.recv()) => next,
+                stale = next_stale(&mut stale_feed) => {
+                    if let Some(note) = subscriptions.update_for(&stale) {
+                        write_event(&mut stream, "message", &note.to_string()).await?;
+                    }
+                    continue;
+                }
+            };
+            match next {
+                Ok(Some(data)) => write_event(&mut stream, "message", &data).await?,
+                Ok(None) => break,
+                Err(_) => {
+                    stream.write_all(b": keepalive\n\n").await?;
+                    stream.flush().await?;
+                }
+            }
+        }
+        anyhow::Ok(())
+    }
+    .await;
+    registry.close(&session_id);
+    tracing::info!("http session closed: {}", session_id);
+    result
+}
+
+async fn post_message(
+    request: &HttpRequest,
+    ctx: &ServerContext,
+    registry: &SessionRegistry,
+) -> Result<(), HttpError> {
+    let session_id = query_param(&request.query, "session_id")
+        .ok_or_else(|| HttpError::new("400 Bad Request", "missing session_id"))?;
+    let session = registry
+        .get(session_id)
+        .ok_or_else(|| HttpError::new("404 Not Found", "unknown session"))?;
+    let raw = std::str::from_utf8(&request.body)
+        .map_err(|_| HttpError::new("400 Bad Request", "body must be UTF-8 JSON"))?;
+
+    // One message at a time per session, like a stdio connection.
+    let mut state = session.state.lock().await;
+    let (notifications, mut pending) = mpsc::unbounded_channel();
+    state.set_notifications(Some(notifications));
+    // Progress notifications go out on the stream while the call runs, and
+    // always ahead of its reply.
+    let (reply, exit) = {
+        let frame = handle_frame(raw, ctx, &mut state);
+        tokio::pin!(frame);
+        loop {
+            tokio::select! {
+                biased;
+                done = &mut frame => break done,
+                Some(note) = pending.recv() => send_event(&session, &note).await?,
+            }
+        }
+    };
+    state.set_notifications(None);
+    while let Ok(note) = pending.try_recv() {
+        send_event(&session, &note).await?;
+    }
+    if let Some(reply) = reply {
+        send_event(&session, &reply).await?;
+    }
+    if exit {
+        registry.close(session_id);
+    }
+    Ok(())
+}
+
+/// Queues one JSON-RPC message as a `message` event on the session stream.
+async fn send_event(session: &HttpSession, message: &impl Serialize) -> Result<(), HttpError> {
+    let data = serde_json::to_string(message)
+        .map_err(|e| HttpError::new("500 Internal Server Error", e.to_string()))?;
+    session
+        .events
+        .send(data)
+        .await
+        .map_err(|_| HttpError::new("410 Gone", "event stream closed"))
+}
+
+/// Reads one request. Header lines are read through a `MAX_HEADER_BYTES + 1`
+/// limit and the body through its `Content-Length`, so an oversized line is
+/// refused before it is buffered.
+async fn read_request<R: AsyncBufRead + Unpin>(stream: &mut R) -> Result<HttpRequest, HttpError> {
+    let (head, content_length) = tokio::time::timeout(HEADER_READ_TIMEOUT, read_head(stream))
+        .await
+        .map_err(|_| HttpError::new("408 Request Timeout", "timed out reading headers"))??;
+
+    let bad = |message: &str| HttpError::new("400 Bad Request", message);
+    let request_line = head.lines().next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad("malformed request line"));
+    };
+    if content_length > MAX_FRAME_BYTES {
+        return Err(HttpError::new(
+            "413 Payload Too Large",
+            format!("body exceeds {} bytes", MAX_FRAME_BYTES),
+        ));
+    }
+    let mut body = Vec::with_capacity(content_length);
+    tokio::time::timeout(
+        BODY_READ_TIMEOUT,
+        (&mut *stream)
+            .take(content_length as u64)
+            .read_to_end(&mut body),
+    )
+    .await
+    .map_err(|_| HttpError::new("408 Request Timeout", "timed out reading body"))?
+    .map_err(|e| bad(&e.to_string()))?;
+    if body.len() < content_length {
+        return Err(bad("connection closed before end of body"));
+    }
+    let headers = head
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    Ok(HttpRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body,
+    })
+}
+
+/// The request line and headers, and the `Content-Length`.
+async fn read_head<R: AsyncBufRead + Unpin>(stream: &mut R) -> Result<(String, usize), HttpError> {
+    let bad = |message: &str| HttpError::new("400 Bad Request", message);
+    let too_large = || HttpError::new("431 Request Header Fields Too Large", "headers too large");
+    let mut limited = stream.take(MAX_HEADER_BYTES as u64 + 1);
+    let mut head = String::new();
+    let mut content_length = 0usize;
+    loop {
+        let mut line = String::new();
+        let n = limited
+            .read_line(&mut line)
+            .await
+            .map_err(|e| bad(&e.to_string()))?;
+        if head.len() + line.len() > MAX_HEADER_BYTES {
+            return Err(too_large());
+        }
+        if n == 0 {
+            return Err(bad("connection closed before end of headers"));
+        }
+        if line == "\r\n" || line == "\n" {
+            break;
+        }
+        if !head.is_empty() {
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| bad("invalid Content-Length"))?;
+                }
+            }
+        }
+        head.push_str(&line);
+    }
+    Ok((head, content_length))
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+async fn write_event(stream: &mut TcpStream, event: &str, data: &str) -> std::io::Result<()> {
+    // Replies are single-line JSON, so one `data:` line carries the whole frame.
+    stream
+        .write_all(format!("event: {}\ndata: {}\n\n", event, data).as_bytes())
+        .await?;
+    stream.flush().await
+}
+
+async fn write_plain(stream: &mut TcpStream, status: &str, body: &str) -> anyhow::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_param_finds_named_non_empty_values() {
+        assert_eq!(query_param("session_id=abc&x=1", "session_id"), Some("abc"));
+        assert_eq!(query_param("x=1&session_id=abc", "session_id"), Some("abc"));
+        assert_eq!(query_param("session_id=", "session_id"), None);
+        assert_eq!(query_param("", "session_id"), None);
+    }
+
+    #[tokio::test]
+    async fn test_access_checks_origin_host_and_token() {
+        let request = |raw: &str| {
+            let raw = format!("GET /sse HTTP/1.1\r\n{raw}\r\n");
+            async move { read_request(&mut raw.as_bytes()).await.unwrap() }
+        };
+        let local = HttpAccess {
+            token: None,
+            loopback: true,
+        };
+        for ok in [
+            "Host: 127.0.0.1:8080\r\n",
+            "Host: localhost\r\nOrigin: http://localhost:3000\r\n",
+            "Host: [::1]:8080\r\n",
+        ] {
+            assert!(local.admit(&request(ok).await).is_ok(), "{ok}");
+        }
+        for refused in [
+            "",
+            "Host: rebound.example:8080\r\n",
+            "Host: 127.0.0.1:8080\r\nOrigin: https://evil.example\r\n",
+            "Host: 127.0.0.1:8080\r\nOrigin: null\r\n",
+        ] {
+            let err = local.admit(&request(refused).await).unwrap_err();
+            assert_eq!(err.status, "403 Forbidden", "{refused}");
+        }
+
+        let exposed = HttpAccess {
+            token: Some("s3cret".into()),
+            loopback: false,
+        };
+        let authorized = request("Host: packs.internal\r\nAuthorization: Bearer s3cret\r\n").await;
+        assert!(exposed.admit(&authorized).is_ok());
+        for wrong in [
+            "",
+            "Authorization: Bearer s3cre\r\n",
+            "Authorization: s3cret\r\n",
+        ] {
+            let raw = format!("Host: packs.internal\r\n{wrong}");
+            let err = exposed.admit(&request(&raw).await).unwrap_err();
+            assert_eq!(err.status, "401 Unauthorized", "{wrong}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_request_limits_headers_and_body() {
+        let mut ok: &[u8] =
+            b"POST /messages?session_id=a HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}extra";
+        let request = read_request(&mut ok).await.unwrap();
+        assert_eq!(request.path, "/messages");
+        assert_eq!(request.body, b"{}");
+        assert_eq!(ok, b"extra", "the body read stops at Content-Length");
+
+        // One endless header line is refused once it passes the limit.
+        let mut line = b"GET /sse HTTP/1.1\r\nX-Pad: ".to_vec();
+        line.resize(4 * MAX_HEADER_BYTES, b'a');
+        let err = read_request(&mut line.as_slice()).await.unwrap_err();
+        assert_eq!(err.status, "431 Request Header Fields Too Large");
+
+        let mut short: &[u8] = b"POST /messages HTTP/1.1\r\nContent-Length: 10\r\n\r\n{}";
+        let err = read_request(&mut short).await.unwrap_err();
+        assert_eq!(err.status, "400 Bad Request");
+    }
+}
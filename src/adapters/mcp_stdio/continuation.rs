@@ -0,0 +1,239 @@
+use rand::Rng;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::domain::errors::DomainError;
+
+use super::MAX_FRAME_BYTES;
+
+/// Text per chunk; half the frame limit leaves room for JSON escaping.
+const CHUNK_BYTES: usize = MAX_FRAME_BYTES / 2;
+/// How long an unfinished stream stays readable after its last chunk.
+const CONTINUATION_TTL: Duration = Duration::from_secs(600);
+/// Streams held at once; starting another drops the least recently read.
+const MAX_PENDING: usize = 16;
+
+struct Pending {
+    text: String,
+    offset: usize,
+    touched: Instant,
+}
+
+/// `output` text too large for one frame, held so `output continue` can hand
+/// it out chunk by chunk. Shared by every session of a server run; clones
+/// share state.
+#[derive(Clone)]
+pub(crate) struct OutputContinuations {
+    pending: Arc<Mutex<HashMap<String, Pending>>>,
+    chunk_bytes: usize,
+}
+
+impl Default for OutputContinuations {
+    fn default() -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            chunk_bytes: CHUNK_BYTES,
+        }
+    }
+}
+
+impl OutputContinuations {
+    /// Returns `result` unchanged when it serializes to at most
+    /// `MAX_FRAME_BYTES`. Otherwise the first text entry (the markdown
+    /// render) is streamed: the reply carries its first chunk plus a
+    /// continuation entry, and any further entries and `structuredContent`
+    /// are dropped.
+    pub(crate) fn fit(&self, result: Value) -> Value {
+        let size = serde_json::to_string(&result).map_or(0, |encoded| encoded.len());
+        if size <= MAX_FRAME_BYTES {
+            return result;
+        }
+        let text = result
+            .get("content")
+            .and_then(|content| content.get(0))
+            .and_then(|entry| entry.get("text"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let total = text.len();
+        let token = new_token();
+        let mut pending = Pending {
+            text,
+            offset: 0,
+            touched: Instant::now(),
+        };
+        let (chunk, start, done) = self.take_chunk(&mut pending);
+        if !done {
+            let mut map = self.pending();
+            evict(&mut map);
+            map.insert(token.clone(), pending);
+        }
+        chunk_result(chunk, start, total, (!done).then_some(token))
+    }
+
+    /// `output continue`: the next chunk of the stream behind `token`. The
+    /// last chunk's continuation entry has `continuation_token: null` and
+    /// the token stops working.
+    pub(crate) fn next(&self, token: &str) -> Result<Value, DomainError> {
+        let mut map = self.pending();
+        evict(&mut map);
+        let Some(mut pending) = map.remove(token) else {
+            return Err(DomainError::DetailedInvalidData {
+                message: format!(
+                    "unknown or expired continuation_token '{}'; read the pack again",
+                    token
+                ),
+                details: json!({
+                    "tool": "output",
+                    "action": "continue",
+                    "field": "continuation_token",
+                    "ttl_seconds": CONTINUATION_TTL.as_secs(),
+                }),
+            });
+        };
+        let total = pending.text.len();
+        let (chunk, start, done) = self.take_chunk(&mut pending);
+        if !done {
+            pending.touched = Instant::now();
+            map.insert(token.to_string(), pending);
+        }
+        Ok(chunk_result(
+            chunk,
+            start,
+            total,
+            (!done).then(|| token.to_string()),
+        ))
+    }
+
+    /// Cuts the next chunk at a line break where one falls in the second
+    /// half of the window, else at the last char boundary that fits.
+    fn take_chunk(&self, pending: &mut Pending) -> (String, usize, bool) {
+        let start = pending.offset;
+        let rest = &pending.text[start..];
+        let end = if rest.len() <= self.chunk_bytes {
+            rest.len()
+        } else {
+            let mut cut = self.chunk_bytes;
+            while !rest.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            match rest[..cut].rfind('\n') {
+                Some(newline) if newline + 1 > cut / 2 => newline + 1,
+                _ => cut,
+            }
+        };
+        pending.offset = start + end;
+        let done = pending.offset == pending.text.len();
+        (rest[..end].to_string(), start, done)
+    }
+
+    fn pending(&self) -> std::sync::MutexGuard<'_, HashMap<String, Pending>> {
+        self.pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    #[cfg(test)]
+    fn with_chunk_bytes(chunk_bytes: usize) -> Self {
+        Self {
+            chunk_bytes,
+            ..Self::default()
+        }
+    }
+}
+
+/// Drops expired streams, then the least recently read ones until there is
+/// room for one more.
+fn evict(map: &mut HashMap<String, Pending>) {
+    map.retain(|_, pending| pending.touched.elapsed() < CONTINUATION_TTL);
+    while map.len() >= MAX_PENDING {
+        let Some(oldest) = map
+            .iter()
+            .min_by_key(|(_, pending)| pending.touched)
+            .map(|(token, _)| token.clone())
+        else {
+            break;
+        };
+        map.remove(&oldest);
+    }
+}
+
+fn new_token() -> String {
+    const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+    let mut rng = rand::thread_rng();
+    let suffix: String = (0..16)
+        .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+        .collect();
+    format!("ct_{}", suffix)
+}
+
+fn chunk_result(chunk: String, start: usize, total: usize, token: Option<String>) -> Value {
+    let end = start + chunk.len();
+    let continuation = json!({
+        "continuation_token": token,
+        "byte_range": [start, end],
+        "total_bytes": total,
+    });
+    json!({
+        "content": [
+            { "type": "text", "text": chunk },
+            { "type": "text", "text": continuation.to_string() }
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oversized(text: &str) -> Value {
+        let padding = "x".repeat(MAX_FRAME_BYTES);
+        json!({
+            "content": [
+                { "type": "text", "text": text },
+                { "type": "text", "text": padding }
+            ],
+            "structuredContent": {}
+        })
+    }
+
+    fn parts(result: &Value) -> (String, Value) {
+        let chunk = result["content"][0]["text"].as_str().unwrap().to_string();
+        let meta = serde_json::from_str(result["content"][1]["text"].as_str().unwrap()).unwrap();
+        (chunk, meta)
+    }
+
+    #[test]
+    fn test_fit_keeps_small_results() {
+        let store = OutputContinuations::with_chunk_bytes(8);
+        let result = json!({ "content": [{ "type": "text", "text": "short enough" }] });
+        assert_eq!(store.fit(result.clone()), result);
+    }
+
+    #[test]
+    fn test_oversized_text_streams_in_line_aligned_chunks_until_done() {
+        let store = OutputContinuations::with_chunk_bytes(10);
+        let text = "line one\nline two\nline three é\n";
+
+        let (mut joined, mut meta) = parts(&store.fit(oversized(text)));
+        assert_eq!(joined, "line one\n");
+        assert_eq!(meta["total_bytes"], text.len());
+        let token = meta["continuation_token"].as_str().unwrap().to_string();
+        assert!(token.starts_with("ct_"));
+        while let Some(token) = meta["continuation_token"].as_str().map(str::to_string) {
+            let (chunk, next) = parts(&store.next(&token).unwrap());
+            assert!(chunk.len() <= 10);
+            joined.push_str(&chunk);
+            meta = next;
+        }
+        assert_eq!(joined, text);
+        assert_eq!(meta["byte_range"][1], text.len());
+
+        let err = store.next(&token).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown or expired continuation_token"));
+    }
+}
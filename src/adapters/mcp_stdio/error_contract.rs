@@ -5,7 +5,37 @@ use crate::adapters::mcp_stdio::to_json_text;
 use crate::domain::errors::DomainError;
 
 pub(super) fn domain_error_response(id: Value, err: &DomainError) -> RpcEnvelope {
-    let (kind, code, details) = match err {
+    let (kind, code, details) = classify(err);
+    let mut payload = json!({
+        "error": true,
+        "kind": kind,
+        "code": code,
+        "message": err.to_string(),
+        "request_id": id
+    });
+    if !details.is_null() {
+        payload["details"] = details;
+    }
+    let text = to_json_text(&payload);
+
+    // MCP convention: tool-level errors are returned inside result + isError=true
+    RpcEnvelope::success(
+        id,
+        json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": true
+        }),
+    )
+}
+
+/// The `code` a tool error is reported under; counted by `server stats`.
+pub(super) fn error_code(err: &DomainError) -> &'static str {
+    classify(err).1
+}
+
+/// `(kind, code, details)` of the error contract for `err`.
+fn classify(err: &DomainError) -> (&'static str, &'static str, Value) {
+    match err {
         DomainError::InvalidData(_) => ("validation", "invalid_data", Value::Null),
         DomainError::DetailedInvalidData {
             details,
@@ -43,23 +73,27 @@ pub(super) fn domain_error_response(id: Value, err: &DomainError) -> RpcEnvelope
             last_updated_at,
             changed_section_keys,
             guidance,
-        } => (
-            "conflict",
-            "revision_conflict",
-            json!({
+            produced_by,
+        } => {
+            let mut details = json!({
                 "expected_revision": expected_revision,
                 "current_revision": current_revision,
                 "actual_revision": current_revision,
                 "last_updated_at": last_updated_at,
                 "changed_section_keys": changed_section_keys,
                 "guidance": guidance,
-            }),
-        ),
+            });
+            if let Some(reason) = produced_by {
+                details["produced_by"] = json!(reason);
+            }
+            ("conflict", "revision_conflict", details)
+        }
         DomainError::InvalidState(_) => ("invalid_state", "invalid_state", Value::Null),
         DomainError::FinalizeValidation {
             missing_sections,
             missing_fields,
             invalid_refs,
+            failed_rules,
             ..
         } => (
             "invalid_state",
@@ -68,35 +102,63 @@ pub(super) fn domain_error_response(id: Value, err: &DomainError) -> RpcEnvelope
                 "missing_sections": missing_sections,
                 "missing_fields": missing_fields,
                 "invalid_refs": invalid_refs,
+                "failed_rules": failed_rules,
+            }),
+        ),
+        DomainError::SignOffRequired {
+            required_approvals,
+            current_approvals,
+            missing_roles,
+            outstanding,
+            ..
+        } => (
+            "validation",
+            "signoff_required",
+            json!({
+                "required_approvals": required_approvals,
+                "current_approvals": current_approvals,
+                "missing_roles": missing_roles,
+                "outstanding": outstanding,
+            }),
+        ),
+        DomainError::Forbidden { owner, actor, .. } => (
+            "forbidden",
+            "owner_mismatch",
+            json!({
+                "owner": owner,
+                "actor": actor,
+                "guidance": "the pack belongs to another actor; retry with force=true only if overriding their work is intended",
             }),
         ),
         DomainError::StaleRef(_) => ("stale_ref", "stale_ref", Value::Null),
         DomainError::Io(_) => ("io_error", "io_error", Value::Null),
+        DomainError::StorageReadOnly(_) => (
+            "storage_read_only",
+            "storage_read_only",
+            json!({
+                "guidance": "the pack store is read-only; output reads still work, restart on writable storage to make changes",
+            }),
+        ),
+        DomainError::DecryptionFailed(_) => (
+            "decryption_failed",
+            "decryption_failed",
+            json!({
+                "guidance": "set CONTEXT_PACK_ENCRYPTION_KEY to the key the pack was written with and restart",
+            }),
+        ),
         DomainError::Deserialize(_) => ("deserialize_error", "deserialize_error", Value::Null),
         DomainError::MigrationRequired(_) => {
             ("migration_required", "migration_required", Value::Null)
         }
         DomainError::PackIdConflict(_) => ("conflict", "pack_id_conflict", Value::Null),
-    };
-
-    let mut payload = json!({
-        "error": true,
-        "kind": kind,
-        "code": code,
-        "message": err.to_string(),
-        "request_id": id
-    });
-    if !details.is_null() {
-        payload["details"] = details;
+        DomainError::CursorVersionUnsupported { version, supported } => (
+            "validation",
+            "cursor_version_unsupported",
+            json!({
+                "token_version": version,
+                "supported_versions": supported,
+                "guidance": "re-issue the read without page_token to get a fresh token",
+            }),
+        ),
     }
-    let text = to_json_text(&payload);
-
-    // MCP convention: tool-level errors are returned inside result + isError=true
-    RpcEnvelope::success(
-        id,
-        json!({
-            "content": [{ "type": "text", "text": text }],
-            "isError": true
-        }),
-    )
 }
@@ -0,0 +1,139 @@
+//! Entry points for the cargo-fuzz targets under `fuzz/` (feature `fuzzing`).
+//! They panic only when a transport invariant breaks, so libFuzzer reports
+//! invariant violations alongside crashes and hangs.
+
+use serde_json::Value;
+use tokio::io::BufReader;
+
+use super::rpc::RpcRequest;
+use super::transport::{read_next_message, TransportMode};
+use super::MAX_FRAME_BYTES;
+use crate::service::ContextPackService;
+
+/// Frame cap for [`read_messages`] under fuzzing: small enough that
+/// generated inputs reach the oversized-frame paths.
+pub const FUZZ_MAX_FRAME_BYTES: usize = 4096;
+
+/// Reads every message in `data` the way the server loop does (a framing
+/// error skips to the next message) and parses each as an RPC request or
+/// batch. Returns how many messages were read.
+pub fn read_messages(data: &[u8], max_frame_bytes: usize) -> usize {
+    block_on(async {
+        let mut reader = BufReader::new(data);
+        let mut read = 0;
+        loop {
+            match read_next_message(&mut reader, max_frame_bytes).await {
+                Ok(Some((raw, mode))) => {
+                    read += 1;
+                    if mode == TransportMode::JsonLine && !raw.is_empty() {
+                        assert!(raw.len() <= max_frame_bytes, "json-line over the cap");
+                        serde_json::from_str::<Value>(&raw)
+                            .expect("json-line messages are checked to be JSON");
+                    }
+                    parse_rpc(&raw);
+                }
+                Ok(None) => return read,
+                Err(_) => continue,
+            }
+        }
+    })
+}
+
+/// Runs one full session over `data` and returns the replies it wrote; every
+/// reply must be a well-formed JSON-RPC envelope or batch of them.
+pub fn serve_bytes(service: &ContextPackService, data: &[u8]) -> Vec<Value> {
+    let mut output = Vec::new();
+    block_on(service.serve_io(data, &mut output)).expect("in-memory session never fails");
+    block_on(async {
+        let mut reader = BufReader::new(output.as_slice());
+        let mut replies = Vec::new();
+        while let Some((raw, _)) = read_next_message(&mut reader, MAX_FRAME_BYTES)
+            .await
+            .expect("replies are well-framed")
+        {
+            let reply: Value = serde_json::from_str(&raw).expect("replies are JSON");
+            let envelopes = match &reply {
+                Value::Array(batch) => batch.iter().collect(),
+                single => vec![single],
+            };
+            for envelope in envelopes {
+                assert_eq!(envelope["jsonrpc"], "2.0", "reply without jsonrpc: {raw}");
+                assert!(
+                    envelope.get("result").is_some() != envelope.get("error").is_some(),
+                    "reply needs exactly one of result/error: {raw}"
+                );
+            }
+            replies.push(reply);
+        }
+        replies
+    })
+}
+
+fn parse_rpc(raw: &str) {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(Value::Array(entries)) => {
+            for entry in entries {
+                let _ = serde_json::from_value::<RpcRequest>(entry);
+            }
+        }
+        Ok(message) => {
+            let _ = serde_json::from_value::<RpcRequest>(message);
+        }
+        Err(_) => {}
+    }
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("current-thread runtime")
+        .block_on(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::ContextPackConfig;
+
+    #[test]
+    fn test_read_messages_survives_malformed_and_mixed_framing() {
+        let oversized = format!("{{\"pad\":\"{}\"}}\n", "x".repeat(FUZZ_MAX_FRAME_BYTES));
+        let inputs: [&[u8]; 7] = [
+            b"Content-Length: 99999999999999999999999\r\n\r\n{}",
+            b"Content-Length: 5\r\nno-colon-header\r\n\r\n{}",
+            b"Content-Length: 12\r\n\r\n{\"id\":\xff\xfe}{\"id\":2}\n",
+            b"{\"id\":1}\nContent-Length: 2\r\n\r\n{}[{\"method\":7},{}]\n",
+            b"Content-Length: 40\r\n\r\n{}",
+            b"\r\n\r\n  \n{\n",
+            oversized.as_bytes(),
+        ];
+        for input in inputs {
+            read_messages(input, FUZZ_MAX_FRAME_BYTES);
+        }
+        assert_eq!(
+            read_messages(b"{\"id\":1}\nContent-Length: 2\r\n\r\n{}", 64),
+            2
+        );
+    }
+
+    #[test]
+    fn test_serve_bytes_replies_to_every_request_in_both_modes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let service =
+            ContextPackService::new(ContextPackConfig::new(tmp.path().join("store"), tmp.path()))
+                .unwrap();
+        let body = br#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#;
+        let mut input =
+            b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\"}\nnot json\n\xff\n".to_vec();
+        input.extend_from_slice(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes());
+        input.extend_from_slice(body);
+        input.extend_from_slice(b"[{\"id\":3,\"method\":\"ping\"},{\"method\":1}]\n");
+        let replies = serve_bytes(&service, &input);
+        assert_eq!(replies[0]["id"], 1);
+        assert!(replies
+            .iter()
+            .any(|reply| reply["id"] == 2 && reply["result"]["tools"].is_array()));
+        assert!(replies.last().unwrap().is_array());
+    }
+}
@@ -0,0 +1,119 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::app::output_usecases::OutputProfile;
+
+/// Client identity reported in `initialize.params.clientInfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ClientInfo {
+    pub(super) name: String,
+    pub(super) version: Option<String>,
+}
+
+impl ClientInfo {
+    pub(super) fn from_initialize_params(params: Option<&Value>) -> Option<Self> {
+        let info = params?.get("clientInfo")?;
+        let name = info
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|name| !name.is_empty())?;
+        Some(Self {
+            name: name.to_string(),
+            version: info
+                .get("version")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        })
+    }
+}
+
+/// `output read` defaults applied when the caller leaves them unset.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct HostOutputDefaults {
+    pub(super) profile: Option<OutputProfile>,
+    pub(super) limit: Option<usize>,
+}
+
+/// Per-host output defaults keyed by lowercase client name, loaded from
+/// `CONTEXT_PACK_HOST_DEFAULTS`, e.g.
+/// `{"claude-code":{"profile":"executor","limit":4}}`.
+#[derive(Debug, Clone, Default)]
+pub(super) struct HostDefaultsConfig {
+    hosts: HashMap<String, HostOutputDefaults>,
+}
+
+impl HostDefaultsConfig {
+    pub(super) fn from_env() -> Self {
+        let Ok(raw) = std::env::var("CONTEXT_PACK_HOST_DEFAULTS") else {
+            return Self::default();
+        };
+        Self::parse(&raw).unwrap_or_else(|err| {
+            tracing::warn!("ignoring invalid CONTEXT_PACK_HOST_DEFAULTS: {err}");
+            Self::default()
+        })
+    }
+
+    pub(super) fn parse(raw: &str) -> Result<Self, serde_json::Error> {
+        if raw.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let hosts: HashMap<String, HostOutputDefaults> = serde_json::from_str(raw)?;
+        Ok(Self {
+            hosts: hosts
+                .into_iter()
+                .map(|(name, defaults)| (name.trim().to_ascii_lowercase(), defaults))
+                .collect(),
+        })
+    }
+
+    pub(super) fn for_client(&self, client: &ClientInfo) -> Option<&HostOutputDefaults> {
+        self.hosts.get(&client.name.to_ascii_lowercase())
+    }
+}
+
+/// Host defaults selected for the current session at `initialize`.
+#[derive(Debug, Clone)]
+pub(super) struct AppliedHostDefaults {
+    pub(super) host: String,
+    pub(super) defaults: HostOutputDefaults,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_host_defaults_match_client_name_case_insensitively() {
+        let config =
+            HostDefaultsConfig::parse(r#"{"Tight-Host":{"profile":"executor","limit":3}}"#)
+                .unwrap();
+        let client = ClientInfo::from_initialize_params(Some(&json!({
+            "clientInfo": { "name": "tight-host", "version": "1.2.0" }
+        })))
+        .unwrap();
+        assert_eq!(client.version.as_deref(), Some("1.2.0"));
+        assert_eq!(
+            config.for_client(&client),
+            Some(&HostOutputDefaults {
+                profile: Some(OutputProfile::Executor),
+                limit: Some(3),
+            })
+        );
+
+        let other = ClientInfo {
+            name: "roomy-host".into(),
+            version: None,
+        };
+        assert!(config.for_client(&other).is_none());
+    }
+
+    #[test]
+    fn test_host_defaults_reject_unknown_fields() {
+        assert!(HostDefaultsConfig::parse(r#"{"h":{"mode":"compact"}}"#).is_err());
+        assert!(HostDefaultsConfig::parse("").unwrap().hosts.is_empty());
+    }
+}
@@ -1,30 +1,64 @@
+mod continuation;
 mod error_contract;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+mod host_defaults;
+mod prompts;
+mod replay;
+mod resources;
 mod rpc;
 mod schema;
 mod tool_input;
 mod tool_output;
+mod tool_server;
+mod trace;
 mod transport;
 
+use serde::Serialize;
 use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use std::sync::Arc;
-use tokio::io::{BufReader, BufWriter};
-use tokio::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, BufReader, BufWriter};
+use tokio::sync::{broadcast, mpsc, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::{Duration, Instant};
 
 use crate::app::input_usecases::InputUseCases;
+use crate::app::metrics::ServerMetrics;
 use crate::app::output_usecases::OutputUseCases;
-use crate::app::ports::FreshnessState;
+use crate::app::ports::{
+    BackupPort, FreshnessState, HealthProbePort, ListFilter, ProgressPort, ReplayJournalPort,
+    SavedFilter, SavedFilterPort,
+};
+use crate::app::progress;
+use crate::app::source_watch::{SourceWatch, StaleRefs};
+use crate::app::supervisor::TaskSupervisor;
 use crate::domain::errors::DomainError;
 use crate::domain::models::Pack;
 use crate::domain::types::Status;
 
-use error_contract::domain_error_response;
+use continuation::OutputContinuations;
+use error_contract::{domain_error_response, error_code};
+use host_defaults::{AppliedHostDefaults, ClientInfo, HostDefaultsConfig};
+use prompts::{handle_prompts_get, handle_prompts_list};
+use replay::{handle_replayable_input, ReplayLocks};
+pub(crate) use resources::ResourceSubscriptions;
+use resources::{handle_resources_list, handle_resources_read, handle_resources_subscription};
 use rpc::{RpcEnvelope, RpcRequest};
 use schema::tools_schema;
 use tool_input::handle_input_tool;
 use tool_output::handle_output_tool;
+use tool_server::handle_server_tool;
+use trace::FrameTracer;
+pub use trace::{trace_frames_enabled, FRAME_TRACE_TARGET, TRACE_FRAMES_ENV};
 use transport::{read_next_message, write_response, TransportMode};
 
-const MAX_FRAME_BYTES: usize = 10 * 1024 * 1024; // 10 MiB
+pub(crate) const MAX_FRAME_BYTES: usize = 10 * 1024 * 1024; // 10 MiB
+/// Distinct unknown method names tracked per session; the rest are counted
+/// under [`OTHER_METHODS`] so a noisy client cannot grow the map unbounded.
+const MAX_TRACKED_METHODS: usize = 64;
+const OTHER_METHODS: &str = "<other>";
+const MAX_CONCURRENT_CALLS_ENV: &str = "CONTEXT_PACK_MAX_CONCURRENT_CALLS";
 
 fn parse_initialize_timeout_ms(raw: Option<&str>) -> Duration {
     const DEFAULT_SECS: u64 = 20;
@@ -37,49 +71,299 @@ fn parse_initialize_timeout_ms(raw: Option<&str>) -> Duration {
     }
 }
 
+/// Validates a raw `CONTEXT_PACK_HOST_DEFAULTS` value for the startup self-check.
+pub(crate) fn check_host_defaults(raw: &str) -> Result<(), String> {
+    HostDefaultsConfig::parse(raw)
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+/// `tools/call` requests a connection runs at once
+/// (`CONTEXT_PACK_MAX_CONCURRENT_CALLS`, default 4); further calls wait.
+fn max_concurrent_calls() -> usize {
+    const DEFAULT: usize = 4;
+    std::env::var(MAX_CONCURRENT_CALLS_ENV)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT)
+}
+
 fn initialize_timeout() -> Duration {
     let raw = std::env::var("CONTEXT_PACK_INITIALIZE_TIMEOUT_MS").ok();
     parse_initialize_timeout_ms(raw.as_deref())
 }
 
-pub async fn start_mcp_server(
+/// Long-lived collaborators shared by every request of a server run.
+#[derive(Clone)]
+pub(crate) struct ServerContext {
     input_uc: Arc<InputUseCases>,
     output_uc: Arc<OutputUseCases>,
-) -> anyhow::Result<()> {
-    let stdin = tokio::io::stdin();
-    let stdout = tokio::io::stdout();
-    let mut reader = BufReader::new(stdin);
-    let mut writer = BufWriter::new(stdout);
-    let mut shutdown_requested = false;
+    replay_journal: Arc<dyn ReplayJournalPort>,
+    backup: Arc<dyn BackupPort>,
+    saved_filters: Arc<dyn SavedFilterPort>,
+    host_defaults: HostDefaultsConfig,
+    /// Background loops reported by `server health`.
+    tasks: TaskSupervisor,
+    /// Tool call counters reported by `server stats`.
+    metrics: ServerMetrics,
+    /// Subsystem checks run by `server health`; none when not configured.
+    health_probe: Option<Arc<dyn HealthProbePort>>,
+    /// Source watcher feeding `resources/subscribe` updates; none when not
+    /// configured, which also hides the `subscribe` capability.
+    source_watch: Option<Arc<SourceWatch>>,
+    /// Oversized `output` renders awaiting `output continue`.
+    continuations: OutputContinuations,
+    /// Serializes replayable calls that share a replay key.
+    replay_locks: ReplayLocks,
+}
+
+/// Per-connection state captured from the client handshake. Concurrent
+/// `tools/call` tasks each run against a snapshot of it.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ServerSession {
+    client: Option<ClientInfo>,
+    host_defaults: Option<AppliedHostDefaults>,
+    initialized: bool,
+    shutdown_requested: bool,
+    unknown: UnknownMethodCounts,
+    /// Where server-initiated notifications (`notifications/progress`) go;
+    /// the transport writes them out ahead of the reply they belong to.
+    notifications: Option<mpsc::UnboundedSender<Value>>,
+    /// Packs from `resources/subscribe`, told when the source watcher marks
+    /// their refs stale.
+    subscriptions: ResourceSubscriptions,
+    /// Transport session the replay journal is scoped to; `None` on stdio,
+    /// where the connection is one client and replays must survive its
+    /// reconnects.
+    replay_scope: Option<String>,
+}
+
+/// Methods this server does not implement, counted per name. Unknown
+/// notifications (`notifications/cancelled`, ...) are dropped silently;
+/// unknown requests still get `-32601`. Exposed by `server stats`.
+#[derive(Debug, Default, Clone, Serialize)]
+struct UnknownMethodCounts {
+    notifications: BTreeMap<String, u64>,
+    requests: BTreeMap<String, u64>,
+}
+
+impl UnknownMethodCounts {
+    fn record(&mut self, method: &str, is_notification: bool) {
+        let counts = if is_notification {
+            &mut self.notifications
+        } else {
+            &mut self.requests
+        };
+        let key = if counts.contains_key(method) || counts.len() < MAX_TRACKED_METHODS {
+            method
+        } else {
+            OTHER_METHODS
+        };
+        let count = counts.entry(key.to_string()).or_default();
+        *count += 1;
+        if *count == 1 {
+            if is_notification {
+                tracing::debug!("ignoring unknown notification '{}'", method);
+            } else {
+                tracing::debug!("rejecting unknown method '{}'", method);
+            }
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.notifications
+            .values()
+            .chain(self.requests.values())
+            .sum()
+    }
+}
+
+impl ServerContext {
+    pub(crate) fn new(
+        input_uc: Arc<InputUseCases>,
+        output_uc: Arc<OutputUseCases>,
+        replay_journal: Arc<dyn ReplayJournalPort>,
+        backup: Arc<dyn BackupPort>,
+        saved_filters: Arc<dyn SavedFilterPort>,
+    ) -> Self {
+        Self {
+            input_uc,
+            output_uc,
+            replay_journal,
+            backup,
+            saved_filters,
+            host_defaults: HostDefaultsConfig::from_env(),
+            tasks: TaskSupervisor::default(),
+            metrics: ServerMetrics::default(),
+            health_probe: None,
+            source_watch: None,
+            continuations: OutputContinuations::default(),
+            replay_locks: ReplayLocks::default(),
+        }
+    }
+
+    pub(crate) fn with_tasks(mut self, tasks: TaskSupervisor) -> Self {
+        self.tasks = tasks;
+        self
+    }
+
+    pub(crate) fn with_metrics(mut self, metrics: ServerMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub(crate) fn with_health_probe(mut self, probe: Arc<dyn HealthProbePort>) -> Self {
+        self.health_probe = Some(probe);
+        self
+    }
+
+    pub(crate) fn with_source_watch(mut self, watch: Arc<SourceWatch>) -> Self {
+        self.source_watch = Some(watch);
+        self
+    }
+
+    /// Stale-ref batches for one session; `None` without a source watcher.
+    pub(crate) fn stale_feed(&self) -> Option<broadcast::Receiver<StaleRefs>> {
+        self.source_watch.as_ref().map(|watch| watch.subscribe())
+    }
+}
+
+impl ServerSession {
+    pub(crate) fn set_notifications(&mut self, sender: Option<mpsc::UnboundedSender<Value>>) {
+        self.notifications = sender;
+    }
+
+    #[cfg(feature = "http")]
+    pub(crate) fn subscriptions(&self) -> &ResourceSubscriptions {
+        &self.subscriptions
+    }
+
+    #[cfg(feature = "http")]
+    pub(crate) fn with_replay_scope(mut self, scope: String) -> Self {
+        self.replay_scope = Some(scope);
+        self
+    }
+
+    /// Whose calls a replay key matches: the transport session when there is
+    /// one, else the client named in `initialize`.
+    fn replay_scope(&self) -> String {
+        match (&self.replay_scope, &self.client) {
+            (Some(scope), _) => scope.clone(),
+            (None, Some(client)) => format!("client:{}", client.name),
+            (None, None) => String::new(),
+        }
+    }
+
+    fn on_initialize(&mut self, params: Option<&Value>, config: &HostDefaultsConfig) {
+        self.client = ClientInfo::from_initialize_params(params);
+        self.host_defaults = self.client.as_ref().and_then(|client| {
+            config
+                .for_client(client)
+                .map(|defaults| AppliedHostDefaults {
+                    host: client.name.clone(),
+                    defaults: defaults.clone(),
+                })
+        });
+        if let Some(client) = &self.client {
+            tracing::info!(
+                "client: {} {} (host defaults: {})",
+                client.name,
+                client.version.as_deref().unwrap_or("unknown"),
+                if self.host_defaults.is_some() {
+                    "applied"
+                } else {
+                    "none"
+                }
+            );
+        }
+    }
+}
+
+/// Runs one MCP session over `input`/`output` until EOF or `exit`: stdin and
+/// stdout for [`crate::service::ContextPackService::serve_stdio`], in-memory
+/// buffers under the fuzz targets.
+pub(crate) async fn serve_connection<R, W>(
+    input: R,
+    output: W,
+    ctx: &ServerContext,
+) -> anyhow::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut session = ServerSession::default();
+    let (notifications, mut pending) = mpsc::unbounded_channel();
+    session.set_notifications(Some(notifications));
+    let mut writer = BufWriter::new(output);
     let init_timeout = initialize_timeout();
-    let mut initialized = false;
     let init_deadline = tokio::time::Instant::now() + init_timeout;
     let mut response_mode: Option<TransportMode> = None;
+    let tracer = FrameTracer::from_env();
+
+    // `tools/call` requests run on their own tasks, at most
+    // `max_concurrent_calls()` at a time; their replies come back through
+    // `replies` so only this loop ever writes. A frame is read only once a
+    // permit is in hand, and a call takes it into its task, so a client
+    // that outpaces the limit waits unread instead of piling parsed
+    // requests up in memory.
+    let shared_ctx = Arc::new(ctx.clone());
+    let limiter = Arc::new(Semaphore::new(max_concurrent_calls()));
+    let mut permit: Option<OwnedSemaphorePermit> = None;
+    let (reply_sender, mut replies) = mpsc::unbounded_channel::<(Reply, Instant)>();
+    let mut in_flight = JoinSet::new();
+    let mut next_frame = Box::pin(read_frame(BufReader::new(input)));
+    let mut stale_feed = ctx.stale_feed();
 
     loop {
-        let read_result = if initialized {
-            read_next_message(&mut reader, MAX_FRAME_BYTES).await
-        } else {
-            let now = tokio::time::Instant::now();
-            if now >= init_deadline {
+        let deadline = (!session.initialized).then_some(init_deadline);
+        // Queued notifications go out before replies, so a call's progress
+        // always precedes its result.
+        let event = tokio::select! {
+            biased;
+            Some(note) = pending.recv() => Inbound::Notification(note),
+            Some((reply, received_at)) = replies.recv() => Inbound::Reply(reply, received_at),
+            Some(joined) = in_flight.join_next(), if !in_flight.is_empty() => Inbound::Finished(joined),
+            stale = next_stale(&mut stale_feed) => Inbound::Stale(stale),
+            acquired = limiter.clone().acquire_owned(), if permit.is_none() => {
+                permit = acquired.ok();
+                continue;
+            }
+            _ = tokio::time::sleep_until(deadline.unwrap_or(init_deadline)), if deadline.is_some() => {
                 return Err(anyhow::anyhow!(
                     "no initialize received within {:?}; closing server",
                     init_timeout
                 ));
             }
-            match tokio::time::timeout(
-                init_deadline.saturating_duration_since(now),
-                read_next_message(&mut reader, MAX_FRAME_BYTES),
-            )
-            .await
-            {
-                Ok(result) => result,
-                Err(_) => {
-                    return Err(anyhow::anyhow!(
-                        "no initialize received within {:?}; closing server",
-                        init_timeout
-                    ));
+            (reader, read_result) = &mut next_frame, if permit.is_some() => Inbound::Frame(reader, read_result),
+        };
+        let read_result = match event {
+            Inbound::Notification(note) => {
+                let mode = response_mode.unwrap_or(TransportMode::Framed);
+                respond(&mut writer, &note, mode, &tracer, Instant::now()).await?;
+                continue;
+            }
+            Inbound::Reply(reply, received_at) => {
+                let mode = response_mode.unwrap_or(TransportMode::Framed);
+                respond(&mut writer, &reply, mode, &tracer, received_at).await?;
+                continue;
+            }
+            Inbound::Finished(joined) => {
+                if let Err(e) = joined {
+                    tracing::warn!("tools/call task failed without a reply: {}", e);
+                }
+                continue;
+            }
+            Inbound::Stale(stale) => {
+                if let Some(note) = session.subscriptions.update_for(&stale) {
+                    let mode = response_mode.unwrap_or(TransportMode::Framed);
+                    respond(&mut writer, &note, mode, &tracer, Instant::now()).await?;
                 }
+                continue;
+            }
+            Inbound::Frame(reader, read_result) => {
+                next_frame = Box::pin(read_frame(reader));
+                read_result
             }
         };
 
@@ -91,6 +375,8 @@ pub async fn start_mcp_server(
                 continue;
             }
         };
+        let received_at = Instant::now();
+        tracer.inbound(&raw, mode);
         if response_mode.is_none() {
             response_mode = Some(mode);
         }
@@ -98,86 +384,294 @@ pub async fn start_mcp_server(
         if raw.trim().is_empty() {
             continue;
         }
+        let mode = response_mode.unwrap_or(mode);
 
-        let req: RpcRequest = match serde_json::from_str(&raw) {
-            Ok(r) => r,
-            Err(e) => {
-                let envelope =
-                    RpcEnvelope::rpc_error(Value::Null, -32700, format!("parse error: {}", e));
-                write_response(&mut writer, &envelope, response_mode.unwrap_or(mode)).await?;
-                continue;
+        if session.initialized && !session.shutdown_requested && is_tool_call(&raw) {
+            let ctx = shared_ctx.clone();
+            let mut snapshot = session.clone();
+            let call_permit = permit.take();
+            let reply_sender = reply_sender.clone();
+            in_flight.spawn(async move {
+                let _permit = call_permit;
+                let (reply, _) = handle_frame(&raw, &ctx, &mut snapshot).await;
+                if let Some(reply) = reply {
+                    let _ = reply_sender.send((reply, received_at));
+                }
+            });
+            continue;
+        }
+
+        // Everything else (lifecycle, listings, batches) runs in order on
+        // this loop; output from running calls keeps flowing meanwhile, and
+        // this frame's notifications go out before its reply.
+        let (reply, exit) = {
+            let frame = handle_frame(&raw, ctx, &mut session);
+            tokio::pin!(frame);
+            loop {
+                tokio::select! {
+                    biased;
+                    done = &mut frame => break done,
+                    Some(note) = pending.recv() => {
+                        respond(&mut writer, &note, mode, &tracer, received_at).await?;
+                    }
+                    Some((reply, at)) = replies.recv() => {
+                        respond(&mut writer, &reply, mode, &tracer, at).await?;
+                    }
+                }
             }
         };
+        while let Ok(note) = pending.try_recv() {
+            respond(&mut writer, &note, mode, &tracer, received_at).await?;
+        }
+        if let Some(reply) = reply {
+            respond(&mut writer, &reply, mode, &tracer, received_at).await?;
+        }
+        if exit {
+            break;
+        }
+    }
 
-        if req.method == "initialize" && !initialized {
-            initialized = true;
+    // Calls still running finish and answer before the connection closes.
+    while let Some(joined) = in_flight.join_next().await {
+        if let Err(e) = joined {
+            tracing::warn!("tools/call task failed without a reply: {}", e);
         }
+    }
+    let mode = response_mode.unwrap_or(TransportMode::Framed);
+    while let Ok(note) = pending.try_recv() {
+        respond(&mut writer, &note, mode, &tracer, Instant::now()).await?;
+    }
+    while let Ok((reply, received_at)) = replies.try_recv() {
+        respond(&mut writer, &reply, mode, &tracer, received_at).await?;
+    }
+
+    Ok(())
+}
 
-        let request_id = req.id.clone().unwrap_or(Value::Null);
-        let is_notification = req.id.is_none();
-        if req.method == "shutdown" {
-            shutdown_requested = true;
-            if !is_notification {
-                let envelope = RpcEnvelope::success(request_id, json!(null));
-                write_response(&mut writer, &envelope, response_mode.unwrap_or(mode)).await?;
+/// What woke the connection loop.
+enum Inbound<R> {
+    Frame(
+        BufReader<R>,
+        anyhow::Result<Option<(String, TransportMode)>>,
+    ),
+    Notification(Value),
+    Reply(Reply, Instant),
+    Finished(Result<(), tokio::task::JoinError>),
+    Stale(StaleRefs),
+}
+
+/// Next batch of refs the source watcher marked stale; pends forever without
+/// a watcher. A session that falls behind skips the missed batches: they
+/// stay visible in `server stats`.
+pub(crate) async fn next_stale(feed: &mut Option<broadcast::Receiver<StaleRefs>>) -> StaleRefs {
+    loop {
+        let Some(receiver) = feed else {
+            return std::future::pending().await;
+        };
+        match receiver.recv().await {
+            Ok(stale) => return stale,
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                tracing::warn!("session skipped {} stale-ref update(s)", missed);
             }
-            continue;
+            Err(broadcast::error::RecvError::Closed) => *feed = None,
         }
+    }
+}
 
-        if req.method == "exit" {
-            if !is_notification {
-                let envelope = RpcEnvelope::success(request_id, json!(null));
-                write_response(&mut writer, &envelope, response_mode.unwrap_or(mode)).await?;
-            }
-            break;
+/// Reads one frame, handing the reader back so the pending read can live
+/// across loop iterations without being cancelled mid-frame.
+async fn read_frame<R: AsyncRead + Unpin>(
+    mut reader: BufReader<R>,
+) -> (
+    BufReader<R>,
+    anyhow::Result<Option<(String, TransportMode)>>,
+) {
+    let result = read_next_message(&mut reader, MAX_FRAME_BYTES).await;
+    (reader, result)
+}
+
+/// A single `tools/call` request (not a notification, not in a batch): the
+/// only frames run off the connection loop.
+fn is_tool_call(raw: &str) -> bool {
+    serde_json::from_str::<RpcRequest>(raw).is_ok_and(|req| {
+        req.method == "tools/call" && req.id.as_ref().is_some_and(|id| !id.is_null())
+    })
+}
+
+/// Reply to one inbound message: a single envelope, or an array for a batch.
+#[derive(Debug, Serialize)]
+pub(crate) struct Reply(ReplyBody);
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ReplyBody {
+    Single(RpcEnvelope),
+    Batch(Vec<RpcEnvelope>),
+}
+
+/// Handles one inbound message (a request, a notification, or a batch of
+/// them) independent of transport. Returns the reply, if any, and whether the
+/// session should stop reading.
+pub(crate) async fn handle_frame(
+    raw: &str,
+    ctx: &ServerContext,
+    session: &mut ServerSession,
+) -> (Option<Reply>, bool) {
+    let message: Value = match serde_json::from_str(raw) {
+        Ok(message) => message,
+        Err(e) => {
+            let envelope =
+                RpcEnvelope::rpc_error(Value::Null, -32700, format!("parse error: {}", e));
+            return (Some(Reply(ReplyBody::Single(envelope))), false);
         }
+    };
 
-        if shutdown_requested {
-            if !is_notification {
-                let envelope = RpcEnvelope::rpc_error(
-                    request_id,
-                    -32000,
-                    "server is shut down; only 'exit' is accepted",
-                );
-                write_response(&mut writer, &envelope, response_mode.unwrap_or(mode)).await?;
+    match message {
+        Value::Array(entries) if entries.is_empty() => {
+            let envelope =
+                RpcEnvelope::rpc_error(Value::Null, -32600, "invalid request: empty batch");
+            (Some(Reply(ReplyBody::Single(envelope))), false)
+        }
+        // Batch: entries run in order; notifications get no reply, and an
+        // all-notification batch gets no response at all.
+        Value::Array(entries) => {
+            let mut replies = Vec::with_capacity(entries.len());
+            let mut exit = false;
+            for entry in entries {
+                let (reply, stop) = match serde_json::from_value::<RpcRequest>(entry) {
+                    Ok(req) => dispatch(&req, ctx, session).await,
+                    Err(e) => (
+                        Some(RpcEnvelope::rpc_error(
+                            Value::Null,
+                            -32600,
+                            format!("invalid request: {}", e),
+                        )),
+                        false,
+                    ),
+                };
+                replies.extend(reply);
+                if stop {
+                    exit = true;
+                    break;
+                }
             }
-            continue;
+            let reply = (!replies.is_empty()).then_some(Reply(ReplyBody::Batch(replies)));
+            (reply, exit)
         }
-
-        if let Some(envelope) = handle_request(&req, &input_uc, &output_uc).await {
-            write_response(&mut writer, &envelope, response_mode.unwrap_or(mode)).await?;
+        message => {
+            let (reply, exit) = match serde_json::from_value::<RpcRequest>(message) {
+                Ok(req) => dispatch(&req, ctx, session).await,
+                Err(e) => (
+                    Some(RpcEnvelope::rpc_error(
+                        Value::Null,
+                        -32700,
+                        format!("parse error: {}", e),
+                    )),
+                    false,
+                ),
+            };
+            (
+                reply.map(|envelope| Reply(ReplyBody::Single(envelope))),
+                exit,
+            )
         }
     }
+}
 
+/// Runs one request through the connection lifecycle (`initialize`,
+/// `shutdown`, `exit`) and the method handlers. Returns the reply, if any, and
+/// whether the server should stop reading.
+async fn dispatch(
+    req: &RpcRequest,
+    ctx: &ServerContext,
+    session: &mut ServerSession,
+) -> (Option<RpcEnvelope>, bool) {
+    if req.method == "initialize" {
+        session.initialized = true;
+    }
+
+    let request_id = req.id.clone().unwrap_or(Value::Null);
+    let is_notification = req.id.is_none();
+    let ack = || (!is_notification).then(|| RpcEnvelope::success(request_id.clone(), json!(null)));
+    if req.method == "shutdown" {
+        session.shutdown_requested = true;
+        return (ack(), false);
+    }
+    if req.method == "exit" {
+        return (ack(), true);
+    }
+    if session.shutdown_requested {
+        let reply = (!is_notification).then(|| {
+            RpcEnvelope::rpc_error(
+                request_id.clone(),
+                -32000,
+                "server is shut down; only 'exit' is accepted",
+            )
+        });
+        return (reply, false);
+    }
+
+    (handle_request(req, ctx, session).await, false)
+}
+
+/// Writes one response frame (an envelope or a batch of them) and traces it.
+async fn respond<W: AsyncWrite + Unpin>(
+    writer: &mut BufWriter<W>,
+    body: &impl Serialize,
+    mode: TransportMode,
+    tracer: &FrameTracer,
+    received_at: Instant,
+) -> anyhow::Result<()> {
+    let bytes = write_response(writer, body, mode).await?;
+    tracer.outbound(body, mode, bytes, received_at);
     Ok(())
 }
 
 async fn handle_request(
     request: &RpcRequest,
-    input_uc: &InputUseCases,
-    output_uc: &OutputUseCases,
+    ctx: &ServerContext,
+    session: &mut ServerSession,
 ) -> Option<RpcEnvelope> {
     let id = request.id.clone().unwrap_or(Value::Null);
     let is_notification = request.id.is_none();
     let params = request.params.clone().unwrap_or(Value::Null);
 
     let envelope = match request.method.as_str() {
-        "initialize" => RpcEnvelope::success(
-            id.clone(),
-            json!({
-                "protocolVersion": initialize_protocol_version(request.params.as_ref()),
-                "capabilities": { "tools": { "listChanged": true } },
-                "serverInfo": {
-                    "name": "context-pack",
-                    "version": env!("CARGO_PKG_VERSION")
-                }
-            }),
-        ),
+        "initialize" => {
+            session.on_initialize(request.params.as_ref(), &ctx.host_defaults);
+            RpcEnvelope::success(
+                id.clone(),
+                json!({
+                    "protocolVersion": initialize_protocol_version(request.params.as_ref()),
+                    "capabilities": {
+                        "tools": { "listChanged": true },
+                        "resources": resources_capability(ctx),
+                        "prompts": { "listChanged": false }
+                    },
+                    "serverInfo": {
+                        "name": "context-pack",
+                        "version": env!("CARGO_PKG_VERSION")
+                    }
+                }),
+            )
+        }
         "ping" => RpcEnvelope::success(id.clone(), json!({})),
         "notifications/initialized" | "initialized" => {
             RpcEnvelope::success(id.clone(), json!(null))
         }
         "tools/list" => RpcEnvelope::success(id.clone(), tools_schema()),
+        "resources/list" => handle_resources_list(id.clone(), &ctx.output_uc).await,
+        "resources/read" => handle_resources_read(id.clone(), &params, &ctx.output_uc).await,
+        "resources/subscribe" | "resources/unsubscribe" if ctx.source_watch.is_some() => {
+            handle_resources_subscription(
+                id.clone(),
+                &params,
+                &session.subscriptions,
+                request.method == "resources/subscribe",
+            )
+        }
+        "prompts/list" => handle_prompts_list(id.clone(), &ctx.output_uc).await,
+        "prompts/get" => handle_prompts_get(id.clone(), &params, &ctx.output_uc).await,
         "tools/call" => {
             let tool_name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
             let args = params
@@ -186,29 +680,49 @@ async fn handle_request(
                 .unwrap_or_else(|| Value::Object(Default::default()));
             if !args.is_object() {
                 RpcEnvelope::rpc_error(id.clone(), -32602, "tool arguments must be an object")
+            } else if !matches!(tool_name, "input" | "output" | "server") {
+                RpcEnvelope::rpc_error(id.clone(), -32602, format!("unknown tool '{}'", tool_name))
             } else {
-                match tool_name {
-                    "input" => match handle_input_tool(&args, input_uc).await {
-                        Ok(v) => RpcEnvelope::success(id.clone(), v),
-                        Err(e) => domain_error_response(id.clone(), &e),
-                    },
-                    "output" => match handle_output_tool(&args, output_uc).await {
-                        Ok(v) => RpcEnvelope::success(id.clone(), v),
-                        Err(e) => domain_error_response(id.clone(), &e),
-                    },
-                    _ => RpcEnvelope::rpc_error(
-                        id.clone(),
-                        -32602,
-                        format!("unknown tool '{}'", tool_name),
-                    ),
+                let action = args
+                    .get("action")
+                    .and_then(Value::as_str)
+                    .unwrap_or("none")
+                    .to_string();
+                let started = Instant::now();
+                let progress_sink = params
+                    .get("_meta")
+                    .and_then(|meta| meta.get("progressToken"))
+                    .filter(|token| token.is_string() || token.is_i64() || token.is_u64())
+                    .zip(session.notifications.as_ref())
+                    .map(|(token, sender)| McpProgress {
+                        token: token.clone(),
+                        sender: sender.clone(),
+                    });
+                let call = call_tool(tool_name, &id, args, ctx, session);
+                let result = match progress_sink {
+                    Some(sink) => progress::scope(Arc::new(sink), call).await,
+                    None => call.await,
+                };
+                let outcome = match &result {
+                    Ok(v) => Ok(content_text_bytes(v)),
+                    Err(e) => Err(error_code(e)),
+                };
+                ctx.metrics
+                    .record(tool_name, &action, started.elapsed(), outcome);
+                match result {
+                    Ok(v) => RpcEnvelope::success(id.clone(), v),
+                    Err(e) => domain_error_response(id.clone(), &e),
                 }
             }
         }
-        _ => RpcEnvelope::rpc_error(
-            id.clone(),
-            -32601,
-            format!("method not found: '{}'", request.method),
-        ),
+        method => {
+            session.unknown.record(method, is_notification);
+            RpcEnvelope::rpc_error(
+                id.clone(),
+                -32601,
+                format!("method not found: '{}'", method),
+            )
+        }
     };
 
     if is_notification {
@@ -218,6 +732,102 @@ async fn handle_request(
     }
 }
 
+/// `subscribe` is only offered while a source watcher can send updates.
+fn resources_capability(ctx: &ServerContext) -> Value {
+    if ctx.source_watch.is_some() {
+        json!({ "subscribe": true, "listChanged": false })
+    } else {
+        json!({ "listChanged": false })
+    }
+}
+
+async fn call_tool(
+    tool_name: &str,
+    id: &Value,
+    args: Value,
+    ctx: &ServerContext,
+    session: &ServerSession,
+) -> Result<Value, DomainError> {
+    match tool_name {
+        "input" => {
+            handle_replayable_input(
+                id,
+                &with_client_actor(args, session.client.as_ref()),
+                ctx,
+                session,
+            )
+            .await
+        }
+        "output" => {
+            handle_output_tool(
+                &args,
+                &ctx.output_uc,
+                ctx.saved_filters.as_ref(),
+                session.host_defaults.as_ref(),
+                &ctx.continuations,
+            )
+            .await
+        }
+        _ => {
+            handle_server_tool(
+                &args,
+                ctx.backup.as_ref(),
+                &ctx.tasks,
+                &ctx.metrics,
+                ctx.health_probe.as_deref(),
+                ctx.source_watch.as_deref(),
+                &session.unknown,
+            )
+            .await
+        }
+    }
+}
+
+/// Sends `notifications/progress` for a `tools/call` that carried
+/// `_meta.progressToken`.
+struct McpProgress {
+    token: Value,
+    sender: mpsc::UnboundedSender<Value>,
+}
+
+impl ProgressPort for McpProgress {
+    fn report(&self, progress: u64, total: u64, message: &str) {
+        // The receiver is gone only when the connection is closing.
+        let _ = self.sender.send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": {
+                "progressToken": self.token,
+                "progress": progress,
+                "total": total,
+                "message": message,
+            }
+        }));
+    }
+}
+
+/// Size of a tool result's `content` text, as counted by `server stats`.
+fn content_text_bytes(result: &Value) -> usize {
+    result
+        .get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.get("text").and_then(Value::as_str))
+        .map(str::len)
+        .sum()
+}
+
+/// Defaults `actor` to the client name from `initialize`, so packs created
+/// by a host record an owner even when its calls don't name one.
+fn with_client_actor(mut args: Value, client: Option<&ClientInfo>) -> Value {
+    if let (Some(client), Some(obj)) = (client, args.as_object_mut()) {
+        obj.entry("actor")
+            .or_insert_with(|| Value::String(client.name.clone()));
+    }
+    args
+}
+
 fn initialize_protocol_version(request_params: Option<&Value>) -> &str {
     request_params
         .and_then(|value| value.get("protocolVersion"))
@@ -251,14 +861,9 @@ pub(super) fn tool_success(action: &str, payload: Value) -> Result<Value, Domain
     }))
 }
 
+/// Markdown output; `output` streams it through [`OutputContinuations`] when
+/// it does not fit in one frame.
 pub(super) fn tool_text_success(text: String) -> Result<Value, DomainError> {
-    if text.len() > MAX_FRAME_BYTES {
-        return Err(DomainError::InvalidData(format!(
-            "tool output too large: {} bytes (max {})",
-            text.len(),
-            MAX_FRAME_BYTES
-        )));
-    }
     Ok(json!({
         "content": [{
             "type": "text",
@@ -267,6 +872,22 @@ pub(super) fn tool_text_success(text: String) -> Result<Value, DomainError> {
     }))
 }
 
+/// Markdown text plus its machine-readable form, both as a second `text`
+/// entry (for clients that only read `content`) and as MCP `structuredContent`.
+pub(super) fn tool_text_with_structure(
+    text: String,
+    structured: Value,
+) -> Result<Value, DomainError> {
+    let encoded = serde_json::to_string(&structured)?;
+    Ok(json!({
+        "content": [
+            { "type": "text", "text": text },
+            { "type": "text", "text": encoded }
+        ],
+        "structuredContent": structured
+    }))
+}
+
 pub(super) fn pack_summary(pack: &Pack) -> Value {
     let now = chrono::Utc::now();
     let ttl_remaining_human = pack.ttl_remaining_human(now);
@@ -282,7 +903,9 @@ pub(super) fn pack_summary(pack: &Pack) -> Value {
         "ttl_remaining_seconds": pack.ttl_remaining_seconds(now),
         "ttl_remaining_human": ttl_remaining_human.clone(),
         "ttl_remaining": ttl_remaining_human,
-        "freshness_state": freshness_state
+        "freshness_state": freshness_state,
+        "sign_offs": pack.sign_offs.len(),
+        "owner": pack.owner
     })
 }
 
@@ -352,6 +975,98 @@ pub(super) fn freshness_opt(
     Ok(Some(raw.parse::<FreshnessState>()?))
 }
 
+/// Parses an optional array of non-empty strings.
+pub(super) fn str_list_opt(args: &Value, key: &str) -> Result<Option<Vec<String>>, DomainError> {
+    let Some(raw) = args.get(key) else {
+        return Ok(None);
+    };
+    let items = raw.as_array().ok_or_else(|| {
+        DomainError::InvalidData(format!("'{}' must be an array of strings", key))
+    })?;
+    items
+        .iter()
+        .map(|item| {
+            item.as_str()
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    DomainError::InvalidData(format!("'{}' must be an array of strings", key))
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+/// Saved filter names: 1-64 chars of `[a-z0-9_-]`.
+pub(super) fn req_filter_name(args: &Value) -> Result<String, DomainError> {
+    let name = str_opt(args, "filter")
+        .ok_or_else(|| DomainError::InvalidData("missing required field 'filter'".into()))?;
+    let valid = name.len() <= 64
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'-');
+    if !valid {
+        return Err(DomainError::InvalidData(format!(
+            "filter name '{}' must be 1-64 chars of [a-z0-9_-]",
+            name
+        )));
+    }
+    Ok(name)
+}
+
+/// Filter fields given directly on a call (`status`, `freshness`, `tags`, `query`).
+pub(super) fn filter_fields_from_args(args: &Value) -> Result<SavedFilter, DomainError> {
+    Ok(SavedFilter {
+        status: status_opt(args, "status")?,
+        freshness: freshness_opt(args, "freshness")?,
+        tags: str_list_opt(args, "tags")?.unwrap_or_default(),
+        query: str_opt(args, "query"),
+    })
+}
+
+/// List filter for `list` calls: the saved filter named by `filter`, if any,
+/// with fields given directly on the call taking precedence.
+pub(super) async fn list_filter_from_args(
+    args: &Value,
+    saved_filters: &dyn SavedFilterPort,
+) -> Result<ListFilter, DomainError> {
+    let explicit = filter_fields_from_args(args)?;
+    let base = match str_opt(args, "filter") {
+        Some(name) => {
+            let mut filters = saved_filters.list_filters().await?;
+            filters
+                .remove(&name)
+                .ok_or_else(|| DomainError::DetailedInvalidData {
+                    message: format!("unknown saved filter '{}'", name),
+                    details: json!({
+                        "field": "filter",
+                        "requested_filter": name,
+                        "available_filters": filters.keys().collect::<Vec<_>>(),
+                    }),
+                })?
+        }
+        None => SavedFilter::default(),
+    };
+    Ok(ListFilter {
+        status: explicit.status.or(base.status),
+        freshness: explicit.freshness.or(base.freshness),
+        query: explicit.query.or(base.query),
+        tags: if explicit.tags.is_empty() {
+            base.tags
+        } else {
+            explicit.tags
+        },
+        include_expired: args.get("include_expired").and_then(Value::as_bool),
+        archived: args
+            .get("archived")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        limit: usize_opt(args, "limit")?,
+        offset: usize_opt(args, "offset")?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -594,6 +1309,23 @@ mod tests {
         assert_eq!(parsed["code"], "pack_id_conflict");
     }
 
+    #[test]
+    fn test_domain_error_contract_for_cursor_version_unsupported() {
+        let envelope = domain_error_response(
+            Value::from(1),
+            &DomainError::CursorVersionUnsupported {
+                version: "v9".into(),
+                supported: vec!["v1".into(), "v2".into()],
+            },
+        );
+        let text = extract_content_text(&envelope);
+        let parsed: Value = serde_json::from_str(&text).expect("must be valid JSON");
+        assert_eq!(parsed["kind"], "validation");
+        assert_eq!(parsed["code"], "cursor_version_unsupported");
+        assert_eq!(parsed["details"]["token_version"], "v9");
+        assert_eq!(parsed["details"]["supported_versions"], json!(["v1", "v2"]));
+    }
+
     #[test]
     fn test_domain_error_contract_for_deserialize_error() {
         let envelope =
@@ -604,6 +1336,50 @@ mod tests {
         assert_eq!(parsed["code"], "deserialize_error");
     }
 
+    #[test]
+    fn test_domain_error_contract_for_forbidden_owner_mismatch() {
+        let envelope = domain_error_response(
+            Value::from(1),
+            &DomainError::Forbidden {
+                message: "pack 'p' is owned by 'agent-a', not 'agent-b'".into(),
+                owner: "agent-a".into(),
+                actor: Some("agent-b".into()),
+            },
+        );
+        let text = extract_content_text(&envelope);
+        let parsed: Value = serde_json::from_str(&text).expect("must be valid JSON");
+        assert_eq!(parsed["kind"], "forbidden");
+        assert_eq!(parsed["code"], "owner_mismatch");
+        assert_eq!(parsed["details"]["owner"], "agent-a");
+        assert_eq!(parsed["details"]["actor"], "agent-b");
+    }
+
+    #[test]
+    fn test_domain_error_contract_for_storage_read_only() {
+        let envelope = domain_error_response(
+            Value::from(1),
+            &DomainError::StorageReadOnly("'/packs' is not writable".into()),
+        );
+        let text = extract_content_text(&envelope);
+        let parsed: Value = serde_json::from_str(&text).expect("must be valid JSON");
+        assert_eq!(parsed["kind"], "storage_read_only");
+        assert_eq!(parsed["code"], "storage_read_only");
+        assert!(parsed["details"]["guidance"].is_string());
+    }
+
+    #[test]
+    fn test_domain_error_contract_for_decryption_failed() {
+        let envelope = domain_error_response(
+            Value::from(1),
+            &DomainError::DecryptionFailed("pack 'pk_a.json' is encrypted".into()),
+        );
+        let text = extract_content_text(&envelope);
+        let parsed: Value = serde_json::from_str(&text).expect("must be valid JSON");
+        assert_eq!(parsed["kind"], "decryption_failed");
+        assert_eq!(parsed["code"], "decryption_failed");
+        assert!(parsed["details"]["guidance"].is_string());
+    }
+
     #[test]
     fn test_output_format_parameter_is_rejected() {
         let args = json!({ "format": "json" });
@@ -0,0 +1,109 @@
+use serde_json::{json, Value};
+
+use crate::adapters::mcp_stdio::rpc::RpcEnvelope;
+use crate::adapters::mcp_stdio::MAX_FRAME_BYTES;
+use crate::app::output_usecases::{OutputProfile, OutputReadRequest, OutputUseCases};
+use crate::app::ports::ListFilter;
+use crate::domain::errors::DomainError;
+use crate::domain::models::Pack;
+use crate::domain::types::{LanguageTag, PackId, Status};
+
+/// `prompts/list`: one prompt per finalized, unexpired pack, named by pack id.
+pub(super) async fn handle_prompts_list(id: Value, output_uc: &OutputUseCases) -> RpcEnvelope {
+    let packs = match output_uc
+        .list_matching(ListFilter {
+            status: Some(Status::Finalized),
+            ..Default::default()
+        })
+        .await
+    {
+        Ok(packs) => packs,
+        Err(e) => return RpcEnvelope::rpc_error(id, -32603, e.to_string()),
+    };
+    let prompts: Vec<Value> = packs
+        .iter()
+        .map(|pack| {
+            let mut prompt = json!({
+                "name": pack.id.as_str(),
+                "title": prompt_title(pack),
+                "arguments": [{
+                    "name": "lang",
+                    "description": "Language tag for section descriptions (e.g. `ru`); defaults to the pack's own text.",
+                    "required": false
+                }]
+            });
+            if let Some(brief) = &pack.brief {
+                prompt["description"] = json!(brief);
+            }
+            prompt
+        })
+        .collect();
+    RpcEnvelope::success(id, json!({ "prompts": prompts }))
+}
+
+/// `prompts/get`: the pack's compact (orchestrator) render as one user message.
+pub(super) async fn handle_prompts_get(
+    id: Value,
+    params: &Value,
+    output_uc: &OutputUseCases,
+) -> RpcEnvelope {
+    let Some(name) = params.get("name").and_then(Value::as_str) else {
+        return RpcEnvelope::rpc_error(id, -32602, "'name' is required");
+    };
+    let lang = match params
+        .get("arguments")
+        .and_then(|args| args.get("lang"))
+        .and_then(Value::as_str)
+        .filter(|raw| !raw.trim().is_empty())
+        .map(LanguageTag::new)
+        .transpose()
+    {
+        Ok(lang) => lang,
+        Err(e) => return RpcEnvelope::rpc_error(id, -32602, e.to_string()),
+    };
+    let unknown = || format!("unknown prompt: '{}'", name);
+    if PackId::parse(name).is_err() {
+        return RpcEnvelope::rpc_error(id, -32602, unknown());
+    }
+    let request = OutputReadRequest {
+        status_filter: Some(Status::Finalized),
+        profile: Some(OutputProfile::Orchestrator),
+        lang,
+        ..Default::default()
+    };
+    let text = match output_uc.get_rendered_with_request(name, request).await {
+        Ok(text) => text,
+        Err(DomainError::NotFound(_) | DomainError::InvalidState(_)) => {
+            return RpcEnvelope::rpc_error(id, -32602, unknown())
+        }
+        Err(e) => return RpcEnvelope::rpc_error(id, -32603, e.to_string()),
+    };
+    if text.len() > MAX_FRAME_BYTES {
+        return RpcEnvelope::rpc_error(
+            id,
+            -32603,
+            format!(
+                "prompt too large: {} bytes (max {})",
+                text.len(),
+                MAX_FRAME_BYTES
+            ),
+        );
+    }
+    RpcEnvelope::success(
+        id,
+        json!({
+            "description": format!("Context pack {}", name),
+            "messages": [{
+                "role": "user",
+                "content": { "type": "text", "text": text }
+            }]
+        }),
+    )
+}
+
+fn prompt_title(pack: &Pack) -> String {
+    pack.title
+        .clone()
+        .or_else(|| pack.name.as_ref().map(|name| name.to_string()))
+        .unwrap_or_else(|| pack.id.to_string())
+}
@@ -0,0 +1,162 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::app::ports::ReplayKey;
+use crate::domain::errors::DomainError;
+use crate::domain::models::fnv1a_64;
+
+use super::{handle_input_tool, str_opt, to_json_text, ServerContext, ServerSession};
+
+/// Input actions whose calls are journaled when tagged with `idempotency_key`.
+const REPLAYABLE_ACTIONS: &[&str] = &[
+    "write",
+    "ttl",
+    "delete",
+    "move_section",
+    "merge",
+    "split",
+    "sign_off",
+    "set_links",
+    "add_link",
+    "rollback",
+    "repair_refs",
+    "upsert_attachment",
+    "delete_attachment",
+    "import",
+    "import_markdown",
+    "create_from_template",
+    "clone",
+];
+
+/// One lock per replay key, held from the journal lookup until the result is
+/// recorded, so two concurrent calls with the same key apply once. Shared by
+/// every session of a server run; clones share state. Servers on the same
+/// storage root don't share these locks.
+#[derive(Clone, Default)]
+pub(crate) struct ReplayLocks {
+    held: Arc<Mutex<HashMap<String, Weak<tokio::sync::Mutex<()>>>>>,
+}
+
+impl ReplayLocks {
+    fn lock_for(&self, key: &ReplayKey) -> Arc<tokio::sync::Mutex<()>> {
+        let name = format!("{}\0{}\0{}", key.scope, key.request_id, key.idempotency_key);
+        let mut held = self.held.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(lock) = held.get(&name).and_then(Weak::upgrade) {
+            return lock;
+        }
+        held.retain(|_, lock| lock.strong_count() > 0);
+        let lock = Arc::new(tokio::sync::Mutex::new(()));
+        held.insert(name, Arc::downgrade(&lock));
+        lock
+    }
+}
+
+/// Runs an input call, consulting the replay journal first when the client
+/// tagged a mutation with `idempotency_key`. A hit returns the recorded result
+/// (marked `_meta.replayed`) instead of applying the mutation a second time;
+/// the same key with different arguments is a `conflict`.
+pub(super) async fn handle_replayable_input(
+    id: &Value,
+    args: &Value,
+    ctx: &ServerContext,
+    session: &ServerSession,
+) -> Result<Value, DomainError> {
+    let input_uc = ctx.input_uc.as_ref();
+    let output_uc = ctx.output_uc.as_ref();
+    let saved_filters = ctx.saved_filters.as_ref();
+    let replay_journal = ctx.replay_journal.as_ref();
+    let Some(key) = replay_key(id, args, session) else {
+        return handle_input_tool(args, input_uc, output_uc, saved_filters).await;
+    };
+
+    let lock = ctx.replay_locks.lock_for(&key);
+    let _held = lock.lock().await;
+    match replay_journal.lookup(&key).await {
+        Ok(Some(mut recorded)) => {
+            if let Some(obj) = recorded.as_object_mut() {
+                obj.insert("_meta".into(), json!({ "replayed": true }));
+            }
+            return Ok(recorded);
+        }
+        Ok(None) => {}
+        Err(e @ DomainError::Conflict(_)) => return Err(e),
+        Err(e) => tracing::warn!("replay journal lookup failed: {e}"),
+    }
+
+    let result = handle_input_tool(args, input_uc, output_uc, saved_filters).await?;
+    if let Err(e) = replay_journal.record(&key, &result).await {
+        tracing::warn!("replay journal record failed: {e}");
+    }
+    Ok(result)
+}
+
+/// The journal key of a replayable call: scoped to the session's caller and
+/// carrying a hash of the arguments.
+fn replay_key(id: &Value, args: &Value, session: &ServerSession) -> Option<ReplayKey> {
+    let action = args.get("action").and_then(Value::as_str)?;
+    if !REPLAYABLE_ACTIONS.contains(&action) || id.is_null() {
+        return None;
+    }
+    let idempotency_key = str_opt(args, "idempotency_key")?;
+    // Object keys serialize sorted, so equal arguments hash equally.
+    let args_hash = format!("{:016x}", fnv1a_64(to_json_text(args).as_bytes()));
+    Some(ReplayKey {
+        scope: session.replay_scope(),
+        request_id: to_json_text(id),
+        idempotency_key,
+        args_hash,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_key_hashes_arguments_and_scopes_to_the_session() {
+        let args = json!({"action":"write","idempotency_key":"k","id":"pk_a"});
+        let stdio = ServerSession::default();
+        let http = ServerSession {
+            replay_scope: Some("http:abc".into()),
+            ..ServerSession::default()
+        };
+
+        let key = replay_key(&json!(1), &args, &stdio).unwrap();
+        let other_session = replay_key(&json!(1), &args, &http).unwrap();
+        assert_ne!(key.scope, other_session.scope);
+        assert_eq!(key.args_hash, other_session.args_hash);
+
+        let mut changed = args.clone();
+        changed["id"] = json!("pk_b");
+        let retried = replay_key(&json!(1), &changed, &stdio).unwrap();
+        assert!(key.same_call(&retried));
+        assert_ne!(key.args_hash, retried.args_hash);
+
+        assert!(replay_key(&Value::Null, &args, &stdio).is_none());
+        assert!(replay_key(
+            &json!(1),
+            &json!({"action":"list","idempotency_key":"k"}),
+            &stdio
+        )
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_locks_are_shared_per_key_and_dropped_when_released() {
+        let locks = ReplayLocks::default();
+        let key = replay_key(
+            &json!(1),
+            &json!({"action":"write","idempotency_key":"k"}),
+            &ServerSession::default(),
+        )
+        .unwrap();
+        let first = locks.lock_for(&key);
+        let guard = first.lock().await;
+        assert!(locks.lock_for(&key).try_lock().is_err());
+        drop(guard);
+        drop(first);
+        let _again = locks.lock_for(&key);
+        assert_eq!(locks.held.lock().unwrap().len(), 1);
+    }
+}
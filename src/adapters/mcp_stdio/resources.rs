@@ -0,0 +1,213 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde_json::{json, Value};
+
+use crate::adapters::mcp_stdio::rpc::RpcEnvelope;
+use crate::adapters::mcp_stdio::MAX_FRAME_BYTES;
+use crate::app::output_usecases::OutputUseCases;
+use crate::app::ports::ListFilter;
+use crate::app::source_watch::StaleRefs;
+use crate::domain::errors::DomainError;
+use crate::domain::types::{PackId, Status};
+
+pub(super) const RESOURCE_URI_SCHEME: &str = "context-pack://";
+const RESOURCE_MIME_TYPE: &str = "text/markdown";
+/// MCP's "resource not found" code.
+const RESOURCE_NOT_FOUND: i64 = -32002;
+
+/// `resources/list`: every finalized, unexpired pack as a `context-pack://<id>`
+/// resource.
+pub(super) async fn handle_resources_list(id: Value, output_uc: &OutputUseCases) -> RpcEnvelope {
+    let packs = match output_uc
+        .list_matching(ListFilter {
+            status: Some(Status::Finalized),
+            ..Default::default()
+        })
+        .await
+    {
+        Ok(packs) => packs,
+        Err(e) => return RpcEnvelope::rpc_error(id, -32603, e.to_string()),
+    };
+    let resources: Vec<Value> = packs
+        .iter()
+        .map(|pack| {
+            let mut resource = json!({
+                "uri": format!("{}{}", RESOURCE_URI_SCHEME, pack.id),
+                "name": pack
+                    .name
+                    .as_ref()
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| pack.id.to_string()),
+                "mimeType": RESOURCE_MIME_TYPE,
+            });
+            if let Some(title) = &pack.title {
+                resource["title"] = json!(title);
+            }
+            if let Some(brief) = &pack.brief {
+                resource["description"] = json!(brief);
+            }
+            resource
+        })
+        .collect();
+    RpcEnvelope::success(id, json!({ "resources": resources }))
+}
+
+/// `resources/read`: renders the pack behind a `context-pack://<id>` URI the
+/// same way `output read` does with default arguments.
+pub(super) async fn handle_resources_read(
+    id: Value,
+    params: &Value,
+    output_uc: &OutputUseCases,
+) -> RpcEnvelope {
+    let Some(uri) = params.get("uri").and_then(Value::as_str) else {
+        return RpcEnvelope::rpc_error(id, -32602, "'uri' is required");
+    };
+    let pack_id = match parse_resource_uri(uri) {
+        Ok(pack_id) => pack_id,
+        Err(e) => return RpcEnvelope::rpc_error(id, -32602, e.to_string()),
+    };
+    let text = match output_uc
+        .get_rendered(pack_id.as_str(), Some(Status::Finalized))
+        .await
+    {
+        Ok(text) => text,
+        Err(DomainError::NotFound(_) | DomainError::InvalidState(_)) => {
+            return RpcEnvelope::rpc_error(
+                id,
+                RESOURCE_NOT_FOUND,
+                format!("resource not found: '{}'", uri),
+            )
+        }
+        Err(e) => return RpcEnvelope::rpc_error(id, -32603, e.to_string()),
+    };
+    if text.len() > MAX_FRAME_BYTES {
+        return RpcEnvelope::rpc_error(
+            id,
+            -32603,
+            format!(
+                "resource too large: {} bytes (max {})",
+                text.len(),
+                MAX_FRAME_BYTES
+            ),
+        );
+    }
+    RpcEnvelope::success(
+        id,
+        json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": RESOURCE_MIME_TYPE,
+                "text": text
+            }]
+        }),
+    )
+}
+
+/// Pack ids a session subscribed to. Snapshots of the session share one
+/// set, so a transport can check it while the session handles a message.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ResourceSubscriptions(Arc<Mutex<BTreeSet<String>>>);
+
+impl ResourceSubscriptions {
+    /// `notifications/resources/updated` for `stale`, if its pack is
+    /// subscribed; `_meta` names the refs and when the first change was seen.
+    pub(crate) fn update_for(&self, stale: &StaleRefs) -> Option<Value> {
+        self.ids().contains(stale.pack_id.as_str()).then(|| {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/resources/updated",
+                "params": {
+                    "uri": format!("{}{}", RESOURCE_URI_SCHEME, stale.pack_id),
+                    "_meta": {
+                        "stale_refs": stale.ref_keys,
+                        "since": stale.since,
+                    }
+                }
+            })
+        })
+    }
+
+    fn ids(&self) -> MutexGuard<'_, BTreeSet<String>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// `resources/subscribe` and `resources/unsubscribe`: adds or drops the pack
+/// behind `uri` from the session's subscriptions. Any pack may be subscribed,
+/// not only listed ones; unknown ids simply never get updates.
+pub(super) fn handle_resources_subscription(
+    id: Value,
+    params: &Value,
+    subscriptions: &ResourceSubscriptions,
+    subscribe: bool,
+) -> RpcEnvelope {
+    let Some(uri) = params.get("uri").and_then(Value::as_str) else {
+        return RpcEnvelope::rpc_error(id, -32602, "'uri' is required");
+    };
+    let pack_id = match parse_resource_uri(uri) {
+        Ok(pack_id) => pack_id,
+        Err(e) => return RpcEnvelope::rpc_error(id, -32602, e.to_string()),
+    };
+    if subscribe {
+        subscriptions.ids().insert(pack_id.to_string());
+    } else {
+        subscriptions.ids().remove(pack_id.as_str());
+    }
+    RpcEnvelope::success(id, json!({}))
+}
+
+fn parse_resource_uri(uri: &str) -> Result<PackId, DomainError> {
+    let raw = uri
+        .trim()
+        .strip_prefix(RESOURCE_URI_SCHEME)
+        .ok_or_else(|| {
+            DomainError::InvalidData(format!(
+                "resource uri must start with '{}'",
+                RESOURCE_URI_SCHEME
+            ))
+        })?;
+    PackId::parse(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resource_uri_requires_scheme_and_pack_id() {
+        let id = parse_resource_uri("context-pack://pk_abcdefgh").unwrap();
+        assert_eq!(id.as_str(), "pk_abcdefgh");
+        assert!(parse_resource_uri("pk_abcdefgh").is_err());
+        assert!(parse_resource_uri("file://pk_abcdefgh").is_err());
+        assert!(parse_resource_uri("context-pack://not-an-id").is_err());
+    }
+
+    #[test]
+    fn test_updates_reach_only_subscribed_packs() {
+        let subscriptions = ResourceSubscriptions::default();
+        let stale = StaleRefs {
+            pack_id: PackId::parse("pk_abcdefgh").unwrap(),
+            ref_keys: vec!["login".into()],
+            since: chrono::Utc::now(),
+        };
+        assert!(subscriptions.update_for(&stale).is_none());
+
+        let params = json!({ "uri": "context-pack://pk_abcdefgh" });
+        let reply = handle_resources_subscription(json!(1), &params, &subscriptions, true);
+        assert!(reply.result.is_some());
+        let update = subscriptions.clone().update_for(&stale).unwrap();
+        assert_eq!(update["method"], "notifications/resources/updated");
+        assert_eq!(update["params"]["uri"], "context-pack://pk_abcdefgh");
+        assert_eq!(update["params"]["_meta"]["stale_refs"], json!(["login"]));
+
+        let bad = json!({ "uri": "pk_abcdefgh" });
+        let reply = handle_resources_subscription(json!(2), &bad, &subscriptions, true);
+        assert_eq!(reply.error.map(|e| e.code), Some(-32602));
+
+        handle_resources_subscription(json!(3), &params, &subscriptions, false);
+        assert!(subscriptions.update_for(&stale).is_none());
+    }
+}
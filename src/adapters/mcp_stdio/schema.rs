@@ -3,89 +3,295 @@ use serde_json::{json, Value};
 pub(super) fn tools_schema() -> Value {
     json!({
         "tools": [
+            input_tool_schema(),
+            output_tool_schema(),
             {
-                "name": "input",
-                "description": "Manage context packs with v3 actions: list/get/write/ttl/delete.",
+                "name": "server",
+                "description": "Operator actions: backup (tar archive of the pack store under the repo lock; restore with `mcp-context-pack restore <archive>`), stats (tool calls, errors by code, latency and response size per action since the server started, plus this session's counts of unknown methods and notifications and, with the source watcher on, the refs marked stale by source changes), health (live checks that storage is writable, the repo lock is obtainable and source roots are readable, plus the state of the background purge/sync loops; status=unhealthy when a check fails, degraded after repeated loop failures).",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
                         "action": {
                             "type": "string",
-                            "description": "Operation to perform",
-                            "enum": ["list", "get", "write", "ttl", "delete"]
-                        },
-                        "id": { "type": "string", "description": "Pack ID" },
-                        "name": { "type": "string", "description": "Pack name (alternative to id)" },
-                        "ttl_minutes": { "type": "integer", "description": "TTL from now in minutes (action=ttl(set))." },
-                        "extend_minutes": { "type": "integer", "description": "Extend existing TTL by this many minutes (action=ttl)." },
-                        "expected_revision": { "type": "integer", "description": "Required for update writes and ttl actions." },
-                        "validate_only": {
-                            "type": "boolean",
-                            "description": "When true, input.write validates document and returns diagnostics without persistence."
-                        },
-                        "document": {
-                            "type": "object",
-                            "description": "Full-replace snapshot payload for action=write.",
-                            "properties": {
-                                "name": { "type": "string", "description": "Optional pack name (new pack only, immutable for updates)." },
-                                "title": { "type": "string" },
-                                "brief": { "type": "string", "description": "Short description of the pack" },
-                                "tags": { "type": "array", "items": { "type": "string" } },
-                                "ttl_minutes": { "type": "integer", "description": "Optional TTL override from now in minutes." },
-                                "status": { "type": "string", "enum": ["draft", "finalized"] },
-                                "sections": {
-                                    "type": "array",
-                                    "description": "Full list of sections (each section can include refs and diagrams)."
-                                }
-                            }
-                        },
-                        "status": { "type": "string", "enum": ["draft", "finalized"] },
-                        "freshness": {
-                            "type": "string",
-                            "enum": ["fresh", "expiring_soon", "expired"],
-                            "description": "Optional list filter by freshness state."
-                        },
-                        "query": { "type": "string", "description": "Text search for list" },
-                        "limit": { "type": "integer" },
-                        "offset": { "type": "integer" }
-                    }
-                }
-            },
-            {
-                "name": "output",
-                "description": "Render v3 output actions: list/read.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "action": {
-                            "type": "string",
-                            "enum": ["list", "read"]
-                        },
-                        "id": { "type": "string", "description": "Pack ID" },
-                        "name": { "type": "string", "description": "Pack name" },
-                        "status": {
-                            "type": "string",
-                            "enum": ["draft", "finalized"],
-                            "description": "Optional status filter (for list and read)"
-                        },
-                        "freshness": {
-                            "type": "string",
-                            "enum": ["fresh", "expiring_soon", "expired"],
-                            "description": "Optional freshness filter for list."
-                        },
-                        "profile": {
-                            "type": "string",
-                            "enum": ["orchestrator", "reviewer", "executor"],
-                            "description": "Read profile defaults: orchestrator (compact bounded), reviewer (full evidence), executor (actionable compact)."
-                        },
-                        "query": { "type": "string", "description": "Optional text search for list" },
-                        "page_token": { "type": "string", "description": "Opaque page token returned by output read paging metadata." },
-                        "contains": { "type": "string", "description": "Optional substring filter applied to rendered chunks." },
-                        "limit": { "type": "integer" },
-                        "offset": { "type": "integer" }
-                    }
+                            "enum": ["backup", "stats", "health"]
+                        }
+                    },
+                    "required": ["action"]
                 }
             }
         ]
     })
 }
+
+/// Split out of [`tools_schema`] to stay under the `json!` recursion limit.
+fn input_tool_schema() -> Value {
+    let mut schema = json!({
+        "name": "input",
+        "description": "Manage context packs with v3 actions: list/get/lint/validate/write/estimate/ttl/delete/prepare_delete/move_section/merge/split/sign_off/set_links/add_link/rollback/repair_refs/upsert_attachment/delete_attachment/export/import/import_markdown/create_from_template/clone/diagram_history/audit/save_filter/delete_filter. validate runs every finalize check (required sections, qa verdict, sign-offs, ref reachability, link targets) on the stored pack without changing it and reports them all in the finalize_validation shape plus `ready`. Deleting a finalized pack needs the confirm_token from prepare_delete; rollback restores a prior revision's content from the store's history journal; repair_refs re-anchors stale or drifted refs by finding their recorded excerpt in the file again; upsert_attachment/delete_attachment keep small text artifacts (logs, JSON evidence) inline in a section; export/import move a pack between stores as one JSON bundle; import_markdown creates a draft pack from a structured markdown document; create_from_template starts a draft with the sections the finalize gate expects; clone copies a pack (or some of its sections) into a new draft; merge folds every section of another pack into this one; set_links/add_link record typed relations (parent, supersedes, depends_on) to other packs; audit returns the last entries of the append-only audit log for a pack (op, revision before/after, actor), deleted packs included.",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "description": "Operation to perform",
+                    "enum": [
+                        "list",
+                        "get",
+                        "lint",
+                        "validate",
+                        "write",
+                        "estimate",
+                        "ttl",
+                        "delete",
+                        "prepare_delete",
+                        "move_section",
+                        "merge",
+                        "split",
+                        "sign_off",
+                        "set_links",
+                        "add_link",
+                        "rollback",
+                        "repair_refs",
+                        "upsert_attachment",
+                        "delete_attachment",
+                        "export",
+                        "import",
+                        "import_markdown",
+                        "create_from_template",
+                        "clone",
+                        "diagram_history",
+                        "audit",
+                        "save_filter",
+                        "delete_filter"
+                    ]
+                },
+                "id": { "type": "string", "description": "Pack ID" },
+                "name": { "type": "string", "description": "Pack name (alternative to id)" },
+                "ttl_minutes": { "type": "integer", "description": "TTL from now in minutes (action=ttl(set), action=import/import_markdown/create_from_template/clone)." },
+                "extend_minutes": { "type": "integer", "description": "Extend existing TTL by this many minutes (action=ttl)." },
+                "sliding_ttl": { "type": "boolean", "description": "action=ttl: true makes every successful output read keep the pack alive for the server's sliding window (CONTEXT_PACK_SLIDING_TTL_MINUTES); false turns it off. Use instead of ttl_minutes/extend_minutes." },
+                "sliding_ttl_minutes": { "type": "integer", "minimum": 1, "description": "action=ttl: turns sliding_ttl on with this pack's own window in minutes instead of the server's CONTEXT_PACK_SLIDING_TTL_MINUTES; sliding_ttl=false clears it. Use instead of ttl_minutes/extend_minutes/sliding_ttl/pin." },
+                "pin": { "type": "boolean", "description": "action=ttl: true pins the pack so it never expires (freshness_state=pinned); false releases the pin and applies the create-time TTL from now. Use instead of ttl_minutes/extend_minutes/sliding_ttl." },
+                "expected_revision": { "type": "integer", "description": "Required for update writes, ttl, merge (target pack), split, sign_off, set_links/add_link, rollback, repair_refs, upsert_attachment/delete_attachment and move_section (source pack)." },
+                "idempotency_key": {
+                    "type": "string",
+                    "description": "Optional client key for write/ttl/delete/move_section/merge/split/sign_off/set_links/add_link/rollback/repair_refs/upsert_attachment/delete_attachment/import/import_markdown/create_from_template/clone. Replaying the same request id + key within the replay window returns the original result instead of re-applying."
+                },
+                "reason": { "type": "string", "description": "Optional note on why this write/ttl/delete/move_section/merge/split/sign_off/set_links/add_link/rollback/repair_refs/upsert_attachment/delete_attachment/import/import_markdown/create_from_template/clone happens (max 500 chars). Stored with the revision it produces and reported as `produced_by` when another writer hits a revision conflict on it." },
+                "actor": { "type": "string", "description": "Caller identity (max 128 chars); defaults to the MCP clientInfo name. Creates record it as the pack's `owner`; with CONTEXT_PACK_OWNERSHIP=enforce, mutations of a pack owned by another actor fail with kind=forbidden (sign_off is exempt)." },
+                "force": { "type": "boolean", "description": "Mutate a pack owned by another actor anyway (CONTEXT_PACK_OWNERSHIP=enforce)." },
+                "confirm_token": { "type": "string", "description": "action=delete: single-use token from prepare_delete; required for finalized packs, expires after 5 minutes." },
+                "snapshot_excerpts": {
+                    "type": "boolean",
+                    "description": "action=write/estimate: store each ref's current excerpt text in the pack so output read can still show it if the source changes or disappears (labeled as snapshotted)."
+                },
+                "validate_only": {
+                    "type": "boolean",
+                    "description": "When true, input.write (document or ops)/import_markdown/create_from_template validates the document or ops (including finalize checks when the result is finalized) and returns diagnostics without persistence; input.repair_refs reports the ranges it would set without saving them."
+                },
+                "include_preview": {
+                    "type": "boolean",
+                    "description": "action=write: also return `preview`, the first page `output read` gives the orchestrator profile for the stored pack, so the author can check the handoff without another call. Ignored with validate_only."
+                },
+                "document": write_document_schema(),
+                "ops": write_ops_schema(),
+                "status": { "type": "string", "enum": ["draft", "finalized"] },
+                "freshness": {
+                    "type": "string",
+                    "enum": ["fresh", "expiring_soon", "expired", "pinned"],
+                    "description": "Optional list filter by freshness state."
+                },
+                "query": { "type": "string", "description": "Text search for list" },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "List filter: packs carrying all of these tags (case-insensitive). action=import_markdown/create_from_template: tags for the new pack."
+                },
+                "filter": {
+                    "type": "string",
+                    "description": "Saved filter name ([a-z0-9_-]{1,64}). action=list applies it (explicit status/freshness/tags/query override its fields); save_filter stores status/freshness/tags/query under it; delete_filter removes it."
+                },
+                "limit": { "type": "integer", "description": "action=list: page size. action=audit: entries to return, newest last (default 20, at most 200)." },
+                "offset": { "type": "integer" }
+            }
+        }
+    });
+    if let (Some(properties), Value::Object(extra)) = (
+        schema["inputSchema"]["properties"].as_object_mut(),
+        input_action_properties(),
+    ) {
+        properties.extend(extra);
+    }
+    schema
+}
+
+/// Arguments of the single-purpose input actions (move_section, split,
+/// sign_off, rollback, attachments, export/import, import_markdown, create_from_template, clone, diagram_history), merged into [`input_tool_schema`]
+/// to stay under the `json!` recursion limit.
+fn input_action_properties() -> Value {
+    json!({
+        "include_expired": { "type": "boolean", "description": "action=list without freshness: also show expired packs still in the grace window (default from CONTEXT_PACK_LIST_INCLUDE_EXPIRED, normally false)." },
+        "archived": { "type": "boolean", "description": "action=list: list packs archived after expiry (CONTEXT_PACK_EXPIRED_RETENTION=archive) instead of live ones; freshness filters do not apply." },
+        "section_key": { "type": "string", "description": "Section holding the diagram (action=diagram_history) or attachment (action=upsert_attachment/delete_attachment), or the section to move (action=move_section)." },
+        "to": { "type": "string", "description": "action=move_section: target pack id or name (id/name is the source)." },
+        "to_expected_revision": { "type": "integer", "description": "action=move_section: expected revision of the target pack." },
+        "section_keys": { "type": "array", "items": { "type": "string" }, "description": "action=split: sections to move into the new pack (at least one section must stay); action=clone: sections to copy (default all)." },
+        "new_name": { "type": "string", "description": "action=split/import/import_markdown/create_from_template/clone: optional name for the new pack (import defaults to the bundled name)." },
+        "new_title": { "type": "string", "description": "action=split: title for the new pack (default: parent title + \" (split)\"); action=create_from_template: optional title." },
+        "target_section_key": { "type": "string", "description": "action=move_section: key in the target pack (default: keep the key, suffixed -2, -3... on conflict)." },
+        "reviewer": { "type": "string", "description": "action=sign_off: reviewer identity (1-128 chars)." },
+        "verdict": { "type": "string", "enum": ["approved", "changes_requested"], "description": "action=sign_off: review outcome for the revision given as expected_revision." },
+        "role": { "type": "string", "description": "action=sign_off: role the reviewer signs as (e.g. security); matched case-insensitively by the sign-off policy." },
+        "comment": { "type": "string", "description": "action=sign_off: optional note (max 2000 chars)." },
+        "from": { "type": "string", "description": "action=merge: source pack id or name whose sections are copied into the pack given by id|name (the source is left unchanged)." },
+        "on_conflict": { "type": "string", "enum": ["skip", "replace", "suffix"], "description": "action=merge: what to do when a source section key already exists in the target: keep the target's (skip), overwrite it (replace) or add the source's as key-2, key-3... (suffix, default)." },
+        "links": { "type": "array", "items": { "type": "object", "properties": { "kind": { "type": "string", "enum": ["parent", "supersedes", "depends_on"] }, "target": { "type": "string", "description": "Pack id of the related pack." } }, "required": ["kind", "target"] }, "description": "action=set_links: the complete link set (empty clears it); duplicates collapse, at most 32. Targets must exist by finalize." },
+        "link": { "type": "object", "properties": { "kind": { "type": "string", "enum": ["parent", "supersedes", "depends_on"] }, "target": { "type": "string", "description": "Pack id of the related pack." } }, "required": ["kind", "target"], "description": "action=add_link: one link to append; re-adding an existing link is a no-op apart from the revision bump." },
+        "to_revision": { "type": "integer", "description": "action=rollback: prior revision to restore; must still be in the pack's history journal (the error lists the available ones)." },
+        "inline_excerpts": { "type": "boolean", "description": "action=export: snapshot every ref's current excerpt into the bundle so it reads the same without the source tree (fails listing refs that no longer resolve)." },
+        "bundle": { "type": "object", "description": "action=import: bundle returned by action=export (a JSON string is accepted too). Recreated as a new draft pack at revision 1." },
+        "markdown": { "type": "string", "description": "action=import_markdown: document to import. `# Title` then brief text; each `## Section title [key]` starts a section (key defaults to a slug of the title); `- ref[ <key>]: path:start[-end][ — why]` lines add refs; ```mermaid blocks add diagrams; other text is the section description." },
+        "template": { "type": "string", "enum": ["audit", "remediation", "research"], "description": "action=create_from_template: pack shape. audit: scope, findings, risks, qa; remediation: scope, findings (root cause), plan, qa; research: scope (question), findings, open_questions, qa. Descriptions start as `TODO(template): …` placeholders that don't count toward finalize." },
+        "diagram_key": { "type": "string", "description": "Diagram to inspect (action=diagram_history)." },
+        "diff": { "type": "boolean", "description": "action=diagram_history: include a line diff (defaults to previous vs current version)." },
+        "from_version": { "type": "integer", "description": "action=diagram_history: 1-based version to diff from." },
+        "to_version": { "type": "integer", "description": "action=diagram_history: 1-based version to diff to." },
+        "attachment_key": { "type": "string", "description": "action=upsert_attachment/delete_attachment: attachment within section_key (upsert replaces one with the same key)." },
+        "content": { "type": "string", "description": "action=upsert_attachment: text stored verbatim (max CONTEXT_PACK_MAX_ATTACHMENT_BYTES, default 64 KiB; it also counts toward the pack size cap)." },
+        "media_type": { "type": "string", "description": "action=upsert_attachment: type/subtype of the content (default text/plain); picks the fence language in output." },
+        "title": { "type": "string", "description": "action=upsert_attachment: optional label shown above the content." }
+    })
+}
+
+/// Split out of [`tools_schema`] to stay under the `json!` recursion limit.
+fn output_tool_schema() -> Value {
+    json!({
+        "name": "output",
+        "description": "Render v3 output actions: list/read/read_delta/watch/graph/continue (read_delta renders only sections and refs changed after since_revision; watch long-polls until a pack's revision advances or it is finalized; graph renders a mermaid map of the listed packs; continue returns the next chunk of a render too large for one frame).",
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "read", "read_delta", "watch", "graph", "continue"]
+                },
+                "id": { "type": "string", "description": "Pack ID" },
+                "name": { "type": "string", "description": "Pack name" },
+                "status": {
+                    "type": "string",
+                    "enum": ["draft", "finalized"],
+                    "description": "Optional status filter (for list, graph and read)"
+                },
+                "freshness": {
+                    "type": "string",
+                    "enum": ["fresh", "expiring_soon", "expired", "pinned"],
+                    "description": "Optional freshness filter for list and graph."
+                },
+                "include_expired": { "type": "boolean", "description": "list/graph without freshness: also show expired packs still in the grace window (default from CONTEXT_PACK_LIST_INCLUDE_EXPIRED, normally false)." },
+                "archived": { "type": "boolean", "description": "list: list packs archived after expiry (CONTEXT_PACK_EXPIRED_RETENTION=archive) instead of live ones; freshness filters do not apply." },
+                "profile": {
+                    "type": "string",
+                    "enum": ["orchestrator", "reviewer", "executor", "archive", "outline"],
+                    "description": "Read profile presets (mode, page size, legend, filters; deployments may tune them): orchestrator (compact bounded), reviewer (full evidence), executor (actionable compact), archive (full, unpaged, minimal stable legend), outline (unpaged navigation: section headings and one line per ref with key, path and stale/drifted markers; no descriptions or code)."
+                },
+                "query": { "type": "string", "description": "Optional text search for list and graph" },
+                "machine_block": { "type": "boolean", "description": "action=list: append a `[PACK_LIST]` block after the markdown list with one `[PACK]` per pack and fixed `- key: value` lines (id, name, title, status, revision, freshness, tags, sign_offs, latest_verdict, updated_at, expires_at; `-` when absent). Keys are stable across versions; parse this instead of the prose." },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "List filter: packs carrying all of these tags (case-insensitive)."
+                },
+                "filter": {
+                    "type": "string",
+                    "description": "Saved filter name for list and graph (see input save_filter); explicit filter fields override it."
+                },
+                "diff_against_revision": { "type": "integer", "description": "action=read: render a key-level diff (pack fields; sections, refs and diagrams added/removed/changed) against this journaled prior revision instead of the content. Not combinable with page_token/offset/export_path." },
+                "export_path": { "type": "string", "description": "action=read: write the full render (every page, no limit) to this .md path relative to the export root (CONTEXT_PACK_EXPORT_ROOT, default <root>/exports) and return the file path instead of the content; for packs beyond the frame limit. Not combinable with limit/offset/page_token." },
+                "page_token": { "type": "string", "description": "Opaque page token returned by output read paging metadata." },
+                "continuation_token": { "type": "string", "description": "action=continue (required): the token from the last content entry of a reply whose render exceeded the frame limit. Each call returns the next chunk (split at a line break where possible) and a new continuation entry; `continuation_token: null` marks the last chunk. Tokens expire 10 minutes after their last use." },
+                "max_tokens": { "type": "integer", "minimum": 1, "description": "action=read: approximate token budget (chars/4) for the response. Chunks past it are deferred to the next page; the legend reports truncated_by_max_tokens and next_page_token resumes (the budget carries over unless given again). At least one chunk is always returned." },
+                "structured": { "type": "boolean", "description": "action=read: also return the page as JSON (pack id/status/revision/etag/freshness, paging cursor, and per section the ref keys with stale/drifted flags, diagram and attachment keys) in a second content entry and in structuredContent. The markdown stays the first entry." },
+                "contains": { "type": "string", "description": "Optional substring filter applied to rendered chunks." },
+                "since_revision": { "type": "integer", "description": "action=read_delta (required): the last revision the reader saw; only sections and refs changed after it are rendered, plus the keys of sections removed since." },
+                "after_revision": { "type": "integer", "description": "action=watch: complete once the pack revision exceeds this (default: the current revision)." },
+                "timeout_seconds": { "type": "integer", "description": "action=watch: give up after this many seconds (default 60, max 600); the reply then says outcome=timed_out." },
+                "if_none_match": { "type": "string", "description": "The `etag` legend value from an earlier read with the same args; if the page is unchanged the reply is a short `not_modified: true` stub instead of a full render." },
+                "anchors": { "type": "string", "enum": ["none", "html", "slug"], "description": "Emit deep-link ids before section/ref headings: `html` → `<a id=\"sec-{section}\"></a>` / `<a id=\"ref-{section}.{ref}\"></a>`, `slug` → `{#…}` heading attributes. Default none; carried across page_token continuation." },
+                "separator": { "type": "string", "enum": ["none", "rule"], "description": "`rule` inserts a `---` thematic break between sections. Default none; carried across page_token continuation." },
+                "trim_trailing_whitespace": { "type": "boolean", "description": "Strip trailing whitespace from every excerpt line and drop trailing blank lines. Default false; carried across page_token continuation." },
+                "tab_width": { "type": "integer", "minimum": 1, "maximum": 16, "description": "Expand tabs in excerpts to spaces with tab stops this many columns apart. Default: tabs kept; carried across page_token continuation." },
+                "strip_ansi": { "type": "boolean", "description": "Remove ANSI escape sequences (terminal colors) from excerpts. Default false; carried across page_token continuation." },
+                "lang": { "type": "string", "description": "Language tag (e.g. `ru`, `pt-br`): section descriptions use the matching `translations` entry (exact tag, then primary language), else the default description. Carried across page_token continuation." },
+                "limit": { "type": "integer" },
+                "offset": { "type": "integer" }
+            }
+        }
+    })
+}
+
+/// Split out of [`tools_schema`] to stay under the `json!` recursion limit.
+fn read_defaults_schema() -> Value {
+    json!({
+        "type": "object",
+        "description": "Pack-preferred output read shape, used when the reader (explicit args or host defaults) leaves profile/limit unset.",
+        "properties": {
+            "profile": { "type": "string", "enum": ["orchestrator", "reviewer", "executor", "archive", "outline"] },
+            "limit": { "type": "integer", "minimum": 1, "maximum": 200 },
+            "excerpt_line_limit": { "type": "integer", "minimum": 1, "description": "Excerpt lines rendered per ref (server default CONTEXT_PACK_EXCERPT_LINES, 200); at most CONTEXT_PACK_MAX_EXCERPT_LINES (2000)." }
+        }
+    })
+}
+
+/// Split out of [`tools_schema`] to stay under the `json!` recursion limit.
+fn write_ops_schema() -> Value {
+    json!({
+        "type": "array",
+        "maxItems": 200,
+        "items": {
+            "type": "object",
+            "properties": {
+                "op": { "type": "string", "enum": ["upsert_section", "delete_section", "upsert_ref", "delete_ref", "set_meta", "set_status"] },
+                "status": { "type": "string", "enum": ["draft", "finalized"] },
+                "section_key": { "type": "string" },
+                "ref_key": { "type": "string" },
+                "title": { "type": "string" },
+                "description": { "type": "string" },
+                "order": { "type": "integer", "minimum": 0 },
+                "root": { "type": "string" },
+                "path": { "type": "string" },
+                "line_start": { "type": "integer", "minimum": 1 },
+                "line_end": { "type": "integer", "minimum": 1 },
+                "why": { "type": "string" },
+                "group": { "type": "string" },
+                "symbol": { "type": "string" },
+                "url": { "type": "string" },
+                "excerpt_line_limit": { "type": "integer", "minimum": 1 },
+                "brief": { "type": "string" },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["op"]
+        },
+        "description": "action=write (instead of document): edits applied in order to an existing draft (id|name + expected_revision) and saved as one revision; if any op fails nothing is saved and the error names ops[i]. upsert_section(section_key, title, description?, order?), delete_section(section_key), upsert_ref(section_key, ref_key, path, line_start, line_end, title?, why?, group?, symbol?, url?, root?, excerpt_line_limit?), delete_ref(section_key, ref_key), set_meta(title?, brief?, tags?), set_status(status). A batch that ends finalized runs the finalize checks; with validate_only nothing is saved and no revision is used, so ops can pre-check refs and finalize readiness."
+    })
+}
+
+/// Split out of [`tools_schema`] to stay under the `json!` recursion limit.
+fn write_document_schema() -> Value {
+    json!({
+        "type": "object",
+        "description": "Full-replace snapshot payload for action=write (or action=estimate to size it without persisting).",
+        "properties": {
+            "name": { "type": "string", "description": "Optional pack name (new pack only, immutable for updates)." },
+            "title": { "type": "string" },
+            "brief": { "type": "string", "description": "Short description of the pack" },
+            "tags": { "type": "array", "items": { "type": "string" } },
+            "ttl_minutes": { "type": "integer", "description": "Optional TTL override from now in minutes." },
+            "status": { "type": "string", "enum": ["draft", "finalized"] },
+            "read_defaults": read_defaults_schema(),
+            "sections": {
+                "type": "array",
+                "description": "Full list of sections (each section can include refs and diagrams). Refs accept `entry_point: true` (max 3 per pack) to pin them to the first compact page, and `git_sha` (7-64 hex digits) to read their lines at that commit instead of the working tree. Refs accept `symbol` (an identifier, optionally qualified: `Parser::parse`, `Store.load`) to follow that definition instead of fixed lines; line_start/line_end are then optional and refreshed from the source on every write and read. Refs accept `root` to read `path` under one of the server's extra source roots (CONTEXT_PACK_SOURCE_ROOTS) instead of the default one. A ref `path` may be a directory or glob (`src/auth/**`, `src/*.rs`, `docs/`; no line range needed) to render the matching files with per-file line counts. A ref may give `url` (http/https: an RFC, issue or doc) instead of `path` and lines; it renders as a labeled link and is exempt from finalize ref checks. Refs accept `excerpt_line_limit` to render more (or fewer) excerpt lines than the pack's limit. Sections accept `translations` ({\"ru\": \"...\"}): descriptions by language tag, picked by output read `lang`."
+            }
+        }
+    })
+}
@@ -1,40 +1,116 @@
 use serde_json::{json, Value};
 
+use crate::app::audit::AuditCaller;
 use crate::app::input_usecases::{
-    InputUseCases, SnapshotDiagram, SnapshotDocument, SnapshotRef, SnapshotSection, TouchTtlMode,
+    AddLinkRequest, ApplyOpsRequest, AuditTail, ClonePackRequest, CreateFromTemplateRequest,
+    DeleteAttachmentRequest, ImportBundleRequest, ImportMarkdownRequest, InputUseCases,
+    MergePacksRequest, MoveSectionRequest, RepairRefsRequest, RollbackRequest, SetLinksRequest,
+    SignOffRequest, SnapshotDiagram, SnapshotDocument, SnapshotRef, SnapshotSection,
+    SplitPackRequest, TouchTtlMode, UpsertAttachmentRequest, UpsertRefRequest, WriteOp,
     WriteSnapshotRequest,
 };
-use crate::app::ports::FreshnessState;
+use crate::app::output_usecases::{OutputReadRequest, OutputUseCases};
+use crate::app::ports::{FreshnessState, SavedFilterPort};
 use crate::domain::errors::DomainError;
-use crate::domain::models::Pack;
-use crate::domain::types::Status;
+use crate::domain::glob;
+use crate::domain::models::{
+    MergePolicy, Pack, PackBundle, PackLink, PackTemplate, ReadDefaults, SignOffVerdict,
+};
+use crate::domain::types::{OutputProfile, PackId, PackSectionRef, Status};
 
 use super::{
-    freshness_opt, pack_summary, req_identifier, req_u64, status_opt, str_opt, tool_success,
-    u64_opt, usize_opt,
+    filter_fields_from_args, list_filter_from_args, pack_summary, req_filter_name, req_identifier,
+    req_u64, str_list_opt, str_opt, tool_success, u64_opt, usize_opt,
 };
 
-const INPUT_ALLOWED_ACTIONS: [&str; 5] = ["list", "get", "write", "ttl", "delete"];
+const INPUT_ALLOWED_ACTIONS: [&str; 28] = [
+    "list",
+    "get",
+    "lint",
+    "validate",
+    "write",
+    "estimate",
+    "ttl",
+    "delete",
+    "prepare_delete",
+    "move_section",
+    "merge",
+    "split",
+    "sign_off",
+    "set_links",
+    "add_link",
+    "rollback",
+    "repair_refs",
+    "upsert_attachment",
+    "delete_attachment",
+    "export",
+    "import",
+    "import_markdown",
+    "create_from_template",
+    "clone",
+    "diagram_history",
+    "audit",
+    "save_filter",
+    "delete_filter",
+];
+
+/// Ops a batched `input write` accepts in `ops[]`.
+const WRITE_OP_NAMES: [&str; 6] = [
+    "upsert_section",
+    "delete_section",
+    "upsert_ref",
+    "delete_ref",
+    "set_meta",
+    "set_status",
+];
+
+/// `input ttl` takes exactly one of these.
+const TTL_MODE_FIELDS: [&str; 5] = [
+    "ttl_minutes",
+    "extend_minutes",
+    "sliding_ttl",
+    "sliding_ttl_minutes",
+    "pin",
+];
 
 pub(super) async fn handle_input_tool(
     args: &Value,
     uc: &InputUseCases,
+    output_uc: &OutputUseCases,
+    saved_filters: &dyn SavedFilterPort,
 ) -> Result<Value, DomainError> {
     let action = args
         .get("action")
         .and_then(|v| v.as_str())
         .unwrap_or("list");
+    let actor = str_opt(args, "actor");
+    let force = args.get("force").and_then(Value::as_bool).unwrap_or(false);
+    for target in owner_guarded_targets(action, args) {
+        uc.authorize_owner(&target, actor.as_deref(), force).await?;
+    }
 
+    AuditCaller::new(action, actor.as_deref())
+        .scope(dispatch_input_action(
+            action,
+            args,
+            uc,
+            output_uc,
+            saved_filters,
+        ))
+        .await
+}
+
+async fn dispatch_input_action(
+    action: &str,
+    args: &Value,
+    uc: &InputUseCases,
+    output_uc: &OutputUseCases,
+    saved_filters: &dyn SavedFilterPort,
+) -> Result<Value, DomainError> {
     match action {
         "list" => {
-            let status = status_opt(args, "status")?;
-            let freshness = freshness_opt(args, "freshness")?;
-            let query = str_opt(args, "query");
-            let limit = usize_opt(args, "limit")?;
-            let offset = usize_opt(args, "offset")?;
-            let packs = uc
-                .list_with_freshness(status, query, limit, offset, freshness)
-                .await?;
+            let filter = list_filter_from_args(args, saved_filters).await?;
+            let packs = uc.list_matching(filter).await?;
             let summaries: Vec<Value> = packs.iter().map(pack_summary).collect();
             tool_success(
                 "list",
@@ -49,50 +125,92 @@ pub(super) async fn handle_input_tool(
             let pack = uc.get(&ident).await?;
             tool_success("get", pack_with_freshness_metadata(pack)?)
         }
-        "write" => handle_write_action(args, uc).await,
+        "lint" => {
+            let ident = req_pack_identifier(args, "input", "lint")?;
+            tool_success("lint", serde_json::to_value(uc.lint(&ident).await?)?)
+        }
+        "validate" => {
+            let ident = req_pack_identifier(args, "input", "validate")?;
+            tool_success(
+                "validate",
+                serde_json::to_value(uc.validate_readiness(&ident).await?)?,
+            )
+        }
+        "write" => handle_write_action(args, uc, output_uc).await,
+        "estimate" => handle_estimate_action(args, uc).await,
         "ttl" => {
             let ident = req_pack_identifier(args, "input", "ttl")?;
             let expected_revision = req_expected_revision(args)?;
-            let ttl_minutes = u64_opt(args, "ttl_minutes")?;
-            let extend_minutes = u64_opt(args, "extend_minutes")?;
-            let mode = match (ttl_minutes, extend_minutes) {
-                (Some(_), Some(_)) => {
+            let sliding_ttl = match args.get("sliding_ttl") {
+                None => None,
+                Some(value) => Some(value.as_bool().ok_or_else(|| {
+                    DomainError::InvalidData("'sliding_ttl' must be a boolean".into())
+                })?),
+            };
+            let pin =
+                match args.get("pin") {
+                    None => None,
+                    Some(value) => Some(value.as_bool().ok_or_else(|| {
+                        DomainError::InvalidData("'pin' must be a boolean".into())
+                    })?),
+                };
+            let modes: Vec<(&str, TouchTtlMode)> = [
+                u64_opt(args, "ttl_minutes")?.map(|m| ("ttl_minutes", TouchTtlMode::SetMinutes(m))),
+                u64_opt(args, "extend_minutes")?
+                    .map(|m| ("extend_minutes", TouchTtlMode::ExtendMinutes(m))),
+                sliding_ttl.map(|on| ("sliding_ttl", TouchTtlMode::Sliding(on))),
+                u64_opt(args, "sliding_ttl_minutes")?
+                    .map(|m| ("sliding_ttl_minutes", TouchTtlMode::SlidingMinutes(m))),
+                pin.map(|on| ("pin", TouchTtlMode::Pin(on))),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            let mode = match modes.as_slice() {
+                [(_, mode)] => *mode,
+                [] => {
                     return Err(DomainError::DetailedInvalidData {
                         message:
-                            "input ttl requires exactly one of 'ttl_minutes' or 'extend_minutes'"
+                            "input ttl requires 'ttl_minutes', 'extend_minutes', 'sliding_ttl', 'sliding_ttl_minutes' or 'pin'"
                                 .into(),
                         details: json!({
                             "action": "ttl",
-                            "required_fields": ["ttl_minutes", "extend_minutes"],
+                            "required_fields": TTL_MODE_FIELDS,
                             "required_mode": "exactly_one_of",
-                            "provided_fields": [
-                                "ttl_minutes",
-                                "extend_minutes"
-                            ],
                         }),
                     });
                 }
-                (Some(minutes), None) => TouchTtlMode::SetMinutes(minutes),
-                (None, Some(minutes)) => TouchTtlMode::ExtendMinutes(minutes),
-                (None, None) => {
+                provided => {
                     return Err(DomainError::DetailedInvalidData {
-                        message: "input ttl requires 'ttl_minutes' or 'extend_minutes'".into(),
+                        message: "input ttl requires exactly one of 'ttl_minutes', 'extend_minutes', 'sliding_ttl', 'sliding_ttl_minutes' or 'pin'".into(),
                         details: json!({
                             "action": "ttl",
-                            "required_fields": ["ttl_minutes", "extend_minutes"],
+                            "required_fields": TTL_MODE_FIELDS,
                             "required_mode": "exactly_one_of",
+                            "provided_fields": provided.iter().map(|(field, _)| *field).collect::<Vec<_>>(),
                         }),
                     });
                 }
             };
             let pack = uc
-                .touch_ttl_checked(&ident, expected_revision, mode)
+                .touch_ttl_checked(
+                    &ident,
+                    expected_revision,
+                    mode,
+                    str_opt(args, "reason").as_deref(),
+                )
                 .await?;
             tool_success("ttl", serde_json::to_value(pack)?)
         }
         "delete" => {
             let ident = req_pack_identifier(args, "input", "delete")?;
-            let deleted = uc.delete_pack_file(&ident).await?;
+            let confirm_token = str_opt(args, "confirm_token");
+            let deleted = uc
+                .delete_pack_confirmed(&ident, confirm_token.as_deref())
+                .await?;
+            if let (true, Some(reason)) = (deleted, str_opt(args, "reason")) {
+                tracing::info!("deleted pack '{}': {}", ident, reason);
+            }
             tool_success(
                 "delete",
                 serde_json::json!({
@@ -101,15 +219,836 @@ pub(super) async fn handle_input_tool(
                 }),
             )
         }
+        "prepare_delete" => {
+            let ident = req_pack_identifier(args, "input", "prepare_delete")?;
+            let confirmation = uc.prepare_delete(&ident).await?;
+            tool_success("prepare_delete", serde_json::to_value(confirmation)?)
+        }
+        "move_section" => {
+            let from = req_pack_identifier(args, "input", "move_section")?;
+            let (Some(to), Some(section_key)) = (str_opt(args, "to"), str_opt(args, "section_key"))
+            else {
+                return Err(DomainError::DetailedInvalidData {
+                    message: "input move_section requires 'to' (target pack id or name) and 'section_key'".into(),
+                    details: json!({
+                        "action": "move_section",
+                        "required_fields": ["to", "to_expected_revision", "section_key"],
+                    }),
+                });
+            };
+            let moved = uc
+                .move_section_checked(MoveSectionRequest {
+                    from,
+                    from_expected_revision: req_expected_revision(args)?,
+                    to,
+                    to_expected_revision: req_u64(args, "to_expected_revision")?,
+                    section_key,
+                    target_section_key: str_opt(args, "target_section_key"),
+                    reason: str_opt(args, "reason"),
+                })
+                .await?;
+            tool_success(
+                "move_section",
+                json!({
+                    "section_key": moved.section_key,
+                    "renamed": moved.renamed,
+                    "source": pack_summary(&moved.source),
+                    "target": pack_summary(&moved.target),
+                }),
+            )
+        }
+        "merge" => {
+            let target = req_pack_identifier(args, "input", "merge")?;
+            let Some(source) = str_opt(args, "from") else {
+                return Err(DomainError::DetailedInvalidData {
+                    message: "input merge requires 'from' (source pack id or name) and 'expected_revision' of the target".into(),
+                    details: json!({
+                        "action": "merge",
+                        "required_fields": ["from", "expected_revision"],
+                    }),
+                });
+            };
+            let policy = match str_opt(args, "on_conflict") {
+                Some(raw) => raw.parse::<MergePolicy>()?,
+                None => MergePolicy::default(),
+            };
+            let merged = uc
+                .merge_packs_checked(MergePacksRequest {
+                    source,
+                    target,
+                    target_expected_revision: req_expected_revision(args)?,
+                    policy,
+                    reason: str_opt(args, "reason"),
+                })
+                .await?;
+            let mut payload = pack_summary(&merged.target);
+            payload["merged_from"] = json!({
+                "pack_id": merged.source_id.as_str(),
+                "revision": merged.source_revision,
+            });
+            payload["on_conflict"] = json!(policy.to_string());
+            payload["sections"] = serde_json::to_value(&merged.sections)?;
+            tool_success("merge", payload)
+        }
+        "split" => {
+            let identifier = req_pack_identifier(args, "input", "split")?;
+            let expected_revision = req_expected_revision(args)?;
+            let Some(section_keys) = str_list_opt(args, "section_keys")?.filter(|k| !k.is_empty())
+            else {
+                return Err(DomainError::DetailedInvalidData {
+                    message: "input split requires a non-empty 'section_keys' array".into(),
+                    details: json!({
+                        "action": "split",
+                        "required_fields": ["section_keys"],
+                    }),
+                });
+            };
+            let split = uc
+                .split_pack_checked(SplitPackRequest {
+                    identifier,
+                    expected_revision,
+                    section_keys,
+                    name: str_opt(args, "new_name"),
+                    title: str_opt(args, "new_title"),
+                    reason: str_opt(args, "reason"),
+                    owner: str_opt(args, "actor"),
+                })
+                .await?;
+            tool_success(
+                "split",
+                json!({
+                    "parent": pack_summary(&split.parent),
+                    "child": pack_summary(&split.child),
+                    "moved_section_keys": split
+                        .child
+                        .sections
+                        .iter()
+                        .map(|s| s.key.as_str())
+                        .collect::<Vec<_>>(),
+                }),
+            )
+        }
+        "sign_off" => {
+            let ident = req_pack_identifier(args, "input", "sign_off")?;
+            let expected_revision = req_expected_revision(args)?;
+            let (Some(reviewer), Some(verdict)) =
+                (str_opt(args, "reviewer"), str_opt(args, "verdict"))
+            else {
+                return Err(DomainError::DetailedInvalidData {
+                    message: "input sign_off requires 'reviewer' and 'verdict' (approved|changes_requested)".into(),
+                    details: json!({
+                        "action": "sign_off",
+                        "required_fields": ["reviewer", "verdict", "expected_revision"],
+                    }),
+                });
+            };
+            let verdict: SignOffVerdict = verdict.parse()?;
+            let pack = uc
+                .sign_off_checked(SignOffRequest {
+                    identifier: ident,
+                    expected_revision,
+                    reviewer,
+                    verdict,
+                    role: str_opt(args, "role"),
+                    comment: str_opt(args, "comment"),
+                    reason: str_opt(args, "reason"),
+                })
+                .await?;
+            let mut payload = pack_summary(&pack);
+            payload["sign_off"] = serde_json::to_value(pack.sign_offs.last())?;
+            tool_success("sign_off", payload)
+        }
+        "rollback" => {
+            let identifier = req_pack_identifier(args, "input", "rollback")?;
+            let expected_revision = req_expected_revision(args)?;
+            let Some(to_revision) = u64_opt(args, "to_revision")? else {
+                return Err(DomainError::DetailedInvalidData {
+                    message: "input rollback requires 'to_revision' (a journaled prior revision)"
+                        .into(),
+                    details: json!({
+                        "action": "rollback",
+                        "required_fields": ["to_revision", "expected_revision"],
+                    }),
+                });
+            };
+            let pack = uc
+                .rollback_checked(RollbackRequest {
+                    identifier,
+                    expected_revision,
+                    to_revision,
+                    reason: str_opt(args, "reason"),
+                })
+                .await?;
+            let mut payload = pack_summary(&pack);
+            payload["restored_from_revision"] = json!(to_revision);
+            tool_success("rollback", payload)
+        }
+        "repair_refs" => {
+            let report = uc
+                .repair_refs_checked(RepairRefsRequest {
+                    identifier: req_pack_identifier(args, "input", "repair_refs")?,
+                    expected_revision: req_expected_revision(args)?,
+                    validate_only: args
+                        .get("validate_only")
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false),
+                    reason: str_opt(args, "reason"),
+                })
+                .await?;
+            let mut payload = pack_summary(&report.pack);
+            payload["applied"] = json!(report.applied);
+            payload["repaired"] = json!(report.repaired);
+            payload["unresolved"] = json!(report.unresolved);
+            tool_success("repair_refs", payload)
+        }
+        "export" => {
+            let ident = req_pack_identifier(args, "input", "export")?;
+            let inline_excerpts = args
+                .get("inline_excerpts")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            let bundle = uc.export_bundle(&ident, inline_excerpts).await?;
+            let bundle_bytes = serde_json::to_string(&bundle)?.len();
+            tool_success(
+                "export",
+                json!({
+                    "pack_id": bundle.pack.id.as_str(),
+                    "revision": bundle.pack.revision,
+                    "excerpts_inlined": bundle.excerpts_inlined,
+                    "bundle_bytes": bundle_bytes,
+                    "bundle": bundle,
+                }),
+            )
+        }
+        "import" => {
+            let bundle =
+                match args.get("bundle") {
+                    Some(Value::String(raw)) => serde_json::from_str::<PackBundle>(raw),
+                    Some(value @ Value::Object(_)) => {
+                        serde_json::from_value::<PackBundle>(value.clone())
+                    }
+                    _ => return Err(DomainError::DetailedInvalidData {
+                        message:
+                            "input import requires 'bundle' (the object returned by input export)"
+                                .into(),
+                        details: json!({
+                            "action": "import",
+                            "required_fields": ["bundle"],
+                        }),
+                    }),
+                }
+                .map_err(|e| DomainError::InvalidData(format!("invalid bundle: {}", e)))?;
+            let source_id = bundle.pack.id.clone();
+            let source_revision = bundle.pack.revision;
+            let source_status = bundle.pack.status;
+            let pack = uc
+                .import_bundle(ImportBundleRequest {
+                    bundle,
+                    name: str_opt(args, "new_name"),
+                    ttl_minutes: u64_opt(args, "ttl_minutes")?,
+                    reason: str_opt(args, "reason"),
+                    owner: str_opt(args, "actor"),
+                })
+                .await?;
+            let mut payload = pack_summary(&pack);
+            payload["imported_from"] = json!({
+                "pack_id": source_id.as_str(),
+                "revision": source_revision,
+                "status": source_status,
+            });
+            tool_success("import", payload)
+        }
+        "upsert_attachment" | "delete_attachment" => {
+            handle_attachment_action(action, args, uc).await
+        }
+        "set_links" | "add_link" => handle_links_action(action, args, uc).await,
+        "import_markdown" => handle_import_markdown_action(args, uc).await,
+        "create_from_template" => handle_create_from_template_action(args, uc).await,
+        "clone" => {
+            let cloned = uc
+                .clone_pack(ClonePackRequest {
+                    identifier: req_pack_identifier(args, "input", "clone")?,
+                    section_keys: str_list_opt(args, "section_keys")?,
+                    name: str_opt(args, "new_name"),
+                    ttl_minutes: u64_opt(args, "ttl_minutes")?,
+                    reason: str_opt(args, "reason"),
+                    owner: str_opt(args, "actor"),
+                })
+                .await?;
+            let mut payload = pack_summary(&cloned.pack);
+            payload["cloned_from"] = json!({
+                "pack_id": cloned.source_id.as_str(),
+                "revision": cloned.source_revision,
+            });
+            payload["section_keys"] = json!(cloned
+                .pack
+                .sections
+                .iter()
+                .map(|s| s.key.as_str())
+                .collect::<Vec<_>>());
+            tool_success("clone", payload)
+        }
+        "diagram_history" => handle_diagram_history_action(args, uc).await,
+        "audit" => {
+            let ident = req_pack_identifier(args, "input", "audit")?;
+            let limit = usize_opt(args, "limit")?.unwrap_or(AuditTail::DEFAULT_LIMIT);
+            let tail = uc.audit_tail(&ident, limit).await?;
+            tool_success(
+                "audit",
+                json!({
+                    "pack_id": tail.pack_id,
+                    "count": tail.entries.len(),
+                    "entries": tail.entries,
+                }),
+            )
+        }
+        "save_filter" => {
+            let name = req_filter_name(args)?;
+            let filter = filter_fields_from_args(args)?;
+            if filter.is_empty() {
+                return Err(DomainError::DetailedInvalidData {
+                    message: "input save_filter requires at least one of 'status', 'freshness', 'tags', 'query'".into(),
+                    details: json!({
+                        "action": "save_filter",
+                        "required_fields": ["status", "freshness", "tags", "query"],
+                        "required_mode": "at_least_one_of",
+                    }),
+                });
+            }
+            saved_filters.save_filter(&name, &filter).await?;
+            let names: Vec<String> = saved_filters.list_filters().await?.into_keys().collect();
+            tool_success(
+                "save_filter",
+                json!({
+                    "filter": name,
+                    "definition": filter,
+                    "saved_filters": names,
+                }),
+            )
+        }
+        "delete_filter" => {
+            let name = req_filter_name(args)?;
+            let deleted = saved_filters.delete_filter(&name).await?;
+            let names: Vec<String> = saved_filters.list_filters().await?.into_keys().collect();
+            tool_success(
+                "delete_filter",
+                json!({
+                    "filter": name,
+                    "deleted": deleted,
+                    "saved_filters": names,
+                }),
+            )
+        }
         _ => Err(unsupported_input_action(action)),
     }
 }
 
-async fn handle_write_action(args: &Value, uc: &InputUseCases) -> Result<Value, DomainError> {
+/// Existing packs an action mutates, checked against their owner before it
+/// runs. Creates have no owner yet and sign-offs come from other reviewers
+/// by design, so neither is guarded.
+fn owner_guarded_targets(action: &str, args: &Value) -> Vec<String> {
+    match action {
+        "write" | "ttl" | "delete" | "merge" | "split" | "set_links" | "add_link" | "rollback"
+        | "repair_refs" | "upsert_attachment" | "delete_attachment" => {
+            req_identifier(args).ok().into_iter().collect()
+        }
+        "move_section" => req_identifier(args)
+            .ok()
+            .into_iter()
+            .chain(str_opt(args, "to"))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+async fn handle_attachment_action(
+    action: &str,
+    args: &Value,
+    uc: &InputUseCases,
+) -> Result<Value, DomainError> {
+    let identifier = req_pack_identifier(args, "input", action)?;
+    let expected_revision = req_expected_revision(args)?;
+    let upsert = action == "upsert_attachment";
+    // Content is kept byte for byte, so it is not trimmed like other strings.
+    let content = args.get("content").and_then(Value::as_str);
+    let (Some(section_key), Some(attachment_key)) = (
+        str_opt(args, "section_key"),
+        str_opt(args, "attachment_key"),
+    ) else {
+        return Err(attachment_fields_error(action, upsert));
+    };
+    if upsert && content.is_none() {
+        return Err(attachment_fields_error(action, upsert));
+    }
+    let pack = if let Some(content) = content.filter(|_| upsert) {
+        uc.upsert_attachment_checked(UpsertAttachmentRequest {
+            identifier,
+            expected_revision,
+            section_key: section_key.clone(),
+            attachment_key: attachment_key.clone(),
+            title: str_opt(args, "title"),
+            media_type: str_opt(args, "media_type"),
+            content: content.to_string(),
+            reason: str_opt(args, "reason"),
+        })
+        .await?
+    } else {
+        uc.delete_attachment_checked(DeleteAttachmentRequest {
+            identifier,
+            expected_revision,
+            section_key: section_key.clone(),
+            attachment_key: attachment_key.clone(),
+            reason: str_opt(args, "reason"),
+        })
+        .await?
+    };
+    let mut payload = pack_summary(&pack);
+    payload["section_key"] = json!(section_key);
+    payload["attachment_key"] = json!(attachment_key);
+    if upsert {
+        let attachment = pack
+            .sections
+            .iter()
+            .find(|s| s.key.as_str() == section_key)
+            .and_then(|s| {
+                s.attachments
+                    .iter()
+                    .find(|a| a.key.as_str() == attachment_key)
+            });
+        if let Some(attachment) = attachment {
+            payload["media_type"] = json!(attachment.media_type);
+            payload["bytes"] = json!(attachment.content.len());
+        }
+    }
+    tool_success(action, payload)
+}
+
+async fn handle_links_action(
+    action: &str,
+    args: &Value,
+    uc: &InputUseCases,
+) -> Result<Value, DomainError> {
+    let identifier = req_pack_identifier(args, "input", action)?;
+    let expected_revision = req_expected_revision(args)?;
+    let reason = str_opt(args, "reason");
+    let pack = if action == "set_links" {
+        let Some(raw) = args.get("links").and_then(Value::as_array) else {
+            return Err(links_fields_error(action, "links"));
+        };
+        let links = raw
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| parse_link(value, &format!("links[{idx}]")))
+            .collect::<Result<Vec<_>, _>>()?;
+        uc.set_links_checked(SetLinksRequest {
+            identifier,
+            expected_revision,
+            links,
+            reason,
+        })
+        .await?
+    } else {
+        let Some(raw) = args.get("link") else {
+            return Err(links_fields_error(action, "link"));
+        };
+        uc.add_link_checked(AddLinkRequest {
+            identifier,
+            expected_revision,
+            link: parse_link(raw, "link")?,
+            reason,
+        })
+        .await?
+    };
+    let mut payload = pack_summary(&pack);
+    payload["links"] = serde_json::to_value(&pack.links)?;
+    tool_success(action, payload)
+}
+
+fn links_fields_error(action: &str, field: &str) -> DomainError {
+    DomainError::DetailedInvalidData {
+        message: format!(
+            "input {} requires '{}' ({{kind: parent|supersedes|depends_on, target: pack id}}) and 'expected_revision'",
+            action,
+            if field == "links" { "links[]" } else { field }
+        ),
+        details: json!({
+            "tool": "input",
+            "action": action,
+            "required_fields": [field, "expected_revision"],
+        }),
+    }
+}
+
+fn parse_link(value: &Value, field: &str) -> Result<PackLink, DomainError> {
+    let obj = value
+        .as_object()
+        .ok_or_else(|| DomainError::InvalidData(format!("{field} must be an object")))?;
+    let kind = obj
+        .get("kind")
+        .and_then(Value::as_str)
+        .ok_or_else(|| DomainError::InvalidData(format!("{field}.kind is required")))?;
+    let target = obj
+        .get("target")
+        .and_then(Value::as_str)
+        .ok_or_else(|| DomainError::InvalidData(format!("{field}.target is required")))?;
+    Ok(PackLink {
+        kind: kind.parse()?,
+        target: PackId::parse(target.trim())?,
+    })
+}
+
+fn attachment_fields_error(action: &str, upsert: bool) -> DomainError {
+    let required: &[&str] = if upsert {
+        &[
+            "section_key",
+            "attachment_key",
+            "content",
+            "expected_revision",
+        ]
+    } else {
+        &["section_key", "attachment_key", "expected_revision"]
+    };
+    DomainError::DetailedInvalidData {
+        message: format!("input {} requires {}", action, required.join(", ")),
+        details: json!({
+            "tool": "input",
+            "action": action,
+            "required_fields": required,
+        }),
+    }
+}
+
+async fn handle_diagram_history_action(
+    args: &Value,
+    uc: &InputUseCases,
+) -> Result<Value, DomainError> {
+    let ident = req_pack_identifier(args, "input", "diagram_history")?;
+    let (Some(section_key), Some(diagram_key)) =
+        (str_opt(args, "section_key"), str_opt(args, "diagram_key"))
+    else {
+        return Err(DomainError::DetailedInvalidData {
+            message: "input diagram_history requires 'section_key' and 'diagram_key'".into(),
+            details: json!({
+                "tool": "input",
+                "action": "diagram_history",
+                "required_fields": ["section_key", "diagram_key"],
+            }),
+        });
+    };
+    let history = uc
+        .diagram_history(&ident, &section_key, &diagram_key)
+        .await?;
+
+    let from_version = usize_opt(args, "from_version")?;
+    let to_version = usize_opt(args, "to_version")?;
+    let want_diff = args.get("diff").and_then(Value::as_bool).unwrap_or(false)
+        || from_version.is_some()
+        || to_version.is_some();
+    let latest = history.versions.len();
+    let diff = if want_diff {
+        let to_version = to_version.unwrap_or(latest);
+        let from_version = from_version.unwrap_or(to_version.saturating_sub(1).max(1));
+        Some(json!({
+            "from_version": from_version,
+            "to_version": to_version,
+            "text": history.diff(from_version, to_version)?,
+        }))
+    } else {
+        None
+    };
+
+    tool_success(
+        "diagram_history",
+        json!({
+            "id": history.pack_id,
+            "section_key": history.section_key,
+            "diagram_key": history.diagram_key,
+            "versions": history.versions,
+            "diff": diff,
+        }),
+    )
+}
+
+async fn handle_write_action(
+    args: &Value,
+    uc: &InputUseCases,
+    output_uc: &OutputUseCases,
+) -> Result<Value, DomainError> {
     reject_legacy_write_contract(args)?;
+    if args.get("ops").is_some() {
+        return handle_write_ops_action(args, uc).await;
+    }
     let request = parse_write_snapshot_request(args)?;
+    let include_preview = args
+        .get("include_preview")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+        && !request.validate_only;
+    let ttl_source = match request.identifier {
+        None => Some(uc.create_ttl(&request.document)?.1),
+        Some(_) => None,
+    };
     let pack = uc.write_snapshot(request).await?;
-    tool_success("write", serde_json::to_value(pack)?)
+    let preview = if include_preview {
+        // The first page an orchestrator would get from `output read`.
+        Some(
+            output_uc
+                .get_rendered_with_request(
+                    pack.id.as_str(),
+                    OutputReadRequest {
+                        profile: Some(OutputProfile::Orchestrator),
+                        ..Default::default()
+                    },
+                )
+                .await?,
+        )
+    } else {
+        None
+    };
+    let mut payload = serde_json::to_value(pack)?;
+    if let Some(source) = ttl_source {
+        payload["ttl_source"] = json!(source.to_string());
+    }
+    if let Some(preview) = preview {
+        payload["preview"] = json!(preview);
+    }
+    tool_success("write", payload)
+}
+
+async fn handle_write_ops_action(args: &Value, uc: &InputUseCases) -> Result<Value, DomainError> {
+    if args.get("document").is_some() {
+        return Err(DomainError::DetailedInvalidData {
+            message: "input write takes either 'document' or 'ops', not both".into(),
+            details: json!({
+                "tool": "input",
+                "action": "write",
+                "mutually_exclusive": ["document", "ops"],
+            }),
+        });
+    }
+    let identifier = req_pack_identifier(args, "input", "write")?;
+    let expected_revision = req_expected_revision(args)?;
+    let raw = args
+        .get("ops")
+        .and_then(Value::as_array)
+        .ok_or_else(|| DomainError::InvalidData("'ops' must be an array".into()))?;
+    let ops = raw
+        .iter()
+        .enumerate()
+        .map(|(idx, value)| parse_write_op(value, idx))
+        .collect::<Result<Vec<_>, _>>()?;
+    let ops_applied = ops.len();
+    let pack = uc
+        .apply_ops_checked(ApplyOpsRequest {
+            identifier,
+            expected_revision,
+            ops,
+            validate_only: args
+                .get("validate_only")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            reason: str_opt(args, "reason"),
+        })
+        .await?;
+    let mut payload = serde_json::to_value(pack)?;
+    payload["ops_applied"] = json!(ops_applied);
+    tool_success("write", payload)
+}
+
+fn parse_write_op(value: &Value, idx: usize) -> Result<WriteOp, DomainError> {
+    let obj = value
+        .as_object()
+        .ok_or_else(|| DomainError::InvalidData(format!("ops[{idx}] must be an object")))?;
+    let opt_str = |key: &str| document_opt_str(obj, key);
+    let req_str = |key: &str| {
+        opt_str(key)
+            .ok_or_else(|| DomainError::InvalidData(format!("ops[{idx}].{key} is required")))
+    };
+    let opt_usize = |key: &str| {
+        obj.get(key)
+            .map(|value| {
+                value
+                    .as_u64()
+                    .and_then(|v| usize::try_from(v).ok())
+                    .ok_or_else(|| {
+                        DomainError::InvalidData(format!(
+                            "ops[{idx}].{key} must be a non-negative integer"
+                        ))
+                    })
+            })
+            .transpose()
+    };
+    let op = req_str("op")?;
+    Ok(match op.as_str() {
+        "upsert_section" => WriteOp::UpsertSection {
+            section_key: req_str("section_key")?,
+            title: req_str("title")?,
+            description: opt_str("description"),
+            order: opt_usize("order")?,
+        },
+        "delete_section" => WriteOp::DeleteSection {
+            section_key: req_str("section_key")?,
+        },
+        "upsert_ref" => {
+            let symbol = opt_str("symbol");
+            let url = opt_str("url");
+            let path = match &url {
+                Some(_) => opt_str("path").unwrap_or_default(),
+                None => req_str("path")?,
+            };
+            // Same rangeless refs as in `document`: lines default to 1.
+            let rangeless = symbol.is_some()
+                || url.is_some()
+                || glob::is_pattern(&path)
+                || PackSectionRef::is_pack_ref(&path);
+            let line = |key: &str| match opt_usize(key)? {
+                Some(line) => Ok(line),
+                None if rangeless => Ok(1),
+                None => Err(DomainError::InvalidData(format!(
+                    "ops[{idx}].{key} is required"
+                ))),
+            };
+            WriteOp::UpsertRef(UpsertRefRequest {
+                section_key: req_str("section_key")?,
+                ref_key: req_str("ref_key")?,
+                root: opt_str("root"),
+                path,
+                line_start: line("line_start")?,
+                line_end: line("line_end")?,
+                title: opt_str("title"),
+                why: opt_str("why"),
+                group: opt_str("group"),
+                symbol,
+                url,
+                excerpt_line_limit: opt_usize("excerpt_line_limit")?,
+            })
+        }
+        "delete_ref" => WriteOp::DeleteRef {
+            section_key: req_str("section_key")?,
+            ref_key: req_str("ref_key")?,
+        },
+        "set_meta" => WriteOp::SetMeta {
+            title: opt_str("title"),
+            brief: opt_str("brief"),
+            tags: obj
+                .get("tags")
+                .map(|tags| {
+                    tags.as_array()
+                        .and_then(|tags| {
+                            tags.iter()
+                                .map(|tag| tag.as_str().map(str::to_string))
+                                .collect::<Option<Vec<_>>>()
+                        })
+                        .ok_or_else(|| {
+                            DomainError::InvalidData(format!(
+                                "ops[{idx}].tags must be an array of strings"
+                            ))
+                        })
+                })
+                .transpose()?,
+        },
+        "set_status" => WriteOp::SetStatus {
+            status: req_str("status")?.parse()?,
+        },
+        other => {
+            return Err(DomainError::DetailedInvalidData {
+                message: format!(
+                    "ops[{idx}].op '{other}' must be one of: {}",
+                    WRITE_OP_NAMES.join(", ")
+                ),
+                details: json!({
+                    "tool": "input",
+                    "action": "write",
+                    "field": format!("ops[{idx}].op"),
+                    "allowed_ops": WRITE_OP_NAMES,
+                }),
+            })
+        }
+    })
+}
+
+async fn handle_import_markdown_action(
+    args: &Value,
+    uc: &InputUseCases,
+) -> Result<Value, DomainError> {
+    let markdown = args
+        .get("markdown")
+        .and_then(Value::as_str)
+        .filter(|raw| !raw.trim().is_empty())
+        .ok_or_else(|| DomainError::DetailedInvalidData {
+            message: "input import_markdown requires a non-empty 'markdown' string".into(),
+            details: json!({
+                "tool": "input",
+                "action": "import_markdown",
+                "required_fields": ["markdown"],
+            }),
+        })?;
+    let request = ImportMarkdownRequest {
+        markdown: markdown.to_string(),
+        name: str_opt(args, "new_name"),
+        tags: str_list_opt(args, "tags")?.unwrap_or_default(),
+        ttl_minutes: u64_opt(args, "ttl_minutes")?,
+        validate_only: args
+            .get("validate_only")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        reason: str_opt(args, "reason"),
+        owner: str_opt(args, "actor"),
+    };
+    let (pack, ttl_source) = uc.import_markdown(request).await?;
+    let mut payload = serde_json::to_value(pack)?;
+    payload["ttl_source"] = json!(ttl_source.to_string());
+    tool_success("import_markdown", payload)
+}
+
+async fn handle_create_from_template_action(
+    args: &Value,
+    uc: &InputUseCases,
+) -> Result<Value, DomainError> {
+    let template: PackTemplate = str_opt(args, "template")
+        .ok_or_else(|| DomainError::DetailedInvalidData {
+            message: "input create_from_template requires 'template' (audit|remediation|research)"
+                .into(),
+            details: json!({
+                "tool": "input",
+                "action": "create_from_template",
+                "required_fields": ["template"],
+                "allowed_templates": PackTemplate::ALL.map(|t| t.to_string()),
+            }),
+        })?
+        .parse()?;
+    let request = CreateFromTemplateRequest {
+        template,
+        name: str_opt(args, "new_name"),
+        title: str_opt(args, "new_title"),
+        tags: str_list_opt(args, "tags")?.unwrap_or_default(),
+        ttl_minutes: u64_opt(args, "ttl_minutes")?,
+        validate_only: args
+            .get("validate_only")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        reason: str_opt(args, "reason"),
+        owner: str_opt(args, "actor"),
+    };
+    let (pack, ttl_source) = uc.create_from_template(request).await?;
+    let mut payload = serde_json::to_value(pack)?;
+    payload["ttl_source"] = json!(ttl_source.to_string());
+    payload["template"] = json!(template.to_string());
+    tool_success("create_from_template", payload)
+}
+
+async fn handle_estimate_action(args: &Value, uc: &InputUseCases) -> Result<Value, DomainError> {
+    reject_legacy_write_contract(args)?;
+    let request = parse_write_snapshot_request(args)?;
+    let request_bytes = args
+        .get("document")
+        .map(|document| serde_json::to_string(document).map(|encoded| encoded.len()))
+        .transpose()?
+        .unwrap_or(0);
+    let estimate = uc.estimate_snapshot(request).await?;
+    let mut payload = serde_json::to_value(estimate)?;
+    payload["request_bytes"] = json!(request_bytes);
+    tool_success("estimate", payload)
 }
 
 fn reject_legacy_write_contract(args: &Value) -> Result<(), DomainError> {
@@ -244,6 +1183,7 @@ fn parse_write_snapshot_request(args: &Value) -> Result<WriteSnapshotRequest, Do
             key: req_document_str(section_obj, "key")?,
             title: req_document_str(section_obj, "title")?,
             description: document_opt_str(section_obj, "description"),
+            translations: parse_document_translations(section_obj.get("translations"))?,
             refs,
             diagrams,
         });
@@ -262,6 +1202,10 @@ fn parse_write_snapshot_request(args: &Value) -> Result<WriteSnapshotRequest, Do
             .get("validate_only")
             .and_then(Value::as_bool)
             .unwrap_or(false),
+        snapshot_excerpts: args
+            .get("snapshot_excerpts")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
         document: SnapshotDocument {
             name: document_opt_str(document_obj, "name"),
             title: document_opt_str(document_obj, "title"),
@@ -270,7 +1214,10 @@ fn parse_write_snapshot_request(args: &Value) -> Result<WriteSnapshotRequest, Do
             ttl_minutes,
             status: parse_document_status(document_obj.get("status"))?,
             sections: parsed_sections,
+            read_defaults: parse_document_read_defaults(document_obj.get("read_defaults"))?,
         },
+        reason: str_opt(args, "reason"),
+        owner: str_opt(args, "actor"),
     })
 }
 
@@ -286,6 +1233,60 @@ fn parse_document_status(value: Option<&Value>) -> Result<Status, DomainError> {
     }
 }
 
+fn parse_document_read_defaults(raw: Option<&Value>) -> Result<ReadDefaults, DomainError> {
+    let Some(raw) = raw else {
+        return Ok(ReadDefaults::default());
+    };
+    let obj = raw.as_object().ok_or_else(|| {
+        DomainError::InvalidData("document.read_defaults must be an object".into())
+    })?;
+    let profile = match obj.get("profile") {
+        None => None,
+        Some(value) => Some(
+            value
+                .as_str()
+                .ok_or_else(|| {
+                    DomainError::InvalidData(
+                        "document.read_defaults.profile must be a string".into(),
+                    )
+                })?
+                .parse::<OutputProfile>()?,
+        ),
+    };
+    let limit = match obj.get("limit") {
+        None => None,
+        Some(value) => Some(
+            value
+                .as_u64()
+                .and_then(|v| usize::try_from(v).ok())
+                .ok_or_else(|| {
+                    DomainError::InvalidData(
+                        "document.read_defaults.limit must be a positive integer".into(),
+                    )
+                })?,
+        ),
+    };
+    let excerpt_line_limit = match obj.get("excerpt_line_limit") {
+        None => None,
+        Some(value) => Some(
+            value
+                .as_u64()
+                .and_then(|v| usize::try_from(v).ok())
+                .ok_or_else(|| {
+                    DomainError::InvalidData(
+                        "document.read_defaults.excerpt_line_limit must be a positive integer"
+                            .into(),
+                    )
+                })?,
+        ),
+    };
+    Ok(ReadDefaults {
+        profile,
+        limit,
+        excerpt_line_limit,
+    })
+}
+
 fn parse_document_tags(raw: Option<&Value>) -> Result<Vec<String>, DomainError> {
     let Some(raw_tags) = raw else {
         return Ok(Vec::new());
@@ -304,6 +1305,25 @@ fn parse_document_tags(raw: Option<&Value>) -> Result<Vec<String>, DomainError>
     Ok(parsed)
 }
 
+fn parse_document_translations(raw: Option<&Value>) -> Result<Vec<(String, String)>, DomainError> {
+    let Some(raw_translations) = raw else {
+        return Ok(Vec::new());
+    };
+
+    let translations = raw_translations
+        .as_object()
+        .ok_or_else(|| DomainError::InvalidData("section.translations must be an object".into()))?;
+    translations
+        .iter()
+        .map(|(lang, text)| {
+            let text = text.as_str().ok_or_else(|| {
+                DomainError::InvalidData("section.translations values must be strings".into())
+            })?;
+            Ok((lang.clone(), text.to_string()))
+        })
+        .collect()
+}
+
 fn parse_document_refs(raw: Option<&Value>) -> Result<Vec<SnapshotRef>, DomainError> {
     let Some(raw_refs) = raw else {
         return Ok(Vec::new());
@@ -317,14 +1337,41 @@ fn parse_document_refs(raw: Option<&Value>) -> Result<Vec<SnapshotRef>, DomainEr
         let obj = value
             .as_object()
             .ok_or_else(|| DomainError::InvalidData("ref must be an object".into()))?;
+        let symbol = document_opt_str(obj, "symbol");
+        let url = document_opt_str(obj, "url");
+        // Url refs have no path; the use case derives one from the link.
+        let path = match &url {
+            Some(_) => document_opt_str(obj, "path").unwrap_or_default(),
+            None => req_document_str(obj, "path")?,
+        };
+        // Symbol refs get their range from the source; directory/glob, url
+        // and `pack:<id>#<section_key>` refs have none. Lines are optional
+        // for all of them.
+        let rangeless = symbol.is_some()
+            || url.is_some()
+            || glob::is_pattern(&path)
+            || PackSectionRef::is_pack_ref(&path);
+        let line = |key: &str| match obj.get(key) {
+            None if rangeless => Ok(1),
+            _ => req_document_usize(obj, key),
+        };
         out.push(SnapshotRef {
             key: req_document_str(obj, "key")?,
-            path: req_document_str(obj, "path")?,
-            line_start: req_document_usize(obj, "line_start")?,
-            line_end: req_document_usize(obj, "line_end")?,
+            root: document_opt_str(obj, "root"),
+            path,
+            line_start: line("line_start")?,
+            line_end: line("line_end")?,
             title: document_opt_str(obj, "title"),
             why: document_opt_str(obj, "why"),
             group: document_opt_str(obj, "group"),
+            entry_point: obj
+                .get("entry_point")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            git_sha: document_opt_str(obj, "git_sha"),
+            symbol,
+            url,
+            excerpt_line_limit: document_opt_usize(obj, "excerpt_line_limit")?,
         });
     }
     Ok(out)
@@ -375,6 +1422,22 @@ fn req_document_usize(
         .map_err(|_| DomainError::InvalidData(format!("document.{} is out of range", key)))
 }
 
+fn document_opt_usize(
+    obj: &serde_json::Map<String, Value>,
+    key: &str,
+) -> Result<Option<usize>, DomainError> {
+    obj.get(key)
+        .map(|value| {
+            value
+                .as_u64()
+                .and_then(|v| usize::try_from(v).ok())
+                .ok_or_else(|| {
+                    DomainError::InvalidData(format!("document.{} must be a positive integer", key))
+                })
+        })
+        .transpose()
+}
+
 fn document_u64(value: &Value) -> Result<u64, DomainError> {
     value
         .as_u64()
@@ -462,8 +1525,9 @@ fn unsupported_input_action(action: &str) -> DomainError {
         },
         _ => DomainError::DetailedInvalidData {
             message: format!(
-                "unknown input action '{}'; allowed actions: list, get, write, ttl, delete",
-                action
+                "unknown input action '{}'; allowed actions: {}",
+                action,
+                INPUT_ALLOWED_ACTIONS.join(", ")
             ),
             details: json!({
                 "tool": "input",
@@ -479,7 +1543,7 @@ fn pack_with_freshness_metadata(pack: Pack) -> Result<Value, DomainError> {
     let now = chrono::Utc::now();
     let ttl_remaining_seconds = pack.ttl_remaining_seconds(now);
     let ttl_remaining_human = pack.ttl_remaining_human(now);
-    let freshness_state = FreshnessState::from_ttl_seconds(ttl_remaining_seconds);
+    let freshness_state = FreshnessState::from_pack(&pack, now);
     let mut payload = serde_json::to_value(pack)?;
     let object = payload.as_object_mut().ok_or_else(|| {
         DomainError::InvalidData("internal error: expected pack payload object".into())
@@ -1,17 +1,37 @@
 use serde_json::{json, Value};
 use std::collections::HashSet;
 
-use crate::app::output_usecases::{OutputProfile, OutputReadRequest, OutputUseCases};
-use crate::app::ports::FreshnessState;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use crate::app::output_usecases::{
+    ExcerptCleanup, OutputProfile, OutputReadRequest, OutputUseCases, WatchOutcome, WatchReason,
+};
+use crate::app::ports::{ExportedFile, FreshnessState, SavedFilterPort};
 use crate::domain::errors::DomainError;
 use crate::domain::models::Pack;
-use crate::domain::types::PackId;
+use crate::domain::types::{LanguageTag, PackId};
+
+use super::continuation::OutputContinuations;
+use super::host_defaults::AppliedHostDefaults;
+use super::{
+    list_filter_from_args, req_identifier, status_opt, str_opt, tool_text_success,
+    tool_text_with_structure, u64_opt, usize_opt,
+};
 
-use super::{freshness_opt, req_identifier, status_opt, str_opt, tool_text_success, usize_opt};
+const OUTPUT_ALLOWED_ACTIONS: [&str; 6] =
+    ["list", "read", "read_delta", "watch", "graph", "continue"];
+
+/// `output watch` timeout bounds, in seconds.
+const WATCH_DEFAULT_TIMEOUT_SECONDS: u64 = 60;
+const WATCH_MAX_TIMEOUT_SECONDS: u64 = 600;
 
 pub(super) async fn handle_output_tool(
     args: &Value,
     uc: &OutputUseCases,
+    saved_filters: &dyn SavedFilterPort,
+    host_defaults: Option<&AppliedHostDefaults>,
+    continuations: &OutputContinuations,
 ) -> Result<Value, DomainError> {
     reject_output_format_param(args)?;
 
@@ -25,25 +45,126 @@ pub(super) async fn handle_output_tool(
         .and_then(|v| v.as_str())
         .unwrap_or(if has_identity { "read" } else { "list" });
 
+    if action == "continue" {
+        let token = str_opt(args, "continuation_token").ok_or_else(|| {
+            DomainError::DetailedInvalidData {
+                message: "output continue requires 'continuation_token'".into(),
+                details: json!({
+                    "tool": "output",
+                    "action": "continue",
+                    "required_fields": ["continuation_token"],
+                }),
+            }
+        })?;
+        return continuations.next(&token);
+    }
+    let result = dispatch_output_action(action, args, uc, saved_filters, host_defaults).await?;
+    Ok(continuations.fit(result))
+}
+
+async fn dispatch_output_action(
+    action: &str,
+    args: &Value,
+    uc: &OutputUseCases,
+    saved_filters: &dyn SavedFilterPort,
+    host_defaults: Option<&AppliedHostDefaults>,
+) -> Result<Value, DomainError> {
     match action {
         "list" => {
-            let status = status_opt(args, "status")?;
-            let freshness = freshness_opt(args, "freshness")?;
-            let query = str_opt(args, "query");
-            let limit = usize_opt(args, "limit")?;
-            let offset = usize_opt(args, "offset")?;
-            let packs = uc
-                .list_filtered_with_freshness(status, query, limit, offset, freshness)
-                .await?;
-            tool_text_success(format_pack_list_markdown(&packs))
+            let filter = list_filter_from_args(args, saved_filters).await?;
+            let packs = uc.list_matching(filter).await?;
+            let mut out = format_pack_list_markdown(&packs);
+            if args
+                .get("machine_block")
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+            {
+                append_pack_list_block(&mut out, &packs, chrono::Utc::now());
+            }
+            tool_text_success(out)
         }
         "read" => {
             let ident = req_output_identifier(args)?;
-            let request = build_output_get_request(args)?;
+            if let Some(against_revision) = u64_opt(args, "diff_against_revision")? {
+                if args.get("page_token").is_some()
+                    || args.get("offset").is_some()
+                    || args.get("export_path").is_some()
+                {
+                    return Err(DomainError::DetailedInvalidData {
+                        message: "output read 'diff_against_revision' renders one page; drop 'page_token'/'offset'/'export_path'".into(),
+                        details: json!({
+                            "tool": "output",
+                            "action": "read",
+                            "conflicting_fields": ["diff_against_revision", "page_token", "offset", "export_path"],
+                        }),
+                    });
+                }
+                let out_str = uc
+                    .get_revision_diff_rendered(&ident, against_revision)
+                    .await?;
+                return tool_text_success(append_selection_metadata(&ident, out_str));
+            }
+            let request = build_output_get_request(args, host_defaults)?;
+            if let Some(export_path) = str_opt(args, "export_path") {
+                let conflicting: Vec<&str> = ["limit", "offset", "page_token"]
+                    .into_iter()
+                    .filter(|field| args.get(*field).is_some())
+                    .collect();
+                if !conflicting.is_empty() {
+                    return Err(DomainError::DetailedInvalidData {
+                        message: "output read 'export_path' writes the whole pack; drop 'limit'/'offset'/'page_token'".into(),
+                        details: json!({
+                            "tool": "output",
+                            "action": "read",
+                            "conflicting_fields": conflicting,
+                        }),
+                    });
+                }
+                let exported = uc.export_rendered(&ident, request, &export_path).await?;
+                return tool_text_success(format_export_markdown(&ident, &exported));
+            }
+            if args.get("structured").and_then(Value::as_bool) == Some(true) {
+                let read = uc.read_structured(&ident, request).await?;
+                let out_str = append_selection_metadata(&ident, read.markdown);
+                return tool_text_with_structure(out_str, serde_json::to_value(read.structure)?);
+            }
             let out_str = uc.get_rendered_with_request(&ident, request).await?;
             let out_str = append_selection_metadata(&ident, out_str);
             tool_text_success(out_str)
         }
+        "read_delta" => {
+            let ident = req_output_identifier(args)?;
+            let since_revision = u64_opt(args, "since_revision")?.ok_or_else(|| {
+                DomainError::DetailedInvalidData {
+                    message: "output read_delta requires 'since_revision'".into(),
+                    details: json!({
+                        "tool": "output",
+                        "action": "read_delta",
+                        "required_fields": ["since_revision"],
+                    }),
+                }
+            })?;
+            let lang = str_opt(args, "lang")
+                .map(|raw| LanguageTag::new(&raw))
+                .transpose()?;
+            let out_str = uc.get_delta_rendered(&ident, since_revision, lang).await?;
+            tool_text_success(append_selection_metadata(&ident, out_str))
+        }
+        "watch" => {
+            let ident = req_output_identifier(args)?;
+            let after_revision = u64_opt(args, "after_revision")?;
+            let timeout_seconds = u64_opt(args, "timeout_seconds")?
+                .unwrap_or(WATCH_DEFAULT_TIMEOUT_SECONDS)
+                .min(WATCH_MAX_TIMEOUT_SECONDS);
+            let outcome = uc
+                .watch(&ident, after_revision, Duration::from_secs(timeout_seconds))
+                .await?;
+            tool_text_success(format_watch_markdown(&outcome))
+        }
+        "graph" => {
+            let filter = list_filter_from_args(args, saved_filters).await?;
+            tool_text_success(uc.graph(filter).await?)
+        }
         _ => Err(unsupported_output_action(action)),
     }
 }
@@ -63,6 +184,40 @@ pub(super) fn reject_output_format_param(args: &Value) -> Result<(), DomainError
     Ok(())
 }
 
+fn format_watch_markdown(outcome: &WatchOutcome) -> String {
+    let mut out = String::from("[LEGEND]\n");
+    let _ = writeln!(out, "- id: {}", outcome.pack_id);
+    let _ = writeln!(out, "- outcome: {}", outcome.reason);
+    let _ = writeln!(out, "- revision: {}", outcome.revision);
+    let _ = writeln!(out, "- status: {}", outcome.status);
+    let _ = writeln!(out, "- waited_ms: {}", outcome.waited_ms);
+    let next = match outcome.reason {
+        WatchReason::TimedOut => format!(
+            "output watch {{\"action\":\"watch\",\"id\":\"{}\",\"after_revision\":{}}}",
+            outcome.pack_id, outcome.revision
+        ),
+        WatchReason::Gone => "output list (the pack was deleted or purged)".to_string(),
+        WatchReason::RevisionAdvanced | WatchReason::Finalized => format!(
+            "output read {{\"action\":\"read\",\"id\":\"{}\"}}",
+            outcome.pack_id
+        ),
+    };
+    let _ = writeln!(out, "- next: {}", next);
+    out
+}
+
+fn format_export_markdown(ident: &str, exported: &ExportedFile) -> String {
+    let mut out = String::from("[LEGEND]\n");
+    let _ = writeln!(out, "- id: {}", ident);
+    let _ = writeln!(out, "- exported_to: {}", exported.path);
+    let _ = writeln!(out, "- bytes: {}", exported.bytes);
+    let _ = writeln!(
+        out,
+        "- next: read the file; it holds every page of the render"
+    );
+    out
+}
+
 fn format_pack_list_markdown(packs: &[Pack]) -> String {
     if packs.is_empty() {
         return "No context packs found.".to_string();
@@ -82,6 +237,13 @@ fn format_pack_list_markdown(packs: &[Pack]) -> String {
             "- `{}` — {} (revision `{}`, ttl `{}`, freshness `{}`)",
             pack.id, title, pack.revision, ttl, freshness
         ));
+        if let Some(latest) = pack.sign_offs.last() {
+            out.push_str(&format!(
+                " [sign_offs: {}, latest {}]",
+                pack.sign_offs.len(),
+                latest.verdict
+            ));
+        }
         if let Some(warning) = freshness.warning_text() {
             out.push_str(&format!(" [warning: {}]", warning));
         }
@@ -90,6 +252,79 @@ fn format_pack_list_markdown(packs: &[Pack]) -> String {
     out
 }
 
+/// Version of the `[PACK_LIST]` block; bumped only when a key is removed or
+/// changes meaning. New keys may be appended to a block without a bump.
+const PACK_LIST_BLOCK_VERSION: u32 = 1;
+
+/// Per-pack keys of the `[PACK_LIST]` block, always present and in this order.
+const PACK_LIST_KEYS: [&str; 11] = [
+    "id",
+    "name",
+    "title",
+    "status",
+    "revision",
+    "freshness",
+    "tags",
+    "sign_offs",
+    "latest_verdict",
+    "updated_at",
+    "expires_at",
+];
+
+/// `list machine_block=true`: a fixed-key block after the human list, one
+/// `[PACK]` per pack. Every key is present; absent values are `-` and values
+/// never span lines, so `- key: value` splits on the first `: `.
+fn append_pack_list_block(out: &mut String, packs: &[Pack], now: chrono::DateTime<chrono::Utc>) {
+    let one_line = |value: &str| {
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        if value.is_empty() {
+            "-".to_string()
+        } else {
+            value
+        }
+    };
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    let _ = writeln!(out, "\n[PACK_LIST]");
+    let _ = writeln!(out, "- version: {}", PACK_LIST_BLOCK_VERSION);
+    let _ = writeln!(out, "- count: {}", packs.len());
+    let _ = writeln!(out, "- keys: {}", PACK_LIST_KEYS.join(","));
+    let _ = writeln!(
+        out,
+        "- generated_at: {}",
+        now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    );
+    for pack in packs {
+        let tags = pack.tags.join(",");
+        let values = [
+            pack.id.to_string(),
+            pack.name
+                .as_ref()
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            pack.title.clone().unwrap_or_default(),
+            pack.status.to_string(),
+            pack.revision.to_string(),
+            FreshnessState::from_pack(pack, now).to_string(),
+            tags,
+            pack.sign_offs.len().to_string(),
+            pack.sign_offs
+                .last()
+                .map(|s| s.verdict.to_string())
+                .unwrap_or_default(),
+            pack.updated_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            pack.expires_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        ];
+        let _ = writeln!(out, "\n[PACK]");
+        for (key, value) in PACK_LIST_KEYS.iter().zip(values) {
+            let _ = writeln!(out, "- {}: {}", key, one_line(&value));
+        }
+    }
+}
+
 fn output_profile_opt(args: &Value) -> Result<Option<OutputProfile>, DomainError> {
     let Some(raw) = args.get("profile").and_then(|v| v.as_str()) else {
         return Ok(None);
@@ -105,35 +340,47 @@ fn unsupported_output_action(action: &str) -> DomainError {
                 "tool": "output",
                 "action": "unsupported",
                 "requested_action": "get",
-                "allowed_actions": ["list", "read"],
+                "allowed_actions": OUTPUT_ALLOWED_ACTIONS,
                 "legacy_mapping": {"action": "read"},
             }),
         }
     } else {
         DomainError::DetailedInvalidData {
             message: format!(
-                "unknown output action '{}'; allowed actions: list, read",
-                action
+                "unknown output action '{}'; allowed actions: {}",
+                action,
+                OUTPUT_ALLOWED_ACTIONS.join(", ")
             ),
             details: json!({
                 "tool": "output",
                 "action": "unknown",
                 "requested_action": action,
-                "allowed_actions": ["list", "read"],
+                "allowed_actions": OUTPUT_ALLOWED_ACTIONS,
             }),
         }
     }
 }
 
-fn build_output_get_request(args: &Value) -> Result<OutputReadRequest, DomainError> {
+fn build_output_get_request(
+    args: &Value,
+    host_defaults: Option<&AppliedHostDefaults>,
+) -> Result<OutputReadRequest, DomainError> {
     let status_filter = status_opt(args, "status")?;
-    let profile = output_profile_opt(args)?;
-    let limit = usize_opt(args, "limit")?;
+    let mut profile = output_profile_opt(args)?;
+    let mut limit = usize_opt(args, "limit")?;
     let offset = usize_opt(args, "offset")?;
     reject_legacy_read_fields(args)?;
     let page_token = str_opt(args, "page_token");
     let contains = str_opt(args, "contains");
 
+    // A page token already pins profile/limit from its first page.
+    let mut host = None;
+    if let (Some(applied), None) = (host_defaults, page_token.as_ref()) {
+        profile = profile.or(applied.defaults.profile);
+        limit = limit.or(applied.defaults.limit);
+        host = Some(applied.host.clone());
+    }
+
     Ok(OutputReadRequest {
         status_filter,
         profile,
@@ -141,9 +388,42 @@ fn build_output_get_request(args: &Value) -> Result<OutputReadRequest, DomainErr
         offset,
         page_token,
         contains,
+        host,
+        if_none_match: str_opt(args, "if_none_match"),
+        anchors: str_opt(args, "anchors")
+            .map(|raw| raw.parse())
+            .transpose()?,
+        separator: str_opt(args, "separator")
+            .map(|raw| raw.parse())
+            .transpose()?,
+        lang: str_opt(args, "lang")
+            .map(|raw| LanguageTag::new(&raw))
+            .transpose()?,
+        unpaged: false,
+        excerpt: excerpt_cleanup_from_args(args)?,
+        max_tokens: usize_opt(args, "max_tokens")?,
     })
 }
 
+/// `None` unless one of the clean-up arguments is given, so a page token's
+/// clean-up carries over.
+fn excerpt_cleanup_from_args(args: &Value) -> Result<Option<ExcerptCleanup>, DomainError> {
+    let flag = |key: &str| args.get(key).and_then(Value::as_bool);
+    let trim = flag("trim_trailing_whitespace");
+    let strip_ansi = flag("strip_ansi");
+    let tab_width = usize_opt(args, "tab_width")?;
+    if trim.is_none() && strip_ansi.is_none() && tab_width.is_none() {
+        return Ok(None);
+    }
+    let cleanup = ExcerptCleanup {
+        trim_trailing_whitespace: trim.unwrap_or(false),
+        tab_width,
+        strip_ansi: strip_ansi.unwrap_or(false),
+    };
+    cleanup.validate()?;
+    Ok(Some(cleanup))
+}
+
 fn reject_legacy_read_fields(args: &Value) -> Result<(), DomainError> {
     if args.get("mode").is_some() {
         return Err(DomainError::DetailedInvalidData {
@@ -160,6 +440,7 @@ fn reject_legacy_read_fields(args: &Value) -> Result<(), DomainError> {
                     "offset",
                     "page_token",
                     "contains",
+                    "if_none_match",
                     "id",
                     "name",
                     "status"
@@ -326,13 +607,50 @@ fn legend_value(markdown: &str, key: &str) -> Option<String> {
 mod tests {
     use serde_json::json;
 
+    use crate::adapters::mcp_stdio::host_defaults::{AppliedHostDefaults, HostOutputDefaults};
     use crate::app::output_usecases::OutputProfile;
 
+    use crate::domain::models::Pack;
+    use crate::domain::types::{PackId, PackName};
+
     use super::{
-        append_selection_metadata, build_output_get_request, legend_lines, legend_value,
-        reject_legacy_read_fields,
+        append_pack_list_block, append_selection_metadata, build_output_get_request, legend_lines,
+        legend_value, reject_legacy_read_fields, PACK_LIST_KEYS,
     };
 
+    #[test]
+    fn pack_list_block_has_every_key_on_one_line_per_pack() {
+        let mut named = Pack::new(PackId::new(), Some(PackName::new("auth").unwrap()));
+        named.title = Some("Auth\naudit".into());
+        named.tags = vec!["security".into(), "q3".into()];
+        let bare = Pack::new(PackId::new(), None);
+
+        let mut out = "# Context packs\n".to_string();
+        append_pack_list_block(&mut out, &[named.clone(), bare], chrono::Utc::now());
+        let block = &out[out.find("[PACK_LIST]").unwrap()..];
+        assert!(block.contains("- version: 1\n- count: 2\n"), "{block}");
+
+        let packs: Vec<Vec<(&str, &str)>> = block
+            .split("\n[PACK]\n")
+            .skip(1)
+            .map(|pack| {
+                pack.lines()
+                    .filter_map(|line| line.strip_prefix("- ")?.split_once(": "))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(packs.len(), 2);
+        for pack in &packs {
+            let keys: Vec<&str> = pack.iter().map(|(key, _)| *key).collect();
+            assert_eq!(keys, PACK_LIST_KEYS);
+        }
+        assert_eq!(packs[0][0], ("id", named.id.as_str()));
+        assert_eq!(packs[0][2], ("title", "Auth audit"));
+        assert_eq!(packs[0][6], ("tags", "security,q3"));
+        assert_eq!(packs[1][1], ("name", "-"));
+        assert_eq!(packs[1][8], ("latest_verdict", "-"));
+    }
+
     #[test]
     fn selected_metadata_remains_present_when_content_contains_marker_substrings() {
         let markdown = r#"[LEGEND]
@@ -410,7 +728,7 @@ noop
 
     #[test]
     fn default_output_read_uses_orchestrator_profile() {
-        let request = build_output_get_request(&json!({ "id": "pk_aaaaaaaa" }))
+        let request = build_output_get_request(&json!({ "id": "pk_aaaaaaaa" }), None)
             .expect("default request must parse");
         assert_eq!(request.profile, None);
         assert_eq!(request.limit, None);
@@ -418,10 +736,13 @@ noop
 
     #[test]
     fn explicit_profile_is_parsed() {
-        let request = build_output_get_request(&json!({
+        let request = build_output_get_request(
+            &json!({
             "id": "pk_aaaaaaaa",
             "profile": "reviewer"
-        }))
+            }),
+            None,
+        )
         .expect("explicit profile request must parse");
         assert_eq!(request.profile, Some(OutputProfile::Reviewer));
         assert_eq!(request.limit, None);
@@ -429,16 +750,51 @@ noop
 
     #[test]
     fn page_token_is_forwarded_without_profile_override() {
-        let request = build_output_get_request(&json!({
+        let request = build_output_get_request(
+            &json!({
             "id": "pk_aaaaaaaa",
             "page_token": "v1:deadbeef"
-        }))
+            }),
+            None,
+        )
         .expect("page_token request must parse");
         assert_eq!(request.profile, None);
         assert_eq!(request.limit, None);
         assert_eq!(request.page_token.as_deref(), Some("v1:deadbeef"));
     }
 
+    #[test]
+    fn host_defaults_fill_unset_profile_and_limit_only() {
+        let applied = AppliedHostDefaults {
+            host: "tight-host".into(),
+            defaults: HostOutputDefaults {
+                profile: Some(OutputProfile::Executor),
+                limit: Some(3),
+            },
+        };
+        let request = build_output_get_request(&json!({ "id": "pk_aaaaaaaa" }), Some(&applied))
+            .expect("host default request must parse");
+        assert_eq!(request.profile, Some(OutputProfile::Executor));
+        assert_eq!(request.limit, Some(3));
+        assert_eq!(request.host.as_deref(), Some("tight-host"));
+
+        let explicit = build_output_get_request(
+            &json!({ "id": "pk_aaaaaaaa", "profile": "reviewer", "limit": 9 }),
+            Some(&applied),
+        )
+        .expect("explicit request must parse");
+        assert_eq!(explicit.profile, Some(OutputProfile::Reviewer));
+        assert_eq!(explicit.limit, Some(9));
+
+        let paged = build_output_get_request(
+            &json!({ "id": "pk_aaaaaaaa", "page_token": "v1:deadbeef" }),
+            Some(&applied),
+        )
+        .expect("page_token request must parse");
+        assert_eq!(paged.profile, None);
+        assert_eq!(paged.limit, None);
+    }
+
     #[test]
     fn legacy_read_fields_are_rejected() {
         let mode = reject_legacy_read_fields(&json!({"mode":"full"}))
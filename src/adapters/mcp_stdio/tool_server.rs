@@ -0,0 +1,78 @@
+use serde_json::{json, Value};
+
+use crate::app::metrics::ServerMetrics;
+use crate::app::ports::{BackupPort, HealthProbePort};
+use crate::app::source_watch::SourceWatch;
+use crate::app::supervisor::TaskSupervisor;
+use crate::domain::errors::DomainError;
+
+use super::{tool_success, UnknownMethodCounts};
+
+const SERVER_ALLOWED_ACTIONS: [&str; 3] = ["backup", "stats", "health"];
+
+pub(super) async fn handle_server_tool(
+    args: &Value,
+    backup: &dyn BackupPort,
+    tasks: &TaskSupervisor,
+    metrics: &ServerMetrics,
+    health_probe: Option<&dyn HealthProbePort>,
+    source_watch: Option<&SourceWatch>,
+    unknown: &UnknownMethodCounts,
+) -> Result<Value, DomainError> {
+    let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("");
+
+    match action {
+        "backup" => {
+            let summary = backup.backup().await?;
+            tool_success("backup", serde_json::to_value(summary)?)
+        }
+        "stats" => {
+            let mut stats = json!({
+                "server": metrics.snapshot(),
+                "unknown_methods": {
+                    "total": unknown.total(),
+                    "notifications": unknown.notifications,
+                    "requests": unknown.requests,
+                }
+            });
+            if let Some(watch) = source_watch {
+                stats["source_watch"] = json!({ "stale_packs": watch.marked() });
+            }
+            tool_success("stats", stats)
+        }
+        "health" => {
+            let checks = match health_probe {
+                Some(probe) => probe.probe().await,
+                None => Vec::new(),
+            };
+            let status = if checks.iter().any(|check| !check.ok) {
+                "unhealthy"
+            } else if tasks.is_degraded() {
+                "degraded"
+            } else {
+                "ok"
+            };
+            tool_success(
+                "health",
+                json!({
+                    "status": status,
+                    "checks": checks,
+                    "background_tasks": tasks.health(),
+                }),
+            )
+        }
+        _ => Err(DomainError::DetailedInvalidData {
+            message: format!(
+                "unknown server action '{}'; allowed actions: {}",
+                action,
+                SERVER_ALLOWED_ACTIONS.join(", ")
+            ),
+            details: json!({
+                "tool": "server",
+                "action": "unknown",
+                "requested_action": action,
+                "allowed_actions": SERVER_ALLOWED_ACTIONS,
+            }),
+        }),
+    }
+}
@@ -0,0 +1,160 @@
+use serde::Serialize;
+use serde_json::Value;
+use tokio::time::Instant;
+
+use super::transport::TransportMode;
+
+pub const TRACE_FRAMES_ENV: &str = "CONTEXT_PACK_TRACE_FRAMES";
+/// Tracing target of frame logs; `main` enables it at debug level when
+/// [`TRACE_FRAMES_ENV`] is set.
+pub const FRAME_TRACE_TARGET: &str = "mcp_context_pack::frames";
+
+const PREVIEW_MAX_CHARS: usize = 240;
+/// String values under these keys are protocol vocabulary, not user data, and
+/// are shown verbatim in previews. The tool name (`params.name`) is too.
+const VERBATIM_KEYS: [&str; 4] = ["jsonrpc", "method", "action", "protocolVersion"];
+
+pub fn trace_frames_enabled() -> bool {
+    std::env::var(TRACE_FRAMES_ENV)
+        .map(|raw| matches!(raw.trim(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+/// Debug logging of every inbound and outbound frame: transport mode, method,
+/// id, byte size, handling time, and a redacted preview. Off unless
+/// `CONTEXT_PACK_TRACE_FRAMES=1`; costs nothing when off.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct FrameTracer {
+    enabled: bool,
+}
+
+impl FrameTracer {
+    pub(super) fn from_env() -> Self {
+        Self {
+            enabled: trace_frames_enabled(),
+        }
+    }
+
+    pub(super) fn inbound(&self, raw: &str, mode: TransportMode) {
+        if !self.enabled {
+            return;
+        }
+        let parsed = serde_json::from_str::<Value>(raw).ok();
+        let field = |key: &str| {
+            parsed
+                .as_ref()
+                .and_then(|v| v.get(key))
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "-".to_string())
+        };
+        tracing::debug!(
+            target: FRAME_TRACE_TARGET,
+            "frame in: mode={} method={} id={} bytes={} preview={}",
+            mode_label(mode),
+            field("method"),
+            field("id"),
+            raw.len(),
+            parsed
+                .as_ref()
+                .map(redacted_preview)
+                .unwrap_or_else(|| "<unparseable>".to_string())
+        );
+    }
+
+    /// `response` is one envelope or a batch of them (logged as `id=batch(n)`).
+    pub(super) fn outbound(
+        &self,
+        response: &impl Serialize,
+        mode: TransportMode,
+        bytes: usize,
+        received_at: Instant,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let value = serde_json::to_value(response).unwrap_or_default();
+        let id = match &value {
+            Value::Array(items) => format!("batch({})", items.len()),
+            other => other.get("id").map(Value::to_string).unwrap_or_default(),
+        };
+        tracing::debug!(
+            target: FRAME_TRACE_TARGET,
+            "frame out: mode={} id={} bytes={} duration_ms={} preview={}",
+            mode_label(mode),
+            id,
+            bytes,
+            received_at.elapsed().as_millis(),
+            redacted_preview(&value)
+        );
+    }
+}
+
+fn mode_label(mode: TransportMode) -> &'static str {
+    match mode {
+        TransportMode::Framed => "content-length",
+        TransportMode::JsonLine => "json-line",
+    }
+}
+
+/// Compact JSON with every string value replaced by `<str:N>` (its length),
+/// except protocol fields in [`VERBATIM_KEYS`]. Keys, numbers and booleans are
+/// kept so the frame's shape stays readable. Truncated to a few hundred chars.
+fn redacted_preview(value: &Value) -> String {
+    let mut redacted = redact(value, None);
+    if let (Some(tool), Some(params)) = (
+        value.pointer("/params/name").filter(|v| v.is_string()),
+        redacted.get_mut("params"),
+    ) {
+        params["name"] = tool.clone();
+    }
+    let mut preview = redacted.to_string();
+    if let Some((cut, _)) = preview.char_indices().nth(PREVIEW_MAX_CHARS) {
+        preview.truncate(cut);
+        preview.push('…');
+    }
+    preview
+}
+
+fn redact(value: &Value, key: Option<&str>) -> Value {
+    match value {
+        Value::String(s) if key.is_some_and(|k| VERBATIM_KEYS.contains(&k)) => {
+            Value::String(s.clone())
+        }
+        Value::String(s) => Value::String(format!("<str:{}>", s.len())),
+        Value::Array(items) => Value::Array(items.iter().map(|v| redact(v, None)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), redact(v, Some(k))))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_preview_redacts_user_strings_and_keeps_protocol_fields() {
+        let frame = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {
+                "name": "input",
+                "arguments": { "action": "write", "document": { "title": "secret plan" } }
+            }
+        });
+        let preview = redacted_preview(&frame);
+        assert!(preview.contains("\"method\":\"tools/call\""), "{preview}");
+        assert!(preview.contains("\"action\":\"write\""), "{preview}");
+        assert!(preview.contains("\"name\":\"input\""), "{preview}");
+        assert!(preview.contains("\"title\":\"<str:11>\""), "{preview}");
+        assert!(!preview.contains("secret"), "{preview}");
+
+        let long = json!({ "items": vec![1; 500] });
+        assert!(redacted_preview(&long).ends_with('…'));
+    }
+}
@@ -1,8 +1,7 @@
+use serde::Serialize;
 use serde_json::Value;
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter};
 
-use crate::adapters::mcp_stdio::rpc::RpcEnvelope;
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum TransportMode {
     Framed,
@@ -159,12 +158,13 @@ where
     Ok(String::from_utf8_lossy(&buf).into_owned())
 }
 
+/// Writes one response frame; returns the JSON body size in bytes.
 pub(super) async fn write_response<W: tokio::io::AsyncWrite + Unpin>(
     writer: &mut BufWriter<W>,
-    envelope: &RpcEnvelope,
+    response: &impl Serialize,
     mode: TransportMode,
-) -> anyhow::Result<()> {
-    let body = serde_json::to_vec(envelope)?;
+) -> anyhow::Result<usize> {
+    let body = serde_json::to_vec(response)?;
     match mode {
         TransportMode::Framed => {
             let header = format!("Content-Length: {}\r\n\r\n", body.len());
@@ -177,5 +177,5 @@ pub(super) async fn write_response<W: tokio::io::AsyncWrite + Unpin>(
         }
     }
     writer.flush().await?;
-    Ok(())
+    Ok(body.len())
 }
@@ -1,3 +1,29 @@
+pub mod audit_log_fs;
+pub mod backup_tar;
+#[cfg(feature = "chaos")]
+pub mod chaos_storage;
 pub mod code_excerpt_fs;
+pub mod health_fs;
+pub mod markdown_export_fs;
+#[cfg(feature = "http")]
+pub mod mcp_http;
+#[cfg(feature = "stdio")]
 pub mod mcp_stdio;
+pub mod pack_cipher;
+pub mod pack_codec;
+pub mod pack_index_fs;
+pub mod pack_layout;
+pub mod read_only_storage;
+pub mod replay_journal_fs;
+pub mod saved_filters_fs;
+pub mod secret_redaction;
+#[cfg(feature = "watch")]
+pub mod source_watch_fs;
+pub mod storage_factory;
 pub mod storage_json;
+pub mod storage_memory;
+pub mod storage_migration;
+#[cfg(feature = "s3")]
+pub mod storage_s3;
+pub mod store_meta_fs;
+pub mod sync_state_fs;
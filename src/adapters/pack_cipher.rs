@@ -0,0 +1,229 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use std::sync::Arc;
+
+use crate::domain::errors::{DomainError, Result};
+
+pub const ENCRYPTION_KEY_ENV: &str = "CONTEXT_PACK_ENCRYPTION_KEY";
+
+/// Leading bytes of an encrypted pack file; plaintext packs start with `{`.
+const MAGIC: &[u8] = b"cpenc1:";
+const NONCE_BYTES: usize = 12;
+const TAG_BYTES: usize = 16;
+/// Bytes an encrypted file adds on top of the pack JSON.
+pub const ENVELOPE_OVERHEAD: usize = MAGIC.len() + NONCE_BYTES + TAG_BYTES;
+
+/// AES-256-GCM for pack files at rest, keyed by `CONTEXT_PACK_ENCRYPTION_KEY`
+/// (64 hex chars). An encrypted file is `cpenc1:` + a random 96-bit nonce +
+/// ciphertext and tag. Clones share the key schedule.
+#[derive(Clone)]
+pub struct PackCipher {
+    cipher: Arc<Aes256Gcm>,
+}
+
+impl std::fmt::Debug for PackCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PackCipher(AES-256-GCM)")
+    }
+}
+
+impl PackCipher {
+    /// Parses a 256-bit key written as 64 hex chars (`openssl rand -hex 32`).
+    pub fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.trim();
+        let invalid = || {
+            DomainError::InvalidData(format!(
+                "{ENCRYPTION_KEY_ENV}: expected 64 hex chars (a 256-bit key), got {} chars",
+                raw.len()
+            ))
+        };
+        if raw.len() != 64 || !raw.is_ascii() {
+            return Err(invalid());
+        }
+        let mut key = [0u8; 32];
+        for (byte, pair) in key.iter_mut().zip(raw.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+        }
+        Ok(Self {
+            cipher: Arc::new(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))),
+        })
+    }
+
+    pub fn from_env() -> Result<Option<Self>> {
+        match std::env::var(ENCRYPTION_KEY_ENV) {
+            Ok(raw) if !raw.trim().is_empty() => Self::parse(&raw).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Encrypts a pack file's contents under a fresh nonce.
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| DomainError::Io("failed to encrypt pack".to_string()))?;
+        let mut sealed = Vec::with_capacity(ENVELOPE_OVERHEAD + plaintext.len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    fn open(&self, sealed: &[u8], location: &str) -> Result<Vec<u8>> {
+        let body = &sealed[MAGIC.len()..];
+        if body.len() < NONCE_BYTES + TAG_BYTES {
+            return Err(DomainError::DecryptionFailed(format!(
+                "pack '{location}' is truncated"
+            )));
+        }
+        let (nonce, ciphertext) = body.split_at(NONCE_BYTES);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                DomainError::DecryptionFailed(format!(
+                    "pack '{location}' does not decrypt with {ENCRYPTION_KEY_ENV} (wrong key or tampered file)"
+                ))
+            })
+    }
+}
+
+/// Whether `raw` is an encrypted pack file.
+pub fn is_sealed(raw: &[u8]) -> bool {
+    raw.starts_with(MAGIC)
+}
+
+/// The pack JSON in a file read from `location`. Plaintext files pass through
+/// whether or not a key is set, so a store can be encrypted gradually as
+/// packs are rewritten; an encrypted file without the right key is
+/// `DecryptionFailed`.
+pub fn open_pack_bytes(
+    cipher: Option<&PackCipher>,
+    raw: Vec<u8>,
+    location: &str,
+) -> Result<Vec<u8>> {
+    if !is_sealed(&raw) {
+        return Ok(raw);
+    }
+    match cipher {
+        Some(cipher) => cipher.open(&raw, location),
+        None => Err(DomainError::DecryptionFailed(format!(
+            "pack '{location}' is encrypted and {ENCRYPTION_KEY_ENV} is not set"
+        ))),
+    }
+}
+
+/// What to write for a pack file holding `payload`: encrypted with a key,
+/// as-is without.
+pub fn seal_pack_bytes(cipher: Option<&PackCipher>, payload: Vec<u8>) -> Result<Vec<u8>> {
+    match cipher {
+        Some(cipher) => cipher.seal(&payload),
+        None => Ok(payload),
+    }
+}
+
+/// What to write for one line of a line-oriented file (the audit log):
+/// with a key, `cpenc1:` + the hex of nonce, ciphertext and tag, so the file
+/// stays one entry per line; as-is without.
+pub fn seal_line(cipher: Option<&PackCipher>, line: &str) -> Result<String> {
+    let Some(cipher) = cipher else {
+        return Ok(line.to_string());
+    };
+    let sealed = cipher.seal(line.as_bytes())?;
+    let mut out = String::from_utf8_lossy(MAGIC).into_owned();
+    for byte in &sealed[MAGIC.len()..] {
+        out.push_str(&format!("{byte:02x}"));
+    }
+    Ok(out)
+}
+
+/// The line [`seal_line`] wrote. Plaintext lines pass through, as in
+/// [`open_pack_bytes`].
+pub fn open_line(cipher: Option<&PackCipher>, line: &str, location: &str) -> Result<String> {
+    let Some(hex) = line.strip_prefix(std::str::from_utf8(MAGIC).unwrap_or_default()) else {
+        return Ok(line.to_string());
+    };
+    let mut raw = MAGIC.to_vec();
+    let bytes = hex.as_bytes();
+    if bytes.len() % 2 != 0 {
+        return Err(DomainError::DecryptionFailed(format!(
+            "'{location}' holds a malformed encrypted line"
+        )));
+    }
+    for pair in bytes.chunks(2) {
+        let byte = std::str::from_utf8(pair)
+            .ok()
+            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+            .ok_or_else(|| {
+                DomainError::DecryptionFailed(format!(
+                    "'{location}' holds a malformed encrypted line"
+                ))
+            })?;
+        raw.push(byte);
+    }
+    let plain = open_pack_bytes(cipher, raw, location)?;
+    String::from_utf8(plain).map_err(|_| {
+        DomainError::DecryptionFailed(format!("'{location}' holds a line that is not UTF-8"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[test]
+    fn test_seal_round_trips_and_other_keys_fail_clearly() {
+        let cipher = PackCipher::parse(KEY).unwrap();
+        let sealed = seal_pack_bytes(Some(&cipher), br#"{"id":"pk_a"}"#.to_vec()).unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(sealed.len(), ENVELOPE_OVERHEAD + 13);
+        assert!(!sealed.windows(4).any(|w| w == b"pk_a"));
+        assert_eq!(
+            open_pack_bytes(Some(&cipher), sealed.clone(), "a").unwrap(),
+            br#"{"id":"pk_a"}"#
+        );
+
+        let other = PackCipher::parse(&"ab".repeat(32)).unwrap();
+        let wrong = open_pack_bytes(Some(&other), sealed.clone(), "a").unwrap_err();
+        assert!(matches!(wrong, DomainError::DecryptionFailed(_)));
+        let missing = open_pack_bytes(None, sealed, "a").unwrap_err();
+        assert!(missing.to_string().contains(ENCRYPTION_KEY_ENV));
+
+        let plain = open_pack_bytes(Some(&cipher), b"{}".to_vec(), "a").unwrap();
+        assert_eq!(plain, b"{}");
+    }
+
+    #[test]
+    fn test_sealed_lines_stay_single_line_and_round_trip() {
+        let cipher = PackCipher::parse(KEY).unwrap();
+        let line = r#"{"pack_id":"pk_a","op":"write"}"#;
+        let sealed = seal_line(Some(&cipher), line).unwrap();
+        assert!(sealed.starts_with("cpenc1:"));
+        assert!(!sealed.contains('\n') && !sealed.contains("pk_a"));
+        assert_eq!(open_line(Some(&cipher), &sealed, "a").unwrap(), line);
+        assert_eq!(open_line(None, line, "a").unwrap(), line);
+        assert!(matches!(
+            open_line(None, &sealed, "a"),
+            Err(DomainError::DecryptionFailed(_))
+        ));
+        assert!(matches!(
+            open_line(Some(&cipher), "cpenc1:zz", "a"),
+            Err(DomainError::DecryptionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_rejects_keys_that_are_not_256_bit_hex() {
+        for raw in ["", "abcd", &"zz".repeat(32), &"ab".repeat(33)] {
+            assert!(matches!(
+                PackCipher::parse(raw),
+                Err(DomainError::InvalidData(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    adapters::{
+        pack_cipher::{open_pack_bytes, seal_pack_bytes, PackCipher, ENVELOPE_OVERHEAD},
+        pack_layout::{parse_layout_from_env, PackLayout},
+        storage_json::parse_max_pack_bytes_from_env,
+    },
+    domain::errors::{DomainError, Result},
+};
+
+pub const COMPRESSION_ENV: &str = "CONTEXT_PACK_COMPRESSION";
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const ZSTD_LEVEL: i32 = 3;
+
+/// How pack files are compressed on disk (`CONTEXT_PACK_COMPRESSION`). The
+/// choice only affects writes: reads recognize every format by its magic
+/// bytes, so a directory can mix them while packs are rewritten.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// `<id>.json`.
+    #[default]
+    None,
+    /// `<id>.json.gz`.
+    Gzip,
+    /// `<id>.json.zst`.
+    Zstd,
+}
+
+/// File-name suffix of each format, longest first so `.json` matches last.
+const SUFFIXES: [(&str, Compression); 3] = [
+    (".json.zst", Compression::Zstd),
+    (".json.gz", Compression::Gzip),
+    (".json", Compression::None),
+];
+
+impl Compression {
+    pub fn parse(raw: &str) -> Result<Self> {
+        match raw.trim() {
+            "" | "none" => Ok(Self::None),
+            "gzip" => Ok(Self::Gzip),
+            "zstd" => Ok(Self::Zstd),
+            other => Err(DomainError::InvalidData(format!(
+                "{COMPRESSION_ENV}: expected none, gzip or zstd, got '{other}'"
+            ))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    fn suffix(self) -> &'static str {
+        SUFFIXES
+            .iter()
+            .find(|(_, compression)| *compression == self)
+            .map(|(suffix, _)| *suffix)
+            .unwrap_or(".json")
+    }
+
+    fn compress(self, json: Vec<u8>) -> Result<Vec<u8>> {
+        let failed = |e: std::io::Error| DomainError::Io(format!("failed to compress pack: {e}"));
+        match self {
+            Self::None => Ok(json),
+            Self::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&json).map_err(failed)?;
+                encoder.finish().map_err(failed)
+            }
+            Self::Zstd => zstd::encode_all(json.as_slice(), ZSTD_LEVEL).map_err(failed),
+        }
+    }
+}
+
+/// Falls back to `None` on a malformed value; the startup self-check reports it.
+pub(crate) fn parse_compression_from_env() -> Compression {
+    std::env::var(COMPRESSION_ENV)
+        .ok()
+        .and_then(|raw| Compression::parse(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// `stem` of a pack or history file named `<stem>.json`, `<stem>.json.gz` or
+/// `<stem>.json.zst`.
+pub fn pack_file_stem(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    SUFFIXES
+        .iter()
+        .find_map(|(suffix, _)| name.strip_suffix(suffix))
+        .filter(|stem| !stem.is_empty())
+}
+
+/// Every file in `dir` that holds `stem`, in any format.
+pub(crate) fn pack_file_variants(dir: &Path, stem: &str) -> Vec<PathBuf> {
+    SUFFIXES
+        .iter()
+        .map(|(suffix, _)| dir.join(format!("{stem}{suffix}")))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// The file holding `stem` in `dir`. If an interrupted rewrite left two
+/// formats behind, the newer one wins.
+pub(crate) fn find_pack_file(dir: &Path, stem: &str) -> Option<PathBuf> {
+    pack_file_variants(dir, stem)
+        .into_iter()
+        .max_by_key(|path| {
+            std::fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .ok()
+        })
+}
+
+/// Removes `stem` in every format but `keep`'s; returns whether any went.
+pub(crate) fn remove_pack_file_variants(
+    dir: &Path,
+    stem: &str,
+    keep: Option<&Path>,
+) -> std::io::Result<bool> {
+    let mut removed = false;
+    for path in pack_file_variants(dir, stem) {
+        if Some(path.as_path()) == keep {
+            continue;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(()) => removed = true,
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(removed)
+}
+
+/// How pack files are read and written: the cap on a pack's JSON, the
+/// compression and directory layout for new files and, with
+/// `CONTEXT_PACK_ENCRYPTION_KEY`, the at-rest cipher. Writes compress, then
+/// encrypt; reads undo both.
+#[derive(Debug, Clone)]
+pub(crate) struct PackCodec {
+    pub(crate) max_pack_bytes: usize,
+    pub(crate) compression: Compression,
+    pub(crate) layout: PackLayout,
+    pub(crate) cipher: Option<PackCipher>,
+}
+
+impl PackCodec {
+    pub(crate) fn plain(max_pack_bytes: usize) -> Self {
+        Self {
+            max_pack_bytes,
+            compression: Compression::None,
+            layout: PackLayout::Flat,
+            cipher: None,
+        }
+    }
+
+    /// The store's settings, for tools (backup, restore, migration) that
+    /// handle pack files outside the adapter.
+    pub(crate) fn from_env() -> Result<Self> {
+        Ok(Self {
+            max_pack_bytes: parse_max_pack_bytes_from_env(),
+            compression: parse_compression_from_env(),
+            layout: parse_layout_from_env(),
+            cipher: PackCipher::from_env()?,
+        })
+    }
+
+    /// File name for `stem` in the configured format.
+    pub(crate) fn file_name(&self, stem: &str) -> String {
+        format!("{stem}{}", self.compression.suffix())
+    }
+
+    /// Where a new file for pack `id` goes under `storage_dir`.
+    pub(crate) fn pack_file_path(&self, storage_dir: &Path, id: &str) -> PathBuf {
+        self.layout.home(storage_dir, id).join(self.file_name(id))
+    }
+
+    /// Largest file worth reading: the pack cap plus worst-case compression
+    /// growth and the encryption envelope.
+    pub(crate) fn max_file_bytes(&self) -> usize {
+        self.max_pack_bytes
+            .saturating_add(self.max_pack_bytes / 128 + 1024 + ENVELOPE_OVERHEAD)
+    }
+
+    pub(crate) fn file_too_large(&self, path: &Path, len: u64) -> DomainError {
+        DomainError::Io(format!(
+            "pack file '{}' is too large: {} bytes (max {})",
+            path.display(),
+            len,
+            self.max_pack_bytes
+        ))
+    }
+
+    /// The pack JSON in `raw`: decrypted, then decompressed, and held to the
+    /// cap on the uncompressed size.
+    pub(crate) fn open(&self, raw: Vec<u8>, path: &Path) -> Result<String> {
+        let location = path.display().to_string();
+        let raw = open_pack_bytes(self.cipher.as_ref(), raw, &location)?;
+        let json = if raw.starts_with(ZSTD_MAGIC) {
+            let decoder = zstd::Decoder::new(raw.as_slice())
+                .map_err(|e| self.decompress_failed(&location, e))?;
+            self.read_capped(decoder, path, &location)?
+        } else if raw.starts_with(GZIP_MAGIC) {
+            self.read_capped(
+                flate2::read::GzDecoder::new(raw.as_slice()),
+                path,
+                &location,
+            )?
+        } else if raw.len() > self.max_pack_bytes {
+            return Err(self.file_too_large(path, raw.len() as u64));
+        } else {
+            raw
+        };
+        String::from_utf8(json).map_err(|e| {
+            DomainError::Deserialize(format!("failed to decode pack '{}': {}", location, e))
+        })
+    }
+
+    /// Stops one byte past the cap, so a small file can't inflate unbounded.
+    fn read_capped(&self, decoder: impl Read, path: &Path, location: &str) -> Result<Vec<u8>> {
+        let mut json = Vec::new();
+        decoder
+            .take((self.max_pack_bytes as u64).saturating_add(1))
+            .read_to_end(&mut json)
+            .map_err(|e| self.decompress_failed(location, e))?;
+        if json.len() > self.max_pack_bytes {
+            return Err(self.file_too_large(path, json.len() as u64));
+        }
+        Ok(json)
+    }
+
+    fn decompress_failed(&self, location: &str, e: std::io::Error) -> DomainError {
+        DomainError::Deserialize(format!("failed to decompress pack '{location}': {e}"))
+    }
+
+    pub(crate) fn seal(&self, payload: String) -> Result<Vec<u8>> {
+        let compressed = self.compression.compress(payload.into_bytes())?;
+        seal_pack_bytes(self.cipher.as_ref(), compressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_format_opens_regardless_of_the_configured_one() {
+        let json = format!(r#"{{"brief":"{}"}}"#, "long description ".repeat(200));
+        let cipher = PackCipher::parse(&"7e".repeat(32)).unwrap();
+        let path = Path::new("pk_a.json");
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd] {
+            for cipher in [None, Some(cipher.clone())] {
+                let writer = PackCodec {
+                    compression,
+                    cipher: cipher.clone(),
+                    ..PackCodec::plain(json.len())
+                };
+                let sealed = writer.seal(json.clone()).unwrap();
+                if compression != Compression::None {
+                    assert!(sealed.len() < json.len() / 4, "{compression:?}");
+                }
+                let reader = PackCodec {
+                    compression: Compression::None,
+                    ..writer
+                };
+                assert_eq!(reader.open(sealed, path).unwrap(), json);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cap_applies_to_uncompressed_size() {
+        let json = "x".repeat(4096);
+        let codec = PackCodec {
+            compression: Compression::Zstd,
+            ..PackCodec::plain(4096)
+        };
+        let sealed = codec.seal(json.clone()).unwrap();
+        assert!(sealed.len() < 100);
+        let tight = PackCodec::plain(4095);
+        let err = tight.open(sealed, Path::new("pk_a.json.zst")).unwrap_err();
+        assert!(err.to_string().contains("too large"), "{err}");
+    }
+
+    #[test]
+    fn test_file_names_and_stems() {
+        let codec = PackCodec {
+            compression: Compression::Gzip,
+            ..PackCodec::plain(1)
+        };
+        assert_eq!(codec.file_name("pk_a"), "pk_a.json.gz");
+        for name in ["pk_a.json", "pk_a.json.gz", "pk_a.json.zst"] {
+            assert_eq!(pack_file_stem(Path::new(name)), Some("pk_a"));
+        }
+        for name in ["pk_a.tmp", ".pack_index", ".json", "pk_a.zst"] {
+            assert_eq!(pack_file_stem(Path::new(name)), None, "{name}");
+        }
+        assert!(Compression::parse("brotli").is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::{
+    adapters::pack_cipher::{is_sealed, open_pack_bytes, seal_pack_bytes, PackCipher},
+    app::ports::FreshnessState,
+    domain::{
+        errors::{DomainError, Result},
+        models::Pack,
+        types::Status,
+    },
+};
+
+/// Metadata cache in the packs directory. No `.json` extension, so pack
+/// scans, backups and restores never mistake it for a pack.
+pub const PACK_INDEX_FILE: &str = ".pack_index";
+const PACK_INDEX_VERSION: u32 = 1;
+
+/// Size and modification time of a pack file; an index entry is trusted only
+/// while its stamp still matches the file on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub len: u64,
+    pub mtime_ns: u64,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        let mtime_ns = meta
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|d| u64::try_from(d.as_nanos()).ok())?;
+        Some(Self {
+            len: meta.len(),
+            mtime_ns,
+        })
+    }
+}
+
+/// What `list_packs` needs to filter, sort and page without decoding packs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackIndexEntry {
+    pub stamp: FileStamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brief: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub status: Status,
+    pub revision: u64,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
+
+impl PackIndexEntry {
+    pub fn from_pack(pack: &Pack, stamp: FileStamp) -> Self {
+        Self {
+            stamp,
+            name: pack.name.as_ref().map(|name| name.to_string()),
+            title: pack.title.clone(),
+            brief: pack.brief.clone(),
+            tags: pack.tags.clone(),
+            status: pack.status,
+            revision: pack.revision,
+            updated_at: pack.updated_at,
+            expires_at: pack.expires_at,
+            pinned: pack.pinned,
+        }
+    }
+
+    /// Same as [`FreshnessState::from_pack`] on the indexed pack.
+    pub fn freshness(&self, now: DateTime<Utc>) -> FreshnessState {
+        if self.pinned {
+            return FreshnessState::Pinned;
+        }
+        let remaining = if self.expires_at <= now {
+            0
+        } else {
+            (self.expires_at - now).num_seconds()
+        };
+        FreshnessState::from_ttl_seconds(remaining)
+    }
+}
+
+/// Pack id → metadata, stored at `{packs}/.pack_index`. Writers update it
+/// under the repository lock, and `list_packs` and name lookups read it
+/// instead of decoding every pack. It is only a cache:
+/// a missing, unreadable or other-version file loads as empty and is rebuilt
+/// from the pack files. With a cipher it is encrypted like the packs, and a
+/// plaintext index is dropped so titles and briefs don't linger unencrypted.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PackIndex {
+    version: u32,
+    pub entries: BTreeMap<String, PackIndexEntry>,
+}
+
+impl PackIndex {
+    pub fn load(storage_dir: &Path, cipher: Option<&PackCipher>) -> Self {
+        let path = storage_dir.join(PACK_INDEX_FILE);
+        let Ok(raw) = std::fs::read(&path) else {
+            return Self::default();
+        };
+        if cipher.is_some() && !is_sealed(&raw) {
+            return Self::default();
+        }
+        let raw = match open_pack_bytes(cipher, raw, &path.display().to_string()) {
+            Ok(raw) => raw,
+            Err(e) => {
+                tracing::warn!("ignoring pack index: {}", e);
+                return Self::default();
+            }
+        };
+        match serde_json::from_slice::<Self>(&raw) {
+            Ok(index) if index.version == PACK_INDEX_VERSION => index,
+            Ok(_) => Self::default(),
+            Err(e) => {
+                tracing::warn!("ignoring unreadable pack index '{}': {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Ids of packs named `name`, in id order.
+    pub fn ids_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(_, entry)| entry.name.as_deref() == Some(name))
+            .map(|(id, _)| id.as_str())
+    }
+
+    pub fn save(&mut self, storage_dir: &Path, cipher: Option<&PackCipher>) -> Result<()> {
+        self.version = PACK_INDEX_VERSION;
+        let path = storage_dir.join(PACK_INDEX_FILE);
+        let tmp = storage_dir.join(format!("{}.tmp", PACK_INDEX_FILE));
+        std::fs::write(&tmp, seal_pack_bytes(cipher, serde_json::to_vec(self)?)?)
+            .map_err(|e| DomainError::Io(format!("failed to write pack index: {}", e)))?;
+        std::fs::rename(&tmp, &path)
+            .map_err(|e| DomainError::Io(format!("failed to rename pack index: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::types::PackId;
+
+    #[test]
+    fn test_index_round_trips_and_ignores_unreadable_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let pack = Pack::new(PackId::new(), None);
+        let path = dir.path().join("pack.json");
+        std::fs::write(&path, "{}").unwrap();
+        let stamp = FileStamp::of(&path).unwrap();
+
+        let mut index = PackIndex::default();
+        index
+            .entries
+            .insert(pack.id.to_string(), PackIndexEntry::from_pack(&pack, stamp));
+        index.save(dir.path(), None).unwrap();
+        let loaded = PackIndex::load(dir.path(), None);
+        assert_eq!(loaded.entries, index.entries);
+        assert_eq!(
+            loaded.entries[pack.id.as_str()].freshness(Utc::now()),
+            FreshnessState::Fresh
+        );
+
+        std::fs::write(dir.path().join(PACK_INDEX_FILE), "{oops").unwrap();
+        assert!(PackIndex::load(dir.path(), None).entries.is_empty());
+    }
+
+    #[test]
+    fn test_encrypted_index_hides_metadata_and_drops_plaintext_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let cipher = PackCipher::parse(&"3c".repeat(32)).unwrap();
+        let mut pack = Pack::new(PackId::new(), None);
+        pack.title = Some("confidential title".into());
+        let stamp = FileStamp {
+            len: 1,
+            mtime_ns: 1,
+        };
+        let mut index = PackIndex::default();
+        index
+            .entries
+            .insert(pack.id.to_string(), PackIndexEntry::from_pack(&pack, stamp));
+
+        index.save(dir.path(), None).unwrap();
+        assert!(PackIndex::load(dir.path(), Some(&cipher))
+            .entries
+            .is_empty());
+
+        index.save(dir.path(), Some(&cipher)).unwrap();
+        let raw = std::fs::read(dir.path().join(PACK_INDEX_FILE)).unwrap();
+        assert!(!raw.windows(12).any(|w| w == b"confidential"));
+        let loaded = PackIndex::load(dir.path(), Some(&cipher));
+        assert_eq!(loaded.entries, index.entries);
+        assert!(PackIndex::load(dir.path(), None).entries.is_empty());
+    }
+}
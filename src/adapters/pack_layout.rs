@@ -0,0 +1,236 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    adapters::pack_codec::{find_pack_file, pack_file_stem, remove_pack_file_variants},
+    domain::errors::{DomainError, Result},
+};
+
+pub const LAYOUT_ENV: &str = "CONTEXT_PACK_LAYOUT";
+
+/// Where pack files sit in the packs directory (`CONTEXT_PACK_LAYOUT`). Like
+/// compression it only steers writes: reads look in both places, and a
+/// locked maintenance pass moves files laid out the other way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PackLayout {
+    /// `packs/pk_ab….json`, with history in `packs/pk_ab…/history/`.
+    #[default]
+    Flat,
+    /// `packs/ab/pk_ab….json`, with history in `packs/ab/pk_ab…/history/`,
+    /// so no directory grows past a few hundred entries per thousand packs.
+    Sharded,
+}
+
+impl PackLayout {
+    pub fn parse(raw: &str) -> Result<Self> {
+        match raw.trim() {
+            "" | "flat" => Ok(Self::Flat),
+            "sharded" => Ok(Self::Sharded),
+            other => Err(DomainError::InvalidData(format!(
+                "{LAYOUT_ENV}: expected flat or sharded, got '{other}'"
+            ))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Flat => "flat",
+            Self::Sharded => "sharded",
+        }
+    }
+
+    /// Directory holding pack `id`'s file and its `<id>/` journal. Ids
+    /// without a shard prefix stay in the packs directory either way.
+    pub fn home(self, storage_dir: &Path, id: &str) -> PathBuf {
+        match (self, shard_of(id)) {
+            (Self::Sharded, Some(shard)) => storage_dir.join(shard),
+            _ => storage_dir.to_path_buf(),
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            Self::Flat => Self::Sharded,
+            Self::Sharded => Self::Flat,
+        }
+    }
+}
+
+/// Falls back to `Flat` on a malformed value; the startup self-check reports it.
+pub(crate) fn parse_layout_from_env() -> PackLayout {
+    std::env::var(LAYOUT_ENV)
+        .ok()
+        .and_then(|raw| PackLayout::parse(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// The shard of pack id `pk_ab…`: `ab`.
+pub fn shard_of(id: &str) -> Option<&str> {
+    id.strip_prefix("pk_")?
+        .get(..2)
+        .filter(|shard| is_shard_name(shard))
+}
+
+fn is_shard_name(name: &str) -> bool {
+    name.len() == 2
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+}
+
+/// Both places pack `id` can live, `storage_dir` first.
+pub(crate) fn homes(storage_dir: &Path, id: &str) -> Vec<PathBuf> {
+    let mut homes = vec![storage_dir.to_path_buf()];
+    if let Some(shard) = shard_of(id) {
+        homes.push(storage_dir.join(shard));
+    }
+    homes
+}
+
+/// The file holding pack `id` under either layout; the newer one if a
+/// half-finished move left both.
+pub(crate) fn locate_pack_file(storage_dir: &Path, id: &str) -> Option<PathBuf> {
+    homes(storage_dir, id)
+        .iter()
+        .filter_map(|home| find_pack_file(home, id))
+        .max_by_key(|path| {
+            std::fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .ok()
+        })
+}
+
+/// Pack `id`'s `<id>/` directory (history) under either layout, if any.
+pub(crate) fn locate_pack_dir(storage_dir: &Path, id: &str) -> Option<PathBuf> {
+    homes(storage_dir, id)
+        .into_iter()
+        .map(|home| home.join(id))
+        .find(|dir| dir.is_dir())
+}
+
+/// Removes pack `id`'s file in every format and layout but `keep`; returns
+/// whether any went.
+pub(crate) fn remove_pack_files(
+    storage_dir: &Path,
+    id: &str,
+    keep: Option<&Path>,
+) -> std::io::Result<bool> {
+    let mut removed = false;
+    for home in homes(storage_dir, id) {
+        removed |= remove_pack_file_variants(&home, id, keep)?;
+    }
+    Ok(removed)
+}
+
+/// Every directory that can hold pack files: `storage_dir` and its shards.
+pub(crate) fn pack_dirs(storage_dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut dirs = vec![storage_dir.to_path_buf()];
+    for entry in std::fs::read_dir(storage_dir)? {
+        let entry = entry?;
+        let is_shard = entry.file_name().to_str().is_some_and(is_shard_name);
+        if is_shard && entry.file_type()?.is_dir() {
+            dirs.push(entry.path());
+        }
+    }
+    Ok(dirs)
+}
+
+/// Moves pack files and their `<id>/` directories laid out the other way to
+/// where `layout` puts them. Callers hold the repository lock. A file whose
+/// id already has a file in the right place is left for the next write of
+/// that pack to clear. Returns how many packs moved.
+pub(crate) fn relayout(storage_dir: &Path, layout: PackLayout) -> std::io::Result<usize> {
+    if !storage_dir.is_dir() {
+        return Ok(0);
+    }
+    let misplaced_dirs = match layout {
+        PackLayout::Flat => pack_dirs(storage_dir)?.split_off(1),
+        PackLayout::Sharded => vec![storage_dir.to_path_buf()],
+    };
+    let mut moved = 0;
+    for dir in misplaced_dirs {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let Some(id) = pack_file_stem(&path) else {
+                continue;
+            };
+            if !path.is_file() {
+                continue;
+            }
+            let home = layout.home(storage_dir, id);
+            if home == dir || find_pack_file(&home, id).is_some() {
+                continue;
+            }
+            std::fs::create_dir_all(&home)?;
+            let Some(file_name) = path.file_name() else {
+                continue;
+            };
+            std::fs::rename(&path, home.join(file_name))?;
+            let journal = layout.other().home(storage_dir, id).join(id);
+            match std::fs::rename(&journal, home.join(id)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => tracing::warn!("failed to move history of '{}': {}", id, e),
+            }
+            moved += 1;
+        }
+        if layout == PackLayout::Flat && dir != storage_dir {
+            // Only succeeds once the shard is empty.
+            let _ = std::fs::remove_dir(&dir);
+        }
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shards_come_from_the_id_suffix() {
+        assert_eq!(shard_of("pk_ab2cdefg"), Some("ab"));
+        assert_eq!(shard_of("pk_a"), None);
+        assert_eq!(shard_of("pk_A1xxxxxx"), None);
+        assert_eq!(shard_of("abcdef"), None);
+        let root = Path::new("/packs");
+        assert_eq!(
+            PackLayout::Sharded.home(root, "pk_ab2cdefg"),
+            root.join("ab")
+        );
+        assert_eq!(PackLayout::Flat.home(root, "pk_ab2cdefg"), root);
+        assert!(PackLayout::parse("nested").is_err());
+    }
+
+    #[test]
+    fn test_relayout_moves_packs_and_history_both_ways() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("pk_ab2cdefg.json.zst"), "a").unwrap();
+        std::fs::create_dir_all(root.join("pk_ab2cdefg/history")).unwrap();
+        std::fs::write(root.join("pk_ab2cdefg/history/1.json"), "h").unwrap();
+        std::fs::write(root.join("pk_cd2cdefg.json"), "b").unwrap();
+        std::fs::write(root.join(".pack_index"), "{}").unwrap();
+
+        assert_eq!(relayout(root, PackLayout::Sharded).unwrap(), 2);
+        assert!(root.join("ab/pk_ab2cdefg.json.zst").is_file());
+        assert!(root.join("ab/pk_ab2cdefg/history/1.json").is_file());
+        assert!(root.join("cd/pk_cd2cdefg.json").is_file());
+        assert!(root.join(".pack_index").is_file());
+        assert_eq!(
+            locate_pack_file(root, "pk_ab2cdefg"),
+            Some(root.join("ab/pk_ab2cdefg.json.zst"))
+        );
+        assert_eq!(
+            locate_pack_dir(root, "pk_ab2cdefg"),
+            Some(root.join("ab/pk_ab2cdefg"))
+        );
+        assert_eq!(pack_dirs(root).unwrap().len(), 3);
+        assert_eq!(relayout(root, PackLayout::Sharded).unwrap(), 0);
+
+        assert_eq!(relayout(root, PackLayout::Flat).unwrap(), 2);
+        assert!(root.join("pk_ab2cdefg.json.zst").is_file());
+        assert!(root.join("pk_ab2cdefg/history/1.json").is_file());
+        assert!(!root.join("ab").exists());
+        assert!(!root.join("cd").exists());
+    }
+}
@@ -0,0 +1,237 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    app::ports::{ListFilter, PackRepositoryPort},
+    domain::{
+        errors::{DomainError, Result},
+        models::Pack,
+        types::{PackId, PackName},
+    },
+};
+
+const PROBE_FILE: &str = ".read_only_probe";
+
+/// Whether `err` means the filesystem refuses writes (an immutable mount or
+/// a directory the server may not write), as opposed to a transient fault.
+pub fn is_read_only_error(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::ReadOnlyFilesystem | ErrorKind::PermissionDenied
+    )
+}
+
+/// Why `storage_dir` cannot be written, or `None` when a probe file can be
+/// created there (or fails for some other reason).
+pub fn probe_read_only(storage_dir: &Path) -> Option<String> {
+    let attempt = std::fs::create_dir_all(storage_dir)
+        .and_then(|()| std::fs::write(storage_dir.join(PROBE_FILE), b"ok"));
+    match attempt {
+        Ok(()) => {
+            let _ = std::fs::remove_file(storage_dir.join(PROBE_FILE));
+            None
+        }
+        Err(e) if is_read_only_error(&e) => Some(format!(
+            "'{}' is not writable: {}",
+            storage_dir.display(),
+            e
+        )),
+        Err(_) => None,
+    }
+}
+
+/// Repository decorator that switches the store to read-only once it turns
+/// out to be unwritable, at startup or on the first failed write: reads keep
+/// working and every later mutation fails fast with `storage_read_only`
+/// instead of an opaque I/O error.
+pub struct ReadOnlyFallbackStorage {
+    inner: Arc<dyn PackRepositoryPort>,
+    storage_dir: PathBuf,
+    /// Set once; the reason the store went read-only.
+    read_only: Mutex<Option<String>>,
+}
+
+impl ReadOnlyFallbackStorage {
+    pub fn new(inner: Arc<dyn PackRepositoryPort>, storage_dir: PathBuf) -> Self {
+        Self {
+            inner,
+            storage_dir,
+            read_only: Mutex::new(None),
+        }
+    }
+
+    /// Like [`Self::new`], probing `storage_dir` right away.
+    pub fn detect(inner: Arc<dyn PackRepositoryPort>, storage_dir: PathBuf) -> Self {
+        let storage = Self::new(inner, storage_dir);
+        if let Some(reason) = probe_read_only(&storage.storage_dir) {
+            storage.mark_read_only(reason);
+        }
+        storage
+    }
+
+    /// Puts the store in read-only mode, e.g. for hosts that know their
+    /// mount is immutable.
+    pub fn mark_read_only(&self, reason: impl Into<String>) {
+        let mut state = self.state();
+        if state.is_none() {
+            let reason = reason.into();
+            tracing::warn!("storage is read-only, input mutations are disabled: {reason}");
+            *state = Some(reason);
+        }
+    }
+
+    pub fn read_only_reason(&self) -> Option<String> {
+        self.state().clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, Option<String>> {
+        self.read_only
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn before_write(&self) -> Result<()> {
+        match self.read_only_reason() {
+            Some(reason) => Err(DomainError::StorageReadOnly(reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// An I/O failure caused by an unwritable store flips the mode and
+    /// comes back as `storage_read_only`; anything else passes through.
+    fn after_write<T>(&self, outcome: Result<T>) -> Result<T> {
+        match outcome {
+            Err(DomainError::Io(message)) => match probe_read_only(&self.storage_dir) {
+                Some(reason) => {
+                    self.mark_read_only(reason.clone());
+                    Err(DomainError::StorageReadOnly(reason))
+                }
+                None => Err(DomainError::Io(message)),
+            },
+            other => other,
+        }
+    }
+}
+
+#[async_trait]
+impl PackRepositoryPort for ReadOnlyFallbackStorage {
+    async fn create_new(&self, pack: &Pack) -> Result<()> {
+        self.before_write()?;
+        self.after_write(self.inner.create_new(pack).await)
+    }
+
+    async fn save_with_expected_revision(&self, pack: &Pack, expected_revision: u64) -> Result<()> {
+        self.before_write()?;
+        let outcome = self
+            .inner
+            .save_with_expected_revision(pack, expected_revision)
+            .await;
+        self.after_write(outcome)
+    }
+
+    async fn create_with_update(
+        &self,
+        created: &Pack,
+        updated: &Pack,
+        expected_revision: u64,
+    ) -> Result<()> {
+        self.before_write()?;
+        let outcome = self
+            .inner
+            .create_with_update(created, updated, expected_revision)
+            .await;
+        self.after_write(outcome)
+    }
+
+    async fn delete_pack_file(&self, id: &PackId) -> Result<bool> {
+        self.before_write()?;
+        self.after_write(self.inner.delete_pack_file(id).await)
+    }
+
+    async fn get_by_id(&self, id: &PackId) -> Result<Option<Pack>> {
+        self.inner.get_by_id(id).await
+    }
+
+    async fn get_by_name(&self, name: &PackName) -> Result<Option<Pack>> {
+        self.inner.get_by_name(name).await
+    }
+
+    async fn list_packs(&self, filter: ListFilter) -> Result<Vec<Pack>> {
+        self.inner.list_packs(filter).await
+    }
+
+    /// Nothing can be purged from a read-only store; readers already hide
+    /// packs past their grace window, so this is a no-op there.
+    async fn purge_expired(&self) -> Result<()> {
+        if self.read_only_reason().is_some() {
+            return Ok(());
+        }
+        self.after_write(self.inner.purge_expired().await)
+    }
+
+    async fn list_revisions(&self, id: &PackId) -> Result<Vec<u64>> {
+        self.inner.list_revisions(id).await
+    }
+
+    async fn get_revision(&self, id: &PackId, revision: u64) -> Result<Option<Pack>> {
+        self.inner.get_revision(id, revision).await
+    }
+
+    /// A read-only store keeps the expiry it has, like `purge_expired`.
+    async fn extend_expiry(&self, id: &PackId, expires_at: DateTime<Utc>) -> Result<()> {
+        if self.read_only_reason().is_some() {
+            return Ok(());
+        }
+        self.after_write(self.inner.extend_expiry(id, expires_at).await)
+    }
+
+    fn max_pack_bytes(&self) -> Option<usize> {
+        self.inner.max_pack_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::storage_json::JsonStorageAdapter;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_read_only_mode_keeps_reads_and_rejects_writes() {
+        let dir = tempdir().unwrap();
+        let inner = Arc::new(JsonStorageAdapter::new(dir.path().to_path_buf()));
+        let storage = ReadOnlyFallbackStorage::detect(inner, dir.path().to_path_buf());
+        assert_eq!(storage.read_only_reason(), None);
+        assert!(!dir.path().join(PROBE_FILE).exists());
+
+        let pack = Pack::new(PackId::new(), Some(PackName::new("kept").unwrap()));
+        storage.create_new(&pack).await.unwrap();
+        storage.mark_read_only("mounted read-only");
+
+        let err = storage
+            .save_with_expected_revision(&pack, pack.revision)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, DomainError::StorageReadOnly(reason) if reason == "mounted read-only"),
+            "{err:?}"
+        );
+        assert!(matches!(
+            storage.delete_pack_file(&pack.id).await,
+            Err(DomainError::StorageReadOnly(_))
+        ));
+        storage.purge_expired().await.unwrap();
+        assert!(storage.get_by_id(&pack.id).await.unwrap().is_some());
+        assert_eq!(
+            storage
+                .list_packs(ListFilter::default())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use tokio::task;
+
+use crate::{
+    adapters::pack_cipher::{open_pack_bytes, seal_pack_bytes, PackCipher},
+    app::ports::{ReplayJournalPort, ReplayKey},
+    domain::errors::{DomainError, Result},
+};
+
+const DEFAULT_REPLAY_WINDOW_SECONDS: i64 = 600;
+const MAX_REPLAY_ENTRIES: usize = 256;
+
+fn parse_replay_window_seconds_from_env() -> i64 {
+    std::env::var("CONTEXT_PACK_REPLAY_WINDOW_SECONDS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<i64>().ok())
+        .filter(|value| *value >= 0)
+        .unwrap_or(DEFAULT_REPLAY_WINDOW_SECONDS)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReplayEntry {
+    #[serde(flatten)]
+    key: ReplayKey,
+    recorded_at: DateTime<Utc>,
+    result: Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReplayJournalFile {
+    entries: Vec<ReplayEntry>,
+}
+
+/// JSON-file replay journal stored next to the pack directory.
+///
+/// Entries older than the replay window are pruned on every write, and the
+/// journal is capped at [`MAX_REPLAY_ENTRIES`] (oldest dropped first).
+/// Recorded results can hold pack contents, so with a cipher the file is
+/// encrypted like the pack files.
+pub struct ReplayJournalFsAdapter {
+    journal_path: PathBuf,
+    window_seconds: i64,
+    cipher: Option<PackCipher>,
+}
+
+impl ReplayJournalFsAdapter {
+    pub fn new(journal_path: PathBuf) -> Self {
+        Self {
+            journal_path,
+            window_seconds: parse_replay_window_seconds_from_env(),
+            cipher: None,
+        }
+    }
+
+    pub fn with_cipher(mut self, cipher: Option<PackCipher>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    #[cfg(test)]
+    fn new_with_window(journal_path: PathBuf, window_seconds: i64) -> Self {
+        Self {
+            journal_path,
+            window_seconds,
+            cipher: None,
+        }
+    }
+
+    fn lock_path(journal_path: &Path) -> PathBuf {
+        journal_path.with_extension("lock")
+    }
+
+    fn is_live(entry: &ReplayEntry, now: DateTime<Utc>, window_seconds: i64) -> bool {
+        now <= entry.recorded_at + chrono::Duration::seconds(window_seconds.max(0))
+    }
+
+    /// The journal only saves re-applying a retried call, so one that can't
+    /// be read or opened is discarded.
+    fn read_journal_sync(journal_path: &Path, cipher: Option<&PackCipher>) -> ReplayJournalFile {
+        let raw = match std::fs::read(journal_path) {
+            Ok(raw) => raw,
+            Err(_) => return ReplayJournalFile::default(),
+        };
+        let raw = match open_pack_bytes(cipher, raw, &journal_path.display().to_string()) {
+            Ok(raw) => raw,
+            Err(err) => {
+                tracing::warn!("discarding replay journal: {}", err);
+                return ReplayJournalFile::default();
+            }
+        };
+        serde_json::from_slice(&raw).unwrap_or_else(|err| {
+            tracing::warn!(
+                "discarding unreadable replay journal '{}': {}",
+                journal_path.display(),
+                err
+            );
+            ReplayJournalFile::default()
+        })
+    }
+
+    fn write_journal_sync(
+        journal_path: &Path,
+        journal: &ReplayJournalFile,
+        cipher: Option<&PackCipher>,
+    ) -> Result<()> {
+        let tmp = journal_path.with_extension("tmp");
+        std::fs::write(&tmp, seal_pack_bytes(cipher, serde_json::to_vec(journal)?)?)
+            .map_err(|e| DomainError::Io(format!("failed to write replay journal: {}", e)))?;
+        std::fs::rename(&tmp, journal_path)
+            .map_err(|e| DomainError::Io(format!("failed to rename replay journal: {}", e)))?;
+        Ok(())
+    }
+
+    fn with_lock<T>(journal_path: &Path, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        if let Some(parent) = journal_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| DomainError::Io(format!("failed to create journal dir: {}", e)))?;
+        }
+        let lock_path = Self::lock_path(journal_path);
+        let lock = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+            .map_err(|e| {
+                DomainError::Io(format!(
+                    "failed to open replay journal lock '{}': {}",
+                    lock_path.display(),
+                    e
+                ))
+            })?;
+        lock.lock_exclusive()
+            .map_err(|e| DomainError::Io(format!("failed to lock replay journal: {}", e)))?;
+        let result = f();
+        if let Err(e) = lock.unlock() {
+            tracing::warn!("failed to unlock replay journal lock: {e}");
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl ReplayJournalPort for ReplayJournalFsAdapter {
+    async fn lookup(&self, key: &ReplayKey) -> Result<Option<Value>> {
+        let journal_path = self.journal_path.clone();
+        let window_seconds = self.window_seconds;
+        let key = key.clone();
+        let cipher = self.cipher.clone();
+        task::spawn_blocking(move || -> Result<Option<Value>> {
+            let now = Utc::now();
+            let journal = Self::read_journal_sync(&journal_path, cipher.as_ref());
+            let Some(entry) = journal.entries.into_iter().rev().find(|entry| {
+                entry.key.same_call(&key) && Self::is_live(entry, now, window_seconds)
+            }) else {
+                return Ok(None);
+            };
+            if entry.key.args_hash != key.args_hash {
+                return Err(DomainError::Conflict(format!(
+                    "idempotency_key '{}' was already used with different arguments",
+                    key.idempotency_key
+                )));
+            }
+            Ok(Some(entry.result))
+        })
+        .await
+        .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+
+    async fn record(&self, key: &ReplayKey, result: &Value) -> Result<()> {
+        let journal_path = self.journal_path.clone();
+        let window_seconds = self.window_seconds;
+        let entry = ReplayEntry {
+            key: key.clone(),
+            recorded_at: Utc::now(),
+            result: result.clone(),
+        };
+        let cipher = self.cipher.clone();
+        task::spawn_blocking(move || -> Result<()> {
+            Self::with_lock(&journal_path, || {
+                let now = Utc::now();
+                let mut journal = Self::read_journal_sync(&journal_path, cipher.as_ref());
+                journal.entries.retain(|existing| {
+                    !existing.key.same_call(&entry.key)
+                        && Self::is_live(existing, now, window_seconds)
+                });
+                journal.entries.push(entry);
+                let overflow = journal.entries.len().saturating_sub(MAX_REPLAY_ENTRIES);
+                journal.entries.drain(..overflow);
+                Self::write_journal_sync(&journal_path, &journal, cipher.as_ref())
+            })
+        })
+        .await
+        .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn key(request_id: &str, idempotency_key: &str) -> ReplayKey {
+        ReplayKey {
+            scope: "client:test".to_string(),
+            request_id: request_id.to_string(),
+            idempotency_key: idempotency_key.to_string(),
+            args_hash: "0".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recorded_result_survives_new_adapter_instance() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("replay_journal.json");
+        let first = ReplayJournalFsAdapter::new_with_window(path.clone(), 600);
+        first
+            .record(&key("7", "create-auth"), &json!({"ok": 1}))
+            .await
+            .unwrap();
+
+        let second = ReplayJournalFsAdapter::new_with_window(path, 600);
+        assert_eq!(
+            second.lookup(&key("7", "create-auth")).await.unwrap(),
+            Some(json!({"ok": 1}))
+        );
+        assert_eq!(
+            second.lookup(&key("8", "create-auth")).await.unwrap(),
+            None,
+            "a different request id must not replay"
+        );
+        let other_scope = ReplayKey {
+            scope: "http:other".to_string(),
+            ..key("7", "create-auth")
+        };
+        assert_eq!(second.lookup(&other_scope).await.unwrap(), None);
+        let other_args = ReplayKey {
+            args_hash: "1".to_string(),
+            ..key("7", "create-auth")
+        };
+        assert!(matches!(
+            second.lookup(&other_args).await,
+            Err(DomainError::Conflict(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_entries_outside_window_are_ignored_and_pruned() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("replay_journal.json");
+        let journal = ReplayJournalFile {
+            entries: vec![ReplayEntry {
+                key: key("1", "stale"),
+                recorded_at: Utc::now() - chrono::Duration::seconds(120),
+                result: json!({"stale": true}),
+            }],
+        };
+        ReplayJournalFsAdapter::write_journal_sync(&path, &journal, None).unwrap();
+
+        let adapter = ReplayJournalFsAdapter::new_with_window(path.clone(), 60);
+        assert_eq!(adapter.lookup(&key("1", "stale")).await.unwrap(), None);
+
+        adapter
+            .record(&key("2", "fresh"), &json!({"fresh": true}))
+            .await
+            .unwrap();
+        let persisted = ReplayJournalFsAdapter::read_journal_sync(&path, None);
+        assert_eq!(persisted.entries.len(), 1, "stale entry must be pruned");
+        assert_eq!(persisted.entries[0].key, key("2", "fresh"));
+    }
+}
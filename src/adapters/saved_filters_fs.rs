@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use tokio::task;
+
+use crate::{
+    app::ports::{SavedFilter, SavedFilterPort},
+    domain::errors::{DomainError, Result},
+};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedFiltersFile {
+    filters: BTreeMap<String, SavedFilter>,
+}
+
+/// Saved list filters in `{root}/saved_filters.json`, shared by every agent
+/// using the same storage root.
+pub struct SavedFiltersFsAdapter {
+    path: PathBuf,
+}
+
+impl SavedFiltersFsAdapter {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn read_sync(path: &Path) -> Result<SavedFiltersFile> {
+        match std::fs::read_to_string(path) {
+            Ok(raw) => serde_json::from_str(&raw).map_err(|e| {
+                DomainError::Deserialize(format!(
+                    "failed to decode saved filters '{}': {}",
+                    path.display(),
+                    e
+                ))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SavedFiltersFile::default()),
+            Err(e) => Err(DomainError::Io(format!(
+                "failed to read saved filters '{}': {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// Read-modify-write under `saved_filters.lock`.
+    fn update_sync<T>(path: &Path, f: impl FnOnce(&mut SavedFiltersFile) -> T) -> Result<T> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| DomainError::Io(format!("failed to create storage root: {}", e)))?;
+        }
+        let lock_path = path.with_extension("lock");
+        let lock = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+            .map_err(|e| {
+                DomainError::Io(format!(
+                    "failed to open saved filters lock '{}': {}",
+                    lock_path.display(),
+                    e
+                ))
+            })?;
+        lock.lock_exclusive()
+            .map_err(|e| DomainError::Io(format!("failed to lock saved filters: {}", e)))?;
+        let result = (|| {
+            let mut file = Self::read_sync(path)?;
+            let out = f(&mut file);
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, serde_json::to_string_pretty(&file)?)
+                .map_err(|e| DomainError::Io(format!("failed to write saved filters: {}", e)))?;
+            std::fs::rename(&tmp, path)
+                .map_err(|e| DomainError::Io(format!("failed to rename saved filters: {}", e)))?;
+            Ok(out)
+        })();
+        if let Err(e) = lock.unlock() {
+            tracing::warn!("failed to unlock saved filters lock: {e}");
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl SavedFilterPort for SavedFiltersFsAdapter {
+    async fn list_filters(&self) -> Result<BTreeMap<String, SavedFilter>> {
+        let path = self.path.clone();
+        task::spawn_blocking(move || Ok(Self::read_sync(&path)?.filters))
+            .await
+            .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+
+    async fn save_filter(&self, name: &str, filter: &SavedFilter) -> Result<()> {
+        let path = self.path.clone();
+        let name = name.to_string();
+        let filter = filter.clone();
+        task::spawn_blocking(move || {
+            Self::update_sync(&path, |file| {
+                file.filters.insert(name, filter);
+            })
+        })
+        .await
+        .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+
+    async fn delete_filter(&self, name: &str) -> Result<bool> {
+        let path = self.path.clone();
+        let name = name.to_string();
+        task::spawn_blocking(move || {
+            Self::update_sync(&path, |file| file.filters.remove(&name).is_some())
+        })
+        .await
+        .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::{
+    app::{
+        ports::PackRepositoryPort,
+        source_watch::{SourceChange, SourceWatch},
+    },
+    domain::{
+        errors::{DomainError, Result},
+        types::SourceRootName,
+    },
+};
+
+/// Quiet time that ends a batch, so a save that touches a file several
+/// times (or a checkout touching many) is matched against packs once.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Raw events buffered ahead of the batching task; a full buffer makes the
+/// watcher thread wait instead of growing memory during a large checkout.
+const EVENT_BUFFER: usize = 1024;
+
+/// Directory names skipped at any depth: VCS metadata and build or
+/// dependency output, which churn on every build and are never referenced.
+const IGNORED_DIR_NAMES: &[&str] = &[".git", "target", "node_modules"];
+
+/// Watches the source roots recursively and feeds changed files to a
+/// [`SourceWatch`]. Changes under `.git/`, `target/`, `node_modules/` and
+/// under ignored directories (the storage root, when it sits inside a source
+/// root) are dropped.
+#[derive(Clone)]
+pub struct SourceWatchFsAdapter {
+    roots: Vec<(Option<SourceRootName>, PathBuf)>,
+    ignored: Vec<PathBuf>,
+}
+
+impl SourceWatchFsAdapter {
+    pub fn new(source_root: PathBuf) -> Self {
+        Self {
+            roots: vec![(None, source_root)],
+            ignored: Vec::new(),
+        }
+    }
+
+    pub fn with_source_roots(mut self, roots: Vec<(SourceRootName, PathBuf)>) -> Self {
+        self.roots
+            .extend(roots.into_iter().map(|(name, path)| (Some(name), path)));
+        self
+    }
+
+    pub fn with_ignored_dir(mut self, dir: PathBuf) -> Self {
+        self.ignored.push(dir);
+        self
+    }
+
+    /// Starts watching and returns the task that records batches into
+    /// `watch`. Fails when a root cannot be watched; the task ends with the
+    /// process.
+    pub fn spawn(
+        &self,
+        watch: Arc<SourceWatch>,
+        repo: Arc<dyn PackRepositoryPort>,
+    ) -> Result<JoinHandle<()>> {
+        // Events carry paths under the watched path as given, so resolve
+        // symlinks once here to strip prefixes reliably.
+        let mut this = self.clone();
+        for (_, path) in &mut this.roots {
+            *path = canonical(path);
+        }
+        for dir in &mut this.ignored {
+            *dir = canonical(dir);
+        }
+        let (events, mut pending) = mpsc::channel(EVENT_BUFFER);
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            // Runs on the watcher's own thread, so blocking is allowed. The
+            // receiver is gone only when the process is exiting.
+            let _ = events.blocking_send(event);
+        })
+        .map_err(watch_error)?;
+        for (_, path) in &this.roots {
+            watcher
+                .watch(path, RecursiveMode::Recursive)
+                .map_err(watch_error)?;
+        }
+        Ok(tokio::spawn(async move {
+            // Dropping the watcher stops the events.
+            let _watcher = watcher;
+            while let Some(first) = pending.recv().await {
+                let mut changes = BTreeSet::new();
+                this.collect(first, &mut changes);
+                while let Ok(Some(event)) = tokio::time::timeout(DEBOUNCE, pending.recv()).await {
+                    this.collect(event, &mut changes);
+                }
+                if changes.is_empty() {
+                    continue;
+                }
+                let changes: Vec<SourceChange> = changes.into_iter().collect();
+                match watch.record(repo.as_ref(), &changes).await {
+                    Ok(stale) if !stale.is_empty() => tracing::info!(
+                        "{} changed file(s) touch refs in {} pack(s)",
+                        changes.len(),
+                        stale.len()
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("failed to match source changes to packs: {}", e),
+                }
+            }
+        }))
+    }
+
+    fn collect(&self, event: notify::Result<Event>, changes: &mut BTreeSet<SourceChange>) {
+        match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                for path in &event.paths {
+                    changes.extend(self.changes_for(path));
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("source watcher error: {}", e),
+        }
+    }
+
+    /// `path` relative to every root it sits under; nested roots each see it.
+    fn changes_for(&self, path: &Path) -> Vec<SourceChange> {
+        if self.ignored.iter().any(|dir| path.starts_with(dir)) {
+            return Vec::new();
+        }
+        self.roots
+            .iter()
+            .filter_map(|(root, root_path)| {
+                let relative = path.strip_prefix(root_path).ok()?;
+                let mut segments = Vec::new();
+                for component in relative.components() {
+                    let Component::Normal(segment) = component else {
+                        return None;
+                    };
+                    let segment = segment.to_str()?;
+                    if IGNORED_DIR_NAMES.contains(&segment) {
+                        return None;
+                    }
+                    segments.push(segment);
+                }
+                (!segments.is_empty()).then(|| SourceChange {
+                    root: root.clone(),
+                    path: segments.join("/"),
+                })
+            })
+            .collect()
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn watch_error(e: notify::Error) -> DomainError {
+    DomainError::Io(format!("failed to watch source roots: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_map_to_every_root_and_skip_ignored_dirs() {
+        let docs = SourceRootName::new("docs").unwrap();
+        let adapter = SourceWatchFsAdapter::new(PathBuf::from("/src"))
+            .with_source_roots(vec![(docs.clone(), PathBuf::from("/src/docs"))])
+            .with_ignored_dir(PathBuf::from("/src/.agents"));
+
+        assert_eq!(
+            adapter.changes_for(Path::new("/src/docs/guide.md")),
+            vec![
+                SourceChange {
+                    root: None,
+                    path: "docs/guide.md".into(),
+                },
+                SourceChange {
+                    root: Some(docs),
+                    path: "guide.md".into(),
+                },
+            ]
+        );
+        assert!(adapter.changes_for(Path::new("/src/.git/index")).is_empty());
+        assert!(adapter
+            .changes_for(Path::new("/src/target/debug/build.log"))
+            .is_empty());
+        assert!(adapter
+            .changes_for(Path::new("/src/docs/node_modules/pkg/index.js"))
+            .is_empty());
+        assert!(adapter
+            .changes_for(Path::new("/src/.agents/mcp/context_pack/packs/pk_x.json"))
+            .is_empty());
+        assert!(adapter.changes_for(Path::new("/elsewhere/a.rs")).is_empty());
+        assert!(adapter.changes_for(Path::new("/src")).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{
+    adapters::{
+        pack_cipher::PackCipher, pack_codec::Compression, pack_layout::PackLayout,
+        read_only_storage::ReadOnlyFallbackStorage, storage_json::JsonStorageAdapter,
+        storage_memory::InMemoryStorageAdapter, store_meta_fs::ensure_store_meta,
+    },
+    app::ports::PackRepositoryPort,
+    domain::errors::{DomainError, Result},
+};
+
+#[cfg(feature = "s3")]
+use crate::adapters::storage_s3::S3StorageAdapter;
+
+pub const STORAGE_ENV: &str = "CONTEXT_PACK_STORAGE";
+
+/// Where packs are kept (`CONTEXT_PACK_STORAGE`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// One JSON file per pack under `{storage_root}/packs`.
+    #[default]
+    Json,
+    /// Process memory ([`InMemoryStorageAdapter`]); packs are lost on exit.
+    Memory,
+    /// An S3-compatible bucket ([`S3StorageAdapter`]), configured from
+    /// `CONTEXT_PACK_S3_BUCKET`, `CONTEXT_PACK_S3_PREFIX` and `AWS_*`.
+    #[cfg(feature = "s3")]
+    S3,
+}
+
+/// Every backend this build can open, by its `CONTEXT_PACK_STORAGE` name.
+/// A backend behind a cargo feature adds its entry under the same `cfg`.
+const BACKENDS: &[(&str, StorageBackend)] = &[
+    ("json", StorageBackend::Json),
+    ("memory", StorageBackend::Memory),
+    #[cfg(feature = "s3")]
+    ("s3", StorageBackend::S3),
+];
+
+impl StorageBackend {
+    pub fn name(self) -> &'static str {
+        BACKENDS
+            .iter()
+            .find(|(_, backend)| *backend == self)
+            .map(|(name, _)| *name)
+            .unwrap_or("unknown")
+    }
+
+    /// Whether packs survive a restart.
+    pub fn is_persistent(self) -> bool {
+        match self {
+            Self::Json => true,
+            Self::Memory => false,
+            #[cfg(feature = "s3")]
+            Self::S3 => true,
+        }
+    }
+}
+
+impl std::str::FromStr for StorageBackend {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let raw = s.trim();
+        BACKENDS
+            .iter()
+            .find(|(name, _)| *name == raw)
+            .map(|(_, backend)| *backend)
+            .ok_or_else(|| {
+                let names: Vec<&str> = BACKENDS.iter().map(|(name, _)| *name).collect();
+                DomainError::InvalidData(format!(
+                    "storage must be one of: {} (got '{}')",
+                    names.join(", "),
+                    raw
+                ))
+            })
+    }
+}
+
+/// Opens the pack store for `backend` under `storage_root`. The JSON store
+/// stamps or checks `store_meta.json` (failing with `MigrationRequired` on a
+/// schema mismatch), writes pack files with `cipher`, `compression` and
+/// `layout`, and falls back to read-only when the root refuses writes; the
+/// memory and S3 stores ignore `storage_root` and those settings. With the
+/// `chaos` feature, `CONTEXT_PACK_CHAOS` wraps whichever store is opened.
+pub fn open_pack_repository(
+    backend: StorageBackend,
+    storage_root: &Path,
+    cipher: Option<PackCipher>,
+    compression: Compression,
+    layout: PackLayout,
+) -> Result<Arc<dyn PackRepositoryPort>> {
+    let storage_dir = storage_root.join("packs");
+    let repo: Arc<dyn PackRepositoryPort> = match backend {
+        StorageBackend::Json => {
+            ensure_store_meta(storage_root)?;
+            Arc::new(ReadOnlyFallbackStorage::detect(
+                Arc::new(
+                    JsonStorageAdapter::new(storage_dir.clone())
+                        .with_cipher(cipher)
+                        .with_compression(compression)
+                        .with_layout(layout),
+                ),
+                storage_dir,
+            ))
+        }
+        StorageBackend::Memory => Arc::new(InMemoryStorageAdapter::new()),
+        #[cfg(feature = "s3")]
+        StorageBackend::S3 => Arc::new(S3StorageAdapter::from_env()?),
+    };
+    #[cfg(feature = "chaos")]
+    let repo: Arc<dyn PackRepositoryPort> =
+        match crate::adapters::chaos_storage::ChaosConfig::from_env()? {
+            Some(chaos) => Arc::new(crate::adapters::chaos_storage::ChaosStorageAdapter::new(
+                repo, chaos,
+            )),
+            None => repo,
+        };
+    Ok(repo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{models::Pack, types::PackId};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_backend_names_round_trip_and_unknown_lists_choices() {
+        for (name, backend) in BACKENDS {
+            assert_eq!(name.parse::<StorageBackend>().unwrap(), *backend);
+            assert_eq!(backend.name(), *name);
+        }
+        let err = "sqlite".parse::<StorageBackend>().unwrap_err();
+        assert!(err.to_string().contains("json, memory"));
+    }
+
+    #[tokio::test]
+    async fn test_open_json_stamps_root_and_memory_leaves_it_alone() {
+        let dir = tempdir().unwrap();
+        let json_root = dir.path().join("json");
+        let cipher = PackCipher::parse(&"11".repeat(32)).unwrap();
+        let repo = open_pack_repository(
+            StorageBackend::Json,
+            &json_root,
+            Some(cipher),
+            Compression::Gzip,
+            PackLayout::Sharded,
+        )
+        .unwrap();
+        let pack = Pack::new(PackId::new(), None);
+        repo.create_new(&pack).await.unwrap();
+        assert!(json_root.join("store_meta.json").is_file());
+        let path = PackLayout::Sharded
+            .home(&json_root.join("packs"), pack.id.as_str())
+            .join(format!("{}.json.gz", pack.id.as_str()));
+        assert!(std::fs::read(&path).unwrap().starts_with(b"cpenc1:"));
+
+        let memory_root = dir.path().join("memory");
+        let repo = open_pack_repository(
+            StorageBackend::Memory,
+            &memory_root,
+            None,
+            Compression::None,
+            PackLayout::Flat,
+        )
+        .unwrap();
+        repo.create_new(&Pack::new(PackId::new(), None))
+            .await
+            .unwrap();
+        assert!(!memory_root.exists());
+    }
+}
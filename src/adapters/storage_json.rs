@@ -1,12 +1,27 @@
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use fs2::FileExt;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::OpenOptions;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tokio::task;
 
 use crate::{
+    adapters::{
+        pack_cipher::PackCipher,
+        pack_codec::{
+            find_pack_file, pack_file_stem, parse_compression_from_env, remove_pack_file_variants,
+            Compression, PackCodec,
+        },
+        pack_index_fs::{FileStamp, PackIndex, PackIndexEntry},
+        pack_layout::{
+            homes, locate_pack_dir, locate_pack_file, pack_dirs, parse_layout_from_env, relayout,
+            remove_pack_files, PackLayout,
+        },
+        read_only_storage::is_read_only_error,
+    },
     app::ports::{FreshnessState, ListFilter, PackRepositoryPort},
     domain::{
         errors::{
@@ -19,6 +34,36 @@ use crate::{
 
 const DEFAULT_MAX_PACK_BYTES: usize = 512 * 1024;
 const DEFAULT_EXPIRED_GRACE_SECONDS: i64 = 900;
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+pub const DEFAULT_ARCHIVE_RETENTION_DAYS: i64 = 30;
+
+pub const EXPIRED_RETENTION_ENV: &str = "CONTEXT_PACK_EXPIRED_RETENTION";
+
+/// What happens to a pack once it is past its expiry grace window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiredRetention {
+    /// The pack file and its history journal are deleted.
+    Delete,
+    /// Both move under `archive/`, listed with `list archived=true`, and are
+    /// deleted once `retention` has passed on top of the grace window.
+    Archive { retention: chrono::Duration },
+}
+
+impl ExpiredRetention {
+    /// Parses the `CONTEXT_PACK_EXPIRED_RETENTION` mode (`delete|archive`).
+    pub fn parse(raw: &str, retention_days: i64) -> Result<Self> {
+        match raw.trim() {
+            "" | "delete" => Ok(Self::Delete),
+            "archive" => Ok(Self::Archive {
+                retention: chrono::Duration::days(retention_days),
+            }),
+            other => Err(DomainError::InvalidData(format!(
+                "expected delete or archive, got '{}'",
+                other
+            ))),
+        }
+    }
+}
 
 /// Minimal pack metadata needed for TTL purge scanning.
 /// Avoids deserializing full Pack (sections, refs, diagrams).
@@ -27,7 +72,26 @@ struct PackMeta {
     expires_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-fn parse_max_pack_bytes_from_env() -> usize {
+/// A pack encoded into `tmp`, waiting to be renamed to `path`.
+struct StagedPack {
+    tmp: PathBuf,
+    path: PathBuf,
+}
+
+impl StagedPack {
+    fn install(&self) -> Result<()> {
+        std::fs::rename(&self.tmp, &self.path)
+            .map_err(|e| DomainError::Io(format!("failed to rename pack file: {}", e)))
+    }
+
+    fn discard(&self) {
+        if let Err(e) = std::fs::remove_file(&self.tmp) {
+            tracing::debug!("tmp pack '{}' not removed: {}", self.tmp.display(), e);
+        }
+    }
+}
+
+pub(crate) fn parse_max_pack_bytes_from_env() -> usize {
     std::env::var("CONTEXT_PACK_MAX_PACK_BYTES")
         .ok()
         .and_then(|raw| raw.trim().parse::<usize>().ok())
@@ -35,7 +99,7 @@ fn parse_max_pack_bytes_from_env() -> usize {
         .unwrap_or(DEFAULT_MAX_PACK_BYTES)
 }
 
-fn parse_expired_grace_seconds_from_env() -> i64 {
+pub(crate) fn parse_expired_grace_seconds_from_env() -> i64 {
     std::env::var("CONTEXT_PACK_EXPIRED_GRACE_SECONDS")
         .ok()
         .and_then(|raw| raw.trim().parse::<i64>().ok())
@@ -43,7 +107,35 @@ fn parse_expired_grace_seconds_from_env() -> i64 {
         .unwrap_or(DEFAULT_EXPIRED_GRACE_SECONDS)
 }
 
-fn conflict_changed_section_keys(current: &Pack, attempted: &Pack) -> Vec<String> {
+/// Prior revisions kept per pack under `<id>/history/`; `0` disables the journal.
+pub(crate) fn parse_history_limit_from_env() -> usize {
+    std::env::var("CONTEXT_PACK_HISTORY_LIMIT")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+}
+
+fn parse_expired_retention_from_env() -> ExpiredRetention {
+    let retention_days = std::env::var("CONTEXT_PACK_ARCHIVE_RETENTION_DAYS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<i64>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_ARCHIVE_RETENTION_DAYS);
+    std::env::var(EXPIRED_RETENTION_ENV)
+        .ok()
+        .and_then(|raw| ExpiredRetention::parse(&raw, retention_days).ok())
+        .unwrap_or(ExpiredRetention::Delete)
+}
+
+/// Whether `list` shows expired packs (within the grace window) when the
+/// request does not say; off keeps the stale-safe default.
+pub(crate) fn parse_list_include_expired_from_env() -> bool {
+    std::env::var("CONTEXT_PACK_LIST_INCLUDE_EXPIRED")
+        .map(|raw| matches!(raw.trim(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+pub(crate) fn conflict_changed_section_keys(current: &Pack, attempted: &Pack) -> Vec<String> {
     use std::collections::{BTreeMap, BTreeSet};
 
     fn map_sections(pack: &Pack) -> BTreeMap<String, String> {
@@ -74,25 +166,107 @@ fn conflict_changed_section_keys(current: &Pack, attempted: &Pack) -> Vec<String
 
 pub struct JsonStorageAdapter {
     pub(crate) storage_dir: PathBuf,
-    max_pack_bytes: usize,
+    codec: PackCodec,
     expired_grace_seconds: i64,
+    history_limit: usize,
+    list_include_expired: bool,
+    expired_retention: ExpiredRetention,
 }
 
 impl JsonStorageAdapter {
     pub fn new(storage_dir: PathBuf) -> Self {
         Self {
             storage_dir,
-            max_pack_bytes: parse_max_pack_bytes_from_env(),
+            codec: PackCodec {
+                compression: parse_compression_from_env(),
+                layout: parse_layout_from_env(),
+                ..PackCodec::plain(parse_max_pack_bytes_from_env())
+            },
             expired_grace_seconds: parse_expired_grace_seconds_from_env(),
+            history_limit: parse_history_limit_from_env(),
+            list_include_expired: parse_list_include_expired_from_env(),
+            expired_retention: parse_expired_retention_from_env(),
         }
     }
 
-    fn repo_lock_path(storage_dir: &Path) -> PathBuf {
+    /// Encrypts pack and history files written from now on; files already
+    /// on disk are read either way. See [`PackCipher`].
+    pub fn with_cipher(mut self, cipher: Option<PackCipher>) -> Self {
+        self.codec.cipher = cipher;
+        self
+    }
+
+    /// Compresses pack and history files written from now on; files already
+    /// on disk are read in whatever format they have. See [`Compression`].
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.codec.compression = compression;
+        self
+    }
+
+    /// Lays out packs written from now on; the next locked write or purge
+    /// moves the rest. See [`PackLayout`].
+    pub fn with_layout(mut self, layout: PackLayout) -> Self {
+        self.codec.layout = layout;
+        self
+    }
+
+    pub fn with_expired_retention(mut self, expired_retention: ExpiredRetention) -> Self {
+        self.expired_retention = expired_retention;
+        self
+    }
+
+    pub(crate) fn repo_lock_path(storage_dir: &Path) -> PathBuf {
         storage_dir.join(".repo.lock")
     }
 
-    fn pack_path(storage_dir: &Path, id: &PackId) -> PathBuf {
-        storage_dir.join(format!("{}.json", id.as_str()))
+    /// Runs `f` under the exclusive repository lock, for maintenance tools
+    /// (backup, restore, migration) that touch pack files directly.
+    pub(crate) fn with_repo_lock<T>(
+        storage_dir: &Path,
+        f: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        Self::ensure_dir_sync(storage_dir)?;
+        let lock_path = Self::repo_lock_path(storage_dir);
+        let lock = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+            .map_err(|e| {
+                DomainError::Io(format!(
+                    "failed to open repo lock '{}': {}",
+                    lock_path.display(),
+                    e
+                ))
+            })?;
+        lock.lock_exclusive()
+            .map_err(|e| DomainError::Io(format!("failed to lock repo: {}", e)))?;
+        let result = f();
+        if let Err(e) = lock.unlock() {
+            tracing::warn!("failed to unlock repo lock: {e}");
+        }
+        result
+    }
+
+    /// The file holding pack `id`, in whichever format and layout it was
+    /// written.
+    fn pack_path(storage_dir: &Path, id: &PackId) -> Option<PathBuf> {
+        locate_pack_file(storage_dir, id.as_str())
+    }
+
+    fn archive_dir(storage_dir: &Path) -> PathBuf {
+        storage_dir.join("archive")
+    }
+
+    fn history_dir(storage_dir: &Path, id: &PackId) -> PathBuf {
+        locate_pack_dir(storage_dir, id.as_str())
+            .unwrap_or_else(|| storage_dir.join(id.as_str()))
+            .join("history")
+    }
+
+    fn history_path(storage_dir: &Path, id: &PackId, revision: u64) -> Option<PathBuf> {
+        find_pack_file(&Self::history_dir(storage_dir, id), &revision.to_string())
     }
 
     fn ensure_dir_sync(storage_dir: &Path) -> Result<()> {
@@ -108,8 +282,11 @@ impl JsonStorageAdapter {
     fn new_with_max(storage_dir: PathBuf, max_pack_bytes: usize) -> Self {
         Self {
             storage_dir,
-            max_pack_bytes,
+            codec: PackCodec::plain(max_pack_bytes),
             expired_grace_seconds: DEFAULT_EXPIRED_GRACE_SECONDS,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            list_include_expired: false,
+            expired_retention: ExpiredRetention::Delete,
         }
     }
 
@@ -121,12 +298,15 @@ impl JsonStorageAdapter {
     ) -> Self {
         Self {
             storage_dir,
-            max_pack_bytes,
+            codec: PackCodec::plain(max_pack_bytes),
             expired_grace_seconds,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            list_include_expired: false,
+            expired_retention: ExpiredRetention::Delete,
         }
     }
 
-    fn is_within_grace_window(
+    pub(crate) fn is_within_grace_window(
         now: chrono::DateTime<chrono::Utc>,
         expires_at: chrono::DateTime<chrono::Utc>,
         expired_grace_seconds: i64,
@@ -135,14 +315,14 @@ impl JsonStorageAdapter {
         now <= expires_at + grace
     }
 
-    fn payload_too_large_error(path: &str, actual: usize, max: usize) -> DomainError {
+    pub(crate) fn payload_too_large_error(path: &str, actual: usize, max: usize) -> DomainError {
         DomainError::InvalidData(format!(
-            "pack '{}' payload is too large: {} bytes (max {})",
+            "pack '{}' payload is too large: {} bytes (max {}); move sections into a new pack with input split",
             path, actual, max
         ))
     }
 
-    fn encoded_pack_payload(pack: &Pack, max_pack_bytes: usize) -> Result<String> {
+    pub(crate) fn encoded_pack_payload(pack: &Pack, max_pack_bytes: usize) -> Result<String> {
         let payload = Self::encode(pack)?;
         if payload.len() > max_pack_bytes {
             return Err(Self::payload_too_large_error(
@@ -180,8 +360,8 @@ impl JsonStorageAdapter {
         }
     }
 
-    fn read_pack_for_lookup(path: &Path, max_pack_bytes: usize) -> Result<Option<Pack>> {
-        match Self::read_pack_from_path(path, max_pack_bytes) {
+    fn read_pack_for_lookup(path: &Path, codec: &PackCodec) -> Result<Option<Pack>> {
+        match Self::read_pack_from_path(path, codec) {
             Ok(pack) => Ok(Some(pack)),
             Err(err) if Self::is_recoverable_pack_read_error(&err) => {
                 Self::remove_corrupt_pack_file(path, &err, "read");
@@ -197,38 +377,61 @@ impl JsonStorageAdapter {
     }
 
     fn decode_with_path(path: &Path, content: &str) -> Result<Pack> {
+        Self::decode_at(&path.display().to_string(), content)
+    }
+
+    /// Decodes a pack read from `location`, naming it in any error.
+    pub(crate) fn decode_at(location: &str, content: &str) -> Result<Pack> {
         match Self::decode(content) {
             Ok(pack) => Ok(pack),
             Err(DomainError::MigrationRequired(msg)) => Err(DomainError::MigrationRequired(
-                format!("{} [path={}]", msg, path.display()),
+                format!("{} [path={}]", msg, location),
             )),
             Err(err @ DomainError::InvalidData(_)) => Err(err),
             Err(err) => Err(DomainError::Deserialize(format!(
                 "failed to decode pack '{}': {}",
-                path.display(),
-                err
+                location, err
             ))),
         }
     }
 
+    /// Pack files in `storage_dir` and its shards, one per pack: where an
+    /// interrupted rewrite or move left the same pack twice, the newer file
+    /// is used.
     fn list_pack_paths_sync(storage_dir: &Path) -> Result<Vec<PathBuf>> {
         if !storage_dir.exists() {
             return Ok(Vec::new());
         }
-        let mut out = Vec::new();
-        for entry in std::fs::read_dir(storage_dir)
-            .map_err(|e| DomainError::Io(format!("failed to read storage dir: {}", e)))?
-        {
+        let mut newest: BTreeMap<String, (Option<SystemTime>, PathBuf)> = BTreeMap::new();
+        let read_failed =
+            |e: std::io::Error| DomainError::Io(format!("failed to read storage dir: {}", e));
+        let entries = pack_dirs(storage_dir)
+            .map_err(read_failed)?
+            .into_iter()
+            .map(std::fs::read_dir)
+            .collect::<std::io::Result<Vec<_>>>()
+            .map_err(read_failed)?;
+        for entry in entries.into_iter().flatten() {
             let entry = entry.map_err(|e| DomainError::Io(format!("dir entry error: {}", e)))?;
             let path = entry.path();
-            if path.is_file() && path.extension().and_then(|v| v.to_str()) == Some("json") {
-                out.push(path);
+            if !path.is_file() {
+                continue;
+            }
+            let Some(stem) = pack_file_stem(&path).map(str::to_string) else {
+                continue;
+            };
+            let modified = entry.metadata().and_then(|meta| meta.modified()).ok();
+            match newest.get(&stem) {
+                Some((seen, _)) if *seen >= modified => {}
+                _ => {
+                    newest.insert(stem, (modified, path));
+                }
             }
         }
-        Ok(out)
+        Ok(newest.into_values().map(|(_, path)| path).collect())
     }
 
-    fn read_pack_from_path(path: &Path, max_pack_bytes: usize) -> Result<Pack> {
+    fn read_pack_from_path(path: &Path, codec: &PackCodec) -> Result<Pack> {
         let meta = std::fs::metadata(path).map_err(|e| {
             DomainError::Io(format!(
                 "failed to stat pack file '{}': {}",
@@ -242,51 +445,113 @@ impl JsonStorageAdapter {
                 path.display()
             )));
         }
-        if usize::try_from(meta.len()).unwrap_or(usize::MAX) > max_pack_bytes {
-            return Err(DomainError::Io(format!(
-                "pack file '{}' is too large: {} bytes (max {})",
-                path.display(),
-                meta.len(),
-                max_pack_bytes
-            )));
+        if usize::try_from(meta.len()).unwrap_or(usize::MAX) > codec.max_file_bytes() {
+            return Err(codec.file_too_large(path, meta.len()));
         }
-        let raw = std::fs::read_to_string(path).map_err(|e| {
+        let raw = std::fs::read(path).map_err(|e| {
             DomainError::Io(format!(
                 "failed to read pack file '{}': {}",
                 path.display(),
                 e
             ))
         })?;
+        let raw = codec.open(raw, path)?;
         Self::decode_with_path(path, &raw)
     }
 
-    fn write_pack_atomic(storage_dir: &Path, pack: &Pack, max_pack_bytes: usize) -> Result<()> {
-        let path = Self::pack_path(storage_dir, &pack.id);
-        let tmp = storage_dir.join(format!("{}.tmp", pack.id.as_str()));
-        let content = Self::encoded_pack_payload(pack, max_pack_bytes)?;
+    /// Writes `pack` in the codec's format and layout, then drops the file
+    /// it replaces if that was in another format or place.
+    fn write_pack_atomic(storage_dir: &Path, pack: &Pack, codec: &PackCodec) -> Result<()> {
+        let staged = Self::stage_pack(storage_dir, pack, codec)?;
+        staged.install()?;
+        Self::finish_pack_write(storage_dir, pack, codec, &staged.path)
+    }
+
+    /// Encodes `pack` into a tmp file next to its final path; nothing a
+    /// reader sees changes until [`StagedPack::install`].
+    fn stage_pack(storage_dir: &Path, pack: &Pack, codec: &PackCodec) -> Result<StagedPack> {
+        let path = codec.pack_file_path(storage_dir, pack.id.as_str());
+        let home = path.parent().unwrap_or(storage_dir);
+        std::fs::create_dir_all(home)
+            .map_err(|e| DomainError::Io(format!("failed to create pack dir: {}", e)))?;
+        let tmp = home.join(format!("{}.tmp", pack.id.as_str()));
+        let content = codec.seal(Self::encoded_pack_payload(pack, codec.max_pack_bytes)?)?;
         std::fs::write(&tmp, content)
             .map_err(|e| DomainError::Io(format!("failed to write tmp pack: {}", e)))?;
-        std::fs::rename(&tmp, &path)
-            .map_err(|e| DomainError::Io(format!("failed to rename pack file: {}", e)))?;
+        Ok(StagedPack { tmp, path })
+    }
+
+    /// Drops the files an installed `path` replaces and indexes it.
+    fn finish_pack_write(
+        storage_dir: &Path,
+        pack: &Pack,
+        codec: &PackCodec,
+        path: &Path,
+    ) -> Result<()> {
+        remove_pack_files(storage_dir, pack.id.as_str(), Some(path))
+            .map_err(|e| DomainError::Io(format!("failed to remove replaced pack file: {}", e)))?;
+        Self::update_index_sync(storage_dir, codec, pack.id.as_str(), Some((pack, path)));
         Ok(())
     }
 
-    fn read_pack_meta_from_path(path: &Path, max_pack_bytes: usize) -> Option<PackMeta> {
-        let file_len = usize::try_from(std::fs::metadata(path).ok()?.len()).unwrap_or(usize::MAX);
-        if file_len > max_pack_bytes {
-            Self::remove_corrupt_pack_file(
-                path,
-                &DomainError::Io(format!(
-                    "pack file '{}' is too large: {} bytes (max {})",
-                    path.display(),
-                    file_len,
-                    max_pack_bytes
-                )),
-                "purge",
-            );
+    /// Fails unless `pack` can be created: its id is unused and no stored
+    /// pack has its name.
+    fn ensure_creatable_sync(storage_dir: &Path, codec: &PackCodec, pack: &Pack) -> Result<()> {
+        if Self::pack_path(storage_dir, &pack.id).is_some() {
+            return Err(DomainError::PackIdConflict(pack.id.to_string()));
+        }
+        if let Some(new_name) = &pack.name {
+            let (mut index, changed) = Self::refresh_index_sync(storage_dir, codec)?;
+            if changed {
+                if let Err(e) = index.save(storage_dir, codec.cipher.as_ref()) {
+                    tracing::debug!("pack index not saved: {}", e);
+                }
+            }
+            if index.ids_named(new_name.as_str()).next().is_some() {
+                return Err(DomainError::Conflict(format!(
+                    "pack with name '{}' already exists",
+                    new_name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The stored revision `pack` replaces, if it is still `expected_revision`.
+    fn current_for_update_sync(
+        storage_dir: &Path,
+        codec: &PackCodec,
+        pack: &Pack,
+        expected_revision: u64,
+    ) -> Result<Pack> {
+        let current = Self::pack_path(storage_dir, &pack.id)
+            .map(|path| Self::read_pack_for_lookup(&path, codec))
+            .transpose()?
+            .flatten()
+            .ok_or_else(|| DomainError::NotFound(format!("pack '{}' not found", pack.id)))?;
+        if current.revision != expected_revision {
+            return Err(DomainError::RevisionConflictDetailed {
+                expected_revision,
+                current_revision: current.revision,
+                last_updated_at: current.updated_at.to_rfc3339(),
+                changed_section_keys: conflict_changed_section_keys(&current, pack),
+                guidance: revision_conflict_guidance(
+                    current.revision,
+                    current.current_write_reason(),
+                ),
+                produced_by: current.current_write_reason().map(str::to_string),
+            });
+        }
+        Ok(current)
+    }
+
+    fn read_pack_meta_from_path(path: &Path, codec: &PackCodec) -> Option<PackMeta> {
+        let file_len = std::fs::metadata(path).ok()?.len();
+        if usize::try_from(file_len).unwrap_or(usize::MAX) > codec.max_file_bytes() {
+            Self::remove_corrupt_pack_file(path, &codec.file_too_large(path, file_len), "purge");
             return None;
         }
-        let raw = match std::fs::read_to_string(path) {
+        let raw = match std::fs::read(path) {
             Ok(raw) => raw,
             Err(err) => {
                 Self::remove_corrupt_pack_file(
@@ -301,6 +566,18 @@ impl JsonStorageAdapter {
                 return None;
             }
         };
+        let raw = match codec.open(raw, path) {
+            Ok(raw) => raw,
+            // Keep what the key can't open; expiry is checked once it can.
+            Err(err @ DomainError::DecryptionFailed(_)) => {
+                tracing::warn!("skipping pack during purge: {}", err);
+                return None;
+            }
+            Err(err) => {
+                Self::remove_corrupt_pack_file(path, &err, "purge");
+                return None;
+            }
+        };
         serde_json::from_str::<PackMeta>(&raw).ok().or_else(|| {
             Self::remove_corrupt_pack_file(
                 path,
@@ -316,24 +593,42 @@ impl JsonStorageAdapter {
 
     fn purge_expired_sync(
         storage_dir: &Path,
-        max_pack_bytes: usize,
+        codec: &PackCodec,
         expired_grace_seconds: i64,
+        retention: ExpiredRetention,
     ) -> Result<()> {
+        match relayout(storage_dir, codec.layout) {
+            Ok(0) => {}
+            Ok(moved) => tracing::info!(
+                "moved {} pack(s) to the {} layout",
+                moved,
+                codec.layout.name()
+            ),
+            Err(e) => tracing::warn!(
+                "failed to move packs to the {} layout: {}",
+                codec.layout.name(),
+                e
+            ),
+        }
         let now = Utc::now();
         let paths = Self::list_pack_paths_sync(storage_dir)?;
         for path in paths {
-            let meta = Self::read_pack_meta_from_path(&path, max_pack_bytes);
+            let meta = Self::read_pack_meta_from_path(&path, codec);
             let is_expired_after_grace =
                 meta.and_then(|m| m.expires_at).is_some_and(|expires_at| {
                     !Self::is_within_grace_window(now, expires_at, expired_grace_seconds)
                 });
             if is_expired_after_grace {
-                match std::fs::remove_file(&path) {
-                    Ok(()) => {}
+                match Self::retire_expired_sync(storage_dir, &path, retention) {
+                    Ok(()) => {
+                        if let Some(id) = pack_file_stem(&path) {
+                            Self::update_index_sync(storage_dir, codec, id, None);
+                        }
+                    }
                     Err(e) if e.kind() == ErrorKind::NotFound => {}
                     Err(e) => {
                         return Err(DomainError::Io(format!(
-                            "failed to remove expired pack '{}': {}",
+                            "failed to retire expired pack '{}': {}",
                             path.display(),
                             e
                         )));
@@ -341,28 +636,135 @@ impl JsonStorageAdapter {
                 }
             }
         }
+        if let ExpiredRetention::Archive { retention } = retention {
+            Self::purge_archive_sync(storage_dir, codec, expired_grace_seconds, retention)?;
+        }
         Ok(())
     }
 
-    fn delete_pack_file_sync(storage_dir: &Path, id: &PackId) -> Result<bool> {
-        let path = Self::pack_path(storage_dir, id);
-        match std::fs::remove_file(path) {
-            Ok(()) => Ok(true),
-            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
-            Err(e) => Err(DomainError::Io(format!(
-                "failed to delete pack file: {}",
-                e
-            ))),
+    /// Deletes or archives the pack file at `path` and its history journal.
+    fn retire_expired_sync(
+        storage_dir: &Path,
+        path: &Path,
+        retention: ExpiredRetention,
+    ) -> std::io::Result<()> {
+        let id = pack_file_stem(path).and_then(|stem| PackId::parse(stem).ok());
+        match retention {
+            ExpiredRetention::Delete => {
+                std::fs::remove_file(path)?;
+                if let Some(id) = &id {
+                    Self::remove_history_sync(storage_dir, id);
+                }
+            }
+            ExpiredRetention::Archive { .. } => {
+                let archive = Self::archive_dir(storage_dir);
+                std::fs::create_dir_all(&archive)?;
+                let Some(file_name) = path.file_name() else {
+                    return Ok(());
+                };
+                std::fs::rename(path, archive.join(file_name))?;
+                if let Some(id) = &id {
+                    let target = archive.join(id.as_str());
+                    if let Some(history) = locate_pack_dir(storage_dir, id.as_str()) {
+                        let _ = std::fs::remove_dir_all(&target);
+                        if let Err(e) = std::fs::rename(&history, &target) {
+                            tracing::warn!("failed to archive history of '{}': {}", id, e);
+                        }
+                    }
+                    tracing::info!("archived expired pack '{}'", id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes archived packs whose archive retention has run out too.
+    fn purge_archive_sync(
+        storage_dir: &Path,
+        codec: &PackCodec,
+        expired_grace_seconds: i64,
+        retention: chrono::Duration,
+    ) -> Result<()> {
+        let archive = Self::archive_dir(storage_dir);
+        let now = Utc::now();
+        for path in Self::list_pack_paths_sync(&archive)? {
+            let expires_at = std::fs::read(&path)
+                .ok()
+                .and_then(|raw| codec.open(raw, &path).ok())
+                .and_then(|raw| serde_json::from_str::<PackMeta>(&raw).ok())
+                .and_then(|meta| meta.expires_at);
+            let Some(expires_at) = expires_at else {
+                continue;
+            };
+            let grace = chrono::Duration::seconds(expired_grace_seconds.max(0));
+            if now <= expires_at + grace + retention {
+                continue;
+            }
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(DomainError::Io(format!(
+                        "failed to remove archived pack '{}': {}",
+                        path.display(),
+                        e
+                    )));
+                }
+            }
+            if let Some(id) = pack_file_stem(&path).and_then(|stem| PackId::parse(stem).ok()) {
+                Self::remove_history_sync(&archive, &id);
+            }
         }
+        Ok(())
     }
 
-    fn load_all_sync(storage_dir: &Path, max_pack_bytes: usize) -> Result<Vec<Pack>> {
+    /// Archived packs matching `filter` (status, tags, query, paging),
+    /// newest first. Unreadable archive files are skipped, not removed.
+    fn list_archived_sync(
+        storage_dir: &Path,
+        codec: &PackCodec,
+        filter: &ListFilter,
+    ) -> Result<Vec<Pack>> {
+        let query_lower = filter
+            .query
+            .as_ref()
+            .map(|query| query.trim().to_lowercase())
+            .filter(|query| !query.is_empty());
         let mut packs = Vec::new();
-        for path in Self::list_pack_paths_sync(storage_dir)? {
-            let pack = match Self::read_pack_for_lookup(&path, max_pack_bytes)? {
-                Some(pack) => pack,
-                None => continue,
+        for path in Self::list_pack_paths_sync(&Self::archive_dir(storage_dir))? {
+            let pack = match Self::read_pack_from_path(&path, codec) {
+                Ok(pack) => pack,
+                Err(e) => {
+                    tracing::warn!(
+                        "skipping unreadable archived pack '{}': {}",
+                        path.display(),
+                        e
+                    );
+                    continue;
+                }
             };
+            if filter.status.is_some_and(|status| pack.status != status) {
+                continue;
+            }
+            if !filter
+                .tags
+                .iter()
+                .all(|wanted| pack.tags.iter().any(|tag| tag.eq_ignore_ascii_case(wanted)))
+            {
+                continue;
+            }
+            if let Some(q_lower) = &query_lower {
+                let haystack = format!(
+                    "{} {} {}",
+                    pack.title.as_deref().unwrap_or(""),
+                    pack.name.as_ref().map(|name| name.as_str()).unwrap_or(""),
+                    pack.brief.as_deref().unwrap_or("")
+                )
+                .to_lowercase();
+                if !haystack.contains(q_lower.as_str()) {
+                    continue;
+                }
+            }
             packs.push(pack);
         }
         packs.sort_by(|a, b| {
@@ -371,21 +773,251 @@ impl JsonStorageAdapter {
                 .then_with(|| b.revision.cmp(&a.revision))
                 .then_with(|| a.id.as_str().cmp(b.id.as_str()))
         });
-        Ok(packs)
+        Ok(packs
+            .into_iter()
+            .skip(filter.offset.unwrap_or(0))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .collect())
     }
 
-    fn load_all_active_sync(
+    fn delete_pack_file_sync(storage_dir: &Path, id: &PackId, codec: &PackCodec) -> Result<bool> {
+        Self::remove_history_sync(storage_dir, id);
+        let removed = remove_pack_files(storage_dir, id.as_str(), None)
+            .map_err(|e| DomainError::Io(format!("failed to delete pack file: {}", e)))?;
+        Self::update_index_sync(storage_dir, codec, id.as_str(), None);
+        Ok(removed)
+    }
+
+    /// Journals `previous` as `<id>/history/<revision>.json[.gz|.zst]` before it is
+    /// overwritten, then drops the oldest entries beyond `history_limit`.
+    fn write_history_sync(
         storage_dir: &Path,
-        max_pack_bytes: usize,
+        previous: &Pack,
+        history_limit: usize,
+        codec: &PackCodec,
+    ) -> Result<()> {
+        if history_limit == 0 {
+            return Ok(());
+        }
+        let id = previous.id.as_str();
+        let pack_dir = codec.layout.home(storage_dir, id).join(id);
+        if let Some(existing) = locate_pack_dir(storage_dir, id).filter(|dir| *dir != pack_dir) {
+            // The journal follows its pack into the configured layout.
+            std::fs::rename(&existing, &pack_dir).map_err(|e| {
+                DomainError::Io(format!("failed to move history of '{}': {}", id, e))
+            })?;
+        }
+        let dir = pack_dir.join("history");
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            DomainError::Io(format!(
+                "failed to create history dir '{}': {}",
+                dir.display(),
+                e
+            ))
+        })?;
+        let stem = previous.revision.to_string();
+        let path = dir.join(codec.file_name(&stem));
+        let tmp = dir.join(format!("{stem}.tmp"));
+        std::fs::write(&tmp, codec.seal(Self::encode(previous)?)?)
+            .map_err(|e| DomainError::Io(format!("failed to write tmp history entry: {}", e)))?;
+        std::fs::rename(&tmp, &path)
+            .map_err(|e| DomainError::Io(format!("failed to rename history entry: {}", e)))?;
+        if let Err(e) = remove_pack_file_variants(&dir, &stem, Some(&path)) {
+            tracing::warn!("failed to remove replaced history entry '{}': {}", stem, e);
+        }
+
+        let revisions = Self::list_history_revisions_sync(storage_dir, &previous.id)?;
+        let excess = revisions.len().saturating_sub(history_limit);
+        for revision in &revisions[..excess] {
+            if let Err(e) = remove_pack_file_variants(&dir, &revision.to_string(), None) {
+                tracing::warn!("failed to prune history entry {}: {}", revision, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Journaled revisions of `id`, oldest first.
+    fn list_history_revisions_sync(storage_dir: &Path, id: &PackId) -> Result<Vec<u64>> {
+        let dir = Self::history_dir(storage_dir, id);
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(DomainError::Io(format!(
+                    "failed to read history dir '{}': {}",
+                    dir.display(),
+                    e
+                )))
+            }
+        };
+        let mut revisions = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| DomainError::Io(format!("dir entry error: {}", e)))?
+                .path();
+            if let Some(revision) = pack_file_stem(&path).and_then(|stem| stem.parse::<u64>().ok())
+            {
+                revisions.push(revision);
+            }
+        }
+        revisions.sort_unstable();
+        revisions.dedup();
+        Ok(revisions)
+    }
+
+    fn remove_history_sync(storage_dir: &Path, id: &PackId) {
+        for home in homes(storage_dir, id.as_str()) {
+            if let Err(e) = std::fs::remove_dir_all(home.join(id.as_str())) {
+                if e.kind() != ErrorKind::NotFound {
+                    tracing::warn!("failed to remove history of '{}': {}", id, e);
+                }
+            }
+        }
+    }
+
+    /// The metadata index with an entry per pack file, re-reading only files
+    /// whose stamp no longer matches. Returns whether any entry changed.
+    fn refresh_index_sync(storage_dir: &Path, codec: &PackCodec) -> Result<(PackIndex, bool)> {
+        let mut index = PackIndex::load(storage_dir, codec.cipher.as_ref());
+        let mut changed = false;
+        let mut seen = BTreeSet::new();
+        for path in Self::list_pack_paths_sync(storage_dir)? {
+            let Some(id) = pack_file_stem(&path) else {
+                continue;
+            };
+            let Some(stamp) = FileStamp::of(&path) else {
+                continue;
+            };
+            let id = id.to_string();
+            if index
+                .entries
+                .get(&id)
+                .is_some_and(|entry| entry.stamp == stamp)
+            {
+                seen.insert(id);
+                continue;
+            }
+            changed = true;
+            if let Some(pack) = Self::read_pack_for_lookup(&path, codec)? {
+                index
+                    .entries
+                    .insert(id.clone(), PackIndexEntry::from_pack(&pack, stamp));
+                seen.insert(id);
+            }
+        }
+        let before = index.entries.len();
+        index.entries.retain(|id, _| seen.contains(id));
+        changed |= index.entries.len() != before;
+        Ok((index, changed))
+    }
+
+    /// Records a write of pack `id` (`None` for a removal) in the index.
+    /// Callers hold the repository lock. Failures only cost the next list a
+    /// re-read, since entries are checked against the file stamps.
+    fn update_index_sync(
+        storage_dir: &Path,
+        codec: &PackCodec,
+        id: &str,
+        written: Option<(&Pack, &Path)>,
+    ) {
+        let mut index = PackIndex::load(storage_dir, codec.cipher.as_ref());
+        match written.and_then(|(pack, path)| Some((pack, FileStamp::of(path)?))) {
+            Some((pack, stamp)) => {
+                index
+                    .entries
+                    .insert(id.to_string(), PackIndexEntry::from_pack(pack, stamp));
+            }
+            None => {
+                index.entries.remove(id);
+            }
+        }
+        if let Err(e) = index.save(storage_dir, codec.cipher.as_ref()) {
+            tracing::debug!("pack index not updated: {}", e);
+        }
+    }
+
+    /// Saves a refreshed index unless a writer holds the repository lock;
+    /// the next list refreshes it again in that case.
+    fn persist_index_if_unlocked(storage_dir: &Path, index: &mut PackIndex, codec: &PackCodec) {
+        let lock_path = Self::repo_lock_path(storage_dir);
+        let Ok(lock) = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+        else {
+            return;
+        };
+        if lock.try_lock_exclusive().is_err() {
+            return;
+        }
+        if let Err(e) = index.save(storage_dir, codec.cipher.as_ref()) {
+            tracing::debug!("pack index not saved: {}", e);
+        }
+        if let Err(e) = lock.unlock() {
+            tracing::warn!("failed to unlock repo lock: {e}");
+        }
+    }
+
+    /// Whether a pack (as its index entry) passes a live `list` filter;
+    /// `list_include_expired` is the store default for `filter.include_expired`.
+    pub(crate) fn index_entry_matches(
+        entry: &PackIndexEntry,
+        filter: &ListFilter,
+        now: DateTime<Utc>,
         expired_grace_seconds: i64,
-    ) -> Result<Vec<Pack>> {
-        let now = Utc::now();
-        Ok(Self::load_all_sync(storage_dir, max_pack_bytes)?
-            .into_iter()
-            .filter(|pack| {
-                Self::is_within_grace_window(now, pack.expires_at, expired_grace_seconds)
-            })
-            .collect())
+        list_include_expired: bool,
+    ) -> bool {
+        let include_expired = filter.include_expired.unwrap_or(list_include_expired);
+        let freshness_state = entry.freshness(now);
+        let is_within_grace =
+            Self::is_within_grace_window(now, entry.expires_at, expired_grace_seconds);
+        if let Some(required_freshness) = filter.freshness {
+            if required_freshness == FreshnessState::Expired {
+                if freshness_state != FreshnessState::Expired || !is_within_grace {
+                    return false;
+                }
+            } else if freshness_state != required_freshness {
+                return false;
+            }
+        } else if freshness_state == FreshnessState::Expired
+            && !(include_expired && is_within_grace)
+        {
+            // Stale-safe default: keep expired packs hidden unless explicitly asked.
+            return false;
+        }
+        if let Some(s) = filter.status {
+            if entry.status != s {
+                return false;
+            }
+        }
+        if !filter.tags.iter().all(|wanted| {
+            entry
+                .tags
+                .iter()
+                .any(|tag| tag.eq_ignore_ascii_case(wanted))
+        }) {
+            return false;
+        }
+        let query_lower = filter
+            .query
+            .as_ref()
+            .map(|query| query.trim().to_lowercase())
+            .filter(|query| !query.is_empty());
+        if let Some(q_lower) = query_lower {
+            let haystack = format!(
+                "{} {} {}",
+                entry.title.as_deref().unwrap_or(""),
+                entry.name.as_deref().unwrap_or(""),
+                entry.brief.as_deref().unwrap_or("")
+            )
+            .to_lowercase();
+            if !haystack.contains(q_lower.as_str()) {
+                return false;
+            }
+        }
+        true
     }
 
     fn candidate_ids(candidates: &[Pack]) -> Vec<String> {
@@ -416,7 +1048,10 @@ impl JsonStorageAdapter {
         }
     }
 
-    fn select_pack_by_name(name: &PackName, candidates: Vec<Pack>) -> Result<Option<Pack>> {
+    pub(crate) fn select_pack_by_name(
+        name: &PackName,
+        candidates: Vec<Pack>,
+    ) -> Result<Option<Pack>> {
         if candidates.is_empty() {
             return Ok(None);
         }
@@ -472,8 +1107,9 @@ impl JsonStorageAdapter {
 
     async fn purge_expired_locked(&self) -> Result<()> {
         let storage_dir = self.storage_dir.clone();
-        let max_pack_bytes = self.max_pack_bytes;
+        let codec = self.codec.clone();
         let expired_grace_seconds = self.expired_grace_seconds;
+        let expired_retention = self.expired_retention;
         task::spawn_blocking(move || -> Result<()> {
             Self::ensure_dir_sync(&storage_dir)?;
             let lock_path = Self::repo_lock_path(&storage_dir);
@@ -492,7 +1128,12 @@ impl JsonStorageAdapter {
                 })?;
             lock.lock_exclusive()
                 .map_err(|e| DomainError::Io(format!("failed to lock repo: {}", e)))?;
-            Self::purge_expired_sync(&storage_dir, max_pack_bytes, expired_grace_seconds)?;
+            Self::purge_expired_sync(
+                &storage_dir,
+                &codec,
+                expired_grace_seconds,
+                expired_retention,
+            )?;
             lock.unlock()
                 .map_err(|e| DomainError::Io(format!("failed to unlock repo: {}", e)))?;
             Ok(())
@@ -507,8 +1148,9 @@ impl JsonStorageAdapter {
 impl PackRepositoryPort for JsonStorageAdapter {
     async fn create_new(&self, pack: &Pack) -> Result<()> {
         let storage_dir = self.storage_dir.clone();
-        let max_pack_bytes = self.max_pack_bytes;
+        let codec = self.codec.clone();
         let expired_grace_seconds = self.expired_grace_seconds;
+        let expired_retention = self.expired_retention;
         let pack = pack.clone();
         task::spawn_blocking(move || -> Result<()> {
             Self::ensure_dir_sync(&storage_dir)?;
@@ -528,36 +1170,21 @@ impl PackRepositoryPort for JsonStorageAdapter {
                 })?;
             lock.lock_exclusive()
                 .map_err(|e| DomainError::Io(format!("failed to lock repo: {}", e)))?;
-            Self::purge_expired_sync(&storage_dir, max_pack_bytes, expired_grace_seconds)?;
-
-            let path = Self::pack_path(&storage_dir, &pack.id);
-            if path.exists() {
+            Self::purge_expired_sync(
+                &storage_dir,
+                &codec,
+                expired_grace_seconds,
+                expired_retention,
+            )?;
+
+            if let Err(err) = Self::ensure_creatable_sync(&storage_dir, &codec, &pack) {
                 if let Err(e) = lock.unlock() {
                     tracing::warn!("failed to unlock repo lock: {e}");
                 }
-                return Err(DomainError::PackIdConflict(pack.id.to_string()));
-            }
-
-            if let Some(new_name) = &pack.name {
-                for existing_path in Self::list_pack_paths_sync(&storage_dir)? {
-                    let existing = match Self::read_pack_for_lookup(&existing_path, max_pack_bytes)?
-                    {
-                        Some(existing) => existing,
-                        None => continue,
-                    };
-                    if existing.name.as_ref() == Some(new_name) {
-                        if let Err(e) = lock.unlock() {
-                            tracing::warn!("failed to unlock repo lock: {e}");
-                        }
-                        return Err(DomainError::Conflict(format!(
-                            "pack with name '{}' already exists",
-                            new_name
-                        )));
-                    }
-                }
+                return Err(err);
             }
 
-            Self::write_pack_atomic(&storage_dir, &pack, max_pack_bytes)?;
+            Self::write_pack_atomic(&storage_dir, &pack, &codec)?;
             lock.unlock()
                 .map_err(|e| DomainError::Io(format!("failed to unlock repo: {}", e)))?;
             Ok(())
@@ -569,8 +1196,10 @@ impl PackRepositoryPort for JsonStorageAdapter {
 
     async fn save_with_expected_revision(&self, pack: &Pack, expected_revision: u64) -> Result<()> {
         let storage_dir = self.storage_dir.clone();
-        let max_pack_bytes = self.max_pack_bytes;
+        let codec = self.codec.clone();
         let expired_grace_seconds = self.expired_grace_seconds;
+        let expired_retention = self.expired_retention;
+        let history_limit = self.history_limit;
         let pack = pack.clone();
         task::spawn_blocking(move || -> Result<()> {
             Self::ensure_dir_sync(&storage_dir)?;
@@ -590,34 +1219,27 @@ impl PackRepositoryPort for JsonStorageAdapter {
                 })?;
             lock.lock_exclusive()
                 .map_err(|e| DomainError::Io(format!("failed to lock repo: {}", e)))?;
-            Self::purge_expired_sync(&storage_dir, max_pack_bytes, expired_grace_seconds)?;
-
-            let path = Self::pack_path(&storage_dir, &pack.id);
-            if !path.exists() {
-                if let Err(e) = lock.unlock() {
-                    tracing::warn!("failed to unlock repo lock: {e}");
-                }
-                return Err(DomainError::NotFound(format!(
-                    "pack '{}' not found",
-                    pack.id
-                )));
-            }
-            let current = Self::read_pack_for_lookup(&path, max_pack_bytes)?
-                .ok_or_else(|| DomainError::NotFound(format!("pack '{}' not found", pack.id)))?;
-            if current.revision != expected_revision {
-                if let Err(e) = lock.unlock() {
-                    tracing::warn!("failed to unlock repo lock: {e}");
-                }
-                return Err(DomainError::RevisionConflictDetailed {
-                    expected_revision,
-                    current_revision: current.revision,
-                    last_updated_at: current.updated_at.to_rfc3339(),
-                    changed_section_keys: conflict_changed_section_keys(&current, &pack),
-                    guidance: revision_conflict_guidance(current.revision),
-                });
-            }
+            Self::purge_expired_sync(
+                &storage_dir,
+                &codec,
+                expired_grace_seconds,
+                expired_retention,
+            )?;
+
+            let current =
+                match Self::current_for_update_sync(&storage_dir, &codec, &pack, expected_revision)
+                {
+                    Ok(current) => current,
+                    Err(err) => {
+                        if let Err(e) = lock.unlock() {
+                            tracing::warn!("failed to unlock repo lock: {e}");
+                        }
+                        return Err(err);
+                    }
+                };
 
-            Self::write_pack_atomic(&storage_dir, &pack, max_pack_bytes)?;
+            Self::write_history_sync(&storage_dir, &current, history_limit, &codec)?;
+            Self::write_pack_atomic(&storage_dir, &pack, &codec)?;
             lock.unlock()
                 .map_err(|e| DomainError::Io(format!("failed to unlock repo: {}", e)))?;
             Ok(())
@@ -627,9 +1249,76 @@ impl PackRepositoryPort for JsonStorageAdapter {
         Ok(())
     }
 
+    /// Both packs are encoded into tmp files before either is renamed into
+    /// place, so an oversized pack or a stale revision stores nothing. If the
+    /// parent's rename fails after the new pack's, the new pack is removed.
+    async fn create_with_update(
+        &self,
+        created: &Pack,
+        updated: &Pack,
+        expected_revision: u64,
+    ) -> Result<()> {
+        let storage_dir = self.storage_dir.clone();
+        let codec = self.codec.clone();
+        let expired_grace_seconds = self.expired_grace_seconds;
+        let expired_retention = self.expired_retention;
+        let history_limit = self.history_limit;
+        let (created, updated) = (created.clone(), updated.clone());
+        task::spawn_blocking(move || {
+            Self::with_repo_lock(&storage_dir, || {
+                Self::purge_expired_sync(
+                    &storage_dir,
+                    &codec,
+                    expired_grace_seconds,
+                    expired_retention,
+                )?;
+                Self::ensure_creatable_sync(&storage_dir, &codec, &created)?;
+                let current = Self::current_for_update_sync(
+                    &storage_dir,
+                    &codec,
+                    &updated,
+                    expected_revision,
+                )?;
+
+                let child = Self::stage_pack(&storage_dir, &created, &codec)?;
+                let parent = match Self::stage_pack(&storage_dir, &updated, &codec) {
+                    Ok(parent) => parent,
+                    Err(e) => {
+                        child.discard();
+                        return Err(e);
+                    }
+                };
+                if let Err(e) =
+                    Self::write_history_sync(&storage_dir, &current, history_limit, &codec)
+                        .and_then(|()| child.install())
+                {
+                    child.discard();
+                    parent.discard();
+                    return Err(e);
+                }
+                if let Err(e) = parent.install() {
+                    parent.discard();
+                    if let Err(cleanup) = std::fs::remove_file(&child.path) {
+                        tracing::warn!(
+                            "failed to remove new pack '{}': {}",
+                            child.path.display(),
+                            cleanup
+                        );
+                    }
+                    return Err(e);
+                }
+                Self::finish_pack_write(&storage_dir, &created, &codec, &child.path)?;
+                Self::finish_pack_write(&storage_dir, &updated, &codec, &parent.path)
+            })
+        })
+        .await
+        .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+
     async fn delete_pack_file(&self, id: &PackId) -> Result<bool> {
         let storage_dir = self.storage_dir.clone();
         let id = id.clone();
+        let codec = self.codec.clone();
         let removed = task::spawn_blocking(move || -> Result<bool> {
             Self::ensure_dir_sync(&storage_dir)?;
             let lock_path = Self::repo_lock_path(&storage_dir);
@@ -648,7 +1337,7 @@ impl PackRepositoryPort for JsonStorageAdapter {
                 })?;
             lock.lock_exclusive()
                 .map_err(|e| DomainError::Io(format!("failed to lock repo: {}", e)))?;
-            let removed = Self::delete_pack_file_sync(&storage_dir, &id);
+            let removed = Self::delete_pack_file_sync(&storage_dir, &id, &codec);
             if let Err(err) = lock.unlock() {
                 tracing::warn!("failed to unlock repo lock: {err}");
             }
@@ -659,27 +1348,53 @@ impl PackRepositoryPort for JsonStorageAdapter {
         Ok(removed)
     }
 
+    async fn extend_expiry(&self, id: &PackId, expires_at: DateTime<Utc>) -> Result<()> {
+        let storage_dir = self.storage_dir.clone();
+        let codec = self.codec.clone();
+        let id = id.clone();
+        task::spawn_blocking(move || {
+            Self::with_repo_lock(&storage_dir, || {
+                let current = match Self::pack_path(&storage_dir, &id) {
+                    Some(path) => Self::read_pack_for_lookup(&path, &codec)?,
+                    None => None,
+                };
+                match current {
+                    Some(mut pack) if pack.expires_at < expires_at => {
+                        pack.expires_at = expires_at;
+                        Self::write_pack_atomic(&storage_dir, &pack, &codec)
+                    }
+                    Some(_) => Ok(()),
+                    None => Err(DomainError::NotFound(format!("pack '{}' not found", id))),
+                }
+            })
+        })
+        .await
+        .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+
     async fn get_by_id(&self, id: &PackId) -> Result<Option<Pack>> {
         let storage_dir = self.storage_dir.clone();
         let id = id.clone();
-        let max_pack_bytes = self.max_pack_bytes;
+        let codec = self.codec.clone();
         let expired_grace_seconds = self.expired_grace_seconds;
+        let expired_retention = self.expired_retention;
         task::spawn_blocking(move || -> Result<Option<Pack>> {
-            let path = Self::pack_path(&storage_dir, &id);
-            if !path.exists() {
+            let Some(path) = Self::pack_path(&storage_dir, &id) else {
                 return Ok(None);
-            }
-            let pack = match Self::read_pack_for_lookup(&path, max_pack_bytes)? {
+            };
+            let pack = match Self::read_pack_for_lookup(&path, &codec)? {
                 Some(pack) => pack,
                 None => return Ok(None),
             };
             if !Self::is_within_grace_window(Utc::now(), pack.expires_at, expired_grace_seconds) {
-                match std::fs::remove_file(&path) {
+                match Self::retire_expired_sync(&storage_dir, &path, expired_retention) {
                     Ok(()) => {}
                     Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    // A read-only store keeps the file; the pack still reads as gone.
+                    Err(e) if is_read_only_error(&e) => return Ok(None),
                     Err(e) => {
                         return Err(DomainError::Io(format!(
-                            "failed to remove expired pack '{}': {}",
+                            "failed to retire expired pack '{}': {}",
                             path.display(),
                             e
                         )));
@@ -695,15 +1410,30 @@ impl PackRepositoryPort for JsonStorageAdapter {
 
     async fn get_by_name(&self, name: &PackName) -> Result<Option<Pack>> {
         let storage_dir = self.storage_dir.clone();
-        let max_pack_bytes = self.max_pack_bytes;
+        let codec = self.codec.clone();
         let expired_grace_seconds = self.expired_grace_seconds;
         let name = name.clone();
         task::spawn_blocking(move || -> Result<Option<Pack>> {
-            let matches =
-                Self::load_all_active_sync(&storage_dir, max_pack_bytes, expired_grace_seconds)?
-                    .into_iter()
-                    .filter(|pack| pack.name.as_ref() == Some(&name))
-                    .collect::<Vec<_>>();
+            let now = Utc::now();
+            let (mut index, changed) = Self::refresh_index_sync(&storage_dir, &codec)?;
+            if changed {
+                Self::persist_index_if_unlocked(&storage_dir, &mut index, &codec);
+            }
+            // Only packs the index lists under this name are decoded.
+            let mut matches = Vec::new();
+            for id in index.ids_named(name.as_str()) {
+                let Some(path) = locate_pack_file(&storage_dir, id) else {
+                    continue;
+                };
+                let Some(pack) = Self::read_pack_for_lookup(&path, &codec)? else {
+                    continue;
+                };
+                if pack.name.as_ref() == Some(&name)
+                    && Self::is_within_grace_window(now, pack.expires_at, expired_grace_seconds)
+                {
+                    matches.push(pack);
+                }
+            }
             Self::select_pack_by_name(&name, matches)
         })
         .await
@@ -712,70 +1442,87 @@ impl PackRepositoryPort for JsonStorageAdapter {
 
     async fn list_packs(&self, filter: ListFilter) -> Result<Vec<Pack>> {
         let storage_dir = self.storage_dir.clone();
-        let max_pack_bytes = self.max_pack_bytes;
+        let codec = self.codec.clone();
         let expired_grace_seconds = self.expired_grace_seconds;
+        let list_include_expired = self.list_include_expired;
         task::spawn_blocking(move || -> Result<Vec<Pack>> {
+            if filter.archived {
+                return Self::list_archived_sync(&storage_dir, &codec, &filter);
+            }
             let now = Utc::now();
-            let packs = Self::load_all_sync(&storage_dir, max_pack_bytes)?;
-            let status_filter = filter.status;
-            let freshness_filter = filter.freshness;
-            let query_lower = filter
-                .query
-                .as_ref()
-                .map(|query| query.trim().to_lowercase())
-                .filter(|query| !query.is_empty());
-            let filtered: Vec<Pack> = packs
-                .into_iter()
-                .filter(|pack| {
-                    let freshness_state = FreshnessState::from_pack(pack, now);
-                    let is_within_grace =
-                        Self::is_within_grace_window(now, pack.expires_at, expired_grace_seconds);
-                    if let Some(required_freshness) = freshness_filter {
-                        if required_freshness == FreshnessState::Expired {
-                            if freshness_state != FreshnessState::Expired || !is_within_grace {
-                                return false;
-                            }
-                        } else if freshness_state != required_freshness {
-                            return false;
-                        }
-                    } else if freshness_state == FreshnessState::Expired {
-                        // Stale-safe default: keep expired packs hidden unless explicitly asked.
-                        return false;
-                    }
-                    if let Some(s) = status_filter {
-                        if pack.status != s {
-                            return false;
-                        }
-                    }
-                    if let Some(ref q_lower) = query_lower {
-                        let haystack = format!(
-                            "{} {} {}",
-                            pack.title.as_deref().unwrap_or(""),
-                            pack.name.as_ref().map(|n| n.as_str()).unwrap_or(""),
-                            pack.brief.as_deref().unwrap_or("")
-                        )
-                        .to_lowercase();
-                        if !haystack.contains(q_lower.as_str()) {
-                            return false;
-                        }
-                    }
-                    true
+            let (mut index, changed) = Self::refresh_index_sync(&storage_dir, &codec)?;
+            if changed {
+                Self::persist_index_if_unlocked(&storage_dir, &mut index, &codec);
+            }
+            let mut matching: Vec<(&String, &PackIndexEntry)> = index
+                .entries
+                .iter()
+                .filter(|(_, entry)| {
+                    Self::index_entry_matches(
+                        entry,
+                        &filter,
+                        now,
+                        expired_grace_seconds,
+                        list_include_expired,
+                    )
                 })
                 .collect();
-
+            matching.sort_by(|(a_id, a), (b_id, b)| {
+                b.updated_at
+                    .cmp(&a.updated_at)
+                    .then_with(|| b.revision.cmp(&a.revision))
+                    .then_with(|| a_id.cmp(b_id))
+            });
+
+            // Only the requested page is decoded.
             let offset = filter.offset.unwrap_or(0);
-            Ok(filtered
+            let mut page = Vec::new();
+            for (id, _) in matching
                 .into_iter()
                 .skip(offset)
                 .take(filter.limit.unwrap_or(usize::MAX))
-                .collect())
+            {
+                let Some(path) = locate_pack_file(&storage_dir, id) else {
+                    continue;
+                };
+                if let Some(pack) = Self::read_pack_for_lookup(&path, &codec)? {
+                    page.push(pack);
+                }
+            }
+            Ok(page)
+        })
+        .await
+        .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+
+    async fn purge_expired(&self) -> Result<()> {
+        self.purge_expired_locked().await
+    }
+
+    async fn list_revisions(&self, id: &PackId) -> Result<Vec<u64>> {
+        let storage_dir = self.storage_dir.clone();
+        let id = id.clone();
+        task::spawn_blocking(move || Self::list_history_revisions_sync(&storage_dir, &id))
+            .await
+            .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+
+    async fn get_revision(&self, id: &PackId, revision: u64) -> Result<Option<Pack>> {
+        let storage_dir = self.storage_dir.clone();
+        let id = id.clone();
+        let codec = self.codec.clone();
+        task::spawn_blocking(move || -> Result<Option<Pack>> {
+            let Some(path) = Self::history_path(&storage_dir, &id, revision) else {
+                return Ok(None);
+            };
+            Self::read_pack_from_path(&path, &codec).map(Some)
         })
         .await
         .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
     }
 
-    async fn purge_expired(&self) -> Result<()> {
-        self.purge_expired_locked().await
+    fn max_pack_bytes(&self) -> Option<usize> {
+        Some(self.codec.max_pack_bytes)
     }
 }
 
@@ -881,6 +1628,7 @@ mod tests {
             err.to_string().contains("payload is too large"),
             "expected too-large payload message, got: {err}"
         );
+        assert!(err.to_string().contains("input split"), "{err}");
     }
 
     #[test]
@@ -896,9 +1644,18 @@ mod tests {
         let expired = make_pack_with_expiry_delta(-(DEFAULT_EXPIRED_GRACE_SECONDS + 1));
 
         // Write both packs
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &active, DEFAULT_MAX_PACK_BYTES).unwrap();
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &expired, DEFAULT_MAX_PACK_BYTES)
-            .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &active,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &expired,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
 
         // Both files should exist
         assert!(dir
@@ -913,8 +1670,9 @@ mod tests {
         // Run purge
         JsonStorageAdapter::purge_expired_sync(
             dir.path(),
-            DEFAULT_MAX_PACK_BYTES,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
             DEFAULT_EXPIRED_GRACE_SECONDS,
+            ExpiredRetention::Delete,
         )
         .unwrap();
 
@@ -939,14 +1697,24 @@ mod tests {
         let active = make_pack();
         let expired = make_pack_with_expiry_delta(-(DEFAULT_EXPIRED_GRACE_SECONDS - 1));
 
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &active, DEFAULT_MAX_PACK_BYTES).unwrap();
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &expired, DEFAULT_MAX_PACK_BYTES)
-            .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &active,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &expired,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
 
         JsonStorageAdapter::purge_expired_sync(
             dir.path(),
-            DEFAULT_MAX_PACK_BYTES,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
             DEFAULT_EXPIRED_GRACE_SECONDS,
+            ExpiredRetention::Delete,
         )
         .unwrap();
 
@@ -968,7 +1736,7 @@ mod tests {
         let max = 1024usize;
 
         let active = make_pack();
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &active, max).unwrap();
+        JsonStorageAdapter::write_pack_atomic(dir.path(), &active, &PackCodec::plain(max)).unwrap();
         let active_path = dir.path().join(format!("{}.json", active.id.as_str()));
 
         let corrupted_path = dir.path().join("pk_corrupt.json");
@@ -996,8 +1764,13 @@ mod tests {
             "oversized pack should exist before purge"
         );
 
-        JsonStorageAdapter::purge_expired_sync(dir.path(), max, DEFAULT_EXPIRED_GRACE_SECONDS)
-            .unwrap();
+        JsonStorageAdapter::purge_expired_sync(
+            dir.path(),
+            &PackCodec::plain(max),
+            DEFAULT_EXPIRED_GRACE_SECONDS,
+            ExpiredRetention::Delete,
+        )
+        .unwrap();
 
         assert!(active_path.exists(), "active pack should remain");
         assert!(
@@ -1010,28 +1783,36 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_load_all_active_excludes_expired_after_grace() {
+    #[tokio::test]
+    async fn test_get_by_name_excludes_expired_after_grace() {
         let dir = tempdir().unwrap();
         let active = make_pack();
         let expired = make_pack_with_expiry_delta(-(DEFAULT_EXPIRED_GRACE_SECONDS + 1));
 
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &active, DEFAULT_MAX_PACK_BYTES).unwrap();
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &expired, DEFAULT_MAX_PACK_BYTES)
-            .unwrap();
-
-        let loaded = JsonStorageAdapter::load_all_active_sync(
+        JsonStorageAdapter::write_pack_atomic(
             dir.path(),
-            DEFAULT_MAX_PACK_BYTES,
-            DEFAULT_EXPIRED_GRACE_SECONDS,
+            &active,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &expired,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
         )
         .unwrap();
 
-        assert_eq!(loaded.len(), 1, "only 1 active pack expected");
-        assert_eq!(
-            loaded[0].id, active.id,
-            "loaded pack should be the active one"
+        let adapter = JsonStorageAdapter::new_with_max_and_grace(
+            dir.path().to_path_buf(),
+            DEFAULT_MAX_PACK_BYTES,
+            DEFAULT_EXPIRED_GRACE_SECONDS,
         );
+        let loaded = adapter
+            .get_by_name(&PackName::new("test-pack").unwrap())
+            .await
+            .unwrap()
+            .expect("active pack should resolve");
+        assert_eq!(loaded.id, active.id, "loaded pack should be the active one");
     }
 
     #[tokio::test]
@@ -1054,19 +1835,28 @@ mod tests {
         let mut expired_after_grace = make_named_pack_with("freshness-d", Status::Draft, now, 1);
         expired_after_grace.expires_at = now - Duration::seconds(DEFAULT_EXPIRED_GRACE_SECONDS + 1);
 
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &fresh, DEFAULT_MAX_PACK_BYTES).unwrap();
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &expiring, DEFAULT_MAX_PACK_BYTES)
-            .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &fresh,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &expiring,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
         JsonStorageAdapter::write_pack_atomic(
             dir.path(),
             &expired_within_grace,
-            DEFAULT_MAX_PACK_BYTES,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
         )
         .unwrap();
         JsonStorageAdapter::write_pack_atomic(
             dir.path(),
             &expired_after_grace,
-            DEFAULT_MAX_PACK_BYTES,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
         )
         .unwrap();
 
@@ -1116,6 +1906,180 @@ mod tests {
         assert_eq!(expiring_only[0].id, expiring.id);
     }
 
+    #[tokio::test]
+    async fn test_archive_retention_moves_expired_packs_and_lists_them() {
+        let dir = tempdir().unwrap();
+        let adapter =
+            JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), DEFAULT_MAX_PACK_BYTES)
+                .with_expired_retention(ExpiredRetention::Archive {
+                    retention: Duration::days(1),
+                });
+        let now = Utc::now();
+        let live = make_named_pack_with("archive-live", Status::Draft, now, 1);
+        let mut expired = make_named_pack_with("archive-old", Status::Finalized, now, 2);
+        expired.expires_at = now - Duration::seconds(DEFAULT_EXPIRED_GRACE_SECONDS + 1);
+        let mut lapsed = make_named_pack_with("archive-lapsed", Status::Draft, now, 1);
+        lapsed.expires_at = now - Duration::days(2);
+        for pack in [&live, &expired, &lapsed] {
+            JsonStorageAdapter::write_pack_atomic(
+                dir.path(),
+                pack,
+                &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+            )
+            .unwrap();
+        }
+        JsonStorageAdapter::write_history_sync(
+            dir.path(),
+            &expired,
+            DEFAULT_HISTORY_LIMIT,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
+
+        adapter.purge_expired().await.unwrap();
+        let archive = dir.path().join("archive");
+        assert!(archive
+            .join(format!("{}.json", expired.id.as_str()))
+            .exists());
+        assert!(archive.join(expired.id.as_str()).join("history").exists());
+        assert!(!dir.path().join(expired.id.as_str()).exists());
+        assert!(adapter.get_by_id(&expired.id).await.unwrap().is_none());
+        // Past the grace window and the archive retention: gone for good.
+        assert!(!archive
+            .join(format!("{}.json", lapsed.id.as_str()))
+            .exists());
+
+        let archived = adapter
+            .list_packs(ListFilter {
+                archived: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let ids: Vec<&PackId> = archived.iter().map(|pack| &pack.id).collect();
+        assert_eq!(ids, vec![&expired.id]);
+        let drafts = adapter
+            .list_packs(ListFilter {
+                archived: true,
+                status: Some(Status::Draft),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(drafts.is_empty());
+        let live_list = adapter.list_packs(ListFilter::default()).await.unwrap();
+        assert_eq!(live_list.len(), 1);
+        assert_eq!(live_list[0].id, live.id);
+    }
+
+    #[tokio::test]
+    async fn test_list_packs_include_expired_overrides_store_default() {
+        let dir = tempdir().unwrap();
+        let mut adapter =
+            JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), DEFAULT_MAX_PACK_BYTES);
+        let now = Utc::now();
+        let live = make_named_pack_with("include-live", Status::Draft, now, 1);
+        let mut in_grace = make_named_pack_with("include-grace", Status::Draft, now, 1);
+        in_grace.expires_at = now - Duration::seconds(1);
+        let mut past_grace = make_named_pack_with("include-gone", Status::Draft, now, 1);
+        past_grace.expires_at = now - Duration::seconds(DEFAULT_EXPIRED_GRACE_SECONDS + 1);
+        for pack in [&live, &in_grace, &past_grace] {
+            JsonStorageAdapter::write_pack_atomic(
+                dir.path(),
+                pack,
+                &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+            )
+            .unwrap();
+        }
+        let listed = |packs: Vec<Pack>| {
+            let mut names: Vec<String> = packs
+                .iter()
+                .map(|pack| pack.name.as_ref().unwrap().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        let with = |include_expired| ListFilter {
+            include_expired,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            listed(adapter.list_packs(with(None)).await.unwrap()),
+            ["include-live"]
+        );
+        assert_eq!(
+            listed(adapter.list_packs(with(Some(true))).await.unwrap()),
+            ["include-grace", "include-live"]
+        );
+
+        adapter.list_include_expired = true;
+        assert_eq!(
+            listed(adapter.list_packs(with(None)).await.unwrap()),
+            ["include-grace", "include-live"]
+        );
+        assert_eq!(
+            listed(adapter.list_packs(with(Some(false))).await.unwrap()),
+            ["include-live"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_packs_uses_index_until_pack_file_changes() {
+        let dir = tempdir().unwrap();
+        let adapter =
+            JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), DEFAULT_MAX_PACK_BYTES);
+        let mut pack = make_named_pack_with("indexed", Status::Draft, Utc::now(), 1);
+        pack.title = Some("alpha".to_string());
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &pack,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
+        let by_query = |query: &str| ListFilter {
+            query: Some(query.to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            adapter.list_packs(by_query("alpha")).await.unwrap().len(),
+            1
+        );
+        let mut index = PackIndex::load(dir.path(), None);
+        assert!(index.entries.contains_key(pack.id.as_str()));
+
+        // A matching stamp means the entry is trusted without re-reading the file.
+        index.entries.get_mut(pack.id.as_str()).unwrap().title = Some("from-index".to_string());
+        index.save(dir.path(), None).unwrap();
+        let listed = adapter.list_packs(by_query("from-index")).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].title.as_deref(), Some("alpha"));
+
+        // External rewrites and deletions are picked up on the next list.
+        pack.title = Some("rewritten beta".to_string());
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &pack,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
+        assert!(adapter
+            .list_packs(by_query("from-index"))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(adapter.list_packs(by_query("beta")).await.unwrap().len(), 1);
+
+        std::fs::remove_file(dir.path().join(format!("{}.json", pack.id))).unwrap();
+        assert!(adapter
+            .list_packs(ListFilter::default())
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(PackIndex::load(dir.path(), None).entries.is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_by_name_prefers_latest_finalized_then_revision() {
         let dir = tempdir().unwrap();
@@ -1135,16 +2099,28 @@ mod tests {
         let same_updated_lower_revision =
             make_named_pack_with("shared-pack", Status::Finalized, now, 3);
 
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &older_finalized, DEFAULT_MAX_PACK_BYTES)
-            .unwrap();
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &selected, DEFAULT_MAX_PACK_BYTES)
-            .unwrap();
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &newer_draft, DEFAULT_MAX_PACK_BYTES)
-            .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &older_finalized,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &selected,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &newer_draft,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
         JsonStorageAdapter::write_pack_atomic(
             dir.path(),
             &same_updated_lower_revision,
-            DEFAULT_MAX_PACK_BYTES,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
         )
         .unwrap();
 
@@ -1170,10 +2146,18 @@ mod tests {
         let candidate_a = make_named_pack_with("ambiguous-pack", Status::Finalized, shared_time, 7);
         let candidate_b = make_named_pack_with("ambiguous-pack", Status::Finalized, shared_time, 7);
 
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &candidate_a, DEFAULT_MAX_PACK_BYTES)
-            .unwrap();
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &candidate_b, DEFAULT_MAX_PACK_BYTES)
-            .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &candidate_a,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &candidate_b,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
 
         let err = adapter
             .get_by_name(&PackName::new("ambiguous-pack").unwrap())
@@ -1221,7 +2205,12 @@ mod tests {
         let bad_id = PackId::new();
         let bad_path = dir.path().join(format!("{}.json", bad_id.as_str()));
 
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &valid, DEFAULT_MAX_PACK_BYTES).unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &valid,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
         std::fs::write(&bad_path, "not-json").unwrap();
 
         assert!(
@@ -1248,20 +2237,117 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_save_journals_previous_revisions_up_to_history_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), 1024 * 1024);
+        storage.history_limit = 2;
+        let mut pack = make_pack();
+        storage.create_new(&pack).await.unwrap();
+        for _ in 0..3 {
+            let expected = pack.revision;
+            pack.touch();
+            storage
+                .save_with_expected_revision(&pack, expected)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(storage.list_revisions(&pack.id).await.unwrap(), vec![2, 3]);
+        let journaled = storage.get_revision(&pack.id, 3).await.unwrap().unwrap();
+        assert_eq!(journaled.revision, 3);
+        assert!(storage.get_revision(&pack.id, 1).await.unwrap().is_none());
+
+        assert!(storage.delete_pack_file(&pack.id).await.unwrap());
+        assert!(storage.list_revisions(&pack.id).await.unwrap().is_empty());
+        assert!(!dir.path().join(pack.id.as_str()).exists());
+    }
+
+    #[tokio::test]
+    async fn test_create_with_update_stores_both_packs_or_neither() {
+        let dir = tempdir().unwrap();
+        let storage = JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), 4096);
+        let parent = make_pack();
+        storage.create_new(&parent).await.unwrap();
+        let child = Pack::new(PackId::new(), Some(PackName::new("child").unwrap()));
+        let tmp_files = || {
+            std::fs::read_dir(dir.path())
+                .unwrap()
+                .filter(|entry| entry.as_ref().unwrap().path().extension() == Some("tmp".as_ref()))
+                .count()
+        };
+
+        let mut oversized = parent.clone();
+        oversized.touch();
+        oversized.brief = Some("x".repeat(8192));
+        let err = storage
+            .create_with_update(&child, &oversized, 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DomainError::InvalidData(_)), "{err:?}");
+        let mut next = parent.clone();
+        next.touch();
+        let err = storage
+            .create_with_update(&child, &next, 7)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, DomainError::RevisionConflictDetailed { .. }),
+            "{err:?}"
+        );
+        assert!(storage.get_by_id(&child.id).await.unwrap().is_none());
+        assert_eq!(
+            storage
+                .get_by_id(&parent.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .revision,
+            1
+        );
+        assert_eq!(tmp_files(), 0);
+
+        storage.create_with_update(&child, &next, 1).await.unwrap();
+        assert_eq!(
+            storage
+                .get_by_id(&parent.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .revision,
+            2
+        );
+        assert_eq!(storage.list_revisions(&parent.id).await.unwrap(), vec![1]);
+        let stored = storage
+            .get_by_name(&PackName::new("child").unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.id, child.id);
+        assert_eq!(tmp_files(), 0);
+    }
+
     #[test]
     fn test_write_pack_atomic_persists_and_is_decodable() {
         let dir = tempdir().unwrap();
         let pack = make_pack();
 
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &pack, DEFAULT_MAX_PACK_BYTES).unwrap();
+        JsonStorageAdapter::write_pack_atomic(
+            dir.path(),
+            &pack,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
 
         let expected_path = dir.path().join(format!("{}.json", pack.id.as_str()));
         assert!(expected_path.exists(), "pack file should exist after write");
 
         // Should be decodable
-        let decoded =
-            JsonStorageAdapter::read_pack_from_path(&expected_path, DEFAULT_MAX_PACK_BYTES)
-                .unwrap();
+        let decoded = JsonStorageAdapter::read_pack_from_path(
+            &expected_path,
+            &PackCodec::plain(DEFAULT_MAX_PACK_BYTES),
+        )
+        .unwrap();
         assert_eq!(decoded.id, pack.id);
         assert_eq!(decoded.schema_version, pack.schema_version);
     }
@@ -1272,7 +2358,7 @@ mod tests {
         let max = 1024usize;
 
         let valid = make_pack();
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &valid, max).unwrap();
+        JsonStorageAdapter::write_pack_atomic(dir.path(), &valid, &PackCodec::plain(max)).unwrap();
         let valid_path = dir.path().join(format!("{}.json", valid.id.as_str()));
         assert!(valid_path.exists(), "valid pack file should exist");
 
@@ -1291,14 +2377,10 @@ mod tests {
         std::fs::write(&oversized_path, oversized_payload).unwrap();
         assert!(oversized_path.exists(), "oversized pack file should exist");
 
-        let loaded = JsonStorageAdapter::load_all_active_sync(
-            dir.path(),
-            max,
-            DEFAULT_EXPIRED_GRACE_SECONDS,
-        )
-        .unwrap();
-        assert_eq!(loaded.len(), 1, "expected only one valid pack");
-        assert_eq!(loaded[0].id, valid.id);
+        let (index, _) =
+            JsonStorageAdapter::refresh_index_sync(dir.path(), &PackCodec::plain(max)).unwrap();
+        assert_eq!(index.entries.len(), 1, "expected only one valid pack");
+        assert!(index.entries.contains_key(valid.id.as_str()));
 
         assert!(
             !corrupt_path.exists(),
@@ -1336,8 +2418,10 @@ mod tests {
         let in_grace_path = dir.path().join(format!("{}.json", in_grace.id.as_str()));
         let past_grace_path = dir.path().join(format!("{}.json", past_grace.id.as_str()));
 
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &in_grace, 1024).unwrap();
-        JsonStorageAdapter::write_pack_atomic(dir.path(), &past_grace, 1024).unwrap();
+        JsonStorageAdapter::write_pack_atomic(dir.path(), &in_grace, &PackCodec::plain(1024))
+            .unwrap();
+        JsonStorageAdapter::write_pack_atomic(dir.path(), &past_grace, &PackCodec::plain(1024))
+            .unwrap();
 
         assert!(adapter.get_by_id(&in_grace.id).await.unwrap().is_some());
         assert!(in_grace_path.exists());
@@ -1369,4 +2453,185 @@ mod tests {
             other => panic!("expected MigrationRequired, got: {:?}", other),
         }
     }
+
+    #[tokio::test]
+    async fn test_encrypted_store_round_trips_and_keeps_files_it_cannot_open() {
+        let dir = tempdir().unwrap();
+        let key = PackCipher::parse(&"5a".repeat(32)).unwrap();
+        let store = JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), 1024 * 1024)
+            .with_cipher(Some(key));
+        let mut pack = make_pack();
+        pack.brief = Some("proprietary excerpt".into());
+        store.create_new(&pack).await.unwrap();
+        pack.touch();
+        store.save_with_expected_revision(&pack, 1).await.unwrap();
+
+        let path = dir.path().join(format!("{}.json", pack.id.as_str()));
+        let history = JsonStorageAdapter::history_path(dir.path(), &pack.id, 1).unwrap();
+        for file in [&path, &history] {
+            let raw = std::fs::read(file).unwrap();
+            assert!(crate::adapters::pack_cipher::is_sealed(&raw));
+            assert!(!raw.windows(11).any(|w| w == b"proprietary"));
+        }
+        let read = store.get_by_id(&pack.id).await.unwrap().unwrap();
+        assert_eq!(read.brief.as_deref(), Some("proprietary excerpt"));
+        assert_eq!(
+            store
+                .get_revision(&pack.id, 1)
+                .await
+                .unwrap()
+                .unwrap()
+                .revision,
+            1
+        );
+
+        let wrong = JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), 1024 * 1024)
+            .with_cipher(Some(PackCipher::parse(&"a5".repeat(32)).unwrap()));
+        let err = wrong.get_by_id(&pack.id).await.unwrap_err();
+        assert!(matches!(err, DomainError::DecryptionFailed(_)), "{err:?}");
+        let keyless = JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), 1024 * 1024);
+        assert!(matches!(
+            keyless.list_packs(ListFilter::default()).await,
+            Err(DomainError::DecryptionFailed(_))
+        ));
+        keyless.purge_expired().await.unwrap();
+        assert!(
+            path.exists(),
+            "a pack the key can't open must not be removed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compressed_store_reads_mixed_formats_and_rewrites_in_place() {
+        let dir = tempdir().unwrap();
+        let plain = JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), 1024 * 1024);
+        let mut pack = make_pack();
+        pack.brief = Some("written before compression was enabled".into());
+        plain.create_new(&pack).await.unwrap();
+        let legacy = dir.path().join(format!("{}.json", pack.id.as_str()));
+        assert!(legacy.exists());
+
+        let zstd = JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), 1024 * 1024)
+            .with_compression(Compression::Zstd);
+        let other = Pack::new(PackId::new(), None);
+        zstd.create_new(&other).await.unwrap();
+        assert!(dir
+            .path()
+            .join(format!("{}.json.zst", other.id.as_str()))
+            .exists());
+        assert_eq!(
+            zstd.list_packs(ListFilter::default()).await.unwrap().len(),
+            2
+        );
+
+        pack.touch();
+        zstd.save_with_expected_revision(&pack, 1).await.unwrap();
+        assert!(!legacy.exists(), "the rewrite must replace the .json file");
+        assert!(dir
+            .path()
+            .join(format!("{}.json.zst", pack.id.as_str()))
+            .exists());
+        let history = JsonStorageAdapter::history_path(dir.path(), &pack.id, 1).unwrap();
+        assert!(history.to_string_lossy().ends_with("1.json.zst"));
+        assert_eq!(
+            plain
+                .get_revision(&pack.id, 1)
+                .await
+                .unwrap()
+                .unwrap()
+                .revision,
+            1
+        );
+        let read = plain.get_by_id(&pack.id).await.unwrap().unwrap();
+        assert_eq!(read.revision, 2);
+
+        assert!(plain.delete_pack_file(&pack.id).await.unwrap());
+        assert!(JsonStorageAdapter::pack_path(dir.path(), &pack.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_writes_keep_the_name_index_current() {
+        let dir = tempdir().unwrap();
+        let store = JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), 1024 * 1024);
+        let mut pack = make_pack();
+        store.create_new(&pack).await.unwrap();
+        pack.touch();
+        pack.status = Status::Finalized;
+        store.save_with_expected_revision(&pack, 1).await.unwrap();
+
+        let index = PackIndex::load(dir.path(), None);
+        let entry = &index.entries[pack.id.as_str()];
+        assert_eq!(entry.revision, 2);
+        assert_eq!(entry.status, Status::Finalized);
+        let path = JsonStorageAdapter::pack_path(dir.path(), &pack.id).unwrap();
+        assert_eq!(Some(entry.stamp), FileStamp::of(&path));
+        assert_eq!(
+            index.ids_named("test-pack").collect::<Vec<_>>(),
+            vec![pack.id.as_str()]
+        );
+
+        let duplicate = make_pack();
+        assert!(matches!(
+            store.create_new(&duplicate).await,
+            Err(DomainError::Conflict(_))
+        ));
+        assert!(store.delete_pack_file(&pack.id).await.unwrap());
+        assert!(PackIndex::load(dir.path(), None).entries.is_empty());
+        assert!(store
+            .get_by_name(&PackName::new("test-pack").unwrap())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sharded_store_moves_flat_packs_and_their_history() {
+        let dir = tempdir().unwrap();
+        let flat = JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), 1024 * 1024);
+        let mut pack = make_pack();
+        flat.create_new(&pack).await.unwrap();
+        pack.touch();
+        flat.save_with_expected_revision(&pack, 1).await.unwrap();
+        let id = pack.id.as_str();
+        assert!(dir.path().join(format!("{id}.json")).exists());
+
+        let sharded = JsonStorageAdapter::new_with_max(dir.path().to_path_buf(), 1024 * 1024)
+            .with_layout(PackLayout::Sharded);
+        assert_eq!(
+            sharded.get_by_id(&pack.id).await.unwrap().unwrap().revision,
+            2
+        );
+        sharded.purge_expired().await.unwrap();
+
+        let shard = dir
+            .path()
+            .join(crate::adapters::pack_layout::shard_of(id).unwrap());
+        assert!(!dir.path().join(format!("{id}.json")).exists());
+        assert!(shard.join(format!("{id}.json")).exists());
+        assert!(shard.join(id).join("history").join("1.json").exists());
+        assert_eq!(sharded.list_revisions(&pack.id).await.unwrap(), vec![1]);
+        assert_eq!(
+            sharded
+                .list_packs(ListFilter::default())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        let other = Pack::new(PackId::new(), None);
+        sharded.create_new(&other).await.unwrap();
+        assert!(JsonStorageAdapter::pack_path(dir.path(), &other.id)
+            .unwrap()
+            .parent()
+            .is_some_and(|parent| parent != dir.path()));
+
+        // Readers on the old layout still find everything.
+        assert_eq!(
+            flat.list_packs(ListFilter::default()).await.unwrap().len(),
+            2
+        );
+        assert!(flat.delete_pack_file(&pack.id).await.unwrap());
+        assert!(!shard.join(format!("{id}.json")).exists());
+        assert!(!shard.join(id).exists());
+    }
 }
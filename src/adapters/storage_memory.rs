@@ -0,0 +1,408 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{
+    adapters::{
+        pack_index_fs::{FileStamp, PackIndexEntry},
+        storage_json::{
+            conflict_changed_section_keys, parse_expired_grace_seconds_from_env,
+            parse_history_limit_from_env, parse_list_include_expired_from_env,
+            parse_max_pack_bytes_from_env, JsonStorageAdapter,
+        },
+    },
+    app::ports::{ListFilter, PackRepositoryPort},
+    domain::{
+        errors::{revision_conflict_guidance, DomainError, Result},
+        models::Pack,
+        types::{PackId, PackName},
+    },
+};
+
+#[derive(Default)]
+struct MemoryState {
+    packs: HashMap<PackId, Pack>,
+    /// Prior revisions per pack, keyed by revision.
+    history: HashMap<PackId, BTreeMap<u64, Pack>>,
+}
+
+/// Pack store held in process memory; everything is gone when the process
+/// exits. Follows [`JsonStorageAdapter`] semantics — name and id conflicts,
+/// revision checks, the encoded size limit, expiry grace, the history journal
+/// and list filtering read the same env settings — except that packs past the
+/// grace window are always deleted, never archived. For throwaway sessions
+/// (`CONTEXT_PACK_STORAGE=memory`) and for embedding the use cases in tests.
+pub struct InMemoryStorageAdapter {
+    state: Mutex<MemoryState>,
+    max_pack_bytes: usize,
+    expired_grace_seconds: i64,
+    history_limit: usize,
+    list_include_expired: bool,
+}
+
+impl Default for InMemoryStorageAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InMemoryStorageAdapter {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(MemoryState::default()),
+            max_pack_bytes: parse_max_pack_bytes_from_env(),
+            expired_grace_seconds: parse_expired_grace_seconds_from_env(),
+            history_limit: parse_history_limit_from_env(),
+            list_include_expired: parse_list_include_expired_from_env(),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, MemoryState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn is_live(&self, pack: &Pack, now: DateTime<Utc>) -> bool {
+        JsonStorageAdapter::is_within_grace_window(now, pack.expires_at, self.expired_grace_seconds)
+    }
+
+    fn purge_expired_in(&self, state: &mut MemoryState) {
+        let now = Utc::now();
+        let expired: Vec<PackId> = state
+            .packs
+            .values()
+            .filter(|pack| !self.is_live(pack, now))
+            .map(|pack| pack.id.clone())
+            .collect();
+        for id in expired {
+            state.packs.remove(&id);
+            state.history.remove(&id);
+        }
+    }
+
+    fn check_size(&self, pack: &Pack) -> Result<()> {
+        JsonStorageAdapter::encoded_pack_payload(pack, self.max_pack_bytes).map(|_| ())
+    }
+
+    fn check_new_in(state: &MemoryState, pack: &Pack) -> Result<()> {
+        if state.packs.contains_key(&pack.id) {
+            return Err(DomainError::PackIdConflict(pack.id.to_string()));
+        }
+        if let Some(new_name) = &pack.name {
+            if state
+                .packs
+                .values()
+                .any(|existing| existing.name.as_ref() == Some(new_name))
+            {
+                return Err(DomainError::Conflict(format!(
+                    "pack with name '{}' already exists",
+                    new_name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The stored revision `pack` replaces, if it is still `expected_revision`.
+    fn current_for_update_in(
+        state: &MemoryState,
+        pack: &Pack,
+        expected_revision: u64,
+    ) -> Result<Pack> {
+        let current = state
+            .packs
+            .get(&pack.id)
+            .cloned()
+            .ok_or_else(|| DomainError::NotFound(format!("pack '{}' not found", pack.id)))?;
+        if current.revision != expected_revision {
+            return Err(DomainError::RevisionConflictDetailed {
+                expected_revision,
+                current_revision: current.revision,
+                last_updated_at: current.updated_at.to_rfc3339(),
+                changed_section_keys: conflict_changed_section_keys(&current, pack),
+                guidance: revision_conflict_guidance(
+                    current.revision,
+                    current.current_write_reason(),
+                ),
+                produced_by: current.current_write_reason().map(str::to_string),
+            });
+        }
+        Ok(current)
+    }
+
+    fn replace_in(&self, state: &mut MemoryState, pack: &Pack, current: Pack) {
+        if self.history_limit > 0 {
+            let history = state.history.entry(pack.id.clone()).or_default();
+            history.insert(current.revision, current);
+            while history.len() > self.history_limit {
+                history.pop_first();
+            }
+        }
+        state.packs.insert(pack.id.clone(), pack.clone());
+    }
+}
+
+#[async_trait]
+impl PackRepositoryPort for InMemoryStorageAdapter {
+    async fn create_new(&self, pack: &Pack) -> Result<()> {
+        self.check_size(pack)?;
+        let mut state = self.state();
+        self.purge_expired_in(&mut state);
+        Self::check_new_in(&state, pack)?;
+        state.packs.insert(pack.id.clone(), pack.clone());
+        Ok(())
+    }
+
+    async fn save_with_expected_revision(&self, pack: &Pack, expected_revision: u64) -> Result<()> {
+        self.check_size(pack)?;
+        let mut state = self.state();
+        self.purge_expired_in(&mut state);
+        let current = Self::current_for_update_in(&state, pack, expected_revision)?;
+        self.replace_in(&mut state, pack, current);
+        Ok(())
+    }
+
+    async fn create_with_update(
+        &self,
+        created: &Pack,
+        updated: &Pack,
+        expected_revision: u64,
+    ) -> Result<()> {
+        self.check_size(created)?;
+        self.check_size(updated)?;
+        let mut state = self.state();
+        self.purge_expired_in(&mut state);
+        Self::check_new_in(&state, created)?;
+        let current = Self::current_for_update_in(&state, updated, expected_revision)?;
+        state.packs.insert(created.id.clone(), created.clone());
+        self.replace_in(&mut state, updated, current);
+        Ok(())
+    }
+
+    async fn delete_pack_file(&self, id: &PackId) -> Result<bool> {
+        let mut state = self.state();
+        state.history.remove(id);
+        Ok(state.packs.remove(id).is_some())
+    }
+
+    async fn extend_expiry(&self, id: &PackId, expires_at: DateTime<Utc>) -> Result<()> {
+        let mut state = self.state();
+        let pack = state
+            .packs
+            .get_mut(id)
+            .ok_or_else(|| DomainError::NotFound(format!("pack '{}' not found", id)))?;
+        if pack.expires_at < expires_at {
+            pack.expires_at = expires_at;
+        }
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: &PackId) -> Result<Option<Pack>> {
+        let mut state = self.state();
+        match state.packs.get(id) {
+            Some(pack) if self.is_live(pack, Utc::now()) => Ok(Some(pack.clone())),
+            Some(_) => {
+                state.packs.remove(id);
+                state.history.remove(id);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn get_by_name(&self, name: &PackName) -> Result<Option<Pack>> {
+        let now = Utc::now();
+        let matches = self
+            .state()
+            .packs
+            .values()
+            .filter(|pack| pack.name.as_ref() == Some(name) && self.is_live(pack, now))
+            .cloned()
+            .collect::<Vec<_>>();
+        JsonStorageAdapter::select_pack_by_name(name, matches)
+    }
+
+    async fn list_packs(&self, filter: ListFilter) -> Result<Vec<Pack>> {
+        // Expired packs are deleted, never archived.
+        if filter.archived {
+            return Ok(Vec::new());
+        }
+        let now = Utc::now();
+        // The stamp only matters to the on-disk index; filtering ignores it.
+        let stamp = FileStamp {
+            len: 0,
+            mtime_ns: 0,
+        };
+        let mut matching: Vec<Pack> = self
+            .state()
+            .packs
+            .values()
+            .filter(|pack| {
+                JsonStorageAdapter::index_entry_matches(
+                    &PackIndexEntry::from_pack(pack, stamp),
+                    &filter,
+                    now,
+                    self.expired_grace_seconds,
+                    self.list_include_expired,
+                )
+            })
+            .cloned()
+            .collect();
+        matching.sort_by(|a, b| {
+            b.updated_at
+                .cmp(&a.updated_at)
+                .then_with(|| b.revision.cmp(&a.revision))
+                .then_with(|| a.id.as_str().cmp(b.id.as_str()))
+        });
+        Ok(matching
+            .into_iter()
+            .skip(filter.offset.unwrap_or(0))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    async fn purge_expired(&self) -> Result<()> {
+        let mut state = self.state();
+        self.purge_expired_in(&mut state);
+        Ok(())
+    }
+
+    async fn list_revisions(&self, id: &PackId) -> Result<Vec<u64>> {
+        Ok(self
+            .state()
+            .history
+            .get(id)
+            .map(|history| history.keys().copied().collect())
+            .unwrap_or_default())
+    }
+
+    async fn get_revision(&self, id: &PackId, revision: u64) -> Result<Option<Pack>> {
+        Ok(self
+            .state()
+            .history
+            .get(id)
+            .and_then(|history| history.get(&revision))
+            .cloned())
+    }
+
+    fn max_pack_bytes(&self) -> Option<usize> {
+        Some(self.max_pack_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn named_pack(name: &str) -> Pack {
+        Pack::new(PackId::new(), Some(PackName::new(name).unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_create_save_and_history_follow_json_store_rules() {
+        let store = InMemoryStorageAdapter::new();
+        let pack = named_pack("alpha");
+        store.create_new(&pack).await.unwrap();
+        let duplicate = store.create_new(&named_pack("alpha")).await.unwrap_err();
+        assert!(matches!(duplicate, DomainError::Conflict(_)));
+
+        let mut next = pack.clone();
+        next.revision = 2;
+        next.title = Some("second".into());
+        store.save_with_expected_revision(&next, 1).await.unwrap();
+        let stale = store
+            .save_with_expected_revision(&next, 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            stale,
+            DomainError::RevisionConflictDetailed {
+                current_revision: 2,
+                ..
+            }
+        ));
+
+        assert_eq!(store.list_revisions(&pack.id).await.unwrap(), vec![1]);
+        let journaled = store.get_revision(&pack.id, 1).await.unwrap().unwrap();
+        assert_eq!(journaled.title, None);
+        let by_name = store
+            .get_by_name(&PackName::new("alpha").unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_name.revision, 2);
+
+        assert!(store.delete_pack_file(&pack.id).await.unwrap());
+        assert!(store.get_by_id(&pack.id).await.unwrap().is_none());
+        assert!(store.list_revisions(&pack.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_with_update_checks_both_packs_before_storing_either() {
+        let store = InMemoryStorageAdapter::new();
+        let parent = named_pack("parent");
+        store.create_new(&parent).await.unwrap();
+        let child = named_pack("child");
+        let mut next = parent.clone();
+        next.revision = 2;
+
+        let stale = store
+            .create_with_update(&child, &next, 2)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            stale,
+            DomainError::RevisionConflictDetailed { .. }
+        ));
+        assert!(store.get_by_id(&child.id).await.unwrap().is_none());
+        let taken = store
+            .create_with_update(&named_pack("parent"), &next, 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(taken, DomainError::Conflict(_)));
+        assert_eq!(
+            store.get_by_id(&parent.id).await.unwrap().unwrap().revision,
+            1
+        );
+
+        store.create_with_update(&child, &next, 1).await.unwrap();
+        assert!(store.get_by_id(&child.id).await.unwrap().is_some());
+        assert_eq!(store.list_revisions(&parent.id).await.unwrap(), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_list_hides_expired_pages_results_and_purge_drops_them() {
+        let store = InMemoryStorageAdapter::new();
+        let mut older = named_pack("older");
+        older.updated_at = Utc::now() - Duration::minutes(5);
+        let newer = named_pack("newer");
+        let mut expired = named_pack("expired");
+        expired.expires_at = Utc::now() - Duration::days(1);
+        for pack in [&older, &newer, &expired] {
+            store.create_new(pack).await.unwrap();
+        }
+
+        let listed = store.list_packs(ListFilter::default()).await.unwrap();
+        let names: Vec<_> = listed
+            .iter()
+            .map(|pack| pack.name.as_ref().unwrap().to_string())
+            .collect();
+        assert_eq!(names, vec!["newer", "older"]);
+        let second_page = store
+            .list_packs(ListFilter {
+                offset: Some(1),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(second_page[0].id, older.id);
+
+        store.purge_expired().await.unwrap();
+        assert!(store.get_by_id(&expired.id).await.unwrap().is_none());
+        assert_eq!(store.state().packs.len(), 2);
+    }
+}
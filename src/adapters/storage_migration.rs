@@ -0,0 +1,282 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    adapters::{
+        pack_codec::{pack_file_stem, PackCodec},
+        pack_layout::shard_of,
+        storage_json::JsonStorageAdapter,
+    },
+    domain::{
+        errors::{DomainError, Result},
+        models::Pack,
+    },
+};
+
+/// Probe files the health and read-only checks write and remove at once.
+const PROBE_FILES: [&str; 2] = [".health-probe", ".read_only_probe"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    pub pack_count: usize,
+    /// Other files copied alongside packs: history, archive, index, audit
+    /// log, journals, backups, quarantines.
+    pub artifact_count: usize,
+    pub bytes: u64,
+}
+
+fn io_err(context: &str, path: &Path, e: impl std::fmt::Display) -> DomainError {
+    DomainError::Io(format!("{} '{}': {}", context, path.display(), e))
+}
+
+fn is_empty_dir(path: &Path) -> Result<bool> {
+    match std::fs::read_dir(path) {
+        Ok(mut entries) => Ok(entries.next().is_none()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(true),
+        Err(e) => Err(io_err("failed to inspect", path, e)),
+    }
+}
+
+/// Files under `dir` (recursively), relative to `dir`, sorted.
+fn files_under(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    let mut stack = vec![PathBuf::new()];
+    while let Some(rel) = stack.pop() {
+        let abs = dir.join(&rel);
+        for entry in std::fs::read_dir(&abs).map_err(|e| io_err("failed to read", &abs, e))? {
+            let entry = entry.map_err(|e| io_err("failed to read", &abs, e))?;
+            let child = rel.join(entry.file_name());
+            let file_type = entry
+                .file_type()
+                .map_err(|e| io_err("failed to stat", &entry.path(), e))?;
+            if file_type.is_dir() {
+                stack.push(child);
+            } else if file_type.is_file() {
+                out.push(child);
+            }
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// `packs/<id>.json[.gz|.zst]`, or `packs/<shard>/<id>…` in the sharded layout.
+pub(crate) fn is_pack_file(rel: &Path) -> bool {
+    let Some(stem) = pack_file_stem(rel) else {
+        return false;
+    };
+    let packs = Path::new("packs");
+    rel.parent() == Some(packs)
+        || shard_of(stem).is_some_and(|shard| rel.parent() == Some(&packs.join(shard)))
+}
+
+/// Lock, temp and probe files only mean something to the process that
+/// wrote them.
+fn is_transient(rel: &Path) -> bool {
+    let Some(name) = rel.file_name().and_then(|name| name.to_str()) else {
+        return true;
+    };
+    name.ends_with(".lock") || name.ends_with(".tmp") || PROBE_FILES.contains(&name)
+}
+
+/// Every file of a storage root worth keeping, relative to the root and
+/// sorted: packs with their history and archive, the index, journals, audit
+/// log, saved filters, sync state and quarantines, exports and backups.
+pub(crate) fn storage_root_files(root: &Path) -> Result<Vec<PathBuf>> {
+    Ok(files_under(root)?
+        .into_iter()
+        .filter(|rel| !is_transient(rel))
+        .collect())
+}
+
+fn copy_verified(
+    from_root: &Path,
+    staging: &Path,
+    plan: &[PathBuf],
+    codec: &PackCodec,
+) -> Result<MigrationReport> {
+    let mut pack_count = 0;
+    let mut bytes = 0u64;
+    for rel in plan {
+        let src = from_root.join(rel);
+        let dst = staging.join(rel);
+        let raw = std::fs::read(&src).map_err(|e| io_err("failed to read", &src, e))?;
+        if is_pack_file(rel) {
+            let plain = codec.open(raw.clone(), &src)?;
+            let pack: Pack = serde_json::from_str(&plain).map_err(|e| {
+                DomainError::InvalidData(format!(
+                    "refusing to migrate unreadable pack '{}': {}",
+                    src.display(),
+                    e
+                ))
+            })?;
+            if pack_file_stem(rel) != Some(pack.id.as_str()) {
+                return Err(DomainError::InvalidData(format!(
+                    "pack file '{}' holds pack id '{}'",
+                    src.display(),
+                    pack.id
+                )));
+            }
+            pack_count += 1;
+        }
+        if let Some(parent) = dst.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| io_err("failed to create", parent, e))?;
+        }
+        std::fs::write(&dst, &raw).map_err(|e| io_err("failed to write", &dst, e))?;
+        let copied = std::fs::read(&dst).map_err(|e| io_err("failed to re-read", &dst, e))?;
+        if copied != raw {
+            return Err(DomainError::Io(format!(
+                "verification failed: '{}' differs from '{}'",
+                dst.display(),
+                src.display()
+            )));
+        }
+        bytes += raw.len() as u64;
+    }
+    Ok(MigrationReport {
+        from: String::new(),
+        to: String::new(),
+        pack_count,
+        artifact_count: plan.len() - pack_count,
+        bytes,
+    })
+}
+
+/// Copies a JSON storage root to a new location.
+///
+/// The copy is staged in a sibling `<to>.migrating` directory while the source
+/// repository lock is held, every file is read back and compared, packs are
+/// decoded (encrypted ones with `CONTEXT_PACK_ENCRYPTION_KEY`, and copied
+/// still encrypted), and only then is the staging directory renamed into place. Any
+/// failure removes the staging directory and leaves `to` untouched. `to` must
+/// not exist or be empty; the source is never modified.
+pub fn migrate_storage_root(from: &Path, to: &Path) -> Result<MigrationReport> {
+    if !from.is_dir() {
+        return Err(DomainError::NotFound(format!(
+            "source storage root '{}' does not exist",
+            from.display()
+        )));
+    }
+    if !is_empty_dir(to)? {
+        return Err(DomainError::Conflict(format!(
+            "target storage root '{}' is not empty",
+            to.display()
+        )));
+    }
+    let codec = PackCodec::from_env()?;
+    let staging = PathBuf::from(format!("{}.migrating", to.display()));
+    if staging.exists() {
+        return Err(DomainError::Conflict(format!(
+            "staging dir '{}' exists; an earlier migration was interrupted — remove it and retry",
+            staging.display()
+        )));
+    }
+
+    let report = JsonStorageAdapter::with_repo_lock(&from.join("packs"), || {
+        let plan = storage_root_files(from)?;
+        let result = copy_verified(from, &staging, &plan, &codec).and_then(|report| {
+            std::fs::create_dir_all(staging.join("packs"))
+                .map_err(|e| io_err("failed to create", &staging, e))?;
+            if to.exists() {
+                std::fs::remove_dir(to).map_err(|e| io_err("failed to replace", to, e))?;
+            }
+            std::fs::rename(&staging, to)
+                .map_err(|e| io_err("failed to move staging dir to", to, e))?;
+            Ok(report)
+        });
+        if result.is_err() {
+            let _ = std::fs::remove_dir_all(&staging);
+        }
+        result
+    })?;
+
+    Ok(MigrationReport {
+        from: from.display().to_string(),
+        to: to.display().to_string(),
+        ..report
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::ports::PackRepositoryPort;
+    use crate::domain::types::PackId;
+
+    #[tokio::test]
+    async fn test_migration_copies_packs_and_artifacts_and_leaves_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("old");
+        let repo = JsonStorageAdapter::new(from.join("packs"));
+        let mut pack = Pack::new(PackId::new(), None);
+        pack.expires_at = chrono::Utc::now() + chrono::Duration::minutes(30);
+        repo.create_new(&pack).await.unwrap();
+        std::fs::write(from.join("replay_journal.json"), "{\"entries\":[]}").unwrap();
+        std::fs::create_dir_all(from.join("backups")).unwrap();
+        std::fs::write(from.join("backups/a.tar"), b"tar").unwrap();
+
+        let to = dir.path().join("new");
+        let report = migrate_storage_root(&from, &to).unwrap();
+        assert_eq!(report.pack_count, 1);
+        // The replay journal, the backup and the pack index.
+        assert_eq!(report.artifact_count, 3);
+        assert!(!dir.path().join("new.migrating").exists());
+
+        let migrated = JsonStorageAdapter::new(to.join("packs"));
+        assert!(migrated.get_by_id(&pack.id).await.unwrap().is_some());
+        assert!(to.join("backups/a.tar").is_file());
+        assert!(repo.get_by_id(&pack.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_migration_keeps_history_archive_and_audit_but_not_locks() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("old");
+        let repo = JsonStorageAdapter::new(from.join("packs"));
+        let mut pack = Pack::new(PackId::new(), None);
+        pack.expires_at = chrono::Utc::now() + chrono::Duration::minutes(30);
+        repo.create_new(&pack).await.unwrap();
+        let mut next = pack.clone();
+        next.revision = 2;
+        repo.save_with_expected_revision(&next, 1).await.unwrap();
+        std::fs::create_dir_all(from.join("packs/archive")).unwrap();
+        std::fs::write(from.join("packs/archive/pk_gone1234.json"), "{}").unwrap();
+        std::fs::write(from.join("audit.jsonl"), "{\"action\":\"write\"}\n").unwrap();
+        std::fs::write(from.join("packs/stray.tmp"), "x").unwrap();
+
+        let to = dir.path().join("new");
+        migrate_storage_root(&from, &to).unwrap();
+
+        let migrated = JsonStorageAdapter::new(to.join("packs"));
+        assert_eq!(migrated.list_revisions(&pack.id).await.unwrap(), vec![1]);
+        assert!(to.join("packs/archive/pk_gone1234.json").is_file());
+        assert!(to.join("packs/.pack_index").is_file());
+        assert_eq!(
+            std::fs::read_to_string(to.join("audit.jsonl")).unwrap(),
+            "{\"action\":\"write\"}\n"
+        );
+        assert!(!to.join("packs/.repo.lock").exists());
+        assert!(!to.join("packs/stray.tmp").exists());
+    }
+
+    #[test]
+    fn test_migration_fails_closed_on_corrupt_pack_and_non_empty_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("old");
+        std::fs::create_dir_all(from.join("packs")).unwrap();
+        std::fs::write(from.join("packs/pk_broken1.json"), "{not json").unwrap();
+
+        let to = dir.path().join("new");
+        let err = migrate_storage_root(&from, &to).unwrap_err();
+        assert!(matches!(err, DomainError::InvalidData(_)), "{err:?}");
+        assert!(!to.exists());
+        assert!(!dir.path().join("new.migrating").exists());
+
+        std::fs::create_dir_all(&to).unwrap();
+        std::fs::write(to.join("keep.txt"), "x").unwrap();
+        let err = migrate_storage_root(&from, &to).unwrap_err();
+        assert!(matches!(err, DomainError::Conflict(_)), "{err:?}");
+    }
+}
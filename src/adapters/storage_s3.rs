@@ -0,0 +1,670 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use object_store::{
+    aws::AmazonS3Builder, path::Path as ObjectPath, ObjectStore, PutMode, PutOptions, PutPayload,
+    UpdateVersion,
+};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use crate::{
+    adapters::{
+        pack_index_fs::{FileStamp, PackIndexEntry},
+        storage_json::{
+            conflict_changed_section_keys, parse_expired_grace_seconds_from_env,
+            parse_history_limit_from_env, parse_list_include_expired_from_env,
+            parse_max_pack_bytes_from_env, JsonStorageAdapter,
+        },
+    },
+    app::ports::{ListFilter, PackRepositoryPort},
+    domain::{
+        errors::{revision_conflict_guidance, DomainError, Result},
+        models::Pack,
+        types::{PackId, PackName},
+    },
+};
+
+pub const S3_BUCKET_ENV: &str = "CONTEXT_PACK_S3_BUCKET";
+pub const S3_PREFIX_ENV: &str = "CONTEXT_PACK_S3_PREFIX";
+const DEFAULT_PREFIX: &str = "context_pack";
+/// Attempts for an expiry refresh that keeps losing the conditional put.
+const EXTEND_EXPIRY_ATTEMPTS: usize = 3;
+/// Attempts to take over a stale name marker that keeps changing.
+const NAME_CLAIM_ATTEMPTS: usize = 3;
+/// Written under `names/` once every named pack has a marker. Marker names
+/// are lowercase hex, so it can't collide with one.
+const NAME_INDEX_READY: &str = "_v1";
+
+/// Pack store in an S3-compatible bucket, laid out like the JSON store:
+/// `{prefix}/packs/<id>.json` plus `{prefix}/packs/<id>/history/<rev>.json`.
+/// Creates are conditional puts that fail if the object exists, and writes
+/// are conditional on the ETag read with the expected revision, so writers
+/// on different hosts can't overwrite each other.
+///
+/// Names resolve through `{prefix}/names/<hex of name>` markers holding the
+/// id of the pack that claimed the name, so a lookup is two GETs and a
+/// create claims its name with a conditional put, atomically across hosts.
+/// A marker whose pack is gone or expired is taken over by the next create
+/// with that name. Buckets written before the markers are indexed once, on
+/// the first name lookup or create. Expired packs are deleted, never
+/// archived, by [`PackRepositoryPort::purge_expired`] (the TTL purge loop)
+/// or when read; writes don't scan the bucket.
+pub struct S3StorageAdapter {
+    store: Arc<dyn ObjectStore>,
+    packs: ObjectPath,
+    names: ObjectPath,
+    names_ready: OnceCell<()>,
+    max_pack_bytes: usize,
+    expired_grace_seconds: i64,
+    history_limit: usize,
+    list_include_expired: bool,
+}
+
+/// A pack as read, with the version a conditional write must match.
+struct Versioned {
+    pack: Pack,
+    version: UpdateVersion,
+}
+
+impl S3StorageAdapter {
+    /// Uses `store` under `prefix`; limits, grace and history come from the
+    /// same env settings as the JSON store.
+    pub fn new(store: Arc<dyn ObjectStore>, prefix: &str) -> Self {
+        Self {
+            store,
+            packs: ObjectPath::from(prefix).child("packs"),
+            names: ObjectPath::from(prefix).child("names"),
+            names_ready: OnceCell::new(),
+            max_pack_bytes: parse_max_pack_bytes_from_env(),
+            expired_grace_seconds: parse_expired_grace_seconds_from_env(),
+            history_limit: parse_history_limit_from_env(),
+            list_include_expired: parse_list_include_expired_from_env(),
+        }
+    }
+
+    /// Bucket from `CONTEXT_PACK_S3_BUCKET`, key prefix from
+    /// `CONTEXT_PACK_S3_PREFIX` (default `context_pack`); endpoint, region
+    /// and credentials from the standard `AWS_*` variables (`AWS_ENDPOINT`
+    /// for S3-compatible services).
+    pub fn from_env() -> Result<Self> {
+        let bucket = std::env::var(S3_BUCKET_ENV)
+            .ok()
+            .map(|raw| raw.trim().to_string())
+            .filter(|raw| !raw.is_empty())
+            .ok_or_else(|| {
+                DomainError::InvalidData(format!("{S3_BUCKET_ENV} is required for s3 storage"))
+            })?;
+        let prefix = std::env::var(S3_PREFIX_ENV)
+            .ok()
+            .map(|raw| raw.trim().trim_matches('/').to_string())
+            .filter(|raw| !raw.is_empty())
+            .unwrap_or_else(|| DEFAULT_PREFIX.to_string());
+        let store = AmazonS3Builder::from_env()
+            .with_bucket_name(bucket)
+            .build()
+            .map_err(|e| DomainError::InvalidData(format!("invalid s3 configuration: {e}")))?;
+        Ok(Self::new(Arc::new(store), &prefix))
+    }
+
+    fn pack_path(&self, id: &PackId) -> ObjectPath {
+        self.packs.child(format!("{}.json", id.as_str()))
+    }
+
+    fn history_dir(&self, id: &PackId) -> ObjectPath {
+        self.packs.child(id.as_str()).child("history")
+    }
+
+    fn history_path(&self, id: &PackId, revision: u64) -> ObjectPath {
+        self.history_dir(id).child(format!("{revision}.json"))
+    }
+
+    /// Hex, so any name is one path segment.
+    fn name_marker(&self, name: &PackName) -> ObjectPath {
+        let hex: String = name
+            .as_str()
+            .bytes()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        self.names.child(hex)
+    }
+
+    fn is_live(&self, pack: &Pack, now: DateTime<Utc>) -> bool {
+        JsonStorageAdapter::is_within_grace_window(now, pack.expires_at, self.expired_grace_seconds)
+    }
+
+    async fn read(&self, location: &ObjectPath) -> Result<Option<Versioned>> {
+        let result = match self.store.get(location).await {
+            Ok(result) => result,
+            Err(object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(e) => return Err(io_error("read", location, e)),
+        };
+        let version = UpdateVersion {
+            e_tag: result.meta.e_tag.clone(),
+            version: result.meta.version.clone(),
+        };
+        let bytes = result
+            .bytes()
+            .await
+            .map_err(|e| io_error("read", location, e))?;
+        if bytes.len() > self.max_pack_bytes {
+            return Err(JsonStorageAdapter::payload_too_large_error(
+                location.as_ref(),
+                bytes.len(),
+                self.max_pack_bytes,
+            ));
+        }
+        let content = std::str::from_utf8(&bytes).map_err(|e| {
+            DomainError::Deserialize(format!("failed to decode pack '{location}': {e}"))
+        })?;
+        let pack = JsonStorageAdapter::decode_at(location.as_ref(), content)?;
+        Ok(Some(Versioned { pack, version }))
+    }
+
+    /// Writes `pack` under `mode`; `Ok(false)` when an update's version no
+    /// longer matches because another writer got there first.
+    async fn put(&self, location: &ObjectPath, pack: &Pack, mode: PutMode) -> Result<bool> {
+        let payload = JsonStorageAdapter::encoded_pack_payload(pack, self.max_pack_bytes)?;
+        let options = PutOptions {
+            mode,
+            ..Default::default()
+        };
+        match self
+            .store
+            .put_opts(location, PutPayload::from(payload), options)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(object_store::Error::Precondition { .. }) => Ok(false),
+            Err(object_store::Error::AlreadyExists { .. }) => {
+                Err(DomainError::PackIdConflict(pack.id.to_string()))
+            }
+            Err(e) => Err(io_error("write", location, e)),
+        }
+    }
+
+    async fn delete_object(&self, location: &ObjectPath) -> Result<bool> {
+        match self.store.delete(location).await {
+            Ok(()) => Ok(true),
+            Err(object_store::Error::NotFound { .. }) => Ok(false),
+            Err(e) => Err(io_error("delete", location, e)),
+        }
+    }
+
+    /// The id a name marker points at, with the version a takeover must match.
+    async fn read_marker(&self, marker: &ObjectPath) -> Result<Option<(PackId, UpdateVersion)>> {
+        let result = match self.store.get(marker).await {
+            Ok(result) => result,
+            Err(object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(e) => return Err(io_error("read", marker, e)),
+        };
+        let version = UpdateVersion {
+            e_tag: result.meta.e_tag.clone(),
+            version: result.meta.version.clone(),
+        };
+        let bytes = result
+            .bytes()
+            .await
+            .map_err(|e| io_error("read", marker, e))?;
+        let id = std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|raw| PackId::parse(raw.trim()).ok())
+            .ok_or_else(|| {
+                DomainError::Deserialize(format!("name marker '{marker}' does not hold a pack id"))
+            })?;
+        Ok(Some((id, version)))
+    }
+
+    async fn put_marker(&self, marker: &ObjectPath, id: &PackId, mode: PutMode) -> Result<bool> {
+        let options = PutOptions {
+            mode,
+            ..Default::default()
+        };
+        match self
+            .store
+            .put_opts(marker, PutPayload::from(id.as_str().to_string()), options)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(
+                object_store::Error::AlreadyExists { .. }
+                | object_store::Error::Precondition { .. },
+            ) => Ok(false),
+            Err(e) => Err(io_error("write", marker, e)),
+        }
+    }
+
+    /// The live pack named `name` that `marker` points at, if any.
+    async fn marked_pack(&self, name: &PackName, id: &PackId) -> Result<Option<Pack>> {
+        Ok(self
+            .read(&self.pack_path(id))
+            .await?
+            .map(|versioned| versioned.pack)
+            .filter(|pack| pack.name.as_ref() == Some(name) && self.is_live(pack, Utc::now())))
+    }
+
+    /// Writes markers for named packs stored before the name index existed.
+    /// Runs once per adapter; a bucket already indexed costs one GET.
+    async fn ensure_name_index(&self) -> Result<()> {
+        self.names_ready
+            .get_or_try_init(|| async {
+                let ready = self.names.child(NAME_INDEX_READY);
+                match self.store.head(&ready).await {
+                    Ok(_) => return Ok(()),
+                    Err(object_store::Error::NotFound { .. }) => {}
+                    Err(e) => return Err(io_error("read", &ready, e)),
+                }
+                let now = Utc::now();
+                for pack in self.load_all().await? {
+                    if let Some(name) = pack.name.as_ref().filter(|_| self.is_live(&pack, now)) {
+                        self.put_marker(&self.name_marker(name), &pack.id, PutMode::Create)
+                            .await?;
+                    }
+                }
+                self.store
+                    .put(&ready, PutPayload::from_static(b""))
+                    .await
+                    .map_err(|e| io_error("write", &ready, e))?;
+                Ok(())
+            })
+            .await
+            .map(|_| ())
+    }
+
+    /// Points `name` at `id`, unless a live pack already holds it.
+    async fn claim_name(&self, name: &PackName, id: &PackId) -> Result<()> {
+        self.ensure_name_index().await?;
+        let marker = self.name_marker(name);
+        for _ in 0..NAME_CLAIM_ATTEMPTS {
+            if self.put_marker(&marker, id, PutMode::Create).await? {
+                return Ok(());
+            }
+            // Deleted since the create failed: try creating again.
+            let Some((holder, version)) = self.read_marker(&marker).await? else {
+                continue;
+            };
+            if self.marked_pack(name, &holder).await?.is_some() {
+                return Err(DomainError::Conflict(format!(
+                    "pack with name '{}' already exists",
+                    name
+                )));
+            }
+            if self
+                .put_marker(&marker, id, PutMode::Update(version))
+                .await?
+            {
+                return Ok(());
+            }
+        }
+        Err(DomainError::Conflict(format!(
+            "failed to claim pack name '{}': it kept changing",
+            name
+        )))
+    }
+
+    /// Every pack in the bucket; unreadable objects are logged and skipped.
+    async fn load_all(&self) -> Result<Vec<Pack>> {
+        let listing = self
+            .store
+            .list_with_delimiter(Some(&self.packs))
+            .await
+            .map_err(|e| io_error("list", &self.packs, e))?;
+        let mut packs = Vec::with_capacity(listing.objects.len());
+        for object in listing.objects {
+            if object.location.extension() != Some("json") {
+                continue;
+            }
+            match self.read(&object.location).await {
+                Ok(Some(versioned)) => packs.push(versioned.pack),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("skipping unreadable pack '{}': {}", object.location, e),
+            }
+        }
+        Ok(packs)
+    }
+
+    async fn history_revisions(&self, id: &PackId) -> Result<Vec<u64>> {
+        let dir = self.history_dir(id);
+        let listing = self
+            .store
+            .list_with_delimiter(Some(&dir))
+            .await
+            .map_err(|e| io_error("list", &dir, e))?;
+        let mut revisions: Vec<u64> = listing
+            .objects
+            .iter()
+            .filter_map(|object| object.location.filename())
+            .filter_map(|name| name.strip_suffix(".json"))
+            .filter_map(|stem| stem.parse().ok())
+            .collect();
+        revisions.sort_unstable();
+        Ok(revisions)
+    }
+
+    /// Journals `previous` before it is overwritten, then drops the oldest
+    /// entries beyond the history limit.
+    async fn write_history(&self, previous: &Pack) -> Result<()> {
+        if self.history_limit == 0 {
+            return Ok(());
+        }
+        let location = self.history_path(&previous.id, previous.revision);
+        let payload = serde_json::to_string(previous)?;
+        self.store
+            .put(&location, PutPayload::from(payload))
+            .await
+            .map_err(|e| io_error("write", &location, e))?;
+        let revisions = self.history_revisions(&previous.id).await?;
+        let excess = revisions.len().saturating_sub(self.history_limit);
+        for revision in &revisions[..excess] {
+            let stale = self.history_path(&previous.id, *revision);
+            if let Err(e) = self.delete_object(&stale).await {
+                tracing::warn!("failed to prune history entry '{}': {}", stale, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, id: &PackId) -> Result<bool> {
+        for revision in self.history_revisions(id).await? {
+            let entry = self.history_path(id, revision);
+            if let Err(e) = self.delete_object(&entry).await {
+                tracing::warn!("failed to remove history entry '{}': {}", entry, e);
+            }
+        }
+        self.delete_object(&self.pack_path(id)).await
+    }
+
+    fn revision_conflict(current: &Pack, attempted: &Pack, expected_revision: u64) -> DomainError {
+        DomainError::RevisionConflictDetailed {
+            expected_revision,
+            current_revision: current.revision,
+            last_updated_at: current.updated_at.to_rfc3339(),
+            changed_section_keys: conflict_changed_section_keys(current, attempted),
+            guidance: revision_conflict_guidance(current.revision, current.current_write_reason()),
+            produced_by: current.current_write_reason().map(str::to_string),
+        }
+    }
+}
+
+fn io_error(op: &str, location: &ObjectPath, e: object_store::Error) -> DomainError {
+    DomainError::Io(format!("failed to {op} s3 object '{location}': {e}"))
+}
+
+#[async_trait]
+impl PackRepositoryPort for S3StorageAdapter {
+    async fn create_new(&self, pack: &Pack) -> Result<()> {
+        // A claim left by a create that then fails points at a missing pack,
+        // which the next claim takes over.
+        if let Some(name) = &pack.name {
+            self.claim_name(name, &pack.id).await?;
+        }
+        self.put(&self.pack_path(&pack.id), pack, PutMode::Create)
+            .await
+            .map(|_| ())
+    }
+
+    async fn save_with_expected_revision(&self, pack: &Pack, expected_revision: u64) -> Result<()> {
+        let location = self.pack_path(&pack.id);
+        let current = self
+            .read(&location)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("pack '{}' not found", pack.id)))?;
+        if current.pack.revision != expected_revision {
+            return Err(Self::revision_conflict(
+                &current.pack,
+                pack,
+                expected_revision,
+            ));
+        }
+        // Validate the size before journaling a revision that won't be replaced.
+        JsonStorageAdapter::encoded_pack_payload(pack, self.max_pack_bytes)?;
+        self.write_history(&current.pack).await?;
+        if self
+            .put(&location, pack, PutMode::Update(current.version))
+            .await?
+        {
+            return Ok(());
+        }
+        // Another writer got in between the read and the put.
+        match self.read(&location).await? {
+            Some(latest) => Err(Self::revision_conflict(
+                &latest.pack,
+                pack,
+                expected_revision,
+            )),
+            None => Err(DomainError::NotFound(format!(
+                "pack '{}' not found",
+                pack.id
+            ))),
+        }
+    }
+
+    async fn delete_pack_file(&self, id: &PackId) -> Result<bool> {
+        self.remove(id).await
+    }
+
+    async fn extend_expiry(&self, id: &PackId, expires_at: DateTime<Utc>) -> Result<()> {
+        let location = self.pack_path(id);
+        for _ in 0..EXTEND_EXPIRY_ATTEMPTS {
+            let Some(current) = self.read(&location).await? else {
+                return Err(DomainError::NotFound(format!("pack '{}' not found", id)));
+            };
+            if current.pack.expires_at >= expires_at {
+                return Ok(());
+            }
+            let mut pack = current.pack;
+            pack.expires_at = expires_at;
+            if self
+                .put(&location, &pack, PutMode::Update(current.version))
+                .await?
+            {
+                return Ok(());
+            }
+        }
+        Err(DomainError::Io(format!(
+            "failed to extend expiry of pack '{}': it kept changing",
+            id
+        )))
+    }
+
+    async fn get_by_id(&self, id: &PackId) -> Result<Option<Pack>> {
+        let Some(current) = self.read(&self.pack_path(id)).await? else {
+            return Ok(None);
+        };
+        if !self.is_live(&current.pack, Utc::now()) {
+            self.remove(id).await?;
+            return Ok(None);
+        }
+        Ok(Some(current.pack))
+    }
+
+    async fn get_by_name(&self, name: &PackName) -> Result<Option<Pack>> {
+        self.ensure_name_index().await?;
+        match self.read_marker(&self.name_marker(name)).await? {
+            Some((id, _)) => self.marked_pack(name, &id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn list_packs(&self, filter: ListFilter) -> Result<Vec<Pack>> {
+        // Expired packs are deleted, never archived.
+        if filter.archived {
+            return Ok(Vec::new());
+        }
+        let now = Utc::now();
+        // The stamp only matters to the on-disk index; filtering ignores it.
+        let stamp = FileStamp {
+            len: 0,
+            mtime_ns: 0,
+        };
+        let mut matching: Vec<Pack> = self
+            .load_all()
+            .await?
+            .into_iter()
+            .filter(|pack| {
+                JsonStorageAdapter::index_entry_matches(
+                    &PackIndexEntry::from_pack(pack, stamp),
+                    &filter,
+                    now,
+                    self.expired_grace_seconds,
+                    self.list_include_expired,
+                )
+            })
+            .collect();
+        matching.sort_by(|a, b| {
+            b.updated_at
+                .cmp(&a.updated_at)
+                .then_with(|| b.revision.cmp(&a.revision))
+                .then_with(|| a.id.as_str().cmp(b.id.as_str()))
+        });
+        Ok(matching
+            .into_iter()
+            .skip(filter.offset.unwrap_or(0))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    async fn purge_expired(&self) -> Result<()> {
+        let now = Utc::now();
+        for pack in self.load_all().await? {
+            if !self.is_live(&pack, now) {
+                self.remove(&pack.id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_revisions(&self, id: &PackId) -> Result<Vec<u64>> {
+        self.history_revisions(id).await
+    }
+
+    async fn get_revision(&self, id: &PackId, revision: u64) -> Result<Option<Pack>> {
+        Ok(self
+            .read(&self.history_path(id, revision))
+            .await?
+            .map(|versioned| versioned.pack))
+    }
+
+    fn max_pack_bytes(&self) -> Option<usize> {
+        Some(self.max_pack_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+
+    fn adapter() -> S3StorageAdapter {
+        S3StorageAdapter::new(Arc::new(InMemory::new()), "team/packs-store")
+    }
+
+    #[tokio::test]
+    async fn test_conditional_puts_guard_create_and_write() {
+        let store = adapter();
+        let pack = Pack::new(PackId::new(), Some(PackName::new("shared").unwrap()));
+        store.create_new(&pack).await.unwrap();
+        let same_name = store
+            .create_new(&Pack::new(PackId::new(), pack.name.clone()))
+            .await
+            .unwrap_err();
+        assert!(matches!(same_name, DomainError::Conflict(_)));
+        let same_id = Pack::new(pack.id.clone(), None);
+        let again = store.create_new(&same_id).await.unwrap_err();
+        assert!(matches!(again, DomainError::PackIdConflict(_)));
+
+        let mut next = pack.clone();
+        next.revision = 2;
+        store.save_with_expected_revision(&next, 1).await.unwrap();
+        let stale = store
+            .save_with_expected_revision(&next, 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            stale,
+            DomainError::RevisionConflictDetailed {
+                current_revision: 2,
+                ..
+            }
+        ));
+        assert_eq!(store.list_revisions(&pack.id).await.unwrap(), vec![1]);
+        let listed = store.list_packs(ListFilter::default()).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].revision, 2);
+
+        assert!(store.delete_pack_file(&pack.id).await.unwrap());
+        assert!(store.get_by_id(&pack.id).await.unwrap().is_none());
+        assert!(store.list_revisions(&pack.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write_loses_to_a_concurrent_writer_between_read_and_put() {
+        let store = adapter();
+        let pack = Pack::new(PackId::new(), None);
+        store.create_new(&pack).await.unwrap();
+        let location = store.pack_path(&pack.id);
+        let stale_version = store.read(&location).await.unwrap().unwrap().version;
+
+        let mut winner = pack.clone();
+        winner.revision = 2;
+        store.save_with_expected_revision(&winner, 1).await.unwrap();
+
+        let mut loser = pack.clone();
+        loser.revision = 2;
+        loser.title = Some("lost update".into());
+        let written = store
+            .put(&location, &loser, PutMode::Update(stale_version))
+            .await
+            .unwrap();
+        assert!(!written);
+        let stored = store.get_by_id(&pack.id).await.unwrap().unwrap();
+        assert_eq!(stored.title, None);
+    }
+
+    #[tokio::test]
+    async fn test_names_resolve_through_markers_and_expired_holders_give_them_up() {
+        let store = Arc::new(InMemory::new());
+        let name = PackName::new("team notes/q3").unwrap();
+        // Written before the name index existed: no marker.
+        let legacy = {
+            let adapter = S3StorageAdapter::new(store.clone(), "p");
+            let mut pack = Pack::new(PackId::new(), Some(name.clone()));
+            pack.expires_at = Utc::now() + chrono::Duration::minutes(30);
+            adapter
+                .put(&adapter.pack_path(&pack.id), &pack, PutMode::Create)
+                .await
+                .unwrap();
+            pack
+        };
+
+        let adapter = S3StorageAdapter::new(store.clone(), "p");
+        let found = adapter.get_by_name(&name).await.unwrap().unwrap();
+        assert_eq!(found.id, legacy.id);
+        let taken = adapter
+            .create_new(&Pack::new(PackId::new(), Some(name.clone())))
+            .await
+            .unwrap_err();
+        assert!(matches!(taken, DomainError::Conflict(_)));
+
+        // Once the holder has expired, a create takes the name over without
+        // purging the bucket first.
+        let mut expired = legacy.clone();
+        expired.revision = 2;
+        expired.expires_at = Utc::now() - chrono::Duration::days(30);
+        adapter
+            .save_with_expected_revision(&expired, 1)
+            .await
+            .unwrap();
+        assert!(adapter.get_by_name(&name).await.unwrap().is_none());
+        let successor = Pack::new(PackId::new(), Some(name.clone()));
+        adapter.create_new(&successor).await.unwrap();
+        assert!(adapter
+            .read(&adapter.pack_path(&legacy.id))
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            adapter.get_by_name(&name).await.unwrap().unwrap().id,
+            successor.id
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{
+    adapters::read_only_storage::is_read_only_error,
+    domain::{
+        errors::{DomainError, Result},
+        types::CURRENT_SCHEMA_VERSION,
+    },
+};
+
+pub const STORE_META_FILE: &str = "store_meta.json";
+
+/// Stamp written to `{storage_root}/store_meta.json` on first run, recording
+/// which build created the store and the pack schema it holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreMeta {
+    pub crate_version: String,
+    pub schema_version: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl StoreMeta {
+    fn current(now: DateTime<Utc>) -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            created_at: now,
+        }
+    }
+
+    /// Fails with `MigrationRequired` when the store holds a different pack
+    /// schema than this build reads.
+    pub fn check_compatible(&self) -> Result<()> {
+        if self.schema_version == CURRENT_SCHEMA_VERSION {
+            return Ok(());
+        }
+        let hint = if self.schema_version > CURRENT_SCHEMA_VERSION {
+            "upgrade mcp-context-pack"
+        } else {
+            "migrate the store or point CONTEXT_PACK_ROOT at a fresh directory"
+        };
+        Err(DomainError::MigrationRequired(format!(
+            "storage root was written by mcp-context-pack {} with schema version {}; this build ({}) supports schema version {}; {}",
+            self.crate_version,
+            self.schema_version,
+            env!("CARGO_PKG_VERSION"),
+            CURRENT_SCHEMA_VERSION,
+            hint
+        )))
+    }
+}
+
+/// Reads the store stamp, writing one for the current build when the root has
+/// none yet, and verifies it is compatible. Run once at startup so a store
+/// from another schema fails up front instead of pack by pack.
+pub fn ensure_store_meta(storage_root: &Path) -> Result<StoreMeta> {
+    let path = storage_root.join(STORE_META_FILE);
+    match std::fs::read(&path) {
+        Ok(raw) => {
+            let meta: StoreMeta = serde_json::from_slice(&raw)
+                .map_err(|e| DomainError::Deserialize(format!("'{}': {}", path.display(), e)))?;
+            meta.check_compatible()?;
+            Ok(meta)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let meta = StoreMeta::current(Utc::now());
+            // A read-only store cannot be stamped; it is served as current
+            // and the repository rejects writes to it.
+            if let Err(e) = std::fs::create_dir_all(storage_root) {
+                if is_read_only_error(&e) {
+                    tracing::warn!("not stamping read-only storage root: {}", e);
+                    return Ok(meta);
+                }
+                return Err(DomainError::Io(format!(
+                    "failed to create storage root '{}': {}",
+                    storage_root.display(),
+                    e
+                )));
+            }
+            let tmp = path.with_extension("tmp");
+            if let Err(e) = std::fs::write(&tmp, serde_json::to_vec_pretty(&meta)?) {
+                if is_read_only_error(&e) {
+                    tracing::warn!("not stamping read-only storage root: {}", e);
+                    return Ok(meta);
+                }
+                return Err(DomainError::Io(format!(
+                    "failed to write '{}': {}",
+                    tmp.display(),
+                    e
+                )));
+            }
+            std::fs::rename(&tmp, &path).map_err(|e| {
+                DomainError::Io(format!("failed to rename '{}': {}", path.display(), e))
+            })?;
+            tracing::info!("stamped new storage root: {}", path.display());
+            Ok(meta)
+        }
+        Err(e) => Err(DomainError::Io(format!(
+            "failed to read '{}': {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_run_stamps_store_and_later_runs_keep_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+
+        let first = ensure_store_meta(&root).unwrap();
+        assert_eq!(first.schema_version, CURRENT_SCHEMA_VERSION);
+        assert!(root.join(STORE_META_FILE).is_file());
+
+        let second = ensure_store_meta(&root).unwrap();
+        assert_eq!(second, first);
+    }
+
+    #[test]
+    fn test_other_schema_versions_require_migration() {
+        let dir = tempfile::tempdir().unwrap();
+        for schema_version in [CURRENT_SCHEMA_VERSION - 1, CURRENT_SCHEMA_VERSION + 1] {
+            let meta = StoreMeta {
+                schema_version,
+                ..StoreMeta::current(Utc::now())
+            };
+            std::fs::write(
+                dir.path().join(STORE_META_FILE),
+                serde_json::to_vec(&meta).unwrap(),
+            )
+            .unwrap();
+            let err = ensure_store_meta(dir.path()).unwrap_err();
+            assert!(matches!(err, DomainError::MigrationRequired(_)), "{err:?}");
+        }
+
+        std::fs::write(dir.path().join(STORE_META_FILE), "{oops").unwrap();
+        let err = ensure_store_meta(dir.path()).unwrap_err();
+        assert!(matches!(err, DomainError::Deserialize(_)), "{err:?}");
+    }
+}
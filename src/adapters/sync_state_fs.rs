@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use tokio::task;
+
+use crate::{
+    adapters::pack_cipher::{open_pack_bytes, seal_pack_bytes, PackCipher},
+    app::ports::{SyncBase, SyncConflict, SyncStatePort},
+    domain::{
+        errors::{DomainError, Result},
+        models::Pack,
+    },
+};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncStateFile {
+    remote_root: String,
+    base: SyncBase,
+}
+
+/// Sync bookkeeping under the local storage root:
+/// `{root}/sync_state.json` holds the agreed revisions for one remote, and
+/// `{root}/sync_conflicts/` holds quarantined conflicts. The base resets when
+/// the configured remote changes. Quarantines hold whole packs, so with a
+/// cipher both files are encrypted like the pack files.
+pub struct SyncStateFsAdapter {
+    storage_root: PathBuf,
+    remote_root: String,
+    cipher: Option<PackCipher>,
+}
+
+impl SyncStateFsAdapter {
+    pub fn new(storage_root: PathBuf, remote_root: &Path) -> Self {
+        Self {
+            storage_root,
+            remote_root: remote_root.display().to_string(),
+            cipher: None,
+        }
+    }
+
+    pub fn with_cipher(mut self, cipher: Option<PackCipher>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    fn state_path(storage_root: &Path) -> PathBuf {
+        storage_root.join("sync_state.json")
+    }
+
+    fn conflicts_dir(storage_root: &Path) -> PathBuf {
+        storage_root.join("sync_conflicts")
+    }
+
+    fn with_lock<T>(storage_root: &Path, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        std::fs::create_dir_all(storage_root)
+            .map_err(|e| DomainError::Io(format!("failed to create storage root: {}", e)))?;
+        let lock_path = Self::state_path(storage_root).with_extension("lock");
+        let lock = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+            .map_err(|e| {
+                DomainError::Io(format!(
+                    "failed to open sync state lock '{}': {}",
+                    lock_path.display(),
+                    e
+                ))
+            })?;
+        lock.lock_exclusive()
+            .map_err(|e| DomainError::Io(format!("failed to lock sync state: {}", e)))?;
+        let result = f();
+        if let Err(e) = lock.unlock() {
+            tracing::warn!("failed to unlock sync state lock: {e}");
+        }
+        result
+    }
+
+    fn write_atomic(path: &Path, content: Vec<u8>) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, content)
+            .map_err(|e| DomainError::Io(format!("failed to write '{}': {}", tmp.display(), e)))?;
+        std::fs::rename(&tmp, path)
+            .map_err(|e| DomainError::Io(format!("failed to rename '{}': {}", path.display(), e)))
+    }
+}
+
+#[async_trait]
+impl SyncStatePort for SyncStateFsAdapter {
+    async fn load_base(&self) -> Result<SyncBase> {
+        let path = Self::state_path(&self.storage_root);
+        let remote_root = self.remote_root.clone();
+        let cipher = self.cipher.clone();
+        task::spawn_blocking(move || -> Result<SyncBase> {
+            let raw = match std::fs::read(&path) {
+                Ok(raw) => raw,
+                Err(_) => return Ok(SyncBase::new()),
+            };
+            // A state file the key can't open fails the sync rather than
+            // resetting the base.
+            let raw = open_pack_bytes(cipher.as_ref(), raw, &path.display().to_string())?;
+            let state: SyncStateFile = serde_json::from_slice(&raw).unwrap_or_else(|err| {
+                tracing::warn!(
+                    "discarding unreadable sync state '{}': {}",
+                    path.display(),
+                    err
+                );
+                SyncStateFile::default()
+            });
+            Ok(if state.remote_root == remote_root {
+                state.base
+            } else {
+                SyncBase::new()
+            })
+        })
+        .await
+        .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+
+    async fn save_base(&self, base: &SyncBase) -> Result<()> {
+        let storage_root = self.storage_root.clone();
+        let state = SyncStateFile {
+            remote_root: self.remote_root.clone(),
+            base: base.clone(),
+        };
+        let content = seal_pack_bytes(self.cipher.as_ref(), serde_json::to_vec_pretty(&state)?)?;
+        task::spawn_blocking(move || -> Result<()> {
+            Self::with_lock(&storage_root, || {
+                Self::write_atomic(&Self::state_path(&storage_root), content)
+            })
+        })
+        .await
+        .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+
+    /// One file per (pack, local revision, remote revision), so an unresolved
+    /// conflict seen on every periodic pass is written once, not piled up.
+    async fn quarantine(&self, conflict: &SyncConflict) -> Result<String> {
+        let dir = Self::conflicts_dir(&self.storage_root);
+        let revision = |side: &Option<Pack>| {
+            side.as_ref()
+                .map_or_else(|| "none".to_string(), |pack| pack.revision.to_string())
+        };
+        let path = dir.join(format!(
+            "{}-local{}-remote{}.json",
+            conflict.pack_id,
+            revision(&conflict.local),
+            revision(&conflict.remote)
+        ));
+        let content = seal_pack_bytes(self.cipher.as_ref(), serde_json::to_vec_pretty(conflict)?)?;
+        task::spawn_blocking(move || -> Result<String> {
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| DomainError::Io(format!("failed to create conflicts dir: {}", e)))?;
+            Self::write_atomic(&path, content)?;
+            Ok(path.display().to_string())
+        })
+        .await
+        .map_err(|e| DomainError::Io(format!("task execution failed: {}", e)))?
+    }
+}
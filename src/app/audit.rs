@@ -0,0 +1,102 @@
+//! Audit trail of stored changes. [`AuditHook`] rides the lifecycle hooks, so
+//! every writer is covered; the input action and caller identity come from
+//! the [`AuditCaller`] scope the call runs in.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::{
+    app::ports::{AuditEntry, AuditEvent, AuditLogPort, PackLifecycleHook},
+    domain::{errors::Result, models::Pack, types::PackId},
+};
+
+tokio::task_local! {
+    static CALLER: AuditCaller;
+}
+
+/// Who is changing packs in the current task.
+#[derive(Debug, Clone)]
+pub struct AuditCaller {
+    pub op: String,
+    pub actor: Option<String>,
+}
+
+impl AuditCaller {
+    pub fn new(op: &str, actor: Option<&str>) -> Self {
+        Self {
+            op: op.to_string(),
+            actor: actor.map(str::to_string),
+        }
+    }
+
+    /// Runs `future` with this caller attached to the changes it stores.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CALLER.scope(self, future).await
+    }
+
+    fn current() -> Self {
+        CALLER
+            .try_with(Clone::clone)
+            .unwrap_or_else(|_| Self::new("unknown", None))
+    }
+}
+
+/// Lifecycle hook appending an [`AuditEntry`] per create, write and delete.
+pub struct AuditHook {
+    log: Arc<dyn AuditLogPort>,
+}
+
+impl AuditHook {
+    pub fn new(log: Arc<dyn AuditLogPort>) -> Self {
+        Self { log }
+    }
+
+    async fn record(
+        &self,
+        event: AuditEvent,
+        pack_id: &PackId,
+        revision_before: Option<u64>,
+        revision_after: Option<u64>,
+    ) -> Result<()> {
+        let caller = AuditCaller::current();
+        self.log
+            .append(&AuditEntry {
+                at: Utc::now(),
+                op: caller.op,
+                event,
+                pack_id: pack_id.as_str().to_string(),
+                revision_before,
+                revision_after,
+                actor: caller.actor,
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl PackLifecycleHook for AuditHook {
+    fn name(&self) -> &str {
+        "audit"
+    }
+
+    async fn on_create(&self, pack: &Pack) -> Result<()> {
+        self.record(AuditEvent::Create, &pack.id, None, Some(pack.revision))
+            .await
+    }
+
+    async fn on_write(&self, previous: Option<&Pack>, pack: &Pack) -> Result<()> {
+        self.record(
+            AuditEvent::Write,
+            &pack.id,
+            previous.map(|p| p.revision),
+            Some(pack.revision),
+        )
+        .await
+    }
+
+    async fn on_delete(&self, id: &PackId) -> Result<()> {
+        self.record(AuditEvent::Delete, id, None, None).await
+    }
+}
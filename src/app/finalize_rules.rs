@@ -0,0 +1,194 @@
+//! Registered finalize rules ([`ValidatorPort`]) and the built-in ones that
+//! `CONTEXT_PACK_FINALIZE_RULES` can turn on.
+
+use async_trait::async_trait;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{
+    app::ports::ValidatorPort,
+    domain::{
+        errors::{DomainError, FinalizeRuleFailure, Result},
+        models::Pack,
+        types::SectionKey,
+    },
+};
+
+/// Ordered set of registered rules; they run in registration order.
+#[derive(Clone, Default)]
+pub struct FinalizeRules {
+    rules: Vec<Arc<dyn ValidatorPort>>,
+}
+
+impl FinalizeRules {
+    /// Parses built-in rules from `min_refs.findings=2, required_tag=security`:
+    /// `min_refs.<section>=<n>` needs at least `n` refs in that section (when
+    /// present), `required_tag=<tag>` needs the tag on the pack.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut rules = Self::default();
+        for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .ok_or_else(|| {
+                    DomainError::InvalidData(format!(
+                        "finalize rules: expected <rule>=<value>, got '{part}'"
+                    ))
+                })?;
+            if let Some(section) = key.strip_prefix("min_refs.") {
+                let min = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| {
+                        DomainError::InvalidData(format!(
+                            "finalize rules: '{part}' needs a positive ref count"
+                        ))
+                    })?;
+                rules.register(Arc::new(MinRefsRule::new(SectionKey::new(section)?, min)));
+            } else if key == "required_tag" && !value.is_empty() {
+                rules.register(Arc::new(RequiredTagRule::new(value)));
+            } else {
+                return Err(DomainError::InvalidData(format!(
+                    "finalize rules: unknown rule '{part}'; use min_refs.<section>=<n> or required_tag=<tag>"
+                )));
+            }
+        }
+        Ok(rules)
+    }
+
+    pub fn register(&mut self, rule: Arc<dyn ValidatorPort>) {
+        self.rules.push(rule);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    /// Every problem every rule finds with `pack`.
+    pub async fn failures(&self, pack: &Pack) -> Result<Vec<FinalizeRuleFailure>> {
+        let mut failures = Vec::new();
+        for rule in &self.rules {
+            failures.extend(rule.validate(pack).await?.into_iter().map(|message| {
+                FinalizeRuleFailure {
+                    rule: rule.name().to_string(),
+                    message,
+                }
+            }));
+        }
+        Ok(failures)
+    }
+}
+
+impl fmt::Debug for FinalizeRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// At least `min` refs in `section`; packs without the section pass (the
+/// finalize checklist decides which sections must exist).
+pub struct MinRefsRule {
+    name: String,
+    section: SectionKey,
+    min: usize,
+}
+
+impl MinRefsRule {
+    pub fn new(section: SectionKey, min: usize) -> Self {
+        Self {
+            name: format!("min_refs.{}", section),
+            section,
+            min,
+        }
+    }
+}
+
+#[async_trait]
+impl ValidatorPort for MinRefsRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn validate(&self, pack: &Pack) -> Result<Vec<String>> {
+        let Some(section) = pack.sections.iter().find(|s| s.key == self.section) else {
+            return Ok(Vec::new());
+        };
+        if section.refs.len() >= self.min {
+            return Ok(Vec::new());
+        }
+        Ok(vec![format!(
+            "section {} has {} ref(s), needs at least {}",
+            self.section,
+            section.refs.len(),
+            self.min
+        )])
+    }
+}
+
+/// The pack carries `tag`.
+pub struct RequiredTagRule {
+    name: String,
+    tag: String,
+}
+
+impl RequiredTagRule {
+    pub fn new(tag: &str) -> Self {
+        Self {
+            name: format!("required_tag.{tag}"),
+            tag: tag.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl ValidatorPort for RequiredTagRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn validate(&self, pack: &Pack) -> Result<Vec<String>> {
+        if pack.tags.contains(&self.tag) {
+            return Ok(Vec::new());
+        }
+        Ok(vec![format!("pack must be tagged '{}'", self.tag)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::types::PackId;
+
+    #[tokio::test]
+    async fn test_built_in_rules_parse_and_report_each_failure() {
+        let rules = FinalizeRules::parse("min_refs.findings=2, required_tag=security").unwrap();
+        assert_eq!(
+            rules.names(),
+            ["min_refs.findings", "required_tag.security"]
+        );
+        assert!(FinalizeRules::parse("min_refs.findings=0").is_err());
+        assert!(FinalizeRules::parse("required_tag=").is_err());
+        assert!(FinalizeRules::parse("max_stale=1").is_err());
+
+        let mut pack = Pack::new(PackId::new(), None);
+        assert_eq!(rules.failures(&pack).await.unwrap().len(), 1);
+        pack.upsert_section(SectionKey::new("findings").unwrap(), "F".into(), None, None)
+            .unwrap();
+        let failures = rules.failures(&pack).await.unwrap();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].rule, "min_refs.findings");
+        assert_eq!(
+            failures[0].message,
+            "section findings has 0 ref(s), needs at least 2"
+        );
+        assert_eq!(failures[1].message, "pack must be tagged 'security'");
+
+        pack.tags.push("security".into());
+        assert_eq!(rules.failures(&pack).await.unwrap().len(), 1);
+    }
+}
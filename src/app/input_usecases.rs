@@ -2,36 +2,366 @@ use std::sync::Arc;
 
 use crate::{
     app::{
-        ports::{CodeExcerptPort, FreshnessState, ListFilter, PackRepositoryPort},
-        resolver::resolve_pack,
+        finalize_rules::FinalizeRules,
+        markdown_import::parse_markdown_document,
+        ports::{
+            AuditEntry, AuditLogPort, CodeExcerptPort, FreshnessState, ListFilter,
+            PackRepositoryPort, ValidatorPort,
+        },
+        resolver::{resolve_pack, resolve_revision},
     },
     domain::{
         errors::{
-            revision_conflict_guidance, DomainError, FinalizeRefIssue, Result,
+            revision_conflict_guidance, DomainError, FinalizeRefIssue, FinalizeRuleFailure, Result,
             REVISION_CONFLICT_CHANGED_KEYS_LIMIT,
         },
-        models::{CodeRef, Diagram, Pack, RefSpec, Section},
+        lint::{lint_pack, LintReport},
+        models::{
+            excerpt_content_hash, excerpt_lines, Attachment, CodeRef, Diagram, DiagramLimits,
+            ExcerptLimits, ExcerptSnapshot, FinalizeChecklist, MergePolicy, MergedSection,
+            OwnershipMode, Pack, PackBundle, PackLink, PackTemplate, ReadDefaults, RefSpec,
+            Section, SectionTemplates, SignOffPolicy, SignOffVerdict, TtlPolicy, TtlSource,
+        },
+        reanchor::{find_by_hash, find_excerpt, Anchor},
+        text_diff::line_diff,
         types::{
-            DiagramKey, LineRange, PackId, PackName, RefKey, RelativePath, SectionKey, Status,
+            AttachmentKey, DiagramKey, ExternalUrl, GitSha, LanguageTag, LineRange, PackId,
+            PackName, PackSectionRef, RefKey, RelativePath, SectionKey, SourceRootName, Status,
+            SymbolName,
         },
     },
 };
-use std::collections::HashSet;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+
+/// How long a `prepare_delete` token stays valid.
+pub const DELETE_CONFIRMATION_TTL_SECONDS: i64 = 300;
 
 pub struct InputUseCases {
     repo: Arc<dyn PackRepositoryPort>,
     excerpt: Arc<dyn CodeExcerptPort>,
+    diagram_limits: DiagramLimits,
+    excerpt_limits: ExcerptLimits,
+    ttl_policy: TtlPolicy,
+    sign_off_policy: SignOffPolicy,
+    section_templates: SectionTemplates,
+    finalize_checklist: FinalizeChecklist,
+    finalize_rules: FinalizeRules,
+    attachment_max_bytes: usize,
+    ownership: OwnershipMode,
+    audit_log: Option<Arc<dyn AuditLogPort>>,
+    /// Outstanding delete confirmations, keyed by token. Process-local: a token
+    /// is only honored by the server that issued it.
+    delete_confirmations: Mutex<HashMap<String, DeleteConfirmation>>,
+}
+
+/// Size of a planned write; see [`InputUseCases::estimate_snapshot`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteEstimate {
+    /// Target pack for updates; `None` for creates.
+    pub pack_id: Option<String>,
+    /// Encoded size of the pack after the write.
+    pub pack_bytes: usize,
+    pub current_pack_bytes: Option<usize>,
+    pub delta_bytes: Option<i64>,
+    pub limits: Vec<LimitDistance>,
+    /// Whether every limit has `remaining >= 0`.
+    pub fits: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LimitDistance {
+    pub limit: &'static str,
+    pub actual: usize,
+    pub max: usize,
+    /// `max - actual`; negative when the write would exceed the limit.
+    pub remaining: i64,
+}
+
+impl LimitDistance {
+    fn new(limit: &'static str, actual: usize, max: usize) -> Self {
+        Self {
+            limit,
+            actual,
+            max,
+            remaining: max as i64 - actual as i64,
+        }
+    }
+}
+
+/// Result of [`InputUseCases::audit_tail`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditTail {
+    pub pack_id: String,
+    /// Oldest first.
+    pub entries: Vec<AuditEntry>,
+}
+
+impl AuditTail {
+    pub const DEFAULT_LIMIT: usize = 20;
+    pub const MAX_LIMIT: usize = 200;
+}
+
+/// Result of [`InputUseCases::validate_readiness`]: every finalize check run
+/// against the stored pack, in the `finalize_validation` shape.
+#[derive(Debug, Clone, Serialize)]
+pub struct FinalizeReadiness {
+    pub pack_id: String,
+    pub revision: u64,
+    pub status: Status,
+    /// Whether finalizing this revision now would pass.
+    pub ready: bool,
+    pub missing_sections: Vec<String>,
+    pub missing_fields: Vec<String>,
+    pub invalid_refs: Vec<FinalizeRefIssue>,
+    pub failed_rules: Vec<FinalizeRuleFailure>,
+    /// One message per failed check (gate, sign-offs, refs, links, rules).
+    pub problems: Vec<String>,
+}
+
+/// Server-issued, single-use permission to delete one pack at one revision.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteConfirmation {
+    pub confirm_token: String,
+    pub pack_id: String,
+    pub revision: u64,
+    pub status: Status,
+    /// Whether `delete` refuses this pack without the token (finalized packs).
+    pub confirmation_required: bool,
+    pub expires_at: DateTime<Utc>,
 }
 
 pub struct UpsertRefRequest {
     pub section_key: String,
     pub ref_key: String,
+    /// Source root `path` is under; `None` is the default root.
+    pub root: Option<String>,
     pub path: String,
     pub line_start: usize,
     pub line_end: usize,
     pub title: Option<String>,
     pub why: Option<String>,
     pub group: Option<String>,
+    /// Anchor the ref to this definition; `line_start`/`line_end` are then
+    /// ignored and the range is resolved from the source.
+    pub symbol: Option<String>,
+    /// Link to an external document instead of source lines; `path` and
+    /// the line range are then ignored.
+    pub url: Option<String>,
+    /// Rendered excerpt length for this ref; `None` uses the pack's.
+    pub excerpt_line_limit: Option<usize>,
+}
+
+/// One step of a batched `write`; see [`InputUseCases::apply_ops_checked`].
+pub enum WriteOp {
+    UpsertSection {
+        section_key: String,
+        title: String,
+        description: Option<String>,
+        order: Option<usize>,
+    },
+    DeleteSection {
+        section_key: String,
+    },
+    UpsertRef(UpsertRefRequest),
+    DeleteRef {
+        section_key: String,
+        ref_key: String,
+    },
+    SetMeta {
+        title: Option<String>,
+        brief: Option<String>,
+        tags: Option<Vec<String>>,
+    },
+    /// Finalizing runs the same checks as a finalizing snapshot write, so
+    /// with `validate_only` it is a readiness pre-check.
+    SetStatus {
+        status: Status,
+    },
+}
+
+impl WriteOp {
+    /// Most ops one batched write accepts.
+    pub const MAX_PER_WRITE: usize = 200;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UpsertSection { .. } => "upsert_section",
+            Self::DeleteSection { .. } => "delete_section",
+            Self::UpsertRef(_) => "upsert_ref",
+            Self::DeleteRef { .. } => "delete_ref",
+            Self::SetMeta { .. } => "set_meta",
+            Self::SetStatus { .. } => "set_status",
+        }
+    }
+}
+
+pub struct ApplyOpsRequest {
+    pub identifier: String,
+    pub expected_revision: u64,
+    pub ops: Vec<WriteOp>,
+    /// Run every op and check the result without saving it.
+    pub validate_only: bool,
+    /// See [`Pack::set_write_reason`].
+    pub reason: Option<String>,
+}
+
+pub struct MoveSectionRequest {
+    pub from: String,
+    pub from_expected_revision: u64,
+    pub to: String,
+    pub to_expected_revision: u64,
+    pub section_key: String,
+    /// Key in the target pack; defaults to the current key, suffixed on conflict.
+    pub target_section_key: Option<String>,
+    /// Recorded on both packs (see [`Pack::set_write_reason`]).
+    pub reason: Option<String>,
+}
+
+pub struct MergePacksRequest {
+    /// Pack whose sections are copied; it is left unchanged.
+    pub source: String,
+    pub target: String,
+    pub target_expected_revision: u64,
+    pub policy: MergePolicy,
+    /// See [`Pack::set_write_reason`]; defaults to `merge of <id> revision N`.
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergedPacks {
+    pub target: Pack,
+    pub source_id: PackId,
+    pub source_revision: u64,
+    pub sections: Vec<MergedSection>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MovedSection {
+    /// Key the section has in the target pack.
+    pub section_key: String,
+    pub renamed: bool,
+    pub source: Pack,
+    pub target: Pack,
+}
+
+pub struct SplitPackRequest {
+    pub identifier: String,
+    pub expected_revision: u64,
+    pub section_keys: Vec<String>,
+    /// Name for the new pack; unnamed when omitted.
+    pub name: Option<String>,
+    /// Title for the new pack; defaults to the parent title plus " (split)".
+    pub title: Option<String>,
+    /// Recorded on both packs (see [`Pack::set_write_reason`]).
+    pub reason: Option<String>,
+    /// Recorded as the new pack's [`Pack::owner`].
+    pub owner: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitPack {
+    pub parent: Pack,
+    pub child: Pack,
+}
+
+pub struct SignOffRequest {
+    pub identifier: String,
+    pub expected_revision: u64,
+    pub reviewer: String,
+    pub verdict: SignOffVerdict,
+    pub role: Option<String>,
+    pub comment: Option<String>,
+    /// See [`Pack::set_write_reason`].
+    pub reason: Option<String>,
+}
+
+pub struct SetLinksRequest {
+    pub identifier: String,
+    pub expected_revision: u64,
+    /// The complete new set; empty clears every link.
+    pub links: Vec<PackLink>,
+    /// See [`Pack::set_write_reason`].
+    pub reason: Option<String>,
+}
+
+pub struct AddLinkRequest {
+    pub identifier: String,
+    pub expected_revision: u64,
+    pub link: PackLink,
+    /// See [`Pack::set_write_reason`].
+    pub reason: Option<String>,
+}
+
+pub struct RollbackRequest {
+    pub identifier: String,
+    pub expected_revision: u64,
+    /// Journaled revision whose content is restored.
+    pub to_revision: u64,
+    /// Defaults to "rollback to revision N" (see [`Pack::set_write_reason`]).
+    pub reason: Option<String>,
+}
+
+pub struct ImportBundleRequest {
+    pub bundle: PackBundle,
+    /// Name for the imported pack; defaults to the bundled pack's name.
+    pub name: Option<String>,
+    /// Defaults to the TTL policy for the pack's name and tags.
+    pub ttl_minutes: Option<u64>,
+    /// Defaults to "import of <source id> revision N" (see [`Pack::set_write_reason`]).
+    pub reason: Option<String>,
+    /// Recorded as the new pack's [`Pack::owner`].
+    pub owner: Option<String>,
+}
+
+pub struct ClonePackRequest {
+    pub identifier: String,
+    /// Sections to copy, in the source's order; `None` copies all of them.
+    pub section_keys: Option<Vec<String>>,
+    /// The clone is unnamed unless given one; the source keeps its name.
+    pub name: Option<String>,
+    /// Defaults to the TTL policy for the name and the source's tags.
+    pub ttl_minutes: Option<u64>,
+    /// Defaults to "clone of <source id> revision N" (see [`Pack::set_write_reason`]).
+    pub reason: Option<String>,
+    /// Recorded as the new pack's [`Pack::owner`].
+    pub owner: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClonedPack {
+    pub pack: Pack,
+    pub source_id: PackId,
+    /// Revision of the source that was copied.
+    pub source_revision: u64,
+}
+
+pub struct ImportMarkdownRequest {
+    /// Document in the format read by [`parse_markdown_document`].
+    pub markdown: String,
+    pub name: Option<String>,
+    pub tags: Vec<String>,
+    /// Defaults to the TTL policy for the name and tags.
+    pub ttl_minutes: Option<u64>,
+    pub validate_only: bool,
+    pub reason: Option<String>,
+    /// Recorded as the new pack's [`Pack::owner`].
+    pub owner: Option<String>,
+}
+
+pub struct CreateFromTemplateRequest {
+    pub template: PackTemplate,
+    pub name: Option<String>,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    /// Defaults to the TTL policy for the name and tags.
+    pub ttl_minutes: Option<u64>,
+    pub validate_only: bool,
+    pub reason: Option<String>,
+    /// Recorded as the new pack's [`Pack::owner`].
+    pub owner: Option<String>,
 }
 
 pub struct UpsertDiagramRequest {
@@ -42,11 +372,38 @@ pub struct UpsertDiagramRequest {
     pub why: Option<String>,
 }
 
+pub struct UpsertAttachmentRequest {
+    pub identifier: String,
+    pub expected_revision: u64,
+    pub section_key: String,
+    pub attachment_key: String,
+    pub title: Option<String>,
+    /// Defaults to [`Attachment::DEFAULT_MEDIA_TYPE`].
+    pub media_type: Option<String>,
+    /// Stored verbatim.
+    pub content: String,
+    pub reason: Option<String>,
+}
+
+pub struct DeleteAttachmentRequest {
+    pub identifier: String,
+    pub expected_revision: u64,
+    pub section_key: String,
+    pub attachment_key: String,
+    pub reason: Option<String>,
+}
+
 pub struct WriteSnapshotRequest {
     pub identifier: Option<String>,
     pub expected_revision: Option<u64>,
     pub validate_only: bool,
+    /// Store each ref's current excerpt text in the pack (see [`ExcerptSnapshot`]).
+    pub snapshot_excerpts: bool,
     pub document: SnapshotDocument,
+    /// Why this write happens; see [`Pack::set_write_reason`].
+    pub reason: Option<String>,
+    /// Recorded as [`Pack::owner`] on creates; updates keep the owner.
+    pub owner: Option<String>,
 }
 
 pub struct SnapshotDocument {
@@ -57,24 +414,39 @@ pub struct SnapshotDocument {
     pub ttl_minutes: Option<u64>,
     pub status: Status,
     pub sections: Vec<SnapshotSection>,
+    /// Full replace like every other field: omitted means no pack defaults.
+    pub read_defaults: ReadDefaults,
 }
 
 pub struct SnapshotSection {
     pub key: String,
     pub title: String,
     pub description: Option<String>,
+    /// Language tag -> translated description.
+    pub translations: Vec<(String, String)>,
     pub refs: Vec<SnapshotRef>,
     pub diagrams: Vec<SnapshotDiagram>,
 }
 
 pub struct SnapshotRef {
     pub key: String,
+    /// Source root `path` is under; `None` is the default root.
+    pub root: Option<String>,
     pub path: String,
     pub line_start: usize,
     pub line_end: usize,
     pub title: Option<String>,
     pub why: Option<String>,
     pub group: Option<String>,
+    pub entry_point: bool,
+    /// Commit to read the lines at; `None` reads the working tree.
+    pub git_sha: Option<String>,
+    /// Definition to follow; the write replaces the lines with its range.
+    pub symbol: Option<String>,
+    /// External link; `path` and the lines are ignored when set.
+    pub url: Option<String>,
+    /// Rendered excerpt length for this ref; `None` uses the pack's.
+    pub excerpt_line_limit: Option<usize>,
 }
 
 pub struct SnapshotDiagram {
@@ -84,15 +456,187 @@ pub struct SnapshotDiagram {
     pub why: Option<String>,
 }
 
+pub struct DiagramHistory {
+    pub pack_id: String,
+    pub section_key: String,
+    pub diagram_key: String,
+    /// Oldest first; the last entry is the current diagram.
+    pub versions: Vec<DiagramHistoryEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagramHistoryEntry {
+    pub version: usize,
+    pub revision: u64,
+    pub current: bool,
+    pub title: String,
+    pub mermaid: String,
+}
+
+impl DiagramHistory {
+    /// Line diff between two 1-based versions.
+    pub fn diff(&self, from_version: usize, to_version: usize) -> Result<String> {
+        let lookup = |version: usize| {
+            version
+                .checked_sub(1)
+                .and_then(|idx| self.versions.get(idx))
+                .ok_or_else(|| {
+                    DomainError::InvalidData(format!(
+                        "diagram '{}' has versions 1..={}; got {}",
+                        self.diagram_key,
+                        self.versions.len(),
+                        version
+                    ))
+                })
+        };
+        let from = lookup(from_version)?;
+        let to = lookup(to_version)?;
+        Ok(line_diff(&from.mermaid, &to.mermaid))
+    }
+}
+
+pub struct RepairRefsRequest {
+    pub identifier: String,
+    pub expected_revision: u64,
+    /// Report the repairs without saving them.
+    pub validate_only: bool,
+    /// Defaults to "repair_refs: N ref(s) re-anchored" (see [`Pack::set_write_reason`]).
+    pub reason: Option<String>,
+}
+
+/// A stale or drifted ref whose excerpt was found again at `to`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RefRepair {
+    pub section_key: String,
+    pub ref_key: String,
+    pub path: String,
+    pub from: LineRange,
+    pub to: LineRange,
+    /// Fraction of excerpt lines found unchanged at `to`.
+    pub score: f64,
+    /// `snapshot` or `content_hash`: what was searched for.
+    pub matched_by: &'static str,
+}
+
+/// A stale or drifted ref `repair_refs` could not re-anchor.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnrepairedRef {
+    pub section_key: String,
+    pub ref_key: String,
+    pub path: String,
+    pub reason: String,
+}
+
+pub struct RepairRefsReport {
+    /// The saved pack, or the pack as it would be saved under `validate_only`.
+    pub pack: Pack,
+    pub repaired: Vec<RefRepair>,
+    pub unresolved: Vec<UnrepairedRef>,
+    /// Whether the repairs were saved.
+    pub applied: bool,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum TouchTtlMode {
     SetMinutes(u64),
     ExtendMinutes(u64),
+    /// Turns read-driven expiry extension on or off (see [`Pack::sliding_expiry`]).
+    Sliding(bool),
+    /// Turns sliding on with a per-pack window in minutes.
+    SlidingMinutes(u64),
+    /// Pins the pack so it never expires, or releases the pin; an unpinned
+    /// pack gets the TTL it would get on create.
+    Pin(bool),
 }
 
 impl InputUseCases {
     pub fn new(repo: Arc<dyn PackRepositoryPort>, excerpt: Arc<dyn CodeExcerptPort>) -> Self {
-        Self { repo, excerpt }
+        Self {
+            repo,
+            excerpt,
+            diagram_limits: DiagramLimits::default(),
+            excerpt_limits: ExcerptLimits::default(),
+            ttl_policy: TtlPolicy::default(),
+            sign_off_policy: SignOffPolicy::default(),
+            section_templates: SectionTemplates::default(),
+            finalize_checklist: FinalizeChecklist::default(),
+            finalize_rules: FinalizeRules::default(),
+            attachment_max_bytes: Attachment::DEFAULT_MAX_BYTES,
+            ownership: OwnershipMode::default(),
+            audit_log: None,
+            delete_confirmations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_diagram_limits(mut self, diagram_limits: DiagramLimits) -> Self {
+        self.diagram_limits = diagram_limits;
+        self
+    }
+
+    /// Cap on pack- and ref-level `excerpt_line_limit` overrides.
+    pub fn with_excerpt_limits(mut self, excerpt_limits: ExcerptLimits) -> Self {
+        self.excerpt_limits = excerpt_limits;
+        self
+    }
+
+    pub fn with_ttl_policy(mut self, ttl_policy: TtlPolicy) -> Self {
+        self.ttl_policy = ttl_policy;
+        self
+    }
+
+    pub fn with_sign_off_policy(mut self, sign_off_policy: SignOffPolicy) -> Self {
+        self.sign_off_policy = sign_off_policy;
+        self
+    }
+
+    pub fn with_section_templates(mut self, section_templates: SectionTemplates) -> Self {
+        self.section_templates = section_templates;
+        self
+    }
+
+    pub fn with_finalize_checklist(mut self, finalize_checklist: FinalizeChecklist) -> Self {
+        self.finalize_checklist = finalize_checklist;
+        self
+    }
+
+    pub fn with_finalize_rules(mut self, finalize_rules: FinalizeRules) -> Self {
+        self.finalize_rules = finalize_rules;
+        self
+    }
+
+    /// Adds one finalize rule after those already registered.
+    pub fn with_validator(mut self, validator: Arc<dyn ValidatorPort>) -> Self {
+        self.finalize_rules.register(validator);
+        self
+    }
+
+    /// Whether [`Self::authorize_owner`] blocks other actors' mutations.
+    pub fn with_ownership_mode(mut self, ownership: OwnershipMode) -> Self {
+        self.ownership = ownership;
+        self
+    }
+
+    /// Log [`Self::audit_tail`] reads; writing to it is the job of
+    /// [`crate::app::audit::AuditHook`] on the repository.
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLogPort>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Per-attachment size cap; the whole pack stays under the store's own cap.
+    pub fn with_attachment_max_bytes(mut self, attachment_max_bytes: usize) -> Self {
+        self.attachment_max_bytes = attachment_max_bytes;
+        self
+    }
+
+    /// TTL a snapshot create applies, and where it comes from: the document's
+    /// `ttl_minutes`, else the tag/namespace/default policy.
+    pub fn create_ttl(&self, document: &SnapshotDocument) -> Result<(u64, TtlSource)> {
+        if let Some(minutes) = document.ttl_minutes {
+            return Ok((minutes, TtlSource::Explicit));
+        }
+        let name = document.name.as_deref().map(PackName::new).transpose()?;
+        Ok(self.ttl_policy.resolve(name.as_ref(), &document.tags))
     }
 
     // ── identity resolution ───────────────────────────────────────────────────
@@ -101,6 +645,25 @@ impl InputUseCases {
         resolve_pack(self.repo.as_ref(), identifier).await
     }
 
+    /// Checks that `actor` may mutate the pack under the ownership mode (see
+    /// [`OwnershipMode::check`]). A pack that does not resolve passes, so the
+    /// mutation itself reports it.
+    pub async fn authorize_owner(
+        &self,
+        identifier: &str,
+        actor: Option<&str>,
+        force: bool,
+    ) -> Result<()> {
+        if self.ownership == OwnershipMode::Record || force {
+            return Ok(());
+        }
+        match self.resolve(identifier).await {
+            Ok(pack) => self.ownership.check(&pack, actor, force),
+            Err(DomainError::NotFound(_)) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
     async fn resolve_for_update(&self, identifier: &str, expected_revision: u64) -> Result<Pack> {
         let pack = self.resolve(identifier).await?;
         if pack.revision != expected_revision {
@@ -109,22 +672,34 @@ impl InputUseCases {
                 current_revision: pack.revision,
                 last_updated_at: pack.updated_at.to_rfc3339(),
                 changed_section_keys: conflict_changed_section_keys(&pack),
-                guidance: revision_conflict_guidance(pack.revision),
+                guidance: revision_conflict_guidance(pack.revision, pack.current_write_reason()),
+                produced_by: pack.current_write_reason().map(str::to_string),
             });
         }
         Ok(pack)
     }
 
+    /// Url refs have nothing to resolve and are exempt.
     async fn validate_refs_resolvable_before_finalize(&self, pack: &Pack) -> Result<()> {
         let mut invalid_refs = Vec::new();
         for section in &pack.sections {
-            for code_ref in &section.refs {
-                match self
-                    .excerpt
-                    .read_lines(&code_ref.path, code_ref.lines)
-                    .await
-                {
-                    Ok(_) => {}
+            for code_ref in section.refs.iter().filter(|r| r.reads_source()) {
+                match self.excerpt.read_ref(code_ref).await {
+                    Ok(snippet) => {
+                        if let Some((stored, current)) = code_ref.content_drift(&snippet.body) {
+                            invalid_refs.push(FinalizeRefIssue {
+                                section_key: section.key.as_str().to_string(),
+                                ref_key: code_ref.key.as_str().to_string(),
+                                path: code_ref.path.as_str().to_string(),
+                                line_start: code_ref.lines.start,
+                                line_end: code_ref.lines.end,
+                                reason: format!(
+                                    "drifted: content hash {} at upsert, {} now; re-upsert the ref to accept the new lines",
+                                    stored, current
+                                ),
+                            });
+                        }
+                    }
                     Err(DomainError::StaleRef(msg)) => {
                         invalid_refs.push(FinalizeRefIssue {
                             section_key: section.key.as_str().to_string(),
@@ -169,18 +744,104 @@ impl InputUseCases {
             missing_sections: Vec::new(),
             missing_fields: Vec::new(),
             invalid_refs,
+            failed_rules: Vec::new(),
+        })
+    }
+
+    /// A `pack:<id>#<section_key>` path makes a section ref; url refs never
+    /// are one, whatever their path says.
+    fn parse_section_ref(url: Option<&ExternalUrl>, path: &str) -> Result<Option<PackSectionRef>> {
+        if url.is_some() || !PackSectionRef::is_pack_ref(path) {
+            return Ok(None);
+        }
+        PackSectionRef::parse(path).map(Some)
+    }
+
+    /// Every link target must still be stored; deleted or purged packs fail.
+    async fn validate_links_resolvable_before_finalize(&self, pack: &Pack) -> Result<()> {
+        let mut missing = Vec::new();
+        for link in &pack.links {
+            if self.repo.get_by_id(&link.target).await?.is_none() {
+                missing.push(format!("{} {}", link.kind, link.target));
+            }
+        }
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(DomainError::FinalizeValidation {
+            message: format!(
+                "links point at packs that do not exist ({} total): {}",
+                missing.len(),
+                missing.join("; ")
+            ),
+            missing_sections: Vec::new(),
+            missing_fields: Vec::new(),
+            invalid_refs: Vec::new(),
+            failed_rules: Vec::new(),
+        })
+    }
+
+    /// Runs every registered [`ValidatorPort`]; all failures are reported
+    /// together.
+    async fn validate_rules_before_finalize(&self, pack: &Pack) -> Result<()> {
+        let failed_rules = self.finalize_rules.failures(pack).await?;
+        if failed_rules.is_empty() {
+            return Ok(());
+        }
+        Err(DomainError::FinalizeValidation {
+            message: format!(
+                "finalize rules failed: {}",
+                failed_rules
+                    .iter()
+                    .map(|failure| format!("{}: {}", failure.rule, failure.message))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            missing_sections: Vec::new(),
+            missing_fields: Vec::new(),
+            invalid_refs: Vec::new(),
+            failed_rules,
         })
     }
 
-    async fn validate_finalize_state_if_needed(&self, pack: &Pack) -> Result<()> {
+    async fn validate_finalize_state_if_needed(
+        &self,
+        current: Option<&Pack>,
+        pack: &Pack,
+    ) -> Result<()> {
         if pack.status == Status::Finalized {
-            pack.validate_finalize_gate()?;
+            pack.validate_finalize_gate_with(&self.finalize_checklist, &self.section_templates)?;
+            self.validate_sign_offs(current, pack)?;
             self.validate_refs_resolvable_before_finalize(pack).await?;
+            self.validate_links_resolvable_before_finalize(pack).await?;
+            self.validate_rules_before_finalize(pack).await?;
         }
         Ok(())
     }
 
-    fn snapshot_sections(snapshot: &[SnapshotSection]) -> Result<Vec<Section>> {
+    /// Applies the sign-off policy to a write that leaves `next` finalized.
+    /// Sign-offs are taken from the stored pack, and only count when the write
+    /// keeps its content; a pack that was already finalized may keep its
+    /// status as long as the content is unchanged.
+    fn validate_sign_offs(&self, current: Option<&Pack>, next: &Pack) -> Result<()> {
+        if self.sign_off_policy.is_empty() {
+            return Ok(());
+        }
+        match current {
+            Some(current) if current.same_content(next) => {
+                if current.status == Status::Finalized {
+                    return Ok(());
+                }
+                self.sign_off_policy.check(&current.current_sign_offs())
+            }
+            _ => self.sign_off_policy.check(&[]),
+        }
+    }
+
+    fn snapshot_sections(
+        snapshot: &[SnapshotSection],
+        diagram_limits: &DiagramLimits,
+    ) -> Result<Vec<Section>> {
         let mut sections = Vec::with_capacity(snapshot.len());
         let mut seen_sections = HashSet::new();
 
@@ -205,14 +866,46 @@ impl InputUseCases {
                         ref_key_str, section_key
                     )));
                 }
-                refs.push(CodeRef {
+                let url = code_ref.url.as_deref().map(ExternalUrl::new).transpose()?;
+                let section_ref = Self::parse_section_ref(url.as_ref(), &code_ref.path)?;
+                let (path, lines) = match &url {
+                    Some(url) => (url.locator(), LineRange::new(1, 1)?),
+                    None if section_ref.is_some() => {
+                        (RelativePath::new(&code_ref.path)?, LineRange::new(1, 1)?)
+                    }
+                    None => (
+                        RelativePath::new(&code_ref.path)?,
+                        LineRange::new(code_ref.line_start, code_ref.line_end)?,
+                    ),
+                };
+                let parsed = CodeRef {
                     key: ref_key,
-                    path: RelativePath::new(&code_ref.path)?,
-                    lines: LineRange::new(code_ref.line_start, code_ref.line_end)?,
+                    root: code_ref
+                        .root
+                        .as_deref()
+                        .map(SourceRootName::new)
+                        .transpose()?,
+                    path,
+                    lines,
                     title: code_ref.title.clone(),
                     why: code_ref.why.clone(),
                     group: code_ref.group.clone(),
-                });
+                    entry_point: code_ref.entry_point,
+                    git_sha: code_ref.git_sha.as_deref().map(GitSha::new).transpose()?,
+                    symbol: code_ref
+                        .symbol
+                        .as_deref()
+                        .map(SymbolName::new)
+                        .transpose()?,
+                    url,
+                    section_ref,
+                    excerpt_line_limit: code_ref.excerpt_line_limit,
+                    snapshot: None,
+                    content_hash: None,
+                    changed_revision: None,
+                };
+                parsed.validate_target()?;
+                refs.push(parsed);
             }
 
             let mut diagrams = Vec::with_capacity(section.diagrams.len());
@@ -226,41 +919,73 @@ impl InputUseCases {
                         diagram_key_str, section_key
                     )));
                 }
+                diagram_limits.validate(&key, &diagram_key, &diagram.mermaid)?;
                 diagrams.push(Diagram {
                     key: diagram_key,
                     title: diagram.title.clone(),
                     mermaid: diagram.mermaid.clone(),
                     why: diagram.why.clone(),
+                    history: Vec::new(),
                 });
             }
 
+            let mut translations = BTreeMap::new();
+            for (lang, text) in &section.translations {
+                let lang = LanguageTag::new(lang)?;
+                if text.trim().is_empty() {
+                    return Err(DomainError::InvalidData(format!(
+                        "translation '{}' of section '{}' is empty",
+                        lang, section_key
+                    )));
+                }
+                if translations.insert(lang.clone(), text.clone()).is_some() {
+                    return Err(DomainError::InvalidData(format!(
+                        "duplicate translation '{}' in section '{}'",
+                        lang, section_key
+                    )));
+                }
+            }
+
             sections.push(Section {
                 key,
                 title: section.title.clone(),
                 description: section.description.clone(),
+                translations,
                 refs,
                 diagrams,
+                attachments: Vec::new(),
+                changed_revision: None,
             });
         }
 
         Ok(sections)
     }
 
-    fn build_create_snapshot(snapshot: SnapshotDocument) -> Result<Pack> {
+    fn build_create_snapshot(
+        snapshot: SnapshotDocument,
+        diagram_limits: &DiagramLimits,
+        ttl_minutes: u64,
+    ) -> Result<Pack> {
         let pack_name = snapshot.name.as_deref().map(PackName::new).transpose()?;
         let mut pack = Pack::new(PackId::new(), pack_name);
-        if let Some(ttl_minutes) = snapshot.ttl_minutes {
-            pack.set_ttl_on_create(ttl_minutes, pack.created_at)?;
-        }
+        pack.set_ttl_on_create(ttl_minutes, pack.created_at)?;
         pack.title = snapshot.title;
         pack.brief = snapshot.brief;
         pack.tags = snapshot.tags;
         pack.status = snapshot.status;
-        pack.sections = Self::snapshot_sections(&snapshot.sections)?;
+        pack.sections = Self::snapshot_sections(&snapshot.sections, diagram_limits)?;
+        snapshot.read_defaults.validate()?;
+        pack.read_defaults = snapshot.read_defaults;
+        pack.validate_entry_points()?;
+        pack.carry_change_revisions(None);
         Ok(pack)
     }
 
-    fn build_update_snapshot(current: &Pack, snapshot: SnapshotDocument) -> Result<Pack> {
+    fn build_update_snapshot(
+        current: &Pack,
+        snapshot: SnapshotDocument,
+        diagram_limits: &DiagramLimits,
+    ) -> Result<Pack> {
         if let Some(snapshot_name) = snapshot.name {
             let parsed = PackName::new(&snapshot_name)?;
             if current.name.as_ref() != Some(&parsed) {
@@ -279,59 +1004,450 @@ impl InputUseCases {
             brief: snapshot.brief,
             status: snapshot.status,
             tags: snapshot.tags,
-            sections: Self::snapshot_sections(&snapshot.sections)?,
+            sections: Self::snapshot_sections(&snapshot.sections, diagram_limits)?,
             revision: current.revision.saturating_add(1),
             created_at: current.created_at,
             updated_at: now,
             expires_at: current.expires_at,
+            sliding_ttl: current.sliding_ttl,
+            sliding_ttl_minutes: current.sliding_ttl_minutes,
+            pinned: current.pinned,
+            read_defaults: snapshot.read_defaults,
+            split_from: current.split_from.clone(),
+            sign_offs: current.sign_offs.clone(),
+            links: current.links.clone(),
+            removed_sections: Vec::new(),
+            last_write_reason: current.last_write_reason.clone(),
+            owner: current.owner.clone(),
         };
+        pack.read_defaults.validate()?;
+        Self::carry_diagram_history(current, &mut pack, now);
+        Self::carry_excerpt_snapshots(current, &mut pack);
+        Self::carry_attachments(current, &mut pack);
+        pack.carry_change_revisions(Some(current));
+        pack.validate_entry_points()?;
 
         if let Some(ttl_minutes) = snapshot.ttl_minutes {
             pack.expires_at = Pack::ttl_deadline_from_now(ttl_minutes, now)?;
+            pack.pinned = false;
         }
         Ok(pack)
     }
 
-    // ── queries ───────────────────────────────────────────────────────────────
-
-    pub async fn list(
-        &self,
-        status: Option<Status>,
-        query: Option<String>,
-        limit: Option<usize>,
-        offset: Option<usize>,
-    ) -> Result<Vec<Pack>> {
-        self.list_with_freshness(status, query, limit, offset, None)
-            .await
+    fn carry_diagram_history(current: &Pack, next: &mut Pack, now: chrono::DateTime<chrono::Utc>) {
+        for section in &mut next.sections {
+            let Some(previous_section) = current.sections.iter().find(|s| s.key == section.key)
+            else {
+                continue;
+            };
+            for diagram in &mut section.diagrams {
+                if let Some(previous) = previous_section
+                    .diagrams
+                    .iter()
+                    .find(|d| d.key == diagram.key)
+                {
+                    diagram.inherit_history(previous, current.revision, now);
+                }
+            }
+        }
     }
 
-    pub async fn list_with_freshness(
-        &self,
-        status: Option<Status>,
-        query: Option<String>,
-        limit: Option<usize>,
-        offset: Option<usize>,
-        freshness: Option<FreshnessState>,
-    ) -> Result<Vec<Pack>> {
-        self.repo
-            .list_packs(ListFilter {
-                status,
-                freshness,
-                query,
-                limit,
-                offset,
-            })
-            .await
+    fn carry_excerpt_snapshots(current: &Pack, next: &mut Pack) {
+        for section in &mut next.sections {
+            let Some(previous_section) = current.sections.iter().find(|s| s.key == section.key)
+            else {
+                continue;
+            };
+            for code_ref in &mut section.refs {
+                if let Some(previous) = previous_section.refs.iter().find(|r| r.key == code_ref.key)
+                {
+                    code_ref.inherit_snapshot(previous);
+                }
+            }
+        }
     }
 
-    pub async fn get(&self, identifier: &str) -> Result<Pack> {
-        self.resolve(identifier).await
+    /// Snapshots don't carry attachments; sections that keep their key keep
+    /// theirs.
+    fn carry_attachments(current: &Pack, next: &mut Pack) {
+        for section in &mut next.sections {
+            if let Some(previous) = current.sections.iter().find(|s| s.key == section.key) {
+                section.attachments = previous.attachments.clone();
+            }
+        }
     }
 
-    pub async fn delete_pack_file(&self, identifier: &str) -> Result<bool> {
-        let pack_id = PackId::parse(identifier)?;
-        self.repo.delete_pack_file(&pack_id).await
-    }
+    /// Rejects refs that newly name a source root the excerpt source doesn't
+    /// have. Refs keeping a root that was configured once are left alone;
+    /// they read as stale until it is configured again.
+    fn validate_source_roots(&self, current: Option<&Pack>, next: &Pack) -> Result<()> {
+        for section in &next.sections {
+            for code_ref in &section.refs {
+                let Some(root) = &code_ref.root else {
+                    continue;
+                };
+                let kept = current
+                    .and_then(|c| c.sections.iter().find(|s| s.key == section.key))
+                    .and_then(|s| s.refs.iter().find(|r| r.key == code_ref.key))
+                    .is_some_and(|previous| previous.root.as_ref() == Some(root));
+                if !kept {
+                    self.check_source_root(root, &section.key, &code_ref.key)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_source_root(
+        &self,
+        root: &SourceRootName,
+        section_key: &SectionKey,
+        ref_key: &RefKey,
+    ) -> Result<()> {
+        let available = self.excerpt.source_roots();
+        if available.contains(root) {
+            return Ok(());
+        }
+        Err(DomainError::DetailedInvalidData {
+            message: format!(
+                "ref '{}' in section '{}' names unknown source root '{}'",
+                ref_key, section_key, root
+            ),
+            details: serde_json::json!({
+                "field": "root",
+                "section_key": section_key.as_str(),
+                "ref_key": ref_key.as_str(),
+                "root": root.as_str(),
+                "available_roots": available.iter().map(SourceRootName::as_str).collect::<Vec<_>>(),
+            }),
+        })
+    }
+
+    /// Sets each symbol-anchored ref's `lines` to where its definition is now.
+    /// Symbols that can't be found keep their last range; reads and finalize
+    /// report them as stale.
+    async fn refresh_symbol_ranges(&self, pack: &mut Pack) -> Result<()> {
+        for section in &mut pack.sections {
+            for code_ref in &mut section.refs {
+                let Some(symbol) = &code_ref.symbol else {
+                    continue;
+                };
+                match self
+                    .excerpt
+                    .resolve_symbol_in(
+                        code_ref.root.as_ref(),
+                        &code_ref.path,
+                        symbol,
+                        code_ref.git_sha.as_ref(),
+                    )
+                    .await
+                {
+                    Ok(lines) => code_ref.lines = lines,
+                    Err(DomainError::StaleRef(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records [`excerpt_content_hash`] for refs without one (plus `restamp`,
+    /// a ref just upserted). Unreadable refs keep `None`; finalize reports
+    /// them as stale instead.
+    async fn stamp_content_hashes(
+        &self,
+        pack: &mut Pack,
+        restamp: Option<(&SectionKey, &RefKey)>,
+    ) -> Result<()> {
+        for section in &mut pack.sections {
+            for code_ref in &mut section.refs {
+                let forced = restamp.is_some_and(|(section_key, ref_key)| {
+                    section.key == *section_key && code_ref.key == *ref_key
+                });
+                if (code_ref.content_hash.is_some() && !forced) || !code_ref.reads_source() {
+                    continue;
+                }
+                code_ref.content_hash = match self.excerpt.read_ref(code_ref).await {
+                    Ok(snippet) => Some(excerpt_content_hash(&snippet.body)),
+                    Err(DomainError::StaleRef(_)) => None,
+                    Err(e) => return Err(e),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces every source ref's snapshot with a fresh read. Refs that no
+    /// longer resolve fail the write, since a snapshot of nothing is not
+    /// evidence.
+    async fn capture_excerpt_snapshots(&self, pack: &mut Pack) -> Result<()> {
+        let mut stale = Vec::new();
+        for section in &mut pack.sections {
+            for code_ref in section.refs.iter_mut().filter(|r| r.reads_source()) {
+                match self.excerpt.read_ref(code_ref).await {
+                    Ok(snippet) => {
+                        code_ref.snapshot = Some(ExcerptSnapshot {
+                            body: snippet.body,
+                            captured_at: snippet.provenance.read_at,
+                            commit_sha: snippet.provenance.commit_sha,
+                        });
+                    }
+                    Err(DomainError::StaleRef(reason)) => stale.push(serde_json::json!({
+                        "section_key": section.key.as_str(),
+                        "ref_key": code_ref.key.as_str(),
+                        "reason": reason,
+                    })),
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        if stale.is_empty() {
+            return Ok(());
+        }
+        Err(DomainError::DetailedInvalidData {
+            message: format!(
+                "snapshot_excerpts: {} ref(s) cannot be read from the source tree",
+                stale.len()
+            ),
+            details: serde_json::json!({ "stale_refs": stale }),
+        })
+    }
+
+    // ── queries ───────────────────────────────────────────────────────────────
+
+    pub async fn list(
+        &self,
+        status: Option<Status>,
+        query: Option<String>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<Pack>> {
+        self.list_with_freshness(status, query, limit, offset, None)
+            .await
+    }
+
+    pub async fn list_with_freshness(
+        &self,
+        status: Option<Status>,
+        query: Option<String>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        freshness: Option<FreshnessState>,
+    ) -> Result<Vec<Pack>> {
+        self.repo
+            .list_packs(ListFilter {
+                status,
+                freshness,
+                query,
+                tags: Vec::new(),
+                include_expired: None,
+                archived: false,
+                limit,
+                offset,
+            })
+            .await
+    }
+
+    /// Lists with a fully specified filter (e.g. one resolved from a saved filter).
+    pub async fn list_matching(&self, filter: ListFilter) -> Result<Vec<Pack>> {
+        self.repo.list_packs(filter).await
+    }
+
+    pub async fn get(&self, identifier: &str) -> Result<Pack> {
+        self.resolve(identifier).await
+    }
+
+    /// The last `limit` audit entries for a pack. A pack id that no longer
+    /// resolves still reads, so the history of a deleted pack stays visible.
+    pub async fn audit_tail(&self, identifier: &str, limit: usize) -> Result<AuditTail> {
+        let Some(audit_log) = &self.audit_log else {
+            return Err(DomainError::InvalidState(
+                "this server keeps no audit log".into(),
+            ));
+        };
+        if limit == 0 || limit > AuditTail::MAX_LIMIT {
+            return Err(DomainError::InvalidData(format!(
+                "'limit' must be between 1 and {}",
+                AuditTail::MAX_LIMIT
+            )));
+        }
+        let pack_id = match self.resolve(identifier).await {
+            Ok(pack) => pack.id,
+            Err(DomainError::NotFound(message)) => {
+                PackId::parse(identifier).map_err(|_| DomainError::NotFound(message))?
+            }
+            Err(err) => return Err(err),
+        };
+        Ok(AuditTail {
+            entries: audit_log.tail(&pack_id, limit).await?,
+            pack_id: pack_id.as_str().to_string(),
+        })
+    }
+
+    /// Non-blocking quality checks; see [`lint_pack`].
+    pub async fn lint(&self, identifier: &str) -> Result<LintReport> {
+        Ok(lint_pack(&self.resolve(identifier).await?))
+    }
+
+    /// Runs every finalize check against the stored pack without changing
+    /// it. Unlike a finalizing write, which stops at the first failed check,
+    /// this reports all of them.
+    pub async fn validate_readiness(&self, identifier: &str) -> Result<FinalizeReadiness> {
+        let pack = self.resolve(identifier).await?;
+        let mut readiness = FinalizeReadiness {
+            pack_id: pack.id.as_str().to_string(),
+            revision: pack.revision,
+            status: pack.status,
+            ready: true,
+            missing_sections: Vec::new(),
+            missing_fields: Vec::new(),
+            invalid_refs: Vec::new(),
+            failed_rules: Vec::new(),
+            problems: Vec::new(),
+        };
+        let sign_offs = if pack.status == Status::Finalized {
+            Ok(())
+        } else {
+            self.sign_off_policy.check(&pack.current_sign_offs())
+        };
+        let checks = [
+            pack.validate_finalize_gate_with(&self.finalize_checklist, &self.section_templates),
+            sign_offs,
+            self.validate_refs_resolvable_before_finalize(&pack).await,
+            self.validate_links_resolvable_before_finalize(&pack).await,
+            self.validate_rules_before_finalize(&pack).await,
+        ];
+        for check in checks {
+            match check {
+                Ok(()) => continue,
+                Err(DomainError::FinalizeValidation {
+                    message,
+                    missing_sections,
+                    missing_fields,
+                    invalid_refs,
+                    failed_rules,
+                }) => {
+                    readiness.missing_sections.extend(missing_sections);
+                    readiness.missing_fields.extend(missing_fields);
+                    readiness.invalid_refs.extend(invalid_refs);
+                    readiness.failed_rules.extend(failed_rules);
+                    readiness.problems.push(message);
+                }
+                Err(err @ DomainError::SignOffRequired { .. }) => {
+                    readiness.problems.push(err.to_string())
+                }
+                Err(err) => return Err(err),
+            }
+            readiness.ready = false;
+        }
+        Ok(readiness)
+    }
+
+    /// Removes the pack file without any status check; see
+    /// [`Self::delete_pack_confirmed`] for the guarded variant agents use.
+    pub async fn delete_pack_file(&self, identifier: &str) -> Result<bool> {
+        let pack_id = PackId::parse(identifier)?;
+        self.repo.delete_pack_file(&pack_id).await
+    }
+
+    /// Issues a single-use token that [`Self::delete_pack_confirmed`] requires
+    /// before deleting a finalized pack. The token is bound to the pack's
+    /// current revision and expires after [`DELETE_CONFIRMATION_TTL_SECONDS`].
+    pub async fn prepare_delete(&self, identifier: &str) -> Result<DeleteConfirmation> {
+        let pack = self.resolve(identifier).await?;
+        let now = Utc::now();
+        let confirmation = DeleteConfirmation {
+            confirm_token: new_confirm_token(),
+            pack_id: pack.id.as_str().to_string(),
+            revision: pack.revision,
+            status: pack.status,
+            confirmation_required: pack.status == Status::Finalized,
+            expires_at: now + chrono::Duration::seconds(DELETE_CONFIRMATION_TTL_SECONDS),
+        };
+        let mut pending = self
+            .delete_confirmations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        pending.retain(|_, issued| issued.expires_at > now);
+        pending.insert(confirmation.confirm_token.clone(), confirmation.clone());
+        Ok(confirmation)
+    }
+
+    /// Deletes a pack file, requiring a `prepare_delete` token for finalized
+    /// packs. Drafts and files that fail to decode are deleted directly; any
+    /// other read error (I/O, decryption, migration) is returned rather than
+    /// letting a finalized pack through. A supplied token is always checked.
+    pub async fn delete_pack_confirmed(
+        &self,
+        identifier: &str,
+        confirm_token: Option<&str>,
+    ) -> Result<bool> {
+        let pack_id = PackId::parse(identifier)?;
+        let current = match self.repo.get_by_id(&pack_id).await {
+            Ok(pack) => pack,
+            Err(DomainError::Deserialize(_) | DomainError::InvalidData(_)) => None,
+            Err(err) => return Err(err),
+        };
+        match confirm_token {
+            Some(token) => self.redeem_delete_token(token, &pack_id, current.as_ref())?,
+            None => {
+                if let Some(pack) = current.as_ref().filter(|p| p.status == Status::Finalized) {
+                    return Err(DomainError::DetailedInvalidData {
+                        message: format!(
+                            "deleting finalized pack '{}' requires 'confirm_token' from input prepare_delete",
+                            pack.id
+                        ),
+                        details: serde_json::json!({
+                            "action": "delete",
+                            "id": pack.id.as_str(),
+                            "status": "finalized",
+                            "required_field": "confirm_token",
+                            "prepare_action": "prepare_delete",
+                        }),
+                    });
+                }
+            }
+        }
+        self.repo.delete_pack_file(&pack_id).await
+    }
+
+    fn redeem_delete_token(
+        &self,
+        token: &str,
+        pack_id: &PackId,
+        current: Option<&Pack>,
+    ) -> Result<()> {
+        let issued = self
+            .delete_confirmations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(token);
+        let reason = match issued {
+            None => Some("unknown_or_used"),
+            Some(issued) if issued.expires_at <= Utc::now() => Some("expired"),
+            Some(issued) if issued.pack_id != pack_id.as_str() => Some("issued_for_another_pack"),
+            Some(issued) if current.is_some_and(|p| p.revision != issued.revision) => {
+                Some("pack_changed_since_prepare")
+            }
+            Some(_) => None,
+        };
+        match reason {
+            None => Ok(()),
+            Some(reason) => Err(DomainError::DetailedInvalidData {
+                message: format!(
+                    "confirm_token rejected ({}); call input prepare_delete again",
+                    reason
+                ),
+                details: serde_json::json!({
+                    "action": "delete",
+                    "id": pack_id.as_str(),
+                    "field": "confirm_token",
+                    "reason": reason,
+                    "prepare_action": "prepare_delete",
+                }),
+            }),
+        }
+    }
 
     // ── pack lifecycle ────────────────────────────────────────────────────────
 
@@ -369,10 +1485,36 @@ impl InputUseCases {
         ))
     }
 
-    pub async fn write_snapshot(&self, request: WriteSnapshotRequest) -> Result<Pack> {
-        match request.identifier {
+    /// Resolves the target and builds the post-write pack without validating
+    /// finalize state or persisting. Returns `(current, next)`; `current` is
+    /// `None` for creates.
+    async fn build_snapshot(&self, request: WriteSnapshotRequest) -> Result<(Option<Pack>, Pack)> {
+        let (current, mut pack) = self
+            .build_snapshot_pack(
+                request.identifier,
+                request.expected_revision,
+                request.document,
+            )
+            .await?;
+        self.validate_source_roots(current.as_ref(), &pack)?;
+        self.excerpt_limits.validate(&pack)?;
+        self.refresh_symbol_ranges(&mut pack).await?;
+        self.stamp_content_hashes(&mut pack, None).await?;
+        if request.snapshot_excerpts {
+            self.capture_excerpt_snapshots(&mut pack).await?;
+        }
+        Ok((current, pack))
+    }
+
+    async fn build_snapshot_pack(
+        &self,
+        identifier: Option<String>,
+        expected_revision: Option<u64>,
+        document: SnapshotDocument,
+    ) -> Result<(Option<Pack>, Pack)> {
+        match identifier {
             Some(identifier) => {
-                let expected_revision = request.expected_revision.ok_or_else(|| {
+                let expected_revision = expected_revision.ok_or_else(|| {
                     DomainError::InvalidData(
                         "expected_revision is required when updating by identifier".into(),
                     )
@@ -384,34 +1526,172 @@ impl InputUseCases {
                         current_revision: current.revision,
                         last_updated_at: current.updated_at.to_rfc3339(),
                         changed_section_keys: conflict_changed_section_keys(&current),
-                        guidance: revision_conflict_guidance(current.revision),
+                        guidance: revision_conflict_guidance(
+                            current.revision,
+                            current.current_write_reason(),
+                        ),
+                        produced_by: current.current_write_reason().map(str::to_string),
                     });
                 }
-
-                let pack = Self::build_update_snapshot(&current, request.document)?;
-                self.validate_finalize_state_if_needed(&pack).await?;
-                if !request.validate_only {
-                    self.repo
-                        .save_with_expected_revision(&pack, expected_revision)
-                        .await?;
-                }
-                Ok(pack)
+                let pack = Self::build_update_snapshot(&current, document, &self.diagram_limits)?;
+                Ok((Some(current), pack))
             }
             None => {
-                if request.expected_revision.is_some() {
+                if expected_revision.is_some() {
                     return Err(DomainError::InvalidData(
                         "expected_revision is only valid when identifier is set".into(),
                     ));
                 }
+                let (ttl_minutes, _) = self.create_ttl(&document)?;
+                let pack =
+                    Self::build_create_snapshot(document, &self.diagram_limits, ttl_minutes)?;
+                Ok((None, pack))
+            }
+        }
+    }
 
-                let pack = Self::build_create_snapshot(request.document)?;
-                self.validate_finalize_state_if_needed(&pack).await?;
-                if !request.validate_only {
-                    self.repo.create_new(&pack).await?;
+    pub async fn write_snapshot(&self, request: WriteSnapshotRequest) -> Result<Pack> {
+        let expected_revision = request.expected_revision;
+        let validate_only = request.validate_only;
+        let reason = request.reason.clone();
+        let owner = request.owner.clone();
+        let (current, mut pack) = self.build_snapshot(request).await?;
+        pack.set_write_reason(reason.as_deref(), chrono::Utc::now())?;
+        if current.is_none() {
+            pack.set_owner(owner.as_deref())?;
+        }
+        self.validate_finalize_state_if_needed(current.as_ref(), &pack)
+            .await?;
+        if !validate_only {
+            match (current, expected_revision) {
+                (Some(_), Some(expected_revision)) => {
+                    self.repo
+                        .save_with_expected_revision(&pack, expected_revision)
+                        .await?
                 }
-                Ok(pack)
+                _ => self.repo.create_new(&pack).await?,
             }
         }
+        Ok(pack)
+    }
+
+    /// Applies `ops` in order to one draft pack and saves the result as a
+    /// single revision. Any failing op fails the whole write (its error names
+    /// the op as `ops[i]`) and nothing is saved. A result that ends finalized
+    /// passes the same checks as a finalizing snapshot write, also with
+    /// `validate_only`.
+    pub async fn apply_ops_checked(&self, request: ApplyOpsRequest) -> Result<Pack> {
+        if request.ops.is_empty() {
+            return Err(DomainError::InvalidData(
+                "'ops' must contain at least one op".into(),
+            ));
+        }
+        if request.ops.len() > WriteOp::MAX_PER_WRITE {
+            return Err(DomainError::InvalidData(format!(
+                "'ops' must contain at most {} ops, got {}",
+                WriteOp::MAX_PER_WRITE,
+                request.ops.len()
+            )));
+        }
+        let current = self
+            .resolve_for_update(&request.identifier, request.expected_revision)
+            .await?;
+        let mut pack = current.clone();
+        for (index, op) in request.ops.into_iter().enumerate() {
+            let name = op.name();
+            self.apply_op(&mut pack, op)
+                .await
+                .map_err(|err| op_error(index, name, err))?;
+        }
+        pack.squash_revisions_since(&current);
+        self.excerpt_limits.validate(&pack)?;
+        pack.set_write_reason(request.reason.as_deref(), chrono::Utc::now())?;
+        self.validate_finalize_state_if_needed(Some(&current), &pack)
+            .await?;
+        if !request.validate_only {
+            self.repo
+                .save_with_expected_revision(&pack, request.expected_revision)
+                .await?;
+        }
+        Ok(pack)
+    }
+
+    async fn apply_op(&self, pack: &mut Pack, op: WriteOp) -> Result<()> {
+        match op {
+            WriteOp::UpsertSection {
+                section_key,
+                title,
+                description,
+                order,
+            } => pack.upsert_section(SectionKey::new(&section_key)?, title, description, order),
+            WriteOp::DeleteSection { section_key } => {
+                pack.delete_section(&SectionKey::new(&section_key)?)
+            }
+            WriteOp::UpsertRef(request) => {
+                let (section_key, ref_key) = self.apply_upsert_ref(pack, request).await?;
+                self.stamp_content_hashes(pack, Some((&section_key, &ref_key)))
+                    .await
+            }
+            WriteOp::DeleteRef {
+                section_key,
+                ref_key,
+            } => pack.delete_ref(&SectionKey::new(&section_key)?, &RefKey::new(&ref_key)?),
+            WriteOp::SetMeta { title, brief, tags } => pack.set_meta(title, brief, tags),
+            WriteOp::SetStatus { status } => {
+                pack.set_status_with(status, &self.finalize_checklist, &self.section_templates)
+            }
+        }
+    }
+
+    /// Sizes a planned snapshot write without persisting it: the encoded
+    /// post-write pack against the store's size cap, plus the per-pack count
+    /// and diagram limits. Finalize checks are skipped; use `validate_only`
+    /// for those.
+    pub async fn estimate_snapshot(&self, request: WriteSnapshotRequest) -> Result<WriteEstimate> {
+        let (current, pack) = self.build_snapshot(request).await?;
+        // Same compact encoding the JSON store writes.
+        let pack_bytes = serde_json::to_string(&pack)?.len();
+        let current_pack_bytes = current
+            .as_ref()
+            .map(|p| serde_json::to_string(p).map(|encoded| encoded.len()))
+            .transpose()?;
+
+        let mut limits = Vec::new();
+        if let Some(max) = self.repo.max_pack_bytes() {
+            limits.push(LimitDistance::new("max_pack_bytes", pack_bytes, max));
+        }
+        let entry_points = pack
+            .sections
+            .iter()
+            .flat_map(|s| &s.refs)
+            .filter(|r| r.entry_point)
+            .count();
+        limits.push(LimitDistance::new(
+            "entry_point_refs",
+            entry_points,
+            Pack::MAX_ENTRY_POINT_REFS,
+        ));
+        let largest_diagram = pack
+            .sections
+            .iter()
+            .flat_map(|s| &s.diagrams)
+            .map(|d| d.mermaid.len())
+            .max()
+            .unwrap_or(0);
+        limits.push(LimitDistance::new(
+            "diagram_max_bytes",
+            largest_diagram,
+            self.diagram_limits.max_bytes,
+        ));
+
+        Ok(WriteEstimate {
+            pack_id: current.is_some().then(|| pack.id.as_str().to_string()),
+            pack_bytes,
+            current_pack_bytes,
+            delta_bytes: current_pack_bytes.map(|before| pack_bytes as i64 - before as i64),
+            fits: limits.iter().all(|l| l.remaining >= 0),
+            limits,
+        })
     }
 
     pub async fn set_status_checked(
@@ -425,11 +1705,17 @@ impl InputUseCases {
             .await?;
 
         if status == Status::Finalized {
-            pack.validate_finalize_gate()?;
+            pack.validate_finalize_gate_with(&self.finalize_checklist, &self.section_templates)?;
+            if pack.status != Status::Finalized {
+                self.sign_off_policy.check(&pack.current_sign_offs())?;
+            }
             self.validate_refs_resolvable_before_finalize(&pack).await?;
+            self.validate_links_resolvable_before_finalize(&pack)
+                .await?;
+            self.validate_rules_before_finalize(&pack).await?;
         }
 
-        pack.set_status(status)?;
+        pack.set_status_with(status, &self.finalize_checklist, &self.section_templates)?;
         self.repo
             .save_with_expected_revision(&pack, expected_revision)
             .await?;
@@ -454,6 +1740,23 @@ impl InputUseCases {
         Ok(pack)
     }
 
+    pub async fn set_read_defaults_checked(
+        &self,
+        identifier: &str,
+        defaults: ReadDefaults,
+        expected_revision: u64,
+    ) -> Result<Pack> {
+        let mut pack = self
+            .resolve_for_update(identifier, expected_revision)
+            .await?;
+        pack.set_read_defaults(defaults)?;
+        self.excerpt_limits.validate(&pack)?;
+        self.repo
+            .save_with_expected_revision(&pack, expected_revision)
+            .await?;
+        Ok(pack)
+    }
+
     // ── section management ────────────────────────────────────────────────────
 
     pub async fn upsert_section_checked(
@@ -493,6 +1796,175 @@ impl InputUseCases {
         Ok(pack)
     }
 
+    /// Moves a section with its refs and diagrams from one pack to another.
+    ///
+    /// Both packs are checked against their expected revisions. The target is
+    /// saved first, then the source; if the source save fails the target is
+    /// restored to its previous content (as a new revision), so a section is
+    /// never lost and never left in both packs.
+    pub async fn move_section_checked(&self, request: MoveSectionRequest) -> Result<MovedSection> {
+        let mut source = self
+            .resolve_for_update(&request.from, request.from_expected_revision)
+            .await?;
+        let mut target = self
+            .resolve_for_update(&request.to, request.to_expected_revision)
+            .await?;
+        if source.id == target.id {
+            return Err(DomainError::InvalidData(
+                "move_section needs two different packs; rename sections with write".into(),
+            ));
+        }
+        let from_key = SectionKey::new(&request.section_key)?;
+        let target_key = request
+            .target_section_key
+            .as_deref()
+            .map(SectionKey::new)
+            .transpose()?;
+        let target_before = target.clone();
+
+        let section = source.take_section(&from_key)?;
+        let section_key = target.adopt_section(section, target_key)?;
+        let now = chrono::Utc::now();
+        source.set_write_reason(request.reason.as_deref(), now)?;
+        target.set_write_reason(request.reason.as_deref(), now)?;
+
+        self.repo
+            .save_with_expected_revision(&target, request.to_expected_revision)
+            .await?;
+        if let Err(err) = self
+            .repo
+            .save_with_expected_revision(&source, request.from_expected_revision)
+            .await
+        {
+            let mut restored = target_before;
+            restored.revision = target.revision.saturating_add(1);
+            restored.updated_at = chrono::Utc::now();
+            if let Err(rollback) = self
+                .repo
+                .save_with_expected_revision(&restored, target.revision)
+                .await
+            {
+                return Err(DomainError::Conflict(format!(
+                    "section '{}' was copied to pack {} but removing it from pack {} failed ({}) and the rollback failed too ({}); delete it from one pack by hand",
+                    section_key, target.id, source.id, err, rollback
+                )));
+            }
+            return Err(err);
+        }
+
+        Ok(MovedSection {
+            renamed: section_key != from_key,
+            section_key: section_key.as_str().to_string(),
+            source,
+            target,
+        })
+    }
+
+    /// Copies every section of `source` (with refs, diagrams, attachments
+    /// and snapshots) into `target` as one new revision; colliding keys
+    /// follow `policy`. The source pack is not changed.
+    pub async fn merge_packs_checked(&self, request: MergePacksRequest) -> Result<MergedPacks> {
+        let source = self.resolve(&request.source).await?;
+        let mut target = self
+            .resolve_for_update(&request.target, request.target_expected_revision)
+            .await?;
+        if source.id == target.id {
+            return Err(DomainError::InvalidData(
+                "merge needs two different packs".into(),
+            ));
+        }
+        if source.sections.is_empty() {
+            return Err(DomainError::InvalidData(format!(
+                "pack {} has no sections to merge",
+                source.id
+            )));
+        }
+        let sections = target.merge_sections(source.sections.clone(), request.policy)?;
+        self.excerpt_limits.validate(&target)?;
+        let reason = request
+            .reason
+            .unwrap_or_else(|| format!("merge of {} revision {}", source.id, source.revision));
+        target.set_write_reason(Some(&reason), chrono::Utc::now())?;
+        self.repo
+            .save_with_expected_revision(&target, request.target_expected_revision)
+            .await?;
+        Ok(MergedPacks {
+            target,
+            source_id: source.id,
+            source_revision: source.revision,
+            sections,
+        })
+    }
+
+    /// Moves `section_keys` out of a draft pack into a new draft pack that
+    /// records the original as `split_from` and inherits its tags and expiry.
+    ///
+    /// Both packs are stored as one change through
+    /// [`PackRepositoryPort::create_with_update`], so a failed save of the
+    /// shrunken parent leaves no new pack behind.
+    pub async fn split_pack_checked(&self, request: SplitPackRequest) -> Result<SplitPack> {
+        let mut parent = self
+            .resolve_for_update(&request.identifier, request.expected_revision)
+            .await?;
+        if request.section_keys.is_empty() {
+            return Err(DomainError::InvalidData(
+                "split requires at least one section key".into(),
+            ));
+        }
+        let keys = request
+            .section_keys
+            .iter()
+            .map(|key| SectionKey::new(key))
+            .collect::<Result<Vec<_>>>()?;
+        if keys.len() == parent.sections.len()
+            && parent.sections.iter().all(|s| keys.contains(&s.key))
+        {
+            return Err(DomainError::InvalidData(
+                "split would leave the original pack empty; keep at least one section".into(),
+            ));
+        }
+        let mut moved = Vec::with_capacity(keys.len());
+        for key in &keys {
+            moved.push(parent.take_section(key)?);
+        }
+        parent.set_write_reason(request.reason.as_deref(), chrono::Utc::now())?;
+        let name = request.name.as_deref().map(PackName::new).transpose()?;
+        let title = request.title.or_else(|| {
+            parent
+                .title
+                .as_ref()
+                .map(|title| format!("{} (split)", title))
+        });
+
+        for _ in 0..8 {
+            let mut child = Pack::new(PackId::new(), name.clone());
+            child.title = title.clone();
+            child.tags = parent.tags.clone();
+            child.expires_at = parent.expires_at;
+            child.pinned = parent.pinned;
+            child.sections = moved.clone();
+            child.split_from = Some(parent.id.clone());
+            child.carry_change_revisions(None);
+            child.set_write_reason(request.reason.as_deref(), chrono::Utc::now())?;
+            child.set_owner(request.owner.as_deref())?;
+            child.validate_entry_points()?;
+
+            match self
+                .repo
+                .create_with_update(&child, &parent, request.expected_revision)
+                .await
+            {
+                Ok(()) => return Ok(SplitPack { parent, child }),
+                Err(DomainError::PackIdConflict(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(DomainError::Conflict(
+            "failed to allocate unique pack id".into(),
+        ))
+    }
+
     // ── ref management ────────────────────────────────────────────────────────
 
     pub async fn upsert_ref_checked(
@@ -504,22 +1976,83 @@ impl InputUseCases {
         let mut pack = self
             .resolve_for_update(identifier, expected_revision)
             .await?;
+        let (section_key, ref_key) = self.apply_upsert_ref(&mut pack, request).await?;
+        self.excerpt_limits.validate(&pack)?;
+        self.stamp_content_hashes(&mut pack, Some((&section_key, &ref_key)))
+            .await?;
+        self.repo
+            .save_with_expected_revision(&pack, expected_revision)
+            .await?;
+        Ok(pack)
+    }
+
+    /// Resolves `request` (source root, url, symbol range) and upserts the
+    /// ref into `pack`; returns its section and ref keys.
+    async fn apply_upsert_ref(
+        &self,
+        pack: &mut Pack,
+        request: UpsertRefRequest,
+    ) -> Result<(SectionKey, RefKey)> {
         let section_key = SectionKey::new(&request.section_key)?;
+        let ref_key = RefKey::new(&request.ref_key)?;
+        let root = request
+            .root
+            .as_deref()
+            .map(SourceRootName::new)
+            .transpose()?;
+        if let Some(root) = &root {
+            self.check_source_root(root, &section_key, &ref_key)?;
+        }
+        let url = request.url.as_deref().map(ExternalUrl::new).transpose()?;
+        let section_ref = Self::parse_section_ref(url.as_ref(), &request.path)?;
+        let path = match &url {
+            Some(url) => url.locator(),
+            None => RelativePath::new(&request.path)?,
+        };
+        let symbol = request.symbol.as_deref().map(SymbolName::new).transpose()?;
+        let lines = match &symbol {
+            _ if url.is_some() || section_ref.is_some() => LineRange::new(1, 1)?,
+            Some(symbol) => {
+                let git_sha = pack
+                    .sections
+                    .iter()
+                    .find(|section| section.key == section_key)
+                    .and_then(|section| section.refs.iter().find(|r| r.key == ref_key))
+                    .and_then(|existing| existing.git_sha.clone());
+                self.excerpt
+                    .resolve_symbol_in(root.as_ref(), &path, symbol, git_sha.as_ref())
+                    .await
+                    .map_err(|e| match e {
+                        DomainError::StaleRef(reason) => DomainError::DetailedInvalidData {
+                            message: reason,
+                            details: serde_json::json!({
+                                "field": "symbol",
+                                "symbol": symbol.as_str(),
+                                "path": path.as_str(),
+                            }),
+                        },
+                        other => other,
+                    })?
+            }
+            None => LineRange::new(request.line_start, request.line_end)?,
+        };
         pack.upsert_ref(
             &section_key,
             RefSpec {
-                key: RefKey::new(&request.ref_key)?,
-                path: RelativePath::new(&request.path)?,
-                lines: LineRange::new(request.line_start, request.line_end)?,
+                key: ref_key.clone(),
+                root,
+                path,
+                lines,
                 title: request.title,
                 why: request.why,
                 group: request.group,
+                symbol,
+                url,
+                section_ref,
+                excerpt_line_limit: request.excerpt_line_limit,
             },
         )?;
-        self.repo
-            .save_with_expected_revision(&pack, expected_revision)
-            .await?;
-        Ok(pack)
+        Ok((section_key, ref_key))
     }
 
     pub async fn delete_ref_checked(
@@ -550,9 +2083,13 @@ impl InputUseCases {
         let mut pack = self
             .resolve_for_update(identifier, expected_revision)
             .await?;
+        let section_key = SectionKey::new(&request.section_key)?;
+        let diagram_key = DiagramKey::new(&request.diagram_key)?;
+        self.diagram_limits
+            .validate(&section_key, &diagram_key, &request.mermaid)?;
         pack.upsert_diagram(
-            &SectionKey::new(&request.section_key)?,
-            DiagramKey::new(&request.diagram_key)?,
+            &section_key,
+            diagram_key,
             request.title,
             request.mermaid,
             request.why,
@@ -563,23 +2100,636 @@ impl InputUseCases {
         Ok(pack)
     }
 
+    // ── attachment management ─────────────────────────────────────────────────
+
+    pub async fn upsert_attachment_checked(
+        &self,
+        request: UpsertAttachmentRequest,
+    ) -> Result<Pack> {
+        let mut pack = self
+            .resolve_for_update(&request.identifier, request.expected_revision)
+            .await?;
+        let now = chrono::Utc::now();
+        let section_key = SectionKey::new(&request.section_key)?;
+        let attachment = Attachment::new(
+            &section_key,
+            AttachmentKey::new(&request.attachment_key)?,
+            request.title,
+            request.media_type.as_deref(),
+            request.content,
+            self.attachment_max_bytes,
+            now,
+        )?;
+        pack.upsert_attachment(&section_key, attachment)?;
+        pack.set_write_reason(request.reason.as_deref(), now)?;
+        self.repo
+            .save_with_expected_revision(&pack, request.expected_revision)
+            .await?;
+        Ok(pack)
+    }
+
+    pub async fn delete_attachment_checked(
+        &self,
+        request: DeleteAttachmentRequest,
+    ) -> Result<Pack> {
+        let mut pack = self
+            .resolve_for_update(&request.identifier, request.expected_revision)
+            .await?;
+        pack.delete_attachment(
+            &SectionKey::new(&request.section_key)?,
+            &AttachmentKey::new(&request.attachment_key)?,
+        )?;
+        pack.set_write_reason(request.reason.as_deref(), chrono::Utc::now())?;
+        self.repo
+            .save_with_expected_revision(&pack, request.expected_revision)
+            .await?;
+        Ok(pack)
+    }
+
+    pub async fn diagram_history(
+        &self,
+        identifier: &str,
+        section_key: &str,
+        diagram_key: &str,
+    ) -> Result<DiagramHistory> {
+        let pack = self.resolve(identifier).await?;
+        let section_key = SectionKey::new(section_key)?;
+        let diagram_key = DiagramKey::new(diagram_key)?;
+        let section = pack
+            .sections
+            .iter()
+            .find(|s| s.key == section_key)
+            .ok_or_else(|| {
+                DomainError::NotFound(format!(
+                    "section '{}' not found in pack {}",
+                    section_key, pack.id
+                ))
+            })?;
+        let diagram = section
+            .diagrams
+            .iter()
+            .find(|d| d.key == diagram_key)
+            .ok_or_else(|| {
+                DomainError::NotFound(format!(
+                    "diagram '{}' not found in section '{}'",
+                    diagram_key, section_key
+                ))
+            })?;
+
+        let mut versions: Vec<DiagramHistoryEntry> = diagram
+            .history
+            .iter()
+            .enumerate()
+            .map(|(idx, past)| DiagramHistoryEntry {
+                version: idx + 1,
+                revision: past.revision,
+                current: false,
+                title: past.title.clone(),
+                mermaid: past.mermaid.clone(),
+            })
+            .collect();
+        versions.push(DiagramHistoryEntry {
+            version: versions.len() + 1,
+            revision: pack.revision,
+            current: true,
+            title: diagram.title.clone(),
+            mermaid: diagram.mermaid.clone(),
+        });
+
+        Ok(DiagramHistory {
+            pack_id: pack.id.as_str().to_string(),
+            section_key: section_key.as_str().to_string(),
+            diagram_key: diagram_key.as_str().to_string(),
+            versions,
+        })
+    }
+
+    pub async fn sign_off_checked(&self, request: SignOffRequest) -> Result<Pack> {
+        let mut pack = self
+            .resolve_for_update(&request.identifier, request.expected_revision)
+            .await?;
+        let now = chrono::Utc::now();
+        pack.record_sign_off(
+            &request.reviewer,
+            request.verdict,
+            request.role.as_deref(),
+            request.comment,
+            now,
+        )?;
+        pack.set_write_reason(request.reason.as_deref(), now)?;
+        self.repo
+            .save_with_expected_revision(&pack, request.expected_revision)
+            .await?;
+        Ok(pack)
+    }
+
+    pub async fn set_links_checked(&self, request: SetLinksRequest) -> Result<Pack> {
+        let mut pack = self
+            .resolve_for_update(&request.identifier, request.expected_revision)
+            .await?;
+        pack.set_links(request.links)?;
+        pack.set_write_reason(request.reason.as_deref(), chrono::Utc::now())?;
+        self.repo
+            .save_with_expected_revision(&pack, request.expected_revision)
+            .await?;
+        Ok(pack)
+    }
+
+    pub async fn add_link_checked(&self, request: AddLinkRequest) -> Result<Pack> {
+        let mut pack = self
+            .resolve_for_update(&request.identifier, request.expected_revision)
+            .await?;
+        pack.add_link(request.link)?;
+        pack.set_write_reason(request.reason.as_deref(), chrono::Utc::now())?;
+        self.repo
+            .save_with_expected_revision(&pack, request.expected_revision)
+            .await?;
+        Ok(pack)
+    }
+
+    /// Restores the content (title, brief, tags, sections, read defaults) of a
+    /// journaled prior revision as a new revision. Identity, status, ttl and
+    /// sign-offs stay as they are now; finalized packs go back to draft first.
+    pub async fn rollback_checked(&self, request: RollbackRequest) -> Result<Pack> {
+        let current = self
+            .resolve_for_update(&request.identifier, request.expected_revision)
+            .await?;
+        current.assert_mutable()?;
+        if request.to_revision >= current.revision {
+            return Err(DomainError::InvalidData(format!(
+                "'to_revision' must be below the current revision {} (got {})",
+                current.revision, request.to_revision
+            )));
+        }
+        let historic = resolve_revision(self.repo.as_ref(), &current, request.to_revision).await?;
+
+        let now = chrono::Utc::now();
+        let mut pack = Pack {
+            title: historic.title,
+            brief: historic.brief,
+            tags: historic.tags,
+            sections: historic.sections,
+            read_defaults: historic.read_defaults,
+            revision: current.revision.saturating_add(1),
+            updated_at: now,
+            ..current.clone()
+        };
+        pack.carry_change_revisions(Some(&current));
+        let default_reason = format!("rollback to revision {}", request.to_revision);
+        pack.set_write_reason(
+            Some(request.reason.as_deref().unwrap_or(&default_reason)),
+            now,
+        )?;
+        self.repo
+            .save_with_expected_revision(&pack, request.expected_revision)
+            .await?;
+        Ok(pack)
+    }
+
+    /// Re-anchors refs whose lines no longer resolve or no longer hash to
+    /// what was recorded at upsert, by searching the file for the ref's
+    /// snapshot (fuzzy, see [`find_excerpt`]) or, without one, its content
+    /// hash. Symbol refs re-resolve on their own and url refs have no lines,
+    /// so both are left alone.
+    pub async fn repair_refs_checked(
+        &self,
+        request: RepairRefsRequest,
+    ) -> Result<RepairRefsReport> {
+        let mut pack = self
+            .resolve_for_update(&request.identifier, request.expected_revision)
+            .await?;
+        let mut repaired = Vec::new();
+        let mut unresolved = Vec::new();
+        for section in &pack.sections {
+            for code_ref in section
+                .refs
+                .iter()
+                .filter(|r| r.symbol.is_none() && r.reads_source() && !r.path.is_pattern())
+            {
+                let unrepaired = |reason: String| UnrepairedRef {
+                    section_key: section.key.as_str().to_string(),
+                    ref_key: code_ref.key.as_str().to_string(),
+                    path: code_ref.path.as_str().to_string(),
+                    reason,
+                };
+                match self.excerpt.read_ref(code_ref).await {
+                    Ok(snippet) if !Self::needs_reanchor(code_ref, &snippet.body) => continue,
+                    Ok(_) | Err(DomainError::StaleRef(_)) => {}
+                    Err(e) => return Err(e),
+                }
+                match self.reanchor(code_ref).await? {
+                    Ok((anchor, matched_by)) => repaired.push(RefRepair {
+                        section_key: section.key.as_str().to_string(),
+                        ref_key: code_ref.key.as_str().to_string(),
+                        path: code_ref.path.as_str().to_string(),
+                        from: code_ref.lines,
+                        to: LineRange::new(anchor.start, anchor.end)?,
+                        score: anchor.score,
+                        matched_by,
+                    }),
+                    Err(reason) => unresolved.push(unrepaired(reason)),
+                }
+            }
+        }
+        if request.validate_only || repaired.is_empty() {
+            return Ok(RepairRefsReport {
+                pack,
+                repaired,
+                unresolved,
+                applied: false,
+            });
+        }
+
+        let moves: Vec<_> = repaired
+            .iter()
+            .map(|repair| {
+                Ok((
+                    SectionKey::new(&repair.section_key)?,
+                    RefKey::new(&repair.ref_key)?,
+                    repair.to,
+                ))
+            })
+            .collect::<Result<_>>()?;
+        pack.reanchor_refs(&moves)?;
+        self.stamp_content_hashes(&mut pack, None).await?;
+        let default_reason = format!("repair_refs: {} ref(s) re-anchored", repaired.len());
+        pack.set_write_reason(
+            Some(request.reason.as_deref().unwrap_or(&default_reason)),
+            chrono::Utc::now(),
+        )?;
+        self.repo
+            .save_with_expected_revision(&pack, request.expected_revision)
+            .await?;
+        Ok(RepairRefsReport {
+            pack,
+            repaired,
+            unresolved,
+            applied: true,
+        })
+    }
+
+    /// A readable ref needs re-anchoring when its text is no longer what was
+    /// recorded: the content hash drifted, or it differs from its snapshot.
+    fn needs_reanchor(code_ref: &CodeRef, body: &str) -> bool {
+        if code_ref.content_hash.is_some() {
+            return code_ref.content_drift(body).is_some();
+        }
+        code_ref
+            .snapshot
+            .as_ref()
+            .is_some_and(|snapshot| excerpt_lines(&snapshot.body) != excerpt_lines(body))
+    }
+
+    /// Where `code_ref`'s recorded excerpt is in the file now, and what was
+    /// matched; the inner `Err` says why it could not be found.
+    async fn reanchor(
+        &self,
+        code_ref: &CodeRef,
+    ) -> Result<std::result::Result<(Anchor, &'static str), String>> {
+        let root = code_ref.root.as_ref();
+        let git_sha = code_ref.git_sha.as_ref();
+        let probe = match self
+            .excerpt
+            .read_lines_in(root, &code_ref.path, LineRange::new(1, 1)?, git_sha)
+            .await
+        {
+            Ok(probe) => probe,
+            Err(DomainError::StaleRef(reason)) => return Ok(Err(reason)),
+            Err(e) => return Err(e),
+        };
+        let whole = self
+            .excerpt
+            .read_lines_in(
+                root,
+                &code_ref.path,
+                LineRange::new(1, probe.total_lines)?,
+                git_sha,
+            )
+            .await?;
+        let file = excerpt_lines(&whole.body);
+        let near = code_ref.lines.start;
+        let found = if let Some(snapshot) = &code_ref.snapshot {
+            find_excerpt(&file, &excerpt_lines(&snapshot.body), near).map(|a| (a, "snapshot"))
+        } else if let Some(hash) = &code_ref.content_hash {
+            let len = code_ref.lines.end - code_ref.lines.start + 1;
+            find_by_hash(&file, len, hash, near).map(|a| (a, "content_hash"))
+        } else {
+            return Ok(Err(
+                "no snapshot or content hash was recorded to search for".to_string(),
+            ));
+        };
+        Ok(match found {
+            Some((anchor, _))
+                if (anchor.start, anchor.end) == (code_ref.lines.start, code_ref.lines.end) =>
+            {
+                Err(
+                    "the best match is the current range: the lines were edited, not moved"
+                        .to_string(),
+                )
+            }
+            Some(found) => Ok(found),
+            None => Err(format!(
+                "no range in '{}' matches the recorded excerpt",
+                code_ref.path
+            )),
+        })
+    }
+
+    /// Bundles a pack for `import` into another store. With `inline_excerpts`
+    /// every ref's snapshot is refreshed from the source tree first, so the
+    /// bundle reads the same without it.
+    pub async fn export_bundle(
+        &self,
+        identifier: &str,
+        inline_excerpts: bool,
+    ) -> Result<PackBundle> {
+        let mut pack = self.resolve(identifier).await?;
+        if inline_excerpts {
+            self.capture_excerpt_snapshots(&mut pack).await?;
+        }
+        Ok(PackBundle::new(pack, inline_excerpts, chrono::Utc::now()))
+    }
+
+    /// Recreates a bundled pack under a new id as a draft at revision 1. Its
+    /// content is validated like a write; excerpt snapshots, diagram history
+    /// and attachments are kept, sign-offs are not (they name the source's revisions).
+    pub async fn import_bundle(&self, request: ImportBundleRequest) -> Result<Pack> {
+        let source = request.bundle.into_pack()?;
+        let name = match request.name.as_deref() {
+            Some(name) => Some(PackName::new(name)?),
+            None => source
+                .name
+                .as_ref()
+                .map(|name| PackName::new(name.as_str()))
+                .transpose()?,
+        };
+        let default_reason = format!("import of {} revision {}", source.id, source.revision);
+        let reason = request.reason.as_deref().unwrap_or(&default_reason);
+        self.create_copy(
+            &source,
+            name,
+            request.ttl_minutes,
+            reason,
+            request.owner.as_deref(),
+        )
+        .await
+    }
+
+    /// Copies a stored pack, or some of its sections, into a new draft pack
+    /// with its own id and TTL. Excerpt snapshots, content hashes, diagram
+    /// history and attachments come along; sign-offs don't.
+    pub async fn clone_pack(&self, request: ClonePackRequest) -> Result<ClonedPack> {
+        let mut source = self.resolve(&request.identifier).await?;
+        if let Some(section_keys) = &request.section_keys {
+            if section_keys.is_empty() {
+                return Err(DomainError::InvalidData(
+                    "clone needs at least one section key when 'section_keys' is given".into(),
+                ));
+            }
+            let keys = section_keys
+                .iter()
+                .map(|key| SectionKey::new(key))
+                .collect::<Result<Vec<_>>>()?;
+            if let Some(missing) = keys
+                .iter()
+                .find(|key| !source.sections.iter().any(|s| s.key == **key))
+            {
+                return Err(DomainError::NotFound(format!(
+                    "section '{}' not found",
+                    missing
+                )));
+            }
+            source
+                .sections
+                .retain(|section| keys.contains(&section.key));
+        }
+        let name = request.name.as_deref().map(PackName::new).transpose()?;
+        let default_reason = format!("clone of {} revision {}", source.id, source.revision);
+        let reason = request.reason.as_deref().unwrap_or(&default_reason);
+        let pack = self
+            .create_copy(
+                &source,
+                name,
+                request.ttl_minutes,
+                reason,
+                request.owner.as_deref(),
+            )
+            .await?;
+        Ok(ClonedPack {
+            pack,
+            source_id: source.id,
+            source_revision: source.revision,
+        })
+    }
+
+    /// Creates a draft at revision 1 holding `source`'s authored content,
+    /// re-validated like a write, plus its snapshots, hashes, diagram history
+    /// and attachments. The copy belongs to `owner`, not the source's owner.
+    async fn create_copy(
+        &self,
+        source: &Pack,
+        name: Option<PackName>,
+        ttl_minutes: Option<u64>,
+        reason: &str,
+        owner: Option<&str>,
+    ) -> Result<Pack> {
+        let mut sections = Self::snapshot_sections(
+            &Self::authored_sections(&source.sections),
+            &self.diagram_limits,
+        )?;
+        for (section, original) in sections.iter_mut().zip(&source.sections) {
+            for (code_ref, original_ref) in section.refs.iter_mut().zip(&original.refs) {
+                code_ref.snapshot = original_ref.snapshot.clone();
+                code_ref.content_hash = original_ref.content_hash.clone();
+            }
+            for (diagram, original_diagram) in section.diagrams.iter_mut().zip(&original.diagrams) {
+                diagram.history = original_diagram.history.clone();
+            }
+            section.attachments = original.attachments.clone();
+        }
+        source.read_defaults.validate()?;
+        let ttl_minutes = match ttl_minutes {
+            Some(minutes) => minutes,
+            None => self.ttl_policy.resolve(name.as_ref(), &source.tags).0,
+        };
+
+        for _ in 0..8 {
+            let mut pack = Pack::new(PackId::new(), name.clone());
+            pack.set_ttl_on_create(ttl_minutes, pack.created_at)?;
+            pack.title = source.title.clone();
+            pack.brief = source.brief.clone();
+            pack.tags = source.tags.clone();
+            pack.sections = sections.clone();
+            pack.read_defaults = source.read_defaults;
+            pack.carry_change_revisions(None);
+            pack.links = source.links.clone();
+            pack.set_write_reason(Some(reason), pack.created_at)?;
+            pack.set_owner(owner)?;
+            pack.validate_entry_points()?;
+            self.excerpt_limits.validate(&pack)?;
+
+            match self.repo.create_new(&pack).await {
+                Ok(()) => return Ok(pack),
+                Err(DomainError::PackIdConflict(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(DomainError::Conflict(
+            "failed to allocate unique pack id".into(),
+        ))
+    }
+
+    /// Creates a draft pack from a markdown document (see
+    /// [`parse_markdown_document`]), validated like a `write` without
+    /// `identifier`. Also returns where the TTL came from.
+    pub async fn import_markdown(
+        &self,
+        request: ImportMarkdownRequest,
+    ) -> Result<(Pack, TtlSource)> {
+        let mut document = parse_markdown_document(&request.markdown)?;
+        document.name = request.name;
+        document.tags = request.tags;
+        document.ttl_minutes = request.ttl_minutes;
+        let (_, ttl_source) = self.create_ttl(&document)?;
+        let pack = self
+            .write_snapshot(WriteSnapshotRequest {
+                identifier: None,
+                expected_revision: None,
+                validate_only: request.validate_only,
+                snapshot_excerpts: false,
+                document,
+                reason: request.reason,
+                owner: request.owner,
+            })
+            .await?;
+        Ok((pack, ttl_source))
+    }
+
+    /// Creates a draft pack seeded with the template's sections, validated
+    /// like a `write` without `identifier`. Also returns where the TTL came
+    /// from.
+    pub async fn create_from_template(
+        &self,
+        request: CreateFromTemplateRequest,
+    ) -> Result<(Pack, TtlSource)> {
+        let document = SnapshotDocument {
+            name: request.name,
+            title: request.title,
+            brief: None,
+            tags: request.tags,
+            ttl_minutes: request.ttl_minutes,
+            status: Status::Draft,
+            sections: request
+                .template
+                .sections()
+                .iter()
+                .map(|(key, title, placeholder)| SnapshotSection {
+                    key: key.to_string(),
+                    title: title.to_string(),
+                    description: Some(placeholder.to_string()),
+                    translations: Vec::new(),
+                    refs: Vec::new(),
+                    diagrams: Vec::new(),
+                })
+                .collect(),
+            read_defaults: ReadDefaults::default(),
+        };
+        let (_, ttl_source) = self.create_ttl(&document)?;
+        let pack = self
+            .write_snapshot(WriteSnapshotRequest {
+                identifier: None,
+                expected_revision: None,
+                validate_only: request.validate_only,
+                snapshot_excerpts: false,
+                document,
+                reason: request.reason,
+                owner: request.owner,
+            })
+            .await?;
+        Ok((pack, ttl_source))
+    }
+
+    /// The authored fields of `sections`, for re-validation through
+    /// [`Self::snapshot_sections`].
+    fn authored_sections(sections: &[Section]) -> Vec<SnapshotSection> {
+        sections
+            .iter()
+            .map(|section| SnapshotSection {
+                key: section.key.as_str().to_string(),
+                title: section.title.clone(),
+                description: section.description.clone(),
+                translations: section
+                    .translations
+                    .iter()
+                    .map(|(lang, text)| (lang.as_str().to_string(), text.clone()))
+                    .collect(),
+                refs: section
+                    .refs
+                    .iter()
+                    .map(|code_ref| SnapshotRef {
+                        key: code_ref.key.as_str().to_string(),
+                        root: code_ref.root.as_ref().map(|root| root.as_str().to_string()),
+                        path: code_ref.path.as_str().to_string(),
+                        line_start: code_ref.lines.start,
+                        line_end: code_ref.lines.end,
+                        title: code_ref.title.clone(),
+                        why: code_ref.why.clone(),
+                        group: code_ref.group.clone(),
+                        entry_point: code_ref.entry_point,
+                        git_sha: code_ref
+                            .git_sha
+                            .as_ref()
+                            .map(|sha| sha.as_str().to_string()),
+                        symbol: code_ref
+                            .symbol
+                            .as_ref()
+                            .map(|symbol| symbol.as_str().to_string()),
+                        url: code_ref.url.as_ref().map(|url| url.as_str().to_string()),
+                        excerpt_line_limit: code_ref.excerpt_line_limit,
+                    })
+                    .collect(),
+                diagrams: section
+                    .diagrams
+                    .iter()
+                    .map(|diagram| SnapshotDiagram {
+                        key: diagram.key.as_str().to_string(),
+                        title: diagram.title.clone(),
+                        mermaid: diagram.mermaid.clone(),
+                        why: diagram.why.clone(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
     pub async fn touch_ttl_checked(
         &self,
         identifier: &str,
         expected_revision: u64,
         mode: TouchTtlMode,
+        reason: Option<&str>,
     ) -> Result<Pack> {
         let mut pack = self
             .resolve_for_update(identifier, expected_revision)
             .await?;
+        let now = chrono::Utc::now();
         match mode {
             TouchTtlMode::SetMinutes(minutes) => {
-                pack.set_ttl_from_now(minutes, chrono::Utc::now())?;
+                pack.set_ttl_from_now(minutes, now)?;
             }
             TouchTtlMode::ExtendMinutes(minutes) => {
-                pack.extend_ttl(minutes, chrono::Utc::now())?;
+                pack.extend_ttl(minutes, now)?;
+            }
+            TouchTtlMode::Sliding(enabled) => pack.set_sliding_ttl(enabled),
+            TouchTtlMode::SlidingMinutes(minutes) => pack.set_sliding_ttl_minutes(minutes)?,
+            TouchTtlMode::Pin(true) => pack.pin(),
+            TouchTtlMode::Pin(false) => {
+                let (minutes, _) = self.ttl_policy.resolve(pack.name.as_ref(), &pack.tags);
+                pack.unpin(minutes, now)?;
             }
         }
+        pack.set_write_reason(reason, now)?;
         self.repo
             .save_with_expected_revision(&pack, expected_revision)
             .await?;
@@ -598,3 +2748,39 @@ fn conflict_changed_section_keys(pack: &Pack) -> Vec<String> {
     keys.truncate(REVISION_CONFLICT_CHANGED_KEYS_LIMIT);
     keys
 }
+
+/// Prefixes validation and lookup errors from op `index` so the caller can
+/// tell which op of a batch failed; other errors pass through unchanged.
+fn op_error(index: usize, op: &str, err: DomainError) -> DomainError {
+    let prefix = format!("ops[{index}] ({op})");
+    match err {
+        DomainError::InvalidData(message) => DomainError::DetailedInvalidData {
+            message: format!("{prefix}: {message}"),
+            details: serde_json::json!({ "op_index": index, "op": op }),
+        },
+        DomainError::DetailedInvalidData {
+            message,
+            mut details,
+        } => {
+            if let Some(obj) = details.as_object_mut() {
+                obj.insert("op_index".into(), index.into());
+                obj.insert("op".into(), op.into());
+            }
+            DomainError::DetailedInvalidData {
+                message: format!("{prefix}: {message}"),
+                details,
+            }
+        }
+        DomainError::NotFound(message) => DomainError::NotFound(format!("{prefix}: {message}")),
+        other => other,
+    }
+}
+
+fn new_confirm_token() -> String {
+    const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+    let mut rng = rand::thread_rng();
+    let suffix: String = (0..20)
+        .map(|_| ALPHABET[rand::Rng::gen_range(&mut rng, 0..ALPHABET.len())] as char)
+        .collect();
+    format!("cf_{}", suffix)
+}
@@ -0,0 +1,202 @@
+//! Dispatch of [`PackLifecycleHook`] events. Hooks are attached once, as a
+//! decorator around the pack repository, so every writer (input actions,
+//! imports, sync pulls, TTL purges) reports through the same path.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{
+    app::ports::{FreshnessState, ListFilter, PackLifecycleHook, PackRepositoryPort},
+    domain::{
+        errors::Result,
+        models::Pack,
+        types::{PackId, PackName, Status},
+    },
+};
+
+/// Ordered set of registered hooks; they run in registration order.
+#[derive(Clone, Default)]
+pub struct LifecycleHooks {
+    hooks: Vec<Arc<dyn PackLifecycleHook>>,
+}
+
+impl LifecycleHooks {
+    pub fn register(&mut self, hook: Arc<dyn PackLifecycleHook>) {
+        self.hooks.push(hook);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.hooks.iter().map(|hook| hook.name()).collect()
+    }
+
+    async fn created(&self, pack: &Pack) {
+        for hook in &self.hooks {
+            report(hook.as_ref(), "on_create", hook.on_create(pack).await);
+        }
+        if pack.status == Status::Finalized {
+            self.finalized(pack).await;
+        }
+    }
+
+    async fn written(&self, previous: Option<&Pack>, pack: &Pack) {
+        for hook in &self.hooks {
+            report(
+                hook.as_ref(),
+                "on_write",
+                hook.on_write(previous, pack).await,
+            );
+        }
+        let was_finalized = previous.is_some_and(|p| p.status == Status::Finalized);
+        if pack.status == Status::Finalized && !was_finalized {
+            self.finalized(pack).await;
+        }
+    }
+
+    async fn finalized(&self, pack: &Pack) {
+        for hook in &self.hooks {
+            report(hook.as_ref(), "on_finalize", hook.on_finalize(pack).await);
+        }
+    }
+
+    async fn deleted(&self, id: &PackId) {
+        for hook in &self.hooks {
+            report(hook.as_ref(), "on_delete", hook.on_delete(id).await);
+        }
+    }
+}
+
+impl fmt::Debug for LifecycleHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+fn report(hook: &dyn PackLifecycleHook, event: &str, outcome: Result<()>) {
+    if let Err(e) = outcome {
+        tracing::warn!(
+            "lifecycle hook '{}' failed in {}: {}",
+            hook.name(),
+            event,
+            e
+        );
+    }
+}
+
+/// Repository decorator that runs [`LifecycleHooks`] after each successful
+/// create, save, delete and purge of the wrapped store. Saves read the
+/// stored revision first so hooks see what changed.
+pub struct HookedRepository {
+    inner: Arc<dyn PackRepositoryPort>,
+    hooks: LifecycleHooks,
+}
+
+impl HookedRepository {
+    pub fn new(inner: Arc<dyn PackRepositoryPort>, hooks: LifecycleHooks) -> Self {
+        Self { inner, hooks }
+    }
+
+    /// `inner` unchanged when no hooks are registered.
+    pub fn wrap(
+        inner: Arc<dyn PackRepositoryPort>,
+        hooks: LifecycleHooks,
+    ) -> Arc<dyn PackRepositoryPort> {
+        if hooks.is_empty() {
+            inner
+        } else {
+            Arc::new(Self::new(inner, hooks))
+        }
+    }
+}
+
+#[async_trait]
+impl PackRepositoryPort for HookedRepository {
+    async fn create_new(&self, pack: &Pack) -> Result<()> {
+        self.inner.create_new(pack).await?;
+        self.hooks.created(pack).await;
+        Ok(())
+    }
+
+    async fn save_with_expected_revision(&self, pack: &Pack, expected_revision: u64) -> Result<()> {
+        let previous = self.inner.get_by_id(&pack.id).await.ok().flatten();
+        self.inner
+            .save_with_expected_revision(pack, expected_revision)
+            .await?;
+        self.hooks.written(previous.as_ref(), pack).await;
+        Ok(())
+    }
+
+    async fn create_with_update(
+        &self,
+        created: &Pack,
+        updated: &Pack,
+        expected_revision: u64,
+    ) -> Result<()> {
+        let previous = self.inner.get_by_id(&updated.id).await.ok().flatten();
+        self.inner
+            .create_with_update(created, updated, expected_revision)
+            .await?;
+        self.hooks.created(created).await;
+        self.hooks.written(previous.as_ref(), updated).await;
+        Ok(())
+    }
+
+    async fn delete_pack_file(&self, id: &PackId) -> Result<bool> {
+        let deleted = self.inner.delete_pack_file(id).await?;
+        if deleted {
+            self.hooks.deleted(id).await;
+        }
+        Ok(deleted)
+    }
+
+    async fn get_by_id(&self, id: &PackId) -> Result<Option<Pack>> {
+        self.inner.get_by_id(id).await
+    }
+
+    async fn get_by_name(&self, name: &PackName) -> Result<Option<Pack>> {
+        self.inner.get_by_name(name).await
+    }
+
+    async fn list_packs(&self, filter: ListFilter) -> Result<Vec<Pack>> {
+        self.inner.list_packs(filter).await
+    }
+
+    async fn purge_expired(&self) -> Result<()> {
+        let expired = self
+            .inner
+            .list_packs(ListFilter {
+                freshness: Some(FreshnessState::Expired),
+                ..ListFilter::default()
+            })
+            .await?;
+        self.inner.purge_expired().await?;
+        for pack in expired {
+            if self.inner.get_by_id(&pack.id).await?.is_none() {
+                self.hooks.deleted(&pack.id).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_revisions(&self, id: &PackId) -> Result<Vec<u64>> {
+        self.inner.list_revisions(id).await
+    }
+
+    async fn get_revision(&self, id: &PackId, revision: u64) -> Result<Option<Pack>> {
+        self.inner.get_revision(id, revision).await
+    }
+
+    /// Not a content change, so no hook runs.
+    async fn extend_expiry(&self, id: &PackId, expires_at: DateTime<Utc>) -> Result<()> {
+        self.inner.extend_expiry(id, expires_at).await
+    }
+
+    fn max_pack_bytes(&self) -> Option<usize> {
+        self.inner.max_pack_bytes()
+    }
+}
@@ -0,0 +1,328 @@
+//! Hand-written markdown → [`SnapshotDocument`] for `input import_markdown`.
+//!
+//! The first `# ` heading is the pack title and text before the first `## `
+//! is the brief. Each `## Title [key]` starts a section (the key defaults to
+//! a slug of the title); inside it, `- ref: path:10-20 — why` lines become
+//! refs (`- ref <key>: …` names one, `path:10-20@<sha>` pins a commit),
+//! ```` ```mermaid ```` blocks become diagrams and everything else is the
+//! description.
+
+use regex::Regex;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use crate::{
+    app::input_usecases::{SnapshotDiagram, SnapshotDocument, SnapshotRef, SnapshotSection},
+    domain::{
+        errors::{DomainError, Result},
+        models::ReadDefaults,
+        types::Status,
+    },
+};
+
+const REF_SYNTAX: &str = "- ref[ <key>]: <path>:<start>[-<end>][@<git_sha>][ — <why>]";
+
+static HEADING_KEY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(.*?)\s*\[([a-z0-9][a-z0-9_\-]{1,63})\]$")
+        .expect("heading key regex must compile")
+});
+static REF_LINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^[-*]\s+ref(?:\s+([a-z0-9][a-z0-9_\-]{1,63}))?:\s*(\S+?):(\d+)(?:-(\d+))?(?:@([0-9A-Fa-f]{7,64}))?(?:\s+[—–-]\s+(.+))?$",
+    )
+    .expect("ref line regex must compile")
+});
+static REF_PREFIX_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[-*]\s+ref(\s+\S+)?:").expect("ref prefix regex must compile"));
+
+struct SectionDraft {
+    key: String,
+    title: String,
+    description: Vec<String>,
+    refs: Vec<SnapshotRef>,
+    diagrams: Vec<SnapshotDiagram>,
+}
+
+struct Fence {
+    mermaid: bool,
+    start_line: usize,
+    body: Vec<String>,
+}
+
+pub fn parse_markdown_document(markdown: &str) -> Result<SnapshotDocument> {
+    let mut title = None;
+    let mut brief = Vec::new();
+    let mut sections: Vec<SectionDraft> = Vec::new();
+    let mut section_keys = HashSet::new();
+    let mut fence: Option<Fence> = None;
+
+    for (index, raw_line) in markdown.lines().enumerate() {
+        let line_no = index + 1;
+        let line = raw_line.trim_end();
+        let is_fence = line.trim_start().starts_with("```");
+
+        if let Some(open) = fence.as_mut() {
+            if !is_fence {
+                if open.mermaid {
+                    open.body.push(line.to_string());
+                } else {
+                    text_sink(&mut brief, &mut sections).push(line.to_string());
+                }
+                continue;
+            }
+            let closed = fence.take().expect("fence is open");
+            if closed.mermaid {
+                let section = sections
+                    .last_mut()
+                    .ok_or_else(|| outside_section(closed.start_line, "mermaid block"))?;
+                let key = next_free_key("diagram", section.diagrams.iter().map(|d| d.key.as_str()));
+                section.diagrams.push(SnapshotDiagram {
+                    key,
+                    title: section.title.clone(),
+                    mermaid: closed.body.join("\n"),
+                    why: None,
+                });
+            } else {
+                text_sink(&mut brief, &mut sections).push(line.to_string());
+            }
+            continue;
+        }
+        if is_fence {
+            let info = line.trim_start().trim_start_matches('`').trim();
+            let mermaid = info == "mermaid";
+            if !mermaid {
+                text_sink(&mut brief, &mut sections).push(line.to_string());
+            }
+            fence = Some(Fence {
+                mermaid,
+                start_line: line_no,
+                body: Vec::new(),
+            });
+            continue;
+        }
+
+        if let Some(heading) = line.strip_prefix("## ") {
+            let heading = heading.trim();
+            let (title, explicit_key) = match HEADING_KEY_RE.captures(heading) {
+                Some(caps) => (caps[1].trim().to_string(), Some(caps[2].to_string())),
+                None => (heading.to_string(), None),
+            };
+            let base = explicit_key
+                .or_else(|| slug(&title))
+                .unwrap_or_else(|| format!("section-{}", sections.len() + 1));
+            let key = next_free_key(&base, section_keys.iter().map(String::as_str));
+            section_keys.insert(key.clone());
+            sections.push(SectionDraft {
+                key,
+                title: if title.is_empty() {
+                    base.clone()
+                } else {
+                    title
+                },
+                description: Vec::new(),
+                refs: Vec::new(),
+                diagrams: Vec::new(),
+            });
+            continue;
+        }
+        if title.is_none() && sections.is_empty() {
+            if let Some(heading) = line.strip_prefix("# ") {
+                title = Some(heading.trim().to_string()).filter(|t| !t.is_empty());
+                continue;
+            }
+        }
+
+        let item = line.trim_start();
+        if REF_PREFIX_RE.is_match(item) {
+            let section = sections
+                .last_mut()
+                .ok_or_else(|| outside_section(line_no, "ref annotation"))?;
+            section
+                .refs
+                .push(parse_ref_line(line_no, item, &section.refs)?);
+            continue;
+        }
+        text_sink(&mut brief, &mut sections).push(line.to_string());
+    }
+
+    if let Some(open) = fence.filter(|open| open.mermaid) {
+        return Err(DomainError::DetailedInvalidData {
+            message: format!(
+                "line {}: mermaid block is never closed with ```",
+                open.start_line
+            ),
+            details: json!({ "line": open.start_line }),
+        });
+    }
+    if sections.is_empty() {
+        return Err(DomainError::InvalidData(
+            "markdown has no '## ' sections to import".into(),
+        ));
+    }
+
+    Ok(SnapshotDocument {
+        name: None,
+        title,
+        brief: join_text(&brief),
+        tags: Vec::new(),
+        ttl_minutes: None,
+        status: Status::Draft,
+        sections: sections
+            .into_iter()
+            .map(|section| SnapshotSection {
+                description: join_text(&section.description),
+                key: section.key,
+                title: section.title,
+                translations: Vec::new(),
+                refs: section.refs,
+                diagrams: section.diagrams,
+            })
+            .collect(),
+        read_defaults: ReadDefaults::default(),
+    })
+}
+
+/// Description of the current section, or the brief before the first one.
+fn text_sink<'a>(
+    brief: &'a mut Vec<String>,
+    sections: &'a mut [SectionDraft],
+) -> &'a mut Vec<String> {
+    match sections.last_mut() {
+        Some(section) => &mut section.description,
+        None => brief,
+    }
+}
+
+fn parse_ref_line(line_no: usize, item: &str, existing: &[SnapshotRef]) -> Result<SnapshotRef> {
+    let caps = REF_LINE_RE
+        .captures(item)
+        .ok_or_else(|| DomainError::DetailedInvalidData {
+            message: format!(
+                "line {}: ref annotation must look like '{}'",
+                line_no, REF_SYNTAX
+            ),
+            details: json!({ "line": line_no, "text": item, "expected": REF_SYNTAX }),
+        })?;
+    let number = |index: usize| -> Result<Option<usize>> {
+        caps.get(index)
+            .map(|m| {
+                m.as_str().parse::<usize>().map_err(|_| {
+                    DomainError::InvalidData(format!("line {}: line number out of range", line_no))
+                })
+            })
+            .transpose()
+    };
+    let line_start = number(3)?.unwrap_or(0);
+    let line_end = number(4)?.unwrap_or(line_start);
+    let key = match caps.get(1) {
+        Some(key) => key.as_str().to_string(),
+        None => next_free_key("ref", existing.iter().map(|r| r.key.as_str())),
+    };
+    Ok(SnapshotRef {
+        key,
+        root: None,
+        path: caps[2].to_string(),
+        line_start,
+        line_end,
+        title: None,
+        why: caps.get(6).map(|why| why.as_str().trim().to_string()),
+        group: None,
+        entry_point: false,
+        git_sha: caps.get(5).map(|sha| sha.as_str().to_string()),
+        symbol: None,
+        url: None,
+        excerpt_line_limit: None,
+    })
+}
+
+fn outside_section(line_no: usize, what: &str) -> DomainError {
+    DomainError::DetailedInvalidData {
+        message: format!("line {}: {} before the first '## ' section", line_no, what),
+        details: json!({ "line": line_no }),
+    }
+}
+
+/// `base` when unused, else `base-2`, `base-3`, …; a bare `ref`/`diagram`
+/// base is always numbered.
+fn next_free_key<'a>(base: &str, taken: impl Iterator<Item = &'a str>) -> String {
+    let taken: HashSet<&str> = taken.collect();
+    let numbered = matches!(base, "ref" | "diagram");
+    if !numbered && !taken.contains(base) {
+        return base.to_string();
+    }
+    (if numbered { 1 } else { 2 }..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|key| !taken.contains(key.as_str()))
+        .expect("unbounded range yields a free key")
+}
+
+/// Lowercase `a-z0-9` runs joined by `-`, cut to leave room for a suffix;
+/// `None` when fewer than two characters remain.
+fn slug(title: &str) -> Option<String> {
+    let mut out = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.truncate(56);
+    let out = out.trim_end_matches('-').to_string();
+    (out.len() >= 2).then_some(out)
+}
+
+fn join_text(lines: &[String]) -> Option<String> {
+    let text = lines.join("\n");
+    let text = text.trim_matches('\n').trim_end();
+    (!text.trim().is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_markdown_document_builds_sections_refs_and_diagrams() {
+        let markdown = "# Auth audit\n\nWhere tokens are checked.\n\n## Token flow [flow]\nTokens are minted once.\n- ref: src/auth.rs:10-20 — mint\n- ref verify: src/auth.rs:40@ABC1234 — verify\n```mermaid\nflowchart LR\n  A --> B\n```\n\n## Open Questions!\n```text\nkept verbatim\n```\n\n## Open questions\n";
+        let document = parse_markdown_document(markdown).unwrap();
+        assert_eq!(document.title.as_deref(), Some("Auth audit"));
+        assert_eq!(document.brief.as_deref(), Some("Where tokens are checked."));
+        let keys: Vec<&str> = document.sections.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, ["flow", "open-questions", "open-questions-2"]);
+
+        let flow = &document.sections[0];
+        assert_eq!(flow.title, "Token flow");
+        assert_eq!(flow.description.as_deref(), Some("Tokens are minted once."));
+        assert_eq!(flow.refs[0].key, "ref-1");
+        assert_eq!((flow.refs[0].line_start, flow.refs[0].line_end), (10, 20));
+        assert_eq!(flow.refs[0].why.as_deref(), Some("mint"));
+        assert_eq!(flow.refs[1].key, "verify");
+        assert_eq!((flow.refs[1].line_start, flow.refs[1].line_end), (40, 40));
+        assert_eq!(flow.refs[1].git_sha.as_deref(), Some("ABC1234"));
+        assert_eq!(flow.refs[1].why.as_deref(), Some("verify"));
+        assert_eq!(flow.diagrams[0].key, "diagram-1");
+        assert_eq!(flow.diagrams[0].mermaid, "flowchart LR\n  A --> B");
+        assert_eq!(
+            document.sections[1].description.as_deref(),
+            Some("```text\nkept verbatim\n```")
+        );
+    }
+
+    #[test]
+    fn test_parse_markdown_document_reports_malformed_lines() {
+        let error_line = |markdown: &str| match parse_markdown_document(markdown) {
+            Err(DomainError::DetailedInvalidData { details, .. }) => details["line"].clone(),
+            Err(e) => panic!("unexpected error: {e}"),
+            Ok(_) => panic!("expected an error for {markdown:?}"),
+        };
+        assert_eq!(error_line("## Scope\n- ref: src/lib.rs\n"), 2);
+        assert_eq!(error_line("- ref: src/lib.rs:1\n## Scope\n"), 1);
+        assert_eq!(error_line("## Scope\n```mermaid\nflowchart LR\n"), 2);
+        assert!(matches!(
+            parse_markdown_document("# Only a title\n"),
+            Err(DomainError::InvalidData(_))
+        ));
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Distinct `tool.action` names tracked; the rest are counted under
+/// [`OTHER_CALLS`] so arbitrary action strings cannot grow the map unbounded.
+const MAX_TRACKED_CALLS: usize = 64;
+const OTHER_CALLS: &str = "<other>";
+
+/// Counters for one `tool.action`.
+#[derive(Debug, Clone, Default)]
+struct CallCounters {
+    calls: u64,
+    errors: u64,
+    total_ms: u64,
+    max_ms: u64,
+    total_bytes: u64,
+    max_bytes: u64,
+}
+
+/// Reported form of [`CallCounters`]; averages are over every call, sizes
+/// over successful ones.
+#[derive(Debug, Clone, Serialize)]
+pub struct CallStats {
+    pub calls: u64,
+    pub errors: u64,
+    pub avg_ms: u64,
+    pub max_ms: u64,
+    pub avg_bytes: u64,
+    pub max_bytes: u64,
+}
+
+/// What the server has handled since it started, as reported by
+/// `server stats`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsSnapshot {
+    pub started_at: DateTime<Utc>,
+    pub uptime_seconds: i64,
+    pub calls: u64,
+    pub errors: u64,
+    /// Keyed by `tool.action` (e.g. `output.read`).
+    pub tools: BTreeMap<String, CallStats>,
+    /// Failed calls by error contract `code`.
+    pub errors_by_code: BTreeMap<String, u64>,
+}
+
+struct MetricsState {
+    started_at: DateTime<Utc>,
+    calls: BTreeMap<String, CallCounters>,
+    errors_by_code: BTreeMap<String, u64>,
+    /// Total calls at the last [`ServerMetrics::log_summary`].
+    logged_calls: u64,
+}
+
+/// Tool call counters shared by every session of a server run: calls and
+/// errors per `tool.action`, errors by code, latency and response size.
+/// Clones share state.
+#[derive(Clone)]
+pub struct ServerMetrics {
+    state: Arc<Mutex<MetricsState>>,
+}
+
+impl Default for ServerMetrics {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MetricsState {
+                started_at: Utc::now(),
+                calls: BTreeMap::new(),
+                errors_by_code: BTreeMap::new(),
+                logged_calls: 0,
+            })),
+        }
+    }
+}
+
+impl ServerMetrics {
+    /// Records one tool call: `Ok` with the response size in bytes, or `Err`
+    /// with its error code.
+    pub fn record(
+        &self,
+        tool: &str,
+        action: &str,
+        elapsed: Duration,
+        outcome: Result<usize, &str>,
+    ) {
+        let name = format!("{tool}.{action}");
+        let elapsed_ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        let mut state = self.state();
+        let key = if state.calls.contains_key(&name) || state.calls.len() < MAX_TRACKED_CALLS {
+            name
+        } else {
+            OTHER_CALLS.to_string()
+        };
+        let counters = state.calls.entry(key).or_default();
+        counters.calls += 1;
+        counters.total_ms = counters.total_ms.saturating_add(elapsed_ms);
+        counters.max_ms = counters.max_ms.max(elapsed_ms);
+        match outcome {
+            Ok(bytes) => {
+                let bytes = bytes as u64;
+                counters.total_bytes = counters.total_bytes.saturating_add(bytes);
+                counters.max_bytes = counters.max_bytes.max(bytes);
+            }
+            Err(code) => {
+                counters.errors += 1;
+                *state.errors_by_code.entry(code.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let state = self.state();
+        let tools: BTreeMap<String, CallStats> = state
+            .calls
+            .iter()
+            .map(|(name, c)| {
+                let succeeded = c.calls - c.errors;
+                let stats = CallStats {
+                    calls: c.calls,
+                    errors: c.errors,
+                    avg_ms: c.total_ms / c.calls.max(1),
+                    max_ms: c.max_ms,
+                    avg_bytes: c.total_bytes / succeeded.max(1),
+                    max_bytes: c.max_bytes,
+                };
+                (name.clone(), stats)
+            })
+            .collect();
+        MetricsSnapshot {
+            started_at: state.started_at,
+            uptime_seconds: (Utc::now() - state.started_at).num_seconds(),
+            calls: tools.values().map(|s| s.calls).sum(),
+            errors: tools.values().map(|s| s.errors).sum(),
+            tools,
+            errors_by_code: state.errors_by_code.clone(),
+        }
+    }
+
+    /// Logs a one-line summary at info level, skipped when no call arrived
+    /// since the previous one.
+    pub fn log_summary(&self) {
+        let snapshot = self.snapshot();
+        {
+            let mut state = self.state();
+            if snapshot.calls == state.logged_calls {
+                return;
+            }
+            state.logged_calls = snapshot.calls;
+        }
+        let busiest = snapshot
+            .tools
+            .iter()
+            .max_by_key(|(_, stats)| stats.calls)
+            .map(|(name, stats)| format!("{name} ({})", stats.calls))
+            .unwrap_or_default();
+        let slowest = snapshot
+            .tools
+            .iter()
+            .max_by_key(|(_, stats)| stats.max_ms)
+            .map(|(name, stats)| format!("{name} ({} ms)", stats.max_ms))
+            .unwrap_or_default();
+        tracing::info!(
+            calls = snapshot.calls,
+            errors = snapshot.errors,
+            uptime_seconds = snapshot.uptime_seconds,
+            "tool calls: busiest {busiest}, slowest {slowest}, errors by code {:?}",
+            snapshot.errors_by_code
+        );
+    }
+
+    /// Logs [`Self::log_summary`] every `period` until the process exits.
+    pub fn spawn_log(&self, period: Duration) -> tokio::task::JoinHandle<()> {
+        let metrics = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                metrics.log_summary();
+            }
+        })
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MetricsState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_aggregates_per_call_and_by_error_code() {
+        let metrics = ServerMetrics::default();
+        metrics.record("output", "read", Duration::from_millis(10), Ok(1000));
+        metrics.record("output", "read", Duration::from_millis(30), Ok(3000));
+        metrics.record("output", "read", Duration::from_millis(5), Err("not_found"));
+        metrics.record(
+            "input",
+            "write",
+            Duration::from_millis(2),
+            Err("revision_conflict"),
+        );
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.calls, 4);
+        assert_eq!(snapshot.errors, 2);
+        let read = &snapshot.tools["output.read"];
+        assert_eq!((read.calls, read.errors), (3, 1));
+        assert_eq!((read.avg_ms, read.max_ms), (15, 30));
+        assert_eq!((read.avg_bytes, read.max_bytes), (2000, 3000));
+        assert_eq!(snapshot.errors_by_code["not_found"], 1);
+        assert_eq!(snapshot.errors_by_code["revision_conflict"], 1);
+    }
+
+    #[test]
+    fn test_untracked_names_fold_into_other() {
+        let metrics = ServerMetrics::default();
+        for idx in 0..MAX_TRACKED_CALLS + 3 {
+            metrics.record("input", &format!("a{idx}"), Duration::ZERO, Ok(0));
+        }
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.tools.len(), MAX_TRACKED_CALLS + 1);
+        assert_eq!(snapshot.tools[OTHER_CALLS].calls, 3);
+    }
+}
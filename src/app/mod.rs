@@ -1,4 +1,13 @@
+pub mod audit;
+pub mod finalize_rules;
 pub mod input_usecases;
+pub mod lifecycle;
+pub mod markdown_import;
+pub mod metrics;
 pub mod output_usecases;
 pub mod ports;
+pub mod progress;
 pub mod resolver;
+pub mod source_watch;
+pub mod supervisor;
+pub mod sync_usecases;
@@ -1,28 +1,42 @@
-use std::collections::BTreeSet;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::{self, Write as FmtWrite};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
 
+use chrono::{DateTime, SecondsFormat, Utc};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     app::{
-        ports::{CodeExcerptPort, FreshnessState, ListFilter, PackRepositoryPort},
-        resolver::resolve_pack,
+        ports::{
+            CodeExcerptPort, ExportedFile, FreshnessState, ListFilter, MarkdownExportPort,
+            PackRepositoryPort, Snippet,
+        },
+        progress,
+        resolver::{resolve_pack, resolve_revision},
     },
     domain::{
         errors::{DomainError, Result},
-        models::Pack,
-        types::Status,
+        glob,
+        models::{fnv1a_64, CodeRef, ExcerptLimits, ExcerptSnapshot, Pack, Section},
+        types::{LanguageTag, PackId, PackSectionRef, SectionKey, Status},
     },
 };
 
+pub use crate::domain::types::OutputProfile;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum OutputMode {
     #[default]
     Full,
     Compact,
+    /// Section headings plus one line per ref (key, path, staleness); no
+    /// descriptions or excerpts.
+    Outline,
 }
 
 impl fmt::Display for OutputMode {
@@ -30,6 +44,7 @@ impl fmt::Display for OutputMode {
         match self {
             OutputMode::Full => write!(f, "full"),
             OutputMode::Compact => write!(f, "compact"),
+            OutputMode::Outline => write!(f, "outline"),
         }
     }
 }
@@ -41,8 +56,49 @@ impl FromStr for OutputMode {
         match s.trim() {
             "full" => Ok(Self::Full),
             "compact" => Ok(Self::Compact),
+            "outline" => Ok(Self::Outline),
+            other => Err(DomainError::InvalidData(format!(
+                "'mode' must be one of: full, compact, outline (got '{}')",
+                other
+            ))),
+        }
+    }
+}
+
+/// Deep-link targets emitted before section and ref headings. Ids are
+/// `sec-{section_key}` and `ref-{section_key}.{ref_key}`; keys are already
+/// slug-safe, and `.` never occurs in a key, so ids are unique per pack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorStyle {
+    #[default]
+    None,
+    /// `<a id="…"></a>` line before the heading.
+    Html,
+    /// `{#…}` heading attribute (pandoc, kramdown, markdown-it-attrs).
+    Slug,
+}
+
+impl fmt::Display for AnchorStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnchorStyle::None => write!(f, "none"),
+            AnchorStyle::Html => write!(f, "html"),
+            AnchorStyle::Slug => write!(f, "slug"),
+        }
+    }
+}
+
+impl FromStr for AnchorStyle {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "none" => Ok(Self::None),
+            "html" => Ok(Self::Html),
+            "slug" => Ok(Self::Slug),
             other => Err(DomainError::InvalidData(format!(
-                "'mode' must be one of: full, compact (got '{}')",
+                "'anchors' must be one of: none, html, slug (got '{}')",
                 other
             ))),
         }
@@ -51,39 +107,261 @@ impl FromStr for OutputMode {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
-pub enum OutputProfile {
+pub enum SectionSeparator {
     #[default]
-    Orchestrator,
-    Reviewer,
-    Executor,
+    None,
+    /// `---` thematic break between sections.
+    Rule,
 }
 
-impl fmt::Display for OutputProfile {
+impl fmt::Display for SectionSeparator {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            OutputProfile::Orchestrator => write!(f, "orchestrator"),
-            OutputProfile::Reviewer => write!(f, "reviewer"),
-            OutputProfile::Executor => write!(f, "executor"),
+            SectionSeparator::None => write!(f, "none"),
+            SectionSeparator::Rule => write!(f, "rule"),
         }
     }
 }
 
-impl FromStr for OutputProfile {
+impl FromStr for SectionSeparator {
     type Err = DomainError;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s.trim() {
-            "orchestrator" => Ok(Self::Orchestrator),
-            "reviewer" => Ok(Self::Reviewer),
-            "executor" => Ok(Self::Executor),
+            "none" => Ok(Self::None),
+            "rule" => Ok(Self::Rule),
             other => Err(DomainError::InvalidData(format!(
-                "'profile' must be one of: orchestrator, reviewer, executor (got '{}')",
+                "'separator' must be one of: none, rule (got '{}')",
                 other
             ))),
         }
     }
 }
 
+static ANSI_ESCAPE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    // CSI (`ESC [ … final`), OSC (`ESC ] … BEL|ESC \`) and other two-byte escapes.
+    Regex::new(r"\x1b(?:\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(?:\x07|\x1b\\)|[@-_])")
+        .expect("ansi escape regex must compile")
+});
+
+/// How much of the pack header the `[LEGEND]` carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LegendVerbosity {
+    #[default]
+    Full,
+    /// Identity, revision, tags and brief plus paging: no TTL, freshness,
+    /// etag or sign-off lines, so renders of one revision stay identical.
+    Minimal,
+}
+
+/// What a read profile implies when the caller leaves the matching argument
+/// unset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfilePreset {
+    pub mode: OutputMode,
+    /// Page size; `None` renders every chunk.
+    pub limit: Option<usize>,
+    pub legend: LegendVerbosity,
+    pub status: Option<Status>,
+    pub contains: Option<String>,
+}
+
+impl ProfilePreset {
+    fn builtin(profile: OutputProfile) -> Self {
+        let (mode, limit, legend) = match profile {
+            OutputProfile::Orchestrator => (OutputMode::Compact, Some(6), LegendVerbosity::Full),
+            OutputProfile::Executor => (OutputMode::Compact, Some(12), LegendVerbosity::Full),
+            OutputProfile::Reviewer => (OutputMode::Full, None, LegendVerbosity::Full),
+            OutputProfile::Archive => (OutputMode::Full, None, LegendVerbosity::Minimal),
+            OutputProfile::Outline => (OutputMode::Outline, None, LegendVerbosity::Full),
+        };
+        Self {
+            mode,
+            limit,
+            legend,
+            status: None,
+            contains: None,
+        }
+    }
+}
+
+/// Per-profile overrides from `CONTEXT_PACK_RENDER_PROFILES`; unset fields
+/// keep the built-in preset and `limit: 0` means unpaged.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileOverride {
+    mode: Option<OutputMode>,
+    limit: Option<usize>,
+    legend: Option<LegendVerbosity>,
+    status: Option<Status>,
+    contains: Option<String>,
+}
+
+/// Read profile presets: the built-ins with any configured overrides, e.g.
+/// `{"archive":{"status":"finalized"},"orchestrator":{"limit":4,"legend":"minimal"}}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderProfiles {
+    overrides: HashMap<OutputProfile, ProfilePreset>,
+}
+
+impl RenderProfiles {
+    pub fn parse(raw: &str) -> Result<Self> {
+        if raw.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let parsed: HashMap<OutputProfile, ProfileOverride> = serde_json::from_str(raw)
+            .map_err(|e| DomainError::InvalidData(format!("render profiles: {}", e)))?;
+        let overrides = parsed
+            .into_iter()
+            .map(|(profile, patch)| {
+                let mut preset = ProfilePreset::builtin(profile);
+                if let Some(mode) = patch.mode {
+                    preset.mode = mode;
+                }
+                if let Some(limit) = patch.limit {
+                    preset.limit = (limit > 0).then_some(limit);
+                }
+                if let Some(legend) = patch.legend {
+                    preset.legend = legend;
+                }
+                preset.status = patch.status.or(preset.status);
+                preset.contains = normalize_contains(patch.contains).or(preset.contains);
+                (profile, preset)
+            })
+            .collect();
+        Ok(Self { overrides })
+    }
+
+    pub fn preset(&self, profile: OutputProfile) -> ProfilePreset {
+        self.overrides
+            .get(&profile)
+            .cloned()
+            .unwrap_or_else(|| ProfilePreset::builtin(profile))
+    }
+
+    /// Profiles whose preset differs from the built-in one.
+    pub fn configured(&self) -> usize {
+        self.overrides.len()
+    }
+}
+
+/// Clean-up applied to excerpt bodies as they are rendered; `contains`
+/// matching and snapshot drift still see the raw text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ExcerptCleanup {
+    /// Trailing whitespace on every line and trailing blank lines.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trim_trailing_whitespace: bool,
+    /// Expand tabs to spaces with tab stops this many columns apart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab_width: Option<usize>,
+    /// Drop ANSI escape sequences (terminal colors, cursor moves).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strip_ansi: bool,
+}
+
+impl ExcerptCleanup {
+    pub const MAX_TAB_WIDTH: usize = 16;
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self.tab_width {
+            Some(width) if width == 0 || width > Self::MAX_TAB_WIDTH => {
+                Err(DomainError::InvalidData(format!(
+                    "'tab_width' must be between 1 and {} (got {})",
+                    Self::MAX_TAB_WIDTH,
+                    width
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn apply<'a>(&self, body: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(body);
+        if self.strip_ansi && text.contains('\x1b') {
+            text = Cow::Owned(ANSI_ESCAPE_RE.replace_all(&text, "").into_owned());
+        }
+        if let Some(width) = self.tab_width.filter(|_| text.contains('\t')) {
+            text = Cow::Owned(
+                text.split('\n')
+                    .map(|line| expand_tabs(line, width))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+        if self.trim_trailing_whitespace {
+            let trimmed = text
+                .split('\n')
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n");
+            let trimmed = trimmed.trim_end_matches('\n');
+            if trimmed != text {
+                text = Cow::Owned(trimmed.to_string());
+            }
+        }
+        text
+    }
+}
+
+impl fmt::Display for ExcerptCleanup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.trim_trailing_whitespace {
+            parts.push("trim_trailing_whitespace".to_string());
+        }
+        if let Some(width) = self.tab_width {
+            parts.push(format!("tab_width={}", width));
+        }
+        if self.strip_ansi {
+            parts.push("strip_ansi".to_string());
+        }
+        if parts.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", parts.join(","))
+        }
+    }
+}
+
+fn expand_tabs(line: &str, width: usize) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut column = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let pad = width - column % width;
+            out.extend(std::iter::repeat_n(' ', pad));
+            column += pad;
+        } else {
+            out.push(c);
+            column += 1;
+        }
+    }
+    out
+}
+
+/// Presentation-only render options; they never change which chunks a page holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct RenderLayout {
+    #[serde(default)]
+    pub anchors: AnchorStyle,
+    #[serde(default)]
+    pub separator: SectionSeparator,
+    #[serde(default, skip_serializing_if = "ExcerptCleanup::is_default")]
+    pub excerpt: ExcerptCleanup,
+}
+
+impl RenderLayout {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct OutputReadRequest {
     pub status_filter: Option<Status>,
@@ -92,10 +370,184 @@ pub struct OutputReadRequest {
     pub offset: Option<usize>,
     pub page_token: Option<String>,
     pub contains: Option<String>,
+    /// Client whose host defaults shaped this request; echoed in the legend.
+    pub host: Option<String>,
+    /// Validator from an earlier read; a match short-circuits to `not_modified`.
+    pub if_none_match: Option<String>,
+    /// Unset fields come from the page token, else the defaults.
+    pub anchors: Option<AnchorStyle>,
+    pub separator: Option<SectionSeparator>,
+    /// Preferred language for section descriptions; carried by page tokens.
+    pub lang: Option<LanguageTag>,
+    /// Render every chunk on one page, ignoring profile and pack default
+    /// limits (exports).
+    pub unpaged: bool,
+    /// Excerpt clean-up; unset comes from the page token, else none.
+    pub excerpt: Option<ExcerptCleanup>,
+    /// Approximate token budget (chars / 4) for the whole response; chunks
+    /// past it move to the next page. Unset comes from the page token.
+    pub max_tokens: Option<usize>,
+}
+
+pub const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchReason {
+    RevisionAdvanced,
+    Finalized,
+    /// Deleted or purged while watching.
+    Gone,
+    TimedOut,
+}
+
+impl fmt::Display for WatchReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchReason::RevisionAdvanced => write!(f, "revision_advanced"),
+            WatchReason::Finalized => write!(f, "finalized"),
+            WatchReason::Gone => write!(f, "gone"),
+            WatchReason::TimedOut => write!(f, "timed_out"),
+        }
+    }
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchOutcome {
+    pub reason: WatchReason,
+    pub pack_id: String,
+    /// Last revision seen (the pre-deletion one for `gone`).
+    pub revision: u64,
+    pub status: Status,
+    pub waited_ms: u64,
+}
+
+impl WatchOutcome {
+    fn new(reason: WatchReason, pack: &Pack, started: Instant) -> Self {
+        Self {
+            reason,
+            pack_id: pack.id.as_str().to_string(),
+            revision: pack.revision,
+            status: pack.status,
+            waited_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        }
+    }
+}
+
+/// Machine-readable companion of an `output read` page: the legend's facts
+/// and the keys rendered on the page, so callers don't scrape the markdown.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadStructure {
+    pub pack_id: String,
+    pub name: Option<String>,
+    pub status: Status,
+    pub revision: u64,
+    pub etag: String,
+    pub expires_at: DateTime<Utc>,
+    pub freshness_state: FreshnessState,
+    /// The `if_none_match` validator matched; `sections` is empty.
+    pub not_modified: bool,
+    /// `None` when the whole pack is on one unpaged page.
+    pub paging: Option<ReadPaging>,
+    /// Sections with chunks on this page, in render order.
+    pub sections: Vec<ReadSection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadPaging {
+    pub offset: usize,
+    pub limit: Option<usize>,
+    pub has_more: bool,
+    pub next_page_token: Option<String>,
+    pub chunks_total: usize,
+    pub chunks_returned: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+    /// Chunks `max_tokens` pushed to the next page.
+    pub deferred_by_max_tokens: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadSection {
+    pub key: String,
+    pub title: String,
+    pub refs: Vec<ReadRef>,
+    pub diagrams: Vec<String>,
+    pub attachments: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadRef {
+    pub key: String,
+    pub group: String,
+    pub stale: bool,
+    pub drifted: bool,
+    pub entry_point: bool,
+    /// The excerpt had secrets masked.
+    pub redacted: bool,
+}
+
+/// One `output read` page as markdown plus its [`ReadStructure`].
+#[derive(Debug, Clone)]
+pub struct RenderedRead {
+    pub markdown: String,
+    pub structure: ReadStructure,
+}
+
+impl ReadStructure {
+    fn new(pack: &Pack, etag: &str, not_modified: bool) -> Self {
+        Self {
+            pack_id: pack.id.as_str().to_string(),
+            name: pack.name.as_ref().map(|name| name.as_str().to_string()),
+            status: pack.status,
+            revision: pack.revision,
+            etag: etag.to_string(),
+            expires_at: pack.expires_at,
+            freshness_state: FreshnessState::from_pack(pack, Utc::now()),
+            not_modified,
+            paging: None,
+            sections: Vec::new(),
+        }
+    }
+
+    fn add_chunk(&mut self, chunk: &RenderChunk) {
+        if self.sections.last().map(|s| s.key.as_str()) != Some(chunk.section_key.as_str()) {
+            self.sections.push(ReadSection {
+                key: chunk.section_key.clone(),
+                title: chunk.section_title.clone(),
+                refs: Vec::new(),
+                diagrams: Vec::new(),
+                attachments: Vec::new(),
+            });
+        }
+        let Some(section) = self.sections.last_mut() else {
+            return;
+        };
+        match &chunk.kind {
+            ChunkKind::Ref { group } => section.refs.push(ReadRef {
+                key: chunk.ref_key.clone().unwrap_or_default(),
+                group: group.clone(),
+                stale: chunk.stale_ref,
+                drifted: chunk.drifted_ref,
+                entry_point: chunk.entry_point,
+                redacted: chunk.redacted_ref,
+            }),
+            ChunkKind::Diagram { key } => section.diagrams.push(key.clone()),
+            ChunkKind::Attachment { key } => section.attachments.push(key.clone()),
+        }
+    }
+}
+
+/// Version stamped on newly issued page tokens (`v<N>:<hex>`).
+const PAGE_TOKEN_VERSION: u8 = 2;
+/// Versions still accepted on continuation: the current one and the one
+/// before it, so paging survives a server upgrade mid-read.
+const SUPPORTED_PAGE_TOKEN_VERSIONS: [u8; 2] = [1, PAGE_TOKEN_VERSION];
+
+/// v1 carries the request fingerprint verbatim; v2 carries its
+/// [`fingerprint_digest`] to keep tokens short. Both decode into this shape.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct OutputPageTokenV1 {
+struct OutputPageToken {
     v: u8,
     pack_id: String,
     revision: u64,
@@ -105,6 +557,12 @@ struct OutputPageTokenV1 {
     status_filter: Option<Status>,
     limit: Option<usize>,
     contains: Option<String>,
+    #[serde(default, skip_serializing_if = "RenderLayout::is_default")]
+    layout: RenderLayout,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lang: Option<LanguageTag>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -117,12 +575,20 @@ struct EffectiveReadArgs {
     contains: Option<String>,
     paging_active: bool,
     fingerprint: String,
+    host: Option<String>,
+    /// Whether the pack's own `read_defaults` filled an unset profile/limit.
+    pack_defaults: bool,
+    layout: RenderLayout,
+    legend: LegendVerbosity,
+    lang: Option<LanguageTag>,
+    max_tokens: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
 enum ChunkKind {
     Ref { group: String },
-    Diagram,
+    Diagram { key: String },
+    Attachment { key: String },
 }
 
 #[derive(Debug, Clone)]
@@ -133,21 +599,61 @@ struct RenderChunk {
     kind: ChunkKind,
     ref_key: Option<String>,
     stale_ref: bool,
+    drifted_ref: bool,
+    redacted_ref: bool,
+    entry_point: bool,
     body_markdown: String,
     searchable_text: String,
 }
 
+/// Full renders of packs with at least this many non-empty sections open
+/// with a table of contents.
+const TOC_MIN_SECTIONS: usize = 3;
+
 const COMPACT_SIGNAL_LIMIT: usize = 3;
 const COMPACT_NAV_HINT_LIMIT: usize = 5;
 
 pub struct OutputUseCases {
     repo: Arc<dyn PackRepositoryPort>,
     excerpt: Arc<dyn CodeExcerptPort>,
+    exporter: Option<Arc<dyn MarkdownExportPort>>,
+    profiles: RenderProfiles,
+    excerpt_limits: ExcerptLimits,
+    sliding_ttl_minutes: u64,
 }
 
 impl OutputUseCases {
     pub fn new(repo: Arc<dyn PackRepositoryPort>, excerpt: Arc<dyn CodeExcerptPort>) -> Self {
-        Self { repo, excerpt }
+        Self {
+            repo,
+            excerpt,
+            exporter: None,
+            profiles: RenderProfiles::default(),
+            excerpt_limits: ExcerptLimits::default(),
+            sliding_ttl_minutes: Pack::DEFAULT_SLIDING_TTL_MINUTES,
+        }
+    }
+
+    pub fn with_render_profiles(mut self, profiles: RenderProfiles) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    pub fn with_excerpt_limits(mut self, excerpt_limits: ExcerptLimits) -> Self {
+        self.excerpt_limits = excerpt_limits;
+        self
+    }
+
+    /// How far a read pushes out the expiry of a `sliding_ttl` pack.
+    pub fn with_sliding_ttl_minutes(mut self, minutes: u64) -> Self {
+        self.sliding_ttl_minutes = minutes;
+        self
+    }
+
+    /// Enables `export_path` reads; without an exporter they fail.
+    pub fn with_exporter(mut self, exporter: Arc<dyn MarkdownExportPort>) -> Self {
+        self.exporter = Some(exporter);
+        self
     }
 
     // ── identity resolution ───────────────────────────────────────────────────
@@ -182,12 +688,308 @@ impl OutputUseCases {
                 status,
                 freshness,
                 query,
+                tags: Vec::new(),
+                include_expired: None,
+                archived: false,
                 limit,
                 offset,
             })
             .await
     }
 
+    /// Lists with a fully specified filter (e.g. one resolved from a saved filter).
+    pub async fn list_matching(&self, filter: ListFilter) -> Result<Vec<Pack>> {
+        self.repo.list_packs(filter).await
+    }
+
+    // ── watch ─────────────────────────────────────────────────────────────────
+
+    /// Long-polls one pack until its revision exceeds `after_revision`
+    /// (default: the revision seen now), it is finalized, it disappears, or
+    /// `timeout` elapses. A name is resolved once, so the watch stays on that
+    /// pack id.
+    pub async fn watch(
+        &self,
+        identifier: &str,
+        after_revision: Option<u64>,
+        timeout: Duration,
+    ) -> Result<WatchOutcome> {
+        let started = Instant::now();
+        let pack = self.resolve(identifier).await?;
+        let pack_id = pack.id.clone();
+        let after_revision = after_revision.unwrap_or(pack.revision);
+        let mut last = pack;
+        loop {
+            let reason = if last.status == Status::Finalized {
+                Some(WatchReason::Finalized)
+            } else if last.revision > after_revision {
+                Some(WatchReason::RevisionAdvanced)
+            } else if started.elapsed() >= timeout {
+                Some(WatchReason::TimedOut)
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                return Ok(WatchOutcome::new(reason, &last, started));
+            }
+            tokio::time::sleep(WATCH_POLL_INTERVAL.min(timeout.saturating_sub(started.elapsed())))
+                .await;
+            match self.repo.get_by_id(&pack_id).await? {
+                Some(pack) => last = pack,
+                None => return Ok(WatchOutcome::new(WatchReason::Gone, &last, started)),
+            }
+        }
+    }
+
+    // ── graph ─────────────────────────────────────────────────────────────────
+
+    /// Mermaid overview of the packs matching `filter`, one node per pack,
+    /// styled by status and freshness, with an edge per link between them.
+    pub async fn graph(&self, filter: ListFilter) -> Result<String> {
+        let packs = self.repo.list_packs(filter).await?;
+        Ok(render_pack_graph(&packs, chrono::Utc::now()))
+    }
+
+    // ── read_delta ────────────────────────────────────────────────────────────
+
+    /// Renders only what changed after `since_revision`: sections stamped
+    /// later, with just their changed refs (full excerpts) and all their
+    /// diagrams, plus the keys of sections removed since.
+    pub async fn get_delta_rendered(
+        &self,
+        identifier: &str,
+        since_revision: u64,
+        lang: Option<LanguageTag>,
+    ) -> Result<String> {
+        let pack = self.resolve(identifier).await?;
+        if since_revision > pack.revision {
+            return Err(DomainError::InvalidData(format!(
+                "'since_revision' {} is ahead of the current revision {}",
+                since_revision, pack.revision
+            )));
+        }
+        let (changed, removed) = pack.changes_since(since_revision);
+        let mut omitted_refs = Vec::with_capacity(changed.len());
+        let mut delta = pack.clone();
+        delta.sections = changed
+            .into_iter()
+            .map(|section| {
+                let mut section = section.clone();
+                let before = section.refs.len();
+                section
+                    .refs
+                    .retain(|code_ref| code_ref.changed_since(since_revision));
+                omitted_refs.push(before - section.refs.len());
+                section
+            })
+            .collect();
+        let chunks = self
+            .collect_chunks(
+                &delta,
+                OutputMode::Full,
+                lang.as_ref(),
+                ExcerptCleanup::default(),
+            )
+            .await?;
+
+        let mut out = String::with_capacity(2048);
+        out.push_str("[LEGEND]\n");
+        write_legend_header(&mut out, &pack, LegendVerbosity::Full);
+        let _ = writeln!(out, "- mode: delta");
+        let _ = writeln!(out, "- base_revision: {}", since_revision);
+        let _ = writeln!(out, "- changed_sections: {}", delta.sections.len());
+        if !removed.is_empty() {
+            let removed: Vec<&str> = removed.iter().map(|key| key.as_str()).collect();
+            let _ = writeln!(out, "- removed_sections: {}", removed.join(", "));
+        }
+        let _ = writeln!(
+            out,
+            "- unchanged_sections_omitted: {}",
+            pack.sections.len() - delta.sections.len()
+        );
+        if let Some(lang) = &lang {
+            let _ = writeln!(out, "- lang: {}", lang);
+        }
+
+        out.push_str("\n[CONTENT]\n");
+        for (section, omitted) in delta.sections.iter().zip(omitted_refs) {
+            let _ = write!(out, "\n## {} [{}]\n", section.title, section.key);
+            if let Some(desc) = section.description_for(lang.as_ref()) {
+                let _ = write!(out, "\n{}\n", desc);
+            }
+            if section.key.as_str() == "qa" {
+                write_sign_offs(&mut out, &pack);
+            }
+            let mut current_group: Option<&str> = None;
+            let mut diagrams_open = false;
+            let mut attachments_open = false;
+            for chunk in chunks
+                .iter()
+                .filter(|chunk| chunk.section_key == section.key.as_str())
+            {
+                match &chunk.kind {
+                    ChunkKind::Ref { group } => {
+                        if current_group != Some(group.as_str()) {
+                            let _ = write!(out, "\n### group: {}\n", group);
+                            current_group = Some(group.as_str());
+                        }
+                    }
+                    ChunkKind::Diagram { .. } => {
+                        if !diagrams_open {
+                            out.push_str("\n### Diagrams\n");
+                            diagrams_open = true;
+                        }
+                    }
+                    ChunkKind::Attachment { .. } => {
+                        if !attachments_open {
+                            out.push_str("\n### Attachments\n");
+                            attachments_open = true;
+                        }
+                    }
+                }
+                out.push_str(&chunk.body_markdown);
+            }
+            if omitted > 0 {
+                let _ = write!(out, "\n_{} unchanged ref(s) omitted._\n", omitted);
+            }
+        }
+        if delta.sections.is_empty() && removed.is_empty() {
+            let _ = write!(out, "\n_No changes since revision {}._\n", since_revision);
+        }
+
+        Ok(out)
+    }
+
+    /// Key-level diff of the current pack against a journaled prior revision:
+    /// pack fields, then sections added/removed/changed with the ref and
+    /// diagram keys that differ. No excerpts are read.
+    pub async fn get_revision_diff_rendered(
+        &self,
+        identifier: &str,
+        against_revision: u64,
+    ) -> Result<String> {
+        let pack = self.resolve(identifier).await?;
+        if against_revision >= pack.revision {
+            return Err(DomainError::InvalidData(format!(
+                "'diff_against_revision' must be below the current revision {} (got {})",
+                pack.revision, against_revision
+            )));
+        }
+        let base = resolve_revision(self.repo.as_ref(), &pack, against_revision).await?;
+
+        let find = |sections: &'_ [Section], key: &SectionKey| -> Option<usize> {
+            sections.iter().position(|s| s.key == *key)
+        };
+        let added: Vec<&Section> = pack
+            .sections
+            .iter()
+            .filter(|s| find(&base.sections, &s.key).is_none())
+            .collect();
+        let removed: Vec<&Section> = base
+            .sections
+            .iter()
+            .filter(|s| find(&pack.sections, &s.key).is_none())
+            .collect();
+        let changed: Vec<(&Section, &Section)> = pack
+            .sections
+            .iter()
+            .filter_map(|s| {
+                let before = &base.sections[find(&base.sections, &s.key)?];
+                (!before.same_content(s)).then_some((before, s))
+            })
+            .collect();
+        let unchanged = pack.sections.len() - added.len() - changed.len();
+
+        let mut out = String::with_capacity(2048);
+        out.push_str("[LEGEND]\n");
+        write_legend_header(&mut out, &pack, LegendVerbosity::Full);
+        let _ = writeln!(out, "- mode: diff");
+        let _ = writeln!(out, "- base_revision: {}", against_revision);
+        let _ = writeln!(out, "- added_sections: {}", added.len());
+        let _ = writeln!(out, "- removed_sections: {}", removed.len());
+        let _ = writeln!(out, "- changed_sections: {}", changed.len());
+        let _ = writeln!(out, "- unchanged_sections: {}", unchanged);
+
+        out.push_str("\n[CONTENT]\n");
+        let mut pack_changes = Vec::new();
+        if base.title != pack.title {
+            pack_changes.push("title");
+        }
+        if base.brief != pack.brief {
+            pack_changes.push("brief");
+        }
+        if base.tags != pack.tags {
+            pack_changes.push("tags");
+        }
+        if base.status != pack.status {
+            pack_changes.push("status");
+        }
+        if base.read_defaults != pack.read_defaults {
+            pack_changes.push("read_defaults");
+        }
+        if !pack_changes.is_empty() {
+            let _ = write!(out, "\n## Pack\n\n- changed: {}\n", pack_changes.join(", "));
+        }
+        for section in &added {
+            let _ = write!(out, "\n## {} [{}] (added)\n\n", section.title, section.key);
+            write_key_list(
+                &mut out,
+                "refs",
+                section.refs.iter().map(|r| r.key.as_str()),
+            );
+            write_key_list(
+                &mut out,
+                "diagrams",
+                section.diagrams.iter().map(|d| d.key.as_str()),
+            );
+        }
+        for section in &removed {
+            let _ = writeln!(out, "\n## {} [{}] (removed)", section.title, section.key);
+        }
+        for (before, after) in &changed {
+            let _ = write!(out, "\n## {} [{}] (changed)\n\n", after.title, after.key);
+            let mut fields = Vec::new();
+            if before.title != after.title {
+                fields.push("title");
+            }
+            if before.description != after.description {
+                fields.push("description");
+            }
+            if before.translations != after.translations {
+                fields.push("translations");
+            }
+            if !fields.is_empty() {
+                let _ = writeln!(out, "- fields: {}", fields.join(", "));
+            }
+            let ref_diff = KeyDiff::between(
+                &before.refs,
+                &after.refs,
+                |r| r.key.as_str(),
+                |a, b| a.same_content(b),
+            );
+            ref_diff.write(&mut out, "refs");
+            let diagram_diff = KeyDiff::between(
+                &before.diagrams,
+                &after.diagrams,
+                |d| d.key.as_str(),
+                |a, b| a.same_content(b),
+            );
+            diagram_diff.write(&mut out, "diagrams");
+            if fields.is_empty() && ref_diff.is_empty() && diagram_diff.is_empty() {
+                out.push_str("- order of refs or diagrams changed\n");
+            }
+        }
+        if pack_changes.is_empty() && added.is_empty() && removed.is_empty() && changed.is_empty() {
+            let _ = write!(
+                out,
+                "\n_No content changes since revision {}._\n",
+                against_revision
+            );
+        }
+
+        Ok(out)
+    }
+
     // ── render ────────────────────────────────────────────────────────────────
 
     pub async fn get_rendered(
@@ -210,7 +1012,17 @@ impl OutputUseCases {
         identifier: &str,
         request: OutputReadRequest,
     ) -> Result<String> {
-        let pack = self.resolve(identifier).await?;
+        Ok(self.read_structured(identifier, request).await?.markdown)
+    }
+
+    /// [`Self::get_rendered_with_request`] plus the page's [`ReadStructure`].
+    pub async fn read_structured(
+        &self,
+        identifier: &str,
+        request: OutputReadRequest,
+    ) -> Result<RenderedRead> {
+        let mut pack = self.resolve(identifier).await?;
+        let if_none_match = request.if_none_match.clone();
         let args = self.resolve_effective_read_args(&pack, request)?;
 
         if let Some(required) = args.status_filter {
@@ -222,7 +1034,56 @@ impl OutputUseCases {
             }
         }
 
-        self.render_pack_advanced(&pack, &args).await
+        let slid_to = pack.sliding_expiry(self.sliding_ttl_minutes, chrono::Utc::now());
+        if let Some(expires_at) = slid_to {
+            pack.expires_at = expires_at;
+        }
+        let etag = render_etag(&pack, &args);
+        let rendered = if if_none_match.as_deref().map(str::trim) == Some(etag.as_str()) {
+            RenderedRead {
+                markdown: render_not_modified(&pack, &etag),
+                structure: ReadStructure::new(&pack, &etag, true),
+            }
+        } else {
+            self.render_pack_advanced(&pack, &args, &etag).await?
+        };
+        if let Some(expires_at) = slid_to {
+            self.store_sliding_expiry(&pack.id, expires_at).await;
+        }
+        Ok(rendered)
+    }
+
+    /// Persists a sliding TTL refresh. The read already succeeded, so a
+    /// failure is only logged; the next read tries again.
+    async fn store_sliding_expiry(&self, id: &PackId, expires_at: DateTime<Utc>) {
+        if let Err(err) = self.repo.extend_expiry(id, expires_at).await {
+            tracing::warn!("failed to extend the sliding TTL of pack {id}: {err}");
+        }
+    }
+
+    /// Renders the whole pack on one page and writes it under the export
+    /// root as `export_path`. `if_none_match` is ignored so the file always
+    /// holds content.
+    pub async fn export_rendered(
+        &self,
+        identifier: &str,
+        request: OutputReadRequest,
+        export_path: &str,
+    ) -> Result<ExportedFile> {
+        let exporter = self.exporter.as_ref().ok_or_else(|| {
+            DomainError::InvalidState("markdown export is not configured on this server".into())
+        })?;
+        let request = OutputReadRequest {
+            limit: None,
+            offset: None,
+            page_token: None,
+            if_none_match: None,
+            unpaged: true,
+            max_tokens: None,
+            ..request
+        };
+        let markdown = self.get_rendered_with_request(identifier, request).await?;
+        exporter.write_export(export_path, &markdown).await
     }
 
     fn resolve_effective_read_args(
@@ -236,15 +1097,27 @@ impl OutputUseCases {
             ));
         }
 
-        let default_profile = request.profile.unwrap_or_default();
-        let default_mode = profile_mode(default_profile);
+        // Precedence: explicit args (incl. host defaults) > pack defaults > profile presets.
+        let pack_defaults = (request.profile.is_none() && pack.read_defaults.profile.is_some())
+            || (request.limit.is_none() && pack.read_defaults.limit.is_some());
+        let default_profile = request
+            .profile
+            .or(pack.read_defaults.profile)
+            .unwrap_or_default();
+        let default_preset = self.profiles.preset(default_profile);
+        let default_mode = default_preset.mode;
         let contains = normalize_contains(request.contains);
-        let paging_requested =
-            request.limit.is_some() || request.offset.is_some() || request.page_token.is_some();
+        if request.max_tokens == Some(0) {
+            return Err(DomainError::InvalidData("'max_tokens' must be >= 1".into()));
+        }
+        let paging_requested = request.limit.is_some()
+            || request.offset.is_some()
+            || request.page_token.is_some()
+            || request.max_tokens.is_some();
 
         match request.page_token {
             Some(raw_page_token) => {
-                let token = decode_page_token_v1(&raw_page_token)?;
+                let token = decode_page_token(&raw_page_token)?;
                 if token.pack_id != pack.id.as_str() {
                     return Err(invalid_page_token("pack id mismatch"));
                 }
@@ -253,13 +1126,23 @@ impl OutputUseCases {
                 }
 
                 let effective_profile = request.profile.unwrap_or(token.profile);
-                let effective_mode = profile_mode(effective_profile);
-                let effective_status = request.status_filter.or(token.status_filter);
-                let effective_limit = request
-                    .limit
-                    .or(token.limit)
-                    .or_else(|| profile_default_limit(effective_profile));
-                let effective_contains = contains.or(token.contains);
+                let preset = self.profiles.preset(effective_profile);
+                let effective_mode = preset.mode;
+                let effective_status = request
+                    .status_filter
+                    .or(token.status_filter)
+                    .or(preset.status);
+                let effective_limit = request.limit.or(token.limit).or(preset.limit);
+                let effective_contains = contains
+                    .or_else(|| token.contains.clone())
+                    .or(preset.contains);
+                let layout = RenderLayout {
+                    anchors: request.anchors.unwrap_or(token.layout.anchors),
+                    separator: request.separator.unwrap_or(token.layout.separator),
+                    excerpt: request.excerpt.unwrap_or(token.layout.excerpt),
+                };
+                let lang = request.lang.or_else(|| token.lang.clone());
+                let max_tokens = request.max_tokens.or(token.max_tokens);
 
                 if let Some(limit) = effective_limit {
                     if limit == 0 {
@@ -275,8 +1158,10 @@ impl OutputUseCases {
                     effective_status,
                     effective_limit,
                     effective_contains.as_deref(),
+                    layout,
+                    lang.as_ref(),
                 );
-                if token.fingerprint != fingerprint {
+                if !token.matches_fingerprint(&fingerprint) {
                     return Err(invalid_page_token("request fingerprint mismatch"));
                 }
 
@@ -289,6 +1174,12 @@ impl OutputUseCases {
                     contains: effective_contains,
                     paging_active: true,
                     fingerprint,
+                    host: request.host,
+                    pack_defaults: false,
+                    layout,
+                    legend: preset.legend,
+                    lang,
+                    max_tokens,
                 })
             }
             None => {
@@ -301,19 +1192,33 @@ impl OutputUseCases {
                         }
                     }
                 }
-                let effective_limit = request
-                    .limit
-                    .or_else(|| profile_default_limit(default_profile));
+                let effective_limit = if request.unpaged {
+                    None
+                } else {
+                    request
+                        .limit
+                        .or(pack.read_defaults.limit)
+                        .or(default_preset.limit)
+                };
+                let status_filter = request.status_filter.or(default_preset.status);
+                let contains = contains.or(default_preset.contains);
                 let paging_active = paging_requested || effective_limit.is_some();
+                let layout = RenderLayout {
+                    anchors: request.anchors.unwrap_or_default(),
+                    separator: request.separator.unwrap_or_default(),
+                    excerpt: request.excerpt.unwrap_or_default(),
+                };
                 let fingerprint = request_fingerprint(
                     default_profile,
                     default_mode,
-                    request.status_filter,
+                    status_filter,
                     effective_limit,
                     contains.as_deref(),
+                    layout,
+                    request.lang.as_ref(),
                 );
                 Ok(EffectiveReadArgs {
-                    status_filter: request.status_filter,
+                    status_filter,
                     profile: default_profile,
                     mode: default_mode,
                     limit: effective_limit,
@@ -321,39 +1226,70 @@ impl OutputUseCases {
                     contains,
                     paging_active,
                     fingerprint,
+                    host: request.host,
+                    pack_defaults,
+                    layout,
+                    legend: default_preset.legend,
+                    lang: request.lang,
+                    max_tokens: request.max_tokens,
                 })
             }
         }
     }
 
-    async fn render_pack_advanced(&self, pack: &Pack, args: &EffectiveReadArgs) -> Result<String> {
-        let mut chunks = self.collect_chunks(pack, args.mode).await?;
+    async fn render_pack_advanced(
+        &self,
+        pack: &Pack,
+        args: &EffectiveReadArgs,
+        etag: &str,
+    ) -> Result<RenderedRead> {
+        let mut chunks = self
+            .collect_chunks(pack, args.mode, args.lang.as_ref(), args.layout.excerpt)
+            .await?;
 
         if let Some(contains) = args.contains.as_deref() {
             let needle = contains.to_lowercase();
             chunks.retain(|chunk| chunk.searchable_text.to_lowercase().contains(&needle));
         }
 
+        if args.mode == OutputMode::Compact {
+            // Entry points lead the first compact page; stable sort keeps the
+            // remaining section order intact.
+            chunks.sort_by_key(|chunk| !chunk.entry_point);
+        }
+
         let total_chunks = chunks.len();
         let start = args.start_offset.min(total_chunks);
-        let end = match args.limit {
+        let mut end = match args.limit {
             Some(limit) => start.saturating_add(limit).min(total_chunks),
             None => total_chunks,
         };
+        let budget_deferred = match args.max_tokens {
+            Some(max_tokens) => {
+                let kept = chunks_within_token_budget(&chunks[start..end], max_tokens);
+                let deferred = end - start - kept;
+                end = start + kept;
+                deferred
+            }
+            None => 0,
+        };
         let page_chunks = &chunks[start..end];
 
         let has_more = end < total_chunks;
         let next_page_token = if args.paging_active && has_more {
-            Some(encode_page_token_v1(&OutputPageTokenV1 {
-                v: 1,
+            Some(encode_page_token(&OutputPageToken {
+                v: PAGE_TOKEN_VERSION,
                 pack_id: pack.id.as_str().to_string(),
                 revision: pack.revision,
                 next_offset: end,
-                fingerprint: args.fingerprint.clone(),
+                fingerprint: fingerprint_digest(&args.fingerprint),
                 profile: args.profile,
                 status_filter: args.status_filter,
                 limit: args.limit,
                 contains: args.contains.clone(),
+                layout: args.layout,
+                lang: args.lang.clone(),
+                max_tokens: args.max_tokens,
             })?)
         } else {
             None
@@ -361,14 +1297,32 @@ impl OutputUseCases {
 
         let mut out = String::with_capacity(2048);
         out.push_str("[LEGEND]\n");
-        write_legend_header(&mut out, pack);
+        write_legend_header(&mut out, pack, args.legend);
         let _ = writeln!(out, "- profile: {}", args.profile);
-        if args.mode == OutputMode::Compact {
-            let _ = writeln!(out, "- mode: compact");
+        if args.legend == LegendVerbosity::Full {
+            let _ = writeln!(out, "- etag: {}", etag);
+            if let Some(host) = &args.host {
+                let _ = writeln!(out, "- host_defaults: {}", host);
+            }
+            if args.pack_defaults {
+                let _ = writeln!(out, "- pack_defaults: applied");
+            }
+        }
+        if args.mode != OutputMode::Full {
+            let _ = writeln!(out, "- mode: {}", args.mode);
         }
         if let Some(contains) = &args.contains {
             let _ = writeln!(out, "- contains: {}", contains);
         }
+        if args.layout.anchors != AnchorStyle::None {
+            let _ = writeln!(out, "- anchors: {}", args.layout.anchors);
+        }
+        if !args.layout.excerpt.is_default() {
+            let _ = writeln!(out, "- excerpt_cleanup: {}", args.layout.excerpt);
+        }
+        if let Some(lang) = &args.lang {
+            let _ = writeln!(out, "- lang: {}", lang);
+        }
         if args.paging_active {
             let _ = writeln!(out, "- paging: active");
             let _ = writeln!(out, "- offset: {}", start);
@@ -392,42 +1346,78 @@ impl OutputUseCases {
             );
             let _ = writeln!(out, "- chunks_total: {}", total_chunks);
             let _ = writeln!(out, "- chunks_returned: {}", page_chunks.len());
+            if let Some(max_tokens) = args.max_tokens {
+                let _ = writeln!(out, "- max_tokens: {}", max_tokens);
+                if budget_deferred > 0 {
+                    let _ = writeln!(
+                        out,
+                        "- truncated_by_max_tokens: {} chunk(s) deferred; resume with next_page_token",
+                        budget_deferred
+                    );
+                }
+            }
         }
 
         out.push_str("\n[CONTENT]\n");
         if args.mode == OutputMode::Compact {
             write_compact_handoff_summary(&mut out, pack, &chunks, page_chunks, has_more);
         }
+        if args.mode == OutputMode::Full && start == 0 {
+            write_table_of_contents(&mut out, &chunks, args.layout.anchors);
+        }
 
         let mut current_section_key: Option<&str> = None;
         let mut current_group: Option<&str> = None;
         let mut diagrams_open = false;
+        let mut attachments_open = false;
 
         for chunk in page_chunks {
             if current_section_key != Some(chunk.section_key.as_str()) {
+                if current_section_key.is_some() && args.layout.separator == SectionSeparator::Rule
+                {
+                    out.push_str("\n---\n");
+                }
                 current_section_key = Some(chunk.section_key.as_str());
                 current_group = None;
                 diagrams_open = false;
-
-                let _ = write!(
-                    out,
-                    "\n## {} [{}]\n",
-                    chunk.section_title, chunk.section_key
-                );
-                if let Some(desc) = &chunk.section_description {
+                attachments_open = false;
+
+                let heading = format!("## {} [{}]", chunk.section_title, chunk.section_key);
+                let anchor = format!("sec-{}", chunk.section_key);
+                out.push('\n');
+                write_anchored_heading(&mut out, &heading, &anchor, args.layout.anchors);
+                if args.mode == OutputMode::Outline {
+                    // Outline lines follow the heading directly.
+                    out.push('\n');
+                } else if let Some(desc) = &chunk.section_description {
                     let _ = write!(out, "\n{}\n", desc);
                 }
+                if chunk.section_key == "qa" && args.mode != OutputMode::Outline {
+                    write_sign_offs(&mut out, pack);
+                }
             }
 
             match &chunk.kind {
+                _ if args.mode == OutputMode::Outline => out.push_str(&chunk.body_markdown),
                 ChunkKind::Ref { group } => {
                     if current_group != Some(group.as_str()) {
                         let _ = write!(out, "\n### group: {}\n", group);
                         current_group = Some(group.as_str());
                     }
-                    out.push_str(&chunk.body_markdown);
+                    match (&chunk.ref_key, args.layout.anchors) {
+                        (Some(ref_key), anchors) if anchors != AnchorStyle::None => {
+                            let anchor = format!("ref-{}.{}", chunk.section_key, ref_key);
+                            // Ref bodies open with "\n#### heading\n".
+                            let body = chunk.body_markdown.trim_start_matches('\n');
+                            let (heading, rest) = body.split_once('\n').unwrap_or((body, ""));
+                            out.push('\n');
+                            write_anchored_heading(&mut out, heading, &anchor, anchors);
+                            out.push_str(rest);
+                        }
+                        _ => out.push_str(&chunk.body_markdown),
+                    }
                 }
-                ChunkKind::Diagram => {
+                ChunkKind::Diagram { .. } => {
                     if !diagrams_open {
                         out.push_str("\n### Diagrams\n");
                         diagrams_open = true;
@@ -435,6 +1425,14 @@ impl OutputUseCases {
                     }
                     out.push_str(&chunk.body_markdown);
                 }
+                ChunkKind::Attachment { .. } => {
+                    if !attachments_open {
+                        out.push_str("\n### Attachments\n");
+                        attachments_open = true;
+                        current_group = None;
+                    }
+                    out.push_str(&chunk.body_markdown);
+                }
             }
         }
 
@@ -442,16 +1440,46 @@ impl OutputUseCases {
             out.push_str("\n_No chunks matched current filters._\n");
         }
 
-        Ok(out)
+        let mut structure = ReadStructure::new(pack, etag, false);
+        if args.paging_active {
+            structure.paging = Some(ReadPaging {
+                offset: start,
+                limit: args.limit,
+                has_more,
+                next_page_token,
+                chunks_total: total_chunks,
+                chunks_returned: page_chunks.len(),
+                max_tokens: args.max_tokens,
+                deferred_by_max_tokens: budget_deferred,
+            });
+        }
+        for chunk in page_chunks {
+            structure.add_chunk(chunk);
+        }
+        Ok(RenderedRead {
+            markdown: out,
+            structure,
+        })
     }
 
-    async fn collect_chunks(&self, pack: &Pack, mode: OutputMode) -> Result<Vec<RenderChunk>> {
+    async fn collect_chunks(
+        &self,
+        pack: &Pack,
+        mode: OutputMode,
+        lang: Option<&LanguageTag>,
+        cleanup: ExcerptCleanup,
+    ) -> Result<Vec<RenderChunk>> {
         let mut chunks = Vec::new();
+        let total = pack
+            .sections
+            .iter()
+            .map(|s| s.refs.len() + s.diagrams.len() + s.attachments.len())
+            .sum::<usize>() as u64;
 
         for section in &pack.sections {
             let section_key = section.key.as_str().to_string();
             let section_title = section.title.clone();
-            let section_description = section.description.clone();
+            let section_description = section.description_for(lang).map(str::to_string);
 
             let groups = Pack::refs_grouped_in_section(section);
             for (group_name, refs) in &groups {
@@ -464,29 +1492,160 @@ impl OutputUseCases {
                         let _ = write!(body_markdown, "**{}**\n\n", t);
                         let _ = writeln!(searchable_text, "{}", t);
                     }
-                    let _ = writeln!(body_markdown, "- path: {}", r.path);
-                    let _ = writeln!(body_markdown, "- lines: {}-{}", r.lines.start, r.lines.end);
+                    if let Some(url) = &r.url {
+                        let label = r.title.as_deref().unwrap_or(r.path.as_str());
+                        let _ = writeln!(body_markdown, "- url: [{}]({})", label, url);
+                        let _ = writeln!(searchable_text, "{}", url);
+                    } else if let Some(target) = &r.section_ref {
+                        let _ = writeln!(body_markdown, "- section_ref: {}", target);
+                    } else {
+                        let _ = writeln!(body_markdown, "- path: {}", r.path);
+                    }
+                    if let Some(root) = &r.root {
+                        let _ = writeln!(body_markdown, "- root: {}", root);
+                    }
+                    if !r.path.is_pattern() && r.reads_source() {
+                        let _ =
+                            writeln!(body_markdown, "- lines: {}-{}", r.lines.start, r.lines.end);
+                    }
+                    if let Some(sha) = &r.git_sha {
+                        let _ = writeln!(body_markdown, "- git_sha: {}", sha);
+                    }
+                    if let Some(symbol) = &r.symbol {
+                        let _ = writeln!(body_markdown, "- symbol: {}", symbol);
+                        let _ = writeln!(searchable_text, "{}", symbol);
+                    }
                     let _ = writeln!(searchable_text, "{}", r.path);
-                    let _ = writeln!(searchable_text, "{}-{}", r.lines.start, r.lines.end);
+                    if !r.path.is_pattern() && r.reads_source() {
+                        let _ = writeln!(searchable_text, "{}-{}", r.lines.start, r.lines.end);
+                    }
                     if let Some(why) = &r.why {
                         let _ = writeln!(body_markdown, "- why: {}", why);
                         let _ = writeln!(searchable_text, "{}", why);
                     }
-
-                    match self.excerpt.read_lines(&r.path, r.lines).await {
-                        Ok(snippet) => {
+                    if r.entry_point {
+                        let _ = writeln!(body_markdown, "- entry_point: true");
+                    }
+                    let max_lines = self.excerpt_limits.lines_for(pack, r);
+
+                    if let Some(target) = &r.section_ref {
+                        self.write_section_ref(
+                            &mut body_markdown,
+                            &mut searchable_text,
+                            target,
+                            mode,
+                            lang,
+                        )
+                        .await?;
+                    }
+                    // Url refs are the link alone and section refs quote
+                    // another pack; neither has source lines to read.
+                    let read = if r.reads_source() {
+                        Some(self.excerpt.read_ref(r).await)
+                    } else {
+                        None
+                    };
+                    let mut drifted_ref = false;
+                    let mut redacted_ref = false;
+                    match read {
+                        None => {}
+                        Some(Ok(snippet)) => {
                             let _ = writeln!(searchable_text, "{}", snippet.body);
+                            if r.path.is_pattern() {
+                                let _ = writeln!(body_markdown, "- files: {}", snippet.total_lines);
+                            }
+                            if (snippet.line_start, snippet.line_end)
+                                != (r.lines.start, r.lines.end)
+                            {
+                                let _ = writeln!(
+                                    body_markdown,
+                                    "- lines_now: {}-{} (symbol moved since the last write)",
+                                    snippet.line_start, snippet.line_end
+                                );
+                            }
+                            if let Some(encoding) = &snippet.provenance.transcoded_from {
+                                let _ = writeln!(
+                                    body_markdown,
+                                    "- encoding: {} (transcoded to UTF-8)",
+                                    encoding
+                                );
+                            }
+                            if snippet.provenance.redactions > 0 {
+                                redacted_ref = true;
+                                let _ = writeln!(
+                                    body_markdown,
+                                    "- redacted: true ({} secret(s) masked)",
+                                    snippet.provenance.redactions
+                                );
+                            }
+                            if let Some((stored, current)) = r.content_drift(&snippet.body) {
+                                drifted_ref = true;
+                                let _ = writeln!(
+                                    body_markdown,
+                                    "- drifted: content hash {} at upsert, {} now",
+                                    stored, current
+                                );
+                            }
+                            if let Some(snapshot) = &r.snapshot {
+                                let drift = if snapshot.body == snippet.body {
+                                    "matches source"
+                                } else {
+                                    "source changed since"
+                                };
+                                let _ = writeln!(
+                                    body_markdown,
+                                    "- snapshot: captured_at {} ({})",
+                                    snapshot
+                                        .captured_at
+                                        .to_rfc3339_opts(SecondsFormat::Secs, true),
+                                    drift
+                                );
+                            }
                             if mode == OutputMode::Full {
                                 let lang = lang_from_path(r.path.as_str());
-                                let _ =
-                                    write!(body_markdown, "\n```{}\n{}\n```\n", lang, snippet.body);
+                                let body =
+                                    clip_excerpt(&mut body_markdown, &snippet.body, max_lines);
+                                let _ = write!(
+                                    body_markdown,
+                                    "\n```{}\n{}\n```\n",
+                                    lang,
+                                    cleanup.apply(body)
+                                );
+                                write_provenance_footer(&mut body_markdown, &snippet);
                             }
                         }
-                        Err(DomainError::StaleRef(msg)) => {
+                        Some(Err(DomainError::StaleRef(msg))) => {
                             let _ = write!(body_markdown, "\n> stale ref: {}\n", msg);
                             let _ = writeln!(searchable_text, "{}", msg);
+                            if let Some(snapshot) = &r.snapshot {
+                                let _ = writeln!(searchable_text, "{}", snapshot.body);
+                                let _ = writeln!(
+                                    body_markdown,
+                                    "> serving SNAPSHOTTED excerpt captured at {}; it may not match the current source",
+                                    snapshot
+                                        .captured_at
+                                        .to_rfc3339_opts(SecondsFormat::Secs, true)
+                                );
+                                if mode == OutputMode::Full {
+                                    let lang = lang_from_path(r.path.as_str());
+                                    let body =
+                                        clip_excerpt(&mut body_markdown, &snapshot.body, max_lines);
+                                    let _ = write!(
+                                        body_markdown,
+                                        "\n```{}\n{}\n```\n",
+                                        lang,
+                                        cleanup.apply(body)
+                                    );
+                                    write_snapshot_footer(&mut body_markdown, r, snapshot);
+                                }
+                            }
                         }
-                        Err(e) => return Err(e),
+                        Some(Err(e)) => return Err(e),
+                    }
+
+                    let stale_ref = body_markdown.contains("> stale ref:");
+                    if mode == OutputMode::Outline {
+                        body_markdown = outline_ref_line(r, stale_ref, drifted_ref, redacted_ref);
                     }
 
                     chunks.push(RenderChunk {
@@ -497,10 +1656,14 @@ impl OutputUseCases {
                             group: group_name.clone(),
                         },
                         ref_key: Some(r.key.as_str().to_string()),
-                        stale_ref: body_markdown.contains("> stale ref:"),
+                        stale_ref,
+                        drifted_ref,
+                        redacted_ref,
+                        entry_point: r.entry_point,
                         body_markdown,
                         searchable_text,
                     });
+                    progress::report(chunks.len() as u64, total, section_key.as_str());
                 }
             }
 
@@ -516,30 +1679,259 @@ impl OutputUseCases {
                 }
                 let _ = write!(body_markdown, "```mermaid\n{}\n```\n", diagram.mermaid);
                 let _ = writeln!(searchable_text, "{}", diagram.mermaid);
+                if mode == OutputMode::Outline {
+                    body_markdown = format!("- diagram {}: {}\n", diagram.key, diagram.title);
+                }
+
+                chunks.push(RenderChunk {
+                    section_title: section_title.clone(),
+                    section_key: section_key.clone(),
+                    section_description: section_description.clone(),
+                    kind: ChunkKind::Diagram {
+                        key: diagram.key.as_str().to_string(),
+                    },
+                    ref_key: None,
+                    stale_ref: false,
+                    drifted_ref: false,
+                    redacted_ref: false,
+                    entry_point: false,
+                    body_markdown,
+                    searchable_text,
+                });
+                progress::report(chunks.len() as u64, total, section_key.as_str());
+            }
+
+            for attachment in &section.attachments {
+                let mut body_markdown = String::new();
+                let mut searchable_text = String::new();
+
+                let title = attachment
+                    .title
+                    .as_deref()
+                    .unwrap_or(attachment.key.as_str());
+                let _ = write!(body_markdown, "\n#### {} [{}]\n", title, attachment.key);
+                let _ = writeln!(searchable_text, "{}", title);
+                let _ = writeln!(body_markdown, "- media_type: {}", attachment.media_type);
+                let _ = writeln!(body_markdown, "- bytes: {}", attachment.content.len());
+                let _ = writeln!(searchable_text, "{}", attachment.content);
+                if mode == OutputMode::Outline {
+                    body_markdown = format!("- attachment {}: {}\n", attachment.key, title);
+                } else if mode == OutputMode::Full {
+                    let max_lines = self.excerpt_limits.lines_for_pack(pack);
+                    let body = clip_excerpt(&mut body_markdown, &attachment.content, max_lines);
+                    let _ = write!(
+                        body_markdown,
+                        "\n```{}\n{}\n```\n",
+                        attachment.fence_lang(),
+                        body.trim_end_matches('\n')
+                    );
+                }
 
                 chunks.push(RenderChunk {
                     section_title: section_title.clone(),
                     section_key: section_key.clone(),
                     section_description: section_description.clone(),
-                    kind: ChunkKind::Diagram,
+                    kind: ChunkKind::Attachment {
+                        key: attachment.key.as_str().to_string(),
+                    },
                     ref_key: None,
                     stale_ref: false,
+                    drifted_ref: false,
+                    redacted_ref: false,
+                    entry_point: false,
                     body_markdown,
                     searchable_text,
                 });
+                progress::report(chunks.len() as u64, total, section_key.as_str());
             }
         }
 
         Ok(chunks)
     }
+
+    /// Quotes the section a `pack:<id>#<section_key>` ref points at: its
+    /// title and the first line of its description (clipped) in compact
+    /// reads, the whole description in full ones. A target that is gone
+    /// renders as a stale ref.
+    async fn write_section_ref(
+        &self,
+        body_markdown: &mut String,
+        searchable_text: &mut String,
+        target: &PackSectionRef,
+        mode: OutputMode,
+        lang: Option<&LanguageTag>,
+    ) -> Result<()> {
+        let source = self.repo.get_by_id(&target.pack).await?;
+        let Some(section) = source
+            .as_ref()
+            .and_then(|pack| pack.sections.iter().find(|s| s.key == target.section))
+        else {
+            let _ = write!(
+                body_markdown,
+                "\n> stale ref: {} not found (pack deleted or section removed)\n",
+                target
+            );
+            return Ok(());
+        };
+        let _ = writeln!(body_markdown, "- section: {}", section.title);
+        let _ = writeln!(searchable_text, "{}", section.title);
+        let Some(description) = section
+            .description_for(lang)
+            .filter(|d| !d.trim().is_empty())
+        else {
+            return Ok(());
+        };
+        let _ = writeln!(searchable_text, "{}", description);
+        if mode == OutputMode::Full {
+            let _ = write!(body_markdown, "\n{}\n", description.trim_end());
+        } else {
+            let first_line = description.lines().find(|l| !l.trim().is_empty());
+            let summary = first_line.unwrap_or_default().trim();
+            let clipped: String = summary.chars().take(SECTION_REF_SUMMARY_CHARS).collect();
+            let ellipsis = if clipped.len() < summary.len() || description.trim() != summary {
+                "…"
+            } else {
+                ""
+            };
+            let _ = writeln!(body_markdown, "- summary: {}{}", clipped, ellipsis);
+        }
+        Ok(())
+    }
+}
+
+/// Characters of a quoted section's first description line that compact
+/// reads show.
+const SECTION_REF_SUMMARY_CHARS: usize = 200;
+
+/// One outline line: `- key: path[:start-end]` (or the url or section ref),
+/// then markers.
+fn outline_ref_line(r: &CodeRef, stale: bool, drifted: bool, redacted: bool) -> String {
+    let mut line = format!("- {}: ", r.key);
+    match &r.url {
+        Some(url) => line.push_str(url.as_str()),
+        None if r.path.is_pattern() || r.section_ref.is_some() => line.push_str(r.path.as_str()),
+        None => {
+            let _ = write!(line, "{}:{}-{}", r.path, r.lines.start, r.lines.end);
+        }
+    }
+    if r.entry_point {
+        line.push_str(" [entry_point]");
+    }
+    if stale {
+        line.push_str(" [stale]");
+    }
+    if drifted {
+        line.push_str(" [drifted]");
+    }
+    if redacted {
+        line.push_str(" [redacted]");
+    }
+    line.push('\n');
+    line
+}
+
+/// Share of `max_tokens` kept for the legend and compact summary.
+const LEGEND_TOKEN_RESERVE: usize = 400;
+
+/// Rough token count: about four characters per token.
+fn approx_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// How many of `chunks`, in order, fit in `max_tokens` once the legend
+/// reserve and section headings are counted. At least one, so a page always
+/// makes progress.
+fn chunks_within_token_budget(chunks: &[RenderChunk], max_tokens: usize) -> usize {
+    let mut remaining = max_tokens.saturating_sub(LEGEND_TOKEN_RESERVE);
+    let mut section: Option<&str> = None;
+    for (kept, chunk) in chunks.iter().enumerate() {
+        let mut cost = approx_tokens(&chunk.body_markdown);
+        if section != Some(chunk.section_key.as_str()) {
+            section = Some(chunk.section_key.as_str());
+            cost += approx_tokens(&chunk.section_title)
+                + approx_tokens(&chunk.section_key)
+                + chunk
+                    .section_description
+                    .as_deref()
+                    .map_or(0, approx_tokens)
+                + 4;
+        }
+        if cost > remaining && kept > 0 {
+            return kept;
+        }
+        remaining = remaining.saturating_sub(cost);
+    }
+    chunks.len()
+}
+
+/// First `max_lines` lines of `body`; notes the cut in `out` when there is one.
+fn clip_excerpt<'a>(out: &mut String, body: &'a str, max_lines: usize) -> &'a str {
+    let total = body.lines().count();
+    if total <= max_lines {
+        return body;
+    }
+    let _ = writeln!(
+        out,
+        "- excerpt_truncated: showing {} of {} lines (raise excerpt_line_limit to see more)",
+        max_lines, total
+    );
+    let cut = body
+        .match_indices('\n')
+        .nth(max_lines - 1)
+        .map_or(body.len(), |(i, _)| i);
+    &body[..cut]
+}
+
+fn write_provenance_footer(out: &mut String, snippet: &Snippet) {
+    let provenance = &snippet.provenance;
+    let _ = write!(out, "_provenance: {}", snippet.path);
+    if !glob::is_pattern(&snippet.path) {
+        let _ = write!(out, ":{}-{}", snippet.line_start, snippet.line_end);
+    }
+    let _ = write!(
+        out,
+        " | read_at: {}",
+        provenance
+            .read_at
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+    if let Some(mtime) = provenance.file_mtime {
+        let _ = write!(
+            out,
+            " | mtime: {}",
+            mtime.to_rfc3339_opts(SecondsFormat::Secs, true)
+        );
+    }
+    if let Some(sha) = &provenance.commit_sha {
+        let _ = write!(out, " | commit: {}", sha);
+    }
+    out.push_str("_\n");
 }
 
-fn write_legend_header(out: &mut String, pack: &Pack) {
+fn write_snapshot_footer(out: &mut String, code_ref: &CodeRef, snapshot: &ExcerptSnapshot) {
+    let _ = write!(
+        out,
+        "_snapshot: {}:{}-{} | captured_at: {}",
+        code_ref.path,
+        code_ref.lines.start,
+        code_ref.lines.end,
+        snapshot
+            .captured_at
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+    if let Some(sha) = &snapshot.commit_sha {
+        let _ = write!(out, " | commit: {}", sha);
+    }
+    out.push_str("_\n");
+}
+
+fn write_legend_header(out: &mut String, pack: &Pack, verbosity: LegendVerbosity) {
     let title = pack
         .title
         .as_deref()
         .or(pack.name.as_ref().map(|n| n.as_str()))
         .unwrap_or("Untitled");
+    let full = verbosity == LegendVerbosity::Full;
     let now = chrono::Utc::now();
     let freshness_state = FreshnessState::from_pack(pack, now);
     let _ = write!(out, "# Context pack: {}\n\n", title);
@@ -549,20 +1941,83 @@ fn write_legend_header(out: &mut String, pack: &Pack) {
     }
     let _ = writeln!(out, "- status: {}", pack.status);
     let _ = writeln!(out, "- revision: {}", pack.revision);
-    let _ = writeln!(out, "- expires_at: {}", pack.expires_at.to_rfc3339());
-    let _ = writeln!(out, "- ttl_remaining: {}", pack.ttl_remaining_human(now));
-    let _ = writeln!(out, "- freshness_state: {}", freshness_state);
-    if let Some(warning) = freshness_state.warning_text() {
-        let _ = writeln!(out, "- warning: {}", warning);
+    if full {
+        let _ = writeln!(out, "- expires_at: {}", pack.expires_at.to_rfc3339());
+        let _ = writeln!(out, "- ttl_remaining: {}", pack.ttl_remaining_human(now));
+        if pack.sliding_ttl {
+            match pack.sliding_ttl_minutes {
+                Some(minutes) => {
+                    let _ = writeln!(
+                        out,
+                        "- sliding_ttl: true (reads extend expires_at, {minutes}m window)"
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "- sliding_ttl: true (reads extend expires_at)");
+                }
+            }
+        }
+        if pack.pinned {
+            let _ = writeln!(out, "- pinned: true (never expires)");
+        }
+        let _ = writeln!(out, "- freshness_state: {}", freshness_state);
+        if let Some(warning) = freshness_state.warning_text() {
+            let _ = writeln!(out, "- warning: {}", warning);
+        }
     }
     if !pack.tags.is_empty() {
         let _ = writeln!(out, "- tags: {}", pack.tags.join(", "));
     }
+    if let Some(parent) = &pack.split_from {
+        let _ = writeln!(out, "- split_from: {}", parent);
+    }
+    if !pack.links.is_empty() {
+        let links = pack
+            .links
+            .iter()
+            .map(|link| format!("{} {}", link.kind, link.target))
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "- links: {}", links.join(", "));
+    }
+    if let Some(latest) = pack.sign_offs.last().filter(|_| full) {
+        let _ = writeln!(
+            out,
+            "- sign_offs: {} (latest: {} {} r{})",
+            pack.sign_offs.len(),
+            latest.reviewer,
+            latest.verdict,
+            latest.revision
+        );
+    }
     if let Some(brief) = &pack.brief {
         let _ = writeln!(out, "- brief: {}", brief);
     }
 }
 
+/// Rendered right under the `qa` section heading, oldest first.
+fn write_sign_offs(out: &mut String, pack: &Pack) {
+    if pack.sign_offs.is_empty() {
+        return;
+    }
+    out.push_str("\n### Sign-offs\n");
+    for sign_off in &pack.sign_offs {
+        let _ = write!(
+            out,
+            "- {}: {} (reviewed r{}, {})",
+            sign_off.reviewer,
+            sign_off.verdict,
+            sign_off.revision,
+            sign_off.signed_at.to_rfc3339()
+        );
+        match &sign_off.comment {
+            Some(comment) => {
+                let _ = writeln!(out, " — {}", comment);
+            }
+            None => out.push('\n'),
+        }
+    }
+}
+
 fn write_compact_handoff_summary(
     out: &mut String,
     pack: &Pack,
@@ -677,6 +2132,15 @@ fn compact_risk_signals(
     if !stale_ref_keys.is_empty() {
         risks.push(format!("stale refs: {}", stale_ref_keys.join(", ")));
     }
+    let drifted_ref_keys = filtered_chunks
+        .iter()
+        .filter(|chunk| chunk.drifted_ref)
+        .filter_map(|chunk| chunk.ref_key.as_deref())
+        .take(COMPACT_SIGNAL_LIMIT)
+        .collect::<Vec<_>>();
+    if !drifted_ref_keys.is_empty() {
+        risks.push(format!("drifted refs: {}", drifted_ref_keys.join(", ")));
+    }
 
     risks.extend(keyword_signals(
         pack,
@@ -786,6 +2250,7 @@ fn pack_text_candidates(pack: &Pack) -> Vec<&str> {
         if let Some(description) = section.description.as_deref() {
             out.push(description);
         }
+        out.extend(section.translations.values().map(String::as_str));
         for code_ref in &section.refs {
             out.push(code_ref.key.as_str());
             if let Some(title) = code_ref.title.as_deref() {
@@ -818,6 +2283,104 @@ fn truncate_signal(raw: &str, max_chars: usize) -> String {
     out
 }
 
+/// `## Contents`: one line per section with the chunk `offset` where it
+/// starts and its chunk count, linked to its anchor when anchors are on.
+fn write_table_of_contents(out: &mut String, chunks: &[RenderChunk], anchors: AnchorStyle) {
+    let mut entries: Vec<(&RenderChunk, usize, usize)> = Vec::new();
+    for (offset, chunk) in chunks.iter().enumerate() {
+        match entries.last_mut() {
+            Some((first, _, count)) if first.section_key == chunk.section_key => *count += 1,
+            _ => entries.push((chunk, offset, 1)),
+        }
+    }
+    if entries.len() < TOC_MIN_SECTIONS {
+        return;
+    }
+    out.push_str("\n## Contents\n\n");
+    for (chunk, offset, count) in entries {
+        if anchors == AnchorStyle::None {
+            let _ = write!(out, "- {} [{}]", chunk.section_title, chunk.section_key);
+        } else {
+            let _ = write!(
+                out,
+                "- [{}](#sec-{}) [{}]",
+                chunk.section_title, chunk.section_key, chunk.section_key
+            );
+        }
+        let _ = writeln!(out, ": offset {}, chunks {}", offset, count);
+    }
+}
+
+fn write_anchored_heading(out: &mut String, heading: &str, anchor: &str, style: AnchorStyle) {
+    match style {
+        AnchorStyle::None => {
+            let _ = writeln!(out, "{}", heading);
+        }
+        AnchorStyle::Html => {
+            let _ = writeln!(out, "<a id=\"{}\"></a>\n{}", anchor, heading);
+        }
+        AnchorStyle::Slug => {
+            let _ = writeln!(out, "{} {{#{}}}", heading, anchor);
+        }
+    }
+}
+
+/// Keys added, removed and changed between two keyed lists.
+struct KeyDiff<'a> {
+    added: Vec<&'a str>,
+    removed: Vec<&'a str>,
+    changed: Vec<&'a str>,
+}
+
+impl<'a> KeyDiff<'a> {
+    fn between<T>(
+        before: &'a [T],
+        after: &'a [T],
+        key: impl Fn(&'a T) -> &'a str,
+        same: impl Fn(&T, &T) -> bool,
+    ) -> Self {
+        let mut diff = Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+        for item in after {
+            match before.iter().find(|old| key(old) == key(item)) {
+                None => diff.added.push(key(item)),
+                Some(old) if !same(old, item) => diff.changed.push(key(item)),
+                Some(_) => {}
+            }
+        }
+        for old in before {
+            if !after.iter().any(|item| key(item) == key(old)) {
+                diff.removed.push(key(old));
+            }
+        }
+        diff
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    fn write(&self, out: &mut String, label: &str) {
+        for (verb, keys) in [
+            ("added", &self.added),
+            ("removed", &self.removed),
+            ("changed", &self.changed),
+        ] {
+            write_key_list(out, &format!("{label} {verb}"), keys.iter().copied());
+        }
+    }
+}
+
+fn write_key_list<'a>(out: &mut String, label: &str, keys: impl Iterator<Item = &'a str>) {
+    let keys: Vec<&str> = keys.collect();
+    if !keys.is_empty() {
+        let _ = writeln!(out, "- {}: {}", label, keys.join(", "));
+    }
+}
+
 fn invalid_page_token(reason: impl Into<String>) -> DomainError {
     DomainError::InvalidData(format!("invalid_page_token: {}", reason.into()))
 }
@@ -828,8 +2391,10 @@ fn request_fingerprint(
     status_filter: Option<Status>,
     limit: Option<usize>,
     contains: Option<&str>,
+    layout: RenderLayout,
+    lang: Option<&LanguageTag>,
 ) -> String {
-    format!(
+    let mut fingerprint = format!(
         "profile={}|mode={}|status={}|limit={}|contains={}",
         profile,
         mode,
@@ -840,40 +2405,70 @@ fn request_fingerprint(
             .map(|value| value.to_string())
             .unwrap_or_else(|| "-".to_string()),
         contains.unwrap_or("-")
-    )
+    );
+    if !layout.is_default() {
+        let _ = write!(
+            fingerprint,
+            "|anchors={}|separator={}",
+            layout.anchors, layout.separator
+        );
+        if !layout.excerpt.is_default() {
+            let _ = write!(fingerprint, "|excerpt={}", layout.excerpt);
+        }
+    }
+    if let Some(lang) = lang {
+        let _ = write!(fingerprint, "|lang={}", lang);
+    }
+    fingerprint
 }
 
-fn encode_page_token_v1(page_token: &OutputPageTokenV1) -> Result<String> {
+impl OutputPageToken {
+    fn matches_fingerprint(&self, fingerprint: &str) -> bool {
+        match self.v {
+            1 => self.fingerprint == fingerprint,
+            _ => self.fingerprint == fingerprint_digest(fingerprint),
+        }
+    }
+}
+
+fn fingerprint_digest(fingerprint: &str) -> String {
+    format!("{:016x}", fnv1a_64(fingerprint.as_bytes()))
+}
+
+fn encode_page_token(page_token: &OutputPageToken) -> Result<String> {
     let raw = serde_json::to_vec(page_token)
         .map_err(|e| invalid_page_token(format!("serialization error: {}", e)))?;
-    Ok(format!("v1:{}", hex_encode(&raw)))
+    Ok(format!("v{}:{}", page_token.v, hex_encode(&raw)))
 }
 
-fn decode_page_token_v1(raw: &str) -> Result<OutputPageTokenV1> {
-    let hex = raw
-        .strip_prefix("v1:")
-        .ok_or_else(|| invalid_page_token("unsupported version"))?;
+fn decode_page_token(raw: &str) -> Result<OutputPageToken> {
+    let (version, hex) = raw
+        .strip_prefix('v')
+        .and_then(|rest| rest.split_once(':'))
+        .ok_or_else(|| invalid_page_token("missing version prefix"))?;
+    let version_tag = format!("v{}", version);
+    let version: u8 = version
+        .parse()
+        .map_err(|_| unsupported_page_token(&version_tag))?;
+    if !SUPPORTED_PAGE_TOKEN_VERSIONS.contains(&version) {
+        return Err(unsupported_page_token(&version_tag));
+    }
     let bytes = hex_decode(hex).map_err(invalid_page_token)?;
-    let page_token: OutputPageTokenV1 =
+    let page_token: OutputPageToken =
         serde_json::from_slice(&bytes).map_err(|_| invalid_page_token("malformed payload"))?;
-    if page_token.v != 1 {
-        return Err(invalid_page_token("unsupported version"));
+    if page_token.v != version {
+        return Err(invalid_page_token("version prefix does not match payload"));
     }
     Ok(page_token)
 }
 
-fn profile_mode(profile: OutputProfile) -> OutputMode {
-    match profile {
-        OutputProfile::Reviewer => OutputMode::Full,
-        OutputProfile::Orchestrator | OutputProfile::Executor => OutputMode::Compact,
-    }
-}
-
-fn profile_default_limit(profile: OutputProfile) -> Option<usize> {
-    match profile {
-        OutputProfile::Orchestrator => Some(6),
-        OutputProfile::Executor => Some(12),
-        OutputProfile::Reviewer => None,
+fn unsupported_page_token(version: &str) -> DomainError {
+    DomainError::CursorVersionUnsupported {
+        version: version.to_string(),
+        supported: SUPPORTED_PAGE_TOKEN_VERSIONS
+            .iter()
+            .map(|v| format!("v{}", v))
+            .collect(),
     }
 }
 
@@ -888,6 +2483,119 @@ fn normalize_contains(raw: Option<String>) -> Option<String> {
     })
 }
 
+/// Validator for one rendered page: pack revision plus a hash of everything
+/// else that shapes the page (read args, offset, freshness). Source files are
+/// not hashed, so edits under the source root alone don't change it.
+fn render_etag(pack: &Pack, args: &EffectiveReadArgs) -> String {
+    let shape = format!(
+        "{}|{}|{}|{}|{:?}|{}|{:?}|{}|{}|{}",
+        pack.id,
+        args.fingerprint,
+        args.start_offset,
+        args.paging_active,
+        args.max_tokens,
+        FreshnessState::from_pack(pack, chrono::Utc::now()),
+        args.host,
+        args.pack_defaults,
+        pack.updated_at.to_rfc3339(),
+        pack.expires_at.to_rfc3339(),
+    );
+    format!("r{}-{:016x}", pack.revision, fnv1a_64(shape.as_bytes()))
+}
+
+/// Node classes are `{status}_{freshness}`; finalized packs get a solid fill,
+/// drafts a dashed border, and expiring/expired packs a warm/grey stroke.
+/// Pinned packs look like fresh ones.
+const GRAPH_CLASS_DEFS: [(&str, &str); 8] = [
+    (
+        "draft_fresh",
+        "fill:#fff,stroke:#2b6cb0,stroke-dasharray:4 2",
+    ),
+    (
+        "draft_expiring_soon",
+        "fill:#fff,stroke:#dd6b20,stroke-dasharray:4 2",
+    ),
+    (
+        "draft_expired",
+        "fill:#eee,stroke:#999,stroke-dasharray:4 2,color:#777",
+    ),
+    ("finalized_fresh", "fill:#c6f6d5,stroke:#2f855a"),
+    ("finalized_expiring_soon", "fill:#c6f6d5,stroke:#dd6b20"),
+    ("finalized_expired", "fill:#eee,stroke:#999,color:#777"),
+    (
+        "draft_pinned",
+        "fill:#fff,stroke:#2b6cb0,stroke-dasharray:4 2",
+    ),
+    ("finalized_pinned", "fill:#c6f6d5,stroke:#2f855a"),
+];
+
+fn graph_label(pack: &Pack) -> String {
+    let name = pack
+        .name
+        .as_ref()
+        .map(|n| n.as_str())
+        .or(pack.title.as_deref())
+        .unwrap_or("untitled");
+    // Mermaid labels are quoted; `#quot;` is its entity for a literal quote.
+    format!(
+        "{}<br/>r{} {}",
+        name.replace('"', "#quot;"),
+        pack.revision,
+        pack.status
+    )
+}
+
+fn render_pack_graph(packs: &[Pack], now: chrono::DateTime<chrono::Utc>) -> String {
+    let ids: std::collections::BTreeSet<&str> = packs.iter().map(|p| p.id.as_str()).collect();
+    let (edges, outside): (Vec<_>, Vec<_>) = packs
+        .iter()
+        .flat_map(|pack| pack.links.iter().map(move |link| (pack, link)))
+        .partition(|(_, link)| ids.contains(link.target.as_str()));
+    let mut out = String::new();
+    let _ = writeln!(out, "[LEGEND]");
+    let _ = writeln!(out, "- nodes: {}", packs.len());
+    let _ = writeln!(out, "- edges: {}", edges.len());
+    if !outside.is_empty() {
+        let _ = writeln!(
+            out,
+            "- edge_note: {} link(s) point at packs outside this view and are not drawn",
+            outside.len()
+        );
+    }
+    let _ = writeln!(
+        out,
+        "- styles: finalized=filled, draft=dashed, expiring_soon=orange stroke, expired=grey"
+    );
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[CONTENT]");
+    let _ = writeln!(out, "```mermaid");
+    let _ = writeln!(out, "graph LR");
+    let mut classes: std::collections::BTreeMap<String, Vec<&str>> = Default::default();
+    for pack in packs {
+        let _ = writeln!(out, "  {}[\"{}\"]", pack.id, graph_label(pack));
+        let class = format!("{}_{}", pack.status, FreshnessState::from_pack(pack, now));
+        classes.entry(class).or_default().push(pack.id.as_str());
+    }
+    for (pack, link) in &edges {
+        let _ = writeln!(out, "  {} -->|{}| {}", pack.id, link.kind, link.target);
+    }
+    for (class, style) in GRAPH_CLASS_DEFS {
+        let _ = writeln!(out, "  classDef {} {}", class, style);
+    }
+    for (class, ids) in classes {
+        let _ = writeln!(out, "  class {} {}", ids.join(","), class);
+    }
+    let _ = writeln!(out, "```");
+    out
+}
+
+fn render_not_modified(pack: &Pack, etag: &str) -> String {
+    format!(
+        "[LEGEND]\n- id: {}\n- status: {}\n- revision: {}\n- etag: {}\n- not_modified: true\n\n[CONTENT]\nNot modified since etag {}; reuse the previous render.\n",
+        pack.id, pack.status, pack.revision, etag, etag
+    )
+}
+
 fn hex_encode(bytes: &[u8]) -> String {
     let mut out = String::with_capacity(bytes.len() * 2);
     for byte in bytes {
@@ -925,6 +2633,9 @@ fn decode_hex_digit(byte: u8) -> Option<u8> {
 }
 
 fn lang_from_path(path: &str) -> &'static str {
+    if glob::is_pattern(path) {
+        return "text";
+    }
     let ext = path.rsplit('.').next().unwrap_or("");
     match ext {
         "rs" => "rust",
@@ -950,3 +2661,105 @@ fn lang_from_path(path: &str) -> &'static str {
         _ => "",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(v: u8, fingerprint: String) -> OutputPageToken {
+        OutputPageToken {
+            v,
+            pack_id: "pk_abcdefgh".into(),
+            revision: 3,
+            next_offset: 2,
+            fingerprint,
+            profile: OutputProfile::Orchestrator,
+            status_filter: None,
+            limit: Some(2),
+            contains: None,
+            layout: RenderLayout::default(),
+            lang: None,
+            max_tokens: None,
+        }
+    }
+
+    #[test]
+    fn test_render_profiles_override_builtin_presets_field_by_field() {
+        let profiles = RenderProfiles::parse(
+            r#"{"archive":{"status":"finalized"},"orchestrator":{"limit":0,"legend":"minimal"}}"#,
+        )
+        .unwrap();
+        assert_eq!(profiles.configured(), 2);
+        let archive = profiles.preset(OutputProfile::Archive);
+        assert_eq!(archive.mode, OutputMode::Full);
+        assert_eq!(archive.legend, LegendVerbosity::Minimal);
+        assert_eq!(archive.status, Some(Status::Finalized));
+        let orchestrator = profiles.preset(OutputProfile::Orchestrator);
+        assert_eq!(orchestrator.mode, OutputMode::Compact);
+        assert_eq!(orchestrator.limit, None);
+        assert_eq!(
+            profiles.preset(OutputProfile::Executor),
+            ProfilePreset::builtin(OutputProfile::Executor)
+        );
+
+        assert!(RenderProfiles::parse(r#"{"archive":{"page":3}}"#).is_err());
+        assert!(RenderProfiles::parse(r#"{"auditor":{}}"#).is_err());
+        assert_eq!(RenderProfiles::parse(" ").unwrap().configured(), 0);
+    }
+
+    #[test]
+    fn test_excerpt_cleanup_strips_ansi_expands_tabs_and_trims() {
+        let body = "\x1b[1;31merror\x1b[0m:\tboom  \n\x1b]0;title\x07ab\tc\n  \n\n";
+        let all = ExcerptCleanup {
+            trim_trailing_whitespace: true,
+            tab_width: Some(4),
+            strip_ansi: true,
+        };
+        assert_eq!(all.apply(body), "error:  boom\nab  c");
+        assert_eq!(
+            all.to_string(),
+            "trim_trailing_whitespace,tab_width=4,strip_ansi"
+        );
+
+        let untouched = "fn main() {}";
+        assert!(matches!(all.apply(untouched), Cow::Borrowed(_)));
+        assert_eq!(ExcerptCleanup::default().apply(body), body);
+        assert!(ExcerptCleanup {
+            tab_width: Some(0),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_page_tokens_accept_previous_version_and_reject_unknown_ones() {
+        let fingerprint = "profile=orchestrator|mode=compact|status=-|limit=2|contains=-";
+
+        let current =
+            encode_page_token(&token(PAGE_TOKEN_VERSION, fingerprint_digest(fingerprint))).unwrap();
+        assert!(current.starts_with("v2:"));
+        assert!(decode_page_token(&current)
+            .unwrap()
+            .matches_fingerprint(fingerprint));
+
+        let legacy = encode_page_token(&token(1, fingerprint.to_string())).unwrap();
+        assert!(legacy.starts_with("v1:"));
+        let decoded = decode_page_token(&legacy).unwrap();
+        assert!(decoded.matches_fingerprint(fingerprint));
+        assert!(!decoded.matches_fingerprint("profile=reviewer"));
+
+        let future = current.replacen("v2:", "v3:", 1);
+        match decode_page_token(&future) {
+            Err(DomainError::CursorVersionUnsupported { version, supported }) => {
+                assert_eq!(version, "v3");
+                assert_eq!(supported, vec!["v1", "v2"]);
+            }
+            other => panic!("expected CursorVersionUnsupported, got: {other:?}"),
+        }
+        assert!(matches!(
+            decode_page_token("deadbeef"),
+            Err(DomainError::InvalidData(_))
+        ));
+    }
+}
@@ -1,13 +1,17 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::str::FromStr;
 
 use crate::domain::{
     errors::{DomainError, Result},
-    models::Pack,
-    types::{LineRange, PackId, PackName, RelativePath, Status},
+    models::{CodeRef, Pack},
+    types::{
+        GitSha, LineRange, PackId, PackName, RelativePath, SourceRootName, Status, SymbolName,
+    },
 };
 
 // ── Ports ─────────────────────────────────────────────────────────────────────
@@ -21,25 +25,471 @@ pub trait PackRepositoryPort: Send + Sync {
     async fn get_by_name(&self, name: &PackName) -> Result<Option<Pack>>;
     async fn list_packs(&self, filter: ListFilter) -> Result<Vec<Pack>>;
     async fn purge_expired(&self) -> Result<()>;
+    /// Stores the new pack `created` and saves `updated` over revision
+    /// `expected_revision` as one change: either both land or neither does.
+    ///
+    /// The default creates, then saves, and deletes `created` again when the
+    /// save fails. If that delete fails too, `created` stays stored next to
+    /// the unchanged `updated` and the call fails with a `Conflict` naming it;
+    /// stores that can stage both writes override this.
+    async fn create_with_update(
+        &self,
+        created: &Pack,
+        updated: &Pack,
+        expected_revision: u64,
+    ) -> Result<()> {
+        self.create_new(created).await?;
+        let Err(err) = self
+            .save_with_expected_revision(updated, expected_revision)
+            .await
+        else {
+            return Ok(());
+        };
+        match self.delete_pack_file(&created.id).await {
+            Ok(_) => Err(err),
+            Err(cleanup) => Err(DomainError::Conflict(format!(
+                "created pack {} but saving pack {} failed ({}) and removing the new pack failed too ({}); delete {} by hand",
+                created.id, updated.id, err, cleanup, created.id
+            ))),
+        }
+    }
+    /// Revisions kept in the store's history journal for `id`, oldest first.
+    async fn list_revisions(&self, _id: &PackId) -> Result<Vec<u64>> {
+        Ok(Vec::new())
+    }
+    /// A journaled prior revision of `id`, if the store still holds it.
+    async fn get_revision(&self, _id: &PackId, _revision: u64) -> Result<Option<Pack>> {
+        Ok(None)
+    }
+    /// Moves `id`'s expiry out to `expires_at` in place (never earlier),
+    /// without a new revision or a history entry: a sliding TTL refresh
+    /// must not conflict with writers. Stores that can't do this ignore it.
+    async fn extend_expiry(&self, _id: &PackId, _expires_at: DateTime<Utc>) -> Result<()> {
+        Ok(())
+    }
+    /// Largest encoded pack the store accepts, if it enforces one.
+    fn max_pack_bytes(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Extension point notified after pack changes are stored (see
+/// [`crate::app::lifecycle::HookedRepository`]). Every method defaults to a
+/// no-op, so a hook implements only the events it cares about. The change is
+/// already durable when a hook runs; a failing hook is logged and does not
+/// fail the call that triggered it.
+#[async_trait]
+pub trait PackLifecycleHook: Send + Sync {
+    /// Short identifier used in logs.
+    fn name(&self) -> &str;
+    /// A new pack was stored.
+    async fn on_create(&self, _pack: &Pack) -> Result<()> {
+        Ok(())
+    }
+    /// An existing pack was replaced by `pack`; `previous` is the stored
+    /// revision it replaced, when the store could still read it.
+    async fn on_write(&self, _previous: Option<&Pack>, _pack: &Pack) -> Result<()> {
+        Ok(())
+    }
+    /// A pack was stored as finalized for the first time, by a create or a
+    /// write; runs after `on_create`/`on_write`.
+    async fn on_finalize(&self, _pack: &Pack) -> Result<()> {
+        Ok(())
+    }
+    /// A pack was deleted or purged after expiry.
+    async fn on_delete(&self, _id: &PackId) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Extra finalize rule registered through
+/// [`crate::app::finalize_rules::FinalizeRules`]. Rules run as the last
+/// finalize check, against the pack as it would be stored, and from `input
+/// validate`; every failing rule is reported in `finalize_validation`
+/// details as `failed_rules[{rule, message}]`.
+#[async_trait]
+pub trait ValidatorPort: Send + Sync {
+    /// Rule name reported with its failures.
+    fn name(&self) -> &str;
+    /// Why `pack` breaks the rule, one message per problem; empty passes.
+    async fn validate(&self, pack: &Pack) -> Result<Vec<String>>;
 }
 
 #[async_trait]
 pub trait CodeExcerptPort: Send + Sync {
     /// Safely read bounded lines from a repo-relative path.
     async fn read_lines(&self, path: &RelativePath, range: LineRange) -> Result<Snippet>;
+
+    /// Like [`Self::read_lines`], but as of commit `git_sha` when one is
+    /// given. Sources without history reject pinned reads.
+    async fn read_lines_at(
+        &self,
+        path: &RelativePath,
+        range: LineRange,
+        git_sha: Option<&GitSha>,
+    ) -> Result<Snippet> {
+        match git_sha {
+            None => self.read_lines(path, range).await,
+            Some(sha) => Err(DomainError::InvalidData(format!(
+                "this excerpt source cannot read '{}' at commit {}",
+                path, sha
+            ))),
+        }
+    }
+
+    /// Current line range of `symbol`'s definition in `path` (as of
+    /// `git_sha` when given). A symbol that is not found is a stale ref.
+    async fn resolve_symbol(
+        &self,
+        path: &RelativePath,
+        symbol: &SymbolName,
+        _git_sha: Option<&GitSha>,
+    ) -> Result<LineRange> {
+        Err(DomainError::InvalidData(format!(
+            "this excerpt source cannot resolve symbol '{}' in '{}'",
+            symbol, path
+        )))
+    }
+
+    /// Names of the extra source roots refs may use; empty for single-root
+    /// sources.
+    fn source_roots(&self) -> Vec<SourceRootName> {
+        Vec::new()
+    }
+
+    /// [`Self::read_lines_at`] under the named source root; `None` is the
+    /// default root. Names outside [`Self::source_roots`] make a stale ref.
+    async fn read_lines_in(
+        &self,
+        root: Option<&SourceRootName>,
+        path: &RelativePath,
+        range: LineRange,
+        git_sha: Option<&GitSha>,
+    ) -> Result<Snippet> {
+        match root {
+            None => self.read_lines_at(path, range, git_sha).await,
+            Some(root) => Err(unconfigured_source_root(root)),
+        }
+    }
+
+    /// [`Self::resolve_symbol`] under the named source root.
+    async fn resolve_symbol_in(
+        &self,
+        root: Option<&SourceRootName>,
+        path: &RelativePath,
+        symbol: &SymbolName,
+        git_sha: Option<&GitSha>,
+    ) -> Result<LineRange> {
+        match root {
+            None => self.resolve_symbol(path, symbol, git_sha).await,
+            Some(root) => Err(unconfigured_source_root(root)),
+        }
+    }
+
+    /// Working-tree files under the named source root matching the
+    /// directory/glob `pattern`, sorted by path, at most `limit` of them.
+    async fn list_files_in(
+        &self,
+        _root: Option<&SourceRootName>,
+        pattern: &RelativePath,
+        _limit: usize,
+    ) -> Result<FileListing> {
+        Err(DomainError::InvalidData(format!(
+            "this excerpt source cannot list files matching '{}'",
+            pattern
+        )))
+    }
+
+    /// Reads a ref's excerpt from its source root: its stored lines, or for
+    /// a symbol-anchored ref wherever the symbol's definition is now. A
+    /// directory/glob ref reads as a listing of the files it matches, one
+    /// `path (N lines)` per line, so hashes, snapshots and drift apply to it
+    /// like to any excerpt. Url and section refs have no excerpt; callers
+    /// skip them.
+    async fn read_ref(&self, code_ref: &CodeRef) -> Result<Snippet> {
+        if let Some(url) = &code_ref.url {
+            return Err(DomainError::InvalidData(format!(
+                "ref '{}' links to {} and has no excerpt to read",
+                code_ref.key, url
+            )));
+        }
+        if let Some(target) = &code_ref.section_ref {
+            return Err(DomainError::InvalidData(format!(
+                "ref '{}' quotes {} and has no excerpt to read",
+                code_ref.key, target
+            )));
+        }
+        let root = code_ref.root.as_ref();
+        if code_ref.path.is_pattern() {
+            let listing = self
+                .list_files_in(root, &code_ref.path, MAX_LISTED_FILES)
+                .await?;
+            return listing.into_snippet(code_ref);
+        }
+        let git_sha = code_ref.git_sha.as_ref();
+        let range = match &code_ref.symbol {
+            Some(symbol) => {
+                self.resolve_symbol_in(root, &code_ref.path, symbol, git_sha)
+                    .await?
+            }
+            None => code_ref.lines,
+        };
+        self.read_lines_in(root, &code_ref.path, range, git_sha)
+            .await
+    }
+}
+
+/// Most files a directory/glob ref lists; the rest are only counted.
+pub const MAX_LISTED_FILES: usize = 200;
+
+/// One file matched by a directory/glob ref; `lines` is `None` when the file
+/// is over the source size limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListedFile {
+    pub path: String,
+    pub lines: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileListing {
+    pub files: Vec<ListedFile>,
+    /// Matches before the listing limit was applied.
+    pub total: usize,
+}
+
+impl FileListing {
+    /// The listing as `code_ref`'s excerpt; no matches is a stale ref.
+    fn into_snippet(self, code_ref: &CodeRef) -> Result<Snippet> {
+        if self.total == 0 {
+            return Err(DomainError::StaleRef(format!(
+                "no files match '{}'",
+                code_ref.path
+            )));
+        }
+        let mut lines: Vec<String> = self
+            .files
+            .iter()
+            .map(|file| match file.lines {
+                Some(count) => format!("{} ({} lines)", file.path, count),
+                None => format!("{} (too large to count)", file.path),
+            })
+            .collect();
+        if self.total > self.files.len() {
+            lines.push(format!(
+                "... and {} more file(s)",
+                self.total - self.files.len()
+            ));
+        }
+        Ok(Snippet {
+            path: code_ref.path.as_str().to_string(),
+            line_start: code_ref.lines.start,
+            line_end: code_ref.lines.end,
+            body: lines.join("\n"),
+            total_lines: self.total,
+            provenance: SnippetProvenance::read_now(),
+        })
+    }
+}
+
+/// Read error for a ref whose source root this server doesn't have; a stale
+/// ref, so reads fall back to its snapshot instead of failing.
+pub fn unconfigured_source_root(root: &SourceRootName) -> DomainError {
+    DomainError::StaleRef(format!("source root '{}' is not configured", root))
+}
+
+/// Short-lived persisted record of applied mutations, keyed by
+/// (scope, request id, idempotency key), so a client that replays its last
+/// unacknowledged call after a reconnect gets the original result back.
+#[async_trait]
+pub trait ReplayJournalPort: Send + Sync {
+    /// Recorded tool result for `key`, if it is still inside the replay
+    /// window; `Conflict` when the recorded call had other arguments.
+    async fn lookup(&self, key: &ReplayKey) -> Result<Option<Value>>;
+    async fn record(&self, key: &ReplayKey, result: &Value) -> Result<()>;
+}
+
+/// Point-in-time archive of the pack store, taken under the repository lock
+/// so no pack file is captured mid-write.
+#[async_trait]
+pub trait BackupPort: Send + Sync {
+    async fn backup(&self) -> Result<BackupSummary>;
+}
+
+/// Writes rendered packs to files under a configured export root
+/// (`output read export_path`).
+#[async_trait]
+pub trait MarkdownExportPort: Send + Sync {
+    /// `relative_path` is resolved under the export root and must stay in it.
+    async fn write_export(&self, relative_path: &str, markdown: &str) -> Result<ExportedFile>;
+}
+
+/// Persistent bookkeeping for replication against one remote store: the
+/// revision each pack had when both sides last agreed, plus a quarantine for
+/// packs that diverged on both sides.
+#[async_trait]
+pub trait SyncStatePort: Send + Sync {
+    async fn load_base(&self) -> Result<SyncBase>;
+    async fn save_base(&self, base: &SyncBase) -> Result<()>;
+    /// Stores both sides of a conflict; returns where they were written.
+    async fn quarantine(&self, conflict: &SyncConflict) -> Result<String>;
+}
+
+#[async_trait]
+pub trait SavedFilterPort: Send + Sync {
+    async fn list_filters(&self) -> Result<BTreeMap<String, SavedFilter>>;
+    async fn save_filter(&self, name: &str, filter: &SavedFilter) -> Result<()>;
+    /// Returns whether a filter with that name existed.
+    async fn delete_filter(&self, name: &str) -> Result<bool>;
+}
+
+/// Append-only record of applied mutations, one [`AuditEntry`] per stored
+/// change (see [`crate::app::audit::AuditHook`]).
+#[async_trait]
+pub trait AuditLogPort: Send + Sync {
+    async fn append(&self, entry: &AuditEntry) -> Result<()>;
+    /// The last `limit` entries for `pack_id`, oldest first.
+    async fn tail(&self, pack_id: &PackId, limit: usize) -> Result<Vec<AuditEntry>>;
+}
+
+/// Live checks of what serving needs (writable storage, the repository
+/// lock, source roots), run on demand by `server health`.
+#[async_trait]
+pub trait HealthProbePort: Send + Sync {
+    async fn probe(&self) -> Vec<SubsystemHealth>;
+}
+
+/// Receives progress of a long call (see [`crate::app::progress`]); must not
+/// block, since it is called from the middle of a render.
+pub trait ProgressPort: Send + Sync {
+    fn report(&self, progress: u64, total: u64, message: &str);
 }
 
 // ── Transfer objects ──────────────────────────────────────────────────────────
 
+/// Outcome of one [`HealthProbePort`] check.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubsystemHealth {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+    pub elapsed_ms: u64,
+}
+
+/// What happened to the stored pack in one [`AuditEntry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    Create,
+    Write,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    /// Input action that made the change, or the background task (`purge`,
+    /// `sync`); `unknown` for writers outside either.
+    pub op: String,
+    pub event: AuditEvent,
+    pub pack_id: String,
+    /// Stored revision the change replaced; `None` for creates, and for
+    /// deletes, which don't read the pack first.
+    pub revision_before: Option<u64>,
+    /// `None` for deletes.
+    pub revision_after: Option<u64>,
+    /// Caller identity (`actor`), when the call had one.
+    pub actor: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayKey {
+    /// Caller the key belongs to (transport session or client name), so
+    /// two callers reusing an id and key don't see each other's results.
+    #[serde(default)]
+    pub scope: String,
+    pub request_id: String,
+    pub idempotency_key: String,
+    /// Hash of the call's arguments; a retry must match it.
+    #[serde(default)]
+    pub args_hash: String,
+}
+
+impl ReplayKey {
+    /// Same caller, request id and idempotency key, whatever the arguments.
+    pub fn same_call(&self, other: &ReplayKey) -> bool {
+        self.scope == other.scope
+            && self.request_id == other.request_id
+            && self.idempotency_key == other.idempotency_key
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupSummary {
+    pub archive_path: String,
+    pub pack_count: usize,
+    /// Other storage-root files archived with the packs: history, archive,
+    /// audit log, journals, saved filters, sync state.
+    pub file_count: usize,
+    pub bytes: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportedFile {
+    /// Absolute path of the written file.
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// Pack id → revision both stores held after the last successful sync.
+pub type SyncBase = BTreeMap<String, u64>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub pack_id: String,
+    pub reason: String,
+    pub base_revision: Option<u64>,
+    pub local: Option<Pack>,
+    pub remote: Option<Pack>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ListFilter {
     pub status: Option<Status>,
     pub freshness: Option<FreshnessState>,
     pub query: Option<String>,
+    /// Packs must carry every listed tag (case-insensitive).
+    pub tags: Vec<String>,
+    /// Without a freshness filter, also list expired packs still inside the
+    /// grace window; `None` uses the store's default (hidden unless configured).
+    pub include_expired: Option<bool>,
+    /// List packs the store archived after expiry instead (see
+    /// `CONTEXT_PACK_EXPIRED_RETENTION`); freshness filters don't apply.
+    pub archived: bool,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
 }
 
+/// Named, server-side list filter (`list filter=<name>`). Fields set
+/// explicitly on the list call override the saved ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<FreshnessState>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+}
+
+impl SavedFilter {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum FreshnessState {
@@ -47,6 +497,8 @@ pub enum FreshnessState {
     Fresh,
     ExpiringSoon,
     Expired,
+    /// Pinned with `ttl pin=true`; never expires.
+    Pinned,
 }
 
 impl FreshnessState {
@@ -63,12 +515,15 @@ impl FreshnessState {
     }
 
     pub fn from_pack(pack: &Pack, now: DateTime<Utc>) -> Self {
+        if pack.pinned {
+            return Self::Pinned;
+        }
         Self::from_ttl_seconds(pack.ttl_remaining_seconds(now))
     }
 
     pub fn warning_text(self) -> Option<&'static str> {
         match self {
-            Self::Fresh => None,
+            Self::Fresh | Self::Pinned => None,
             Self::ExpiringSoon => Some("expiring soon — refresh or extend ttl"),
             Self::Expired => Some("expired — treat as stale evidence"),
         }
@@ -81,6 +536,7 @@ impl fmt::Display for FreshnessState {
             Self::Fresh => write!(f, "fresh"),
             Self::ExpiringSoon => write!(f, "expiring_soon"),
             Self::Expired => write!(f, "expired"),
+            Self::Pinned => write!(f, "pinned"),
         }
     }
 }
@@ -93,8 +549,9 @@ impl FromStr for FreshnessState {
             "fresh" => Ok(Self::Fresh),
             "expiring_soon" => Ok(Self::ExpiringSoon),
             "expired" => Ok(Self::Expired),
+            "pinned" => Ok(Self::Pinned),
             other => Err(DomainError::InvalidData(format!(
-                "'freshness' must be one of: fresh, expiring_soon, expired (got '{}')",
+                "'freshness' must be one of: fresh, expiring_soon, expired, pinned (got '{}')",
                 other
             ))),
         }
@@ -103,7 +560,80 @@ impl FromStr for FreshnessState {
 
 #[cfg(test)]
 mod tests {
-    use super::FreshnessState;
+    use super::{FreshnessState, ListFilter, PackRepositoryPort};
+    use crate::adapters::storage_memory::InMemoryStorageAdapter;
+    use crate::domain::errors::{DomainError, Result};
+    use crate::domain::models::Pack;
+    use crate::domain::types::{PackId, PackName};
+    use async_trait::async_trait;
+
+    /// Leaves `create_with_update` to the trait default; deletes can fail.
+    struct DefaultSplitRepo {
+        inner: InMemoryStorageAdapter,
+        deletes_fail: bool,
+    }
+
+    #[async_trait]
+    impl PackRepositoryPort for DefaultSplitRepo {
+        async fn create_new(&self, pack: &Pack) -> Result<()> {
+            self.inner.create_new(pack).await
+        }
+        async fn save_with_expected_revision(&self, pack: &Pack, expected: u64) -> Result<()> {
+            self.inner.save_with_expected_revision(pack, expected).await
+        }
+        async fn delete_pack_file(&self, id: &PackId) -> Result<bool> {
+            if self.deletes_fail {
+                return Err(DomainError::Io("disk gone".into()));
+            }
+            self.inner.delete_pack_file(id).await
+        }
+        async fn get_by_id(&self, id: &PackId) -> Result<Option<Pack>> {
+            self.inner.get_by_id(id).await
+        }
+        async fn get_by_name(&self, name: &PackName) -> Result<Option<Pack>> {
+            self.inner.get_by_name(name).await
+        }
+        async fn list_packs(&self, filter: ListFilter) -> Result<Vec<Pack>> {
+            self.inner.list_packs(filter).await
+        }
+        async fn purge_expired(&self) -> Result<()> {
+            self.inner.purge_expired().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_create_with_update_rolls_back_or_names_the_leftover_pack() {
+        for deletes_fail in [false, true] {
+            let repo = DefaultSplitRepo {
+                inner: InMemoryStorageAdapter::new(),
+                deletes_fail,
+            };
+            let parent = Pack::new(PackId::new(), None);
+            repo.create_new(&parent).await.unwrap();
+            let child = Pack::new(PackId::new(), None);
+
+            let err = repo
+                .create_with_update(&child, &parent, 5)
+                .await
+                .unwrap_err();
+            let leftover = repo.get_by_id(&child.id).await.unwrap();
+            if deletes_fail {
+                assert!(matches!(err, DomainError::Conflict(_)), "{err:?}");
+                assert!(
+                    err.to_string()
+                        .contains(&format!("delete {} by hand", child.id)),
+                    "{err}"
+                );
+                assert!(leftover.is_some());
+            } else {
+                assert!(
+                    matches!(err, DomainError::RevisionConflictDetailed { .. }),
+                    "{err:?}"
+                );
+                assert!(leftover.is_none());
+            }
+        }
+    }
 
     #[test]
     fn freshness_state_boundaries_are_stable() {
@@ -135,4 +665,36 @@ pub struct Snippet {
     /// Numbered lines: "   5: fn foo() {"
     pub body: String,
     pub total_lines: usize,
+    pub provenance: SnippetProvenance,
+}
+
+/// Where and when an excerpt was read, rendered under full-mode excerpts so
+/// pasted evidence stays traceable on its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetProvenance {
+    pub read_at: DateTime<Utc>,
+    pub file_mtime: Option<DateTime<Utc>>,
+    pub commit_sha: Option<String>,
+    /// Source encoding when the file was not UTF-8 and had to be transcoded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcoded_from: Option<String>,
+    /// Secrets the excerpt adapter masked in the body.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub redactions: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+impl SnippetProvenance {
+    pub fn read_now() -> Self {
+        Self {
+            read_at: Utc::now(),
+            file_mtime: None,
+            commit_sha: None,
+            transcoded_from: None,
+            redactions: 0,
+        }
+    }
 }
@@ -0,0 +1,51 @@
+//! Progress of long calls. The transport scopes a call with a
+//! [`ProgressPort`] when the client asked for progress; use cases report
+//! into whichever one the current task runs under, and reports outside a
+//! scope go nowhere.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::app::ports::ProgressPort;
+
+tokio::task_local! {
+    static SINK: Arc<dyn ProgressPort>;
+}
+
+/// Runs `future` with `sink` receiving the progress it reports.
+pub async fn scope<F: Future>(sink: Arc<dyn ProgressPort>, future: F) -> F::Output {
+    SINK.scope(sink, future).await
+}
+
+/// Reports `progress` out of `total` to the current scope, if any.
+pub fn report(progress: u64, total: u64, message: &str) {
+    let _ = SINK.try_with(|sink| sink.report(progress, total, message));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(u64, u64)>>);
+
+    impl ProgressPort for Recorder {
+        fn report(&self, progress: u64, total: u64, _message: &str) {
+            self.0.lock().unwrap().push((progress, total));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reports_reach_only_the_scoped_sink() {
+        let recorder = Arc::new(Recorder::default());
+        report(1, 3, "outside");
+        scope(recorder.clone(), async {
+            report(1, 2, "first");
+            report(2, 2, "second");
+        })
+        .await;
+        report(3, 3, "after");
+        assert_eq!(*recorder.0.lock().unwrap(), vec![(1, 2), (2, 2)]);
+    }
+}
@@ -48,6 +48,33 @@ pub async fn resolve_pack(repo: &dyn PackRepositoryPort, identifier: &str) -> Re
     )))
 }
 
+/// A journaled prior revision of `pack`; [`DomainError::NotFound`] lists the
+/// revisions the store still holds when `revision` is not among them.
+pub async fn resolve_revision(
+    repo: &dyn PackRepositoryPort,
+    pack: &Pack,
+    revision: u64,
+) -> Result<Pack> {
+    if let Some(historic) = repo.get_revision(&pack.id, revision).await? {
+        return Ok(historic);
+    }
+    let available = repo.list_revisions(&pack.id).await?;
+    Err(DomainError::NotFound(format!(
+        "revision {} of pack '{}' is not in the history journal (available: {})",
+        revision,
+        pack.id,
+        if available.is_empty() {
+            "none".to_string()
+        } else {
+            available
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
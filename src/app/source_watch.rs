@@ -0,0 +1,272 @@
+//! Source changes seen by a file watcher. The watcher reports changed
+//! files; [`SourceWatch`] works out which pack refs point at them, marks
+//! those refs as possibly stale and broadcasts the result, so transports can
+//! tell subscribed clients before they next read the pack.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::app::ports::{ListFilter, PackRepositoryPort};
+use crate::domain::{
+    errors::Result,
+    glob,
+    models::Pack,
+    types::{PackId, SourceRootName},
+};
+
+/// Batches a subscriber may fall behind by before it misses some.
+const BROADCAST_CAPACITY: usize = 64;
+
+/// Per pack: when its first marked change was seen, and the marked ref keys.
+type Marks = HashMap<PackId, (DateTime<Utc>, BTreeSet<String>)>;
+
+/// A file created, modified or removed under a source root.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SourceChange {
+    /// `None` for the default root.
+    pub root: Option<SourceRootName>,
+    /// `/`-separated and relative to the root.
+    pub path: String,
+}
+
+/// Refs of one pack whose source changed since the pack was last written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StaleRefs {
+    pub pack_id: PackId,
+    pub ref_keys: Vec<String>,
+    /// When the first of these changes was seen.
+    pub since: DateTime<Utc>,
+}
+
+/// Refs in `packs` that read one of the `changes`: file refs by path, and
+/// directory/glob refs by pattern. Refs pinned to a commit read history,
+/// not the working tree, so they are never affected.
+pub fn affected_refs(
+    packs: &[Pack],
+    changes: &[SourceChange],
+    now: DateTime<Utc>,
+) -> Vec<StaleRefs> {
+    let mut affected = Vec::new();
+    for pack in packs {
+        let ref_keys: Vec<String> = pack
+            .sections
+            .iter()
+            .flat_map(|section| &section.refs)
+            .filter(|r| r.reads_source() && r.git_sha.is_none())
+            .filter(|r| {
+                changes.iter().any(|change| {
+                    change.root == r.root
+                        && if r.path.is_pattern() {
+                            glob::matches(r.path.as_str(), &change.path)
+                        } else {
+                            r.path.as_str() == change.path
+                        }
+                })
+            })
+            .map(|r| r.key.as_str().to_string())
+            .collect();
+        if !ref_keys.is_empty() {
+            affected.push(StaleRefs {
+                pack_id: pack.id.clone(),
+                ref_keys,
+                since: now,
+            });
+        }
+    }
+    affected
+}
+
+/// Refs marked possibly stale, and the feed of new marks. A mark lasts
+/// until its pack is written again. Shared by the watcher and every
+/// session.
+pub struct SourceWatch {
+    marks: Mutex<Marks>,
+    updates: broadcast::Sender<StaleRefs>,
+}
+
+impl Default for SourceWatch {
+    fn default() -> Self {
+        Self {
+            marks: Mutex::new(HashMap::new()),
+            updates: broadcast::channel(BROADCAST_CAPACITY).0,
+        }
+    }
+}
+
+impl SourceWatch {
+    /// New marks as they are recorded, one [`StaleRefs`] per pack.
+    pub fn subscribe(&self) -> broadcast::Receiver<StaleRefs> {
+        self.updates.subscribe()
+    }
+
+    /// Marks the refs in the live packs of `repo` that read one of
+    /// `changes`, drops marks of packs written since they were set, and
+    /// broadcasts the packs that gained marks.
+    pub async fn record(
+        &self,
+        repo: &dyn PackRepositoryPort,
+        changes: &[SourceChange],
+    ) -> Result<Vec<StaleRefs>> {
+        let packs = repo.list_packs(ListFilter::default()).await?;
+        let now = Utc::now();
+        let affected = affected_refs(&packs, changes, now);
+        {
+            let mut marks = self.state();
+            marks.retain(|id, (since, _)| {
+                packs
+                    .iter()
+                    .find(|pack| &pack.id == id)
+                    .is_some_and(|pack| pack.updated_at <= *since)
+            });
+            for stale in &affected {
+                let (_, keys) = marks
+                    .entry(stale.pack_id.clone())
+                    .or_insert_with(|| (now, BTreeSet::new()));
+                keys.extend(stale.ref_keys.iter().cloned());
+            }
+        }
+        for stale in &affected {
+            // No subscribers is fine: the marks stay queryable.
+            let _ = self.updates.send(stale.clone());
+        }
+        Ok(affected)
+    }
+
+    /// Every pack with marked refs, by id.
+    pub fn marked(&self) -> Vec<StaleRefs> {
+        let mut marked: Vec<StaleRefs> = self
+            .state()
+            .iter()
+            .map(|(id, (since, keys))| StaleRefs {
+                pack_id: id.clone(),
+                ref_keys: keys.iter().cloned().collect(),
+                since: *since,
+            })
+            .collect();
+        marked.sort_by(|a, b| a.pack_id.as_str().cmp(b.pack_id.as_str()));
+        marked
+    }
+
+    fn state(&self) -> MutexGuard<'_, Marks> {
+        self.marks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::storage_memory::InMemoryStorageAdapter;
+    use crate::domain::models::{CodeRef, Section};
+    use crate::domain::types::{GitSha, LineRange, RefKey, RelativePath, SectionKey};
+
+    fn code_ref(key: &str, root: Option<&str>, path: &str) -> CodeRef {
+        CodeRef {
+            key: RefKey::new(key).unwrap(),
+            root: root.map(|name| SourceRootName::new(name).unwrap()),
+            path: RelativePath::new(path).unwrap(),
+            lines: LineRange::new(1, 10).unwrap(),
+            title: None,
+            why: None,
+            group: None,
+            entry_point: false,
+            git_sha: None,
+            symbol: None,
+            url: None,
+            section_ref: None,
+            excerpt_line_limit: None,
+            snapshot: None,
+            content_hash: None,
+            changed_revision: None,
+        }
+    }
+
+    fn pack_with(refs: Vec<CodeRef>) -> Pack {
+        let mut pack = Pack::new(PackId::new(), None);
+        pack.sections = vec![Section {
+            key: SectionKey::new("scope").unwrap(),
+            title: "Scope".into(),
+            description: None,
+            translations: Default::default(),
+            refs,
+            diagrams: Vec::new(),
+            attachments: Vec::new(),
+            changed_revision: None,
+        }];
+        pack
+    }
+
+    fn change(root: Option<&str>, path: &str) -> SourceChange {
+        SourceChange {
+            root: root.map(|name| SourceRootName::new(name).unwrap()),
+            path: path.into(),
+        }
+    }
+
+    #[test]
+    fn test_affected_refs_match_files_and_patterns_in_their_root() {
+        let mut pinned = code_ref("pinned", None, "src/lib.rs");
+        pinned.git_sha = Some(GitSha::new("abc1234").unwrap());
+        let pack = pack_with(vec![
+            code_ref("file", None, "src/lib.rs"),
+            code_ref("tree", None, "src/**/*.rs"),
+            code_ref("other_root", Some("docs"), "src/lib.rs"),
+            code_ref("untouched", None, "src/main.rs"),
+            pinned,
+        ]);
+        let quiet = pack_with(vec![code_ref("readme", None, "README.md")]);
+        let now = Utc::now();
+
+        let affected = affected_refs(&[pack.clone(), quiet], &[change(None, "src/lib.rs")], now);
+        assert_eq!(
+            affected,
+            vec![StaleRefs {
+                pack_id: pack.id.clone(),
+                ref_keys: vec!["file".into(), "tree".into()],
+                since: now,
+            }]
+        );
+
+        let affected = affected_refs(&[pack], &[change(Some("docs"), "src/lib.rs")], now);
+        assert_eq!(affected[0].ref_keys, vec!["other_root".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_marks_broadcast_and_clear_when_the_pack_is_written() {
+        let store = InMemoryStorageAdapter::new();
+        let pack = pack_with(vec![code_ref("file", None, "src/lib.rs")]);
+        store.create_new(&pack).await.unwrap();
+        let watch = SourceWatch::default();
+        let mut updates = watch.subscribe();
+
+        let affected = watch
+            .record(&store, &[change(None, "src/lib.rs")])
+            .await
+            .unwrap();
+        assert_eq!(affected.len(), 1);
+        assert_eq!(updates.try_recv().unwrap(), affected[0]);
+        assert_eq!(watch.marked(), affected);
+
+        watch
+            .record(&store, &[change(None, "README.md")])
+            .await
+            .unwrap();
+        assert_eq!(watch.marked().len(), 1);
+        assert!(updates.try_recv().is_err());
+
+        let mut next = pack.clone();
+        next.revision = 2;
+        next.updated_at = Utc::now() + chrono::Duration::seconds(1);
+        store.save_with_expected_revision(&next, 1).await.unwrap();
+        watch
+            .record(&store, &[change(None, "README.md")])
+            .await
+            .unwrap();
+        assert!(watch.marked().is_empty());
+    }
+}
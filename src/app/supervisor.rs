@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::domain::errors::Result;
+
+/// Consecutive failures after which a task reports as degraded.
+pub const DEGRADED_AFTER_FAILURES: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    /// The last pass succeeded (or none has finished yet).
+    Running,
+    /// The last pass failed; the next one waits out a backoff.
+    Backoff,
+    /// [`DEGRADED_AFTER_FAILURES`] or more passes in a row failed.
+    Degraded,
+}
+
+/// What a supervised task has done since the server started.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskHealth {
+    pub name: String,
+    pub state: TaskState,
+    pub passes: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failure_at: Option<DateTime<Utc>>,
+    /// The last error or panic message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl TaskHealth {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            state: TaskState::Running,
+            passes: 0,
+            failures: 0,
+            consecutive_failures: 0,
+            last_success_at: None,
+            last_failure_at: None,
+            last_error: None,
+        }
+    }
+}
+
+/// Runs background loops (TTL purge, sync) one pass at a time. Each pass
+/// runs as its own task, so an error or a panic ends only that pass: the
+/// supervisor records it and runs the next one after an exponential backoff
+/// instead of letting the loop die silently. Clones share state.
+#[derive(Clone)]
+pub struct TaskSupervisor {
+    tasks: Arc<Mutex<BTreeMap<String, TaskHealth>>>,
+    backoff_base: Duration,
+    backoff_max: Duration,
+}
+
+impl Default for TaskSupervisor {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_BACKOFF_BASE, Self::DEFAULT_BACKOFF_MAX)
+    }
+}
+
+impl TaskSupervisor {
+    pub const DEFAULT_BACKOFF_BASE: Duration = Duration::from_secs(1);
+    pub const DEFAULT_BACKOFF_MAX: Duration = Duration::from_secs(300);
+
+    pub fn new(backoff_base: Duration, backoff_max: Duration) -> Self {
+        Self {
+            tasks: Arc::new(Mutex::new(BTreeMap::new())),
+            backoff_base,
+            backoff_max,
+        }
+    }
+
+    /// Runs `pass` now and then every `period`; after a failed pass the next
+    /// one follows the backoff (base doubling per consecutive failure, capped
+    /// at the max) instead.
+    pub fn supervise<F, Fut>(
+        &self,
+        name: &str,
+        period: Duration,
+        pass: F,
+    ) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.state().insert(name.to_string(), TaskHealth::new(name));
+        let supervisor = self.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            loop {
+                let outcome = match tokio::spawn(pass()).await {
+                    Ok(result) => result.map_err(|e| e.to_string()),
+                    Err(join) => Err(panic_message(join)),
+                };
+                let wait = match outcome {
+                    Ok(()) => {
+                        supervisor.record_success(&name);
+                        period
+                    }
+                    Err(message) => supervisor.record_failure(&name, message),
+                };
+                tokio::time::sleep(wait).await;
+            }
+        })
+    }
+
+    /// Every supervised task, by name.
+    pub fn health(&self) -> Vec<TaskHealth> {
+        self.state().values().cloned().collect()
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.state()
+            .values()
+            .any(|task| task.state == TaskState::Degraded)
+    }
+
+    fn record_success(&self, name: &str) {
+        let mut tasks = self.state();
+        let task = tasks
+            .entry(name.to_string())
+            .or_insert_with(|| TaskHealth::new(name));
+        if task.consecutive_failures > 0 {
+            tracing::info!(
+                "background task '{}' recovered after {} failed pass(es)",
+                name,
+                task.consecutive_failures
+            );
+        }
+        task.passes += 1;
+        task.consecutive_failures = 0;
+        task.state = TaskState::Running;
+        task.last_success_at = Some(Utc::now());
+    }
+
+    /// Records a failed pass; returns how long to wait before the next one.
+    fn record_failure(&self, name: &str, message: String) -> Duration {
+        let mut tasks = self.state();
+        let task = tasks
+            .entry(name.to_string())
+            .or_insert_with(|| TaskHealth::new(name));
+        task.passes += 1;
+        task.failures += 1;
+        task.consecutive_failures = task.consecutive_failures.saturating_add(1);
+        task.state = if task.consecutive_failures >= DEGRADED_AFTER_FAILURES {
+            TaskState::Degraded
+        } else {
+            TaskState::Backoff
+        };
+        task.last_failure_at = Some(Utc::now());
+        let backoff = self.backoff(task.consecutive_failures);
+        tracing::warn!(
+            "background task '{}' failed ({} in a row), retrying in {:?}: {}",
+            name,
+            task.consecutive_failures,
+            backoff,
+            message
+        );
+        task.last_error = Some(message);
+        backoff
+    }
+
+    fn backoff(&self, consecutive_failures: u32) -> Duration {
+        let factor = 2u32.saturating_pow(consecutive_failures.saturating_sub(1).min(16));
+        self.backoff_base
+            .saturating_mul(factor)
+            .min(self.backoff_max)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, TaskHealth>> {
+        self.tasks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn panic_message(join: tokio::task::JoinError) -> String {
+    if !join.is_panic() {
+        return "pass was cancelled".to_string();
+    }
+    let payload = join.into_panic();
+    let detail = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string payload".to_string());
+    format!("panicked: {detail}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::errors::DomainError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_supervisor_restarts_failing_passes_with_backoff() {
+        let supervisor = TaskSupervisor::new(Duration::from_millis(1), Duration::from_millis(4));
+        assert_eq!(supervisor.backoff(1), Duration::from_millis(1));
+        assert_eq!(supervisor.backoff(2), Duration::from_millis(2));
+        assert_eq!(supervisor.backoff(10), Duration::from_millis(4));
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let handle = supervisor.supervise("flaky", Duration::from_secs(3600), move || {
+            let call = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match call {
+                    0 => panic!("boom"),
+                    1 | 2 => Err(DomainError::Io("disk full".into())),
+                    _ => Ok(()),
+                }
+            }
+        });
+
+        for _ in 0..200 {
+            if supervisor.health()[0].last_success_at.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        handle.abort();
+
+        let task = &supervisor.health()[0];
+        assert_eq!(task.name, "flaky");
+        assert_eq!(task.state, TaskState::Running);
+        assert_eq!((task.passes, task.failures), (4, 3));
+        assert_eq!(task.consecutive_failures, 0);
+        assert!(task.last_error.as_deref().unwrap().contains("disk full"));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert!(!supervisor.is_degraded());
+    }
+
+    #[test]
+    fn test_supervisor_reports_degraded_after_repeated_failures() {
+        let supervisor = TaskSupervisor::default();
+        supervisor.record_failure("purge", "panicked: boom".into());
+        supervisor.record_failure("purge", "io".into());
+        assert_eq!(supervisor.health()[0].state, TaskState::Backoff);
+        assert!(!supervisor.is_degraded());
+        supervisor.record_failure("purge", "io".into());
+        assert_eq!(supervisor.health()[0].state, TaskState::Degraded);
+        assert!(supervisor.is_degraded());
+        supervisor.record_success("purge");
+        assert!(!supervisor.is_degraded());
+    }
+}
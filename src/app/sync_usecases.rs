@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    app::ports::{ListFilter, PackRepositoryPort, SyncConflict, SyncStatePort},
+    domain::{errors::Result, models::Pack},
+};
+
+/// Replicates packs between a local store and a remote (e.g. shared network)
+/// store.
+///
+/// The side with the higher revision wins. A pack is a conflict, and is left
+/// untouched on both sides and quarantined instead, when both sides changed
+/// since the last agreed revision, or when both hold the same revision with
+/// different content. Deletions are not propagated: a pack missing on one side
+/// is copied over from the other.
+pub struct SyncUseCases {
+    local: Arc<dyn PackRepositoryPort>,
+    remote: Arc<dyn PackRepositoryPort>,
+    state: Arc<dyn SyncStatePort>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub synced_at: Option<DateTime<Utc>>,
+    /// Pack ids copied local → remote.
+    pub pushed: Vec<String>,
+    /// Pack ids copied remote → local.
+    pub pulled: Vec<String>,
+    pub unchanged: usize,
+    pub conflicts: Vec<SyncConflictSummary>,
+    pub errors: Vec<SyncError>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncConflictSummary {
+    pub pack_id: String,
+    pub reason: String,
+    pub local_revision: Option<u64>,
+    pub remote_revision: Option<u64>,
+    pub quarantined_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncError {
+    pub pack_id: String,
+    pub message: String,
+}
+
+enum SyncAction {
+    Unchanged(u64),
+    Push { expected: Option<u64>, pack: Pack },
+    Pull { expected: Option<u64>, pack: Pack },
+    Conflict(&'static str),
+}
+
+fn same_content(a: &Pack, b: &Pack) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+fn plan(local: Option<&Pack>, remote: Option<&Pack>, base: Option<u64>) -> SyncAction {
+    match (local, remote) {
+        (Some(l), None) => SyncAction::Push {
+            expected: None,
+            pack: l.clone(),
+        },
+        (None, Some(r)) => SyncAction::Pull {
+            expected: None,
+            pack: r.clone(),
+        },
+        (Some(l), Some(r)) if l.revision == r.revision => {
+            if same_content(l, r) {
+                SyncAction::Unchanged(l.revision)
+            } else {
+                SyncAction::Conflict("same_revision_different_content")
+            }
+        }
+        (Some(l), Some(r)) => {
+            let both_changed = base.is_some_and(|b| l.revision > b && r.revision > b);
+            if both_changed {
+                SyncAction::Conflict("both_changed_since_last_sync")
+            } else if l.revision > r.revision {
+                SyncAction::Push {
+                    expected: Some(r.revision),
+                    pack: l.clone(),
+                }
+            } else {
+                SyncAction::Pull {
+                    expected: Some(l.revision),
+                    pack: r.clone(),
+                }
+            }
+        }
+        (None, None) => unreachable!("pack id comes from one of the stores"),
+    }
+}
+
+async fn copy_to(
+    target: &dyn PackRepositoryPort,
+    pack: &Pack,
+    expected: Option<u64>,
+) -> Result<()> {
+    match expected {
+        Some(revision) => target.save_with_expected_revision(pack, revision).await,
+        None => target.create_new(pack).await,
+    }
+}
+
+impl SyncUseCases {
+    pub fn new(
+        local: Arc<dyn PackRepositoryPort>,
+        remote: Arc<dyn PackRepositoryPort>,
+        state: Arc<dyn SyncStatePort>,
+    ) -> Self {
+        Self {
+            local,
+            remote,
+            state,
+        }
+    }
+
+    /// One sync pass. Per-pack write failures are collected in
+    /// [`SyncReport::errors`] rather than aborting the pass.
+    pub async fn sync_once(&self) -> Result<SyncReport> {
+        let mut base = self.state.load_base().await?;
+        let local = index(self.local.list_packs(ListFilter::default()).await?);
+        let remote = index(self.remote.list_packs(ListFilter::default()).await?);
+
+        let mut ids: Vec<&String> = local.keys().chain(remote.keys()).collect();
+        ids.sort();
+        ids.dedup();
+
+        let mut report = SyncReport {
+            synced_at: Some(Utc::now()),
+            ..SyncReport::default()
+        };
+        for id in ids {
+            let (l, r) = (local.get(id), remote.get(id));
+            match plan(l, r, base.get(id).copied()) {
+                SyncAction::Unchanged(revision) => {
+                    report.unchanged += 1;
+                    base.insert(id.clone(), revision);
+                }
+                SyncAction::Push { expected, pack } => {
+                    match copy_to(self.remote.as_ref(), &pack, expected).await {
+                        Ok(()) => {
+                            report.pushed.push(id.clone());
+                            base.insert(id.clone(), pack.revision);
+                        }
+                        Err(e) => report.errors.push(SyncError {
+                            pack_id: id.clone(),
+                            message: e.to_string(),
+                        }),
+                    }
+                }
+                SyncAction::Pull { expected, pack } => {
+                    match copy_to(self.local.as_ref(), &pack, expected).await {
+                        Ok(()) => {
+                            report.pulled.push(id.clone());
+                            base.insert(id.clone(), pack.revision);
+                        }
+                        Err(e) => report.errors.push(SyncError {
+                            pack_id: id.clone(),
+                            message: e.to_string(),
+                        }),
+                    }
+                }
+                SyncAction::Conflict(reason) => {
+                    let conflict = SyncConflict {
+                        pack_id: id.clone(),
+                        reason: reason.to_string(),
+                        base_revision: base.get(id).copied(),
+                        local: l.cloned(),
+                        remote: r.cloned(),
+                    };
+                    let quarantined_at = self.state.quarantine(&conflict).await?;
+                    report.conflicts.push(SyncConflictSummary {
+                        pack_id: id.clone(),
+                        reason: conflict.reason,
+                        local_revision: l.map(|p| p.revision),
+                        remote_revision: r.map(|p| p.revision),
+                        quarantined_at,
+                    });
+                }
+            }
+        }
+
+        self.state.save_base(&base).await?;
+        Ok(report)
+    }
+}
+
+fn index(packs: Vec<Pack>) -> BTreeMap<String, Pack> {
+    packs
+        .into_iter()
+        .map(|pack| (pack.id.as_str().to_string(), pack))
+        .collect()
+}
@@ -14,6 +14,14 @@ pub struct FinalizeRefIssue {
     pub reason: String,
 }
 
+/// A registered finalize rule (see [`crate::app::ports::ValidatorPort`])
+/// the pack does not satisfy.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FinalizeRuleFailure {
+    pub rule: String,
+    pub message: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RevisionConflictDiagnostics {
     pub expected_revision: u64,
@@ -21,12 +29,18 @@ pub struct RevisionConflictDiagnostics {
     pub last_updated_at: String,
     pub changed_section_keys: Vec<String>,
     pub guidance: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub produced_by: Option<String>,
 }
 
-pub fn revision_conflict_guidance(current_revision: u64) -> String {
-    format!(
+pub fn revision_conflict_guidance(current_revision: u64, produced_by: Option<&str>) -> String {
+    let guidance = format!(
         "re-read latest pack via get, merge intent, retry with expected_revision={current_revision}"
-    )
+    );
+    match produced_by {
+        Some(reason) => format!("{guidance}; current revision was produced by: {reason}"),
+        None => guidance,
+    }
 }
 
 #[derive(Debug, Error)]
@@ -64,6 +78,8 @@ pub enum DomainError {
         last_updated_at: String,
         changed_section_keys: Vec<String>,
         guidance: String,
+        /// `reason` the writer of the current revision gave, if any.
+        produced_by: Option<String>,
     },
 
     #[error("invalid state: {0}")]
@@ -75,6 +91,25 @@ pub enum DomainError {
         missing_sections: Vec<String>,
         missing_fields: Vec<String>,
         invalid_refs: Vec<FinalizeRefIssue>,
+        failed_rules: Vec<FinalizeRuleFailure>,
+    },
+
+    #[error("sign-off required: {message}")]
+    SignOffRequired {
+        message: String,
+        required_approvals: usize,
+        current_approvals: usize,
+        missing_roles: Vec<String>,
+        outstanding: Vec<String>,
+    },
+
+    /// A mutation by someone other than the pack's owner, under
+    /// [`crate::domain::models::OwnershipMode::Enforce`].
+    #[error("forbidden: {message}")]
+    Forbidden {
+        message: String,
+        owner: String,
+        actor: Option<String>,
     },
 
     #[error("stale ref: {0}")]
@@ -83,6 +118,16 @@ pub enum DomainError {
     #[error("{0}")]
     Io(String),
 
+    /// The pack store cannot be written (e.g. an immutable mount); reads
+    /// still work.
+    #[error("storage is read-only: {0}")]
+    StorageReadOnly(String),
+
+    /// An encrypted pack file that the configured key cannot open (or no
+    /// key is configured). The file is left in place.
+    #[error("decryption failed: {0}")]
+    DecryptionFailed(String),
+
     #[error("failed to deserialize: {0}")]
     Deserialize(String),
 
@@ -91,6 +136,15 @@ pub enum DomainError {
 
     #[error("pack id already exists: {0}")]
     PackIdConflict(String),
+
+    #[error(
+        "page token version {version} is not supported (supported: {}); restart paging without page_token",
+        supported.join(", ")
+    )]
+    CursorVersionUnsupported {
+        version: String,
+        supported: Vec<String>,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, DomainError>;
@@ -0,0 +1,87 @@
+//! Path patterns for directory/glob refs (`src/auth/**`, `src/*.rs`,
+//! `docs/`). `*` and `?` match within one path segment, `**` spans any
+//! number of segments, and a trailing `/` covers everything under that
+//! directory.
+
+/// Whether `path` is a pattern rather than a single file.
+pub fn is_pattern(path: &str) -> bool {
+    path.ends_with('/') || path.contains(['*', '?'])
+}
+
+/// Leading segments of `pattern` before the first wildcard: the directory
+/// every match lives under (empty for the root).
+pub fn base_dir(pattern: &str) -> String {
+    let segments: Vec<&str> = pattern.split('/').collect();
+    let literal = if pattern.ends_with('/') {
+        &segments[..]
+    } else {
+        &segments[..segments.len() - 1]
+    };
+    literal
+        .iter()
+        .take_while(|segment| !is_pattern(segment))
+        .filter(|segment| !segment.is_empty() && **segment != ".")
+        .copied()
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether the `/`-separated relative `path` matches `pattern`.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let mut wanted: Vec<&str> = pattern
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect();
+    if pattern.ends_with('/') {
+        wanted.push("**");
+    }
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match_segments(&wanted, &segments)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => path.split_first().is_some_and(|(name, path)| {
+            match_segment(segment.as_bytes(), name.as_bytes()) && match_segments(rest, path)
+        }),
+    }
+}
+
+/// `*` and `?` wildcard match of one segment, byte-wise.
+fn match_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && match_segment(rest, &name[1..]),
+        Some((byte, rest)) => name.first() == Some(byte) && match_segment(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns_match_segments_and_recursive_directories() {
+        assert!(matches("src/auth/**", "src/auth/mod.rs"));
+        assert!(matches("src/auth/**", "src/auth/jwt/verify.rs"));
+        assert!(!matches("src/auth/**", "src/authz/mod.rs"));
+        assert!(matches("src/*.rs", "src/main.rs"));
+        assert!(!matches("src/*.rs", "src/auth/mod.rs"));
+        assert!(matches("src/**/test_?.rs", "src/a/b/test_1.rs"));
+        assert!(matches("src/**/test_?.rs", "src/test_2.rs"));
+        assert!(matches("docs/", "docs/guide/intro.md"));
+        assert!(!is_pattern("src/main.rs"));
+        assert!(is_pattern("docs/") && is_pattern("src/*.rs"));
+    }
+
+    #[test]
+    fn test_base_dir_stops_at_the_first_wildcard() {
+        assert_eq!(base_dir("src/auth/**"), "src/auth");
+        assert_eq!(base_dir("src/*/mod.rs"), "src");
+        assert_eq!(base_dir("docs/"), "docs");
+        assert_eq!(base_dir("*.md"), "");
+    }
+}
@@ -0,0 +1,248 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::models::{is_template_placeholder, Pack};
+
+/// Refs spanning more lines than this are flagged as `ref_giant_range`.
+pub const GIANT_RANGE_LINES: usize = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Info,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    pub code: &'static str,
+    pub severity: LintSeverity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ref_key: Option<String>,
+}
+
+/// Quality findings for one pack revision. Unlike the finalize gate, lint
+/// never blocks a write; it tells the author what a reader will trip over.
+#[derive(Debug, Clone, Serialize)]
+pub struct LintReport {
+    pub pack_id: String,
+    pub revision: u64,
+    pub warnings: usize,
+    pub infos: usize,
+    pub findings: Vec<LintFinding>,
+}
+
+fn finding(
+    code: &'static str,
+    severity: LintSeverity,
+    message: String,
+    section_key: &str,
+    ref_key: Option<&str>,
+) -> LintFinding {
+    LintFinding {
+        code,
+        severity,
+        message,
+        section_key: Some(section_key.to_string()),
+        ref_key: ref_key.map(str::to_string),
+    }
+}
+
+pub fn lint_pack(pack: &Pack) -> LintReport {
+    let mut findings = Vec::new();
+    // group -> (section_key, ref_key) of every ref using it
+    let mut groups: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+
+    for section in &pack.sections {
+        let section_key = section.key.as_str();
+        if section
+            .description
+            .as_deref()
+            .is_none_or(|d| d.trim().is_empty())
+        {
+            findings.push(finding(
+                "section_missing_description",
+                LintSeverity::Info,
+                format!("section '{}' has no description", section_key),
+                section_key,
+                None,
+            ));
+        }
+        if section
+            .description
+            .as_deref()
+            .is_some_and(is_template_placeholder)
+        {
+            findings.push(finding(
+                "section_template_placeholder",
+                LintSeverity::Warning,
+                format!(
+                    "section '{}' still has its template placeholder description",
+                    section_key
+                ),
+                section_key,
+                None,
+            ));
+        }
+        if section.refs.is_empty() {
+            findings.push(finding(
+                "section_without_refs",
+                LintSeverity::Warning,
+                format!("section '{}' has no code refs", section_key),
+                section_key,
+                None,
+            ));
+        }
+        for code_ref in &section.refs {
+            let ref_key = code_ref.key.as_str();
+            if code_ref.why.as_deref().is_none_or(|w| w.trim().is_empty()) {
+                findings.push(finding(
+                    "ref_missing_why",
+                    LintSeverity::Warning,
+                    format!("ref '{}' does not say why it matters", ref_key),
+                    section_key,
+                    Some(ref_key),
+                ));
+            }
+            let span = code_ref.lines.end - code_ref.lines.start + 1;
+            if span > GIANT_RANGE_LINES {
+                findings.push(finding(
+                    "ref_giant_range",
+                    LintSeverity::Warning,
+                    format!(
+                        "ref '{}' spans {} lines (> {}); narrow it to the relevant lines",
+                        ref_key, span, GIANT_RANGE_LINES
+                    ),
+                    section_key,
+                    Some(ref_key),
+                ));
+            }
+            if let Some(group) = code_ref.group.as_deref().filter(|g| !g.trim().is_empty()) {
+                groups
+                    .entry(group)
+                    .or_default()
+                    .push((section_key, ref_key));
+            }
+        }
+    }
+
+    for (group, members) in groups {
+        if let [(section_key, ref_key)] = members.as_slice() {
+            findings.push(finding(
+                "orphan_group",
+                LintSeverity::Info,
+                format!(
+                    "group '{}' is used only by ref '{}'; drop it or group related refs",
+                    group, ref_key
+                ),
+                section_key,
+                Some(ref_key),
+            ));
+        }
+    }
+
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    let warnings = findings
+        .iter()
+        .filter(|f| f.severity == LintSeverity::Warning)
+        .count();
+    LintReport {
+        pack_id: pack.id.as_str().to_string(),
+        revision: pack.revision,
+        warnings,
+        infos: findings.len() - warnings,
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::{CodeRef, Section};
+    use crate::domain::types::{LineRange, PackId, RefKey, RelativePath, SectionKey};
+
+    fn code_ref(
+        key: &str,
+        lines: (usize, usize),
+        why: Option<&str>,
+        group: Option<&str>,
+    ) -> CodeRef {
+        CodeRef {
+            key: RefKey::new(key).unwrap(),
+            root: None,
+            path: RelativePath::new("src/lib.rs").unwrap(),
+            lines: LineRange::new(lines.0, lines.1).unwrap(),
+            title: None,
+            why: why.map(str::to_string),
+            group: group.map(str::to_string),
+            entry_point: false,
+            git_sha: None,
+            symbol: None,
+            url: None,
+            section_ref: None,
+            excerpt_line_limit: None,
+            snapshot: None,
+            content_hash: None,
+            changed_revision: None,
+        }
+    }
+
+    #[test]
+    fn test_lint_reports_each_check_with_warnings_first() {
+        let mut pack = Pack::new(PackId::new(), None);
+        pack.sections = vec![
+            Section {
+                key: SectionKey::new("scope").unwrap(),
+                title: "Scope".into(),
+                description: Some("what we looked at".into()),
+                translations: Default::default(),
+                refs: vec![
+                    code_ref("entry", (1, 10), Some("entry point"), Some("core")),
+                    code_ref("helper", (20, 30), Some("called by entry"), Some("core")),
+                ],
+                diagrams: Vec::new(),
+                attachments: Vec::new(),
+                changed_revision: None,
+            },
+            Section {
+                key: SectionKey::new("notes").unwrap(),
+                title: "Notes".into(),
+                description: None,
+                translations: Default::default(),
+                refs: vec![code_ref("dump", (1, 900), None, Some("misc"))],
+                diagrams: Vec::new(),
+                attachments: Vec::new(),
+                changed_revision: None,
+            },
+            Section {
+                key: SectionKey::new("empty").unwrap(),
+                title: "Empty".into(),
+                description: Some("todo".into()),
+                translations: Default::default(),
+                refs: Vec::new(),
+                diagrams: Vec::new(),
+                attachments: Vec::new(),
+                changed_revision: None,
+            },
+        ];
+
+        let report = lint_pack(&pack);
+        let codes: Vec<&str> = report.findings.iter().map(|f| f.code).collect();
+        assert_eq!(
+            codes,
+            [
+                "ref_missing_why",
+                "ref_giant_range",
+                "section_without_refs",
+                "section_missing_description",
+                "orphan_group",
+            ]
+        );
+        assert_eq!(report.warnings, 3);
+        assert_eq!(report.infos, 2);
+        assert_eq!(report.findings[4].ref_key.as_deref(), Some("dump"));
+    }
+}
@@ -1,3 +1,8 @@
 pub mod errors;
+pub mod glob;
+pub mod lint;
 pub mod models;
+pub mod reanchor;
+pub mod symbols;
+pub mod text_diff;
 pub mod types;
@@ -1,12 +1,14 @@
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use super::{
     errors::{DomainError, Result},
     types::{
-        DiagramKey, LineRange, PackId, PackName, RefKey, RelativePath, SectionKey, Status,
-        CURRENT_SCHEMA_VERSION,
+        AttachmentKey, DiagramKey, ExternalUrl, GitSha, LanguageTag, LineRange, OutputProfile,
+        PackId, PackName, PackSectionRef, RefKey, RelativePath, SectionKey, SourceRootName, Status,
+        SymbolName, CURRENT_SCHEMA_VERSION, MAX_REF_LINE_SPAN,
     },
 };
 
@@ -15,21 +17,193 @@ use super::{
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeRef {
     pub key: RefKey,
+    /// Named source root `path` is relative to; `None` is the default root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<SourceRootName>,
     pub path: RelativePath,
     pub lines: LineRange,
     pub title: Option<String>,
     pub why: Option<String>,
     pub group: Option<String>,
+    /// Pinned to the first compact page regardless of section order.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub entry_point: bool,
+    /// Read the lines as of this commit instead of the working tree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<GitSha>,
+    /// Definition the ref follows: reads resolve its current range, and
+    /// `lines` holds the range found at the last write.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<SymbolName>,
+    /// External link (RFC, issue, doc) instead of source lines: `path` holds
+    /// its [`ExternalUrl::locator`] and nothing is read, hashed or
+    /// snapshotted for it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<ExternalUrl>,
+    /// Section of another pack instead of source lines: `path` holds the
+    /// `pack:<id>#<section_key>` text, reads quote the target section, and
+    /// nothing is read, hashed or snapshotted for it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section_ref: Option<PackSectionRef>,
+    /// Rendered excerpt length for this ref, overriding the pack's
+    /// `read_defaults.excerpt_line_limit`; see [`ExcerptLimits`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excerpt_line_limit: Option<usize>,
+    /// Excerpt text captured at write time (`snapshot_excerpts: true`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<ExcerptSnapshot>,
+    /// [`excerpt_content_hash`] of the lines when the ref was last upserted;
+    /// a different hash on read means the file was edited inside the range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Pack revision that last changed this ref; `None` on refs written
+    /// before revisions were tracked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed_revision: Option<u64>,
+}
+
+impl CodeRef {
+    /// Same authored fields; snapshots and revision stamps are ignored.
+    pub fn same_content(&self, other: &CodeRef) -> bool {
+        self.key == other.key
+            && self.root == other.root
+            && self.path == other.path
+            && self.lines == other.lines
+            && self.title == other.title
+            && self.why == other.why
+            && self.group == other.group
+            && self.entry_point == other.entry_point
+            && self.git_sha == other.git_sha
+            && self.symbol == other.symbol
+            && self.url == other.url
+            && self.section_ref == other.section_ref
+            && self.excerpt_line_limit == other.excerpt_line_limit
+    }
+
+    /// Whether this ref changed after `revision`; unstamped refs count as changed.
+    pub fn changed_since(&self, revision: u64) -> bool {
+        self.changed_revision
+            .is_none_or(|changed| changed > revision)
+    }
+
+    /// Keeps `previous`'s snapshot and content hash when they still describe
+    /// this ref's range.
+    pub fn inherit_snapshot(&mut self, previous: &CodeRef) {
+        if self.root == previous.root
+            && self.path == previous.path
+            && self.lines == previous.lines
+            && self.git_sha == previous.git_sha
+        {
+            if self.snapshot.is_none() {
+                self.snapshot = previous.snapshot.clone();
+            }
+            if self.content_hash.is_none() {
+                self.content_hash = previous.content_hash.clone();
+            }
+        }
+    }
+
+    /// Whether the ref has source lines to read (it is not a url or
+    /// section ref).
+    pub fn reads_source(&self) -> bool {
+        self.url.is_none() && self.section_ref.is_none()
+    }
+
+    /// Directory/glob refs stand for a set of files, so they cannot pin a
+    /// commit or follow a symbol; url and section refs have no source to
+    /// read at all.
+    pub fn validate_target(&self) -> Result<()> {
+        if self.path.is_pattern() && (self.git_sha.is_some() || self.symbol.is_some()) {
+            return Err(DomainError::InvalidData(format!(
+                "ref '{}' covers '{}', a directory/glob; git_sha and symbol apply to single files only",
+                self.key, self.path
+            )));
+        }
+        if let Some(url) = &self.url {
+            if self.root.is_some()
+                || self.git_sha.is_some()
+                || self.symbol.is_some()
+                || self.excerpt_line_limit.is_some()
+            {
+                return Err(DomainError::InvalidData(format!(
+                    "ref '{}' links to {}; root, git_sha, symbol and excerpt_line_limit apply to source refs only",
+                    self.key, url
+                )));
+            }
+        }
+        if let Some(target) = &self.section_ref {
+            if self.url.is_some()
+                || self.root.is_some()
+                || self.git_sha.is_some()
+                || self.symbol.is_some()
+                || self.excerpt_line_limit.is_some()
+            {
+                return Err(DomainError::InvalidData(format!(
+                    "ref '{}' quotes {}; url, root, git_sha, symbol and excerpt_line_limit apply to source refs only",
+                    self.key, target
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// `(stored, current)` hashes when `body` no longer matches the hash
+    /// recorded at upsert; `None` when they agree or nothing was recorded.
+    pub fn content_drift(&self, body: &str) -> Option<(&str, String)> {
+        let stored = self.content_hash.as_deref()?;
+        let current = excerpt_content_hash(body);
+        (stored != current).then_some((stored, current))
+    }
+}
+
+/// Hash stored in [`CodeRef::content_hash`]: FNV-1a over the excerpt text
+/// without its line-number gutter, so a symbol ref that only moved keeps it.
+pub fn excerpt_content_hash(body: &str) -> String {
+    let text = excerpt_lines(body).join("\n");
+    format!("{:016x}", fnv1a_64(text.as_bytes()))
+}
+
+/// Lines of a numbered excerpt body with the line-number gutter removed.
+pub fn excerpt_lines(body: &str) -> Vec<&str> {
+    body.lines()
+        .map(|line| line.split_once(": ").map_or(line, |(_, text)| text))
+        .collect()
+}
+
+/// Stable across processes and Rust versions, unlike `DefaultHasher`.
+pub fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Excerpt body stored inside the pack so evidence survives source changes.
+/// `body` uses the same numbered-line format as a live read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExcerptSnapshot {
+    pub body: String,
+    pub captured_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RefSpec {
     pub key: RefKey,
+    pub root: Option<SourceRootName>,
     pub path: RelativePath,
     pub lines: LineRange,
     pub title: Option<String>,
     pub why: Option<String>,
     pub group: Option<String>,
+    pub symbol: Option<SymbolName>,
+    pub url: Option<ExternalUrl>,
+    pub section_ref: Option<PackSectionRef>,
+    pub excerpt_line_limit: Option<usize>,
 }
 
 // ── Diagram ───────────────────────────────────────────────────────────────────
@@ -40,6 +214,406 @@ pub struct Diagram {
     pub title: String,
     pub mermaid: String,
     pub why: Option<String>,
+    /// Previous versions, oldest first, capped at [`Diagram::MAX_HISTORY`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<DiagramVersion>,
+}
+
+/// A superseded diagram body, kept so architecture changes stay reviewable.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiagramVersion {
+    /// Pack revision in which this version was current.
+    pub revision: u64,
+    pub replaced_at: DateTime<Utc>,
+    pub title: String,
+    pub mermaid: String,
+}
+
+impl Diagram {
+    pub const MAX_HISTORY: usize = 5;
+
+    /// Same authored fields; history is ignored.
+    pub fn same_content(&self, other: &Diagram) -> bool {
+        self.key == other.key
+            && self.title == other.title
+            && self.mermaid == other.mermaid
+            && self.why == other.why
+    }
+
+    /// Carries `previous` history into `self`, archiving `previous` as a new
+    /// version when its title or mermaid body differs.
+    pub fn inherit_history(&mut self, previous: &Diagram, revision: u64, now: DateTime<Utc>) {
+        let mut history = previous.history.clone();
+        if previous.mermaid != self.mermaid || previous.title != self.title {
+            history.push(DiagramVersion {
+                revision,
+                replaced_at: now,
+                title: previous.title.clone(),
+                mermaid: previous.mermaid.clone(),
+            });
+        }
+        let overflow = history.len().saturating_sub(Self::MAX_HISTORY);
+        history.drain(..overflow);
+        self.history = history;
+    }
+}
+
+/// Upper bounds enforced on every diagram written to a pack, so generated
+/// mermaid blobs cannot bloat pack files or stall rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagramLimits {
+    pub max_bytes: usize,
+    pub max_nodes: usize,
+    pub max_edges: usize,
+}
+
+impl DiagramLimits {
+    pub const DEFAULT_MAX_BYTES: usize = 32 * 1024;
+    pub const DEFAULT_MAX_NODES: usize = 200;
+    pub const DEFAULT_MAX_EDGES: usize = 400;
+
+    pub fn validate(
+        &self,
+        section_key: &SectionKey,
+        diagram_key: &DiagramKey,
+        mermaid: &str,
+    ) -> Result<()> {
+        let stats = MermaidStats::measure(mermaid);
+        let checks = [
+            ("max_bytes", mermaid.len(), self.max_bytes),
+            ("max_nodes", stats.nodes, self.max_nodes),
+            ("max_edges", stats.edges, self.max_edges),
+        ];
+        let exceeded: Vec<serde_json::Value> = checks
+            .iter()
+            .filter(|(_, actual, max)| actual > max)
+            .map(|(limit, actual, max)| json!({ "limit": limit, "actual": actual, "max": max }))
+            .collect();
+        if exceeded.is_empty() {
+            return Ok(());
+        }
+        Err(DomainError::DetailedInvalidData {
+            message: format!(
+                "diagram '{}' in section '{}' exceeds size limits",
+                diagram_key, section_key
+            ),
+            details: json!({
+                "section_key": section_key.as_str(),
+                "diagram_key": diagram_key.as_str(),
+                "bytes": mermaid.len(),
+                "nodes": stats.nodes,
+                "edges": stats.edges,
+                "exceeded": exceeded,
+            }),
+        })
+    }
+}
+
+impl Default for DiagramLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+            max_nodes: Self::DEFAULT_MAX_NODES,
+            max_edges: Self::DEFAULT_MAX_EDGES,
+        }
+    }
+}
+
+/// How many lines of each excerpt a full render shows. Longer excerpts are
+/// cut at `default_lines` unless the ref's `excerpt_line_limit`, else the
+/// pack's `read_defaults.excerpt_line_limit`, says otherwise; overrides above
+/// `max_lines` are rejected on write and clamped on read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExcerptLimits {
+    pub default_lines: usize,
+    pub max_lines: usize,
+}
+
+impl ExcerptLimits {
+    pub const DEFAULT_LINES: usize = 200;
+    pub const DEFAULT_MAX_LINES: usize = MAX_REF_LINE_SPAN;
+
+    /// Lines to render for `code_ref` in `pack`.
+    pub fn lines_for(&self, pack: &Pack, code_ref: &CodeRef) -> usize {
+        code_ref
+            .excerpt_line_limit
+            .or(pack.read_defaults.excerpt_line_limit)
+            .unwrap_or(self.default_lines)
+            .clamp(1, self.max_lines.max(1))
+    }
+
+    /// Lines to render for text in `pack` that has no override of its own,
+    /// such as attachments.
+    pub fn lines_for_pack(&self, pack: &Pack) -> usize {
+        pack.read_defaults
+            .excerpt_line_limit
+            .unwrap_or(self.default_lines)
+            .clamp(1, self.max_lines.max(1))
+    }
+
+    /// Rejects pack- or ref-level overrides above `max_lines`.
+    pub fn validate(&self, pack: &Pack) -> Result<()> {
+        if let Some(limit) = pack.read_defaults.excerpt_line_limit {
+            self.check(
+                limit,
+                json!({ "field": "read_defaults.excerpt_line_limit" }),
+            )?;
+        }
+        for section in &pack.sections {
+            for code_ref in &section.refs {
+                if let Some(limit) = code_ref.excerpt_line_limit {
+                    self.check(
+                        limit,
+                        json!({
+                            "field": "excerpt_line_limit",
+                            "section_key": section.key.as_str(),
+                            "ref_key": code_ref.key.as_str(),
+                        }),
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check(&self, limit: usize, mut details: serde_json::Value) -> Result<()> {
+        if (1..=self.max_lines).contains(&limit) {
+            return Ok(());
+        }
+        details["limit"] = json!(limit);
+        details["max"] = json!(self.max_lines);
+        Err(DomainError::DetailedInvalidData {
+            message: format!(
+                "{} must be between 1 and {} (got {})",
+                details["field"].as_str().unwrap_or("excerpt_line_limit"),
+                self.max_lines,
+                limit
+            ),
+            details,
+        })
+    }
+}
+
+impl Default for ExcerptLimits {
+    fn default() -> Self {
+        Self {
+            default_lines: Self::DEFAULT_LINES,
+            max_lines: Self::DEFAULT_MAX_LINES,
+        }
+    }
+}
+
+/// Heuristic node/edge count for a mermaid source.
+///
+/// Edges are link tokens (`-->`, `---`, `-.->`, `==>`, `->>`, ...); nodes are
+/// the distinct identifiers on either side of a link or declared on their own
+/// line. Directive lines (`subgraph`, `style`, `classDef`, ...) are skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MermaidStats {
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+impl MermaidStats {
+    const DIRECTIVES: [&'static str; 14] = [
+        "graph",
+        "flowchart",
+        "sequencediagram",
+        "classdiagram",
+        "statediagram",
+        "statediagram-v2",
+        "erdiagram",
+        "subgraph",
+        "end",
+        "style",
+        "classdef",
+        "class",
+        "click",
+        "linkstyle",
+    ];
+
+    pub fn measure(mermaid: &str) -> Self {
+        let mut nodes = HashSet::new();
+        let mut edges = 0usize;
+        for raw_line in mermaid.lines() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with("%%") {
+                continue;
+            }
+            let first_word = line
+                .split_whitespace()
+                .next()
+                .unwrap_or("")
+                .to_ascii_lowercase();
+            if matches!(first_word.as_str(), "participant" | "actor") {
+                if let Some(id) = line.split_whitespace().nth(1).and_then(Self::node_id) {
+                    nodes.insert(id);
+                }
+                continue;
+            }
+            if Self::DIRECTIVES.contains(&first_word.as_str()) {
+                continue;
+            }
+            let (segments, line_edges) = Self::split_links(line);
+            edges += line_edges;
+            for segment in segments {
+                if let Some(id) = Self::node_id(segment) {
+                    nodes.insert(id);
+                }
+            }
+        }
+        Self {
+            nodes: nodes.len(),
+            edges,
+        }
+    }
+
+    /// Splits a line on link tokens: runs of `-`, `=`, `.`, `>` that are at
+    /// least two chars long and contain a `-` or `=`.
+    fn split_links(line: &str) -> (Vec<&str>, usize) {
+        let is_link_char = |c: char| matches!(c, '-' | '=' | '.' | '>');
+        let mut segments = Vec::new();
+        let mut edges = 0usize;
+        let mut segment_start = 0usize;
+        let mut chars = line.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if !is_link_char(c) {
+                continue;
+            }
+            let mut end = start + c.len_utf8();
+            while let Some(&(idx, next)) = chars.peek() {
+                if !is_link_char(next) {
+                    break;
+                }
+                end = idx + next.len_utf8();
+                chars.next();
+            }
+            let run = &line[start..end];
+            if run.len() >= 2 && run.contains(['-', '=']) {
+                segments.push(&line[segment_start..start]);
+                segment_start = end;
+                edges += 1;
+            }
+        }
+        segments.push(&line[segment_start..]);
+        (segments, edges)
+    }
+
+    fn node_id(segment: &str) -> Option<String> {
+        let mut rest = segment.trim_start();
+        // Edge labels: `A -->|label| B`.
+        if let Some(stripped) = rest.strip_prefix('|') {
+            rest = stripped.split_once('|').map(|(_, tail)| tail).unwrap_or("");
+            rest = rest.trim_start();
+        }
+        let id: String = rest
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        (!id.is_empty()).then_some(id)
+    }
+}
+
+// ── Attachment ────────────────────────────────────────────────────────────────
+
+/// Small text artifact kept verbatim in a section (a log, JSON evidence,
+/// command output) for findings that need the raw output rather than a ref.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub key: AttachmentKey,
+    pub title: Option<String>,
+    /// `type/subtype`, [`Attachment::DEFAULT_MEDIA_TYPE`] unless given.
+    pub media_type: String,
+    pub content: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Attachment {
+    /// Per-attachment cap when `CONTEXT_PACK_MAX_ATTACHMENT_BYTES` is unset.
+    pub const DEFAULT_MAX_BYTES: usize = 64 * 1024;
+    pub const DEFAULT_MEDIA_TYPE: &'static str = "text/plain";
+
+    /// Validates `content` against `max_bytes` and the media type's shape.
+    pub fn new(
+        section_key: &SectionKey,
+        key: AttachmentKey,
+        title: Option<String>,
+        media_type: Option<&str>,
+        content: String,
+        max_bytes: usize,
+        now: DateTime<Utc>,
+    ) -> Result<Self> {
+        let media_type = media_type
+            .map(|m| m.trim().to_ascii_lowercase())
+            .filter(|m| !m.is_empty())
+            .unwrap_or_else(|| Self::DEFAULT_MEDIA_TYPE.to_string());
+        let well_formed = media_type.len() <= 100
+            && media_type.split_once('/').is_some_and(|(kind, sub)| {
+                !kind.is_empty()
+                    && !sub.is_empty()
+                    && media_type
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "/.+-".contains(c))
+            });
+        if !well_formed {
+            return Err(DomainError::InvalidData(format!(
+                "media_type must look like 'text/plain' or 'application/json' (got '{}')",
+                media_type
+            )));
+        }
+        if content.is_empty() {
+            return Err(DomainError::InvalidData(format!(
+                "attachment '{}' has no content",
+                key
+            )));
+        }
+        if content.len() > max_bytes {
+            return Err(DomainError::DetailedInvalidData {
+                message: format!(
+                    "attachment '{}' in section '{}' is {} bytes (max {})",
+                    key,
+                    section_key,
+                    content.len(),
+                    max_bytes
+                ),
+                details: json!({
+                    "field": "content",
+                    "section_key": section_key.as_str(),
+                    "attachment_key": key.as_str(),
+                    "bytes": content.len(),
+                    "max": max_bytes,
+                }),
+            });
+        }
+        Ok(Self {
+            key,
+            title: title
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty()),
+            media_type,
+            content,
+            updated_at: now,
+        })
+    }
+
+    /// Same authored fields; the timestamp is ignored.
+    pub fn same_content(&self, other: &Attachment) -> bool {
+        self.key == other.key
+            && self.title == other.title
+            && self.media_type == other.media_type
+            && self.content == other.content
+    }
+
+    /// Fence language for rendering the content.
+    pub fn fence_lang(&self) -> &'static str {
+        match self.media_type.split_once('/').map_or("", |(_, sub)| sub) {
+            "json" => "json",
+            "yaml" | "x-yaml" => "yaml",
+            "xml" => "xml",
+            "markdown" => "markdown",
+            _ => "text",
+        }
+    }
 }
 
 // ── Section ───────────────────────────────────────────────────────────────────
@@ -49,8 +623,790 @@ pub struct Section {
     pub key: SectionKey,
     pub title: String,
     pub description: Option<String>,
+    /// Translated descriptions by language; `description` is the default.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<LanguageTag, String>,
     pub refs: Vec<CodeRef>,
     pub diagrams: Vec<Diagram>,
+    /// Managed by `upsert_attachment`/`delete_attachment` and kept across
+    /// writes that keep the section.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    /// Pack revision that last changed anything in this section.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed_revision: Option<u64>,
+}
+
+impl Section {
+    /// Same authored content, refs, diagrams and attachments included;
+    /// diagram history, excerpt snapshots and revision stamps are ignored.
+    pub fn same_content(&self, other: &Section) -> bool {
+        self.key == other.key
+            && self.title == other.title
+            && self.description == other.description
+            && self.translations == other.translations
+            && self.refs.len() == other.refs.len()
+            && self
+                .refs
+                .iter()
+                .zip(&other.refs)
+                .all(|(a, b)| a.same_content(b))
+            && self.diagrams.len() == other.diagrams.len()
+            && self
+                .diagrams
+                .iter()
+                .zip(&other.diagrams)
+                .all(|(a, b)| a.same_content(b))
+            && self.attachments.len() == other.attachments.len()
+            && self
+                .attachments
+                .iter()
+                .zip(&other.attachments)
+                .all(|(a, b)| a.same_content(b))
+    }
+
+    /// Whether this section changed after `revision`. Unstamped sections
+    /// count as changed, so delta reads never hide content.
+    pub fn changed_since(&self, revision: u64) -> bool {
+        self.changed_revision
+            .is_none_or(|changed| changed > revision)
+    }
+
+    /// Description in `lang` (exact tag, then its primary language), falling
+    /// back to the default description.
+    pub fn description_for(&self, lang: Option<&LanguageTag>) -> Option<&str> {
+        lang.and_then(|lang| {
+            self.translations.get(lang).or_else(|| {
+                self.translations
+                    .iter()
+                    .find(|(tag, _)| tag.as_str() == lang.primary())
+                    .map(|(_, text)| text)
+            })
+        })
+        .map(String::as_str)
+        .or(self.description.as_deref())
+    }
+}
+
+/// A section dropped from a pack, remembered so delta reads can report it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemovedSection {
+    pub key: SectionKey,
+    /// Revision that removed it.
+    pub revision: u64,
+}
+
+// ── ReadDefaults ──────────────────────────────────────────────────────────────
+
+/// Pack author's preferred `output read` shape, used for whatever the reader
+/// (explicit args or host defaults) leaves unset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<OutputProfile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Rendered excerpt length for this pack's refs; see [`ExcerptLimits`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excerpt_line_limit: Option<usize>,
+}
+
+impl ReadDefaults {
+    pub const MAX_LIMIT: usize = 200;
+
+    pub fn is_empty(&self) -> bool {
+        self.profile.is_none() && self.limit.is_none() && self.excerpt_line_limit.is_none()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(limit) = self.limit {
+            if limit == 0 || limit > Self::MAX_LIMIT {
+                return Err(DomainError::InvalidData(format!(
+                    "read_defaults.limit must be between 1 and {} (got {})",
+                    Self::MAX_LIMIT,
+                    limit
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+// ── SignOff ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignOffVerdict {
+    Approved,
+    ChangesRequested,
+}
+
+impl std::fmt::Display for SignOffVerdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignOffVerdict::Approved => write!(f, "approved"),
+            SignOffVerdict::ChangesRequested => write!(f, "changes_requested"),
+        }
+    }
+}
+
+impl std::str::FromStr for SignOffVerdict {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "approved" => Ok(Self::Approved),
+            "changes_requested" => Ok(Self::ChangesRequested),
+            other => Err(DomainError::InvalidData(format!(
+                "'verdict' must be one of: approved, changes_requested (got '{}')",
+                other
+            ))),
+        }
+    }
+}
+
+/// A reviewer's verdict on one revision of a pack. Sign-offs are append-only;
+/// a reviewer signing again adds a new record rather than replacing the old one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignOff {
+    pub reviewer: String,
+    pub verdict: SignOffVerdict,
+    /// Pack revision the reviewer read; recording the sign-off bumps it by one.
+    pub revision: u64,
+    pub signed_at: DateTime<Utc>,
+    /// Lowercase role the reviewer signed as (e.g. `security`), matched by
+    /// [`SignOffPolicy::required_roles`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl SignOff {
+    pub const MAX_REVIEWER_CHARS: usize = 128;
+    pub const MAX_COMMENT_CHARS: usize = 2000;
+}
+
+/// Why a revision was written, as stated by the writer (`reason` on
+/// mutating ops).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteReason {
+    /// Revision the reason describes; a later write without a reason leaves
+    /// it behind, so it no longer applies.
+    pub revision: u64,
+    pub reason: String,
+    pub at: DateTime<Utc>,
+}
+
+impl WriteReason {
+    pub const MAX_CHARS: usize = 500;
+}
+
+/// Whether a pack's [`Pack::owner`] guards its mutations
+/// (`CONTEXT_PACK_OWNERSHIP`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OwnershipMode {
+    /// The owner is recorded at create time but never checked.
+    #[default]
+    Record,
+    /// Mutations by anyone but the owner need `force`.
+    Enforce,
+}
+
+impl OwnershipMode {
+    /// Fails with [`DomainError::Forbidden`] when enforcing and `actor` is not
+    /// the owner of an owned pack, unless `force` is set.
+    pub fn check(self, pack: &Pack, actor: Option<&str>, force: bool) -> Result<()> {
+        let Some(owner) = pack.owner.as_deref() else {
+            return Ok(());
+        };
+        if self == Self::Record || force || actor == Some(owner) {
+            return Ok(());
+        }
+        Err(DomainError::Forbidden {
+            message: format!(
+                "pack '{}' is owned by '{}'{}; pass force=true to write anyway",
+                pack.id,
+                owner,
+                actor
+                    .map(|actor| format!(", not '{}'", actor))
+                    .unwrap_or_else(|| " and the call names no actor".to_string())
+            ),
+            owner: owner.to_string(),
+            actor: actor.map(str::to_string),
+        })
+    }
+}
+
+impl std::str::FromStr for OwnershipMode {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "record" => Ok(Self::Record),
+            "enforce" => Ok(Self::Enforce),
+            other => Err(DomainError::InvalidData(format!(
+                "ownership mode must be one of: record, enforce (got '{}')",
+                other
+            ))),
+        }
+    }
+}
+
+/// Approvals a draft needs before it may be finalized. Only current sign-offs
+/// count (see [`Pack::current_sign_offs`]), and only each reviewer's latest
+/// verdict among them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignOffPolicy {
+    pub min_approvals: usize,
+    pub required_roles: BTreeSet<String>,
+}
+
+impl SignOffPolicy {
+    /// Parses `approvals=2,role=security,role=lead`; `role` may repeat.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut policy = Self::default();
+        for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(|| {
+                DomainError::InvalidData(format!(
+                    "sign-off policy: expected key=value, got '{part}'"
+                ))
+            })?;
+            let value = value.trim();
+            match key.trim() {
+                "approvals" => {
+                    policy.min_approvals = value.parse().map_err(|_| {
+                        DomainError::InvalidData(format!(
+                            "sign-off policy: approvals must be a non-negative integer (got '{value}')"
+                        ))
+                    })?;
+                }
+                "role" if !value.is_empty() => {
+                    policy.required_roles.insert(value.to_lowercase());
+                }
+                other => {
+                    return Err(DomainError::InvalidData(format!(
+                        "sign-off policy: unknown key '{other}' (expected approvals, role)"
+                    )))
+                }
+            }
+        }
+        Ok(policy)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_approvals == 0 && self.required_roles.is_empty()
+    }
+
+    /// Fails with [`DomainError::SignOffRequired`] unless `sign_offs` (already
+    /// filtered to current ones) satisfy the policy.
+    pub fn check(&self, sign_offs: &[&SignOff]) -> Result<()> {
+        let mut latest: BTreeMap<&str, &SignOff> = BTreeMap::new();
+        for sign_off in sign_offs {
+            latest.insert(sign_off.reviewer.as_str(), sign_off);
+        }
+        let approvals: Vec<&SignOff> = latest
+            .into_values()
+            .filter(|s| s.verdict == SignOffVerdict::Approved)
+            .collect();
+        let missing_roles: Vec<String> = self
+            .required_roles
+            .iter()
+            .filter(|role| {
+                !approvals
+                    .iter()
+                    .any(|s| s.role.as_deref() == Some(role.as_str()))
+            })
+            .cloned()
+            .collect();
+
+        let mut outstanding = Vec::new();
+        if approvals.len() < self.min_approvals {
+            outstanding.push(format!(
+                "{} more approval(s) ({} of {})",
+                self.min_approvals - approvals.len(),
+                approvals.len(),
+                self.min_approvals
+            ));
+        }
+        outstanding.extend(missing_roles.iter().map(|role| format!("role:{role}")));
+        if outstanding.is_empty() {
+            return Ok(());
+        }
+        Err(DomainError::SignOffRequired {
+            message: format!("finalize needs {}", outstanding.join(", ")),
+            required_approvals: self.min_approvals,
+            current_approvals: approvals.len(),
+            missing_roles,
+            outstanding,
+        })
+    }
+}
+
+// ── PackLink ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackLinkKind {
+    Parent,
+    Supersedes,
+    DependsOn,
+}
+
+impl std::fmt::Display for PackLinkKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackLinkKind::Parent => write!(f, "parent"),
+            PackLinkKind::Supersedes => write!(f, "supersedes"),
+            PackLinkKind::DependsOn => write!(f, "depends_on"),
+        }
+    }
+}
+
+impl std::str::FromStr for PackLinkKind {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "parent" => Ok(Self::Parent),
+            "supersedes" => Ok(Self::Supersedes),
+            "depends_on" => Ok(Self::DependsOn),
+            other => Err(DomainError::InvalidData(format!(
+                "link 'kind' must be one of: parent, supersedes, depends_on (got '{}')",
+                other
+            ))),
+        }
+    }
+}
+
+/// A typed relation from this pack to another, by id. Targets are only
+/// checked for existence at finalize, so links may be recorded before the
+/// target is written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackLink {
+    pub kind: PackLinkKind,
+    pub target: PackId,
+}
+
+impl PackLink {
+    pub const MAX_PER_PACK: usize = 32;
+}
+
+// ── Merge ─────────────────────────────────────────────────────────────────────
+
+/// What `merge` does with a source section whose key the target already uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergePolicy {
+    /// Keep the target's section and drop the source's.
+    Skip,
+    /// Put the source's section in place of the target's.
+    Replace,
+    /// Append the source's section under the first free `{key}-{n}`.
+    #[default]
+    Suffix,
+}
+
+impl std::fmt::Display for MergePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergePolicy::Skip => write!(f, "skip"),
+            MergePolicy::Replace => write!(f, "replace"),
+            MergePolicy::Suffix => write!(f, "suffix"),
+        }
+    }
+}
+
+impl std::str::FromStr for MergePolicy {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "skip" => Ok(Self::Skip),
+            "replace" => Ok(Self::Replace),
+            "suffix" => Ok(Self::Suffix),
+            other => Err(DomainError::InvalidData(format!(
+                "'on_conflict' must be one of: skip, replace, suffix (got '{}')",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeOutcome {
+    /// The key was free.
+    Added,
+    Replaced,
+    Suffixed,
+    Skipped,
+}
+
+/// What happened to one source section during a merge.
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedSection {
+    pub source_key: SectionKey,
+    /// Key the section has in the target; the target's own for `skipped`.
+    pub key: SectionKey,
+    pub outcome: MergeOutcome,
+}
+
+// ── PackTemplate ──────────────────────────────────────────────────────────────
+
+/// Prefix of the placeholder descriptions [`PackTemplate`] seeds. The finalize
+/// gate treats a description starting with it as unwritten, and lint flags it.
+pub const TEMPLATE_PLACEHOLDER_PREFIX: &str = "TODO(template):";
+
+/// Built-in pack shapes for `input create_from_template`. Each seeds the
+/// sections the finalize gate requires (`scope`, `findings`, `qa`) plus a few
+/// typical for the kind of work, with placeholder descriptions saying what
+/// belongs there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackTemplate {
+    Audit,
+    Remediation,
+    Research,
+}
+
+impl PackTemplate {
+    pub const ALL: [PackTemplate; 3] = [Self::Audit, Self::Remediation, Self::Research];
+
+    /// `(key, title, placeholder)` per seeded section, in render order.
+    pub fn sections(self) -> &'static [(&'static str, &'static str, &'static str)] {
+        match self {
+            PackTemplate::Audit => &[
+                ("scope", "Scope", "TODO(template): what was audited, at which commit, and what was left out."),
+                ("findings", "Findings", "TODO(template): one ref per finding, with severity and impact in its why."),
+                ("risks", "Risks", "TODO(template): open risks and suggested mitigations."),
+                ("qa", "QA", "TODO(template): checks run and their results, then a 'verdict: pass|fail' line."),
+            ],
+            PackTemplate::Remediation => &[
+                ("scope", "Scope", "TODO(template): the defect or finding being fixed, with a link to its report."),
+                ("findings", "Root cause", "TODO(template): refs to the faulty code and why it fails."),
+                ("plan", "Fix plan", "TODO(template): the change per file, in the order to apply it."),
+                ("qa", "QA", "TODO(template): tests proving the fix, then a 'verdict: pass|fail' line."),
+            ],
+            PackTemplate::Research => &[
+                ("scope", "Question", "TODO(template): the question to answer and the parts of the code base in scope."),
+                ("findings", "Findings", "TODO(template): refs to the code that answers it, one point per ref."),
+                ("open_questions", "Open questions", "TODO(template): what is still unknown and where to look next."),
+                ("qa", "QA", "TODO(template): how the answer was checked, then a 'verdict: pass|fail' line."),
+            ],
+        }
+    }
+}
+
+impl std::fmt::Display for PackTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackTemplate::Audit => write!(f, "audit"),
+            PackTemplate::Remediation => write!(f, "remediation"),
+            PackTemplate::Research => write!(f, "research"),
+        }
+    }
+}
+
+impl std::str::FromStr for PackTemplate {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "audit" => Ok(Self::Audit),
+            "remediation" => Ok(Self::Remediation),
+            "research" => Ok(Self::Research),
+            other => Err(DomainError::InvalidData(format!(
+                "'template' must be one of: audit, remediation, research (got '{}')",
+                other
+            ))),
+        }
+    }
+}
+
+/// Whether `description` is still a [`PackTemplate`] placeholder.
+pub fn is_template_placeholder(description: &str) -> bool {
+    description
+        .trim_start()
+        .starts_with(TEMPLATE_PLACEHOLDER_PREFIX)
+}
+
+// ── FinalizeChecklist ────────────────────────────────────────────────────────
+
+/// Sections a pack must have to finalize, in report order, each with the
+/// fields it must carry. `content` means a non-placeholder description, a
+/// ref or a diagram; `verdict` means any mention of "verdict"; any other
+/// field needs a `<field>:` line, as in [`SectionTemplates`]. The default is
+/// `scope.content,findings.content,qa.verdict`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinalizeChecklist {
+    pub sections: Vec<(String, Vec<String>)>,
+}
+
+impl Default for FinalizeChecklist {
+    fn default() -> Self {
+        Self::parse("scope.content,findings.content,qa.verdict")
+            .expect("built-in finalize checklist parses")
+    }
+}
+
+impl FinalizeChecklist {
+    /// Fields checked by a built-in rule instead of a `<field>:` line.
+    pub const BUILT_IN_FIELDS: [&'static str; 2] = ["content", "verdict"];
+
+    /// Parses `scope.content,findings,qa.verdict,handoff.owner`: a bare key
+    /// only requires the section, `<section>.<field>` also the field.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut sections: Vec<(String, Vec<String>)> = Vec::new();
+        for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (section, field) = match part.split_once('.') {
+                Some((section, field)) => (section, Some(field.trim().to_lowercase())),
+                None => (part, None),
+            };
+            let section = SectionKey::new(section)?;
+            if let Some(field) = &field {
+                if field.is_empty()
+                    || !field
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                {
+                    return Err(DomainError::InvalidData(format!(
+                        "finalize checklist: field in '{part}' must be [a-z0-9_-]+"
+                    )));
+                }
+            }
+            let index = match sections.iter().position(|(key, _)| key == section.as_str()) {
+                Some(index) => index,
+                None => {
+                    sections.push((section.as_str().to_string(), Vec::new()));
+                    sections.len() - 1
+                }
+            };
+            let fields = &mut sections[index].1;
+            if let Some(field) = field.filter(|f| !fields.contains(f)) {
+                fields.push(field);
+            }
+        }
+        if sections.is_empty() {
+            return Err(DomainError::InvalidData(
+                "finalize checklist must require at least one section".into(),
+            ));
+        }
+        Ok(Self { sections })
+    }
+
+    /// Missing section keys, and `<section>.<field>` for every required field
+    /// a present section lacks.
+    pub fn missing(&self, pack: &Pack) -> (Vec<String>, Vec<String>) {
+        let mut missing_sections = Vec::new();
+        let mut missing_fields = Vec::new();
+        for (key, fields) in &self.sections {
+            let Some(section) = pack.sections.iter().find(|s| s.key.as_str() == key) else {
+                missing_sections.push(key.clone());
+                continue;
+            };
+            for field in fields {
+                let present = match field.as_str() {
+                    "content" => section_has_substance(section),
+                    "verdict" => section_contains_verdict(section),
+                    other => section_has_field_line(section, other),
+                };
+                if !present {
+                    missing_fields.push(format!("{key}.{field}"));
+                }
+            }
+        }
+        (missing_sections, missing_fields)
+    }
+}
+
+// ── SectionTemplates ──────────────────────────────────────────────────────────
+
+/// Finalize-time field requirements per section key, e.g. `qa` must carry
+/// `verdict:` and `checks:` lines. A field counts as present when a line of
+/// the section's title, description, ref titles/whys or diagram titles/whys
+/// starts with `<field>:` (case-insensitive, list markers allowed). Sections
+/// the [`FinalizeChecklist`] does not require are checked only when the pack
+/// has them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SectionTemplates {
+    pub required_fields: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SectionTemplates {
+    /// Parses `qa.verdict,qa.checks,risks.mitigation`.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut templates = Self::default();
+        for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (section, field) = part.split_once('.').ok_or_else(|| {
+                DomainError::InvalidData(format!(
+                    "section templates: expected <section>.<field>, got '{part}'"
+                ))
+            })?;
+            let section = SectionKey::new(section)?;
+            let field = field.trim().to_lowercase();
+            if field.is_empty()
+                || !field
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(DomainError::InvalidData(format!(
+                    "section templates: field in '{part}' must be [a-z0-9_-]+"
+                )));
+            }
+            templates
+                .required_fields
+                .entry(section.as_str().to_string())
+                .or_default()
+                .insert(field);
+        }
+        Ok(templates)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.required_fields.is_empty()
+    }
+
+    /// `<section>.<field>` for every templated field an existing section lacks.
+    pub fn missing_fields(&self, pack: &Pack) -> Vec<String> {
+        let mut missing = Vec::new();
+        for (key, fields) in &self.required_fields {
+            let Some(section) = pack.sections.iter().find(|s| s.key.as_str() == key) else {
+                continue;
+            };
+            for field in fields {
+                if !section_has_field_line(section, field) {
+                    missing.push(format!("{key}.{field}"));
+                }
+            }
+        }
+        missing
+    }
+}
+
+// ── TtlPolicy ─────────────────────────────────────────────────────────────────
+
+/// Where the TTL of a newly created pack came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TtlSource {
+    /// `ttl_minutes` was given in the request.
+    Explicit,
+    Tag(String),
+    /// Name prefix before the first `/`.
+    Namespace(String),
+    Default,
+}
+
+impl std::fmt::Display for TtlSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TtlSource::Explicit => write!(f, "explicit"),
+            TtlSource::Tag(tag) => write!(f, "tag:{}", tag),
+            TtlSource::Namespace(namespace) => write!(f, "namespace:{}", namespace),
+            TtlSource::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Create-time TTL defaults used when a write omits `ttl_minutes`.
+///
+/// A matching tag wins over the name namespace, which wins over the global
+/// default; when several tags match, the longest TTL applies so a pack is
+/// never kept for less than any of its policies asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TtlPolicy {
+    pub default_minutes: u64,
+    /// Keyed by lowercase tag.
+    pub by_tag: BTreeMap<String, u64>,
+    pub by_namespace: BTreeMap<String, u64>,
+}
+
+impl Default for TtlPolicy {
+    fn default() -> Self {
+        Self {
+            default_minutes: Self::DEFAULT_MINUTES,
+            by_tag: BTreeMap::new(),
+            by_namespace: BTreeMap::new(),
+        }
+    }
+}
+
+impl TtlPolicy {
+    pub const DEFAULT_MINUTES: u64 = 24 * 60;
+
+    /// Parses `default=24h,tag:compliance=30d,namespace:scratch=90m`.
+    /// Durations take an `m`/`h`/`d` suffix; a bare number means minutes.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let mut policy = Self::default();
+        for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(|| {
+                DomainError::InvalidData(format!(
+                    "ttl defaults: expected key=duration, got '{part}'"
+                ))
+            })?;
+            let minutes = parse_ttl_minutes(value.trim())?;
+            ttl_duration(minutes)?;
+            match key.trim().split_once(':') {
+                None if key.trim() == "default" => policy.default_minutes = minutes,
+                Some(("tag", tag)) if !tag.trim().is_empty() => {
+                    policy.by_tag.insert(tag.trim().to_lowercase(), minutes);
+                }
+                Some(("namespace", namespace)) if !namespace.trim().is_empty() => {
+                    policy
+                        .by_namespace
+                        .insert(namespace.trim().to_string(), minutes);
+                }
+                _ => {
+                    return Err(DomainError::InvalidData(format!(
+                        "ttl defaults: unknown key '{}' (expected default, tag:<tag>, namespace:<prefix>)",
+                        key.trim()
+                    )))
+                }
+            }
+        }
+        Ok(policy)
+    }
+
+    pub fn resolve(&self, name: Option<&PackName>, tags: &[String]) -> (u64, TtlSource) {
+        let by_tag = tags
+            .iter()
+            .filter_map(|tag| {
+                let tag = tag.to_lowercase();
+                self.by_tag.get(&tag).map(|minutes| (*minutes, tag))
+            })
+            .max_by_key(|(minutes, _)| *minutes);
+        if let Some((minutes, tag)) = by_tag {
+            return (minutes, TtlSource::Tag(tag));
+        }
+        let namespace = name
+            .and_then(|name| name.as_str().split_once('/'))
+            .map(|(ns, _)| ns);
+        if let Some((namespace, minutes)) =
+            namespace.and_then(|ns| self.by_namespace.get_key_value(ns))
+        {
+            return (*minutes, TtlSource::Namespace(namespace.clone()));
+        }
+        (self.default_minutes, TtlSource::Default)
+    }
+}
+
+fn parse_ttl_minutes(raw: &str) -> Result<u64> {
+    let (digits, factor) = match raw.char_indices().last() {
+        Some((idx, 'm')) => (&raw[..idx], 1),
+        Some((idx, 'h')) => (&raw[..idx], 60),
+        Some((idx, 'd')) => (&raw[..idx], 24 * 60),
+        _ => (raw, 1),
+    };
+    digits
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|value| value.checked_mul(factor))
+        .ok_or_else(|| {
+            DomainError::InvalidData(format!(
+                "ttl defaults: invalid duration '{raw}' (use e.g. 90m, 24h, 30d)"
+            ))
+        })
 }
 
 // ── Pack (aggregate root) ────────────────────────────────────────────────────
@@ -69,9 +1425,46 @@ pub struct Pack {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
+    /// `output read` keeps the pack alive for the server's sliding window
+    /// (see [`Pack::sliding_expiry`]).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub sliding_ttl: bool,
+    /// This pack's sliding window; `None` uses the server's
+    /// (`CONTEXT_PACK_SLIDING_TTL_MINUTES`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sliding_ttl_minutes: Option<u64>,
+    /// Never expires: `expires_at` holds [`Pack::pinned_expires_at`] until
+    /// `ttl pin=false` (or an explicit `ttl_minutes`) releases it.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "ReadDefaults::is_empty")]
+    pub read_defaults: ReadDefaults,
+    /// Pack this one was split off from (see `input split`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_from: Option<PackId>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sign_offs: Vec<SignOff>,
+    /// Relations to other packs, in the order they were added.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<PackLink>,
+    /// Most recent section removals, oldest first, capped at
+    /// [`Pack::MAX_REMOVED_SECTIONS`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_sections: Vec<RemovedSection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_write_reason: Option<WriteReason>,
+    /// Who created the pack (the caller's `actor`); see [`OwnershipMode`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
 }
 
 impl Pack {
+    pub const MAX_ENTRY_POINT_REFS: usize = 3;
+    pub const MAX_OWNER_CHARS: usize = 128;
+    /// Sliding window when `CONTEXT_PACK_SLIDING_TTL_MINUTES` is unset.
+    pub const DEFAULT_SLIDING_TTL_MINUTES: u64 = 24 * 60;
+    pub const MAX_REMOVED_SECTIONS: usize = 32;
+
     pub fn new(id: PackId, name: Option<PackName>) -> Self {
         let now = Utc::now();
         Self {
@@ -87,6 +1480,16 @@ impl Pack {
             created_at: now,
             updated_at: now,
             expires_at: now + Duration::hours(24),
+            sliding_ttl: false,
+            sliding_ttl_minutes: None,
+            pinned: false,
+            read_defaults: ReadDefaults::default(),
+            split_from: None,
+            sign_offs: Vec::new(),
+            links: Vec::new(),
+            removed_sections: Vec::new(),
+            last_write_reason: None,
+            owner: None,
         }
     }
 
@@ -102,67 +1505,248 @@ impl Pack {
         Ok(())
     }
 
+    /// Refs flagged `entry_point`, in section order.
+    pub fn entry_point_refs(&self) -> impl Iterator<Item = (&Section, &CodeRef)> {
+        self.sections.iter().flat_map(|section| {
+            section
+                .refs
+                .iter()
+                .filter(|r| r.entry_point)
+                .map(move |r| (section, r))
+        })
+    }
+
+    pub fn validate_entry_points(&self) -> Result<()> {
+        let pinned: Vec<String> = self
+            .entry_point_refs()
+            .map(|(section, r)| format!("{}/{}", section.key, r.key))
+            .collect();
+        if pinned.len() <= Self::MAX_ENTRY_POINT_REFS {
+            return Ok(());
+        }
+        Err(DomainError::DetailedInvalidData {
+            message: format!(
+                "at most {} refs may be marked entry_point (got {})",
+                Self::MAX_ENTRY_POINT_REFS,
+                pinned.len()
+            ),
+            details: json!({
+                "max_entry_points": Self::MAX_ENTRY_POINT_REFS,
+                "entry_point_refs": pinned,
+            }),
+        })
+    }
+
     pub(crate) fn touch(&mut self) {
         self.revision = self.revision.saturating_add(1);
         self.updated_at = Utc::now();
     }
 
-    // ── lifecycle FSM ─────────────────────────────────────────────────────────
-
-    pub fn set_status(&mut self, status: Status) -> Result<()> {
-        if self.status == status {
+    /// Records why the current revision was written. `None` keeps whatever
+    /// an earlier revision recorded; it stops applying by revision number.
+    pub fn set_write_reason(&mut self, reason: Option<&str>, now: DateTime<Utc>) -> Result<()> {
+        let Some(reason) = reason.map(str::trim).filter(|r| !r.is_empty()) else {
             return Ok(());
+        };
+        if reason.chars().count() > WriteReason::MAX_CHARS {
+            return Err(DomainError::InvalidData(format!(
+                "'reason' must be at most {} characters",
+                WriteReason::MAX_CHARS
+            )));
         }
-        match (self.status, status) {
-            (Status::Draft, Status::Finalized) => {
-                self.validate_finalize_gate()?;
-                self.status = status;
-                self.touch();
-                Ok(())
-            }
-            (Status::Finalized, Status::Draft) => {
-                self.status = status;
-                self.touch();
-                Ok(())
-            }
-            _ => Err(DomainError::InvalidState(
-                "unsupported status transition".into(),
-            )),
+        self.last_write_reason = Some(WriteReason {
+            revision: self.revision,
+            reason: reason.to_string(),
+            at: now,
+        });
+        Ok(())
+    }
+
+    /// Records the creating actor; blank means an unowned pack.
+    pub fn set_owner(&mut self, owner: Option<&str>) -> Result<()> {
+        let owner = owner.map(str::trim).filter(|o| !o.is_empty());
+        if owner.is_some_and(|o| o.chars().count() > Self::MAX_OWNER_CHARS) {
+            return Err(DomainError::InvalidData(format!(
+                "'actor' must be at most {} characters",
+                Self::MAX_OWNER_CHARS
+            )));
         }
+        self.owner = owner.map(str::to_string);
+        Ok(())
     }
 
-    pub fn validate_finalize_gate(&self) -> Result<()> {
-        let scope = self.find_section("scope");
-        let findings = self.find_section("findings");
-        let qa = self.find_section("qa");
+    /// Reason given for the current revision, if its writer stated one.
+    pub fn current_write_reason(&self) -> Option<&str> {
+        self.last_write_reason
+            .as_ref()
+            .filter(|r| r.revision == self.revision)
+            .map(|r| r.reason.as_str())
+    }
 
-        let mut missing_sections = Vec::new();
-        if scope.is_none() {
-            missing_sections.push("scope".to_string());
-        }
-        if findings.is_none() {
-            missing_sections.push("findings".to_string());
+    // ── change tracking ───────────────────────────────────────────────────────
+
+    /// Stamps sections and refs that differ from `previous` (all of them when
+    /// `None`) with this revision, carries earlier stamps for the rest, and
+    /// records sections that `previous` had and this pack lacks.
+    pub fn carry_change_revisions(&mut self, previous: Option<&Pack>) {
+        let revision = self.revision;
+        for section in &mut self.sections {
+            let before = previous.and_then(|p| p.sections.iter().find(|s| s.key == section.key));
+            for code_ref in &mut section.refs {
+                let ref_before = before.and_then(|s| s.refs.iter().find(|r| r.key == code_ref.key));
+                code_ref.changed_revision = match ref_before {
+                    Some(old) if old.same_content(code_ref) => old.changed_revision,
+                    _ => Some(revision),
+                };
+            }
+            section.changed_revision = match before {
+                Some(old) if old.same_content(section) => old.changed_revision,
+                _ => Some(revision),
+            };
         }
-        if qa.is_none() {
-            missing_sections.push("qa".to_string());
+        let Some(previous) = previous else {
+            return;
+        };
+        self.removed_sections = previous.removed_sections.clone();
+        for old in &previous.sections {
+            if !self.sections.iter().any(|s| s.key == old.key) {
+                self.removed_sections.push(RemovedSection {
+                    key: old.key.clone(),
+                    revision,
+                });
+            }
         }
+        self.forget_readded_sections();
+    }
 
-        let mut missing_fields = Vec::new();
-        if let Some(scope_section) = scope {
-            if !section_has_substance(scope_section) {
-                missing_fields.push("scope.content".to_string());
+    /// Folds the revisions a batch of edits stepped through into one: the
+    /// pack ends one revision after `previous`, stamped as if written at once.
+    pub fn squash_revisions_since(&mut self, previous: &Pack) {
+        self.revision = previous.revision.saturating_add(1);
+        self.updated_at = Utc::now();
+        self.carry_change_revisions(Some(previous));
+    }
+
+    /// Stamps one section (and optionally one of its refs) with the current
+    /// revision; call after [`Pack::touch`].
+    fn mark_section_changed(&mut self, section_key: &SectionKey, ref_key: Option<&RefKey>) {
+        let revision = self.revision;
+        if let Some(section) = self.sections.iter_mut().find(|s| s.key == *section_key) {
+            section.changed_revision = Some(revision);
+            if let Some(code_ref) =
+                ref_key.and_then(|k| section.refs.iter_mut().find(|r| r.key == *k))
+            {
+                code_ref.changed_revision = Some(revision);
             }
         }
-        if let Some(findings_section) = findings {
-            if !section_has_substance(findings_section) {
-                missing_fields.push("findings.content".to_string());
-            }
+        self.forget_readded_sections();
+    }
+
+    fn mark_section_removed(&mut self, key: &SectionKey) {
+        self.removed_sections.push(RemovedSection {
+            key: key.clone(),
+            revision: self.revision,
+        });
+        self.forget_readded_sections();
+    }
+
+    fn forget_readded_sections(&mut self) {
+        let sections = &self.sections;
+        self.removed_sections
+            .retain(|removed| !sections.iter().any(|s| s.key == removed.key));
+        let overflow = self
+            .removed_sections
+            .len()
+            .saturating_sub(Self::MAX_REMOVED_SECTIONS);
+        self.removed_sections.drain(..overflow);
+    }
+
+    /// Sections changed after `revision` and keys of sections removed after
+    /// it, for delta reads.
+    pub fn changes_since(&self, revision: u64) -> (Vec<&Section>, Vec<&SectionKey>) {
+        let changed = self
+            .sections
+            .iter()
+            .filter(|s| s.changed_since(revision))
+            .collect();
+        let removed = self
+            .removed_sections
+            .iter()
+            .filter(|r| r.revision > revision)
+            .map(|r| &r.key)
+            .collect();
+        (changed, removed)
+    }
+
+    // ── lifecycle FSM ─────────────────────────────────────────────────────────
+
+    pub fn set_status(&mut self, status: Status) -> Result<()> {
+        self.set_status_with(
+            status,
+            &FinalizeChecklist::default(),
+            &SectionTemplates::default(),
+        )
+    }
+
+    /// [`Pack::set_status`] with the configured finalize gate.
+    pub fn set_status_with(
+        &mut self,
+        status: Status,
+        checklist: &FinalizeChecklist,
+        templates: &SectionTemplates,
+    ) -> Result<()> {
+        if self.status == status {
+            return Ok(());
         }
-        if let Some(qa_section) = qa {
-            if !section_contains_verdict(qa_section) {
-                missing_fields.push("qa.verdict".to_string());
+        match (self.status, status) {
+            (Status::Draft, Status::Finalized) => {
+                self.validate_finalize_gate_with(checklist, templates)?;
+                self.status = status;
+                self.touch();
+                Ok(())
+            }
+            (Status::Finalized, Status::Draft) => {
+                self.status = status;
+                self.touch();
+                Ok(())
             }
+            _ => Err(DomainError::InvalidState(
+                "unsupported status transition".into(),
+            )),
         }
+    }
+
+    pub fn validate_finalize_gate(&self) -> Result<()> {
+        self.validate_finalize_gate_with(
+            &FinalizeChecklist::default(),
+            &SectionTemplates::default(),
+        )
+    }
+
+    /// Checks `checklist` (required sections and what they must contain) and
+    /// the configured per-section field requirements.
+    pub fn validate_finalize_gate_with(
+        &self,
+        checklist: &FinalizeChecklist,
+        templates: &SectionTemplates,
+    ) -> Result<()> {
+        let (missing_sections, mut missing_fields) = checklist.missing(self);
+        let mut labelled_fields: Vec<String> = missing_fields
+            .iter()
+            .filter(|field| {
+                field
+                    .split_once('.')
+                    .is_some_and(|(_, f)| !FinalizeChecklist::BUILT_IN_FIELDS.contains(&f))
+            })
+            .cloned()
+            .collect();
+        let template_fields: Vec<String> = templates
+            .missing_fields(self)
+            .into_iter()
+            .filter(|field| !missing_fields.contains(field))
+            .collect();
+        missing_fields.extend(template_fields.iter().cloned());
+        labelled_fields.extend(template_fields);
 
         if missing_sections.is_empty() && missing_fields.is_empty() {
             return Ok(());
@@ -175,18 +1759,138 @@ impl Pack {
         if !missing_fields.is_empty() {
             message_parts.push(format!("missing fields: {}", missing_fields.join(", ")));
         }
+        if let Some(example) = labelled_fields.first() {
+            let (section, field) = example.split_once('.').unwrap_or(("", example));
+            message_parts.push(format!(
+                "section templates need a '<field>:' line per field (e.g. '{field}: ...' in section {section})"
+            ));
+        }
 
         Err(DomainError::FinalizeValidation {
             message: message_parts.join("; "),
             missing_sections,
             missing_fields,
             invalid_refs: Vec::new(),
+            failed_rules: Vec::new(),
         })
     }
 
+    /// Sign-offs still describing this revision's content: the trailing run of
+    /// records, each of which produced the revision after the one it reviewed.
+    /// Any other write (content, TTL, status) breaks the run.
+    pub fn current_sign_offs(&self) -> Vec<&SignOff> {
+        let mut head = self.revision;
+        let mut current: Vec<&SignOff> = self
+            .sign_offs
+            .iter()
+            .rev()
+            .take_while(|sign_off| {
+                let follows = sign_off.revision.saturating_add(1) == head;
+                head = sign_off.revision;
+                follows
+            })
+            .collect();
+        current.reverse();
+        current
+    }
+
+    /// Whether `other` carries the same reviewable content (title, brief,
+    /// tags, sections); lifecycle and presentation fields are ignored.
+    pub fn same_content(&self, other: &Pack) -> bool {
+        self.title == other.title
+            && self.brief == other.brief
+            && self.tags == other.tags
+            && serde_json::to_value(&self.sections).ok()
+                == serde_json::to_value(&other.sections).ok()
+    }
+
+    /// Records a review of the current revision. Allowed on finalized packs:
+    /// a sign-off is review metadata, not pack content.
+    /// Replaces every link; an empty list clears them. Duplicates collapse.
+    pub fn set_links(&mut self, links: Vec<PackLink>) -> Result<()> {
+        self.assert_mutable()?;
+        let mut deduped: Vec<PackLink> = Vec::with_capacity(links.len());
+        for link in links {
+            self.check_link_target(&link)?;
+            if !deduped.contains(&link) {
+                deduped.push(link);
+            }
+        }
+        if deduped.len() > PackLink::MAX_PER_PACK {
+            return Err(DomainError::InvalidData(format!(
+                "a pack may carry at most {} links (got {})",
+                PackLink::MAX_PER_PACK,
+                deduped.len()
+            )));
+        }
+        self.links = deduped;
+        self.touch();
+        Ok(())
+    }
+
+    /// Appends one link; re-adding an existing link changes nothing but the
+    /// revision.
+    pub fn add_link(&mut self, link: PackLink) -> Result<()> {
+        let mut links = self.links.clone();
+        links.push(link);
+        self.set_links(links)
+    }
+
+    fn check_link_target(&self, link: &PackLink) -> Result<()> {
+        if link.target == self.id {
+            return Err(DomainError::InvalidData(format!(
+                "pack {} cannot link to itself",
+                self.id
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn record_sign_off(
+        &mut self,
+        reviewer: &str,
+        verdict: SignOffVerdict,
+        role: Option<&str>,
+        comment: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Result<&SignOff> {
+        let reviewer = reviewer.trim();
+        if reviewer.is_empty() || reviewer.chars().count() > SignOff::MAX_REVIEWER_CHARS {
+            return Err(DomainError::InvalidData(format!(
+                "'reviewer' must be 1..={} characters",
+                SignOff::MAX_REVIEWER_CHARS
+            )));
+        }
+        let comment = comment
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty());
+        if comment
+            .as_ref()
+            .is_some_and(|c| c.chars().count() > SignOff::MAX_COMMENT_CHARS)
+        {
+            return Err(DomainError::InvalidData(format!(
+                "'comment' must be at most {} characters",
+                SignOff::MAX_COMMENT_CHARS
+            )));
+        }
+        self.sign_offs.push(SignOff {
+            reviewer: reviewer.to_string(),
+            verdict,
+            revision: self.revision,
+            signed_at: now,
+            role: role
+                .map(|r| r.trim().to_lowercase())
+                .filter(|r| !r.is_empty()),
+            comment,
+        });
+        self.touch();
+        Ok(self.sign_offs.last().expect("just pushed"))
+    }
+
     pub fn set_ttl_from_now(&mut self, minutes: u64, now: DateTime<Utc>) -> Result<()> {
         let duration = ttl_duration(minutes)?;
         self.expires_at = now + duration;
+        self.pinned = false;
         self.touch();
         Ok(())
     }
@@ -199,6 +1903,11 @@ impl Pack {
 
     pub fn extend_ttl(&mut self, minutes: u64, now: DateTime<Utc>) -> Result<()> {
         let duration = ttl_duration(minutes)?;
+        if self.pinned {
+            return Err(DomainError::InvalidData(
+                "pack is pinned and never expires; unpin it or set ttl_minutes instead".into(),
+            ));
+        }
         let base = if self.expires_at > now {
             self.expires_at
         } else {
@@ -209,6 +1918,68 @@ impl Pack {
         Ok(())
     }
 
+    /// Turning sliding off also drops a per-pack window.
+    pub fn set_sliding_ttl(&mut self, enabled: bool) {
+        self.sliding_ttl = enabled;
+        if !enabled {
+            self.sliding_ttl_minutes = None;
+        }
+        self.touch();
+    }
+
+    /// Turns sliding on with a window of its own instead of the server's.
+    pub fn set_sliding_ttl_minutes(&mut self, minutes: u64) -> Result<()> {
+        ttl_duration(minutes)?;
+        self.sliding_ttl = true;
+        self.sliding_ttl_minutes = Some(minutes);
+        self.touch();
+        Ok(())
+    }
+
+    /// Expiry stored on pinned packs: far enough out that storage, the index
+    /// and older readers all treat the pack as live without special cases.
+    pub fn pinned_expires_at() -> DateTime<Utc> {
+        DateTime::<Utc>::from_naive_utc_and_offset(
+            chrono::NaiveDate::from_ymd_opt(9999, 12, 31)
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .expect("valid pinned expiry"),
+            Utc,
+        )
+    }
+
+    pub fn pin(&mut self) {
+        self.pinned = true;
+        self.expires_at = Self::pinned_expires_at();
+        self.touch();
+    }
+
+    /// Releases a pin; the pack expires `minutes` from `now`. A no-op on
+    /// packs that aren't pinned.
+    pub fn unpin(&mut self, minutes: u64, now: DateTime<Utc>) -> Result<()> {
+        if !self.pinned {
+            return Ok(());
+        }
+        self.set_ttl_from_now(minutes, now)
+    }
+
+    /// New expiry for a read at `now` of a `sliding_ttl` pack: `now` plus
+    /// the window (the pack's own, else `default_window_minutes`), once less
+    /// than half of it remains. `None` when the pack doesn't slide, has
+    /// already expired, or still has enough time left, so repeated reads
+    /// don't rewrite the pack each time.
+    pub fn sliding_expiry(
+        &self,
+        default_window_minutes: u64,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        if !self.sliding_ttl || self.pinned || self.is_expired(now) {
+            return None;
+        }
+        let window =
+            ttl_duration(self.sliding_ttl_minutes.unwrap_or(default_window_minutes)).ok()?;
+        (self.expires_at - now < window / 2).then(|| now + window)
+    }
+
     pub fn ttl_deadline_from_now(minutes: u64, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
         Ok(now + ttl_duration(minutes)?)
     }
@@ -226,6 +1997,9 @@ impl Pack {
     }
 
     pub fn ttl_remaining_human(&self, now: DateTime<Utc>) -> String {
+        if self.pinned {
+            return "never (pinned)".to_string();
+        }
         human_ttl(self.ttl_remaining_seconds(now))
     }
 
@@ -269,6 +2043,16 @@ impl Pack {
         Ok(())
     }
 
+    pub fn set_read_defaults(&mut self, defaults: ReadDefaults) -> Result<()> {
+        self.assert_mutable()?;
+        defaults.validate()?;
+        if self.read_defaults != defaults {
+            self.read_defaults = defaults;
+            self.touch();
+        }
+        Ok(())
+    }
+
     // ── section management ────────────────────────────────────────────────────
 
     pub fn upsert_section(
@@ -287,8 +2071,11 @@ impl Pack {
                 key,
                 title: String::new(),
                 description: None,
+                translations: BTreeMap::new(),
                 refs: Vec::new(),
                 diagrams: Vec::new(),
+                attachments: Vec::new(),
+                changed_revision: None,
             }
         };
         section.title = title;
@@ -301,8 +2088,10 @@ impl Pack {
                 .map(|idx| idx.min(self.sections.len()))
                 .unwrap_or(self.sections.len()),
         };
+        let key = section.key.clone();
         self.sections.insert(insert_at, section);
         self.touch();
+        self.mark_section_changed(&key, None);
         Ok(())
     }
 
@@ -317,9 +2106,120 @@ impl Pack {
             )));
         }
         self.touch();
+        self.mark_section_removed(key);
         Ok(())
     }
 
+    /// Detaches a section (refs and diagrams included) for a move to another pack.
+    pub fn take_section(&mut self, key: &SectionKey) -> Result<Section> {
+        self.assert_mutable()?;
+        let idx = self
+            .sections
+            .iter()
+            .position(|s| s.key == *key)
+            .ok_or_else(|| DomainError::NotFound(format!("section '{}' not found", key)))?;
+        let section = self.sections.remove(idx);
+        self.touch();
+        self.mark_section_removed(key);
+        Ok(section)
+    }
+
+    /// Appends a section moved from another pack. An explicit `key` must be
+    /// free; otherwise the section keeps its key, or gets the first free
+    /// `{key}-{n}` (n >= 2) when this pack already uses it. Returns the key used.
+    pub fn adopt_section(
+        &mut self,
+        mut section: Section,
+        key: Option<SectionKey>,
+    ) -> Result<SectionKey> {
+        self.assert_mutable()?;
+        let key = match key {
+            Some(key) if self.has_section(&key) => {
+                return Err(DomainError::Conflict(format!(
+                    "section '{}' already exists in pack {}",
+                    key, self.id
+                )))
+            }
+            Some(key) => key,
+            None if !self.has_section(&section.key) => section.key.clone(),
+            None => self.free_section_key(&section.key)?,
+        };
+        section.key = key.clone();
+        self.sections.push(section);
+        self.validate_entry_points()?;
+        self.touch();
+        let revision = self.revision;
+        if let Some(adopted) = self.sections.last_mut() {
+            for code_ref in &mut adopted.refs {
+                code_ref.changed_revision = Some(revision);
+            }
+        }
+        self.mark_section_changed(&key, None);
+        Ok(key)
+    }
+
+    /// Folds another pack's sections in, in order, as one revision. Free
+    /// keys are appended; a taken key follows `policy`. Merged sections and
+    /// their refs are stamped with the new revision.
+    pub fn merge_sections(
+        &mut self,
+        sections: Vec<Section>,
+        policy: MergePolicy,
+    ) -> Result<Vec<MergedSection>> {
+        self.assert_mutable()?;
+        self.touch();
+        let revision = self.revision;
+        let mut merged = Vec::with_capacity(sections.len());
+        for mut section in sections {
+            let source_key = section.key.clone();
+            let existing = self.sections.iter().position(|s| s.key == source_key);
+            let (key, outcome) = match (existing, policy) {
+                (None, _) => (source_key.clone(), MergeOutcome::Added),
+                (Some(_), MergePolicy::Skip) => {
+                    merged.push(MergedSection {
+                        key: source_key.clone(),
+                        source_key,
+                        outcome: MergeOutcome::Skipped,
+                    });
+                    continue;
+                }
+                (Some(_), MergePolicy::Replace) => (source_key.clone(), MergeOutcome::Replaced),
+                (Some(_), MergePolicy::Suffix) => {
+                    (self.free_section_key(&source_key)?, MergeOutcome::Suffixed)
+                }
+            };
+            section.key = key.clone();
+            for code_ref in &mut section.refs {
+                code_ref.changed_revision = Some(revision);
+            }
+            match (existing, outcome) {
+                (Some(index), MergeOutcome::Replaced) => self.sections[index] = section,
+                _ => self.sections.push(section),
+            }
+            self.mark_section_changed(&key, None);
+            merged.push(MergedSection {
+                source_key,
+                key,
+                outcome,
+            });
+        }
+        self.validate_entry_points()?;
+        Ok(merged)
+    }
+
+    fn has_section(&self, key: &SectionKey) -> bool {
+        self.sections.iter().any(|s| s.key == *key)
+    }
+
+    /// First `{key}-{n}` (n >= 2) this pack does not use.
+    fn free_section_key(&self, key: &SectionKey) -> Result<SectionKey> {
+        let base: String = key.as_str().chars().take(56).collect();
+        (2..)
+            .map(|n| SectionKey::new(&format!("{}-{}", base, n)))
+            .find(|candidate| candidate.as_ref().map_or(true, |c| !self.has_section(c)))
+            .expect("unbounded suffix search always finds a free key")
+    }
+
     // ── ref management ────────────────────────────────────────────────────────
 
     fn get_section_mut(&mut self, section_key: &SectionKey) -> Result<&mut Section> {
@@ -332,20 +2232,58 @@ impl Pack {
     pub fn upsert_ref(&mut self, section_key: &SectionKey, spec: RefSpec) -> Result<()> {
         self.assert_mutable()?;
         let section = self.get_section_mut(section_key)?;
-        let new_ref = CodeRef {
+        let mut new_ref = CodeRef {
             key: spec.key.clone(),
+            root: spec.root,
             path: spec.path,
             lines: spec.lines,
             title: spec.title,
             why: spec.why,
             group: spec.group,
+            entry_point: false,
+            git_sha: None,
+            symbol: spec.symbol,
+            url: spec.url,
+            section_ref: spec.section_ref,
+            excerpt_line_limit: spec.excerpt_line_limit,
+            snapshot: None,
+            content_hash: None,
+            changed_revision: None,
         };
         if let Some(existing) = section.refs.iter_mut().find(|r| r.key == spec.key) {
+            new_ref.entry_point = existing.entry_point;
+            new_ref.git_sha = existing.git_sha.clone();
+            new_ref.inherit_snapshot(existing);
+            new_ref.validate_target()?;
             *existing = new_ref;
         } else {
+            new_ref.validate_target()?;
             section.refs.push(new_ref);
         }
         self.touch();
+        self.mark_section_changed(section_key, Some(&spec.key));
+        Ok(())
+    }
+
+    /// Moves refs to new line ranges in one revision. Their content hashes
+    /// are cleared for the caller to re-stamp; snapshots are kept, since they
+    /// are the text the new range was matched against.
+    pub fn reanchor_refs(&mut self, moves: &[(SectionKey, RefKey, LineRange)]) -> Result<()> {
+        self.assert_mutable()?;
+        for (section_key, ref_key, lines) in moves {
+            let section = self.get_section_mut(section_key)?;
+            let code_ref = section
+                .refs
+                .iter_mut()
+                .find(|r| r.key == *ref_key)
+                .ok_or_else(|| DomainError::NotFound(format!("ref '{}' not found", ref_key)))?;
+            code_ref.lines = *lines;
+            code_ref.content_hash = None;
+        }
+        self.touch();
+        for (section_key, ref_key, _) in moves {
+            self.mark_section_changed(section_key, Some(ref_key));
+        }
         Ok(())
     }
 
@@ -361,6 +2299,7 @@ impl Pack {
             )));
         }
         self.touch();
+        self.mark_section_changed(section_key, None);
         Ok(())
     }
 
@@ -375,19 +2314,66 @@ impl Pack {
         why: Option<String>,
     ) -> Result<()> {
         self.assert_mutable()?;
+        let revision = self.revision;
         let section = self.get_section_mut(section_key)?;
-        let new_diagram = Diagram {
+        let mut new_diagram = Diagram {
             key: diagram_key.clone(),
             title,
             mermaid,
             why,
+            history: Vec::new(),
         };
         if let Some(existing) = section.diagrams.iter_mut().find(|d| d.key == diagram_key) {
+            new_diagram.inherit_history(existing, revision, Utc::now());
             *existing = new_diagram;
         } else {
             section.diagrams.push(new_diagram);
         }
         self.touch();
+        self.mark_section_changed(section_key, None);
+        Ok(())
+    }
+
+    // ── attachment management ─────────────────────────────────────────────────
+
+    pub fn upsert_attachment(
+        &mut self,
+        section_key: &SectionKey,
+        attachment: Attachment,
+    ) -> Result<()> {
+        self.assert_mutable()?;
+        let section = self.get_section_mut(section_key)?;
+        if let Some(existing) = section
+            .attachments
+            .iter_mut()
+            .find(|a| a.key == attachment.key)
+        {
+            *existing = attachment;
+        } else {
+            section.attachments.push(attachment);
+        }
+        self.touch();
+        self.mark_section_changed(section_key, None);
+        Ok(())
+    }
+
+    pub fn delete_attachment(
+        &mut self,
+        section_key: &SectionKey,
+        attachment_key: &AttachmentKey,
+    ) -> Result<()> {
+        self.assert_mutable()?;
+        let section = self.get_section_mut(section_key)?;
+        let before = section.attachments.len();
+        section.attachments.retain(|a| a.key != *attachment_key);
+        if section.attachments.len() == before {
+            return Err(DomainError::NotFound(format!(
+                "attachment '{}' not found",
+                attachment_key
+            )));
+        }
+        self.touch();
+        self.mark_section_changed(section_key, None);
         Ok(())
     }
 
@@ -403,12 +2389,6 @@ impl Pack {
         map
     }
 
-    fn find_section(&self, key: &str) -> Option<&Section> {
-        self.sections
-            .iter()
-            .find(|section| section.key.as_str() == key)
-    }
-
     // ── schema migration ──────────────────────────────────────────────────────
 
     pub fn migrate_schema(self) -> Result<Self> {
@@ -458,10 +2438,16 @@ fn human_ttl(seconds: i64) -> String {
     format!("{}d", days)
 }
 
-fn section_has_substance(section: &Section) -> bool {
+/// The section's description unless it is still a template placeholder.
+fn authored_description(section: &Section) -> Option<&str> {
     section
         .description
         .as_deref()
+        .filter(|description| !is_template_placeholder(description))
+}
+
+fn section_has_substance(section: &Section) -> bool {
+    authored_description(section)
         .map(|description| !description.trim().is_empty())
         .unwrap_or(false)
         || !section.refs.is_empty()
@@ -470,7 +2456,7 @@ fn section_has_substance(section: &Section) -> bool {
 
 fn section_contains_verdict(section: &Section) -> bool {
     text_contains_verdict(Some(section.title.as_str()))
-        || text_contains_verdict(section.description.as_deref())
+        || text_contains_verdict(authored_description(section))
         || section.refs.iter().any(|code_ref| {
             text_contains_verdict(code_ref.title.as_deref())
                 || text_contains_verdict(code_ref.why.as_deref())
@@ -481,6 +2467,32 @@ fn section_contains_verdict(section: &Section) -> bool {
         })
 }
 
+fn section_has_field_line(section: &Section, field: &str) -> bool {
+    let label = format!("{field}:");
+    let texts = [Some(section.title.as_str()), authored_description(section)]
+        .into_iter()
+        .chain(
+            section
+                .refs
+                .iter()
+                .flat_map(|r| [r.title.as_deref(), r.why.as_deref()]),
+        )
+        .chain(
+            section
+                .diagrams
+                .iter()
+                .flat_map(|d| [Some(d.title.as_str()), d.why.as_deref()]),
+        )
+        .flatten();
+    texts.flat_map(str::lines).any(|line| {
+        line.trim_start()
+            .trim_start_matches(['-', '*'])
+            .trim_start()
+            .to_ascii_lowercase()
+            .starts_with(&label)
+    })
+}
+
 fn text_contains_verdict(text: Option<&str>) -> bool {
     let Some(raw) = text else {
         return false;
@@ -488,6 +2500,57 @@ fn text_contains_verdict(text: Option<&str>) -> bool {
     raw.to_ascii_lowercase().contains("verdict")
 }
 
+// ── PackBundle ────────────────────────────────────────────────────────────────
+
+/// One pack serialized for moving between storage roots (`input export` /
+/// `input import`). The pack is carried as stored, so excerpt snapshots and
+/// diagram history travel with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackBundle {
+    pub format: String,
+    pub bundle_version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Every ref carries a snapshot taken at export time.
+    #[serde(default)]
+    pub excerpts_inlined: bool,
+    pub pack: Pack,
+}
+
+impl PackBundle {
+    pub const FORMAT: &'static str = "context_pack_bundle";
+    pub const VERSION: u32 = 1;
+
+    pub fn new(pack: Pack, excerpts_inlined: bool, now: DateTime<Utc>) -> Self {
+        Self {
+            format: Self::FORMAT.to_string(),
+            bundle_version: Self::VERSION,
+            exported_at: now,
+            excerpts_inlined,
+            pack,
+        }
+    }
+
+    /// Rejects other formats and bundle versions, and packs whose schema this
+    /// build does not read.
+    pub fn into_pack(self) -> Result<Pack> {
+        if self.format != Self::FORMAT {
+            return Err(DomainError::InvalidData(format!(
+                "not a context pack bundle: format '{}' (expected '{}')",
+                self.format,
+                Self::FORMAT
+            )));
+        }
+        if self.bundle_version != Self::VERSION {
+            return Err(DomainError::InvalidData(format!(
+                "unsupported bundle_version {} (supported: {})",
+                self.bundle_version,
+                Self::VERSION
+            )));
+        }
+        self.pack.migrate_schema()
+    }
+}
+
 // ── tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -521,11 +2584,16 @@ mod tests {
             &findings_key,
             RefSpec {
                 key: RefKey::new("finding-ref").unwrap(),
+                root: None,
                 path: RelativePath::new("src/main.rs").unwrap(),
                 lines: LineRange::new(1, 1).unwrap(),
                 title: Some("finding".into()),
                 why: Some("supports finding".into()),
                 group: None,
+                symbol: None,
+                url: None,
+                section_ref: None,
+                excerpt_line_limit: None,
             },
         )
         .unwrap();
@@ -616,11 +2684,16 @@ mod tests {
             &findings_key,
             RefSpec {
                 key: RefKey::new("finding-ref").unwrap(),
+                root: None,
                 path: RelativePath::new("src/main.rs").unwrap(),
                 lines: LineRange::new(1, 1).unwrap(),
                 title: None,
                 why: None,
                 group: None,
+                symbol: None,
+                url: None,
+                section_ref: None,
+                excerpt_line_limit: None,
             },
         )
         .unwrap();
@@ -660,17 +2733,55 @@ mod tests {
             &sk,
             RefSpec {
                 key: RefKey::new("ref-one").unwrap(),
+                root: None,
                 path: RelativePath::new("src/main.rs").unwrap(),
                 lines: LineRange::new(1, 5).unwrap(),
                 title: None,
                 why: None,
                 group: None,
+                symbol: None,
+                url: None,
+                section_ref: None,
+                excerpt_line_limit: None,
             },
         )
         .unwrap();
         assert_eq!(pack.revision, 3);
     }
 
+    #[test]
+    fn test_change_revisions_stamp_edits_and_record_removals() {
+        let mut pack = make_pack();
+        let kept = SectionKey::new("kept").unwrap();
+        let edited = SectionKey::new("edited").unwrap();
+        let dropped = SectionKey::new("dropped").unwrap();
+        for key in [&kept, &edited, &dropped] {
+            pack.upsert_section(key.clone(), "S".into(), None, None)
+                .unwrap();
+        }
+        let base = pack.revision;
+
+        let mut next = pack.clone();
+        next.revision = base + 1;
+        next.sections.retain(|s| s.key != dropped);
+        next.sections[1].description = Some("new text".into());
+        next.carry_change_revisions(Some(&pack));
+
+        let (changed, removed) = next.changes_since(base);
+        assert_eq!(
+            changed.iter().map(|s| s.key.as_str()).collect::<Vec<_>>(),
+            vec!["edited"]
+        );
+        assert_eq!(removed, vec![&dropped]);
+        assert!(next.changes_since(next.revision).0.is_empty());
+
+        next.upsert_section(dropped.clone(), "Back".into(), None, None)
+            .unwrap();
+        let (changed, removed) = next.changes_since(base + 1);
+        assert_eq!(changed.len(), 1);
+        assert!(removed.is_empty());
+    }
+
     #[test]
     fn test_upsert_ref_replaces_existing() {
         let mut pack = make_pack();
@@ -682,11 +2793,16 @@ mod tests {
             &sk,
             RefSpec {
                 key: rk.clone(),
+                root: None,
                 path: RelativePath::new("a.rs").unwrap(),
                 lines: LineRange::new(1, 5).unwrap(),
                 title: None,
                 why: None,
                 group: None,
+                symbol: None,
+                url: None,
+                section_ref: None,
+                excerpt_line_limit: None,
             },
         )
         .unwrap();
@@ -694,11 +2810,16 @@ mod tests {
             &sk,
             RefSpec {
                 key: rk.clone(),
+                root: None,
                 path: RelativePath::new("b.rs").unwrap(),
                 lines: LineRange::new(2, 7).unwrap(),
                 title: None,
                 why: None,
                 group: None,
+                symbol: None,
+                url: None,
+                section_ref: None,
+                excerpt_line_limit: None,
             },
         )
         .unwrap();
@@ -827,4 +2948,257 @@ mod tests {
             "remaining should be ~3600s, got: {remaining}"
         );
     }
+
+    #[test]
+    fn test_mermaid_stats_counts_links_and_distinct_nodes() {
+        let stats = MermaidStats::measure(
+            "flowchart LR\n  %% comment --> ignored\n  A[Client] -->|calls| B(Api)\n  B -.-> C\n  B ==> D --> A\n  style A fill:#fff\n",
+        );
+        assert_eq!(stats, MermaidStats { nodes: 4, edges: 4 });
+
+        let sequence = MermaidStats::measure(
+            "sequenceDiagram\n  participant Agent\n  Agent->>Store: write\n  Store-->>Agent: ok\n",
+        );
+        assert_eq!(sequence, MermaidStats { nodes: 2, edges: 2 });
+    }
+
+    #[test]
+    fn test_diagram_limits_report_exceeded_bounds() {
+        let limits = DiagramLimits {
+            max_bytes: 1024,
+            max_nodes: 2,
+            max_edges: 1,
+        };
+        let section = SectionKey::new("flow").unwrap();
+        let diagram = DiagramKey::new("main").unwrap();
+        limits
+            .validate(&section, &diagram, "graph TD\n  A --> B\n")
+            .unwrap();
+
+        let err = limits
+            .validate(&section, &diagram, "graph TD\n  A --> B --> C\n")
+            .unwrap_err();
+        match err {
+            DomainError::DetailedInvalidData { details, .. } => {
+                assert_eq!(details["diagram_key"], "main");
+                let exceeded: Vec<&str> = details["exceeded"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .filter_map(|item| item["limit"].as_str())
+                    .collect();
+                assert_eq!(exceeded, vec!["max_nodes", "max_edges"]);
+            }
+            other => panic!("expected DetailedInvalidData, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_ttl_policy_prefers_tag_then_namespace_then_default() {
+        let policy =
+            TtlPolicy::parse("default=12h, tag:compliance=30d, tag:audit=7d, namespace:scratch=90")
+                .unwrap();
+        let name = |raw: &str| PackName::new(raw).unwrap();
+        let tags = |raw: &[&str]| raw.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            policy.resolve(Some(&name("scratch/auth")), &tags(&["Audit", "compliance"])),
+            (30 * 24 * 60, TtlSource::Tag("compliance".into()))
+        );
+        assert_eq!(
+            policy.resolve(Some(&name("scratch/auth")), &tags(&["misc"])),
+            (90, TtlSource::Namespace("scratch".into()))
+        );
+        assert_eq!(
+            policy.resolve(Some(&name("scratch-auth")), &[]),
+            (12 * 60, TtlSource::Default)
+        );
+        assert!(TtlPolicy::parse("owner:bob=1d").is_err());
+        assert!(TtlPolicy::parse("default=0").is_err());
+        assert!(TtlPolicy::parse("default=soon").is_err());
+    }
+
+    #[test]
+    fn test_section_templates_require_labelled_field_lines() {
+        let templates = SectionTemplates::parse("qa.verdict, qa.Checks,risks.mitigation").unwrap();
+        assert_eq!(templates.required_fields["qa"].len(), 2);
+        assert!(SectionTemplates::parse("qa").is_err());
+        assert!(SectionTemplates::parse("qa.").is_err());
+        assert!(SectionTemplates::parse("QA.verdict").is_err());
+
+        let mut pack = make_pack();
+        seed_finalize_minimum(&mut pack);
+        let err = pack
+            .validate_finalize_gate_with(&FinalizeChecklist::default(), &templates)
+            .expect_err("qa lacks a checks: line");
+        match err {
+            DomainError::FinalizeValidation {
+                message,
+                missing_fields,
+                ..
+            } => {
+                // `risks` is absent, so only the qa template applies.
+                assert_eq!(missing_fields, vec!["qa.checks".to_string()]);
+                assert!(message.contains("'checks: ...' in section qa"), "{message}");
+            }
+            other => panic!("expected FinalizeValidation, got: {other:?}"),
+        }
+
+        pack.upsert_section(
+            SectionKey::new("qa").unwrap(),
+            "QA".into(),
+            Some("Verdict: pass\n- checks: cargo test".into()),
+            None,
+        )
+        .unwrap();
+        pack.validate_finalize_gate_with(&FinalizeChecklist::default(), &templates)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_finalize_checklist_replaces_built_in_sections() {
+        let checklist =
+            FinalizeChecklist::parse("handoff.owner, handoff, findings.content").unwrap();
+        assert_eq!(
+            checklist.sections,
+            vec![
+                ("handoff".to_string(), vec!["owner".to_string()]),
+                ("findings".to_string(), vec!["content".to_string()]),
+            ]
+        );
+        assert!(FinalizeChecklist::parse("").is_err());
+        assert!(FinalizeChecklist::parse("qa.").is_err());
+        assert!(FinalizeChecklist::parse("QA").is_err());
+
+        let templates = SectionTemplates::default();
+        let mut pack = make_pack();
+        seed_finalize_minimum(&mut pack);
+        match pack.validate_finalize_gate_with(&checklist, &templates) {
+            Err(DomainError::FinalizeValidation {
+                missing_sections, ..
+            }) => assert_eq!(missing_sections, vec!["handoff".to_string()]),
+            other => panic!("expected FinalizeValidation, got: {other:?}"),
+        }
+
+        pack.upsert_section(
+            SectionKey::new("handoff").unwrap(),
+            "Handoff".into(),
+            Some("notes".into()),
+            None,
+        )
+        .unwrap();
+        match pack.validate_finalize_gate_with(&checklist, &templates) {
+            Err(DomainError::FinalizeValidation {
+                missing_fields,
+                message,
+                ..
+            }) => {
+                assert_eq!(missing_fields, vec!["handoff.owner".to_string()]);
+                assert!(
+                    message.contains("'owner: ...' in section handoff"),
+                    "{message}"
+                );
+            }
+            other => panic!("expected FinalizeValidation, got: {other:?}"),
+        }
+
+        pack.delete_section(&SectionKey::new("qa").unwrap())
+            .unwrap();
+        pack.upsert_section(
+            SectionKey::new("handoff").unwrap(),
+            "Handoff".into(),
+            Some("owner: infra team".into()),
+            None,
+        )
+        .unwrap();
+        pack.set_status_with(Status::Finalized, &checklist, &templates)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sign_off_policy_counts_only_current_latest_approvals() {
+        let policy = SignOffPolicy::parse("approvals=2, role=Security").unwrap();
+        let mut pack = Pack::new(PackId::new(), None);
+        let now = Utc::now();
+        pack.record_sign_off(
+            "carol",
+            SignOffVerdict::Approved,
+            Some("security"),
+            None,
+            now,
+        )
+        .unwrap();
+        // A content write between sign-offs makes carol's approval stale.
+        pack.touch();
+        pack.record_sign_off("alice", SignOffVerdict::Approved, None, None, now)
+            .unwrap();
+        pack.record_sign_off("bob", SignOffVerdict::Approved, None, None, now)
+            .unwrap();
+        pack.record_sign_off("bob", SignOffVerdict::ChangesRequested, None, None, now)
+            .unwrap();
+        assert_eq!(pack.current_sign_offs().len(), 3);
+
+        match policy.check(&pack.current_sign_offs()) {
+            Err(DomainError::SignOffRequired {
+                current_approvals,
+                missing_roles,
+                outstanding,
+                ..
+            }) => {
+                assert_eq!(current_approvals, 1);
+                assert_eq!(missing_roles, vec!["security".to_string()]);
+                assert_eq!(
+                    outstanding,
+                    vec!["1 more approval(s) (1 of 2)", "role:security"]
+                );
+            }
+            other => panic!("expected SignOffRequired, got {other:?}"),
+        }
+
+        pack.record_sign_off(
+            "carol",
+            SignOffVerdict::Approved,
+            Some("SECURITY"),
+            None,
+            now,
+        )
+        .unwrap();
+        policy.check(&pack.current_sign_offs()).unwrap();
+        assert!(SignOffPolicy::parse("approvals=two").is_err());
+        assert!(SignOffPolicy::parse("owner=bob").is_err());
+    }
+
+    #[test]
+    fn test_attachment_validates_media_type_and_size() {
+        let section = SectionKey::new("findings").unwrap();
+        let key = || AttachmentKey::new("log").unwrap();
+        let now = Utc::now();
+        let plain = Attachment::new(&section, key(), None, None, "ok\n".into(), 8, now).unwrap();
+        assert_eq!(plain.media_type, Attachment::DEFAULT_MEDIA_TYPE);
+        assert_eq!(plain.fence_lang(), "text");
+        let yaml = Attachment::new(
+            &section,
+            key(),
+            None,
+            Some("Application/X-YAML"),
+            "a: 1".into(),
+            8,
+            now,
+        )
+        .unwrap();
+        assert_eq!(yaml.fence_lang(), "yaml");
+
+        for media_type in ["json", "text/", "text/plain; charset=utf-8"] {
+            assert!(
+                Attachment::new(&section, key(), None, Some(media_type), "x".into(), 8, now)
+                    .is_err(),
+                "{media_type}"
+            );
+        }
+        assert!(Attachment::new(&section, key(), None, None, String::new(), 8, now).is_err());
+        assert!(matches!(
+            Attachment::new(&section, key(), None, None, "123456789".into(), 8, now),
+            Err(DomainError::DetailedInvalidData { .. })
+        ));
+    }
 }
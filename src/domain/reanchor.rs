@@ -0,0 +1,122 @@
+//! Finding where a ref's excerpt went after the file was edited around it.
+//! A ref's captured excerpt (its snapshot, or only its content hash) is slid
+//! over the current file one line at a time; a window matches when enough of
+//! its lines equal the excerpt's, ignoring leading and trailing whitespace.
+
+use super::models::excerpt_content_hash;
+
+/// Fraction of excerpt lines a window must reproduce to count as the same
+/// code; below this a repair would guess rather than re-anchor.
+pub const MIN_MATCH_SCORE: f64 = 0.6;
+
+/// Where an excerpt was found: 1-based inclusive lines and the fraction of
+/// its lines that matched (`1.0` for an exact or content-hash match).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anchor {
+    pub start: usize,
+    pub end: usize,
+    pub score: f64,
+}
+
+/// Best window of `file` for `excerpt`, line for line. Ties go to the window
+/// starting nearest `near` (the ref's old start), so a duplicated block
+/// re-anchors to the copy that moved least. `None` when no window reaches
+/// [`MIN_MATCH_SCORE`] or the excerpt is blank.
+pub fn find_excerpt(file: &[&str], excerpt: &[&str], near: usize) -> Option<Anchor> {
+    let wanted: Vec<&str> = excerpt.iter().map(|line| line.trim()).collect();
+    let significant = wanted.iter().filter(|line| !line.is_empty()).count();
+    if significant == 0 || wanted.len() > file.len() {
+        return None;
+    }
+    let mut best: Option<(usize, usize)> = None;
+    for offset in 0..=file.len() - wanted.len() {
+        let matched = wanted
+            .iter()
+            .zip(&file[offset..])
+            .filter(|(want, have)| !want.is_empty() && **want == have.trim())
+            .count();
+        let better = best.is_none_or(|(best_offset, best_matched)| {
+            matched > best_matched
+                || (matched == best_matched
+                    && (offset + 1).abs_diff(near) < (best_offset + 1).abs_diff(near))
+        });
+        if better {
+            best = Some((offset, matched));
+        }
+    }
+    let (offset, matched) = best?;
+    let score = matched as f64 / significant as f64;
+    (score >= MIN_MATCH_SCORE).then_some(Anchor {
+        start: offset + 1,
+        end: offset + wanted.len(),
+        score,
+    })
+}
+
+/// Window of `len` lines whose [`excerpt_content_hash`] is `hash`, nearest
+/// `near` when the block occurs more than once.
+pub fn find_by_hash(file: &[&str], len: usize, hash: &str, near: usize) -> Option<Anchor> {
+    if len == 0 || len > file.len() {
+        return None;
+    }
+    (0..=file.len() - len)
+        .filter(|&offset| excerpt_content_hash(&numbered(&file[offset..offset + len])) == hash)
+        .min_by_key(|&offset| (offset + 1).abs_diff(near))
+        .map(|offset| Anchor {
+            start: offset + 1,
+            end: offset + len,
+            score: 1.0,
+        })
+}
+
+/// `lines` in the numbered-excerpt format the content hash is defined over.
+fn numbered(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| format!("{:>4}: {}", i + 1, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_excerpt_follows_moved_and_lightly_edited_blocks() {
+        let file = [
+            "use std::io;",
+            "",
+            "fn helper() {}",
+            "",
+            "fn parse(input: &str) -> usize {",
+            "    let n = input.len();",
+            "    n * 2",
+            "}",
+        ];
+        let excerpt = [
+            "fn parse(input: &str) -> usize {",
+            "  let n = input.len();",
+            "    n",
+            "}",
+        ];
+        let anchor = find_excerpt(&file, &excerpt, 1).unwrap();
+        assert_eq!((anchor.start, anchor.end), (5, 8));
+        assert!((anchor.score - 0.75).abs() < f64::EPSILON);
+
+        let unrelated = ["struct Other;", "impl Other {}"];
+        assert_eq!(find_excerpt(&file, &unrelated, 1), None);
+        assert_eq!(find_excerpt(&file, &["", "  "], 1), None);
+    }
+
+    #[test]
+    fn test_find_by_hash_prefers_the_copy_nearest_the_old_range() {
+        let file = ["a: 1", "b", "x", "a: 1", "b"];
+        let hash = excerpt_content_hash("  10: a: 1\n  11: b");
+        let near_end = find_by_hash(&file, 2, &hash, 4).unwrap();
+        assert_eq!((near_end.start, near_end.end), (4, 5));
+        assert_eq!(find_by_hash(&file, 2, &hash, 1).unwrap().start, 1);
+        assert_eq!(find_by_hash(&file, 2, "0000000000000000", 1), None);
+    }
+}
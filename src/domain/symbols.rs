@@ -0,0 +1,172 @@
+//! Heuristic definition lookup for symbol-anchored refs, without a parser.
+//! A definition is a line naming the symbol after a declaration keyword
+//! (`fn`, `struct`, `impl … for`, `class`, `def`, `func`, …). It runs to its
+//! matching close brace or terminating `;`, or, for a declaration ending in
+//! `:`, to the end of its indented body. Comments, attributes and decorators
+//! directly above it are included.
+
+use regex::Regex;
+
+use super::types::SymbolName;
+
+/// Lines a declaration may span before its body opens; longer runs without
+/// `{`, `;` or `:` are taken to be a one-line item.
+const MAX_SIGNATURE_LINES: usize = 16;
+
+/// 1-based inclusive line range of `symbol` in `text`. A qualified name is
+/// looked up inside each definition of its enclosing item in turn, so
+/// `Parser::parse` finds the method in `impl Parser` even when
+/// `struct Parser` comes first.
+pub fn locate_symbol(text: &str, symbol: &SymbolName) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = text.lines().collect();
+    locate_in(&lines, 0, lines.len(), &symbol.segments()).map(|(start, end)| (start + 1, end + 1))
+}
+
+fn locate_in(lines: &[&str], from: usize, to: usize, segments: &[&str]) -> Option<(usize, usize)> {
+    let (name, inner) = segments.split_first()?;
+    let definition = definition_re(name);
+    for decl in (from..to).filter(|&i| definition.is_match(lines[i])) {
+        let end = item_end(lines, decl, to);
+        if inner.is_empty() {
+            return Some((leading_trivia_start(lines, decl, from), end));
+        }
+        if let Some(found) = locate_in(lines, decl + 1, end + 1, inner) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn definition_re(name: &str) -> Regex {
+    let name = regex::escape(name);
+    Regex::new(&format!(
+        r#"^\s*(?:(?:pub(?:\([^)]*\))?|export|default|async|unsafe|const|extern(?:\s+"[^"]*")?|public|private|protected|internal|static|final|abstract|override|open|sealed|data|inline)\s+)*(?:(?:fn|struct|enum|trait|union|type|mod|class|interface|object|def|function|func|fun|const|static|let|var|val|macro_rules!)\s+{name}\b|impl(?:<[^>]*>)?\s+(?:[\w:<>&', ]+\s+for\s+)?{name}\b|func\s*\([^)]*\)\s*{name}\b)"#
+    ))
+    .expect("definition regex is built from an escaped identifier")
+}
+
+/// 0-based last line of the item declared on `decl`, below `to`.
+fn item_end(lines: &[&str], decl: usize, to: usize) -> usize {
+    if code_text(lines[decl]).trim_end().ends_with(':') {
+        return indented_block_end(lines, decl, to);
+    }
+    let mut braces = 0usize;
+    let mut parens = 0usize;
+    let mut opened = false;
+    for (i, line) in lines.iter().enumerate().take(to).skip(decl) {
+        for c in code_text(line).chars() {
+            match c {
+                '(' | '[' => parens += 1,
+                ')' | ']' => parens = parens.saturating_sub(1),
+                '{' => {
+                    braces += 1;
+                    opened = true;
+                }
+                '}' if opened => {
+                    braces = braces.saturating_sub(1);
+                    if braces == 0 {
+                        return i;
+                    }
+                }
+                ';' if !opened && parens == 0 => return i,
+                _ => {}
+            }
+        }
+        if !opened && i - decl + 1 >= MAX_SIGNATURE_LINES {
+            return decl;
+        }
+    }
+    if opened {
+        to.saturating_sub(1).max(decl)
+    } else {
+        decl
+    }
+}
+
+fn indented_block_end(lines: &[&str], decl: usize, to: usize) -> usize {
+    let indent = indent_of(lines[decl]);
+    let mut end = decl;
+    for (i, line) in lines.iter().enumerate().take(to).skip(decl + 1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if indent_of(line) <= indent {
+            break;
+        }
+        end = i;
+    }
+    end
+}
+
+fn leading_trivia_start(lines: &[&str], decl: usize, from: usize) -> usize {
+    let mut start = decl;
+    while start > from {
+        let above = lines[start - 1].trim_start();
+        let trivia = ["//", "#", "@", "/*", "*"]
+            .iter()
+            .any(|prefix| above.starts_with(prefix));
+        if !trivia {
+            break;
+        }
+        start -= 1;
+    }
+    start
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// `line` with string literals blanked and a trailing `//` comment dropped,
+/// so braces inside them are not counted.
+fn code_text(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '/' if chars.peek() == Some(&'/') => break,
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locate(text: &str, symbol: &str) -> Option<(usize, usize)> {
+        locate_symbol(text, &SymbolName::new(symbol).unwrap())
+    }
+
+    #[test]
+    fn test_locate_symbol_spans_rust_items_with_docs_and_qualified_methods() {
+        let source = "use std::fmt;\n\n/// A parser.\n#[derive(Debug)]\npub struct Parser {\n    depth: usize,\n}\n\nimpl Parser {\n    pub fn new() -> Self {\n        let open = \"{\"; // not a brace\n        Self { depth: 0 }\n    }\n\n    pub(crate) fn parse(&mut self, buf: [u8; 4]) -> usize {\n        buf.len()\n    }\n}\n\nconst LIMIT: usize = 4;\n";
+        assert_eq!(locate(source, "Parser"), Some((3, 7)));
+        assert_eq!(locate(source, "Parser::new"), Some((10, 13)));
+        assert_eq!(locate(source, "Parser::parse"), Some((15, 17)));
+        assert_eq!(locate(source, "LIMIT"), Some((20, 20)));
+        assert_eq!(locate(source, "Parser::missing"), None);
+        assert_eq!(locate(source, "pars"), None);
+    }
+
+    #[test]
+    fn test_locate_symbol_follows_python_indentation() {
+        let source = "class Store:\n    @property\n    def size(self):\n        return 1\n\n    def load(self, raw: dict = {}):\n        if raw:\n\n            return raw\n\nx = Store()\n";
+        assert_eq!(locate(source, "Store"), Some((1, 9)));
+        assert_eq!(locate(source, "Store.size"), Some((2, 4)));
+        assert_eq!(locate(source, "Store.load"), Some((6, 9)));
+    }
+}
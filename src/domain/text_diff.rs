@@ -0,0 +1,60 @@
+/// Line-oriented diff rendered in a unified-like form: unchanged lines are
+/// prefixed with `"  "`, removed lines with `"- "`, added lines with `"+ "`.
+///
+/// Uses a plain LCS table; inputs here are bounded (diagrams, section text),
+/// so the quadratic cost is acceptable.
+pub fn line_diff(old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let (n, m) = (old_lines.len(), new_lines.len());
+
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_lines[i] == new_lines[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0usize, 0usize);
+    while i < n && j < m {
+        if old_lines[i] == new_lines[j] {
+            out.push(format!("  {}", old_lines[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(format!("- {}", old_lines[i]));
+            i += 1;
+        } else {
+            out.push(format!("+ {}", new_lines[j]));
+            j += 1;
+        }
+    }
+    out.extend(old_lines[i..].iter().map(|line| format!("- {line}")));
+    out.extend(new_lines[j..].iter().map(|line| format!("+ {line}")));
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::line_diff;
+
+    #[test]
+    fn test_line_diff_marks_removed_and_added_lines() {
+        let diff = line_diff(
+            "graph TD\n  A --> B\n  B --> C",
+            "graph TD\n  A --> B\n  B --> D",
+        );
+        assert_eq!(diff, "  graph TD\n    A --> B\n-   B --> C\n+   B --> D");
+    }
+
+    #[test]
+    fn test_line_diff_of_identical_inputs_has_no_markers() {
+        let diff = line_diff("a\nb", "a\nb");
+        assert_eq!(diff, "  a\n  b");
+    }
+}
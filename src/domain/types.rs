@@ -11,6 +11,15 @@ pub const MAX_REF_LINE_SPAN: usize = 2_000;
 
 static TOKEN_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^[a-z0-9][a-z0-9_\-]{1,63}$").expect("token regex must compile"));
+static LANGUAGE_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[a-z]{2,3}(-[a-z0-9]{2,8}){0,2}$").expect("language tag regex must compile")
+});
+static GIT_SHA_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[0-9a-f]{7,64}$").expect("git sha regex must compile"));
+static SYMBOL_NAME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*(?:(?:::|\.)[A-Za-z_][A-Za-z0-9_]*){0,3}$")
+        .expect("symbol name regex must compile")
+});
 static PACK_ID_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^pk_[a-z2-7]{8}$").expect("pack id regex must compile"));
 
@@ -177,6 +186,159 @@ impl fmt::Display for DiagramKey {
     }
 }
 
+// ── AttachmentKey ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AttachmentKey(String);
+
+impl AttachmentKey {
+    pub fn new(s: &str) -> Result<Self> {
+        validate_token("attachment_key", s.trim())?;
+        Ok(Self(s.trim().to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for AttachmentKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// ── LanguageTag ───────────────────────────────────────────────────────────────
+
+/// BCP 47-style language tag (`ru`, `pt-br`), stored lowercase.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LanguageTag(String);
+
+impl LanguageTag {
+    pub fn new(s: &str) -> Result<Self> {
+        let tag = s.trim().replace('_', "-").to_ascii_lowercase();
+        if !LANGUAGE_TAG_RE.is_match(&tag) {
+            return Err(DomainError::InvalidData(format!(
+                "language tag must look like 'ru' or 'pt-br' (got '{}')",
+                s.trim()
+            )));
+        }
+        Ok(Self(tag))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The language without region or script (`pt` for `pt-br`).
+    pub fn primary(&self) -> &str {
+        self.0.split('-').next().unwrap_or(&self.0)
+    }
+}
+
+impl fmt::Display for LanguageTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// ── GitSha ────────────────────────────────────────────────────────────────────
+
+/// Commit id a ref is pinned to: 7-64 hex digits (abbreviated, SHA-1 or
+/// SHA-256), stored lowercase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GitSha(String);
+
+impl GitSha {
+    pub fn new(s: &str) -> Result<Self> {
+        let sha = s.trim().to_ascii_lowercase();
+        if !GIT_SHA_RE.is_match(&sha) {
+            return Err(DomainError::InvalidData(format!(
+                "git_sha must be 7-64 hex digits (got '{}')",
+                s.trim()
+            )));
+        }
+        Ok(Self(sha))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for GitSha {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// ── SourceRootName ────────────────────────────────────────────────────────────
+
+/// Name of an extra source root (`CONTEXT_PACK_SOURCE_ROOTS`) a ref reads
+/// from instead of the default one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SourceRootName(String);
+
+impl SourceRootName {
+    pub fn new(s: &str) -> Result<Self> {
+        validate_token("root", s.trim())?;
+        Ok(Self(s.trim().to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SourceRootName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// ── SymbolName ────────────────────────────────────────────────────────────────
+
+/// Definition a ref is anchored to: an identifier, optionally qualified by
+/// its enclosing items (`Parser::parse`, `Config.load`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SymbolName(String);
+
+impl SymbolName {
+    pub fn new(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.len() > 200 || !SYMBOL_NAME_RE.is_match(s) {
+            return Err(DomainError::InvalidData(format!(
+                "symbol must be an identifier, optionally qualified with '::' or '.' (up to 4 parts), got '{}'",
+                s
+            )));
+        }
+        Ok(Self(s.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The identifiers from the outermost item inward.
+    pub fn segments(&self) -> Vec<&str> {
+        self.0
+            .split("::")
+            .flat_map(|part| part.split('.'))
+            .collect()
+    }
+}
+
+impl fmt::Display for SymbolName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 // ── RelativePath ──────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -214,6 +376,12 @@ impl RelativePath {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Directory or glob (`src/auth/**`, `docs/`) rather than one file; see
+    /// [`super::glob`].
+    pub fn is_pattern(&self) -> bool {
+        super::glob::is_pattern(&self.0)
+    }
 }
 
 impl fmt::Display for RelativePath {
@@ -222,6 +390,101 @@ impl fmt::Display for RelativePath {
     }
 }
 
+// ── ExternalUrl ───────────────────────────────────────────────────────────────
+
+/// Link an external ref points at (an RFC, issue or design doc): an absolute
+/// `http(s)` URL without whitespace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ExternalUrl(String);
+
+impl ExternalUrl {
+    pub fn new(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let rest = s
+            .strip_prefix("https://")
+            .or_else(|| s.strip_prefix("http://"));
+        let host = rest.and_then(|rest| rest.split(['/', '?', '#']).next());
+        if host.is_none_or(|host| matches!(host, "" | "." | ".."))
+            || s.len() > 2048
+            || s.chars().any(|c| c.is_whitespace() || c.is_control())
+        {
+            return Err(DomainError::InvalidData(format!(
+                "url must be an absolute http(s) URL without spaces, got '{}'",
+                s
+            )));
+        }
+        Ok(Self(s.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Host and path without the scheme, query or fragment
+    /// (`www.rfc-editor.org/rfc/rfc7519`): the `path` a url ref is stored
+    /// and listed under.
+    pub fn locator(&self) -> RelativePath {
+        let rest = self.0.split_once("://").map_or(&*self.0, |(_, rest)| rest);
+        let rest = rest.split(['?', '#']).next().unwrap_or_default();
+        let segments: Vec<String> = rest
+            .split('/')
+            .filter(|segment| !segment.is_empty() && *segment != "." && *segment != "..")
+            .map(|segment| segment.replace('*', "%2A"))
+            .collect();
+        RelativePath(segments.join("/"))
+    }
+}
+
+impl fmt::Display for ExternalUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// ── PackSectionRef ────────────────────────────────────────────────────────────
+
+/// Section of another pack a ref quotes instead of source lines, written
+/// `pack:<id>#<section_key>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackSectionRef {
+    pub pack: PackId,
+    pub section: SectionKey,
+}
+
+impl PackSectionRef {
+    pub const PREFIX: &'static str = "pack:";
+
+    /// Whether `raw` uses the `pack:` form at all (it may still be malformed).
+    pub fn is_pack_ref(raw: &str) -> bool {
+        raw.trim().starts_with(Self::PREFIX)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self> {
+        let malformed = || {
+            DomainError::InvalidData(format!(
+                "section ref must look like pack:<id>#<section_key>, got '{}'",
+                raw.trim()
+            ))
+        };
+        let (pack, section) = raw
+            .trim()
+            .strip_prefix(Self::PREFIX)
+            .and_then(|rest| rest.split_once('#'))
+            .ok_or_else(malformed)?;
+        Ok(Self {
+            pack: PackId::parse(pack)?,
+            section: SectionKey::new(section)?,
+        })
+    }
+}
+
+impl fmt::Display for PackSectionRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}#{}", Self::PREFIX, self.pack, self.section)
+    }
+}
+
 // ── LineRange ─────────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -285,6 +548,51 @@ impl FromStr for Status {
     }
 }
 
+// ── OutputProfile ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputProfile {
+    #[default]
+    Orchestrator,
+    Reviewer,
+    Executor,
+    /// Full, unpaged render with only the stable legend fields.
+    Archive,
+    /// Unpaged navigation view: headings, ref keys, paths and staleness.
+    Outline,
+}
+
+impl fmt::Display for OutputProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputProfile::Orchestrator => write!(f, "orchestrator"),
+            OutputProfile::Reviewer => write!(f, "reviewer"),
+            OutputProfile::Executor => write!(f, "executor"),
+            OutputProfile::Archive => write!(f, "archive"),
+            OutputProfile::Outline => write!(f, "outline"),
+        }
+    }
+}
+
+impl FromStr for OutputProfile {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "orchestrator" => Ok(Self::Orchestrator),
+            "reviewer" => Ok(Self::Reviewer),
+            "executor" => Ok(Self::Executor),
+            "archive" => Ok(Self::Archive),
+            "outline" => Ok(Self::Outline),
+            other => Err(DomainError::InvalidData(format!(
+                "'profile' must be one of: orchestrator, reviewer, executor, archive, outline (got '{}')",
+                other
+            ))),
+        }
+    }
+}
+
 // ── private helpers ───────────────────────────────────────────────────────────
 
 fn validate_token(name: &str, value: &str) -> Result<()> {
@@ -309,6 +617,40 @@ fn validate_token(name: &str, value: &str) -> Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_language_tag_normalizes_case_and_separator() {
+        let tag = LanguageTag::new(" pt_BR ").unwrap();
+        assert_eq!(tag.as_str(), "pt-br");
+        assert_eq!(tag.primary(), "pt");
+        assert_eq!(LanguageTag::new("ru").unwrap().primary(), "ru");
+        assert!(LanguageTag::new("").is_err());
+        assert!(LanguageTag::new("russian").is_err());
+        assert!(LanguageTag::new("r").is_err());
+    }
+
+    #[test]
+    fn test_git_sha_accepts_abbreviated_and_full_hex() {
+        assert_eq!(GitSha::new(" ABC1234 ").unwrap().as_str(), "abc1234");
+        assert!(GitSha::new(&"f".repeat(40)).is_ok());
+        assert!(GitSha::new("abc123").is_err());
+        assert!(GitSha::new("HEAD~1").is_err());
+        assert!(GitSha::new(&"a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_symbol_name_accepts_qualified_identifiers() {
+        let symbol = SymbolName::new(" Parser::parse ").unwrap();
+        assert_eq!(symbol.as_str(), "Parser::parse");
+        assert_eq!(symbol.segments(), ["Parser", "parse"]);
+        assert_eq!(
+            SymbolName::new("Store.load").unwrap().segments(),
+            ["Store", "load"]
+        );
+        assert!(SymbolName::new("fn parse").is_err());
+        assert!(SymbolName::new("Parser::").is_err());
+        assert!(SymbolName::new("a::b::c::d::e").is_err());
+    }
+
     #[test]
     fn test_pack_name_validation() {
         assert!(PackName::new("").is_err());
@@ -357,6 +699,41 @@ mod tests {
         assert!(RelativePath::new("nested/deep/file.ts").is_ok());
     }
 
+    #[test]
+    fn test_pack_section_ref_round_trips() {
+        let id = PackId::new();
+        let raw = format!(" pack:{id}#findings ");
+        assert!(PackSectionRef::is_pack_ref(&raw));
+        let parsed = PackSectionRef::parse(&raw).unwrap();
+        assert_eq!(parsed.pack, id);
+        assert_eq!(parsed.section.as_str(), "findings");
+        assert_eq!(parsed.to_string(), format!("pack:{id}#findings"));
+        assert!(PackSectionRef::parse(&format!("pack:{id}")).is_err());
+        assert!(PackSectionRef::parse("pack:not-an-id#findings").is_err());
+        assert!(!PackSectionRef::is_pack_ref("src/pack.rs"));
+    }
+
+    #[test]
+    fn test_external_url_validation_and_locator() {
+        assert!(ExternalUrl::new("ftp://example.com/x").is_err());
+        assert!(ExternalUrl::new("https://").is_err());
+        assert!(ExternalUrl::new("https://example.com/a b").is_err());
+        let url =
+            ExternalUrl::new(" https://www.rfc-editor.org/rfc/rfc7519?x=1#section-4 ").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://www.rfc-editor.org/rfc/rfc7519?x=1#section-4"
+        );
+        assert_eq!(url.locator().as_str(), "www.rfc-editor.org/rfc/rfc7519");
+        assert_eq!(
+            ExternalUrl::new("http://host/../a//b/")
+                .unwrap()
+                .locator()
+                .as_str(),
+            "host/a/b"
+        );
+    }
+
     #[test]
     fn test_line_range_validation() {
         assert!(LineRange::new(0, 10).is_err(), "0-indexed is invalid");
@@ -1,87 +1,126 @@
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
 
-use mcp_context_pack::app::ports::PackRepositoryPort;
-
-fn source_root_from_env_or_cwd() -> PathBuf {
-    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-
-    let raw = match std::env::var("CONTEXT_PACK_SOURCE_ROOT") {
-        Ok(v) => v,
-        Err(_) => return cwd,
-    };
-
-    let value = raw.trim();
-    if value.is_empty()
-        || value.eq_ignore_ascii_case("cwd")
-        || value.eq_ignore_ascii_case("session_cwd")
-        || value.eq_ignore_ascii_case("__SESSION_CWD__")
-        || value == "."
-    {
-        cwd
-    } else {
-        PathBuf::from(value)
-    }
-}
+use mcp_context_pack::adapters::backup_tar::restore_backup;
+#[cfg(feature = "http")]
+use mcp_context_pack::adapters::mcp_http::{http_addr_from_env, http_token_from_env};
+use mcp_context_pack::adapters::mcp_stdio::{trace_frames_enabled, FRAME_TRACE_TARGET};
+use mcp_context_pack::adapters::storage_migration::migrate_storage_root;
+use mcp_context_pack::service::{ContextPackConfig, ContextPackService, StorageBackend};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let env_filter = if std::env::var("CONTEXT_PACK_LOG").is_ok() {
+    let mut env_filter = if std::env::var("CONTEXT_PACK_LOG").is_ok() {
         tracing_subscriber::EnvFilter::from_env("CONTEXT_PACK_LOG")
     } else {
         tracing_subscriber::EnvFilter::new("mcp_context_pack=info")
     };
+    if trace_frames_enabled() {
+        env_filter = env_filter.add_directive(format!("{FRAME_TRACE_TARGET}=debug").parse()?);
+    }
 
     tracing_subscriber::fmt()
         .with_writer(std::io::stderr) // log to stderr so stdout stays clean for MCP
         .with_env_filter(env_filter)
         .init();
 
-    let source_root = source_root_from_env_or_cwd();
-
-    let storage_root = std::env::var("CONTEXT_PACK_ROOT")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from(".agents").join("mcp").join("context_pack"));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("restore") => return run_restore(&args[1..]),
+        Some("sync") => return run_sync(&args[1..]).await,
+        Some("migrate") => return run_migrate(&args[1..]),
+        _ => {}
+    }
 
-    let storage_dir = storage_root.join("packs");
+    let config = ContextPackConfig::from_env();
+    tracing::info!(
+        "storage dir: {} ({} backend)",
+        config.storage_dir().display(),
+        config.storage.name()
+    );
+    tracing::info!("source root: {}", config.source_root.display());
+    for (name, path) in &config.source_roots {
+        tracing::info!("source root '{}': {}", name, path.display());
+    }
 
-    tracing::info!("storage dir: {}", storage_dir.display());
-    tracing::info!("source root: {}", source_root.display());
+    let report = config.self_check();
+    report.log();
+    report.into_result().map_err(anyhow::Error::new)?;
+
+    let service = ContextPackService::new(config).map_err(anyhow::Error::new)?;
+    service.spawn_ttl_purge();
+    service.spawn_sync();
+    service.spawn_metrics_log();
+    service.spawn_source_watch();
+    #[cfg(feature = "http")]
+    if let Some(addr) = http_addr_from_env().map_err(anyhow::Error::msg)? {
+        return service.serve_http(addr, http_token_from_env()).await;
+    }
+    service.serve_stdio().await?;
 
-    let storage =
-        Arc::new(mcp_context_pack::adapters::storage_json::JsonStorageAdapter::new(storage_dir));
-    let repo: Arc<dyn PackRepositoryPort> = storage;
+    Ok(())
+}
 
-    // Background TTL cleanup: purge expired packs every 30 minutes.
-    // The interval fires immediately on first tick, so cleanup also runs at startup.
-    {
-        let repo_for_bg = repo.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_secs(30 * 60));
-            loop {
-                interval.tick().await;
-                if let Err(e) = repo_for_bg.purge_expired().await {
-                    tracing::warn!("background TTL purge failed: {e}");
-                }
-            }
-        });
+/// `mcp-context-pack restore <archive>`: restores a `server action=backup`
+/// archive into `CONTEXT_PACK_ROOT`.
+fn run_restore(args: &[String]) -> anyhow::Result<()> {
+    let [archive] = args else {
+        anyhow::bail!("usage: mcp-context-pack restore <archive.tar>");
+    };
+    let config = ContextPackConfig::from_env();
+    if config.storage != StorageBackend::Json {
+        anyhow::bail!(
+            "restore writes the json storage backend; CONTEXT_PACK_STORAGE is '{}'",
+            config.storage.name()
+        );
     }
-
-    let excerpts = Arc::new(
-        mcp_context_pack::adapters::code_excerpt_fs::CodeExcerptFsAdapter::new(source_root)
-            .map_err(anyhow::Error::new)?,
+    let summary =
+        restore_backup(&config.storage_root, Path::new(archive)).map_err(anyhow::Error::new)?;
+    println!(
+        "restored {} pack(s) and {} other file(s) into {}",
+        summary.pack_count,
+        summary.file_count,
+        config.storage_dir().display()
     );
+    if let Some(dir) = summary.displaced_dir {
+        println!("previous files moved to {}", dir.display());
+    }
+    Ok(())
+}
 
-    let input_uc = Arc::new(mcp_context_pack::app::input_usecases::InputUseCases::new(
-        repo.clone(),
-        excerpts.clone(),
-    ));
-    let output_uc = Arc::new(mcp_context_pack::app::output_usecases::OutputUseCases::new(
-        repo.clone(),
-        excerpts.clone(),
-    ));
-
-    mcp_context_pack::adapters::mcp_stdio::start_mcp_server(input_uc, output_uc).await?;
+/// `mcp-context-pack sync [<remote_root>]`: one sync pass against
+/// `remote_root` (default `CONTEXT_PACK_SYNC_ROOT`); prints the JSON report.
+async fn run_sync(args: &[String]) -> anyhow::Result<()> {
+    let config = ContextPackConfig::from_env();
+    let remote_root = match args {
+        [remote] => PathBuf::from(remote),
+        [] => config.sync_root.clone().ok_or_else(|| {
+            anyhow::anyhow!(
+                "usage: mcp-context-pack sync <remote_root> (or set CONTEXT_PACK_SYNC_ROOT)"
+            )
+        })?,
+        _ => anyhow::bail!("usage: mcp-context-pack sync [<remote_root>]"),
+    };
+    let service = ContextPackService::new(config).map_err(anyhow::Error::new)?;
+    let report = service
+        .sync_with(&remote_root)
+        .await
+        .map_err(anyhow::Error::new)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
 
+/// `mcp-context-pack migrate <from_root> <to_root>`: verified copy of a
+/// storage root (packs plus journals, backups and quarantines).
+fn run_migrate(args: &[String]) -> anyhow::Result<()> {
+    let [from, to] = args else {
+        anyhow::bail!("usage: mcp-context-pack migrate <from_root> <to_root>");
+    };
+    let report =
+        migrate_storage_root(Path::new(from), Path::new(to)).map_err(anyhow::Error::new)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    println!(
+        "verified; point CONTEXT_PACK_ROOT at {} and remove {} when satisfied",
+        report.to, report.from
+    );
     Ok(())
 }
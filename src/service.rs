@@ -0,0 +1,1418 @@
+//! In-process embedding API.
+//!
+//! [`ContextPackService`] owns the repository, excerpt reader and use cases
+//! that the MCP binary wires up, so other Rust programs can manage packs
+//! without going through the stdio transport. The service is cheap to clone
+//! and safe to share across tasks: all state lives behind `Arc`s and storage
+//! writes are serialized by the repository lock.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::json;
+
+pub use crate::adapters::storage_factory::StorageBackend;
+
+use crate::{
+    adapters::{
+        audit_log_fs::AuditLogFsAdapter,
+        backup_tar::{TarBackupAdapter, UnsupportedBackupAdapter},
+        code_excerpt_fs::CodeExcerptFsAdapter,
+        health_fs::StoreHealthFsAdapter,
+        markdown_export_fs::{MarkdownExportFsAdapter, EXPORT_ROOT_ENV},
+        pack_cipher::{PackCipher, ENCRYPTION_KEY_ENV},
+        pack_codec::{parse_compression_from_env, Compression, COMPRESSION_ENV},
+        pack_layout::{parse_layout_from_env, PackLayout, LAYOUT_ENV},
+        read_only_storage::is_read_only_error,
+        replay_journal_fs::ReplayJournalFsAdapter,
+        saved_filters_fs::SavedFiltersFsAdapter,
+        secret_redaction::SecretRedactor,
+        storage_factory::{open_pack_repository, STORAGE_ENV},
+        storage_json::{
+            ExpiredRetention, JsonStorageAdapter, DEFAULT_ARCHIVE_RETENTION_DAYS,
+            EXPIRED_RETENTION_ENV,
+        },
+        sync_state_fs::SyncStateFsAdapter,
+    },
+    app::{
+        audit::{AuditCaller, AuditHook},
+        finalize_rules::FinalizeRules,
+        input_usecases::InputUseCases,
+        lifecycle::{HookedRepository, LifecycleHooks},
+        metrics::ServerMetrics,
+        output_usecases::{OutputUseCases, RenderProfiles},
+        ports::{
+            AuditLogPort, BackupPort, BackupSummary, CodeExcerptPort, HealthProbePort,
+            PackRepositoryPort, ReplayJournalPort, SavedFilterPort,
+        },
+        source_watch::SourceWatch,
+        supervisor::TaskSupervisor,
+        sync_usecases::{SyncReport, SyncUseCases},
+    },
+    domain::{
+        errors::{DomainError, Result},
+        models::{
+            Attachment, DiagramLimits, ExcerptLimits, FinalizeChecklist, OwnershipMode, Pack,
+            SectionTemplates, SignOffPolicy, TtlPolicy,
+        },
+        types::SourceRootName,
+    },
+};
+
+const DEFAULT_PURGE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_METRICS_LOG_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const METRICS_LOG_ENV: &str = "CONTEXT_PACK_METRICS_LOG_SECONDS";
+const TTL_DEFAULTS_ENV: &str = "CONTEXT_PACK_TTL_DEFAULTS";
+const SIGNOFF_POLICY_ENV: &str = "CONTEXT_PACK_SIGNOFF_POLICY";
+const SECTION_TEMPLATES_ENV: &str = "CONTEXT_PACK_SECTION_TEMPLATES";
+const FINALIZE_CHECKLIST_ENV: &str = "CONTEXT_PACK_FINALIZE_CHECKLIST";
+const FINALIZE_RULES_ENV: &str = "CONTEXT_PACK_FINALIZE_RULES";
+const OWNERSHIP_ENV: &str = "CONTEXT_PACK_OWNERSHIP";
+const RENDER_PROFILES_ENV: &str = "CONTEXT_PACK_RENDER_PROFILES";
+const SOURCE_ROOTS_ENV: &str = "CONTEXT_PACK_SOURCE_ROOTS";
+const REDACT_PATTERNS_ENV: &str = "CONTEXT_PACK_REDACT_PATTERNS";
+const WATCH_SOURCES_ENV: &str = "CONTEXT_PACK_WATCH_SOURCES";
+#[derive(Debug, Clone)]
+pub struct ContextPackConfig {
+    /// Storage root; packs live in `{storage_root}/packs`.
+    pub storage_root: PathBuf,
+    /// Pack store backend; journals, the audit log and exports stay under
+    /// `storage_root` either way.
+    pub storage: StorageBackend,
+    /// Root that ref paths are resolved against.
+    pub source_root: PathBuf,
+    /// Extra roots a ref selects with `root`; see [`parse_source_roots`].
+    pub source_roots: Vec<(SourceRootName, PathBuf)>,
+    /// Secret patterns masked in excerpts on top of the built-in ones.
+    pub redaction: SecretRedactor,
+    pub diagram_limits: DiagramLimits,
+    /// Rendered excerpt length and the cap on per-pack/per-ref overrides.
+    pub excerpt_limits: ExcerptLimits,
+    pub purge_interval: Duration,
+    /// How far `output read` pushes out the expiry of `sliding_ttl` packs.
+    pub sliding_ttl_minutes: u64,
+    /// Size cap on one section attachment's content.
+    pub attachment_max_bytes: usize,
+    /// Remote storage root for periodic sync; `None` disables it.
+    pub sync_root: Option<PathBuf>,
+    pub sync_interval: Duration,
+    /// How often tool call metrics are logged to stderr; zero disables it.
+    pub metrics_log_interval: Duration,
+    /// TTL applied to creates that omit `ttl_minutes`.
+    pub ttl_policy: TtlPolicy,
+    /// Approvals required before a pack may be finalized; empty means none.
+    pub sign_off_policy: SignOffPolicy,
+    /// Per-section fields required before finalize; empty means only the
+    /// built-in checks.
+    pub section_templates: SectionTemplates,
+    /// Sections (and their fields) a pack needs to finalize; defaults to
+    /// `scope`, `findings` and `qa` with a verdict.
+    pub finalize_checklist: FinalizeChecklist,
+    /// Extra finalize rules ([`crate::app::ports::ValidatorPort`]); register
+    /// custom ones here, built-ins come from `CONTEXT_PACK_FINALIZE_RULES`.
+    pub finalize_rules: FinalizeRules,
+    /// Whether pack owners guard their packs against other actors' writes.
+    pub ownership: OwnershipMode,
+    /// Directory `output read export_path` writes under.
+    pub export_root: PathBuf,
+    /// `output read` profile presets; empty means the built-ins.
+    pub render_profiles: RenderProfiles,
+    /// Extensions notified after packs are created, written, finalized or
+    /// deleted; see [`crate::app::ports::PackLifecycleHook`].
+    pub lifecycle_hooks: LifecycleHooks,
+    /// Watch the source roots and mark refs to changed files stale; only
+    /// builds with the `watch` feature act on it.
+    pub watch_sources: bool,
+    /// At-rest key for pack files and the journals, audit log and sync state
+    /// beside them; `None` writes plaintext. Only the json store encrypts.
+    pub encryption_key: Option<PackCipher>,
+    /// Format of pack files the json store writes from now on.
+    pub compression: Compression,
+    /// Directory layout of the json store's pack files.
+    pub layout: PackLayout,
+}
+
+impl ContextPackConfig {
+    pub fn new(storage_root: impl Into<PathBuf>, source_root: impl Into<PathBuf>) -> Self {
+        let storage_root = storage_root.into();
+        Self {
+            export_root: storage_root.join("exports"),
+            storage_root,
+            storage: StorageBackend::default(),
+            source_root: source_root.into(),
+            source_roots: Vec::new(),
+            redaction: SecretRedactor::default(),
+            diagram_limits: DiagramLimits::default(),
+            excerpt_limits: ExcerptLimits::default(),
+            purge_interval: DEFAULT_PURGE_INTERVAL,
+            sliding_ttl_minutes: Pack::DEFAULT_SLIDING_TTL_MINUTES,
+            attachment_max_bytes: Attachment::DEFAULT_MAX_BYTES,
+            sync_root: None,
+            sync_interval: DEFAULT_SYNC_INTERVAL,
+            metrics_log_interval: DEFAULT_METRICS_LOG_INTERVAL,
+            ttl_policy: TtlPolicy::default(),
+            sign_off_policy: SignOffPolicy::default(),
+            section_templates: SectionTemplates::default(),
+            finalize_checklist: FinalizeChecklist::default(),
+            finalize_rules: FinalizeRules::default(),
+            ownership: OwnershipMode::default(),
+            render_profiles: RenderProfiles::default(),
+            lifecycle_hooks: LifecycleHooks::default(),
+            watch_sources: false,
+            encryption_key: None,
+            compression: Compression::default(),
+            layout: PackLayout::default(),
+        }
+    }
+
+    /// Configuration from the `CONTEXT_PACK_*` environment, as used by the binary.
+    pub fn from_env() -> Self {
+        let storage_root = std::env::var("CONTEXT_PACK_ROOT")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(".agents").join("mcp").join("context_pack"));
+        let mut config = Self::new(storage_root, source_root_from_env_or_cwd());
+        config.diagram_limits = diagram_limits_from_env();
+        config.excerpt_limits = excerpt_limits_from_env();
+        config.sliding_ttl_minutes = positive_usize_from_env(
+            "CONTEXT_PACK_SLIDING_TTL_MINUTES",
+            Pack::DEFAULT_SLIDING_TTL_MINUTES as usize,
+        ) as u64;
+        config.attachment_max_bytes = positive_usize_from_env(
+            "CONTEXT_PACK_MAX_ATTACHMENT_BYTES",
+            Attachment::DEFAULT_MAX_BYTES,
+        );
+        // Invalid values keep the built-in default here; self_check rejects them.
+        if let Some(roots) = std::env::var(SOURCE_ROOTS_ENV)
+            .ok()
+            .and_then(|raw| parse_source_roots(&raw).ok())
+        {
+            config.source_roots = roots;
+        }
+        if let Some(redaction) = std::env::var(REDACT_PATTERNS_ENV)
+            .ok()
+            .and_then(|raw| SecretRedactor::parse(&raw).ok())
+        {
+            config.redaction = redaction;
+        }
+        config.sync_root = std::env::var("CONTEXT_PACK_SYNC_ROOT")
+            .ok()
+            .map(|raw| raw.trim().to_string())
+            .filter(|raw| !raw.is_empty())
+            .map(PathBuf::from);
+        if let Some(export_root) = std::env::var(EXPORT_ROOT_ENV)
+            .ok()
+            .map(|raw| raw.trim().to_string())
+            .filter(|raw| !raw.is_empty())
+        {
+            config.export_root = PathBuf::from(export_root);
+        }
+        config.sync_interval = Duration::from_secs(positive_usize_from_env(
+            "CONTEXT_PACK_SYNC_INTERVAL_SECONDS",
+            DEFAULT_SYNC_INTERVAL.as_secs() as usize,
+        ) as u64);
+        if let Some(secs) = std::env::var(METRICS_LOG_ENV)
+            .ok()
+            .and_then(|raw| raw.trim().parse::<u64>().ok())
+        {
+            config.metrics_log_interval = Duration::from_secs(secs);
+        }
+        // Invalid values keep the built-in default here; self_check rejects them.
+        if let Some(policy) = std::env::var(TTL_DEFAULTS_ENV)
+            .ok()
+            .and_then(|raw| TtlPolicy::parse(&raw).ok())
+        {
+            config.ttl_policy = policy;
+        }
+        if let Some(policy) = std::env::var(SIGNOFF_POLICY_ENV)
+            .ok()
+            .and_then(|raw| SignOffPolicy::parse(&raw).ok())
+        {
+            config.sign_off_policy = policy;
+        }
+        if let Some(templates) = std::env::var(SECTION_TEMPLATES_ENV)
+            .ok()
+            .and_then(|raw| SectionTemplates::parse(&raw).ok())
+        {
+            config.section_templates = templates;
+        }
+        if let Some(checklist) = std::env::var(FINALIZE_CHECKLIST_ENV)
+            .ok()
+            .and_then(|raw| FinalizeChecklist::parse(&raw).ok())
+        {
+            config.finalize_checklist = checklist;
+        }
+        if let Some(rules) = std::env::var(FINALIZE_RULES_ENV)
+            .ok()
+            .and_then(|raw| FinalizeRules::parse(&raw).ok())
+        {
+            config.finalize_rules = rules;
+        }
+        if let Some(storage) = std::env::var(STORAGE_ENV)
+            .ok()
+            .and_then(|raw| raw.parse::<StorageBackend>().ok())
+        {
+            config.storage = storage;
+        }
+        if let Some(ownership) = std::env::var(OWNERSHIP_ENV)
+            .ok()
+            .and_then(|raw| raw.parse::<OwnershipMode>().ok())
+        {
+            config.ownership = ownership;
+        }
+        if let Some(profiles) = std::env::var(RENDER_PROFILES_ENV)
+            .ok()
+            .and_then(|raw| RenderProfiles::parse(&raw).ok())
+        {
+            config.render_profiles = profiles;
+        }
+        config.watch_sources = std::env::var(WATCH_SOURCES_ENV)
+            .map(|raw| matches!(raw.trim(), "1" | "true" | "yes" | "on"))
+            .unwrap_or(false);
+        config.encryption_key = PackCipher::from_env().ok().flatten();
+        config.compression = parse_compression_from_env();
+        config.layout = parse_layout_from_env();
+        config
+    }
+
+    pub fn storage_dir(&self) -> PathBuf {
+        self.storage_root.join("packs")
+    }
+
+    pub fn replay_journal_path(&self) -> PathBuf {
+        self.storage_root.join("replay_journal.json")
+    }
+
+    pub fn audit_log_path(&self) -> PathBuf {
+        self.storage_root.join("audit.jsonl")
+    }
+
+    /// Validates paths, limits and `CONTEXT_PACK_*` values before serving, so
+    /// misconfiguration surfaces at startup rather than on the first tool call.
+    /// Creates the storage directory if it is missing.
+    pub fn self_check(&self) -> SelfCheckReport {
+        self.self_check_with_env(|name| std::env::var(name).ok())
+    }
+
+    fn self_check_with_env(&self, env: impl Fn(&str) -> Option<String>) -> SelfCheckReport {
+        let mut report = SelfCheckReport::default();
+        report.push(self.check_storage_writable());
+        report.push(self.check_source_root());
+        match env(SOURCE_ROOTS_ENV).map(|raw| parse_source_roots(&raw)) {
+            Some(Err(err)) => report.push(SelfCheck::critical(SOURCE_ROOTS_ENV, err.to_string())),
+            _ if self.source_roots.is_empty() => {}
+            _ => report.push(self.check_source_roots()),
+        }
+        report.push(self.check_limits());
+        if let Some(sync_root) = &self.sync_root {
+            report.push(check_sync_root(sync_root));
+        }
+        for (name, rule) in NUMERIC_ENV_KEYS {
+            if let Some(raw) = env(name) {
+                report.push(check_numeric_env(name, *rule, &raw));
+            }
+        }
+        if let Some(check) = check_pack_fits_diagram(&self.diagram_limits, &env) {
+            report.push(check);
+        }
+        if let Some(raw) = env(TTL_DEFAULTS_ENV) {
+            report.push(match TtlPolicy::parse(&raw) {
+                Ok(policy) => SelfCheck::ok(
+                    TTL_DEFAULTS_ENV,
+                    format!(
+                        "default {}m, {} tag and {} namespace override(s)",
+                        policy.default_minutes,
+                        policy.by_tag.len(),
+                        policy.by_namespace.len()
+                    ),
+                ),
+                Err(err) => SelfCheck::critical(TTL_DEFAULTS_ENV, err.to_string()),
+            });
+        }
+        if let Some(raw) = env(EXPIRED_RETENTION_ENV) {
+            report.push(
+                match ExpiredRetention::parse(&raw, DEFAULT_ARCHIVE_RETENTION_DAYS) {
+                    Ok(ExpiredRetention::Delete) => {
+                        SelfCheck::ok(EXPIRED_RETENTION_ENV, "expired packs are deleted")
+                    }
+                    Ok(ExpiredRetention::Archive { .. }) => SelfCheck::ok(
+                        EXPIRED_RETENTION_ENV,
+                        "expired packs are archived under <storage>/archive",
+                    ),
+                    Err(err) => SelfCheck::critical(EXPIRED_RETENTION_ENV, err.to_string()),
+                },
+            );
+        }
+        if let Some(raw) = env(SIGNOFF_POLICY_ENV) {
+            report.push(match SignOffPolicy::parse(&raw) {
+                Ok(policy) => SelfCheck::ok(
+                    SIGNOFF_POLICY_ENV,
+                    format!(
+                        "finalize needs {} approval(s), role(s): {}",
+                        policy.min_approvals,
+                        if policy.required_roles.is_empty() {
+                            "none".to_string()
+                        } else {
+                            policy
+                                .required_roles
+                                .iter()
+                                .cloned()
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        }
+                    ),
+                ),
+                Err(err) => SelfCheck::critical(SIGNOFF_POLICY_ENV, err.to_string()),
+            });
+        }
+        if let Some(raw) = env(REDACT_PATTERNS_ENV) {
+            report.push(match SecretRedactor::parse(&raw) {
+                Ok(redaction) => SelfCheck::ok(
+                    REDACT_PATTERNS_ENV,
+                    format!(
+                        "excerpts also mask: {}",
+                        redaction.extra_rule_names().join(", ")
+                    ),
+                ),
+                Err(err) => SelfCheck::critical(REDACT_PATTERNS_ENV, err.to_string()),
+            });
+        }
+        if let Some(raw) = env(SECTION_TEMPLATES_ENV) {
+            report.push(match SectionTemplates::parse(&raw) {
+                Ok(templates) => SelfCheck::ok(
+                    SECTION_TEMPLATES_ENV,
+                    format!(
+                        "finalize requires fields in {} section(s)",
+                        templates.required_fields.len()
+                    ),
+                ),
+                Err(err) => SelfCheck::critical(SECTION_TEMPLATES_ENV, err.to_string()),
+            });
+        }
+        if let Some(raw) = env(FINALIZE_CHECKLIST_ENV) {
+            report.push(match FinalizeChecklist::parse(&raw) {
+                Ok(checklist) => SelfCheck::ok(
+                    FINALIZE_CHECKLIST_ENV,
+                    format!(
+                        "finalize requires section(s): {}",
+                        checklist
+                            .sections
+                            .iter()
+                            .map(|(key, _)| key.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ),
+                Err(err) => SelfCheck::critical(FINALIZE_CHECKLIST_ENV, err.to_string()),
+            });
+        }
+        if let Some(raw) = env(FINALIZE_RULES_ENV) {
+            report.push(match FinalizeRules::parse(&raw) {
+                Ok(rules) => SelfCheck::ok(
+                    FINALIZE_RULES_ENV,
+                    format!("finalize rules: {}", rules.names().join(", ")),
+                ),
+                Err(err) => SelfCheck::critical(FINALIZE_RULES_ENV, err.to_string()),
+            });
+        }
+        if let Some(raw) = env(STORAGE_ENV) {
+            report.push(match raw.parse::<StorageBackend>() {
+                Ok(backend) if backend.is_persistent() => SelfCheck::ok(
+                    STORAGE_ENV,
+                    format!("packs are stored by the {} backend", backend.name()),
+                ),
+                Ok(backend) => SelfCheck::warning(
+                    STORAGE_ENV,
+                    format!(
+                        "packs are kept by the {} backend and lost when the server exits",
+                        backend.name()
+                    ),
+                ),
+                Err(err) => SelfCheck::critical(STORAGE_ENV, err.to_string()),
+            });
+        }
+        let env_key = env(ENCRYPTION_KEY_ENV).filter(|raw| !raw.trim().is_empty());
+        if let Some(Err(err)) = env_key.as_deref().map(PackCipher::parse) {
+            report.push(SelfCheck::critical(ENCRYPTION_KEY_ENV, err.to_string()));
+        } else if env_key.is_some() || self.encryption_key.is_some() {
+            report.push(if self.storage == StorageBackend::Json {
+                SelfCheck::ok(
+                    ENCRYPTION_KEY_ENV,
+                    "pack files are encrypted at rest (AES-256-GCM)",
+                )
+            } else {
+                SelfCheck::critical(
+                    ENCRYPTION_KEY_ENV,
+                    format!(
+                        "the {} backend would store packs unencrypted; only the json store encrypts",
+                        self.storage.name()
+                    ),
+                )
+            });
+        }
+        if let Some(raw) = env(COMPRESSION_ENV) {
+            report.push(match Compression::parse(&raw) {
+                Ok(compression) if self.storage == StorageBackend::Json => SelfCheck::ok(
+                    COMPRESSION_ENV,
+                    format!("new pack files are written as {}", compression.name()),
+                ),
+                Ok(_) => SelfCheck::warning(
+                    COMPRESSION_ENV,
+                    format!(
+                        "ignored by the {} backend; only the json store compresses",
+                        self.storage.name()
+                    ),
+                ),
+                Err(err) => SelfCheck::critical(COMPRESSION_ENV, err.to_string()),
+            });
+        }
+        if let Some(raw) = env(LAYOUT_ENV) {
+            report.push(match PackLayout::parse(&raw) {
+                Ok(layout) if self.storage == StorageBackend::Json => SelfCheck::ok(
+                    LAYOUT_ENV,
+                    format!("pack files use the {} layout", layout.name()),
+                ),
+                Ok(_) => SelfCheck::warning(
+                    LAYOUT_ENV,
+                    format!(
+                        "ignored by the {} backend; only the json store lays out files",
+                        self.storage.name()
+                    ),
+                ),
+                Err(err) => SelfCheck::critical(LAYOUT_ENV, err.to_string()),
+            });
+        }
+        if self.watch_sources {
+            let roots = 1 + self.source_roots.len();
+            report.push(if cfg!(feature = "watch") {
+                SelfCheck::ok(
+                    WATCH_SOURCES_ENV,
+                    format!("watching {roots} source root(s)"),
+                )
+            } else {
+                SelfCheck::warning(
+                    WATCH_SOURCES_ENV,
+                    "ignored; this build lacks the watch feature",
+                )
+            });
+        }
+        if let Some(raw) = env(OWNERSHIP_ENV) {
+            report.push(match raw.parse::<OwnershipMode>() {
+                Ok(OwnershipMode::Record) => {
+                    SelfCheck::ok(OWNERSHIP_ENV, "owners are recorded, not enforced")
+                }
+                Ok(OwnershipMode::Enforce) => {
+                    SelfCheck::ok(OWNERSHIP_ENV, "mutations by non-owners need force=true")
+                }
+                Err(err) => SelfCheck::critical(OWNERSHIP_ENV, err.to_string()),
+            });
+        }
+        if let Some(raw) = env(RENDER_PROFILES_ENV) {
+            report.push(match RenderProfiles::parse(&raw) {
+                Ok(profiles) => SelfCheck::ok(
+                    RENDER_PROFILES_ENV,
+                    format!("{} read profile(s) customized", profiles.configured()),
+                ),
+                Err(err) => SelfCheck::critical(RENDER_PROFILES_ENV, err.to_string()),
+            });
+        }
+        #[cfg(feature = "chaos")]
+        if let Some(raw) = env(crate::adapters::chaos_storage::CHAOS_ENV) {
+            let name = crate::adapters::chaos_storage::CHAOS_ENV;
+            report.push(
+                match crate::adapters::chaos_storage::ChaosConfig::parse(&raw) {
+                    Ok(chaos) => SelfCheck::warning(
+                        name,
+                        format!("fault injection active (test builds only): {chaos:?}"),
+                    ),
+                    Err(err) => SelfCheck::critical(name, err.to_string()),
+                },
+            );
+        }
+        #[cfg(feature = "http")]
+        if let Some(raw) = env(crate::adapters::mcp_http::HTTP_ADDR_ENV) {
+            let name = crate::adapters::mcp_http::HTTP_ADDR_ENV;
+            let token = env(crate::adapters::mcp_http::HTTP_TOKEN_ENV)
+                .filter(|token| !token.trim().is_empty());
+            report.push(match raw.trim().parse::<std::net::SocketAddr>() {
+                Ok(addr) if addr.ip().is_loopback() || token.is_some() => {
+                    SelfCheck::ok(name, format!("serving MCP over HTTP/SSE on {addr}"))
+                }
+                Ok(addr) => SelfCheck::critical(
+                    name,
+                    format!(
+                        "{addr} is reachable beyond this host; set {} to require a bearer token",
+                        crate::adapters::mcp_http::HTTP_TOKEN_ENV
+                    ),
+                ),
+                Err(_) if raw.trim().is_empty() => SelfCheck::ok(name, "unset; serving stdio"),
+                Err(err) => {
+                    SelfCheck::critical(name, format!("expected host:port, got '{raw}': {err}"))
+                }
+            });
+        }
+        #[cfg(feature = "stdio")]
+        if let Some(raw) = env("CONTEXT_PACK_HOST_DEFAULTS") {
+            report.push(
+                match crate::adapters::mcp_stdio::check_host_defaults(&raw) {
+                    Ok(()) => SelfCheck::ok("CONTEXT_PACK_HOST_DEFAULTS", "parsed"),
+                    Err(err) => SelfCheck::critical(
+                        "CONTEXT_PACK_HOST_DEFAULTS",
+                        format!("invalid JSON: {err}"),
+                    ),
+                },
+            );
+        }
+        report
+    }
+
+    /// A store that refuses writes (immutable mount, no write permission) is
+    /// a warning: the server starts read-only. Other failures are critical.
+    fn check_storage_writable(&self) -> SelfCheck {
+        const NAME: &str = "storage_root";
+        let read_only = |e: &std::io::Error, what: String| {
+            if is_read_only_error(e) {
+                SelfCheck::warning(
+                    NAME,
+                    format!("{what}: {e}; serving read-only, input mutations fail with storage_read_only"),
+                )
+            } else {
+                SelfCheck::critical(NAME, format!("{what}: {e}"))
+            }
+        };
+        let dir = self.storage_dir();
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            return read_only(&e, format!("cannot create '{}'", dir.display()));
+        }
+        let probe = self.storage_root.join(".self_check_probe");
+        match std::fs::write(&probe, b"ok") {
+            Ok(()) => {
+                let _ = std::fs::remove_file(&probe);
+                SelfCheck::ok(NAME, format!("'{}' is writable", dir.display()))
+            }
+            Err(e) => read_only(
+                &e,
+                format!("'{}' is not writable", self.storage_root.display()),
+            ),
+        }
+    }
+
+    fn check_source_roots(&self) -> SelfCheck {
+        let missing: Vec<String> = self
+            .source_roots
+            .iter()
+            .filter(|(_, path)| !path.is_dir())
+            .map(|(name, path)| format!("{name}='{}'", path.display()))
+            .collect();
+        if !missing.is_empty() {
+            return SelfCheck::critical(
+                SOURCE_ROOTS_ENV,
+                format!("not a directory: {}", missing.join(", ")),
+            );
+        }
+        let names: Vec<&str> = self.source_roots.iter().map(|(n, _)| n.as_str()).collect();
+        SelfCheck::ok(
+            SOURCE_ROOTS_ENV,
+            format!("{} extra source root(s): {}", names.len(), names.join(", ")),
+        )
+    }
+
+    fn check_source_root(&self) -> SelfCheck {
+        const NAME: &str = "source_root";
+        match std::fs::metadata(&self.source_root) {
+            Ok(meta) if meta.is_dir() => SelfCheck::ok(
+                NAME,
+                format!("'{}' is a directory", self.source_root.display()),
+            ),
+            Ok(_) => SelfCheck::critical(
+                NAME,
+                format!("'{}' is not a directory", self.source_root.display()),
+            ),
+            Err(e) => SelfCheck::critical(
+                NAME,
+                format!("'{}' is not accessible: {e}", self.source_root.display()),
+            ),
+        }
+    }
+
+    fn check_limits(&self) -> SelfCheck {
+        const NAME: &str = "limits";
+        let limits = &self.diagram_limits;
+        if limits.max_bytes == 0 || limits.max_nodes == 0 || limits.max_edges == 0 {
+            return SelfCheck::critical(NAME, "diagram limits must be positive");
+        }
+        if self.purge_interval.is_zero() {
+            return SelfCheck::critical(NAME, "purge interval must be positive");
+        }
+        SelfCheck::ok(
+            NAME,
+            format!(
+                "diagram max_bytes={} max_nodes={} max_edges={}; purge every {}s",
+                limits.max_bytes,
+                limits.max_nodes,
+                limits.max_edges,
+                self.purge_interval.as_secs()
+            ),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    /// Usable, but likely not what the operator intended.
+    Warning,
+    /// The server must not start.
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl SelfCheck {
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Ok, detail)
+    }
+
+    fn warning(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Warning, detail)
+    }
+
+    fn critical(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Critical, detail)
+    }
+}
+
+/// Result of [`ContextPackConfig::self_check`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SelfCheckReport {
+    pub checks: Vec<SelfCheck>,
+}
+
+impl SelfCheckReport {
+    fn push(&mut self, check: SelfCheck) {
+        self.checks.push(check);
+    }
+
+    pub fn has_critical(&self) -> bool {
+        self.checks
+            .iter()
+            .any(|check| check.status == CheckStatus::Critical)
+    }
+
+    /// Emits one structured event per check plus a summary.
+    pub fn log(&self) {
+        for check in &self.checks {
+            match check.status {
+                CheckStatus::Ok => {
+                    tracing::info!(check = %check.name, status = "ok", "{}", check.detail)
+                }
+                CheckStatus::Warning => {
+                    tracing::warn!(check = %check.name, status = "warning", "{}", check.detail)
+                }
+                CheckStatus::Critical => {
+                    tracing::error!(check = %check.name, status = "critical", "{}", check.detail)
+                }
+            }
+        }
+        let count = |status| self.checks.iter().filter(|c| c.status == status).count();
+        tracing::info!(
+            ok = count(CheckStatus::Ok),
+            warnings = count(CheckStatus::Warning),
+            critical = count(CheckStatus::Critical),
+            "startup self-check finished"
+        );
+    }
+
+    /// Fails closed when any check is critical.
+    pub fn into_result(self) -> Result<Self> {
+        if !self.has_critical() {
+            return Ok(self);
+        }
+        let failed: Vec<&SelfCheck> = self
+            .checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Critical)
+            .collect();
+        Err(DomainError::DetailedInvalidData {
+            message: format!(
+                "startup self-check failed: {}",
+                failed
+                    .iter()
+                    .map(|check| format!("{}: {}", check.name, check.detail))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            details: json!({ "failed_checks": failed }),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum EnvRule {
+    Positive,
+    NonNegative,
+}
+
+const NUMERIC_ENV_KEYS: &[(&str, EnvRule)] = &[
+    ("CONTEXT_PACK_MAX_PACK_BYTES", EnvRule::Positive),
+    ("CONTEXT_PACK_MAX_SOURCE_BYTES", EnvRule::Positive),
+    ("CONTEXT_PACK_MAX_DIAGRAM_BYTES", EnvRule::Positive),
+    ("CONTEXT_PACK_MAX_DIAGRAM_NODES", EnvRule::Positive),
+    ("CONTEXT_PACK_MAX_DIAGRAM_EDGES", EnvRule::Positive),
+    ("CONTEXT_PACK_EXCERPT_LINES", EnvRule::Positive),
+    ("CONTEXT_PACK_MAX_EXCERPT_LINES", EnvRule::Positive),
+    ("CONTEXT_PACK_SLIDING_TTL_MINUTES", EnvRule::Positive),
+    ("CONTEXT_PACK_MAX_ATTACHMENT_BYTES", EnvRule::Positive),
+    ("CONTEXT_PACK_INITIALIZE_TIMEOUT_MS", EnvRule::Positive),
+    ("CONTEXT_PACK_MAX_CONCURRENT_CALLS", EnvRule::Positive),
+    ("CONTEXT_PACK_EXPIRED_GRACE_SECONDS", EnvRule::NonNegative),
+    ("CONTEXT_PACK_ARCHIVE_RETENTION_DAYS", EnvRule::Positive),
+    ("CONTEXT_PACK_REPLAY_WINDOW_SECONDS", EnvRule::NonNegative),
+    ("CONTEXT_PACK_SYNC_INTERVAL_SECONDS", EnvRule::Positive),
+    (METRICS_LOG_ENV, EnvRule::NonNegative),
+];
+
+/// Adapters silently fall back to defaults on unparseable values; at startup
+/// that is treated as misconfiguration instead.
+fn check_numeric_env(name: &str, rule: EnvRule, raw: &str) -> SelfCheck {
+    let parsed = raw.trim().parse::<i64>().ok();
+    let valid = match rule {
+        EnvRule::Positive => parsed.is_some_and(|value| value > 0),
+        EnvRule::NonNegative => parsed.is_some_and(|value| value >= 0),
+    };
+    if valid {
+        SelfCheck::ok(name, format!("set to {}", raw.trim()))
+    } else {
+        let expected = match rule {
+            EnvRule::Positive => "a positive integer",
+            EnvRule::NonNegative => "a non-negative integer",
+        };
+        SelfCheck::critical(name, format!("expected {expected}, got '{raw}'"))
+    }
+}
+
+fn check_sync_root(sync_root: &Path) -> SelfCheck {
+    const NAME: &str = "sync_root";
+    let packs = sync_root.join("packs");
+    match std::fs::create_dir_all(&packs) {
+        Ok(()) => SelfCheck::ok(NAME, format!("'{}' is reachable", sync_root.display())),
+        Err(e) => SelfCheck::critical(NAME, format!("cannot create '{}': {e}", packs.display())),
+    }
+}
+
+fn check_pack_fits_diagram(
+    limits: &DiagramLimits,
+    env: &impl Fn(&str) -> Option<String>,
+) -> Option<SelfCheck> {
+    let max_pack_bytes = env("CONTEXT_PACK_MAX_PACK_BYTES")?
+        .trim()
+        .parse::<usize>()
+        .ok()?;
+    (max_pack_bytes < limits.max_bytes).then(|| {
+        SelfCheck::warning(
+            "limits",
+            format!(
+                "CONTEXT_PACK_MAX_PACK_BYTES ({max_pack_bytes}) is below the diagram byte limit ({}); a single maximal diagram cannot be stored",
+                limits.max_bytes
+            ),
+        )
+    })
+}
+
+#[derive(Clone)]
+pub struct ContextPackService {
+    repo: Arc<dyn PackRepositoryPort>,
+    input: Arc<InputUseCases>,
+    output: Arc<OutputUseCases>,
+    replay_journal: Arc<dyn ReplayJournalPort>,
+    backup: Arc<dyn BackupPort>,
+    saved_filters: Arc<dyn SavedFilterPort>,
+    purge_interval: Duration,
+    /// Local root for sync bookkeeping; only set for config-built services.
+    storage_root: Option<PathBuf>,
+    /// Pack file settings the sync remote is written with; see
+    /// [`ContextPackConfig::encryption_key`].
+    cipher: Option<PackCipher>,
+    compression: Compression,
+    layout: PackLayout,
+    sync_root: Option<PathBuf>,
+    sync_interval: Duration,
+    /// Runs the background loops; reported by `server health`.
+    tasks: TaskSupervisor,
+    /// Tool call counters shared by every session; reported by `server stats`.
+    metrics: ServerMetrics,
+    metrics_log_interval: Duration,
+    /// Storage and source root checks for `server health`; only set for
+    /// config-built services.
+    health_probe: Option<Arc<dyn HealthProbePort>>,
+    /// Refs marked stale by source changes; only set when
+    /// `config.watch_sources` is on in a `watch` build.
+    source_watch: Option<Arc<SourceWatch>>,
+    #[cfg(feature = "watch")]
+    source_watcher: Option<crate::adapters::source_watch_fs::SourceWatchFsAdapter>,
+}
+
+impl ContextPackService {
+    /// Builds the default filesystem-backed service. Stamps a new storage root
+    /// with `store_meta.json` and fails with `MigrationRequired` when an
+    /// existing one holds another schema version; `config.storage` picks the
+    /// pack store (see [`open_pack_repository`]).
+    pub fn new(config: ContextPackConfig) -> Result<Self> {
+        let cipher = config.encryption_key.clone();
+        let repo = open_pack_repository(
+            config.storage,
+            &config.storage_root,
+            cipher.clone(),
+            config.compression,
+            config.layout,
+        )?;
+        // Journals and the audit log sit next to the packs and get the same
+        // at-rest encryption.
+        let audit_log: Arc<dyn AuditLogPort> =
+            Arc::new(AuditLogFsAdapter::new(config.audit_log_path()).with_cipher(cipher.clone()));
+        let mut hooks = config.lifecycle_hooks.clone();
+        hooks.register(Arc::new(AuditHook::new(audit_log.clone())));
+        let repo = HookedRepository::wrap(repo, hooks);
+        let health_probe: Arc<dyn HealthProbePort> = Arc::new(
+            StoreHealthFsAdapter::new(config.storage_dir(), config.source_root.clone())
+                .with_source_roots(config.source_roots.clone()),
+        );
+        let excerpt: Arc<dyn CodeExcerptPort> = Arc::new(
+            CodeExcerptFsAdapter::new(config.source_root.clone())?
+                .with_source_roots(config.source_roots.clone())?
+                .with_redactor(config.redaction.clone()),
+        );
+        let replay_journal: Arc<dyn ReplayJournalPort> = Arc::new(
+            ReplayJournalFsAdapter::new(config.replay_journal_path()).with_cipher(cipher.clone()),
+        );
+        let backup: Arc<dyn BackupPort> = if config.storage == StorageBackend::Json {
+            Arc::new(TarBackupAdapter::new(config.storage_root.clone()).with_cipher(cipher.clone()))
+        } else {
+            Arc::new(UnsupportedBackupAdapter::new(config.storage.name()))
+        };
+        let saved_filters: Arc<dyn SavedFilterPort> = Arc::new(SavedFiltersFsAdapter::new(
+            config.storage_root.join("saved_filters.json"),
+        ));
+        let input = InputUseCases::new(repo.clone(), excerpt.clone())
+            .with_diagram_limits(config.diagram_limits)
+            .with_excerpt_limits(config.excerpt_limits)
+            .with_ttl_policy(config.ttl_policy)
+            .with_sign_off_policy(config.sign_off_policy)
+            .with_section_templates(config.section_templates)
+            .with_finalize_checklist(config.finalize_checklist)
+            .with_finalize_rules(config.finalize_rules)
+            .with_ownership_mode(config.ownership)
+            .with_audit_log(audit_log)
+            .with_attachment_max_bytes(config.attachment_max_bytes);
+        let output = OutputUseCases::new(repo.clone(), excerpt.clone())
+            .with_exporter(Arc::new(MarkdownExportFsAdapter::new(
+                config.export_root.clone(),
+            )))
+            .with_render_profiles(config.render_profiles)
+            .with_excerpt_limits(config.excerpt_limits)
+            .with_sliding_ttl_minutes(config.sliding_ttl_minutes);
+        let mut service =
+            Self::from_parts(repo, replay_journal, backup, saved_filters, input, output);
+        service.purge_interval = config.purge_interval;
+        #[cfg(feature = "watch")]
+        if config.watch_sources {
+            service.source_watch = Some(Arc::new(SourceWatch::default()));
+            service.source_watcher = Some(
+                crate::adapters::source_watch_fs::SourceWatchFsAdapter::new(config.source_root)
+                    .with_source_roots(config.source_roots)
+                    .with_ignored_dir(config.storage_root.clone()),
+            );
+        }
+        service.storage_root = Some(config.storage_root);
+        service.cipher = cipher;
+        service.compression = config.compression;
+        service.layout = config.layout;
+        service.sync_root = config.sync_root;
+        service.sync_interval = config.sync_interval;
+        service.metrics_log_interval = config.metrics_log_interval;
+        service.health_probe = Some(health_probe);
+        Ok(service)
+    }
+
+    /// Builds a service over caller-provided adapters.
+    pub fn from_ports(
+        repo: Arc<dyn PackRepositoryPort>,
+        excerpt: Arc<dyn CodeExcerptPort>,
+        replay_journal: Arc<dyn ReplayJournalPort>,
+        backup: Arc<dyn BackupPort>,
+        saved_filters: Arc<dyn SavedFilterPort>,
+        diagram_limits: DiagramLimits,
+    ) -> Self {
+        let input =
+            InputUseCases::new(repo.clone(), excerpt.clone()).with_diagram_limits(diagram_limits);
+        let output = OutputUseCases::new(repo.clone(), excerpt);
+        Self::from_parts(repo, replay_journal, backup, saved_filters, input, output)
+    }
+
+    fn from_parts(
+        repo: Arc<dyn PackRepositoryPort>,
+        replay_journal: Arc<dyn ReplayJournalPort>,
+        backup: Arc<dyn BackupPort>,
+        saved_filters: Arc<dyn SavedFilterPort>,
+        input: InputUseCases,
+        output: OutputUseCases,
+    ) -> Self {
+        let input = Arc::new(input);
+        let output = Arc::new(output);
+        Self {
+            repo,
+            input,
+            output,
+            replay_journal,
+            backup,
+            saved_filters,
+            purge_interval: DEFAULT_PURGE_INTERVAL,
+            storage_root: None,
+            cipher: None,
+            compression: Compression::default(),
+            layout: PackLayout::default(),
+            sync_root: None,
+            sync_interval: DEFAULT_SYNC_INTERVAL,
+            tasks: TaskSupervisor::default(),
+            metrics: ServerMetrics::default(),
+            metrics_log_interval: DEFAULT_METRICS_LOG_INTERVAL,
+            health_probe: None,
+            source_watch: None,
+            #[cfg(feature = "watch")]
+            source_watcher: None,
+        }
+    }
+
+    pub fn input(&self) -> &Arc<InputUseCases> {
+        &self.input
+    }
+
+    pub fn output(&self) -> &Arc<OutputUseCases> {
+        &self.output
+    }
+
+    pub fn repository(&self) -> &Arc<dyn PackRepositoryPort> {
+        &self.repo
+    }
+
+    pub fn replay_journal(&self) -> &Arc<dyn ReplayJournalPort> {
+        &self.replay_journal
+    }
+
+    /// Archives the pack store; see [`BackupPort`].
+    pub async fn backup(&self) -> Result<BackupSummary> {
+        self.backup.backup().await
+    }
+
+    pub fn saved_filters(&self) -> &Arc<dyn SavedFilterPort> {
+        &self.saved_filters
+    }
+
+    /// State of the loops started by [`Self::spawn_ttl_purge`] and
+    /// [`Self::spawn_sync`].
+    pub fn tasks(&self) -> &TaskSupervisor {
+        &self.tasks
+    }
+
+    /// Tool call counters of every session served by this service.
+    pub fn metrics(&self) -> &ServerMetrics {
+        &self.metrics
+    }
+
+    /// Logs a metrics summary every `metrics_log_interval`; `None` when the
+    /// interval is zero.
+    pub fn spawn_metrics_log(&self) -> Option<tokio::task::JoinHandle<()>> {
+        (!self.metrics_log_interval.is_zero())
+            .then(|| self.metrics.spawn_log(self.metrics_log_interval))
+    }
+
+    /// Refs marked stale by source changes since their pack was last
+    /// written; `None` unless the service watches its source roots.
+    pub fn source_watch(&self) -> Option<&Arc<SourceWatch>> {
+        self.source_watch.as_ref()
+    }
+
+    /// Starts the source watcher when `config.watch_sources` is on in a
+    /// `watch` build; `None` otherwise, or when a root cannot be watched.
+    pub fn spawn_source_watch(&self) -> Option<tokio::task::JoinHandle<()>> {
+        #[cfg(feature = "watch")]
+        if let (Some(watcher), Some(watch)) = (&self.source_watcher, &self.source_watch) {
+            return match watcher.spawn(watch.clone(), self.repo.clone()) {
+                Ok(handle) => Some(handle),
+                Err(e) => {
+                    tracing::warn!("source watch not started: {}", e);
+                    None
+                }
+            };
+        }
+        None
+    }
+
+    pub async fn purge_expired(&self) -> Result<()> {
+        self.repo.purge_expired().await
+    }
+
+    /// Purges expired packs every `purge_interval` under the task supervisor.
+    /// The first pass runs immediately, so cleanup also runs at startup.
+    pub fn spawn_ttl_purge(&self) -> tokio::task::JoinHandle<()> {
+        let repo = self.repo.clone();
+        self.tasks
+            .supervise("ttl_purge", self.purge_interval, move || {
+                let repo = repo.clone();
+                AuditCaller::new("ttl_purge", None).scope(async move { repo.purge_expired().await })
+            })
+    }
+
+    /// One sync pass against the store rooted at `remote_root`; see
+    /// [`SyncUseCases`] for the conflict rules. State and quarantined
+    /// conflicts are kept under the local storage root.
+    pub async fn sync_with(&self, remote_root: &Path) -> Result<SyncReport> {
+        self.sync_usecases(remote_root)?.sync_once().await
+    }
+
+    /// Syncs with `config.sync_root` every `sync_interval` under the task
+    /// supervisor; `None` when sync is not configured. The first pass runs
+    /// immediately.
+    pub fn spawn_sync(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let remote_root = self.sync_root.clone()?;
+        let sync = Arc::new(self.sync_usecases(&remote_root).ok()?);
+        Some(self.tasks.supervise("sync", self.sync_interval, move || {
+            let sync = sync.clone();
+            let remote_root = remote_root.clone();
+            AuditCaller::new("sync", None).scope(async move {
+                let report = sync.sync_once().await?;
+                if !report.conflicts.is_empty() || !report.errors.is_empty() {
+                    tracing::warn!(
+                        conflicts = report.conflicts.len(),
+                        errors = report.errors.len(),
+                        "sync with '{}' left packs unsynced",
+                        remote_root.display()
+                    );
+                }
+                Ok(())
+            })
+        }))
+    }
+
+    fn sync_usecases(&self, remote_root: &Path) -> Result<SyncUseCases> {
+        let storage_root = self.storage_root.clone().ok_or_else(|| {
+            DomainError::InvalidState(
+                "sync needs a storage root; build the service with ContextPackService::new"
+                    .to_string(),
+            )
+        })?;
+        // Remote packs are encrypted with the local key, so peers syncing
+        // through the same root need the same key.
+        let cipher = self.cipher.clone();
+        let remote: Arc<dyn PackRepositoryPort> = Arc::new(
+            JsonStorageAdapter::new(remote_root.join("packs"))
+                .with_cipher(cipher.clone())
+                .with_compression(self.compression)
+                .with_layout(self.layout),
+        );
+        let state =
+            Arc::new(SyncStateFsAdapter::new(storage_root, remote_root).with_cipher(cipher));
+        Ok(SyncUseCases::new(self.repo.clone(), remote, state))
+    }
+
+    /// Serves the MCP protocol over stdin/stdout until the client exits.
+    #[cfg(feature = "stdio")]
+    pub async fn serve_stdio(&self) -> anyhow::Result<()> {
+        crate::adapters::mcp_stdio::serve_connection(
+            tokio::io::stdin(),
+            tokio::io::stdout(),
+            &self.server_context(),
+        )
+        .await
+    }
+
+    /// Runs one MCP session over an arbitrary byte stream, exactly as
+    /// [`Self::serve_stdio`] does over stdin/stdout.
+    #[cfg(feature = "stdio")]
+    pub async fn serve_io<R, W>(&self, input: R, output: W) -> anyhow::Result<()>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        crate::adapters::mcp_stdio::serve_connection(input, output, &self.server_context()).await
+    }
+
+    /// Serves MCP over HTTP/SSE on `addr` until the process exits; each SSE
+    /// stream is an independent session over the same store. With `token`,
+    /// every request must send it as `Authorization: Bearer`; a non-loopback
+    /// `addr` without one is refused.
+    #[cfg(feature = "http")]
+    pub async fn serve_http(
+        &self,
+        addr: std::net::SocketAddr,
+        token: Option<String>,
+    ) -> anyhow::Result<()> {
+        crate::adapters::mcp_http::start_mcp_http_server(addr, token, self.server_context()).await
+    }
+
+    #[cfg(feature = "stdio")]
+    fn server_context(&self) -> crate::adapters::mcp_stdio::ServerContext {
+        let ctx = crate::adapters::mcp_stdio::ServerContext::new(
+            self.input.clone(),
+            self.output.clone(),
+            self.replay_journal.clone(),
+            self.backup.clone(),
+            self.saved_filters.clone(),
+        )
+        .with_tasks(self.tasks.clone())
+        .with_metrics(self.metrics.clone());
+        let ctx = match &self.health_probe {
+            Some(probe) => ctx.with_health_probe(probe.clone()),
+            None => ctx,
+        };
+        match &self.source_watch {
+            Some(watch) => ctx.with_source_watch(watch.clone()),
+            None => ctx,
+        }
+    }
+}
+
+fn source_root_from_env_or_cwd() -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+    let raw = match std::env::var("CONTEXT_PACK_SOURCE_ROOT") {
+        Ok(v) => v,
+        Err(_) => return cwd,
+    };
+
+    let value = raw.trim();
+    if value.is_empty()
+        || value.eq_ignore_ascii_case("cwd")
+        || value.eq_ignore_ascii_case("session_cwd")
+        || value.eq_ignore_ascii_case("__SESSION_CWD__")
+        || value == "."
+    {
+        cwd
+    } else {
+        PathBuf::from(value)
+    }
+}
+
+/// Parses `CONTEXT_PACK_SOURCE_ROOTS`: colon-separated `name=path` entries,
+/// or bare paths named after their last directory (lowercased). Names must
+/// be valid tokens and unique.
+pub fn parse_source_roots(raw: &str) -> Result<Vec<(SourceRootName, PathBuf)>> {
+    let mut roots: Vec<(SourceRootName, PathBuf)> = Vec::new();
+    for entry in raw.split(':').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, path) = match entry.split_once('=') {
+            Some((name, path)) => (name.trim().to_string(), path.trim()),
+            None => {
+                let name = Path::new(entry)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                (name, entry)
+            }
+        };
+        let name = SourceRootName::new(&name).map_err(|e| {
+            DomainError::InvalidData(format!("{SOURCE_ROOTS_ENV}: entry '{entry}': {e}"))
+        })?;
+        if path.is_empty() {
+            return Err(DomainError::InvalidData(format!(
+                "{SOURCE_ROOTS_ENV}: root '{name}' has no path"
+            )));
+        }
+        if roots.iter().any(|(existing, _)| *existing == name) {
+            return Err(DomainError::InvalidData(format!(
+                "{SOURCE_ROOTS_ENV}: root name '{name}' is used twice; name entries explicitly with name=path"
+            )));
+        }
+        roots.push((name, PathBuf::from(path)));
+    }
+    Ok(roots)
+}
+
+fn positive_usize_from_env(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(default)
+}
+
+fn diagram_limits_from_env() -> DiagramLimits {
+    DiagramLimits {
+        max_bytes: positive_usize_from_env(
+            "CONTEXT_PACK_MAX_DIAGRAM_BYTES",
+            DiagramLimits::DEFAULT_MAX_BYTES,
+        ),
+        max_nodes: positive_usize_from_env(
+            "CONTEXT_PACK_MAX_DIAGRAM_NODES",
+            DiagramLimits::DEFAULT_MAX_NODES,
+        ),
+        max_edges: positive_usize_from_env(
+            "CONTEXT_PACK_MAX_DIAGRAM_EDGES",
+            DiagramLimits::DEFAULT_MAX_EDGES,
+        ),
+    }
+}
+
+fn excerpt_limits_from_env() -> ExcerptLimits {
+    ExcerptLimits {
+        default_lines: positive_usize_from_env(
+            "CONTEXT_PACK_EXCERPT_LINES",
+            ExcerptLimits::DEFAULT_LINES,
+        ),
+        max_lines: positive_usize_from_env(
+            "CONTEXT_PACK_MAX_EXCERPT_LINES",
+            ExcerptLimits::DEFAULT_MAX_LINES,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_parse_source_roots_names_bare_paths_and_rejects_duplicates() {
+        let roots = parse_source_roots(" web=/srv/web-app : /home/me/Api ::").unwrap();
+        let names: Vec<&str> = roots.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["web", "api"]);
+        assert_eq!(roots[1].1, PathBuf::from("/home/me/Api"));
+
+        assert!(parse_source_roots("/a/web:/b/web").is_err());
+        assert!(parse_source_roots("Web=/srv/web").is_err());
+        assert!(parse_source_roots("web=").is_err());
+        assert!(parse_source_roots("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_self_check_passes_for_writable_storage_and_existing_source() {
+        let storage = tempfile::tempdir().unwrap();
+        let source = tempfile::tempdir().unwrap();
+        let config = ContextPackConfig::new(storage.path().join("root"), source.path());
+
+        let report = config.self_check_with_env(no_env);
+
+        assert!(!report.has_critical(), "{report:?}");
+        assert!(config.storage_dir().is_dir());
+        assert!(!storage.path().join("root/.self_check_probe").exists());
+        assert!(report.into_result().is_ok());
+    }
+
+    #[test]
+    fn test_self_check_fails_closed_on_critical_misconfiguration() {
+        let storage = tempfile::tempdir().unwrap();
+        let mut config =
+            ContextPackConfig::new(storage.path(), storage.path().join("missing-source"));
+        config.diagram_limits.max_nodes = 0;
+
+        let report = config.self_check_with_env(|name| match name {
+            "CONTEXT_PACK_REPLAY_WINDOW_SECONDS" => Some("ten".into()),
+            "CONTEXT_PACK_MAX_PACK_BYTES" => Some("1024".into()),
+            _ => None,
+        });
+
+        let status_of = |name: &str| -> Vec<CheckStatus> {
+            report
+                .checks
+                .iter()
+                .filter(|check| check.name == name)
+                .map(|check| check.status)
+                .collect()
+        };
+        assert_eq!(status_of("source_root"), vec![CheckStatus::Critical]);
+        assert_eq!(
+            status_of("CONTEXT_PACK_REPLAY_WINDOW_SECONDS"),
+            vec![CheckStatus::Critical]
+        );
+        assert_eq!(
+            status_of("limits"),
+            vec![CheckStatus::Critical, CheckStatus::Warning]
+        );
+
+        let err = report.into_result().unwrap_err();
+        let DomainError::DetailedInvalidData { message, details } = err else {
+            panic!("expected detailed error");
+        };
+        assert!(message.starts_with("startup self-check failed"));
+        assert_eq!(details["failed_checks"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_self_check_rejects_malformed_encryption_key_without_echoing_it() {
+        let storage = tempfile::tempdir().unwrap();
+        let config = ContextPackConfig::new(storage.path(), storage.path());
+        let secret = "not-a-hex-key-but-still-secret";
+
+        let report = config
+            .self_check_with_env(|name| (name == ENCRYPTION_KEY_ENV).then(|| secret.to_string()));
+
+        let check = report
+            .checks
+            .iter()
+            .find(|check| check.name == ENCRYPTION_KEY_ENV)
+            .unwrap();
+        assert_eq!(check.status, CheckStatus::Critical);
+        assert!(!check.detail.contains(secret), "{}", check.detail);
+    }
+
+    #[test]
+    fn test_self_check_fails_when_the_backend_cannot_encrypt() {
+        let storage = tempfile::tempdir().unwrap();
+        let mut config = ContextPackConfig::new(storage.path(), storage.path());
+        config.storage = StorageBackend::Memory;
+        let key = "00".repeat(32);
+
+        let report =
+            config.self_check_with_env(|name| (name == ENCRYPTION_KEY_ENV).then(|| key.clone()));
+
+        let check = report
+            .checks
+            .iter()
+            .find(|check| check.name == ENCRYPTION_KEY_ENV)
+            .unwrap();
+        assert_eq!(check.status, CheckStatus::Critical, "{}", check.detail);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_self_check_requires_a_token_for_non_loopback_http() {
+        use crate::adapters::mcp_http::{HTTP_ADDR_ENV, HTTP_TOKEN_ENV};
+        let storage = tempfile::tempdir().unwrap();
+        let config = ContextPackConfig::new(storage.path(), storage.path());
+        let status = |addr: &str, token: Option<&str>| {
+            let report = config.self_check_with_env(|name| match name {
+                HTTP_ADDR_ENV => Some(addr.to_string()),
+                HTTP_TOKEN_ENV => token.map(str::to_string),
+                _ => None,
+            });
+            report
+                .checks
+                .iter()
+                .find(|check| check.name == HTTP_ADDR_ENV)
+                .unwrap()
+                .status
+        };
+        assert_eq!(status("127.0.0.1:7070", None), CheckStatus::Ok);
+        assert_eq!(status("0.0.0.0:7070", None), CheckStatus::Critical);
+        assert_eq!(status("0.0.0.0:7070", Some(" ")), CheckStatus::Critical);
+        assert_eq!(status("0.0.0.0:7070", Some("s3cret")), CheckStatus::Ok);
+    }
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "stdio")]
+
 use anyhow::{Context, Result};
 use chrono::{Duration, Utc};
 use mcp_context_pack::domain::{
@@ -21,12 +23,21 @@ struct McpE2EClient {
 
 impl McpE2EClient {
     async fn spawn(storage_root: &Path, source_root: &Path) -> Result<Self> {
+        Self::spawn_with_env(storage_root, source_root, &[]).await
+    }
+
+    async fn spawn_with_env(
+        storage_root: &Path,
+        source_root: &Path,
+        extra_env: &[(&str, &str)],
+    ) -> Result<Self> {
         let bin_path = resolve_binary_path()?;
 
         let mut child = Command::new(bin_path)
             .env("CONTEXT_PACK_ROOT", storage_root)
             .env("CONTEXT_PACK_SOURCE_ROOT", source_root)
             .env("CONTEXT_PACK_LOG", "off")
+            .envs(extra_env.iter().copied())
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .spawn()
@@ -305,11 +316,40 @@ async fn e2e_tool_call_roundtrip_with_real_stdio() -> Result<()> {
             .context("missing output tool schema")?;
         assert_eq!(
             input_tool["inputSchema"]["properties"]["action"]["enum"],
-            json!(["list", "get", "write", "ttl", "delete"])
+            json!([
+                "list",
+                "get",
+                "lint",
+                "validate",
+                "write",
+                "estimate",
+                "ttl",
+                "delete",
+                "prepare_delete",
+                "move_section",
+                "merge",
+                "split",
+                "sign_off",
+                "set_links",
+                "add_link",
+                "rollback",
+                "repair_refs",
+                "upsert_attachment",
+                "delete_attachment",
+                "export",
+                "import",
+                "import_markdown",
+                "create_from_template",
+                "clone",
+                "diagram_history",
+                "audit",
+                "save_filter",
+                "delete_filter"
+            ])
         );
         assert_eq!(
             output_tool["inputSchema"]["properties"]["action"]["enum"],
-            json!(["list", "read"])
+            json!(["list", "read", "read_delta", "watch", "graph", "continue"])
         );
 
         let created = client
@@ -588,59 +628,97 @@ async fn e2e_input_delete_pack_is_deterministic() -> Result<()> {
 }
 
 #[tokio::test]
-async fn e2e_tool_error_contract_is_machine_readable() -> Result<()> {
+async fn e2e_replayed_write_after_reconnect_is_not_applied_twice() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
 
-    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let create_request = json!({
+        "jsonrpc":"2.0",
+        "id":2,
+        "method":"tools/call",
+        "params":{
+            "name":"input",
+            "arguments":{
+                "action":"write",
+                "idempotency_key":"create-replay-pack",
+                "document":{
+                    "title":"Replay pack",
+                    "ttl_minutes":60,
+                    "sections":[]
+                }
+            }
+        }
+    });
+
+    let mut first = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let first_result: Result<String> = async {
+        let _ = first
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+        let created = first.call(create_request.clone()).await?;
+        let payload = parse_tool_payload(&created)?;
+        Ok(payload["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string())
+    }
+    .await;
+    first.stop().await?;
+    let pack_id = first_result?;
 
+    let mut second = McpE2EClient::spawn(&storage_root, &source_root).await?;
     let result: Result<()> = async {
-        let _ = client
+        let _ = second
             .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
             .await?;
+        let replayed = second.call(create_request.clone()).await?;
+        assert_eq!(replayed["result"]["_meta"]["replayed"], true);
+        let replayed_payload = parse_tool_payload(&replayed)?;
+        assert_eq!(
+            replayed_payload["payload"]["id"].as_str(),
+            Some(pack_id.as_str())
+        );
 
-        let response = client
+        let list = second
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":2,
+                "id":3,
                 "method":"tools/call",
-                "params":{
-                    "name":"input",
-                    "arguments":{
-                        "action":"boom"
-                    }
-                }
+                "params":{ "name":"input", "arguments":{ "action":"list" } }
             }))
             .await?;
-
-        assert_eq!(response["result"]["isError"], true);
-        let err_payload = parse_tool_payload(&response)?;
-        assert_eq!(err_payload["error"], true);
-        assert_eq!(err_payload["kind"], "validation");
-        assert_eq!(err_payload["code"], "invalid_data");
-        assert_eq!(err_payload["request_id"], 2);
-        assert!(err_payload["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("unknown input action"));
-        assert_eq!(err_payload["details"]["requested_action"], "boom");
-        assert_eq!(
-            err_payload["details"]["allowed_actions"],
-            json!(["list", "get", "write", "ttl", "delete"])
+        let list_payload = parse_tool_payload(&list)?;
+        assert_eq!(list_payload["payload"]["count"].as_u64(), Some(1));
+
+        // Same id and key with other arguments is not a retry.
+        let mut altered_request = create_request.clone();
+        altered_request["params"]["arguments"]["document"]["title"] = json!("Other pack");
+        let altered = second.call(altered_request).await?;
+        assert_eq!(altered["result"]["isError"], true);
+        assert_eq!(parse_tool_payload(&altered)?["code"], "conflict");
+
+        let mut fresh_request = create_request.clone();
+        fresh_request["id"] = json!(4);
+        let fresh = second.call(fresh_request).await?;
+        assert!(fresh["result"].get("_meta").is_none());
+        let fresh_payload = parse_tool_payload(&fresh)?;
+        assert_ne!(
+            fresh_payload["payload"]["id"].as_str(),
+            Some(pack_id.as_str())
         );
         Ok(())
     }
     .await;
 
-    client.stop().await?;
+    second.stop().await?;
     result
 }
 
 #[tokio::test]
-async fn e2e_v3_rejects_legacy_actions_with_invalid_data() -> Result<()> {
+async fn e2e_diagram_history_keeps_previous_versions_and_diffs() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
@@ -654,7 +732,19 @@ async fn e2e_v3_rejects_legacy_actions_with_invalid_data() -> Result<()> {
             .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
             .await?;
 
-        let legacy_input = client
+        let document = |mermaid: &str| {
+            json!({
+                "name":"diagram-history",
+                "ttl_minutes":60,
+                "sections":[{
+                    "key":"arch",
+                    "title":"Architecture",
+                    "diagrams":[{ "key":"flow", "title":"Flow", "mermaid": mermaid }]
+                }]
+            })
+        };
+
+        let create = client
             .call(json!({
                 "jsonrpc":"2.0",
                 "id":2,
@@ -662,65 +752,65 @@ async fn e2e_v3_rejects_legacy_actions_with_invalid_data() -> Result<()> {
                 "params":{
                     "name":"input",
                     "arguments":{
-                        "action":"create",
-                        "name":"legacy-cutover-check",
-                        "ttl_minutes":30
+                        "action":"write",
+                        "document": document("graph TD\n  A --> B")
                     }
                 }
             }))
             .await?;
-        assert_eq!(legacy_input["result"]["isError"], true);
-        let legacy_input_payload = parse_tool_payload(&legacy_input)?;
-        assert_eq!(legacy_input_payload["code"], "invalid_data");
-        assert!(
-            legacy_input_payload["message"]
-                .as_str()
-                .unwrap_or_default()
-                .contains("not supported in v3"),
-            "legacy input action must return explicit invalid_data"
-        );
-        assert!(
-            legacy_input_payload["message"]
-                .as_str()
-                .unwrap_or_default()
-                .contains("action='write'"),
-            "legacy input action error must include v3 guidance"
-        );
-        assert_eq!(
-            legacy_input_payload["details"]["legacy_mapping"]["action"],
-            "write"
-        );
-        assert_eq!(
-            legacy_input_payload["details"]["legacy_mapping"]["required_fields"],
-            json!(["document"])
-        );
+        let created = parse_tool_payload(&create)?;
+        let pack_id = created["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string();
 
-        let legacy_output = client
+        let update = client
             .call(json!({
                 "jsonrpc":"2.0",
                 "id":3,
                 "method":"tools/call",
                 "params":{
-                    "name":"output",
+                    "name":"input",
                     "arguments":{
-                        "action":"get",
-                        "id":"pk_aaaaaaaa"
+                        "action":"write",
+                        "id": pack_id,
+                        "expected_revision": payload_pack_revision(&created)?,
+                        "document": document("graph TD\n  A --> C")
                     }
                 }
             }))
             .await?;
-        assert_eq!(legacy_output["result"]["isError"], true);
-        let legacy_output_payload = parse_tool_payload(&legacy_output)?;
-        assert_eq!(legacy_output_payload["code"], "invalid_data");
-        assert!(
-            legacy_output_payload["message"]
-                .as_str()
-                .unwrap_or_default()
-                .contains("use action='read'"),
-            "legacy output action must return explicit invalid_data guidance"
-        );
-        assert_eq!(legacy_output_payload["details"]["requested_action"], "get");
+        assert!(update["result"].get("isError").is_none());
 
+        let history = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":4,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"diagram_history",
+                        "id": pack_id,
+                        "section_key":"arch",
+                        "diagram_key":"flow",
+                        "diff": true
+                    }
+                }
+            }))
+            .await?;
+        let payload = parse_tool_payload(&history)?;
+        let versions = payload["payload"]["versions"]
+            .as_array()
+            .context("missing versions")?;
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0]["mermaid"], "graph TD\n  A --> B");
+        assert_eq!(versions[0]["revision"], 1);
+        assert_eq!(versions[1]["current"], true);
+        assert_eq!(
+            payload["payload"]["diff"]["text"],
+            "  graph TD\n-   A --> B\n+   A --> C"
+        );
         Ok(())
     }
     .await;
@@ -730,12 +820,13 @@ async fn e2e_v3_rejects_legacy_actions_with_invalid_data() -> Result<()> {
 }
 
 #[tokio::test]
-async fn e2e_input_rejects_missing_write_expected_revision_with_details() -> Result<()> {
+async fn e2e_write_ops_batch_bumps_revision_once() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
-    tokio::fs::create_dir_all(&source_root).await?;
+    tokio::fs::create_dir_all(source_root.join("src")).await?;
+    tokio::fs::write(source_root.join("src/lib.rs"), "fn a() {}\nfn b() {}\n").await?;
 
     let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
 
@@ -743,8 +834,7 @@ async fn e2e_input_rejects_missing_write_expected_revision_with_details() -> Res
         let _ = client
             .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
             .await?;
-
-        let created = client
+        let create = client
             .call(json!({
                 "jsonrpc":"2.0",
                 "id":2,
@@ -753,22 +843,14 @@ async fn e2e_input_rejects_missing_write_expected_revision_with_details() -> Res
                     "name":"input",
                     "arguments":{
                         "action":"write",
-                        "document":{
-                            "name":"missing-revision-pack",
-                            "ttl_minutes":30,
-                            "sections":[]
-                        }
+                        "document":{ "name":"ops-pack", "ttl_minutes":60, "sections":[] }
                     }
                 }
             }))
             .await?;
-        let created_payload = parse_tool_payload(&created)?;
-        let pack_id = created_payload["payload"]["id"]
-            .as_str()
-            .context("missing created pack id")?
-            .to_string();
+        let revision = payload_pack_revision(&parse_tool_payload(&create)?)?;
 
-        let response = client
+        let ops = client
             .call(json!({
                 "jsonrpc":"2.0",
                 "id":3,
@@ -777,26 +859,43 @@ async fn e2e_input_rejects_missing_write_expected_revision_with_details() -> Res
                     "name":"input",
                     "arguments":{
                         "action":"write",
-                        "id":pack_id,
-                        "document":{
-                            "name":"missing-revision-pack",
-                            "ttl_minutes":30,
-                            "status":"draft",
-                            "sections":[]
-                        }
+                        "name":"ops-pack",
+                        "expected_revision": revision,
+                        "ops":[
+                            { "op":"upsert_section", "section_key":"refs", "title":"Refs" },
+                            { "op":"upsert_ref", "section_key":"refs", "ref_key":"fn-a", "path":"src/lib.rs", "line_start":1, "line_end":1 },
+                            { "op":"upsert_ref", "section_key":"refs", "ref_key":"fn-b", "path":"src/lib.rs", "line_start":2, "line_end":2 },
+                            { "op":"set_meta", "tags":["batched"] }
+                        ]
                     }
                 }
             }))
             .await?;
+        let payload = parse_tool_payload(&ops)?;
+        assert_eq!(payload_pack_revision(&payload)?, revision + 1);
+        assert_eq!(payload["payload"]["ops_applied"], 4);
+        assert_eq!(payload["payload"]["tags"], json!(["batched"]));
 
-        assert_eq!(response["result"]["isError"], true);
-        let err_payload = parse_tool_payload(&response)?;
-        assert_eq!(err_payload["kind"], "validation");
-        assert_eq!(err_payload["code"], "invalid_data");
-        assert_eq!(
-            err_payload["details"]["required_fields"],
-            json!(["expected_revision"])
-        );
+        let bad = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":4,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "name":"ops-pack",
+                        "expected_revision": revision + 1,
+                        "ops":[{ "op":"rename_section", "section_key":"refs" }]
+                    }
+                }
+            }))
+            .await?;
+        assert_eq!(bad["result"]["isError"], true);
+        let payload = parse_tool_payload(&bad)?;
+        assert_eq!(payload["code"], "invalid_data");
+        assert_eq!(payload["details"]["field"], "ops[0].op");
         Ok(())
     }
     .await;
@@ -806,191 +905,216 @@ async fn e2e_input_rejects_missing_write_expected_revision_with_details() -> Res
 }
 
 #[tokio::test]
-async fn e2e_request_id_and_revision_conflict_contract() -> Result<()> {
+async fn e2e_enforced_ownership_requires_force_for_other_actors() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
 
-    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let mut client = McpE2EClient::spawn_with_env(
+        &storage_root,
+        &source_root,
+        &[("CONTEXT_PACK_OWNERSHIP", "enforce")],
+    )
+    .await?;
 
     let result: Result<()> = async {
         let _ = client
-            .call(json!({"jsonrpc":"2.0","id":"init-1","method":"initialize","params":{}}))
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":1,
+                "method":"initialize",
+                "params":{ "clientInfo":{ "name":"agent-a" } }
+            }))
             .await?;
-
-        let created = client
+        let create = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":"create-1",
+                "id":2,
                 "method":"tools/call",
                 "params":{
                     "name":"input",
                     "arguments":{
                         "action":"write",
-                        "document":{
-                            "name":"conflict-e2e",
-                            "ttl_minutes":120,
-                            "status":"draft",
-                            "sections":[
-                                {"key":"sec","title":"Section"}
-                            ]
-                        }
+                        "document":{ "name":"owned-pack", "ttl_minutes":60, "sections":[] }
                     }
                 }
             }))
             .await?;
-        let created_payload = parse_tool_payload(&created)?;
-        let pack_id = created_payload["payload"]["id"]
-            .as_str()
-            .context("missing created pack id")?
-            .to_string();
-        let stale_revision = payload_pack_revision(&created_payload)?;
+        let payload = parse_tool_payload(&create)?;
+        assert_eq!(payload["payload"]["owner"], "agent-a");
+        let revision = payload_pack_revision(&payload)?;
 
-        let updated = client
+        let set_tags = |id: u64, revision: u64, extra: Value| {
+            let mut arguments = json!({
+                "action":"write",
+                "name":"owned-pack",
+                "expected_revision": revision,
+                "ops":[{ "op":"set_meta", "tags":["touched"] }]
+            });
+            for (key, value) in extra.as_object().into_iter().flatten() {
+                arguments[key] = value.clone();
+            }
+            json!({
+                "jsonrpc":"2.0",
+                "id":id,
+                "method":"tools/call",
+                "params":{ "name":"input", "arguments":arguments }
+            })
+        };
+
+        let denied = client
+            .call(set_tags(3, revision, json!({ "actor":"agent-b" })))
+            .await?;
+        assert_eq!(denied["result"]["isError"], true);
+        let err = parse_tool_payload(&denied)?;
+        assert_eq!(err["kind"], "forbidden");
+        assert_eq!(err["code"], "owner_mismatch");
+        assert_eq!(err["details"]["owner"], "agent-a");
+        assert_eq!(err["details"]["actor"], "agent-b");
+
+        let forced = client
+            .call(set_tags(
+                4,
+                revision,
+                json!({ "actor":"agent-b", "force":true }),
+            ))
+            .await?;
+        let payload = parse_tool_payload(&forced)?;
+        assert_eq!(payload_pack_revision(&payload)?, revision + 1);
+        assert_eq!(payload["payload"]["owner"], "agent-a");
+
+        // No actor: the session's client name, the owner here.
+        let own = client.call(set_tags(5, revision + 1, json!({}))).await?;
+        assert_eq!(
+            payload_pack_revision(&parse_tool_payload(&own)?)?,
+            revision + 2
+        );
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_audit_log_records_mutations_and_survives_delete() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":"update-1",
+                "id":1,
+                "method":"initialize",
+                "params":{ "clientInfo":{ "name":"agent-a" } }
+            }))
+            .await?;
+        let create = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
                 "method":"tools/call",
                 "params":{
                     "name":"input",
                     "arguments":{
                         "action":"write",
-                        "id": pack_id.clone(),
-                        "expected_revision": stale_revision,
-                        "document":{
-                            "name":"conflict-e2e",
-                            "ttl_minutes":120,
-                            "status":"draft",
-                            "sections":[
-                                {"key":"sec","title":"Section updated"}
-                            ]
-                        }
+                        "document":{ "name":"audited-pack", "ttl_minutes":60, "sections":[] }
                     }
                 }
             }))
             .await?;
-        let _next_revision = payload_pack_revision(&parse_tool_payload(&updated)?)?;
+        let payload = parse_tool_payload(&create)?;
+        let revision = payload_pack_revision(&payload)?;
+        let pack_id = payload["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string();
 
-        let conflict = client
+        let _ = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":"conflict-req",
+                "id":3,
                 "method":"tools/call",
                 "params":{
                     "name":"input",
                     "arguments":{
                         "action":"write",
-                        "id": pack_id.clone(),
-                        "expected_revision": stale_revision,
-                        "document":{
-                            "name":"conflict-e2e",
-                            "title":"new title",
-                            "ttl_minutes":120,
-                            "status":"draft",
-                            "sections":[
-                                {"key":"sec","title":"Section updated"}
-                            ]
-                        }
+                        "id": pack_id,
+                        "actor":"agent-b",
+                        "expected_revision": revision,
+                        "ops":[{ "op":"set_meta", "tags":["audited"] }]
                     }
                 }
             }))
             .await?;
-
-        assert_eq!(conflict["result"]["isError"], true);
-        let err_payload = parse_tool_payload(&conflict)?;
-        assert_eq!(err_payload["kind"], "conflict");
-        assert_eq!(err_payload["code"], "revision_conflict");
-        assert_eq!(err_payload["request_id"], "conflict-req");
-        assert_eq!(
-            err_payload["details"]["expected_revision"].as_u64(),
-            Some(stale_revision)
-        );
-        let current_revision = err_payload["details"]["current_revision"]
-            .as_u64()
-            .context("missing current_revision details")?;
-        assert!(
-            current_revision > stale_revision,
-            "conflict should include fresher current_revision"
-        );
-        assert_eq!(
-            err_payload["details"]["actual_revision"].as_u64(),
-            Some(current_revision),
-            "actual_revision is kept as compatibility alias"
-        );
-        assert!(
-            err_payload["details"]["last_updated_at"]
-                .as_str()
-                .unwrap_or_default()
-                .contains('T'),
-            "details must include last_updated_at timestamp"
-        );
-        let changed_keys = err_payload["details"]["changed_section_keys"]
-            .as_array()
-            .context("missing changed_section_keys")?;
-        assert!(
-            changed_keys.iter().any(|key| key.as_str() == Some("sec")),
-            "changed_section_keys should include the modified section"
-        );
-        assert!(
-            changed_keys.len() <= 12,
-            "changed_section_keys should be bounded"
-        );
-        assert!(
-            err_payload["details"]["guidance"]
-                .as_str()
-                .unwrap_or_default()
-                .contains("re-read latest pack"),
-            "guidance must explain retry workflow"
-        );
-
-        let reread = client
+        let deleted = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":"reread-1",
+                "id":4,
                 "method":"tools/call",
                 "params":{
                     "name":"input",
-                    "arguments":{
-                        "action":"get",
-                        "id": pack_id.clone()
-                    }
+                    "arguments":{ "action":"delete", "id": pack_id }
                 }
             }))
             .await?;
-        let latest_revision = payload_pack_revision(&parse_tool_payload(&reread)?)?;
+        assert_eq!(parse_tool_payload(&deleted)?["payload"]["deleted"], true);
+        assert!(storage_root.join("audit.jsonl").exists());
 
-        let retry = client
+        let audit = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":"retry-1",
+                "id":5,
                 "method":"tools/call",
                 "params":{
                     "name":"input",
-                    "arguments":{
-                        "action":"write",
-                        "id": pack_id,
-                        "expected_revision": latest_revision,
-                        "document":{
-                            "name":"conflict-e2e",
-                            "title":"resolved-after-reread",
-                            "ttl_minutes":120,
-                            "status":"draft",
-                            "sections":[
-                                {"key":"sec","title":"Section updated"}
-                            ]
-                        }
-                    }
+                    "arguments":{ "action":"audit", "id": pack_id }
                 }
             }))
             .await?;
-        assert_ne!(retry["result"]["isError"], true);
-        let retried_payload = parse_tool_payload(&retry)?;
-        assert!(
-            payload_pack_revision(&retried_payload)? > latest_revision,
-            "retry with fresh revision should succeed and bump revision"
+        let payload = parse_tool_payload(&audit)?;
+        assert_eq!(payload["payload"]["pack_id"], pack_id.as_str());
+        assert_eq!(payload["payload"]["count"], 3);
+        let entries = payload["payload"]["entries"]
+            .as_array()
+            .context("missing audit entries")?;
+        let events: Vec<_> = entries
+            .iter()
+            .map(|e| (e["event"].as_str(), e["op"].as_str(), e["actor"].as_str()))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (Some("create"), Some("write"), Some("agent-a")),
+                (Some("write"), Some("write"), Some("agent-b")),
+                (Some("delete"), Some("delete"), Some("agent-a")),
+            ]
         );
+        assert_eq!(entries[1]["revision_before"], revision);
+        assert_eq!(entries[1]["revision_after"], revision + 1);
+
+        let last = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":6,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{ "action":"audit", "id": pack_id, "limit": 1 }
+                }
+            }))
+            .await?;
+        let payload = parse_tool_payload(&last)?;
+        assert_eq!(payload["payload"]["entries"][0]["event"], "delete");
         Ok(())
     }
     .await;
@@ -1000,22 +1124,34 @@ async fn e2e_request_id_and_revision_conflict_contract() -> Result<()> {
 }
 
 #[tokio::test]
-async fn e2e_finalize_validation_reports_missing_sections_and_invalid_refs() -> Result<()> {
+async fn e2e_host_defaults_apply_to_known_client() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
-    tokio::fs::write(source_root.join("short.rs"), "line1\nline2\n").await?;
 
-    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let mut client = McpE2EClient::spawn_with_env(
+        &storage_root,
+        &source_root,
+        &[(
+            "CONTEXT_PACK_HOST_DEFAULTS",
+            r#"{"tight-host":{"profile":"executor","limit":2}}"#,
+        )],
+    )
+    .await?;
 
     let result: Result<()> = async {
         let _ = client
-            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":1,
+                "method":"initialize",
+                "params":{ "clientInfo": { "name":"tight-host", "version":"0.9.0" } }
+            }))
             .await?;
 
-        let created = client
+        let create = client
             .call(json!({
                 "jsonrpc":"2.0",
                 "id":2,
@@ -1024,176 +1160,128 @@ async fn e2e_finalize_validation_reports_missing_sections_and_invalid_refs() ->
                     "name":"input",
                     "arguments":{
                         "action":"write",
-                        "document":{
-                            "name":"finalize-validation-e2e",
-                            "ttl_minutes":30,
-                            "status":"draft",
-                            "sections":[
-                                {"key":"scope","title":"Scope","description":"scope text"},
-                                {
-                                    "key":"findings",
-                                    "title":"Findings",
-                                    "description":"finding text",
-                                    "refs":[
-                                        {
-                                            "key":"ref-one",
-                                            "path":"short.rs",
-                                            "line_start":10,
-                                            "line_end":10
-                                        }
-                                    ]
-                                }
-                            ]
-                        }
+                        "document":{ "name":"host-pack", "ttl_minutes":60, "sections":[] }
                     }
                 }
             }))
             .await?;
-        let created_payload = parse_tool_payload(&created)?;
-        let pack_id = created_payload["payload"]["id"]
-            .as_str()
-            .context("missing created pack id")?
-            .to_string();
-        let mut revision = payload_pack_revision(&created_payload)?;
+        assert!(create["result"].get("isError").is_none());
 
-        let missing_qa = client
+        let read = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":6,
+                "id":3,
                 "method":"tools/call",
-                "params":{
-                    "name":"input",
-                    "arguments":{
-                        "action":"write",
-                        "id": pack_id.clone(),
-                        "expected_revision": revision,
-                        "document":{
-                            "name":"finalize-validation-e2e",
-                            "ttl_minutes":30,
-                            "status":"finalized",
-                            "sections":[
-                                {"key":"scope","title":"Scope","description":"scope text"},
-                                {
-                                    "key":"findings",
-                                    "title":"Findings",
-                                    "description":"finding text",
-                                    "refs":[
-                                        {
-                                            "key":"ref-one",
-                                            "path":"short.rs",
-                                            "line_start":10,
-                                            "line_end":10
-                                        }
-                                    ]
-                                }
-                            ]
-                        }
-                    }
-                }
+                "params":{ "name":"output", "arguments":{ "action":"read", "name":"host-pack" } }
             }))
             .await?;
-        assert_eq!(missing_qa["result"]["isError"], true);
-        let missing_qa_payload = parse_tool_payload(&missing_qa)?;
-        assert_eq!(missing_qa_payload["code"], "finalize_validation");
+        let markdown = output_markdown(&read)?;
         assert_eq!(
-            missing_qa_payload["details"]["missing_sections"],
-            json!(["qa"])
+            legend_value(markdown, "profile").as_deref(),
+            Some("executor")
+        );
+        assert_eq!(legend_value(markdown, "limit").as_deref(), Some("2"));
+        assert_eq!(
+            legend_value(markdown, "host_defaults").as_deref(),
+            Some("tight-host")
         );
-        assert_eq!(missing_qa_payload["details"]["missing_fields"], json!([]));
-        assert_eq!(missing_qa_payload["details"]["invalid_refs"], json!([]));
 
-        let qa = client
+        let explicit = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":7,
+                "id":4,
                 "method":"tools/call",
                 "params":{
-                    "name":"input",
-                    "arguments":{
-                        "action":"write",
-                        "id": pack_id.clone(),
-                        "expected_revision": revision,
-                        "document":{
-                            "name":"finalize-validation-e2e",
-                            "ttl_minutes":30,
-                            "status":"draft",
-                            "sections":[
-                                {"key":"scope","title":"Scope","description":"scope text"},
-                                {
-                                    "key":"findings",
-                                    "title":"Findings",
-                                    "description":"finding text",
-                                    "refs":[
-                                        {
-                                            "key":"ref-one",
-                                            "path":"short.rs",
-                                            "line_start":10,
-                                            "line_end":10
-                                        }
-                                    ]
-                                },
-                                {"key":"qa","title":"QA","description":"verdict: fail"}
-                            ]
-                        }
-                    }
+                    "name":"output",
+                    "arguments":{ "action":"read", "name":"host-pack", "profile":"reviewer" }
                 }
             }))
             .await?;
-        revision = payload_pack_revision(&parse_tool_payload(&qa)?)?;
+        let markdown = output_markdown(&explicit)?;
+        assert_eq!(
+            legend_value(markdown, "profile").as_deref(),
+            Some("reviewer")
+        );
+        Ok(())
+    }
+    .await;
 
-        let broken_ref = client
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_tool_error_contract_is_machine_readable() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+
+        let response = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":8,
+                "id":2,
                 "method":"tools/call",
                 "params":{
                     "name":"input",
                     "arguments":{
-                        "action":"write",
-                        "id": pack_id,
-                        "expected_revision": revision,
-                        "document":{
-                            "name":"finalize-validation-e2e",
-                            "ttl_minutes":30,
-                            "status":"finalized",
-                            "sections":[
-                                {"key":"scope","title":"Scope","description":"scope text"},
-                                {
-                                    "key":"findings",
-                                    "title":"Findings",
-                                    "description":"finding text",
-                                    "refs":[
-                                        {
-                                            "key":"ref-one",
-                                            "path":"short.rs",
-                                            "line_start":10,
-                                            "line_end":10
-                                        }
-                                    ]
-                                },
-                                {"key":"qa","title":"QA","description":"verdict: fail"}
-                            ]
-                        }
+                        "action":"boom"
                     }
                 }
             }))
             .await?;
-        assert_eq!(broken_ref["result"]["isError"], true);
-        let broken_ref_payload = parse_tool_payload(&broken_ref)?;
-        assert_eq!(broken_ref_payload["code"], "finalize_validation");
-        assert_eq!(broken_ref_payload["details"]["missing_sections"], json!([]));
-        assert_eq!(broken_ref_payload["details"]["missing_fields"], json!([]));
-        assert_eq!(
-            broken_ref_payload["details"]["invalid_refs"][0]["section_key"],
-            "findings"
-        );
-        assert_eq!(
-            broken_ref_payload["details"]["invalid_refs"][0]["ref_key"],
-            "ref-one"
-        );
+
+        assert_eq!(response["result"]["isError"], true);
+        let err_payload = parse_tool_payload(&response)?;
+        assert_eq!(err_payload["error"], true);
+        assert_eq!(err_payload["kind"], "validation");
+        assert_eq!(err_payload["code"], "invalid_data");
+        assert_eq!(err_payload["request_id"], 2);
+        assert!(err_payload["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("unknown input action"));
+        assert_eq!(err_payload["details"]["requested_action"], "boom");
         assert_eq!(
-            broken_ref_payload["details"]["invalid_refs"][0]["path"],
-            "short.rs"
+            err_payload["details"]["allowed_actions"],
+            json!([
+                "list",
+                "get",
+                "lint",
+                "validate",
+                "write",
+                "estimate",
+                "ttl",
+                "delete",
+                "prepare_delete",
+                "move_section",
+                "merge",
+                "split",
+                "sign_off",
+                "set_links",
+                "add_link",
+                "rollback",
+                "repair_refs",
+                "upsert_attachment",
+                "delete_attachment",
+                "export",
+                "import",
+                "import_markdown",
+                "create_from_template",
+                "clone",
+                "diagram_history",
+                "audit",
+                "save_filter",
+                "delete_filter"
+            ])
         );
         Ok(())
     }
@@ -1204,13 +1292,12 @@ async fn e2e_finalize_validation_reports_missing_sections_and_invalid_refs() ->
 }
 
 #[tokio::test]
-async fn e2e_write_snapshot_validate_only_precheck_and_finalize_commit() -> Result<()> {
+async fn e2e_v3_rejects_legacy_actions_with_invalid_data() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
-    tokio::fs::write(source_root.join("auth.rs"), "line1\nline2\nline3\n").await?;
 
     let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
 
@@ -1219,7 +1306,7 @@ async fn e2e_write_snapshot_validate_only_precheck_and_finalize_commit() -> Resu
             .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
             .await?;
 
-        let created = client
+        let legacy_input = client
             .call(json!({
                 "jsonrpc":"2.0",
                 "id":2,
@@ -1227,110 +1314,64 @@ async fn e2e_write_snapshot_validate_only_precheck_and_finalize_commit() -> Resu
                 "params":{
                     "name":"input",
                     "arguments":{
-                        "action":"write",
-                        "document":{
-                            "name":"snapshot-e2e",
-                            "title":"Snapshot E2E",
-                            "ttl_minutes":30,
-                            "status":"draft",
-                            "sections":[
-                                {"key":"notes","title":"Notes","description":"draft content"}
-                            ]
-                        }
-                    }
-                }
-            }))
-            .await?;
-        let created_payload = parse_tool_payload(&created)?;
-        let pack_id = created_payload["payload"]["id"]
-            .as_str()
-            .context("missing created pack id")?
-            .to_string();
-        let revision = payload_pack_revision(&created_payload)?;
-
-        let precheck = client
-            .call(json!({
-                "jsonrpc":"2.0",
-                "id":3,
-                "method":"tools/call",
-                "params":{
-                    "name":"input",
-                    "arguments":{
-                        "action":"write",
-                        "id": pack_id.clone(),
-                        "expected_revision": revision,
-                        "validate_only": true,
-                        "document":{
-                            "name":"snapshot-e2e",
-                            "title":"Finalize precheck",
-                            "status":"finalized",
-                            "sections":[
-                                {"key":"scope","title":"Scope","description":"scope text"},
-                                {"key":"findings","title":"Findings","description":"finding text","refs":[{"key":"ref-one","path":"auth.rs","line_start":1,"line_end":2}]}
-                            ]
-                        }
-                    }
-                }
-            }))
-            .await?;
-        assert_eq!(precheck["result"]["isError"], true);
-        let precheck_payload = parse_tool_payload(&precheck)?;
-        assert_eq!(precheck_payload["code"], "finalize_validation");
-        assert_eq!(precheck_payload["details"]["missing_sections"], json!(["qa"]));
-
-        let reread = client
-            .call(json!({
-                "jsonrpc":"2.0",
-                "id":4,
-                "method":"tools/call",
-                "params":{
-                    "name":"input",
-                    "arguments":{
-                        "action":"get",
-                        "id": pack_id.clone()
+                        "action":"create",
+                        "name":"legacy-cutover-check",
+                        "ttl_minutes":30
                     }
                 }
             }))
             .await?;
-        let reread_payload = parse_tool_payload(&reread)?;
+        assert_eq!(legacy_input["result"]["isError"], true);
+        let legacy_input_payload = parse_tool_payload(&legacy_input)?;
+        assert_eq!(legacy_input_payload["code"], "invalid_data");
+        assert!(
+            legacy_input_payload["message"]
+                .as_str()
+                .unwrap_or_default()
+                .contains("not supported in v3"),
+            "legacy input action must return explicit invalid_data"
+        );
+        assert!(
+            legacy_input_payload["message"]
+                .as_str()
+                .unwrap_or_default()
+                .contains("action='write'"),
+            "legacy input action error must include v3 guidance"
+        );
         assert_eq!(
-            payload_pack_revision(&reread_payload)?,
-            revision,
-            "validate_only precheck must not persist pack state"
+            legacy_input_payload["details"]["legacy_mapping"]["action"],
+            "write"
+        );
+        assert_eq!(
+            legacy_input_payload["details"]["legacy_mapping"]["required_fields"],
+            json!(["document"])
         );
-        assert_eq!(reread_payload["payload"]["status"], "draft");
 
-        let finalized = client
+        let legacy_output = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":5,
+                "id":3,
                 "method":"tools/call",
                 "params":{
-                    "name":"input",
+                    "name":"output",
                     "arguments":{
-                        "action":"write",
-                        "id": pack_id.clone(),
-                        "expected_revision": revision,
-                        "document":{
-                            "name":"snapshot-e2e",
-                            "title":"Finalize commit",
-                            "status":"finalized",
-                            "sections":[
-                                {"key":"scope","title":"Scope","description":"scope text"},
-                                {"key":"findings","title":"Findings","description":"finding text","refs":[{"key":"ref-one","path":"auth.rs","line_start":1,"line_end":2}]},
-                                {"key":"qa","title":"QA","description":"verdict: pass"}
-                            ]
-                        }
+                        "action":"get",
+                        "id":"pk_aaaaaaaa"
                     }
                 }
             }))
             .await?;
-        let finalized_payload = parse_tool_payload(&finalized)?;
-        assert_eq!(finalized_payload["payload"]["status"], "finalized");
+        assert_eq!(legacy_output["result"]["isError"], true);
+        let legacy_output_payload = parse_tool_payload(&legacy_output)?;
+        assert_eq!(legacy_output_payload["code"], "invalid_data");
         assert!(
-            payload_pack_revision(&finalized_payload)? > revision,
-            "persisted write should increment revision"
+            legacy_output_payload["message"]
+                .as_str()
+                .unwrap_or_default()
+                .contains("use action='read'"),
+            "legacy output action must return explicit invalid_data guidance"
         );
+        assert_eq!(legacy_output_payload["details"]["requested_action"], "get");
 
         Ok(())
     }
@@ -1341,7 +1382,7 @@ async fn e2e_write_snapshot_validate_only_precheck_and_finalize_commit() -> Resu
 }
 
 #[tokio::test]
-async fn e2e_touch_ttl_requires_mode_field() -> Result<()> {
+async fn e2e_input_rejects_missing_write_expected_revision_with_details() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
@@ -1365,7 +1406,7 @@ async fn e2e_touch_ttl_requires_mode_field() -> Result<()> {
                     "arguments":{
                         "action":"write",
                         "document":{
-                            "name":"ttl-mode-pack",
+                            "name":"missing-revision-pack",
                             "ttl_minutes":30,
                             "sections":[]
                         }
@@ -1378,7 +1419,6 @@ async fn e2e_touch_ttl_requires_mode_field() -> Result<()> {
             .as_str()
             .context("missing created pack id")?
             .to_string();
-        let revision = payload_pack_revision(&created_payload)?;
 
         let response = client
             .call(json!({
@@ -1388,9 +1428,14 @@ async fn e2e_touch_ttl_requires_mode_field() -> Result<()> {
                 "params":{
                     "name":"input",
                     "arguments":{
-                        "action":"ttl",
-                        "id": pack_id,
-                        "expected_revision": revision
+                        "action":"write",
+                        "id":pack_id,
+                        "document":{
+                            "name":"missing-revision-pack",
+                            "ttl_minutes":30,
+                            "status":"draft",
+                            "sections":[]
+                        }
                     }
                 }
             }))
@@ -1402,7 +1447,7 @@ async fn e2e_touch_ttl_requires_mode_field() -> Result<()> {
         assert_eq!(err_payload["code"], "invalid_data");
         assert_eq!(
             err_payload["details"]["required_fields"],
-            json!(["ttl_minutes", "extend_minutes"])
+            json!(["expected_revision"])
         );
         Ok(())
     }
@@ -1413,7 +1458,7 @@ async fn e2e_touch_ttl_requires_mode_field() -> Result<()> {
 }
 
 #[tokio::test]
-async fn e2e_write_requires_document() -> Result<()> {
+async fn e2e_request_id_and_revision_conflict_contract() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
@@ -1424,103 +1469,180 @@ async fn e2e_write_requires_document() -> Result<()> {
 
     let result: Result<()> = async {
         let _ = client
-            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .call(json!({"jsonrpc":"2.0","id":"init-1","method":"initialize","params":{}}))
             .await?;
 
-        let response = client
+        let created = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":2,
+                "id":"create-1",
                 "method":"tools/call",
                 "params":{
                     "name":"input",
                     "arguments":{
                         "action":"write",
-                        "name":"missing-document"
+                        "document":{
+                            "name":"conflict-e2e",
+                            "ttl_minutes":120,
+                            "status":"draft",
+                            "sections":[
+                                {"key":"sec","title":"Section"}
+                            ]
+                        }
                     }
                 }
             }))
             .await?;
+        let created_payload = parse_tool_payload(&created)?;
+        let pack_id = created_payload["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string();
+        let stale_revision = payload_pack_revision(&created_payload)?;
 
-        assert_eq!(response["result"]["isError"], true);
-        let err_payload = parse_tool_payload(&response)?;
-        assert_eq!(err_payload["kind"], "validation");
-        assert_eq!(err_payload["code"], "invalid_data");
-        assert_eq!(
-            err_payload["details"]["required_fields"],
-            json!(["document"])
-        );
-        Ok(())
-    }
-    .await;
-
-    client.stop().await?;
-    result
-}
+        let updated = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":"update-1",
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "id": pack_id.clone(),
+                        "expected_revision": stale_revision,
+                        "document":{
+                            "name":"conflict-e2e",
+                            "ttl_minutes":120,
+                            "status":"draft",
+                            "sections":[
+                                {"key":"sec","title":"Section updated"}
+                            ]
+                        }
+                    }
+                }
+            }))
+            .await?;
+        let _next_revision = payload_pack_revision(&parse_tool_payload(&updated)?)?;
 
-#[tokio::test]
-async fn e2e_shutdown_exit_terminates_server() -> Result<()> {
-    let dir = tempdir()?;
-    let storage_root = dir.path().join("storage");
-    let source_root = dir.path().join("source");
-    tokio::fs::create_dir_all(&storage_root).await?;
-    tokio::fs::create_dir_all(&source_root).await?;
-
-    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
-
-    let _ = client
-        .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
-        .await?;
-    let shutdown = client
-        .call(json!({"jsonrpc":"2.0","id":2,"method":"shutdown","params":{}}))
-        .await?;
-    assert_eq!(shutdown["id"], 2);
-
-    // exit can be notification; server should terminate promptly
-    client
-        .send_raw_json(json!({"jsonrpc":"2.0","method":"exit","params":{}}))
-        .await?;
-
-    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
-    let status = client.child.try_wait().context("query child status")?;
-    assert!(status.is_some(), "server must exit after 'exit'");
-    Ok(())
-}
-
-#[tokio::test]
-async fn e2e_notification_without_id_produces_no_response() -> Result<()> {
-    let dir = tempdir()?;
-    let storage_root = dir.path().join("storage");
-    let source_root = dir.path().join("source");
-    tokio::fs::create_dir_all(&storage_root).await?;
-    tokio::fs::create_dir_all(&source_root).await?;
-
-    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
-
-    let result: Result<()> = async {
-        let _ = client
-            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+        let conflict = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":"conflict-req",
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "id": pack_id.clone(),
+                        "expected_revision": stale_revision,
+                        "document":{
+                            "name":"conflict-e2e",
+                            "title":"new title",
+                            "ttl_minutes":120,
+                            "status":"draft",
+                            "sections":[
+                                {"key":"sec","title":"Section updated"}
+                            ]
+                        }
+                    }
+                }
+            }))
             .await?;
 
-        client
-            .send_raw_json(json!({
+        assert_eq!(conflict["result"]["isError"], true);
+        let err_payload = parse_tool_payload(&conflict)?;
+        assert_eq!(err_payload["kind"], "conflict");
+        assert_eq!(err_payload["code"], "revision_conflict");
+        assert_eq!(err_payload["request_id"], "conflict-req");
+        assert_eq!(
+            err_payload["details"]["expected_revision"].as_u64(),
+            Some(stale_revision)
+        );
+        let current_revision = err_payload["details"]["current_revision"]
+            .as_u64()
+            .context("missing current_revision details")?;
+        assert!(
+            current_revision > stale_revision,
+            "conflict should include fresher current_revision"
+        );
+        assert_eq!(
+            err_payload["details"]["actual_revision"].as_u64(),
+            Some(current_revision),
+            "actual_revision is kept as compatibility alias"
+        );
+        assert!(
+            err_payload["details"]["last_updated_at"]
+                .as_str()
+                .unwrap_or_default()
+                .contains('T'),
+            "details must include last_updated_at timestamp"
+        );
+        let changed_keys = err_payload["details"]["changed_section_keys"]
+            .as_array()
+            .context("missing changed_section_keys")?;
+        assert!(
+            changed_keys.iter().any(|key| key.as_str() == Some("sec")),
+            "changed_section_keys should include the modified section"
+        );
+        assert!(
+            changed_keys.len() <= 12,
+            "changed_section_keys should be bounded"
+        );
+        assert!(
+            err_payload["details"]["guidance"]
+                .as_str()
+                .unwrap_or_default()
+                .contains("re-read latest pack"),
+            "guidance must explain retry workflow"
+        );
+
+        let reread = client
+            .call(json!({
                 "jsonrpc":"2.0",
-                "method":"notifications/initialized",
-                "params": {}
+                "id":"reread-1",
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"get",
+                        "id": pack_id.clone()
+                    }
+                }
             }))
             .await?;
+        let latest_revision = payload_pack_revision(&parse_tool_payload(&reread)?)?;
 
-        let ping = client
+        let retry = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":2,
-                "method":"ping",
-                "params": {}
+                "id":"retry-1",
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "id": pack_id,
+                        "expected_revision": latest_revision,
+                        "document":{
+                            "name":"conflict-e2e",
+                            "title":"resolved-after-reread",
+                            "ttl_minutes":120,
+                            "status":"draft",
+                            "sections":[
+                                {"key":"sec","title":"Section updated"}
+                            ]
+                        }
+                    }
+                }
             }))
             .await?;
-
-        assert_eq!(ping["id"], 2);
-        assert!(ping["result"].is_object());
+        assert_ne!(retry["result"]["isError"], true);
+        let retried_payload = parse_tool_payload(&retry)?;
+        assert!(
+            payload_pack_revision(&retried_payload)? > latest_revision,
+            "retry with fresh revision should succeed and bump revision"
+        );
         Ok(())
     }
     .await;
@@ -1530,17 +1652,13 @@ async fn e2e_notification_without_id_produces_no_response() -> Result<()> {
 }
 
 #[tokio::test]
-async fn e2e_output_read_supports_profiles_contains_and_page_token() -> Result<()> {
+async fn e2e_finalize_validation_reports_missing_sections_and_invalid_refs() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
-    tokio::fs::write(
-        source_root.join("flow.rs"),
-        "fn a() { let token = \"TOKEN_01\"; }\nfn b() { let token = \"TOKEN_02\"; }\nfn c() { let token = \"TOKEN_03\"; }\n",
-    )
-    .await?;
+    tokio::fs::write(source_root.join("short.rs"), "line1\nline2\n").await?;
 
     let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
 
@@ -1559,17 +1677,22 @@ async fn e2e_output_read_supports_profiles_contains_and_page_token() -> Result<(
                     "arguments":{
                         "action":"write",
                         "document":{
-                            "name":"paging-e2e-pack",
-                            "ttl_minutes":60,
+                            "name":"finalize-validation-e2e",
+                            "ttl_minutes":30,
                             "status":"draft",
                             "sections":[
+                                {"key":"scope","title":"Scope","description":"scope text"},
                                 {
-                                    "key":"flow",
-                                    "title":"Flow",
+                                    "key":"findings",
+                                    "title":"Findings",
+                                    "description":"finding text",
                                     "refs":[
-                                        {"key":"ref-01","path":"flow.rs","line_start":1,"line_end":1},
-                                        {"key":"ref-02","path":"flow.rs","line_start":2,"line_end":2},
-                                        {"key":"ref-03","path":"flow.rs","line_start":3,"line_end":3}
+                                        {
+                                            "key":"ref-one",
+                                            "path":"short.rs",
+                                            "line_start":10,
+                                            "line_end":10
+                                        }
                                     ]
                                 }
                             ]
@@ -1583,82 +1706,147 @@ async fn e2e_output_read_supports_profiles_contains_and_page_token() -> Result<(
             .as_str()
             .context("missing created pack id")?
             .to_string();
-        let revision = payload_pack_revision(&created_payload)?;
-        assert!(revision >= 1);
+        let mut revision = payload_pack_revision(&created_payload)?;
 
-        let page1 = client
+        let missing_qa = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":7,
+                "id":6,
                 "method":"tools/call",
                 "params":{
-                    "name":"output",
+                    "name":"input",
                     "arguments":{
-                        "action":"read",
+                        "action":"write",
                         "id": pack_id.clone(),
-                        "profile":"orchestrator",
-                        "contains":"TOKEN_0",
-                        "limit":1
+                        "expected_revision": revision,
+                        "document":{
+                            "name":"finalize-validation-e2e",
+                            "ttl_minutes":30,
+                            "status":"finalized",
+                            "sections":[
+                                {"key":"scope","title":"Scope","description":"scope text"},
+                                {
+                                    "key":"findings",
+                                    "title":"Findings",
+                                    "description":"finding text",
+                                    "refs":[
+                                        {
+                                            "key":"ref-one",
+                                            "path":"short.rs",
+                                            "line_start":10,
+                                            "line_end":10
+                                        }
+                                    ]
+                                }
+                            ]
+                        }
                     }
                 }
             }))
             .await?;
-        let markdown1 = output_markdown(&page1)?;
-        assert!(!markdown1.contains("```rust"));
-        assert_eq!(legend_value(markdown1, "has_more").as_deref(), Some("true"));
-        let next = legend_value(markdown1, "next_page_token")
-            .filter(|value| value != "null")
-            .context("missing next page token on first page")?;
-        assert_eq!(rendered_ref_keys(markdown1), vec!["ref-01"]);
+        assert_eq!(missing_qa["result"]["isError"], true);
+        let missing_qa_payload = parse_tool_payload(&missing_qa)?;
+        assert_eq!(missing_qa_payload["code"], "finalize_validation");
+        assert_eq!(
+            missing_qa_payload["details"]["missing_sections"],
+            json!(["qa"])
+        );
+        assert_eq!(missing_qa_payload["details"]["missing_fields"], json!([]));
+        assert_eq!(missing_qa_payload["details"]["invalid_refs"], json!([]));
 
-        let page2 = client
+        let qa = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":8,
+                "id":7,
                 "method":"tools/call",
                 "params":{
-                    "name":"output",
+                    "name":"input",
                     "arguments":{
-                        "action":"read",
-                        "id": pack_id,
-                        "page_token": next
-                    }
-                }
-            }))
-            .await?;
-        let markdown2 = output_markdown(&page2)?;
-        assert_eq!(legend_value(markdown2, "has_more").as_deref(), Some("true"));
-        let next_page_two = legend_value(markdown2, "next_page_token")
-            .filter(|value| value != "null")
-            .context("missing next page token on second page")?;
-        assert_eq!(rendered_ref_keys(markdown2), vec!["ref-02"]);
+                        "action":"write",
+                        "id": pack_id.clone(),
+                        "expected_revision": revision,
+                        "document":{
+                            "name":"finalize-validation-e2e",
+                            "ttl_minutes":30,
+                            "status":"draft",
+                            "sections":[
+                                {"key":"scope","title":"Scope","description":"scope text"},
+                                {
+                                    "key":"findings",
+                                    "title":"Findings",
+                                    "description":"finding text",
+                                    "refs":[
+                                        {
+                                            "key":"ref-one",
+                                            "path":"short.rs",
+                                            "line_start":10,
+                                            "line_end":10
+                                        }
+                                    ]
+                                },
+                                {"key":"qa","title":"QA","description":"verdict: fail"}
+                            ]
+                        }
+                    }
+                }
+            }))
+            .await?;
+        revision = payload_pack_revision(&parse_tool_payload(&qa)?)?;
 
-        let page3 = client
+        let broken_ref = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":9,
+                "id":8,
                 "method":"tools/call",
                 "params":{
-                    "name":"output",
+                    "name":"input",
                     "arguments":{
-                        "action":"read",
+                        "action":"write",
                         "id": pack_id,
-                        "page_token": next_page_two
+                        "expected_revision": revision,
+                        "document":{
+                            "name":"finalize-validation-e2e",
+                            "ttl_minutes":30,
+                            "status":"finalized",
+                            "sections":[
+                                {"key":"scope","title":"Scope","description":"scope text"},
+                                {
+                                    "key":"findings",
+                                    "title":"Findings",
+                                    "description":"finding text",
+                                    "refs":[
+                                        {
+                                            "key":"ref-one",
+                                            "path":"short.rs",
+                                            "line_start":10,
+                                            "line_end":10
+                                        }
+                                    ]
+                                },
+                                {"key":"qa","title":"QA","description":"verdict: fail"}
+                            ]
+                        }
                     }
                 }
             }))
             .await?;
-        let markdown3 = output_markdown(&page3)?;
+        assert_eq!(broken_ref["result"]["isError"], true);
+        let broken_ref_payload = parse_tool_payload(&broken_ref)?;
+        assert_eq!(broken_ref_payload["code"], "finalize_validation");
+        assert_eq!(broken_ref_payload["details"]["missing_sections"], json!([]));
+        assert_eq!(broken_ref_payload["details"]["missing_fields"], json!([]));
         assert_eq!(
-            legend_value(markdown3, "has_more").as_deref(),
-            Some("false")
+            broken_ref_payload["details"]["invalid_refs"][0]["section_key"],
+            "findings"
         );
         assert_eq!(
-            legend_value(markdown3, "next_page_token").as_deref(),
-            Some("null")
+            broken_ref_payload["details"]["invalid_refs"][0]["ref_key"],
+            "ref-one"
+        );
+        assert_eq!(
+            broken_ref_payload["details"]["invalid_refs"][0]["path"],
+            "short.rs"
         );
-        assert_eq!(rendered_ref_keys(markdown3), vec!["ref-03"]);
-
         Ok(())
     }
     .await;
@@ -1668,18 +1856,13 @@ async fn e2e_output_read_supports_profiles_contains_and_page_token() -> Result<(
 }
 
 #[tokio::test]
-async fn e2e_output_read_orchestrator_default_is_bounded_and_reviewer_is_full() -> Result<()> {
+async fn e2e_write_snapshot_validate_only_precheck_and_finalize_commit() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
-
-    let source_body = (1_u32..=15_u32)
-        .map(|idx| format!("fn step_{idx:02}() {{ let token = \"TOKEN_{idx:02}\"; }}"))
-        .collect::<Vec<_>>()
-        .join("\n");
-    tokio::fs::write(source_root.join("bounded.rs"), format!("{source_body}\n")).await?;
+    tokio::fs::write(source_root.join("auth.rs"), "line1\nline2\nline3\n").await?;
 
     let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
 
@@ -1688,17 +1871,6 @@ async fn e2e_output_read_orchestrator_default_is_bounded_and_reviewer_is_full()
             .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
             .await?;
 
-        let refs = (1_u32..=15_u32)
-            .map(|idx| {
-                json!({
-                    "key": format!("ref-{idx:02}"),
-                    "path": "bounded.rs",
-                    "line_start": idx,
-                    "line_end": idx
-                })
-            })
-            .collect::<Vec<_>>();
-
         let created = client
             .call(json!({
                 "jsonrpc":"2.0",
@@ -1709,17 +1881,12 @@ async fn e2e_output_read_orchestrator_default_is_bounded_and_reviewer_is_full()
                     "arguments":{
                         "action":"write",
                         "document":{
-                            "name":"bounded-default-pack",
-                            "title":"Bounded default pack",
-                            "brief":"Routing handoff for bounded compact output",
-                            "ttl_minutes":60,
+                            "name":"snapshot-e2e",
+                            "title":"Snapshot E2E",
+                            "ttl_minutes":30,
                             "status":"draft",
                             "sections":[
-                                {
-                                    "key":"routing",
-                                    "title":"Routing",
-                                    "refs":refs
-                                }
+                                {"key":"notes","title":"Notes","description":"draft content"}
                             ]
                         }
                     }
@@ -1732,132 +1899,89 @@ async fn e2e_output_read_orchestrator_default_is_bounded_and_reviewer_is_full()
             .context("missing created pack id")?
             .to_string();
         let revision = payload_pack_revision(&created_payload)?;
-        assert!(revision >= 1);
 
-        let compact_default = client
+        let precheck = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":4,
+                "id":3,
                 "method":"tools/call",
                 "params":{
-                    "name":"output",
+                    "name":"input",
                     "arguments":{
-                        "action":"read",
-                        "id": pack_id.clone()
+                        "action":"write",
+                        "id": pack_id.clone(),
+                        "expected_revision": revision,
+                        "validate_only": true,
+                        "document":{
+                            "name":"snapshot-e2e",
+                            "title":"Finalize precheck",
+                            "status":"finalized",
+                            "sections":[
+                                {"key":"scope","title":"Scope","description":"scope text"},
+                                {"key":"findings","title":"Findings","description":"finding text","refs":[{"key":"ref-one","path":"auth.rs","line_start":1,"line_end":2}]}
+                            ]
+                        }
                     }
                 }
             }))
             .await?;
-        let compact_markdown = output_markdown(&compact_default)?;
-        assert!(compact_markdown.contains("- profile: orchestrator"));
-        assert!(compact_markdown.contains("- mode: compact"));
-        assert!(compact_markdown.contains("- paging: active"));
-        assert!(compact_markdown.contains("- limit: 6"));
-        assert_eq!(
-            legend_value(compact_markdown, "has_more").as_deref(),
-            Some("true")
-        );
-        assert!(compact_markdown.contains("## Handoff summary [handoff]"));
-        assert!(compact_markdown.contains("- objective:"));
-        assert!(compact_markdown.contains("- scope:"));
-        assert!(compact_markdown.contains("- verdict_status:"));
-        assert!(compact_markdown.contains("- top_risks:"));
-        assert!(compact_markdown.contains("- top_gaps:"));
-        assert!(compact_markdown.contains("- deep_nav_hints:"));
-        assert!(!compact_markdown.contains("```rust"));
-        assert_eq!(
-            rendered_ref_keys(compact_markdown).len(),
-            6,
-            "default compact handoff must be bounded by default page size"
-        );
-        let next_cursor = legend_value(compact_markdown, "next_page_token")
-            .filter(|value| value != "null")
-            .context("default compact page should expose next page token for remainder")?;
+        assert_eq!(precheck["result"]["isError"], true);
+        let precheck_payload = parse_tool_payload(&precheck)?;
+        assert_eq!(precheck_payload["code"], "finalize_validation");
+        assert_eq!(precheck_payload["details"]["missing_sections"], json!(["qa"]));
 
-        let compact_page_two = client
+        let reread = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":5,
+                "id":4,
                 "method":"tools/call",
                 "params":{
-                    "name":"output",
+                    "name":"input",
                     "arguments":{
-                        "action":"read",
-                        "id": pack_id.clone(),
-                        "page_token": next_cursor
+                        "action":"get",
+                        "id": pack_id.clone()
                     }
                 }
             }))
             .await?;
-        let compact_page_two_markdown = output_markdown(&compact_page_two)?;
-        assert_eq!(
-            rendered_ref_keys(compact_page_two_markdown),
-            vec![
-                "ref-07".to_string(),
-                "ref-08".to_string(),
-                "ref-09".to_string(),
-                "ref-10".to_string(),
-                "ref-11".to_string(),
-                "ref-12".to_string()
-            ]
-        );
+        let reread_payload = parse_tool_payload(&reread)?;
         assert_eq!(
-            legend_value(compact_page_two_markdown, "has_more").as_deref(),
-            Some("true")
+            payload_pack_revision(&reread_payload)?,
+            revision,
+            "validate_only precheck must not persist pack state"
         );
-        let next_cursor_page_two = legend_value(compact_page_two_markdown, "next_page_token")
-            .filter(|value| value != "null")
-            .context("second compact page should still expose next page token")?;
+        assert_eq!(reread_payload["payload"]["status"], "draft");
 
-        let compact_page_three = client
+        let finalized = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":6,
+                "id":5,
                 "method":"tools/call",
                 "params":{
-                    "name":"output",
+                    "name":"input",
                     "arguments":{
-                        "action":"read",
+                        "action":"write",
                         "id": pack_id.clone(),
-                        "page_token": next_cursor_page_two
+                        "expected_revision": revision,
+                        "document":{
+                            "name":"snapshot-e2e",
+                            "title":"Finalize commit",
+                            "status":"finalized",
+                            "sections":[
+                                {"key":"scope","title":"Scope","description":"scope text"},
+                                {"key":"findings","title":"Findings","description":"finding text","refs":[{"key":"ref-one","path":"auth.rs","line_start":1,"line_end":2}]},
+                                {"key":"qa","title":"QA","description":"verdict: pass"}
+                            ]
+                        }
                     }
                 }
             }))
             .await?;
-        let compact_page_three_markdown = output_markdown(&compact_page_three)?;
-        assert_eq!(
-            rendered_ref_keys(compact_page_three_markdown),
-            vec![
-                "ref-13".to_string(),
-                "ref-14".to_string(),
-                "ref-15".to_string()
-            ]
-        );
-        assert_eq!(
-            legend_value(compact_page_three_markdown, "next_page_token").as_deref(),
-            Some("null")
-        );
-
-        let full = client
-            .call(json!({
-                "jsonrpc":"2.0",
-                "id":7,
-                "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"read",
-                        "id": pack_id,
-                        "profile":"reviewer"
-                    }
-                }
-            }))
-            .await?;
-        let full_markdown = output_markdown(&full)?;
-        assert!(full_markdown.contains("```rust"));
-        assert!(
-            compact_markdown.len() < full_markdown.len(),
-            "compact default should be materially smaller than full output"
+        let finalized_payload = parse_tool_payload(&finalized)?;
+        assert_eq!(finalized_payload["payload"]["status"], "finalized");
+        assert!(
+            payload_pack_revision(&finalized_payload)? > revision,
+            "persisted write should increment revision"
         );
 
         Ok(())
@@ -1869,7 +1993,7 @@ async fn e2e_output_read_orchestrator_default_is_bounded_and_reviewer_is_full()
 }
 
 #[tokio::test]
-async fn e2e_output_read_rejects_legacy_match_cursor_mode_fields() -> Result<()> {
+async fn e2e_touch_ttl_requires_mode_field() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
@@ -1893,8 +2017,8 @@ async fn e2e_output_read_rejects_legacy_match_cursor_mode_fields() -> Result<()>
                     "arguments":{
                         "action":"write",
                         "document":{
-                            "name":"regex-e2e-pack",
-                            "ttl_minutes":60,
+                            "name":"ttl-mode-pack",
+                            "ttl_minutes":30,
                             "sections":[]
                         }
                     }
@@ -1906,6 +2030,7 @@ async fn e2e_output_read_rejects_legacy_match_cursor_mode_fields() -> Result<()>
             .as_str()
             .context("missing created pack id")?
             .to_string();
+        let revision = payload_pack_revision(&created_payload)?;
 
         let response = client
             .call(json!({
@@ -1913,11 +2038,11 @@ async fn e2e_output_read_rejects_legacy_match_cursor_mode_fields() -> Result<()>
                 "id":3,
                 "method":"tools/call",
                 "params":{
-                    "name":"output",
+                    "name":"input",
                     "arguments":{
-                        "action":"read",
+                        "action":"ttl",
                         "id": pack_id,
-                        "match":"legacy-regex"
+                        "expected_revision": revision
                     }
                 }
             }))
@@ -1927,59 +2052,16 @@ async fn e2e_output_read_rejects_legacy_match_cursor_mode_fields() -> Result<()>
         let err_payload = parse_tool_payload(&response)?;
         assert_eq!(err_payload["kind"], "validation");
         assert_eq!(err_payload["code"], "invalid_data");
-        assert!(err_payload["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("'match' is not supported"));
-        assert_eq!(err_payload["details"]["unsupported_field"], "match");
-        assert_eq!(err_payload["details"]["supported_field"], "contains");
-
-        let cursor_response = client
-            .call(json!({
-                "jsonrpc":"2.0",
-                "id":4,
-                "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"read",
-                        "id":"pk_aaaaaaaa",
-                        "cursor":"v1:abc"
-                    }
-                }
-            }))
-            .await?;
-        assert_eq!(cursor_response["result"]["isError"], true);
-        let cursor_payload = parse_tool_payload(&cursor_response)?;
-        assert!(cursor_payload["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("'cursor' is not supported"));
-        assert_eq!(cursor_payload["details"]["unsupported_field"], "cursor");
-        assert_eq!(cursor_payload["details"]["supported_field"], "page_token");
-
-        let mode_response = client
-            .call(json!({
-                "jsonrpc":"2.0",
-                "id":5,
-                "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"read",
-                        "id":"pk_aaaaaaaa",
-                        "mode":"full"
-                    }
-                }
-            }))
-            .await?;
-        assert_eq!(mode_response["result"]["isError"], true);
-        let mode_payload = parse_tool_payload(&mode_response)?;
-        assert!(mode_payload["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("'mode' is not supported"));
-        assert_eq!(mode_payload["details"]["unsupported_field"], "mode");
+        assert_eq!(
+            err_payload["details"]["required_fields"],
+            json!([
+                "ttl_minutes",
+                "extend_minutes",
+                "sliding_ttl",
+                "sliding_ttl_minutes",
+                "pin"
+            ])
+        );
         Ok(())
     }
     .await;
@@ -1989,7 +2071,7 @@ async fn e2e_output_read_rejects_legacy_match_cursor_mode_fields() -> Result<()>
 }
 
 #[tokio::test]
-async fn e2e_output_read_requires_id_or_name() -> Result<()> {
+async fn e2e_write_requires_document() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
@@ -2009,9 +2091,10 @@ async fn e2e_output_read_requires_id_or_name() -> Result<()> {
                 "id":2,
                 "method":"tools/call",
                 "params":{
-                    "name":"output",
+                    "name":"input",
                     "arguments":{
-                        "action":"read"
+                        "action":"write",
+                        "name":"missing-document"
                     }
                 }
             }))
@@ -2023,7 +2106,7 @@ async fn e2e_output_read_requires_id_or_name() -> Result<()> {
         assert_eq!(err_payload["code"], "invalid_data");
         assert_eq!(
             err_payload["details"]["required_fields"],
-            json!(["id", "name"])
+            json!(["document"])
         );
         Ok(())
     }
@@ -2034,12 +2117,88 @@ async fn e2e_output_read_requires_id_or_name() -> Result<()> {
 }
 
 #[tokio::test]
-async fn e2e_output_rejects_format_parameter() -> Result<()> {
+async fn e2e_shutdown_exit_terminates_server() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let _ = client
+        .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+        .await?;
+    let shutdown = client
+        .call(json!({"jsonrpc":"2.0","id":2,"method":"shutdown","params":{}}))
+        .await?;
+    assert_eq!(shutdown["id"], 2);
+
+    // exit can be notification; server should terminate promptly
+    client
+        .send_raw_json(json!({"jsonrpc":"2.0","method":"exit","params":{}}))
+        .await?;
+
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let status = client.child.try_wait().context("query child status")?;
+    assert!(status.is_some(), "server must exit after 'exit'");
+    Ok(())
+}
+
+#[tokio::test]
+async fn e2e_notification_without_id_produces_no_response() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+
+        client
+            .send_raw_json(json!({
+                "jsonrpc":"2.0",
+                "method":"notifications/initialized",
+                "params": {}
+            }))
+            .await?;
+
+        let ping = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"ping",
+                "params": {}
+            }))
+            .await?;
+
+        assert_eq!(ping["id"], 2);
+        assert!(ping["result"].is_object());
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_output_read_supports_profiles_contains_and_page_token() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
+    tokio::fs::write(
+        source_root.join("flow.rs"),
+        "fn a() { let token = \"TOKEN_01\"; }\nfn b() { let token = \"TOKEN_02\"; }\nfn c() { let token = \"TOKEN_03\"; }\n",
+    )
+    .await?;
 
     let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
 
@@ -2058,9 +2217,20 @@ async fn e2e_output_rejects_format_parameter() -> Result<()> {
                     "arguments":{
                         "action":"write",
                         "document":{
-                            "name":"format-pack",
-                            "ttl_minutes":30,
-                            "sections":[]
+                            "name":"paging-e2e-pack",
+                            "ttl_minutes":60,
+                            "status":"draft",
+                            "sections":[
+                                {
+                                    "key":"flow",
+                                    "title":"Flow",
+                                    "refs":[
+                                        {"key":"ref-01","path":"flow.rs","line_start":1,"line_end":1},
+                                        {"key":"ref-02","path":"flow.rs","line_start":2,"line_end":2},
+                                        {"key":"ref-03","path":"flow.rs","line_start":3,"line_end":3}
+                                    ]
+                                }
+                            ]
                         }
                     }
                 }
@@ -2071,169 +2241,688 @@ async fn e2e_output_rejects_format_parameter() -> Result<()> {
             .as_str()
             .context("missing created pack id")?
             .to_string();
+        let revision = payload_pack_revision(&created_payload)?;
+        assert!(revision >= 1);
 
-        let response = client
+        let page1 = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":3,
+                "id":7,
                 "method":"tools/call",
                 "params":{
                     "name":"output",
                     "arguments":{
-                        "id": pack_id,
-                        "format":"json"
+                        "action":"read",
+                        "id": pack_id.clone(),
+                        "profile":"orchestrator",
+                        "contains":"TOKEN_0",
+                        "limit":1
                     }
                 }
             }))
             .await?;
+        let markdown1 = output_markdown(&page1)?;
+        assert!(!markdown1.contains("```rust"));
+        assert_eq!(legend_value(markdown1, "has_more").as_deref(), Some("true"));
+        let next = legend_value(markdown1, "next_page_token")
+            .filter(|value| value != "null")
+            .context("missing next page token on first page")?;
+        assert_eq!(rendered_ref_keys(markdown1), vec!["ref-01"]);
 
-        assert_eq!(response["result"]["isError"], true);
-        let err_payload = parse_tool_payload(&response)?;
-        assert_eq!(err_payload["kind"], "validation");
-        assert_eq!(err_payload["code"], "invalid_data");
-        assert!(err_payload["message"]
-            .as_str()
-            .unwrap_or_default()
-            .contains("always markdown"));
-        assert_eq!(err_payload["details"]["field"], "format");
-        assert_eq!(err_payload["details"]["required_mode"], "markdown");
-        Ok(())
-    }
-    .await;
-
-    client.stop().await?;
-    result
-}
-
-#[tokio::test]
-async fn e2e_shutdown_notification_has_no_side_effects() -> Result<()> {
-    let dir = tempdir()?;
-    let storage_root = dir.path().join("storage");
-    let source_root = dir.path().join("source");
-    tokio::fs::create_dir_all(&storage_root).await?;
-    tokio::fs::create_dir_all(&source_root).await?;
-
-    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
-
-    let _ = client
-        .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
-        .await?;
-    let _ = client
-        .call(json!({"jsonrpc":"2.0","id":2,"method":"shutdown","params":{}}))
-        .await?;
-
-    client
-        .send_raw_json(json!({
-            "jsonrpc":"2.0",
-            "method":"tools/call",
-            "params":{
-                "name":"input",
-                "arguments":{
-                    "action":"write",
-                    "document":{
-                        "name":"should-not-exist",
-                        "ttl_minutes":30,
-                        "sections":[]
+        let page2 = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":8,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id": pack_id,
+                        "page_token": next
                     }
                 }
-            }
-        }))
-        .await?;
+            }))
+            .await?;
+        let markdown2 = output_markdown(&page2)?;
+        assert_eq!(legend_value(markdown2, "has_more").as_deref(), Some("true"));
+        let next_page_two = legend_value(markdown2, "next_page_token")
+            .filter(|value| value != "null")
+            .context("missing next page token on second page")?;
+        assert_eq!(rendered_ref_keys(markdown2), vec!["ref-02"]);
 
-    let blocked = client
-        .call(json!({
-            "jsonrpc":"2.0",
-            "id":3,
-            "method":"tools/call",
-            "params":{
-                "name":"input",
-                "arguments":{"action":"list"}
-            }
-        }))
-        .await?;
-    assert_eq!(blocked["error"]["code"], -32000);
+        let page3 = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":9,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id": pack_id,
+                        "page_token": next_page_two
+                    }
+                }
+            }))
+            .await?;
+        let markdown3 = output_markdown(&page3)?;
+        assert_eq!(
+            legend_value(markdown3, "has_more").as_deref(),
+            Some("false")
+        );
+        assert_eq!(
+            legend_value(markdown3, "next_page_token").as_deref(),
+            Some("null")
+        );
+        assert_eq!(rendered_ref_keys(markdown3), vec!["ref-03"]);
 
-    client
-        .send_raw_json(json!({"jsonrpc":"2.0","method":"exit","params":{}}))
-        .await?;
-    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let structured = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":10,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id": pack_id,
+                        "page_token": next_page_two,
+                        "structured": true
+                    }
+                }
+            }))
+            .await?;
+        let content = structured["result"]["content"]
+            .as_array()
+            .context("missing content")?;
+        assert_eq!(content.len(), 2);
+        assert_eq!(rendered_ref_keys(output_markdown(&structured)?), vec!["ref-03"]);
+        let page: Value = serde_json::from_str(
+            content[1]["text"].as_str().context("missing structured text")?,
+        )?;
+        assert_eq!(page, structured["result"]["structuredContent"]);
+        assert_eq!(page["pack_id"], pack_id);
+        assert_eq!(page["paging"]["has_more"], false);
+        assert_eq!(page["paging"]["next_page_token"], Value::Null);
+        assert_eq!(page["sections"][0]["refs"][0]["key"], "ref-03");
+        assert_eq!(page["sections"][0]["refs"][0]["stale"], false);
 
-    let packs_dir = storage_root.join("packs");
-    if packs_dir.exists() {
-        let mut entries = tokio::fs::read_dir(&packs_dir).await?;
-        let mut has_pack_files = false;
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) == Some("json") {
-                has_pack_files = true;
-                break;
-            }
-        }
-        assert!(
-            !has_pack_files,
-            "no pack .json files should be created after shutdown notification"
-        );
+        Ok(())
     }
-    Ok(())
+    .await;
+
+    client.stop().await?;
+    result
 }
 
 #[tokio::test]
-async fn e2e_concurrent_create_same_name_rejects_one_process() -> Result<()> {
+async fn e2e_output_read_orchestrator_default_is_bounded_and_reviewer_is_full() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
 
-    let mut client1 = McpE2EClient::spawn(&storage_root, &source_root).await?;
-    let mut client2 = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let source_body = (1_u32..=15_u32)
+        .map(|idx| format!("fn step_{idx:02}() {{ let token = \"TOKEN_{idx:02}\"; }}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    tokio::fs::write(source_root.join("bounded.rs"), format!("{source_body}\n")).await?;
 
-    let _ = client1
-        .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
-        .await?;
-    let _ = client2
-        .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
-        .await?;
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
 
-    let barrier = std::sync::Arc::new(tokio::sync::Barrier::new(2));
-    let b1 = barrier.clone();
-    let b2 = barrier.clone();
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
 
-    let create_req = json!({
-        "jsonrpc":"2.0",
-        "id":2,
-        "method":"tools/call",
-        "params":{
-            "name":"input",
-            "arguments":{
-                "action":"write",
-                "document":{
-                    "name":"same-name",
-                    "ttl_minutes":30,
-                    "sections":[]
+        let refs = (1_u32..=15_u32)
+            .map(|idx| {
+                json!({
+                    "key": format!("ref-{idx:02}"),
+                    "path": "bounded.rs",
+                    "line_start": idx,
+                    "line_end": idx
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let created = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "document":{
+                            "name":"bounded-default-pack",
+                            "title":"Bounded default pack",
+                            "brief":"Routing handoff for bounded compact output",
+                            "ttl_minutes":60,
+                            "status":"draft",
+                            "sections":[
+                                {
+                                    "key":"routing",
+                                    "title":"Routing",
+                                    "refs":refs
+                                }
+                            ]
+                        }
+                    }
                 }
-            }
-        }
-    });
+            }))
+            .await?;
+        let created_payload = parse_tool_payload(&created)?;
+        let pack_id = created_payload["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string();
+        let revision = payload_pack_revision(&created_payload)?;
+        assert!(revision >= 1);
 
-    let create_req_1 = create_req.clone();
-    let t1 = tokio::spawn(async move {
-        b1.wait().await;
-        let resp = client1.call(create_req_1).await?;
-        client1.stop().await?;
-        Ok::<Value, anyhow::Error>(resp)
-    });
-    let t2 = tokio::spawn(async move {
-        b2.wait().await;
-        let resp = client2.call(create_req).await?;
-        client2.stop().await?;
-        Ok::<Value, anyhow::Error>(resp)
-    });
+        let compact_default = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":4,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id": pack_id.clone()
+                    }
+                }
+            }))
+            .await?;
+        let compact_markdown = output_markdown(&compact_default)?;
+        assert!(compact_markdown.contains("- profile: orchestrator"));
+        assert!(compact_markdown.contains("- mode: compact"));
+        assert!(compact_markdown.contains("- paging: active"));
+        assert!(compact_markdown.contains("- limit: 6"));
+        assert_eq!(
+            legend_value(compact_markdown, "has_more").as_deref(),
+            Some("true")
+        );
+        assert!(compact_markdown.contains("## Handoff summary [handoff]"));
+        assert!(compact_markdown.contains("- objective:"));
+        assert!(compact_markdown.contains("- scope:"));
+        assert!(compact_markdown.contains("- verdict_status:"));
+        assert!(compact_markdown.contains("- top_risks:"));
+        assert!(compact_markdown.contains("- top_gaps:"));
+        assert!(compact_markdown.contains("- deep_nav_hints:"));
+        assert!(!compact_markdown.contains("```rust"));
+        assert_eq!(
+            rendered_ref_keys(compact_markdown).len(),
+            6,
+            "default compact handoff must be bounded by default page size"
+        );
+        let next_cursor = legend_value(compact_markdown, "next_page_token")
+            .filter(|value| value != "null")
+            .context("default compact page should expose next page token for remainder")?;
 
-    let r1 = t1.await??;
-    let r2 = t2.await??;
+        let compact_page_two = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":5,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id": pack_id.clone(),
+                        "page_token": next_cursor
+                    }
+                }
+            }))
+            .await?;
+        let compact_page_two_markdown = output_markdown(&compact_page_two)?;
+        assert_eq!(
+            rendered_ref_keys(compact_page_two_markdown),
+            vec![
+                "ref-07".to_string(),
+                "ref-08".to_string(),
+                "ref-09".to_string(),
+                "ref-10".to_string(),
+                "ref-11".to_string(),
+                "ref-12".to_string()
+            ]
+        );
+        assert_eq!(
+            legend_value(compact_page_two_markdown, "has_more").as_deref(),
+            Some("true")
+        );
+        let next_cursor_page_two = legend_value(compact_page_two_markdown, "next_page_token")
+            .filter(|value| value != "null")
+            .context("second compact page should still expose next page token")?;
 
-    let responses = vec![r1, r2];
+        let compact_page_three = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":6,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id": pack_id.clone(),
+                        "page_token": next_cursor_page_two
+                    }
+                }
+            }))
+            .await?;
+        let compact_page_three_markdown = output_markdown(&compact_page_three)?;
+        assert_eq!(
+            rendered_ref_keys(compact_page_three_markdown),
+            vec![
+                "ref-13".to_string(),
+                "ref-14".to_string(),
+                "ref-15".to_string()
+            ]
+        );
+        assert_eq!(
+            legend_value(compact_page_three_markdown, "next_page_token").as_deref(),
+            Some("null")
+        );
+
+        let full = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":7,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id": pack_id,
+                        "profile":"reviewer"
+                    }
+                }
+            }))
+            .await?;
+        let full_markdown = output_markdown(&full)?;
+        assert!(full_markdown.contains("```rust"));
+        assert!(
+            compact_markdown.len() < full_markdown.len(),
+            "compact default should be materially smaller than full output"
+        );
+
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_output_read_rejects_legacy_match_cursor_mode_fields() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+
+        let created = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "document":{
+                            "name":"regex-e2e-pack",
+                            "ttl_minutes":60,
+                            "sections":[]
+                        }
+                    }
+                }
+            }))
+            .await?;
+        let created_payload = parse_tool_payload(&created)?;
+        let pack_id = created_payload["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string();
+
+        let response = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":3,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id": pack_id,
+                        "match":"legacy-regex"
+                    }
+                }
+            }))
+            .await?;
+
+        assert_eq!(response["result"]["isError"], true);
+        let err_payload = parse_tool_payload(&response)?;
+        assert_eq!(err_payload["kind"], "validation");
+        assert_eq!(err_payload["code"], "invalid_data");
+        assert!(err_payload["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("'match' is not supported"));
+        assert_eq!(err_payload["details"]["unsupported_field"], "match");
+        assert_eq!(err_payload["details"]["supported_field"], "contains");
+
+        let cursor_response = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":4,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id":"pk_aaaaaaaa",
+                        "cursor":"v1:abc"
+                    }
+                }
+            }))
+            .await?;
+        assert_eq!(cursor_response["result"]["isError"], true);
+        let cursor_payload = parse_tool_payload(&cursor_response)?;
+        assert!(cursor_payload["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("'cursor' is not supported"));
+        assert_eq!(cursor_payload["details"]["unsupported_field"], "cursor");
+        assert_eq!(cursor_payload["details"]["supported_field"], "page_token");
+
+        let mode_response = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":5,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id":"pk_aaaaaaaa",
+                        "mode":"full"
+                    }
+                }
+            }))
+            .await?;
+        assert_eq!(mode_response["result"]["isError"], true);
+        let mode_payload = parse_tool_payload(&mode_response)?;
+        assert!(mode_payload["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("'mode' is not supported"));
+        assert_eq!(mode_payload["details"]["unsupported_field"], "mode");
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_output_read_requires_id_or_name() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+
+        let response = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read"
+                    }
+                }
+            }))
+            .await?;
+
+        assert_eq!(response["result"]["isError"], true);
+        let err_payload = parse_tool_payload(&response)?;
+        assert_eq!(err_payload["kind"], "validation");
+        assert_eq!(err_payload["code"], "invalid_data");
+        assert_eq!(
+            err_payload["details"]["required_fields"],
+            json!(["id", "name"])
+        );
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_output_rejects_format_parameter() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+
+        let created = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "document":{
+                            "name":"format-pack",
+                            "ttl_minutes":30,
+                            "sections":[]
+                        }
+                    }
+                }
+            }))
+            .await?;
+        let created_payload = parse_tool_payload(&created)?;
+        let pack_id = created_payload["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string();
+
+        let response = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":3,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "id": pack_id,
+                        "format":"json"
+                    }
+                }
+            }))
+            .await?;
+
+        assert_eq!(response["result"]["isError"], true);
+        let err_payload = parse_tool_payload(&response)?;
+        assert_eq!(err_payload["kind"], "validation");
+        assert_eq!(err_payload["code"], "invalid_data");
+        assert!(err_payload["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("always markdown"));
+        assert_eq!(err_payload["details"]["field"], "format");
+        assert_eq!(err_payload["details"]["required_mode"], "markdown");
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_shutdown_notification_has_no_side_effects() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let _ = client
+        .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+        .await?;
+    let _ = client
+        .call(json!({"jsonrpc":"2.0","id":2,"method":"shutdown","params":{}}))
+        .await?;
+
+    client
+        .send_raw_json(json!({
+            "jsonrpc":"2.0",
+            "method":"tools/call",
+            "params":{
+                "name":"input",
+                "arguments":{
+                    "action":"write",
+                    "document":{
+                        "name":"should-not-exist",
+                        "ttl_minutes":30,
+                        "sections":[]
+                    }
+                }
+            }
+        }))
+        .await?;
+
+    let blocked = client
+        .call(json!({
+            "jsonrpc":"2.0",
+            "id":3,
+            "method":"tools/call",
+            "params":{
+                "name":"input",
+                "arguments":{"action":"list"}
+            }
+        }))
+        .await?;
+    assert_eq!(blocked["error"]["code"], -32000);
+
+    client
+        .send_raw_json(json!({"jsonrpc":"2.0","method":"exit","params":{}}))
+        .await?;
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    let packs_dir = storage_root.join("packs");
+    if packs_dir.exists() {
+        let mut entries = tokio::fs::read_dir(&packs_dir).await?;
+        let mut has_pack_files = false;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                has_pack_files = true;
+                break;
+            }
+        }
+        assert!(
+            !has_pack_files,
+            "no pack .json files should be created after shutdown notification"
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn e2e_concurrent_create_same_name_rejects_one_process() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client1 = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let mut client2 = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let _ = client1
+        .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+        .await?;
+    let _ = client2
+        .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+        .await?;
+
+    let barrier = std::sync::Arc::new(tokio::sync::Barrier::new(2));
+    let b1 = barrier.clone();
+    let b2 = barrier.clone();
+
+    let create_req = json!({
+        "jsonrpc":"2.0",
+        "id":2,
+        "method":"tools/call",
+        "params":{
+            "name":"input",
+            "arguments":{
+                "action":"write",
+                "document":{
+                    "name":"same-name",
+                    "ttl_minutes":30,
+                    "sections":[]
+                }
+            }
+        }
+    });
+
+    let create_req_1 = create_req.clone();
+    let t1 = tokio::spawn(async move {
+        b1.wait().await;
+        let resp = client1.call(create_req_1).await?;
+        client1.stop().await?;
+        Ok::<Value, anyhow::Error>(resp)
+    });
+    let t2 = tokio::spawn(async move {
+        b2.wait().await;
+        let resp = client2.call(create_req).await?;
+        client2.stop().await?;
+        Ok::<Value, anyhow::Error>(resp)
+    });
+
+    let r1 = t1.await??;
+    let r2 = t2.await??;
+
+    let responses = vec![r1, r2];
     let mut success_count = 0usize;
     let mut conflict_count = 0usize;
     for response in responses {
@@ -2248,13 +2937,1001 @@ async fn e2e_concurrent_create_same_name_rejects_one_process() -> Result<()> {
         }
     }
 
-    assert_eq!(success_count, 1, "exactly one create should succeed");
-    assert_eq!(conflict_count, 1, "second create must return conflict");
-    Ok(())
+    assert_eq!(success_count, 1, "exactly one create should succeed");
+    assert_eq!(conflict_count, 1, "second create must return conflict");
+    Ok(())
+}
+
+#[tokio::test]
+async fn e2e_initialize_with_content_type_header() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    client
+        .send_raw_json_with_content_type(
+            json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}),
+        )
+        .await?;
+    let init = client.read_response().await?;
+    assert_eq!(init["id"], 1);
+    assert!(init["result"]["serverInfo"]["name"].is_string());
+
+    client.stop().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn e2e_server_backup_restores_into_fresh_root() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let restored_root = dir.path().join("restored");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let result: Result<(String, String)> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+        let created = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "document":{ "title":"Backed up", "ttl_minutes":60, "sections":[] }
+                    }
+                }
+            }))
+            .await?;
+        let pack_id = parse_tool_payload(&created)?["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string();
+
+        let backup = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":3,
+                "method":"tools/call",
+                "params":{ "name":"server", "arguments":{ "action":"backup" } }
+            }))
+            .await?;
+        let payload = parse_tool_payload(&backup)?;
+        assert_eq!(payload["action"], "backup");
+        assert_eq!(payload["payload"]["pack_count"], 1);
+        let archive = payload["payload"]["archive_path"]
+            .as_str()
+            .context("missing archive path")?
+            .to_string();
+        Ok((pack_id, archive))
+    }
+    .await;
+    client.stop().await?;
+    let (pack_id, archive) = result?;
+
+    let restore = Command::new(resolve_binary_path()?)
+        .arg("restore")
+        .arg(&archive)
+        .env("CONTEXT_PACK_ROOT", &restored_root)
+        .env("CONTEXT_PACK_LOG", "off")
+        .output()
+        .await
+        .context("run restore subcommand")?;
+    assert!(
+        restore.status.success(),
+        "restore failed: {}",
+        String::from_utf8_lossy(&restore.stderr)
+    );
+    assert!(String::from_utf8_lossy(&restore.stdout).contains("restored 1 pack(s)"));
+
+    let mut restored = McpE2EClient::spawn(&restored_root, &source_root).await?;
+    let result: Result<()> = async {
+        let _ = restored
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+        let get = restored
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{ "name":"input", "arguments":{ "action":"get", "id": pack_id } }
+            }))
+            .await?;
+        assert_eq!(parse_tool_payload(&get)?["payload"]["title"], "Backed up");
+        Ok(())
+    }
+    .await;
+    restored.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_startup_self_check_refuses_invalid_config() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let output = Command::new(resolve_binary_path()?)
+        .env("CONTEXT_PACK_ROOT", &storage_root)
+        .env("CONTEXT_PACK_SOURCE_ROOT", &source_root)
+        .env("CONTEXT_PACK_LOG", "off")
+        .env("CONTEXT_PACK_MAX_DIAGRAM_NODES", "many")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .context("run MCP server")?;
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("startup self-check failed")
+            && stderr.contains("CONTEXT_PACK_MAX_DIAGRAM_NODES"),
+        "unexpected stderr: {stderr}"
+    );
+    assert!(output.stdout.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn e2e_startup_refuses_store_stamped_with_other_schema() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+    tokio::fs::write(
+        storage_root.join("store_meta.json"),
+        serde_json::to_vec(&json!({
+            "crate_version": "9.0.0",
+            "schema_version": 99,
+            "created_at": "2026-01-01T00:00:00Z"
+        }))?,
+    )
+    .await?;
+
+    let output = Command::new(resolve_binary_path()?)
+        .env("CONTEXT_PACK_ROOT", &storage_root)
+        .env("CONTEXT_PACK_SOURCE_ROOT", &source_root)
+        .env("CONTEXT_PACK_LOG", "off")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .context("run MCP server")?;
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("schema migration required") && stderr.contains("schema version 99"),
+        "unexpected stderr: {stderr}"
+    );
+    assert!(output.stdout.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn e2e_initialize_accepts_unframed_json_message() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let bin_path = resolve_binary_path()?;
+    let mut child = Command::new(bin_path)
+        .env("CONTEXT_PACK_ROOT", &storage_root)
+        .env("CONTEXT_PACK_SOURCE_ROOT", &source_root)
+        .env("CONTEXT_PACK_LOG", "off")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("spawn MCP server")?;
+
+    let mut stdin = tokio::io::BufWriter::new(
+        child
+            .stdin
+            .take()
+            .context("missing piped stdin for spawned server")?,
+    );
+    let mut stdout = BufReader::new(
+        child
+            .stdout
+            .take()
+            .context("missing piped stdout for spawned server")?,
+    );
+
+    stdin
+        .write_all(br#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#)
+        .await?;
+    stdin.write_all(b"\n").await?;
+    stdin.flush().await?;
+
+    let init = read_mcp_response(&mut stdout).await?;
+    assert_eq!(init["id"], 1);
+    assert_eq!(init["result"]["serverInfo"]["name"], "context-pack");
+
+    child.kill().await.ok();
+    child.wait().await.context("wait for server exit")?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn e2e_unframed_json_line_batch_is_processed_sequentially() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let bin_path = resolve_binary_path()?;
+    let mut child = Command::new(bin_path)
+        .env("CONTEXT_PACK_ROOT", &storage_root)
+        .env("CONTEXT_PACK_SOURCE_ROOT", &source_root)
+        .env("CONTEXT_PACK_LOG", "off")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("spawn MCP server")?;
+
+    let mut stdin = tokio::io::BufWriter::new(
+        child
+            .stdin
+            .take()
+            .context("missing piped stdin for spawned server")?,
+    );
+    let mut stdout = BufReader::new(
+        child
+            .stdout
+            .take()
+            .context("missing piped stdout for spawned server")?,
+    );
+
+    stdin
+        .write_all(br#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#)
+        .await?;
+    stdin.write_all(b"\n").await?;
+    stdin.flush().await?;
+
+    let init = read_mcp_response(&mut stdout).await?;
+    assert_eq!(init["id"], 1);
+
+    stdin
+        .write_all(
+            br#"{"jsonrpc":"2.0","id":2,"method":"ping"}
+{"jsonrpc":"2.0","id":3,"method":"ping"}
+"#,
+        )
+        .await?;
+    stdin.flush().await?;
+
+    let ping1 = read_mcp_response(&mut stdout).await?;
+    let ping2 = read_mcp_response(&mut stdout).await?;
+    assert_eq!(ping1["id"], 2);
+    assert_eq!(ping2["id"], 3);
+
+    child.kill().await.ok();
+    child.wait().await.context("wait for server exit")?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn e2e_server_exits_if_initialize_never_arrives() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let bin_path = resolve_binary_path()?;
+    let mut child = Command::new(bin_path)
+        .env("CONTEXT_PACK_ROOT", &storage_root)
+        .env("CONTEXT_PACK_SOURCE_ROOT", &source_root)
+        .env("CONTEXT_PACK_LOG", "off")
+        .env("CONTEXT_PACK_INITIALIZE_TIMEOUT_MS", "250")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("spawn MCP server")?;
+
+    let status = tokio::time::timeout(std::time::Duration::from_secs(3), child.wait())
+        .await
+        .context("wait timeout for child exit")?
+        .context("wait for child status")?;
+    assert!(
+        status.code().is_some(),
+        "server must exit if initialize is not received within timeout"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn e2e_input_write_rejects_legacy_fields() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+
+        let response = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "snapshot":{
+                            "name":"legacy-alias-pack",
+                            "ttl_minutes":30,
+                            "sections":[]
+                        }
+                    }
+                }
+            }))
+            .await?;
+
+        assert_eq!(response["result"]["isError"], true);
+        let err_payload = parse_tool_payload(&response)?;
+        assert_eq!(err_payload["kind"], "validation");
+        assert_eq!(err_payload["code"], "invalid_data");
+        assert_eq!(err_payload["details"]["unsupported_field"], "snapshot");
+        assert_eq!(err_payload["details"]["supported_field"], "document");
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_schema_mismatch_reports_migration_required() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(storage_root.join("packs")).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    tokio::fs::write(
+        storage_root.join("packs").join("pk_aaaaaaaa.json"),
+        r#"{"schema_version":1,"id":"pk_aaaaaaaa","name":"old-pack","title":null,"brief":null,"status":"draft","tags":[],"sections":[],"revision":1,"created_at":"2026-01-01T00:00:00Z","updated_at":"2026-01-01T00:00:00Z","expires_at":"2099-01-01T00:00:00Z"}"#,
+    )
+    .await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+
+        let response = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{"action":"list"}
+                }
+            }))
+            .await?;
+
+        assert_eq!(response["result"]["isError"], true);
+        let err_payload = parse_tool_payload(&response)?;
+        assert_eq!(err_payload["kind"], "migration_required");
+        assert_eq!(err_payload["code"], "migration_required");
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_output_get_name_resolution_metadata_and_ambiguity_candidates() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let now = Utc::now();
+    let selected = make_named_pack_with("name-resolve-pack", Status::Finalized, now, 12);
+    let older_finalized = make_named_pack_with(
+        "name-resolve-pack",
+        Status::Finalized,
+        now - Duration::minutes(15),
+        4,
+    );
+    let newer_draft = make_named_pack_with(
+        "name-resolve-pack",
+        Status::Draft,
+        now + Duration::minutes(1),
+        100,
+    );
+
+    let tie_time = now + Duration::minutes(5);
+    let ambiguous_a = make_named_pack_with("ambiguous-e2e-pack", Status::Finalized, tie_time, 9);
+    let ambiguous_b = make_named_pack_with("ambiguous-e2e-pack", Status::Finalized, tie_time, 9);
+
+    write_pack_file(&storage_root, &selected)?;
+    write_pack_file(&storage_root, &older_finalized)?;
+    write_pack_file(&storage_root, &newer_draft)?;
+    write_pack_file(&storage_root, &ambiguous_a)?;
+    write_pack_file(&storage_root, &ambiguous_b)?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+
+        let by_name = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "name":"name-resolve-pack"
+                    }
+                }
+            }))
+            .await?;
+        let markdown_name = output_markdown(&by_name)?;
+        assert_eq!(
+            legend_value(markdown_name, "id").as_deref(),
+            Some(selected.id.as_str())
+        );
+        assert_eq!(
+            legend_value(markdown_name, "selected_by").as_deref(),
+            Some("name_latest_finalized_updated_at_then_revision")
+        );
+        assert_eq!(
+            legend_value(markdown_name, "selected_revision").as_deref(),
+            Some("12")
+        );
+        assert_eq!(
+            legend_value(markdown_name, "selected_status").as_deref(),
+            Some("finalized")
+        );
+
+        let by_id = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":3,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id": selected.id.as_str()
+                    }
+                }
+            }))
+            .await?;
+        let markdown_id = output_markdown(&by_id)?;
+        assert_eq!(
+            legend_value(markdown_id, "selected_by").as_deref(),
+            Some("exact_id")
+        );
+        assert_eq!(
+            legend_value(markdown_id, "selected_revision").as_deref(),
+            Some("12")
+        );
+        assert_eq!(
+            legend_value(markdown_id, "selected_status").as_deref(),
+            Some("finalized")
+        );
+
+        let ambiguous = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":4,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "name":"ambiguous-e2e-pack"
+                    }
+                }
+            }))
+            .await?;
+        assert_eq!(ambiguous["result"]["isError"], true);
+        let err_payload = parse_tool_payload(&ambiguous)?;
+        assert_eq!(err_payload["code"], "ambiguous");
+        let mut actual_candidates = err_payload["details"]["candidate_ids"]
+            .as_array()
+            .context("missing candidate_ids details")?
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        actual_candidates.sort();
+        let mut expected_candidates = vec![
+            ambiguous_a.id.as_str().to_string(),
+            ambiguous_b.id.as_str().to_string(),
+        ];
+        expected_candidates.sort();
+        assert_eq!(actual_candidates, expected_candidates);
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_freshness_metadata_filters_and_warnings() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+
+        let create_pack = |request_id: i64, name: &str| {
+            json!({
+                "jsonrpc":"2.0",
+                "id":request_id,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "document":{
+                            "name":name,
+                            "ttl_minutes":120,
+                            "sections":[]
+                        }
+                    }
+                }
+            })
+        };
+
+        let created_fresh = parse_tool_payload(&client.call(create_pack(2, "fresh-pack")).await?)?;
+        let created_expiring =
+            parse_tool_payload(&client.call(create_pack(3, "expiring-pack")).await?)?;
+        let created_expired =
+            parse_tool_payload(&client.call(create_pack(4, "expired-pack")).await?)?;
+        let created_stale =
+            parse_tool_payload(&client.call(create_pack(5, "past-expired-pack")).await?)?;
+
+        let fresh_id = created_fresh["payload"]["id"]
+            .as_str()
+            .context("missing fresh id")?
+            .to_string();
+        let expiring_id = created_expiring["payload"]["id"]
+            .as_str()
+            .context("missing expiring id")?
+            .to_string();
+        let expired_id = created_expired["payload"]["id"]
+            .as_str()
+            .context("missing expired id")?
+            .to_string();
+        let stale_id = created_stale["payload"]["id"]
+            .as_str()
+            .context("missing stale id")?
+            .to_string();
+
+        let patch_expiry = |pack_id: &str, expires_at: chrono::DateTime<Utc>| -> Result<()> {
+            let path = storage_root.join("packs").join(format!("{}.json", pack_id));
+            let raw = std::fs::read_to_string(&path)?;
+            let mut value: Value = serde_json::from_str(&raw)?;
+            value["expires_at"] = Value::String(expires_at.to_rfc3339());
+            std::fs::write(path, serde_json::to_string(&value)?)?;
+            Ok(())
+        };
+
+        patch_expiry(&expiring_id, Utc::now() + Duration::seconds(60))?;
+        patch_expiry(&expired_id, Utc::now() - Duration::seconds(5))?;
+        patch_expiry(&stale_id, Utc::now() - Duration::seconds(2000))?;
+
+        let input_list_default = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":6,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{"action":"list"}
+                }
+            }))
+            .await?;
+        let input_list_payload = parse_tool_payload(&input_list_default)?;
+        let packs = input_list_payload["payload"]["packs"]
+            .as_array()
+            .context("missing input list packs")?;
+        assert!(
+            packs
+                .iter()
+                .all(|pack| pack.get("freshness_state").is_some()),
+            "input list must include normalized freshness_state"
+        );
+        assert!(
+            packs.iter().all(|pack| pack.get("ttl_remaining").is_some()),
+            "input list must include stable ttl_remaining field"
+        );
+        assert!(
+            packs
+                .iter()
+                .all(|pack| pack.get("id").and_then(Value::as_str) != Some(expired_id.as_str())),
+            "default stale-safe list must hide expired packs"
+        );
+        assert!(
+            packs
+                .iter()
+                .any(|pack| pack.get("id").and_then(Value::as_str) == Some(fresh_id.as_str())),
+            "fresh pack should remain visible in stale-safe default list"
+        );
+
+        let input_list_expired = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":7,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"list",
+                        "freshness":"expired"
+                    }
+                }
+            }))
+            .await?;
+        let expired_payload = parse_tool_payload(&input_list_expired)?;
+        let expired_packs = expired_payload["payload"]["packs"]
+            .as_array()
+            .context("missing expired filter packs")?;
+        assert_eq!(
+            expired_packs.len(),
+            1,
+            "expired filter should isolate stale pack"
+        );
+        assert_eq!(expired_packs[0]["id"], Value::String(expired_id.clone()));
+        assert!(!expired_packs
+            .iter()
+            .any(|pack| pack["id"] == Value::String(stale_id.clone())));
+        assert_eq!(
+            expired_packs[0]["freshness_state"],
+            Value::String("expired".into())
+        );
+
+        let input_get = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":8,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"get",
+                        "id":expiring_id.as_str()
+                    }
+                }
+            }))
+            .await?;
+        let input_get_payload = parse_tool_payload(&input_get)?;
+        assert_eq!(
+            input_get_payload["payload"]["freshness_state"],
+            Value::String("expiring_soon".into())
+        );
+        assert!(
+            input_get_payload["payload"].get("ttl_remaining").is_some()
+                && input_get_payload["payload"].get("expires_at").is_some(),
+            "input get must expose stable freshness summary fields"
+        );
+
+        let output_get = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":9,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id":expiring_id.as_str()
+                    }
+                }
+            }))
+            .await?;
+        let output_markdown_expiring = output_markdown(&output_get)?;
+        assert_eq!(
+            legend_value(output_markdown_expiring, "freshness_state").as_deref(),
+            Some("expiring_soon")
+        );
+        assert!(
+            legend_value(output_markdown_expiring, "warning")
+                .as_deref()
+                .is_some_and(|warning| warning.contains("expiring soon")),
+            "output read legend must include expiring warning"
+        );
+
+        let output_list_expired = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":10,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"list",
+                        "freshness":"expired"
+                    }
+                }
+            }))
+            .await?;
+        let expired_markdown = output_markdown(&output_list_expired)?;
+        assert!(
+            expired_markdown.contains(&expired_id),
+            "output list freshness=expired must surface expired pack"
+        );
+        assert!(
+            !expired_markdown.contains(&stale_id),
+            "past-grace expired pack should not be surfaced"
+        );
+        assert!(
+            expired_markdown.contains("warning: expired"),
+            "human-readable output must warn about expired packs"
+        );
+
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_multi_agent_handoff_compact_full_and_stale_path() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+    tokio::fs::write(
+        source_root.join("handoff.rs"),
+        "fn auth() {\n  let evidence_token = \"E2E\";\n  let reviewer_hint = true;\n}\n",
+    )
+    .await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":"init","method":"initialize","params":{}}))
+            .await?;
+
+        // Explorer publishes pack with finalizable minimum evidence.
+        let created = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":"create-main-pack",
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "document":{
+                            "name":"multi-agent-handoff-pack",
+                            "title":"Multi-agent handoff",
+                            "brief":"Explorer to orchestrator/reviewer",
+                            "ttl_minutes":120,
+                            "status":"draft",
+                            "sections":[
+                                {"key":"notes","title":"Notes","description":"bootstrap"}
+                            ]
+                        }
+                    }
+                }
+            }))
+            .await?;
+        let created_payload = parse_tool_payload(&created)?;
+        let pack_id = created_payload["payload"]["id"]
+            .as_str()
+            .context("missing primary pack id")?
+            .to_string();
+        let revision = payload_pack_revision(&created_payload)?;
+
+        let finalized = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":"finalize",
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "id":pack_id.clone(),
+                        "expected_revision":revision,
+                        "document":{
+                            "name":"multi-agent-handoff-pack",
+                            "title":"Multi-agent handoff",
+                            "brief":"Explorer to orchestrator/reviewer",
+                            "ttl_minutes":120,
+                            "status":"finalized",
+                            "sections":[
+                                {"key":"scope","title":"Scope","description":"explorer coverage"},
+                                {
+                                    "key":"findings",
+                                    "title":"Findings",
+                                    "description":"auth evidence",
+                                    "refs":[
+                                        {"key":"auth-ref","path":"handoff.rs","line_start":1,"line_end":4}
+                                    ]
+                                },
+                                {"key":"qa","title":"QA","description":"verdict: pass"}
+                            ]
+                        }
+                    }
+                }
+            }))
+            .await?;
+        let finalized_payload = parse_tool_payload(&finalized)?;
+        assert_eq!(
+            finalized_payload["payload"]["status"],
+            Value::String("finalized".into())
+        );
+
+        // Orchestrator consumes compact handoff for routing decisions.
+        let compact = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":"orchestrator-read",
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id":pack_id.clone()
+                    }
+                }
+            }))
+            .await?;
+        let compact_markdown = output_markdown(&compact)?;
+        assert!(compact_markdown.contains("- mode: compact"));
+        assert!(compact_markdown.contains("## Handoff summary [handoff]"));
+        assert!(compact_markdown.contains("- deep_nav_hints:"));
+        assert!(
+            !compact_markdown.contains("```rust"),
+            "compact routing view should avoid full code snippets"
+        );
+
+        // Reviewer consumes full evidence.
+        let full = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":"reviewer-read",
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"read",
+                        "id":pack_id.clone(),
+                        "profile":"reviewer"
+                    }
+                }
+            }))
+            .await?;
+        let full_markdown = output_markdown(&full)?;
+        assert!(full_markdown.contains("```rust"));
+        assert!(full_markdown.contains("evidence_token = \"E2E\""));
+
+        // Explicit stale-pack path handling.
+        let stale_created = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":"create-stale-pack",
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "document":{
+                            "name":"multi-agent-stale-pack",
+                            "ttl_minutes":120,
+                            "sections":[]
+                        }
+                    }
+                }
+            }))
+            .await?;
+        let stale_payload = parse_tool_payload(&stale_created)?;
+        let stale_id = stale_payload["payload"]["id"]
+            .as_str()
+            .context("missing stale pack id")?
+            .to_string();
+
+        let stale_path = storage_root.join("packs").join(format!("{stale_id}.json"));
+        let stale_raw = std::fs::read_to_string(&stale_path)?;
+        let mut stale_json: Value = serde_json::from_str(&stale_raw)?;
+        stale_json["expires_at"] = Value::String((Utc::now() - Duration::seconds(5)).to_rfc3339());
+        std::fs::write(stale_path, serde_json::to_string(&stale_json)?)?;
+
+        let output_default = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":"stale-default-list",
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{"action":"list"}
+                }
+            }))
+            .await?;
+        let default_markdown = output_markdown(&output_default)?;
+        assert!(
+            !default_markdown.contains(&stale_id),
+            "stale-safe default list should hide expired pack"
+        );
+
+        let output_expired = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":"stale-expired-list",
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{
+                        "action":"list",
+                        "freshness":"expired"
+                    }
+                }
+            }))
+            .await?;
+        let expired_markdown = output_markdown(&output_expired)?;
+        assert!(
+            expired_markdown.contains(&stale_id),
+            "explicit freshness=expired list should surface stale pack"
+        );
+        assert!(
+            expired_markdown.contains("warning: expired"),
+            "stale path should be explicit in human-readable output"
+        );
+
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
 }
 
 #[tokio::test]
-async fn e2e_initialize_with_content_type_header() -> Result<()> {
+async fn e2e_saved_filters_apply_to_input_and_output_list() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
@@ -2262,157 +3939,324 @@ async fn e2e_initialize_with_content_type_header() -> Result<()> {
     tokio::fs::create_dir_all(&source_root).await?;
 
     let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+        for (id, title, tags) in [(2, "Auth review", json!(["review", "auth"])), (3, "Scratch", json!(["scratch"]))] {
+            let created = client
+                .call(json!({
+                    "jsonrpc":"2.0",
+                    "id":id,
+                    "method":"tools/call",
+                    "params":{
+                        "name":"input",
+                        "arguments":{
+                            "action":"write",
+                            "document":{ "title":title, "tags":tags, "ttl_minutes":60, "sections":[] }
+                        }
+                    }
+                }))
+                .await?;
+            assert!(created["result"].get("isError").is_none());
+        }
 
-    client
-        .send_raw_json_with_content_type(
-            json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}),
-        )
-        .await?;
-    let init = client.read_response().await?;
-    assert_eq!(init["id"], 1);
-    assert!(init["result"]["serverInfo"]["name"].is_string());
+        let saved = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":4,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"save_filter",
+                        "filter":"ready_for_review",
+                        "status":"draft",
+                        "freshness":"fresh",
+                        "tags":["Review"]
+                    }
+                }
+            }))
+            .await?;
+        let payload = parse_tool_payload(&saved)?;
+        assert_eq!(payload["payload"]["saved_filters"], json!(["ready_for_review"]));
+
+        let listed = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":5,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{ "action":"list", "filter":"ready_for_review" }
+                }
+            }))
+            .await?;
+        let payload = parse_tool_payload(&listed)?;
+        assert_eq!(payload["payload"]["count"], 1);
+        assert_eq!(payload["payload"]["packs"][0]["title"], "Auth review");
+
+        let rendered = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":6,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{ "action":"list", "filter":"ready_for_review" }
+                }
+            }))
+            .await?;
+        let markdown = output_markdown(&rendered)?;
+        assert!(markdown.contains("Auth review"), "{markdown}");
+        assert!(!markdown.contains("Scratch"), "{markdown}");
+
+        let unknown = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":7,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{ "action":"list", "filter":"nope" }
+                }
+            }))
+            .await?;
+        assert_eq!(unknown["result"]["isError"], true);
+        let err_payload = parse_tool_payload(&unknown)?;
+        assert_eq!(
+            err_payload["details"]["available_filters"],
+            json!(["ready_for_review"])
+        );
+
+        let deleted = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":8,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{ "action":"delete_filter", "filter":"ready_for_review" }
+                }
+            }))
+            .await?;
+        let payload = parse_tool_payload(&deleted)?;
+        assert_eq!(payload["payload"]["deleted"], true);
+        assert_eq!(payload["payload"]["saved_filters"], json!([]));
+        Ok(())
+    }
+    .await;
 
     client.stop().await?;
-    Ok(())
+    result
 }
 
 #[tokio::test]
-async fn e2e_initialize_accepts_unframed_json_message() -> Result<()> {
+async fn e2e_input_estimate_reports_size_against_limits_without_writing() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
 
-    let bin_path = resolve_binary_path()?;
-    let mut child = Command::new(bin_path)
-        .env("CONTEXT_PACK_ROOT", &storage_root)
-        .env("CONTEXT_PACK_SOURCE_ROOT", &source_root)
-        .env("CONTEXT_PACK_LOG", "off")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()
-        .context("spawn MCP server")?;
-
-    let mut stdin = tokio::io::BufWriter::new(
-        child
-            .stdin
-            .take()
-            .context("missing piped stdin for spawned server")?,
-    );
-    let mut stdout = BufReader::new(
-        child
-            .stdout
-            .take()
-            .context("missing piped stdout for spawned server")?,
-    );
+    let mut client = McpE2EClient::spawn_with_env(
+        &storage_root,
+        &source_root,
+        &[("CONTEXT_PACK_MAX_PACK_BYTES", "4096")],
+    )
+    .await?;
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+        let created = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"write",
+                        "document":{ "title":"Sized", "ttl_minutes":60, "sections":[] }
+                    }
+                }
+            }))
+            .await?;
+        let created = parse_tool_payload(&created)?;
+        let pack_id = created["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string();
+        let revision = payload_pack_revision(&created)?;
 
-    stdin
-        .write_all(br#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#)
-        .await?;
-    stdin.write_all(b"\n").await?;
-    stdin.flush().await?;
+        let estimate = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":3,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"estimate",
+                        "id":pack_id,
+                        "expected_revision":revision,
+                        "document":{ "title":"Sized", "brief":"x".repeat(8000), "sections":[] }
+                    }
+                }
+            }))
+            .await?;
+        let payload = parse_tool_payload(&estimate)?;
+        assert_eq!(payload["action"], "estimate");
+        let estimate = &payload["payload"];
+        assert_eq!(estimate["pack_id"], pack_id.as_str());
+        assert_eq!(estimate["fits"], false);
+        assert!(estimate["request_bytes"].as_u64().unwrap_or(0) > 8000);
+        assert!(estimate["delta_bytes"].as_i64().unwrap_or(0) > 7900);
+        let pack_limit = estimate["limits"]
+            .as_array()
+            .and_then(|limits| limits.iter().find(|l| l["limit"] == "max_pack_bytes"))
+            .context("missing max_pack_bytes limit")?;
+        assert_eq!(pack_limit["max"], 4096);
+        assert!(pack_limit["remaining"].as_i64().unwrap_or(0) < 0);
 
-    let init = read_mcp_response(&mut stdout).await?;
-    assert_eq!(init["id"], 1);
-    assert_eq!(init["result"]["serverInfo"]["name"], "context-pack");
+        let current = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":4,
+                "method":"tools/call",
+                "params":{ "name":"input", "arguments":{ "action":"get", "id":pack_id } }
+            }))
+            .await?;
+        assert_eq!(
+            payload_pack_revision(&parse_tool_payload(&current)?)?,
+            revision
+        );
+        Ok(())
+    }
+    .await;
 
-    child.kill().await.ok();
-    child.wait().await.context("wait for server exit")?;
-    Ok(())
+    client.stop().await?;
+    result
 }
 
 #[tokio::test]
-async fn e2e_unframed_json_line_batch_is_processed_sequentially() -> Result<()> {
+async fn e2e_create_with_include_preview_returns_first_page() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
 
-    let bin_path = resolve_binary_path()?;
-    let mut child = Command::new(bin_path)
-        .env("CONTEXT_PACK_ROOT", &storage_root)
-        .env("CONTEXT_PACK_SOURCE_ROOT", &source_root)
-        .env("CONTEXT_PACK_LOG", "off")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()
-        .context("spawn MCP server")?;
-
-    let mut stdin = tokio::io::BufWriter::new(
-        child
-            .stdin
-            .take()
-            .context("missing piped stdin for spawned server")?,
-    );
-    let mut stdout = BufReader::new(
-        child
-            .stdout
-            .take()
-            .context("missing piped stdout for spawned server")?,
-    );
-
-    stdin
-        .write_all(br#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#)
-        .await?;
-    stdin.write_all(b"\n").await?;
-    stdin.flush().await?;
-
-    let init = read_mcp_response(&mut stdout).await?;
-    assert_eq!(init["id"], 1);
-
-    stdin
-        .write_all(
-            br#"{"jsonrpc":"2.0","id":2,"method":"ping"}
-{"jsonrpc":"2.0","id":3,"method":"ping"}
-"#,
-        )
-        .await?;
-    stdin.flush().await?;
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+        let document = json!({
+            "title":"Preview me",
+            "sections":[{ "key":"scope", "title":"Scope", "description":"login flow" }]
+        });
+        let created = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":2,
+                "method":"tools/call",
+                "params":{ "name":"input", "arguments":{
+                    "action":"write", "document":document, "include_preview":true
+                } }
+            }))
+            .await?;
+        let created = parse_tool_payload(&created)?;
+        let preview = created["payload"]["preview"]
+            .as_str()
+            .context("missing preview")?;
+        assert!(preview.contains("# Context pack: Preview me"), "{preview}");
+        assert!(preview.contains("- mode: compact"), "{preview}");
+        assert!(preview.contains("sections in scope: Scope"), "{preview}");
 
-    let ping1 = read_mcp_response(&mut stdout).await?;
-    let ping2 = read_mcp_response(&mut stdout).await?;
-    assert_eq!(ping1["id"], 2);
-    assert_eq!(ping2["id"], 3);
+        let plain = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":3,
+                "method":"tools/call",
+                "params":{ "name":"input", "arguments":{ "action":"write", "document":document } }
+            }))
+            .await?;
+        assert!(parse_tool_payload(&plain)?["payload"]
+            .get("preview")
+            .is_none());
+        Ok(())
+    }
+    .await;
 
-    child.kill().await.ok();
-    child.wait().await.context("wait for server exit")?;
-    Ok(())
+    client.stop().await?;
+    result
 }
 
 #[tokio::test]
-async fn e2e_server_exits_if_initialize_never_arrives() -> Result<()> {
+async fn e2e_create_applies_tag_ttl_default_and_reports_source() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
-    let source_root = dir.path().join("source");
-    tokio::fs::create_dir_all(&storage_root).await?;
-    tokio::fs::create_dir_all(&source_root).await?;
-
-    let bin_path = resolve_binary_path()?;
-    let mut child = Command::new(bin_path)
-        .env("CONTEXT_PACK_ROOT", &storage_root)
-        .env("CONTEXT_PACK_SOURCE_ROOT", &source_root)
-        .env("CONTEXT_PACK_LOG", "off")
-        .env("CONTEXT_PACK_INITIALIZE_TIMEOUT_MS", "250")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()
-        .context("spawn MCP server")?;
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
 
-    let status = tokio::time::timeout(std::time::Duration::from_secs(3), child.wait())
-        .await
-        .context("wait timeout for child exit")?
-        .context("wait for child status")?;
-    assert!(
-        status.code().is_some(),
-        "server must exit if initialize is not received within timeout"
-    );
-    Ok(())
+    let mut client = McpE2EClient::spawn_with_env(
+        &storage_root,
+        &source_root,
+        &[("CONTEXT_PACK_TTL_DEFAULTS", "default=2h,tag:compliance=30d")],
+    )
+    .await?;
+    let result: Result<()> = async {
+        let _ = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+        let mut applied = Vec::new();
+        for (id, document) in [
+            (2, json!({ "title":"Kept", "tags":["Compliance"], "sections":[] })),
+            (3, json!({ "title":"Plain", "sections":[] })),
+            (4, json!({ "title":"Pinned", "tags":["compliance"], "ttl_minutes":10, "sections":[] })),
+        ] {
+            let created = client
+                .call(json!({
+                    "jsonrpc":"2.0",
+                    "id":id,
+                    "method":"tools/call",
+                    "params":{ "name":"input", "arguments":{ "action":"write", "document":document } }
+                }))
+                .await?;
+            let created = parse_tool_payload(&created)?;
+            let pack = &created["payload"];
+            let created_at = chrono::DateTime::parse_from_rfc3339(
+                pack["created_at"].as_str().context("missing created_at")?,
+            )?;
+            let expires_at = chrono::DateTime::parse_from_rfc3339(
+                pack["expires_at"].as_str().context("missing expires_at")?,
+            )?;
+            applied.push((
+                pack["ttl_source"].as_str().unwrap_or_default().to_string(),
+                (expires_at - created_at).num_minutes(),
+            ));
+        }
+        assert_eq!(
+            applied,
+            vec![
+                ("tag:compliance".to_string(), 30 * 24 * 60),
+                ("default".to_string(), 120),
+                ("explicit".to_string(), 10),
+            ]
+        );
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
 }
 
 #[tokio::test]
-async fn e2e_input_write_rejects_legacy_fields() -> Result<()> {
+async fn e2e_sign_off_records_reviewer_and_renders_under_qa() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
@@ -2420,13 +4264,11 @@ async fn e2e_input_write_rejects_legacy_fields() -> Result<()> {
     tokio::fs::create_dir_all(&source_root).await?;
 
     let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
-
     let result: Result<()> = async {
         let _ = client
             .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
             .await?;
-
-        let response = client
+        let created = client
             .call(json!({
                 "jsonrpc":"2.0",
                 "id":2,
@@ -2435,22 +4277,99 @@ async fn e2e_input_write_rejects_legacy_fields() -> Result<()> {
                     "name":"input",
                     "arguments":{
                         "action":"write",
-                        "snapshot":{
-                            "name":"legacy-alias-pack",
-                            "ttl_minutes":30,
-                            "sections":[]
+                        "document":{
+                            "title":"Reviewed",
+                            "ttl_minutes":60,
+                            "sections":[{
+                                "key":"qa",
+                                "title":"QA",
+                                "description":"verdict: pass",
+                                "refs":[{ "key":"check", "path":"src/lib.rs", "line_start":1, "line_end":1 }]
+                            }]
                         }
                     }
                 }
             }))
             .await?;
+        let created = parse_tool_payload(&created)?;
+        let pack_id = created["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string();
+        let revision = payload_pack_revision(&created)?;
 
-        assert_eq!(response["result"]["isError"], true);
-        let err_payload = parse_tool_payload(&response)?;
-        assert_eq!(err_payload["kind"], "validation");
-        assert_eq!(err_payload["code"], "invalid_data");
-        assert_eq!(err_payload["details"]["unsupported_field"], "snapshot");
-        assert_eq!(err_payload["details"]["supported_field"], "document");
+        let bad_verdict = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":3,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"sign_off",
+                        "id":pack_id,
+                        "expected_revision":revision,
+                        "reviewer":"alice",
+                        "verdict":"lgtm"
+                    }
+                }
+            }))
+            .await?;
+        assert_eq!(bad_verdict["result"]["isError"], true);
+
+        let signed = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":4,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"sign_off",
+                        "id":pack_id,
+                        "expected_revision":revision,
+                        "reviewer":"alice",
+                        "verdict":"approved",
+                        "comment":"evidence checks out"
+                    }
+                }
+            }))
+            .await?;
+        let signed = parse_tool_payload(&signed)?;
+        assert_eq!(signed["action"], "sign_off");
+        assert_eq!(signed["payload"]["sign_offs"], 1);
+        assert_eq!(signed["payload"]["revision"], revision + 1);
+        assert_eq!(signed["payload"]["sign_off"]["reviewer"], "alice");
+        assert_eq!(signed["payload"]["sign_off"]["verdict"], "approved");
+        assert_eq!(signed["payload"]["sign_off"]["revision"], revision);
+
+        let read = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":5,
+                "method":"tools/call",
+                "params":{
+                    "name":"output",
+                    "arguments":{ "action":"read", "id":pack_id, "profile":"reviewer" }
+                }
+            }))
+            .await?;
+        let markdown = output_markdown(&read)?;
+        assert_eq!(
+            legend_value(markdown, "sign_offs").as_deref(),
+            Some(format!("1 (latest: alice approved r{revision})").as_str())
+        );
+        let qa = markdown
+            .split_once("## QA [qa]")
+            .context("missing qa section")?
+            .1;
+        assert!(
+            qa.contains(&format!(
+                "### Sign-offs\n- alice: approved (reviewed r{revision}, "
+            )),
+            "{markdown}"
+        );
+        assert!(qa.contains(" — evidence checks out\n"), "{markdown}");
         Ok(())
     }
     .await;
@@ -2460,42 +4379,105 @@ async fn e2e_input_write_rejects_legacy_fields() -> Result<()> {
 }
 
 #[tokio::test]
-async fn e2e_schema_mismatch_reports_migration_required() -> Result<()> {
+async fn e2e_finalize_requires_sign_offs_from_policy() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
-    tokio::fs::create_dir_all(storage_root.join("packs")).await?;
+    tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
+    tokio::fs::write(source_root.join("auth.rs"), "fn login() {}\n").await?;
 
-    tokio::fs::write(
-        storage_root.join("packs").join("pk_aaaaaaaa.json"),
-        r#"{"schema_version":1,"id":"pk_aaaaaaaa","name":"old-pack","title":null,"brief":null,"status":"draft","tags":[],"sections":[],"revision":1,"created_at":"2026-01-01T00:00:00Z","updated_at":"2026-01-01T00:00:00Z","expires_at":"2099-01-01T00:00:00Z"}"#,
+    let mut client = McpE2EClient::spawn_with_env(
+        &storage_root,
+        &source_root,
+        &[("CONTEXT_PACK_SIGNOFF_POLICY", "approvals=1,role=security")],
     )
     .await?;
-
-    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
-
     let result: Result<()> = async {
         let _ = client
             .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
             .await?;
-
-        let response = client
+        let document = |status: &str| {
+            json!({
+                "title":"Gated",
+                "status":status,
+                "sections":[
+                    {"key":"scope","title":"Scope","description":"login"},
+                    {
+                        "key":"findings",
+                        "title":"Findings",
+                        "refs":[{ "key":"login", "path":"auth.rs", "line_start":1, "line_end":1 }]
+                    },
+                    {"key":"qa","title":"QA","description":"verdict: pass"}
+                ]
+            })
+        };
+        let mut draft = document("draft");
+        draft["ttl_minutes"] = json!(60);
+        let created = client
             .call(json!({
                 "jsonrpc":"2.0",
                 "id":2,
                 "method":"tools/call",
+                "params":{ "name":"input", "arguments":{ "action":"write", "document":draft } }
+            }))
+            .await?;
+        let created = parse_tool_payload(&created)?;
+        let pack_id = created["payload"]["id"]
+            .as_str()
+            .context("missing created pack id")?
+            .to_string();
+        let revision = payload_pack_revision(&created)?;
+
+        let finalize = |id: u64, revision: u64| {
+            json!({
+                "jsonrpc":"2.0",
+                "id":id,
+                "method":"tools/call",
                 "params":{
                     "name":"input",
-                    "arguments":{"action":"list"}
+                    "arguments":{
+                        "action":"write",
+                        "id":pack_id,
+                        "expected_revision":revision,
+                        "document":document("finalized")
+                    }
+                }
+            })
+        };
+        let blocked = client.call(finalize(3, revision)).await?;
+        assert_eq!(blocked["result"]["isError"], true);
+        let blocked = parse_tool_payload(&blocked)?;
+        assert_eq!(blocked["code"], "signoff_required");
+        assert_eq!(blocked["kind"], "validation");
+        assert_eq!(
+            blocked["details"]["outstanding"],
+            json!(["1 more approval(s) (0 of 1)", "role:security"])
+        );
+
+        let signed = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":4,
+                "method":"tools/call",
+                "params":{
+                    "name":"input",
+                    "arguments":{
+                        "action":"sign_off",
+                        "id":pack_id,
+                        "expected_revision":revision,
+                        "reviewer":"alice",
+                        "role":"Security",
+                        "verdict":"approved"
+                    }
                 }
             }))
             .await?;
+        let revision = payload_pack_revision(&parse_tool_payload(&signed)?)?;
 
-        assert_eq!(response["result"]["isError"], true);
-        let err_payload = parse_tool_payload(&response)?;
-        assert_eq!(err_payload["kind"], "migration_required");
-        assert_eq!(err_payload["code"], "migration_required");
+        let finalized = client.call(finalize(5, revision)).await?;
+        let finalized = parse_tool_payload(&finalized)?;
+        assert_eq!(finalized["payload"]["status"], "finalized");
         Ok(())
     }
     .await;
@@ -2505,136 +4487,241 @@ async fn e2e_schema_mismatch_reports_migration_required() -> Result<()> {
 }
 
 #[tokio::test]
-async fn e2e_output_get_name_resolution_metadata_and_ambiguity_candidates() -> Result<()> {
+async fn e2e_trace_frames_logs_redacted_previews_for_both_framings() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
 
-    let now = Utc::now();
-    let selected = make_named_pack_with("name-resolve-pack", Status::Finalized, now, 12);
-    let older_finalized = make_named_pack_with(
-        "name-resolve-pack",
-        Status::Finalized,
-        now - Duration::minutes(15),
-        4,
+    let mut child = Command::new(resolve_binary_path()?)
+        .env("CONTEXT_PACK_ROOT", &storage_root)
+        .env("CONTEXT_PACK_SOURCE_ROOT", &source_root)
+        .env("CONTEXT_PACK_LOG", "off")
+        .env("CONTEXT_PACK_TRACE_FRAMES", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("spawn MCP server")?;
+    let mut stdin = child.stdin.take().context("missing piped stdin")?;
+    stdin
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\",\"params\":{}}\n")
+        .await?;
+    let body = serde_json::to_vec(&json!({
+        "jsonrpc":"2.0",
+        "id":2,
+        "method":"tools/call",
+        "params":{ "name":"input", "arguments":{ "action":"get", "name":"hunter2-secret" } }
+    }))?;
+    stdin
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    stdin.write_all(&body).await?;
+    stdin.flush().await?;
+    drop(stdin);
+
+    let output = child.wait_with_output().await.context("wait for server")?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("frame in: mode=json-line method=\"initialize\" id=1 bytes="),
+        "{stderr}"
     );
-    let newer_draft = make_named_pack_with(
-        "name-resolve-pack",
-        Status::Draft,
-        now + Duration::minutes(1),
-        100,
+    assert!(
+        stderr.contains("frame in: mode=content-length method=\"tools/call\" id=2"),
+        "{stderr}"
     );
+    assert!(
+        stderr.contains("frame out: mode=json-line id=2 bytes="),
+        "{stderr}"
+    );
+    assert!(stderr.contains("duration_ms="), "{stderr}");
+    assert!(stderr.contains("\"action\":\"get\""), "{stderr}");
+    assert!(!stderr.contains("hunter2"), "{stderr}");
+    Ok(())
+}
 
-    let tie_time = now + Duration::minutes(5);
-    let ambiguous_a = make_named_pack_with("ambiguous-e2e-pack", Status::Finalized, tie_time, 9);
-    let ambiguous_b = make_named_pack_with("ambiguous-e2e-pack", Status::Finalized, tie_time, 9);
-
-    write_pack_file(&storage_root, &selected)?;
-    write_pack_file(&storage_root, &older_finalized)?;
-    write_pack_file(&storage_root, &newer_draft)?;
-    write_pack_file(&storage_root, &ambiguous_a)?;
-    write_pack_file(&storage_root, &ambiguous_b)?;
+#[tokio::test]
+async fn e2e_jsonrpc_batch_array_gets_one_reply_per_request() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
 
     let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let result: Result<()> = async {
+        let replies = client
+            .call(json!([
+                {"jsonrpc":"2.0","id":1,"method":"initialize","params":{}},
+                {"jsonrpc":"2.0","method":"notifications/initialized"},
+                {"jsonrpc":"2.0","id":"two","method":"ping"},
+                42
+            ]))
+            .await?;
+        let replies = replies.as_array().context("batch reply must be an array")?;
+        assert_eq!(replies.len(), 3, "{replies:?}");
+        assert_eq!(replies[0]["id"], 1);
+        assert_eq!(replies[0]["result"]["serverInfo"]["name"], "context-pack");
+        assert_eq!(replies[1]["id"], "two");
+        assert_eq!(replies[1]["result"], json!({}));
+        assert_eq!(replies[2]["id"], Value::Null);
+        assert_eq!(replies[2]["error"]["code"], -32600);
+
+        let empty = client.call(json!([])).await?;
+        assert_eq!(empty["id"], Value::Null);
+        assert_eq!(empty["error"]["code"], -32600);
+
+        // A batch of notifications only gets no reply; the next call's
+        // response must be the next thing on the wire.
+        client
+            .send_raw_json(json!([{"jsonrpc":"2.0","method":"notifications/initialized"}]))
+            .await?;
+        let ping = client
+            .call(json!({"jsonrpc":"2.0","id":3,"method":"ping"}))
+            .await?;
+        assert_eq!(ping["id"], 3);
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_unknown_notifications_are_ignored_and_counted() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
 
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
     let result: Result<()> = async {
-        let _ = client
+        client
             .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
             .await?;
+        for _ in 0..2 {
+            client
+                .send_raw_json(json!({
+                    "jsonrpc":"2.0",
+                    "method":"notifications/cancelled",
+                    "params":{ "requestId": 1 }
+                }))
+                .await?;
+        }
+        // Unknown requests are still answered, so the next reply on the wire
+        // is proof the notifications above got none.
+        let sampling = client
+            .call(json!({"jsonrpc":"2.0","id":2,"method":"sampling/createMessage"}))
+            .await?;
+        assert_eq!(sampling["id"], 2);
+        assert_eq!(sampling["error"]["code"], -32601);
 
-        let by_name = client
+        let stats = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":2,
+                "id":3,
                 "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"read",
-                        "name":"name-resolve-pack"
-                    }
-                }
+                "params":{ "name":"server", "arguments":{ "action":"stats" } }
             }))
             .await?;
-        let markdown_name = output_markdown(&by_name)?;
-        assert_eq!(
-            legend_value(markdown_name, "id").as_deref(),
-            Some(selected.id.as_str())
-        );
-        assert_eq!(
-            legend_value(markdown_name, "selected_by").as_deref(),
-            Some("name_latest_finalized_updated_at_then_revision")
-        );
-        assert_eq!(
-            legend_value(markdown_name, "selected_revision").as_deref(),
-            Some("12")
-        );
-        assert_eq!(
-            legend_value(markdown_name, "selected_status").as_deref(),
-            Some("finalized")
+        let payload = parse_tool_payload(&stats)?;
+        assert_eq!(payload["action"], "stats");
+        let unknown = &payload["payload"]["unknown_methods"];
+        assert_eq!(unknown["total"], 3);
+        assert_eq!(unknown["notifications"]["notifications/cancelled"], 2);
+        assert_eq!(unknown["requests"]["sampling/createMessage"], 1);
+
+        let health = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":4,
+                "method":"tools/call",
+                "params":{ "name":"server", "arguments":{ "action":"health" } }
+            }))
+            .await?;
+        let payload = parse_tool_payload(&health)?;
+        assert_eq!(payload["action"], "health");
+        assert_eq!(payload["payload"]["status"], "ok");
+        let checks = payload["payload"]["checks"]
+            .as_array()
+            .expect("checks array");
+        let names: Vec<_> = checks
+            .iter()
+            .filter(|check| check["ok"] == true)
+            .filter_map(|check| check["name"].as_str())
+            .collect();
+        assert_eq!(names, vec!["storage", "repo_lock", "source_root"]);
+        let tasks = payload["payload"]["background_tasks"]
+            .as_array()
+            .expect("background_tasks array");
+        assert!(
+            tasks.iter().any(|task| task["name"] == "ttl_purge"
+                && task["state"] == "running"
+                && task["consecutive_failures"] == 0),
+            "{tasks:?}"
         );
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
 
-        let by_id = client
+#[tokio::test]
+async fn e2e_server_stats_count_tool_calls_by_action_and_error_code() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let result: Result<()> = async {
+        client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
+        for id in 2..4 {
+            client
+                .call(json!({
+                    "jsonrpc":"2.0",
+                    "id":id,
+                    "method":"tools/call",
+                    "params":{ "name":"input", "arguments":{ "action":"list" } }
+                }))
+                .await?;
+        }
+        let missing = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":3,
+                "id":4,
                 "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"read",
-                        "id": selected.id.as_str()
-                    }
-                }
+                "params":{ "name":"output", "arguments":{ "action":"read", "name":"no-such-pack" } }
             }))
             .await?;
-        let markdown_id = output_markdown(&by_id)?;
-        assert_eq!(
-            legend_value(markdown_id, "selected_by").as_deref(),
-            Some("exact_id")
-        );
-        assert_eq!(
-            legend_value(markdown_id, "selected_revision").as_deref(),
-            Some("12")
-        );
-        assert_eq!(
-            legend_value(markdown_id, "selected_status").as_deref(),
-            Some("finalized")
-        );
+        assert_eq!(missing["result"]["isError"], true);
 
-        let ambiguous = client
+        let stats = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":4,
+                "id":5,
                 "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"read",
-                        "name":"ambiguous-e2e-pack"
-                    }
-                }
+                "params":{ "name":"server", "arguments":{ "action":"stats" } }
             }))
             .await?;
-        assert_eq!(ambiguous["result"]["isError"], true);
-        let err_payload = parse_tool_payload(&ambiguous)?;
-        assert_eq!(err_payload["code"], "ambiguous");
-        let mut actual_candidates = err_payload["details"]["candidate_ids"]
-            .as_array()
-            .context("missing candidate_ids details")?
-            .iter()
-            .filter_map(Value::as_str)
-            .map(str::to_string)
-            .collect::<Vec<_>>();
-        actual_candidates.sort();
-        let mut expected_candidates = vec![
-            ambiguous_a.id.as_str().to_string(),
-            ambiguous_b.id.as_str().to_string(),
-        ];
-        expected_candidates.sort();
-        assert_eq!(actual_candidates, expected_candidates);
+        let payload = parse_tool_payload(&stats)?;
+        let server = &payload["payload"]["server"];
+        assert_eq!(server["calls"], 3);
+        assert_eq!(server["errors"], 1);
+        assert_eq!(server["tools"]["input.list"]["calls"], 2);
+        assert_eq!(server["tools"]["input.list"]["errors"], 0);
+        assert!(server["tools"]["input.list"]["max_bytes"].as_u64() > Some(0));
+        assert_eq!(server["tools"]["output.read"]["errors"], 1);
+        assert_eq!(server["errors_by_code"]["not_found"], 1);
+        assert!(server["uptime_seconds"].as_i64().is_some());
         Ok(())
     }
     .await;
@@ -2644,7 +4731,7 @@ async fn e2e_output_get_name_resolution_metadata_and_ambiguity_candidates() -> R
 }
 
 #[tokio::test]
-async fn e2e_freshness_metadata_filters_and_warnings() -> Result<()> {
+async fn e2e_read_with_progress_token_streams_progress_before_reply() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
@@ -2652,218 +4739,246 @@ async fn e2e_freshness_metadata_filters_and_warnings() -> Result<()> {
     tokio::fs::create_dir_all(&source_root).await?;
 
     let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
-
     let result: Result<()> = async {
-        let _ = client
+        client
             .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
             .await?;
-
-        let create_pack = |request_id: i64, name: &str| {
-            json!({
+        let diagram = |key: &str| json!({ "key":key, "title":key, "mermaid":"graph TD\n  A --> B" });
+        let create = client
+            .call(json!({
                 "jsonrpc":"2.0",
-                "id":request_id,
+                "id":2,
                 "method":"tools/call",
                 "params":{
                     "name":"input",
                     "arguments":{
                         "action":"write",
                         "document":{
-                            "name":name,
-                            "ttl_minutes":120,
-                            "sections":[]
+                            "name":"progress-pack",
+                            "ttl_minutes":60,
+                            "sections":[
+                                { "key":"arch", "title":"Architecture", "diagrams":[diagram("flow"), diagram("deploy")] },
+                                { "key":"data", "title":"Data", "diagrams":[diagram("schema")] }
+                            ]
                         }
                     }
                 }
-            })
-        };
-
-        let created_fresh = parse_tool_payload(&client.call(create_pack(2, "fresh-pack")).await?)?;
-        let created_expiring =
-            parse_tool_payload(&client.call(create_pack(3, "expiring-pack")).await?)?;
-        let created_expired =
-            parse_tool_payload(&client.call(create_pack(4, "expired-pack")).await?)?;
-        let created_stale =
-            parse_tool_payload(&client.call(create_pack(5, "past-expired-pack")).await?)?;
-
-        let fresh_id = created_fresh["payload"]["id"]
-            .as_str()
-            .context("missing fresh id")?
-            .to_string();
-        let expiring_id = created_expiring["payload"]["id"]
-            .as_str()
-            .context("missing expiring id")?
-            .to_string();
-        let expired_id = created_expired["payload"]["id"]
-            .as_str()
-            .context("missing expired id")?
-            .to_string();
-        let stale_id = created_stale["payload"]["id"]
-            .as_str()
-            .context("missing stale id")?
-            .to_string();
-
-        let patch_expiry = |pack_id: &str, expires_at: chrono::DateTime<Utc>| -> Result<()> {
-            let path = storage_root.join("packs").join(format!("{}.json", pack_id));
-            let raw = std::fs::read_to_string(&path)?;
-            let mut value: Value = serde_json::from_str(&raw)?;
-            value["expires_at"] = Value::String(expires_at.to_rfc3339());
-            std::fs::write(path, serde_json::to_string(&value)?)?;
-            Ok(())
-        };
-
-        patch_expiry(&expiring_id, Utc::now() + Duration::seconds(60))?;
-        patch_expiry(&expired_id, Utc::now() - Duration::seconds(5))?;
-        patch_expiry(&stale_id, Utc::now() - Duration::seconds(2000))?;
+            }))
+            .await?;
+        parse_tool_payload(&create)?;
 
-        let input_list_default = client
-            .call(json!({
+        client
+            .send_raw_json(json!({
                 "jsonrpc":"2.0",
-                "id":6,
+                "id":3,
                 "method":"tools/call",
                 "params":{
-                    "name":"input",
-                    "arguments":{"action":"list"}
+                    "name":"output",
+                    "arguments":{ "action":"read", "name":"progress-pack" },
+                    "_meta":{ "progressToken":"render-1" }
                 }
             }))
             .await?;
-        let input_list_payload = parse_tool_payload(&input_list_default)?;
-        let packs = input_list_payload["payload"]["packs"]
-            .as_array()
-            .context("missing input list packs")?;
-        assert!(
-            packs
-                .iter()
-                .all(|pack| pack.get("freshness_state").is_some()),
-            "input list must include normalized freshness_state"
-        );
-        assert!(
-            packs.iter().all(|pack| pack.get("ttl_remaining").is_some()),
-            "input list must include stable ttl_remaining field"
-        );
-        assert!(
-            packs
-                .iter()
-                .all(|pack| pack.get("id").and_then(Value::as_str) != Some(expired_id.as_str())),
-            "default stale-safe list must hide expired packs"
-        );
-        assert!(
-            packs
-                .iter()
-                .any(|pack| pack.get("id").and_then(Value::as_str) == Some(fresh_id.as_str())),
-            "fresh pack should remain visible in stale-safe default list"
+        let mut progress = Vec::new();
+        let reply = loop {
+            let message = client.read_response().await?;
+            if message["method"] == "notifications/progress" {
+                let params = &message["params"];
+                assert_eq!(params["progressToken"], "render-1");
+                assert_eq!(params["total"], 3);
+                progress.push((params["progress"].as_u64(), params["message"].clone()));
+            } else {
+                break message;
+            }
+        };
+        assert_eq!(reply["id"], 3);
+        assert!(reply["result"]["content"].is_array());
+        assert_eq!(
+            progress,
+            vec![
+                (Some(1), json!("arch")),
+                (Some(2), json!("arch")),
+                (Some(3), json!("data")),
+            ]
         );
 
-        let input_list_expired = client
+        // Without a token the reply comes alone.
+        let plain = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":7,
+                "id":4,
                 "method":"tools/call",
-                "params":{
-                    "name":"input",
-                    "arguments":{
-                        "action":"list",
-                        "freshness":"expired"
-                    }
-                }
+                "params":{ "name":"output", "arguments":{ "action":"read", "name":"progress-pack" } }
             }))
             .await?;
-        let expired_payload = parse_tool_payload(&input_list_expired)?;
-        let expired_packs = expired_payload["payload"]["packs"]
-            .as_array()
-            .context("missing expired filter packs")?;
+        assert_eq!(plain["id"], 4);
+        Ok(())
+    }
+    .await;
+
+    client.stop().await?;
+    result
+}
+
+#[tokio::test]
+async fn e2e_resources_and_prompts_expose_finalized_packs() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&storage_root).await?;
+    tokio::fs::create_dir_all(&source_root).await?;
+    tokio::fs::write(source_root.join("auth.rs"), "fn login() {}\n").await?;
+
+    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
+    let result: Result<()> = async {
+        let init = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
+            .await?;
         assert_eq!(
-            expired_packs.len(),
-            1,
-            "expired filter should isolate stale pack"
+            init["result"]["capabilities"]["resources"],
+            json!({ "listChanged": false })
         );
-        assert_eq!(expired_packs[0]["id"], Value::String(expired_id.clone()));
-        assert!(!expired_packs
-            .iter()
-            .any(|pack| pack["id"] == Value::String(stale_id.clone())));
         assert_eq!(
-            expired_packs[0]["freshness_state"],
-            Value::String("expired".into())
+            init["result"]["capabilities"]["prompts"],
+            json!({ "listChanged": false })
         );
 
-        let input_get = client
+        let document = |title: &str, status: &str| {
+            json!({
+                "title":title,
+                "status":status,
+                "sections":[
+                    {"key":"scope","title":"Scope","description":"login"},
+                    {
+                        "key":"findings",
+                        "title":"Findings",
+                        "refs":[{ "key":"login", "path":"auth.rs", "line_start":1, "line_end":1 }]
+                    },
+                    {"key":"qa","title":"QA","description":"verdict: pass"}
+                ]
+            })
+        };
+        let mut ids = Vec::new();
+        for (n, title) in [(2u64, "Published"), (3, "Still drafting")] {
+            let mut draft = document(title, "draft");
+            draft["ttl_minutes"] = json!(60);
+            let created = client
+                .call(json!({
+                    "jsonrpc":"2.0",
+                    "id":n,
+                    "method":"tools/call",
+                    "params":{ "name":"input", "arguments":{ "action":"write", "document":draft } }
+                }))
+                .await?;
+            let created = parse_tool_payload(&created)?;
+            ids.push((
+                created["payload"]["id"]
+                    .as_str()
+                    .context("missing created pack id")?
+                    .to_string(),
+                payload_pack_revision(&created)?,
+            ));
+        }
+        let (published, revision) = ids[0].clone();
+        let draft_id = ids[1].0.clone();
+        let finalized = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":8,
+                "id":4,
                 "method":"tools/call",
                 "params":{
                     "name":"input",
                     "arguments":{
-                        "action":"get",
-                        "id":expiring_id.as_str()
+                        "action":"write",
+                        "id":published,
+                        "expected_revision":revision,
+                        "document":document("Published", "finalized")
                     }
                 }
             }))
             .await?;
-        let input_get_payload = parse_tool_payload(&input_get)?;
         assert_eq!(
-            input_get_payload["payload"]["freshness_state"],
-            Value::String("expiring_soon".into())
+            parse_tool_payload(&finalized)?["payload"]["status"],
+            "finalized"
         );
-        assert!(
-            input_get_payload["payload"].get("ttl_remaining").is_some()
-                && input_get_payload["payload"].get("expires_at").is_some(),
-            "input get must expose stable freshness summary fields"
+
+        let listed = client
+            .call(json!({"jsonrpc":"2.0","id":5,"method":"resources/list","params":{}}))
+            .await?;
+        let uri = format!("context-pack://{published}");
+        assert_eq!(
+            listed["result"]["resources"],
+            json!([{
+                "uri": uri,
+                "name": published,
+                "title": "Published",
+                "mimeType": "text/markdown"
+            }])
         );
 
-        let output_get = client
+        let read = client
+            .call(json!({"jsonrpc":"2.0","id":6,"method":"resources/read","params":{"uri":uri}}))
+            .await?;
+        let contents = &read["result"]["contents"][0];
+        assert_eq!(contents["uri"], uri.as_str());
+        assert_eq!(contents["mimeType"], "text/markdown");
+        let text = contents["text"].as_str().context("missing resource text")?;
+        assert!(text.contains("[LEGEND]"), "{text}");
+        assert!(text.contains("# Context pack: Published"), "{text}");
+
+        let draft = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":9,
-                "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"read",
-                        "id":expiring_id.as_str()
-                    }
-                }
+                "id":7,
+                "method":"resources/read",
+                "params":{"uri":format!("context-pack://{draft_id}")}
             }))
             .await?;
-        let output_markdown_expiring = output_markdown(&output_get)?;
-        assert_eq!(
-            legend_value(output_markdown_expiring, "freshness_state").as_deref(),
-            Some("expiring_soon")
-        );
-        assert!(
-            legend_value(output_markdown_expiring, "warning")
-                .as_deref()
-                .is_some_and(|warning| warning.contains("expiring soon")),
-            "output read legend must include expiring warning"
-        );
+        assert_eq!(draft["error"]["code"], -32002);
+
+        let invalid = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":8,
+                "method":"resources/read",
+                "params":{"uri":format!("file://{published}")}
+            }))
+            .await?;
+        assert_eq!(invalid["error"]["code"], -32602);
 
-        let output_list_expired = client
+        let prompts = client
+            .call(json!({"jsonrpc":"2.0","id":9,"method":"prompts/list","params":{}}))
+            .await?;
+        let prompts = &prompts["result"]["prompts"];
+        assert_eq!(prompts.as_array().map(Vec::len), Some(1), "{prompts}");
+        assert_eq!(prompts[0]["name"], published.as_str());
+        assert_eq!(prompts[0]["title"], "Published");
+
+        let prompt = client
             .call(json!({
                 "jsonrpc":"2.0",
                 "id":10,
-                "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"list",
-                        "freshness":"expired"
-                    }
-                }
+                "method":"prompts/get",
+                "params":{"name":published}
             }))
             .await?;
-        let expired_markdown = output_markdown(&output_list_expired)?;
-        assert!(
-            expired_markdown.contains(&expired_id),
-            "output list freshness=expired must surface expired pack"
-        );
-        assert!(
-            !expired_markdown.contains(&stale_id),
-            "past-grace expired pack should not be surfaced"
-        );
-        assert!(
-            expired_markdown.contains("warning: expired"),
-            "human-readable output must warn about expired packs"
-        );
+        let message = &prompt["result"]["messages"][0];
+        assert_eq!(message["role"], "user");
+        let text = message["content"]["text"]
+            .as_str()
+            .context("missing prompt text")?;
+        assert!(text.contains("- mode: compact"), "{text}");
+        assert!(text.contains("## Handoff summary"), "{text}");
 
+        let unknown = client
+            .call(json!({
+                "jsonrpc":"2.0",
+                "id":11,
+                "method":"prompts/get",
+                "params":{"name":draft_id}
+            }))
+            .await?;
+        assert_eq!(unknown["error"]["code"], -32602);
         Ok(())
     }
     .await;
@@ -2872,217 +4987,262 @@ async fn e2e_freshness_metadata_filters_and_warnings() -> Result<()> {
     result
 }
 
+#[cfg(feature = "watch")]
 #[tokio::test]
-async fn e2e_multi_agent_handoff_compact_full_and_stale_path() -> Result<()> {
+async fn e2e_source_watch_notifies_subscribers_of_stale_refs() -> Result<()> {
     let dir = tempdir()?;
     let storage_root = dir.path().join("storage");
     let source_root = dir.path().join("source");
     tokio::fs::create_dir_all(&storage_root).await?;
     tokio::fs::create_dir_all(&source_root).await?;
-    tokio::fs::write(
-        source_root.join("handoff.rs"),
-        "fn auth() {\n  let evidence_token = \"E2E\";\n  let reviewer_hint = true;\n}\n",
+    tokio::fs::write(source_root.join("auth.rs"), "fn login() {}\n").await?;
+    tokio::fs::write(source_root.join("notes.md"), "todo\n").await?;
+
+    let mut client = McpE2EClient::spawn_with_env(
+        &storage_root,
+        &source_root,
+        &[("CONTEXT_PACK_WATCH_SOURCES", "1")],
     )
     .await?;
-
-    let mut client = McpE2EClient::spawn(&storage_root, &source_root).await?;
-
     let result: Result<()> = async {
-        let _ = client
-            .call(json!({"jsonrpc":"2.0","id":"init","method":"initialize","params":{}}))
+        let init = client
+            .call(json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}))
             .await?;
+        assert_eq!(
+            init["result"]["capabilities"]["resources"],
+            json!({ "subscribe": true, "listChanged": false })
+        );
 
-        // Explorer publishes pack with finalizable minimum evidence.
         let created = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":"create-main-pack",
+                "id":2,
                 "method":"tools/call",
-                "params":{
-                    "name":"input",
-                    "arguments":{
-                        "action":"write",
-                        "document":{
-                            "name":"multi-agent-handoff-pack",
-                            "title":"Multi-agent handoff",
-                            "brief":"Explorer to orchestrator/reviewer",
-                            "ttl_minutes":120,
-                            "status":"draft",
-                            "sections":[
-                                {"key":"notes","title":"Notes","description":"bootstrap"}
-                            ]
-                        }
-                    }
-                }
+                "params":{ "name":"input", "arguments":{ "action":"write", "document":{
+                    "title":"Login",
+                    "ttl_minutes":60,
+                    "sections":[{
+                        "key":"findings",
+                        "title":"Findings",
+                        "refs":[{ "key":"login", "path":"auth.rs", "line_start":1, "line_end":1 }]
+                    }]
+                } } }
             }))
             .await?;
-        let created_payload = parse_tool_payload(&created)?;
-        let pack_id = created_payload["payload"]["id"]
+        let id = parse_tool_payload(&created)?["payload"]["id"]
             .as_str()
-            .context("missing primary pack id")?
+            .context("missing created pack id")?
             .to_string();
-        let revision = payload_pack_revision(&created_payload)?;
-
-        let finalized = client
-            .call(json!({
-                "jsonrpc":"2.0",
-                "id":"finalize",
-                "method":"tools/call",
-                "params":{
-                    "name":"input",
-                    "arguments":{
-                        "action":"write",
-                        "id":pack_id.clone(),
-                        "expected_revision":revision,
-                        "document":{
-                            "name":"multi-agent-handoff-pack",
-                            "title":"Multi-agent handoff",
-                            "brief":"Explorer to orchestrator/reviewer",
-                            "ttl_minutes":120,
-                            "status":"finalized",
-                            "sections":[
-                                {"key":"scope","title":"Scope","description":"explorer coverage"},
-                                {
-                                    "key":"findings",
-                                    "title":"Findings",
-                                    "description":"auth evidence",
-                                    "refs":[
-                                        {"key":"auth-ref","path":"handoff.rs","line_start":1,"line_end":4}
-                                    ]
-                                },
-                                {"key":"qa","title":"QA","description":"verdict: pass"}
-                            ]
-                        }
-                    }
-                }
-            }))
+        let uri = format!("context-pack://{id}");
+        let subscribed = client
+            .call(
+                json!({"jsonrpc":"2.0","id":3,"method":"resources/subscribe","params":{"uri":uri}}),
+            )
             .await?;
-        let finalized_payload = parse_tool_payload(&finalized)?;
-        assert_eq!(
-            finalized_payload["payload"]["status"],
-            Value::String("finalized".into())
-        );
-
-        // Orchestrator consumes compact handoff for routing decisions.
-        let compact = client
+        assert_eq!(subscribed["result"], json!({}));
+
+        // An unreferenced file changes nothing; the referenced one does.
+        tokio::fs::write(source_root.join("notes.md"), "done\n").await?;
+        tokio::fs::write(source_root.join("auth.rs"), "fn login() { check() }\n").await?;
+        let update =
+            tokio::time::timeout(std::time::Duration::from_secs(10), client.read_response())
+                .await
+                .context("no resource update within 10s")??;
+        assert_eq!(update["method"], "notifications/resources/updated");
+        assert_eq!(update["params"]["uri"], uri.as_str());
+        assert_eq!(update["params"]["_meta"]["stale_refs"], json!(["login"]));
+
+        let stats = client
             .call(json!({
                 "jsonrpc":"2.0",
-                "id":"orchestrator-read",
+                "id":4,
                 "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"read",
-                        "id":pack_id.clone()
-                    }
-                }
+                "params":{ "name":"server", "arguments":{ "action":"stats" } }
             }))
             .await?;
-        let compact_markdown = output_markdown(&compact)?;
-        assert!(compact_markdown.contains("- mode: compact"));
-        assert!(compact_markdown.contains("## Handoff summary [handoff]"));
-        assert!(compact_markdown.contains("- deep_nav_hints:"));
-        assert!(
-            !compact_markdown.contains("```rust"),
-            "compact routing view should avoid full code snippets"
-        );
+        let stale = &parse_tool_payload(&stats)?["payload"]["source_watch"]["stale_packs"];
+        assert_eq!(stale[0]["pack_id"], id.as_str(), "{stale}");
+        assert_eq!(stale[0]["ref_keys"], json!(["login"]));
+        Ok(())
+    }
+    .await;
 
-        // Reviewer consumes full evidence.
-        let full = client
-            .call(json!({
-                "jsonrpc":"2.0",
-                "id":"reviewer-read",
-                "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"read",
-                        "id":pack_id.clone(),
-                        "profile":"reviewer"
-                    }
-                }
-            }))
-            .await?;
-        let full_markdown = output_markdown(&full)?;
-        assert!(full_markdown.contains("```rust"));
-        assert!(full_markdown.contains("evidence_token = \"E2E\""));
+    client.stop().await?;
+    result
+}
 
-        // Explicit stale-pack path handling.
-        let stale_created = client
-            .call(json!({
+#[cfg(feature = "http")]
+async fn next_sse_event(stream: &mut BufReader<tokio::net::TcpStream>) -> Result<(String, String)> {
+    let (mut event, mut data) = (String::new(), String::new());
+    loop {
+        let mut line = String::new();
+        let read = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            stream.read_line(&mut line),
+        )
+        .await
+        .context("timed out waiting for SSE event")??;
+        anyhow::ensure!(read > 0, "SSE stream closed");
+        let line = line.trim_end();
+        if let Some(value) = line.strip_prefix("event: ") {
+            event = value.to_string();
+        } else if let Some(value) = line.strip_prefix("data: ") {
+            data = value.to_string();
+        } else if line.is_empty() && !event.is_empty() {
+            return Ok((event, data));
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+async fn http_post(addr: &str, path: &str, body: &Value) -> Result<String> {
+    let body = serde_json::to_vec(body)?;
+    let mut stream = tokio::net::TcpStream::connect(addr).await?;
+    stream
+        .write_all(
+            format!(
+                "POST {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+                body.len()
+            )
+            .as_bytes(),
+        )
+        .await?;
+    stream.write_all(&body).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response.lines().next().unwrap_or_default().to_string())
+}
+
+#[cfg(feature = "http")]
+#[tokio::test]
+async fn e2e_http_sse_sessions_share_one_store() -> Result<()> {
+    let dir = tempdir()?;
+    let storage_root = dir.path().join("storage");
+    let source_root = dir.path().join("source");
+    tokio::fs::create_dir_all(&source_root).await?;
+    let addr = {
+        let probe = std::net::TcpListener::bind("127.0.0.1:0")?;
+        probe.local_addr()?.to_string()
+    };
+
+    let mut child = Command::new(resolve_binary_path()?)
+        .env("CONTEXT_PACK_ROOT", &storage_root)
+        .env("CONTEXT_PACK_SOURCE_ROOT", &source_root)
+        .env("CONTEXT_PACK_LOG", "off")
+        .env("CONTEXT_PACK_HTTP_ADDR", &addr)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .context("spawn MCP server")?;
+
+    let result: Result<()> = async {
+        let mut connected = None;
+        for _ in 0..100 {
+            match tokio::net::TcpStream::connect(&addr).await {
+                Ok(stream) => {
+                    connected = Some(stream);
+                    break;
+                }
+                Err(_) => tokio::time::sleep(std::time::Duration::from_millis(50)).await,
+            }
+        }
+        let open_session = |stream: Option<tokio::net::TcpStream>| async {
+            let mut stream = match stream {
+                Some(stream) => stream,
+                None => tokio::net::TcpStream::connect(&addr).await?,
+            };
+            stream
+                .write_all(format!("GET /sse HTTP/1.1\r\nHost: {addr}\r\n\r\n").as_bytes())
+                .await?;
+            let mut stream = BufReader::new(stream);
+            let (event, endpoint) = next_sse_event(&mut stream).await?;
+            anyhow::ensure!(event == "endpoint", "unexpected first event '{event}'");
+            anyhow::Ok((stream, endpoint))
+        };
+        let (mut first, first_endpoint) =
+            open_session(Some(connected.context("server never listened")?)).await?;
+        let (mut second, second_endpoint) = open_session(None).await?;
+        assert_ne!(first_endpoint, second_endpoint);
+
+        let status = http_post(
+            &addr,
+            &first_endpoint,
+            &json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}),
+        )
+        .await?;
+        assert!(status.contains("202"), "{status}");
+        let (event, data) = next_sse_event(&mut first).await?;
+        assert_eq!(event, "message");
+        let reply: Value = serde_json::from_str(&data)?;
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["result"]["serverInfo"]["name"], "context-pack");
+
+        http_post(
+            &addr,
+            &first_endpoint,
+            &json!({
                 "jsonrpc":"2.0",
-                "id":"create-stale-pack",
+                "id":2,
                 "method":"tools/call",
                 "params":{
                     "name":"input",
                     "arguments":{
                         "action":"write",
-                        "document":{
-                            "name":"multi-agent-stale-pack",
-                            "ttl_minutes":120,
-                            "sections":[]
-                        }
+                        "document":{ "name":"shared-over-http", "ttl_minutes":60, "sections":[] }
                     }
                 }
-            }))
-            .await?;
-        let stale_payload = parse_tool_payload(&stale_created)?;
-        let stale_id = stale_payload["payload"]["id"]
-            .as_str()
-            .context("missing stale pack id")?
-            .to_string();
-
-        let stale_path = storage_root.join("packs").join(format!("{stale_id}.json"));
-        let stale_raw = std::fs::read_to_string(&stale_path)?;
-        let mut stale_json: Value = serde_json::from_str(&stale_raw)?;
-        stale_json["expires_at"] = Value::String((Utc::now() - Duration::seconds(5)).to_rfc3339());
-        std::fs::write(stale_path, serde_json::to_string(&stale_json)?)?;
-
-        let output_default = client
-            .call(json!({
+            }),
+        )
+        .await?;
+        let (_, data) = next_sse_event(&mut first).await?;
+        let created: Value = serde_json::from_str(&data)?;
+        assert_eq!(created["id"], 2);
+
+        // The second session sees the pack written through the first.
+        http_post(
+            &addr,
+            &second_endpoint,
+            &json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}),
+        )
+        .await?;
+        next_sse_event(&mut second).await?;
+        http_post(
+            &addr,
+            &second_endpoint,
+            &json!({
                 "jsonrpc":"2.0",
-                "id":"stale-default-list",
+                "id":2,
                 "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{"action":"list"}
-                }
-            }))
-            .await?;
-        let default_markdown = output_markdown(&output_default)?;
-        assert!(
-            !default_markdown.contains(&stale_id),
-            "stale-safe default list should hide expired pack"
-        );
+                "params":{ "name":"input", "arguments":{ "action":"get", "name":"shared-over-http" } }
+            }),
+        )
+        .await?;
+        let (_, data) = next_sse_event(&mut second).await?;
+        let fetched: Value = serde_json::from_str(&data)?;
+        let payload = parse_tool_payload(&fetched)?;
+        assert_eq!(payload["payload"]["name"], "shared-over-http");
+
+        let status = http_post(
+            &addr,
+            "/messages?session_id=nope",
+            &json!({"jsonrpc":"2.0","id":1,"method":"ping"}),
+        )
+        .await?;
+        assert!(status.contains("404"), "{status}");
 
-        let output_expired = client
-            .call(json!({
-                "jsonrpc":"2.0",
-                "id":"stale-expired-list",
-                "method":"tools/call",
-                "params":{
-                    "name":"output",
-                    "arguments":{
-                        "action":"list",
-                        "freshness":"expired"
-                    }
-                }
-            }))
+        // A page reached through a rebound DNS name sends its own Host.
+        let mut rebound = tokio::net::TcpStream::connect(&addr).await?;
+        rebound
+            .write_all(b"GET /sse HTTP/1.1\r\nHost: rebound.example\r\n\r\n")
             .await?;
-        let expired_markdown = output_markdown(&output_expired)?;
-        assert!(
-            expired_markdown.contains(&stale_id),
-            "explicit freshness=expired list should surface stale pack"
-        );
-        assert!(
-            expired_markdown.contains("warning: expired"),
-            "stale path should be explicit in human-readable output"
-        );
-
+        let mut response = String::new();
+        rebound.read_to_string(&mut response).await?;
+        assert!(response.starts_with("HTTP/1.1 403"), "{response}");
         Ok(())
     }
     .await;
 
-    client.stop().await?;
+    child.kill().await.ok();
     result
 }
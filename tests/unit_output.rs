@@ -6,12 +6,15 @@ use std::{collections::HashMap, sync::Arc, sync::Mutex};
 
 use mcp_context_pack::{
     app::{
-        output_usecases::{OutputProfile, OutputReadRequest, OutputUseCases},
-        ports::{CodeExcerptPort, ListFilter, PackRepositoryPort, Snippet},
+        output_usecases::{
+            AnchorStyle, ExcerptCleanup, OutputProfile, OutputReadRequest, OutputUseCases,
+            RenderProfiles, SectionSeparator,
+        },
+        ports::{CodeExcerptPort, ListFilter, PackRepositoryPort, Snippet, SnippetProvenance},
     },
     domain::{
         errors::{DomainError, Result},
-        models::Pack,
+        models::{Pack, PackLink, PackLinkKind},
         types::{LineRange, PackId, PackName, RelativePath, Status},
     },
 };
@@ -106,6 +109,7 @@ impl CodeExcerptPort for FakeExcerptPort {
                 line_end: range.end,
                 body: body.clone(),
                 total_lines: range.end,
+                provenance: SnippetProvenance::read_now(),
             }),
         }
     }
@@ -204,18 +208,31 @@ async fn test_render_with_section_and_ref() {
 
     let code_ref = CodeRef {
         key: ref_key,
+        root: None,
         path: path.clone(),
         lines,
         title: Some("My ref".to_string()),
         why: None,
         group: None,
+        entry_point: false,
+        git_sha: None,
+        symbol: None,
+        url: None,
+        section_ref: None,
+        excerpt_line_limit: None,
+        snapshot: None,
+        content_hash: None,
+        changed_revision: None,
     };
     let section = Section {
         key: section_key,
         title: "Main Section".to_string(),
         description: None,
+        translations: Default::default(),
         refs: vec![code_ref],
         diagrams: vec![],
+        attachments: vec![],
+        changed_revision: None,
     };
     pack.sections = vec![section];
 
@@ -237,6 +254,10 @@ async fn test_render_with_section_and_ref() {
     assert!(rendered.contains("[CONTENT]"), "missing [CONTENT]");
     assert!(rendered.contains("Main Section"), "section title missing");
     assert!(rendered.contains("fn main()"), "code excerpt missing");
+    assert!(
+        rendered.contains("_provenance: src/lib.rs:1-3 | read_at: "),
+        "full-mode excerpt must carry a provenance footer"
+    );
 }
 
 /// Stale ref renders as "> stale ref:" warning line.
@@ -250,15 +271,28 @@ async fn test_render_stale_ref_shown_as_warning() {
         key: SectionKey::new("s1").unwrap(),
         title: "Section".to_string(),
         description: None,
+        translations: Default::default(),
         refs: vec![CodeRef {
             key: RefKey::new("r1").unwrap(),
+            root: None,
             path: RelativePath::new("missing.rs").unwrap(),
             lines: LineRange::new(1, 2).unwrap(),
             title: None,
             why: None,
             group: None,
+            entry_point: false,
+            git_sha: None,
+            symbol: None,
+            url: None,
+            section_ref: None,
+            excerpt_line_limit: None,
+            snapshot: None,
+            content_hash: None,
+            changed_revision: None,
         }],
         diagrams: vec![],
+        attachments: vec![],
+        changed_revision: None,
     };
     pack.sections = vec![section];
 
@@ -292,13 +326,17 @@ async fn test_render_with_diagram_block() {
         key: SectionKey::new("s1").unwrap(),
         title: "Section".to_string(),
         description: None,
+        translations: Default::default(),
         refs: vec![],
         diagrams: vec![Diagram {
             key: DiagramKey::new("d1").unwrap(),
             title: "My Diagram".to_string(),
             mermaid: "graph TD; A-->B".to_string(),
             why: None,
+            history: Vec::new(),
         }],
+        attachments: vec![],
+        changed_revision: None,
     };
     pack.sections = vec![section];
 
@@ -389,15 +427,28 @@ async fn test_lang_detection_rust_extension() {
         key: SectionKey::new("s1").unwrap(),
         title: "S".to_string(),
         description: None,
+        translations: Default::default(),
         refs: vec![CodeRef {
             key: RefKey::new("r1").unwrap(),
+            root: None,
             path: RelativePath::new("src/main.rs").unwrap(),
             lines: LineRange::new(1, 1).unwrap(),
             title: None,
             why: None,
             group: None,
+            entry_point: false,
+            git_sha: None,
+            symbol: None,
+            url: None,
+            section_ref: None,
+            excerpt_line_limit: None,
+            snapshot: None,
+            content_hash: None,
+            changed_revision: None,
         }],
         diagrams: vec![],
+        attachments: vec![],
+        changed_revision: None,
     }];
     let id_str = pack.id.as_str().to_string();
     let uc = make_output(
@@ -431,15 +482,28 @@ async fn test_lang_detection_unknown_extension() {
         key: SectionKey::new("s1").unwrap(),
         title: "S".to_string(),
         description: None,
+        translations: Default::default(),
         refs: vec![CodeRef {
             key: RefKey::new("r1").unwrap(),
+            root: None,
             path: RelativePath::new("data/file.xyz").unwrap(),
             lines: LineRange::new(1, 1).unwrap(),
             title: None,
             why: None,
             group: None,
+            entry_point: false,
+            git_sha: None,
+            symbol: None,
+            url: None,
+            section_ref: None,
+            excerpt_line_limit: None,
+            snapshot: None,
+            content_hash: None,
+            changed_revision: None,
         }],
         diagrams: vec![],
+        attachments: vec![],
+        changed_revision: None,
     }];
     let id_str = pack.id.as_str().to_string();
     let uc = make_output(
@@ -480,3 +544,445 @@ async fn test_resolve_by_name() {
         "name should appear in rendered output"
     );
 }
+
+fn legend_etag(rendered: &str) -> String {
+    rendered
+        .lines()
+        .find_map(|line| line.strip_prefix("- etag: "))
+        .expect("etag missing from legend")
+        .to_string()
+}
+
+/// A matching `if_none_match` returns a short not-modified stub; other read
+/// args or a new revision produce a different validator.
+#[tokio::test]
+async fn test_if_none_match_returns_not_modified_until_pack_or_args_change() {
+    let mut pack = named_pack("etag-pack");
+    pack.brief = Some("large evidence".to_string());
+    let id_str = pack.id.as_str().to_string();
+    let repo = FakePackRepo::with(vec![pack.clone()]);
+    let uc = OutputUseCases::new(repo.clone(), FakeExcerptPort::stale());
+
+    let first = uc.get_rendered(&id_str, None).await.unwrap();
+    let etag = legend_etag(&first);
+    assert!(etag.starts_with(&format!("r{}-", pack.revision)), "{etag}");
+
+    let cached = uc
+        .get_rendered_with_request(
+            &id_str,
+            OutputReadRequest {
+                if_none_match: Some(etag.clone()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(cached.contains("- not_modified: true"), "{cached}");
+    assert!(!cached.contains("large evidence"));
+
+    let other_profile = uc
+        .get_rendered_with_request(
+            &id_str,
+            OutputReadRequest {
+                profile: Some(OutputProfile::Reviewer),
+                if_none_match: Some(etag.clone()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(!other_profile.contains("not_modified"));
+    assert_ne!(legend_etag(&other_profile), etag);
+
+    pack.revision += 1;
+    pack.brief = Some("updated evidence".to_string());
+    repo.save_with_expected_revision(&pack, pack.revision - 1)
+        .await
+        .unwrap();
+    let changed = uc
+        .get_rendered_with_request(
+            &id_str,
+            OutputReadRequest {
+                if_none_match: Some(etag.clone()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(changed.contains("updated evidence"));
+    assert_ne!(legend_etag(&changed), etag);
+}
+
+/// Graph nodes carry the pack name and a `{status}_{freshness}` class;
+/// links between packs in view become edges.
+#[tokio::test]
+async fn test_graph_renders_one_styled_node_per_pack() {
+    let now = chrono::Utc::now();
+    let mut done = named_pack("auth-review");
+    done.status = Status::Finalized;
+    done.expires_at = now + chrono::Duration::hours(2);
+    let mut stale = named_pack("cache-notes");
+    stale.expires_at = now - chrono::Duration::minutes(1);
+    done.links = vec![
+        PackLink {
+            kind: PackLinkKind::DependsOn,
+            target: stale.id.clone(),
+        },
+        PackLink {
+            kind: PackLinkKind::Supersedes,
+            target: PackId::new(),
+        },
+    ];
+    let (done_id, stale_id) = (done.id.to_string(), stale.id.to_string());
+    let uc = make_output(vec![done, stale], FakeExcerptPort::stale());
+
+    let graph = uc.graph(ListFilter::default()).await.unwrap();
+    assert!(graph.contains("- nodes: 2"), "{graph}");
+    assert!(graph.contains("- edges: 1"), "{graph}");
+    assert!(graph.contains("- edge_note: 1 link(s) point at packs outside this view"));
+    assert!(graph.contains(&format!("{done_id} -->|depends_on| {stale_id}")));
+    assert!(graph.contains("```mermaid\ngraph LR\n"), "{graph}");
+    assert!(graph.contains(&format!("{done_id}[\"auth-review<br/>r1 finalized\"]")));
+    assert!(graph.contains(&format!("class {done_id} finalized_fresh")));
+    assert!(graph.contains(&format!("class {stale_id} draft_expired")));
+}
+
+/// Anchors precede section and ref headings; `rule` separates sections only.
+#[tokio::test]
+async fn test_read_emits_anchors_and_section_separators() {
+    use mcp_context_pack::domain::models::{CodeRef, Section};
+    use mcp_context_pack::domain::types::{RefKey, SectionKey};
+
+    let section = |key: &str, ref_key: &str| Section {
+        key: SectionKey::new(key).unwrap(),
+        title: key.to_uppercase(),
+        description: None,
+        translations: Default::default(),
+        refs: vec![CodeRef {
+            key: RefKey::new(ref_key).unwrap(),
+            root: None,
+            path: RelativePath::new("src/main.rs").unwrap(),
+            lines: LineRange::new(1, 1).unwrap(),
+            title: None,
+            why: None,
+            group: None,
+            entry_point: false,
+            git_sha: None,
+            symbol: None,
+            url: None,
+            section_ref: None,
+            excerpt_line_limit: None,
+            snapshot: None,
+            content_hash: None,
+            changed_revision: None,
+        }],
+        diagrams: vec![],
+        attachments: vec![],
+        changed_revision: None,
+    };
+    let mut pack = simple_pack();
+    pack.sections = vec![section("auth", "handler"), section("cache", "evict")];
+    let id_str = pack.id.as_str().to_string();
+    let uc = make_output(
+        vec![pack],
+        FakeExcerptPort::with(vec![("src/main.rs", "   1: fn main() {}")]),
+    );
+    let read = |anchors, separator| {
+        uc.get_rendered_with_request(
+            &id_str,
+            OutputReadRequest {
+                profile: Some(OutputProfile::Reviewer),
+                anchors: Some(anchors),
+                separator: Some(separator),
+                ..Default::default()
+            },
+        )
+    };
+
+    let html = read(AnchorStyle::Html, SectionSeparator::Rule)
+        .await
+        .unwrap();
+    assert!(html.contains("- anchors: html"), "{html}");
+    assert!(
+        html.contains("\n<a id=\"sec-auth\"></a>\n## AUTH [auth]\n"),
+        "{html}"
+    );
+    assert!(
+        html.contains("\n<a id=\"ref-auth.handler\"></a>\n#### handler"),
+        "{html}"
+    );
+    assert!(html.contains("<a id=\"ref-cache.evict\"></a>"), "{html}");
+    assert_eq!(html.matches("\n---\n").count(), 1, "{html}");
+
+    let slug = read(AnchorStyle::Slug, SectionSeparator::None)
+        .await
+        .unwrap();
+    assert!(slug.contains("## CACHE [cache] {#sec-cache}\n"), "{slug}");
+    assert!(slug.contains("{#ref-cache.evict}\n"), "{slug}");
+    assert!(!slug.contains("<a id="), "{slug}");
+
+    let plain = read(AnchorStyle::None, SectionSeparator::None)
+        .await
+        .unwrap();
+    assert!(
+        !plain.contains("{#") && !plain.contains("<a id="),
+        "{plain}"
+    );
+}
+
+/// `archive` renders every chunk in full under a legend without the fields
+/// that change between reads; configured presets replace the built-ins.
+#[tokio::test]
+async fn test_archive_profile_and_configured_presets() {
+    let mut pack = simple_pack();
+    pack.tags = vec!["audit".into()];
+    let id_str = pack.id.as_str().to_string();
+    async fn read(uc: &OutputUseCases, id: &str, profile: OutputProfile) -> Result<String> {
+        let request = OutputReadRequest {
+            profile: Some(profile),
+            ..Default::default()
+        };
+        uc.get_rendered_with_request(id, request).await
+    }
+
+    let uc = make_output(vec![pack.clone()], FakeExcerptPort::stale());
+    let archive = read(&uc, &id_str, OutputProfile::Archive).await.unwrap();
+    assert!(archive.contains("- profile: archive"), "{archive}");
+    assert!(archive.contains("- tags: audit"), "{archive}");
+    for volatile in ["- etag:", "- ttl_remaining:", "- expires_at:", "- paging:"] {
+        assert!(!archive.contains(volatile), "{volatile} in {archive}");
+    }
+    assert_eq!(
+        archive,
+        read(&uc, &id_str, OutputProfile::Archive).await.unwrap()
+    );
+
+    let profiles = RenderProfiles::parse(
+        r#"{"archive":{"status":"finalized"},"reviewer":{"legend":"minimal","limit":2}}"#,
+    )
+    .unwrap();
+    let uc = make_output(vec![pack], FakeExcerptPort::stale()).with_render_profiles(profiles);
+    let err = read(&uc, &id_str, OutputProfile::Archive)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DomainError::InvalidState(_)), "{err:?}");
+    let reviewer = read(&uc, &id_str, OutputProfile::Reviewer).await.unwrap();
+    assert!(reviewer.contains("- limit: 2"), "{reviewer}");
+    assert!(!reviewer.contains("- etag:"), "{reviewer}");
+}
+
+/// Clean-up changes the rendered excerpt and the legend, not `contains` matching.
+#[tokio::test]
+async fn test_read_applies_excerpt_cleanup_to_rendered_excerpts() {
+    use mcp_context_pack::domain::models::{CodeRef, Section};
+    use mcp_context_pack::domain::types::{RefKey, SectionKey};
+
+    let mut pack = simple_pack();
+    pack.sections = vec![Section {
+        key: SectionKey::new("logs").unwrap(),
+        title: "Logs".into(),
+        description: None,
+        translations: Default::default(),
+        refs: vec![CodeRef {
+            key: RefKey::new("output").unwrap(),
+            root: None,
+            path: RelativePath::new("src/main.rs").unwrap(),
+            lines: LineRange::new(1, 2).unwrap(),
+            title: None,
+            why: None,
+            group: None,
+            entry_point: false,
+            git_sha: None,
+            symbol: None,
+            url: None,
+            section_ref: None,
+            excerpt_line_limit: None,
+            snapshot: None,
+            content_hash: None,
+            changed_revision: None,
+        }],
+        diagrams: vec![],
+        attachments: vec![],
+        changed_revision: None,
+    }];
+    let id_str = pack.id.as_str().to_string();
+    let uc = make_output(
+        vec![pack],
+        FakeExcerptPort::with(vec![("src/main.rs", "\x1b[32mok\x1b[0m\tdone   \n\n")]),
+    );
+    let read = |excerpt| {
+        uc.get_rendered_with_request(
+            &id_str,
+            OutputReadRequest {
+                profile: Some(OutputProfile::Reviewer),
+                contains: Some("[32mok".into()),
+                excerpt,
+                ..Default::default()
+            },
+        )
+    };
+
+    let raw = read(None).await.unwrap();
+    assert!(raw.contains("\x1b[32mok"), "{raw}");
+    assert!(!raw.contains("excerpt_cleanup"), "{raw}");
+
+    let clean = read(Some(ExcerptCleanup {
+        trim_trailing_whitespace: true,
+        tab_width: Some(2),
+        strip_ansi: true,
+    }))
+    .await
+    .unwrap();
+    assert!(
+        clean.contains("- excerpt_cleanup: trim_trailing_whitespace,tab_width=2,strip_ansi"),
+        "{clean}"
+    );
+    assert!(clean.contains("\nok  done\n```"), "{clean}");
+    assert!(!clean.contains('\x1b'), "{clean}");
+}
+
+/// `lang` picks the translated section description, falls back to the primary
+/// language, then to the default description.
+#[tokio::test]
+async fn test_render_lang_prefers_translated_description() {
+    use mcp_context_pack::domain::models::{CodeRef, Section};
+    use mcp_context_pack::domain::types::{LanguageTag, RefKey, SectionKey};
+
+    let mut pack = simple_pack();
+    pack.sections = vec![Section {
+        key: SectionKey::new("main-section").unwrap(),
+        title: "Main Section".to_string(),
+        description: Some("Entry flow".to_string()),
+        translations: [(LanguageTag::new("ru").unwrap(), "Точка входа".to_string())]
+            .into_iter()
+            .collect(),
+        refs: vec![CodeRef {
+            key: RefKey::new("my-ref").unwrap(),
+            root: None,
+            path: RelativePath::new("src/lib.rs").unwrap(),
+            lines: LineRange::new(1, 1).unwrap(),
+            title: None,
+            why: None,
+            group: None,
+            entry_point: false,
+            git_sha: None,
+            symbol: None,
+            url: None,
+            section_ref: None,
+            excerpt_line_limit: None,
+            snapshot: None,
+            content_hash: None,
+            changed_revision: None,
+        }],
+        diagrams: vec![],
+        attachments: vec![],
+        changed_revision: None,
+    }];
+
+    let id_str = pack.id.as_str().to_string();
+    let uc = make_output(
+        vec![pack],
+        FakeExcerptPort::with(vec![("src/lib.rs", "   1: fn main() {}")]),
+    );
+    let read = |lang: Option<&str>| OutputReadRequest {
+        profile: Some(OutputProfile::Reviewer),
+        lang: lang.map(|tag| LanguageTag::new(tag).unwrap()),
+        ..Default::default()
+    };
+
+    let russian = uc
+        .get_rendered_with_request(&id_str, read(Some("ru-RU")))
+        .await
+        .unwrap();
+    assert!(russian.contains("Точка входа"), "{russian}");
+    assert!(!russian.contains("Entry flow"), "{russian}");
+    assert!(russian.contains("- lang: ru-ru"), "{russian}");
+
+    let fallback = uc
+        .get_rendered_with_request(&id_str, read(Some("de")))
+        .await
+        .unwrap();
+    assert!(fallback.contains("Entry flow"), "{fallback}");
+
+    let default = uc
+        .get_rendered_with_request(&id_str, read(None))
+        .await
+        .unwrap();
+    assert!(default.contains("Entry flow"), "{default}");
+    assert!(!default.contains("- lang:"), "{default}");
+}
+
+#[tokio::test]
+async fn test_read_delta_renders_only_changes_since_base_revision() {
+    use mcp_context_pack::domain::models::RefSpec;
+    use mcp_context_pack::domain::types::{RefKey, SectionKey};
+
+    let spec = |key: &str, line: usize| RefSpec {
+        key: RefKey::new(key).unwrap(),
+        root: None,
+        path: RelativePath::new("src/lib.rs").unwrap(),
+        lines: LineRange::new(line, line).unwrap(),
+        title: None,
+        why: None,
+        group: None,
+        symbol: None,
+        url: None,
+        section_ref: None,
+        excerpt_line_limit: None,
+    };
+    let section = |key: &str| SectionKey::new(key).unwrap();
+    let mut pack = simple_pack();
+    pack.upsert_section(
+        section("intro"),
+        "Intro".into(),
+        Some("unchanged".into()),
+        None,
+    )
+    .unwrap();
+    pack.upsert_section(section("flow"), "Flow".into(), None, None)
+        .unwrap();
+    pack.upsert_ref(&section("flow"), spec("old-ref", 1))
+        .unwrap();
+    pack.upsert_ref(&section("flow"), spec("new-ref", 1))
+        .unwrap();
+    pack.upsert_section(section("scratch"), "Scratch".into(), None, None)
+        .unwrap();
+    let base = pack.revision;
+    pack.upsert_ref(&section("flow"), spec("new-ref", 2))
+        .unwrap();
+    pack.delete_section(&section("scratch")).unwrap();
+
+    let id_str = pack.id.as_str().to_string();
+    let current = pack.revision;
+    let uc = make_output(
+        vec![pack],
+        FakeExcerptPort::with(vec![("src/lib.rs", "   1: fn a() {}\n   2: fn b() {}")]),
+    );
+
+    let delta = uc.get_delta_rendered(&id_str, base, None).await.unwrap();
+    assert!(
+        delta.contains(&format!("- base_revision: {base}")),
+        "{delta}"
+    );
+    assert!(delta.contains("- changed_sections: 1"), "{delta}");
+    assert!(delta.contains("- removed_sections: scratch"), "{delta}");
+    assert!(delta.contains("- unchanged_sections_omitted: 1"), "{delta}");
+    assert!(delta.contains("## Flow [flow]"), "{delta}");
+    assert!(delta.contains("#### new-ref [flow]"), "{delta}");
+    assert!(!delta.contains("#### old-ref"), "{delta}");
+    assert!(delta.contains("_1 unchanged ref(s) omitted._"), "{delta}");
+    assert!(!delta.contains("## Intro"), "{delta}");
+
+    let nothing = uc.get_delta_rendered(&id_str, current, None).await.unwrap();
+    assert!(nothing.contains("- changed_sections: 0"), "{nothing}");
+    assert!(
+        nothing.contains(&format!("_No changes since revision {current}._")),
+        "{nothing}"
+    );
+
+    let ahead = uc.get_delta_rendered(&id_str, current + 1, None).await;
+    assert!(
+        matches!(ahead, Err(DomainError::InvalidData(_))),
+        "{ahead:?}"
+    );
+}